@@ -13,6 +13,7 @@ use test::Bencher;
 
 use std::mem;
 use std::ptr;
+use std::rc::Rc;
 
 mod support;
 
@@ -151,3 +152,228 @@ fn build_buffer(b: &mut Bencher) {
         ).unwrap()
     });
 }
+
+fn stream_persistent_buffer(b: &mut Bencher, mode: glium::buffer::BufferMode) {
+    let display = support::build_context();
+
+    let mut buffer = glium::buffer::Buffer::<[[f32; 4]]>::empty_array(&display,
+                                                        glium::buffer::BufferType::ArrayBuffer,
+                                                        16384, mode).unwrap();
+
+    b.iter(|| {
+        let mut mapping = buffer.map_write();
+        for i in 0 .. mapping.len() {
+            mapping.set(i, [i as f32, 0.0, 0.0, 1.0]);
+        }
+    });
+}
+
+#[bench]
+fn stream_persistent_explicit_flush(b: &mut Bencher) {
+    stream_persistent_buffer(b, glium::buffer::BufferMode::Persistent);
+}
+
+#[bench]
+fn stream_persistent_coherent(b: &mut Bencher) {
+    stream_persistent_buffer(b, glium::buffer::BufferMode::PersistentCoherent);
+}
+
+// compares read-modify-write through a `Mapping` with writes through a `WriteMapping` ; with a
+// real driver the memory of persistent buffers is write-combined and reading from it is slow
+fn persistent_buffer_update(b: &mut Bencher, write_only: bool) {
+    let display = support::build_context();
+
+    let mut buffer = glium::buffer::Buffer::<[f32]>::empty_array(&display,
+                                                        glium::buffer::BufferType::ArrayBuffer,
+                                                        65536,
+                                                        glium::buffer::BufferMode::Persistent)
+                                                        .unwrap();
+
+    let data = (0 .. 65536).map(|i| i as f32).collect::<Vec<_>>();
+
+    b.iter(|| {
+        if write_only {
+            let mut mapping = buffer.map_write();
+            mapping.copy_from_slice(0, &data);
+        } else {
+            let mut mapping = buffer.map();
+            for (dest, src) in mapping.iter_mut().zip(data.iter()) {
+                *dest += *src;
+            }
+        }
+    });
+}
+
+#[bench]
+fn persistent_read_modify_write(b: &mut Bencher) {
+    persistent_buffer_update(b, false);
+}
+
+#[bench]
+fn persistent_write_only(b: &mut Bencher) {
+    persistent_buffer_update(b, true);
+}
+
+fn build_scenery(display: &Rc<glium::backend::Context>)
+                 -> (glium::VertexBuffer<SceneryVertex>, glium::Program)
+{
+    let vertex_buffer = glium::VertexBuffer::new(display,
+        &[
+            SceneryVertex { position: [-0.5, -0.5] },
+            SceneryVertex { position: [ 0.0,  0.5] },
+            SceneryVertex { position: [ 0.5, -0.5] },
+        ]
+    ).unwrap();
+
+    let program = program!(display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+
+            fragment: "
+                #version 140
+
+                uniform vec3 color;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(color, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    (vertex_buffer, program)
+}
+
+#[derive(Copy, Clone)]
+struct SceneryVertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(SceneryVertex, position);
+
+#[bench]
+#[ignore]       // TODO: segfaults
+fn draw_scenery_naive(b: &mut Bencher) {
+    let display = support::build_context();
+    let (vertex_buffer, program) = build_scenery(&display);
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    let uniforms = uniform! { color: [1.0, 0.0, 0.0f32] };
+    let params = Default::default();
+
+    b.iter(|| {
+        let mut target = glium::Frame::new(display.clone(), (800, 600));
+        for _ in 0 .. 800 {
+            target.draw(&vertex_buffer, &indices, &program, &uniforms, &params).unwrap();
+        }
+        target.finish().unwrap();
+    });
+}
+
+#[bench]
+#[ignore]       // TODO: segfaults
+fn draw_scenery_command_list(b: &mut Bencher) {
+    let display = support::build_context();
+    let (vertex_buffer, program) = build_scenery(&display);
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    let uniforms = uniform! { color: [1.0, 0.0, 0.0f32] };
+    let params = Default::default();
+
+    let mut list = glium::CommandList::new(&display);
+    for _ in 0 .. 800 {
+        list.draw(&vertex_buffer, &indices, &program, &uniforms, &params).unwrap();
+    }
+
+    b.iter(|| {
+        let mut target = glium::Frame::new(display.clone(), (800, 600));
+        target.draw_command_list(&list).unwrap();
+        target.finish().unwrap();
+    });
+}
+
+// each object has its own values for six uniforms, that are either set with `glUniform` or
+// gathered in the `glium_DrawUniforms` block
+fn draw_objects_uniforms(b: &mut Bencher, with_draw_block: bool) {
+    let display = support::build_context();
+    let (vertex_buffer, _) = build_scenery(&display);
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    let params = Default::default();
+
+    let declaration = if with_draw_block {
+        "layout(std140) uniform glium_DrawUniforms {
+            mat4 matrix; vec3 color; float alpha; vec2 offset; float scale; int layer;
+        };"
+    } else {
+        "uniform mat4 matrix; uniform vec3 color; uniform float alpha; uniform vec2 offset;
+         uniform float scale; uniform int layer;"
+    };
+
+    let vertex = format!("
+        #version 140
+
+        {}
+
+        in vec2 position;
+
+        void main() {{
+            gl_Position = matrix * vec4(position * scale + offset, float(layer), 1.0);
+        }}
+    ", declaration);
+
+    let fragment = format!("
+        #version 140
+
+        {}
+
+        out vec4 f_color;
+
+        void main() {{
+            f_color = vec4(color, alpha);
+        }}
+    ", declaration);
+
+    let program = glium::Program::from_source(&display, &vertex, &fragment, None).unwrap();
+
+    b.iter(|| {
+        let mut target = glium::Frame::new(display.clone(), (800, 600));
+        for i in 0 .. 5000 {
+            let value = i as f32 / 5000.0;
+            let uniforms = uniform! {
+                matrix: [
+                    [1.0, 0.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0, 0.0],
+                    [0.0, 0.0, 1.0, 0.0],
+                    [value, 0.0, 0.0, 1.0f32],
+                ],
+                color: [value, 0.0, 0.0f32],
+                alpha: value,
+                offset: [value, value],
+                scale: value,
+                layer: i,
+            };
+
+            target.draw(&vertex_buffer, &indices, &program, &uniforms, &params).unwrap();
+        }
+        target.finish().unwrap();
+    });
+}
+
+#[bench]
+#[ignore]       // TODO: segfaults
+fn draw_5000_objects_gl_uniform(b: &mut Bencher) {
+    draw_objects_uniforms(b, false);
+}
+
+#[bench]
+#[ignore]       // TODO: segfaults
+fn draw_5000_objects_draw_block(b: &mut Bencher) {
+    draw_objects_uniforms(b, true);
+}
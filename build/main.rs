@@ -41,13 +41,17 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_AMD_depth_clamp_separate".to_string(),
                 "GL_APPLE_vertex_array_object".to_string(),
                 "GL_ARB_bindless_texture".to_string(),
+                "GL_ARB_blend_func_extended".to_string(),
                 "GL_ARB_buffer_storage".to_string(),
+                "GL_ARB_clear_buffer_object".to_string(),
+                "GL_ARB_color_buffer_float".to_string(),
                 "GL_ARB_compute_shader".to_string(),
                 "GL_ARB_copy_buffer".to_string(),
                 "GL_ARB_debug_output".to_string(),
                 "GL_ARB_depth_texture".to_string(),
                 "GL_ARB_direct_state_access".to_string(),
                 "GL_ARB_draw_buffers".to_string(),
+                "GL_ARB_draw_instanced".to_string(),
                 "GL_ARB_ES2_compatibility".to_string(),
                 "GL_ARB_ES3_compatibility".to_string(),
                 "GL_ARB_ES3_1_compatibility".to_string(),
@@ -57,6 +61,7 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_ARB_gpu_shader_fp64".to_string(),
                 "GL_ARB_gpu_shader_int64".to_string(),
                 "GL_ARB_invalidate_subdata".to_string(),
+                "GL_ARB_multi_bind".to_string(),
                 "GL_ARB_multi_draw_indirect".to_string(),
                 "GL_ARB_occlusion_query".to_string(),
                 "GL_ARB_pixel_buffer_object".to_string(),
@@ -114,8 +119,10 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_APPLE_framebuffer_multisample".to_string(),
                 "GL_APPLE_sync".to_string(),
                 "GL_ARM_rgba8".to_string(),
+                "GL_EXT_blend_func_extended".to_string(),
                 "GL_EXT_buffer_storage".to_string(),
                 "GL_EXT_disjoint_timer_query".to_string(),
+                "GL_EXT_map_buffer_range".to_string(),
                 "GL_EXT_multi_draw_indirect".to_string(),
                 "GL_EXT_multisampled_render_to_texture".to_string(),
                 "GL_EXT_occlusion_query_boolean".to_string(),
@@ -128,6 +135,7 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_NV_pixel_buffer_object".to_string(),
                 "GL_OES_depth_texture".to_string(),
                 "GL_OES_draw_elements_base_vertex".to_string(),
+                "GL_OES_mapbuffer".to_string(),
                 "GL_OES_packed_depth_stencil".to_string(),
                 "GL_OES_primitive_bounding_box".to_string(),
                 "GL_OES_rgb8_rgba8".to_string(),
@@ -314,8 +314,9 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
             use texture::{{TextureCreationError, Texture1dDataSource, Texture2dDataSource}};
             use texture::{{Texture3dDataSource, Texture2dDataSink, MipmapsOption, CompressedMipmapsOption}};
             use texture::{{RawImage1d, RawImage2d, RawImage3d, CubeLayer}};
+            use texture::{{PixelValue, WriteRowsError, CompressedLevelError}};
 
-            use image_format::{{ClientFormatAny, TextureFormatRequest}};
+            use image_format::{{ClientFormat, ClientFormatAny, TextureFormatRequest}};
             use image_format::{{UncompressedFloatFormat, UncompressedIntFormat}};
             use image_format::{{CompressedFormat, DepthFormat, DepthStencilFormat, StencilFormat}};
             use image_format::{{CompressedSrgbFormat, SrgbFormat, UncompressedUintFormat}};
@@ -409,6 +410,32 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                 }}
             ", name)).unwrap();
 
+    // `DeferredDestroy` trait impl
+    (writeln!(dest, "
+                impl ::sync::DeferredDestroy for {name} {{
+                    type Detached = ::texture::DetachedTexture;
+
+                    #[inline]
+                    fn detach(self) -> ::texture::DetachedTexture {{
+                        self.0.detach()
+                    }}
+
+                    #[inline]
+                    fn attach(detached: ::texture::DetachedTexture,
+                              context: &::std::rc::Rc<::context::Context>) -> {name}
+                    {{
+                        {name}(TextureAny::attach(detached, context))
+                    }}
+
+                    #[inline]
+                    fn into_deferred_object(detached: ::texture::DetachedTexture)
+                                            -> ::sync::DeferredObject
+                    {{
+                        ::sync::DeferredObject::Texture(detached)
+                    }}
+                }}
+            ", name = name)).unwrap();
+
     // `UniformValue` trait impl
     {
         match ty {
@@ -472,7 +499,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                         }}
                     ", name = name)).unwrap();
             },
-            TextureType::Srgb => {
+            TextureType::Srgb | TextureType::Integral | TextureType::Unsigned => {
                 (writeln!(dest, "
                         impl<'t> ::framebuffer::ToColorAttachment<'t> for &'t {name} {{
                             #[inline]
@@ -589,6 +616,25 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                mipmaps = mipmaps_option_ty)).unwrap();
     }
 
+    // writing the `from_bgra` function
+    if dimensions == TextureDimensions::Texture2d && ty == TextureType::Regular {
+        (writeln!(dest, "
+                /// Builds a new texture by uploading data whose pixels are in the BGR or BGRA
+                /// order.
+                ///
+                /// The data is uploaded directly if the backend supports it, and converted on the
+                /// CPU otherwise. The format of `image` must be `U8U8U8` or `U8U8U8U8`.
+                #[inline]
+                pub fn from_bgra<'a, F>(facade: &F, image: RawImage2d<'a, u8>)
+                                        -> Result<{name}, TextureCreationError>
+                                        where F: Facade
+                {{
+                    any::new_texture_bgra(facade, {default_format}, image,
+                                          MipmapsOption::AutoGeneratedMipmaps).map(|t| {name}(t))
+                }}
+            ", name = name, default_format = default_format)).unwrap();
+    }
+
     // writing the `with_compressed_data` function
     if is_compressed && !dimensions.is_multisample() && !dimensions.is_cube() {
         let param = match dimensions {
@@ -612,12 +658,13 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                 {{
                     let data = Cow::Borrowed(data.as_ref());
                     let client_format = {client_format_any}(format);
-                    Ok({name}(try!(any::new_texture(facade, {default_format}, Some((client_format, data)),
+                    let format = TextureFormatRequest::Specific(format.to_texture_format());
+                    Ok({name}(try!(any::new_texture(facade, format, Some((client_format, data)),
                                                     mipmaps.into(), {dim_params_passing}))))
                 }}
             ", dim_params = dimensions_parameters_input, dim_params_passing = dimensions_parameters_passing,
                param = param, client_format_any = client_format_any_ty, 
-               name = name, format = relevant_format, default_format = default_format,
+               name = name, format = relevant_format,
                mipmaps = mipmaps_option_ty).unwrap());
     }
 
@@ -748,6 +795,10 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
         (writeln!(dest, "
                 /// Creates an empty texture with a specific format.
                 ///
+                /// The texture is created with exactly the requested format. If this format is
+                /// not supported by the backend, `FormatNotSupported` is returned instead of
+                /// picking another format.
+                ///
                 /// The texture (and its mipmaps) will contain undefined data.
                 #[inline]
                 pub fn empty_with_format<F>(facade: &F, format: {format}, mipmaps: {mipmaps}, {dim_params}) -> Result<{name}, TextureCreationError> where F: Facade {{
@@ -765,6 +816,39 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
         (writeln!(dest, "}}")).unwrap();
     }
 
+    // writing the `empty_renderable` function
+    if ty == TextureType::Regular || ty == TextureType::Srgb || ty == TextureType::Integral ||
+       ty == TextureType::Unsigned
+    {
+        // opening function
+        (writeln!(dest, "
+                /// Creates an empty texture with a specific format that can be used as a render
+                /// target.
+                ///
+                /// Contrary to `empty_with_format`, this function returns `FormatNotSupported` if
+                /// the format is not color-renderable by the backend. The texture is always
+                /// created with exactly the requested format.
+                ///
+                /// The texture (and its mipmaps) will contain undefined data.
+                #[inline]
+                pub fn empty_renderable<F>(facade: &F, format: {format}, mipmaps: {mipmaps}, {dim_params}) -> Result<{name}, TextureCreationError> where F: Facade {{
+                    if !format.is_color_renderable(facade) {{
+                        return Err(TextureCreationError::FormatNotSupported);
+                    }}
+
+                    let format = format.to_texture_format();
+                    let format = TextureFormatRequest::Specific(format);
+            ", format = relevant_format, dim_params = dimensions_parameters_input, name = name,
+               mipmaps = mipmaps_option_ty)).unwrap();
+
+        // writing the constructor
+        (write!(dest, "any::new_texture::<_, u8>(facade, format, None, mipmaps.into(), {})", dimensions_parameters_passing)).unwrap();
+        (writeln!(dest, ".map(|t| {}(t))", name)).unwrap();
+
+        // closing function
+        (writeln!(dest, "}}")).unwrap();
+    }
+
     // writing the `empty_with_mipmaps` function
     if !is_compressed {
         // opening function
@@ -836,6 +920,83 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                     let rect = Rect {{ left: 0, bottom: 0, width: self.get_width(),
                                        height: self.get_height().unwrap_or(1) }};
                     self.0.main_level().first_layer().into_image(None).unwrap().raw_read(&rect)
+                          .unwrap()
+                }}
+
+                /// Reads the content of the texture to RAM, with the rows in top-to-bottom
+                /// order.
+                ///
+                /// Same as `read`, except that the first row of the result is the top of the
+                /// texture instead of the bottom.
+                #[inline]
+                pub fn read_flipped<T>(&self) -> T where T: Texture2dDataSink<(u8, u8, u8, u8)> {{
+                    let rect = Rect {{ left: 0, bottom: 0, width: self.get_width(),
+                                       height: self.get_height().unwrap_or(1) }};
+                    self.0.main_level().first_layer().into_image(None).unwrap()
+                          .raw_read_flipped(&rect).unwrap()
+                }}
+
+                /// Reads the content of the texture to RAM as floating-point values.
+                ///
+                /// Contrary to `read`, the values are not clamped. This is useful for
+                /// floating-point textures that contain values outside of the `[0.0, 1.0]`
+                /// range.
+                ///
+                /// # Panic
+                ///
+                /// Panics if the backend doesn't support reading floating-point values, which is
+                /// the case of OpenGL ES.
+                #[inline]
+                pub fn read_float<T>(&self) -> T
+                                     where T: Texture2dDataSink<(f32, f32, f32, f32)>
+                {{
+                    let rect = Rect {{ left: 0, bottom: 0, width: self.get_width(),
+                                       height: self.get_height().unwrap_or(1) }};
+                    self.0.main_level().first_layer().into_image(None).unwrap()
+                          .raw_read_float(&rect).unwrap()
+                }}
+
+                /// Reads the content of the texture to RAM as 16-bits unsigned values.
+                ///
+                /// Contrary to `read_float`, the values are not converted, which means that
+                /// reading a texture with a 16-bits normalized format doesn't lose precision.
+                ///
+                /// # Panic
+                ///
+                /// Panics if the backend doesn't support reading 16-bits values, which is the
+                /// case of OpenGL ES without the `GL_EXT_texture_norm16` extension.
+                #[inline]
+                pub fn read_u16<T>(&self) -> T
+                                   where T: Texture2dDataSink<(u16, u16, u16, u16)>
+                {{
+                    let rect = Rect {{ left: 0, bottom: 0, width: self.get_width(),
+                                       height: self.get_height().unwrap_or(1) }};
+                    self.0.main_level().first_layer().into_image(None).unwrap()
+                          .raw_read_u16(&rect).unwrap()
+                }}
+
+                /// Reads a part of the texture into an existing slice, without allocating.
+                ///
+                /// The rectangle must be entirely inside of the texture and the length of
+                /// `dest` must be exactly `rect.width * rect.height * format.get_size()`.
+                /// This is useful to read the texture every frame into buffers that you reuse.
+                #[inline]
+                pub fn read_into(&self, rect: &Rect, dest: &mut [u8], format: ClientFormat)
+                                 -> Result<(), framebuffer::ReadError>
+                {{
+                    self.0.main_level().first_layer().into_image(None).unwrap()
+                          .raw_read_into(rect, dest, format)
+                }}
+
+                /// Same as `read_into`, except that the first row written to `dest` is the top
+                /// of the rectangle instead of the bottom.
+                #[inline]
+                pub fn read_into_flipped(&self, rect: &Rect, dest: &mut [u8],
+                                         format: ClientFormat)
+                                         -> Result<(), framebuffer::ReadError>
+                {{
+                    self.0.main_level().first_layer().into_image(None).unwrap()
+                          .raw_read_into_flipped(rect, dest, format)
                 }}
             "#)).unwrap();
 
@@ -858,6 +1019,36 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
             "#)).unwrap();
     }
 
+    // writing the `read` function of integral textures
+    if dimensions == TextureDimensions::Texture2d &&
+       (ty == TextureType::Integral || ty == TextureType::Unsigned)
+    {
+        let (pixel, function) = if ty == TextureType::Integral {
+            ("(i32, i32, i32, i32)", "raw_read_integer")
+        } else {
+            ("(u32, u32, u32, u32)", "raw_read_unsigned")
+        };
+
+        (write!(dest, r#"
+                /// Reads the content of the texture to RAM.
+                ///
+                /// You should avoid doing this at all cost during performance-critical
+                /// operations (for example, while you're drawing).
+                ///
+                /// # Panic
+                ///
+                /// Panics if the backend doesn't support reading integers, which is the case
+                /// of OpenGL ES.
+                #[inline]
+                pub fn read<T>(&self) -> T where T: Texture2dDataSink<{pixel}> {{
+                    let rect = Rect {{ left: 0, bottom: 0, width: self.get_width(),
+                                       height: self.get_height().unwrap_or(1) }};
+                    self.0.main_level().first_layer().into_image(None).unwrap().{function}(&rect)
+                          .unwrap()
+                }}
+            "#, pixel = pixel, function = function)).unwrap();
+    }
+
     // writing the `read_compressed_data` function
     if is_compressed && !dimensions.is_array() {
         (write!(dest, r#"
@@ -912,6 +1103,33 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                 compressed_restrictions = compressed_restrictions)).unwrap();
     }
 
+    // writing the `write_rows` function
+    if dimensions == TextureDimensions::Texture2d &&
+            (ty == TextureType::Regular || ty == TextureType::Srgb)
+    {
+        (write!(dest, r#"
+                /// Uploads some data in the texture, one row at a time, without holding the whole
+                /// image in memory.
+                ///
+                /// The first row is written at the bottom of the rectangle. Returns an error if
+                /// `rows` produces less than `rect.height` rows or if a row doesn't contain
+                /// exactly `rect.width` pixels.
+                ///
+                /// See `TextureAnyMipmap::write_rows` for more details.
+                ///
+                /// ## Panic
+                ///
+                /// Panics if the rectangle is out of the bounds of the texture.
+                #[inline]
+                pub fn write_rows<P, I, R>(&self, rect: Rect, rows: I) -> Result<(), WriteRowsError>
+                                           where P: PixelValue, I: IntoIterator<Item = R>,
+                                                 R: AsRef<[P]>
+                {{
+                    self.main_level().write_rows(rect, rows)
+                }}
+            "#)).unwrap();
+    }
+
     // writing the `write_compressed_data` function
     // TODO: implement for other types too
     if dimensions == TextureDimensions::Texture2d && is_compressed
@@ -944,6 +1162,58 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
             "#, format = relevant_format)).unwrap();
     }
 
+    // writing the `read_compressed_level` and `write_compressed_level` functions
+    if dimensions == TextureDimensions::Texture2d && is_compressed {
+        (write!(dest, r#"
+                /// Reads a whole mipmap level of the texture to RAM without decompressing it.
+                ///
+                /// The data is exactly what the OpenGL implementation stores, which makes it
+                /// possible to cache it and to upload it later with `write_compressed_level`.
+                /// The size of the data is given by `GL_TEXTURE_COMPRESSED_IMAGE_SIZE`.
+                ///
+                /// Returns an error if the level doesn't exist, if its dimensions are not aligned
+                /// to the blocks of the format, or with OpenGL ES which can't read compressed
+                /// data.
+                pub fn read_compressed_level(&self, level: u32)
+                                             -> Result<({format}, Vec<u8>), CompressedLevelError>
+                {{
+                    let mipmap = match self.0.mipmap(level) {{
+                        Some(mipmap) => mipmap,
+                        None => return Err(CompressedLevelError::LevelOutOfRange {{
+                            level: level,
+                            levels: self.0.get_mipmap_levels(),
+                        }}),
+                    }};
+
+                    match try!(mipmap.download_compressed_level()) {{
+                        ({client_format_any}(format), buf) => Ok((format, buf)),
+                        _ => Err(CompressedLevelError::UnknownFormat),
+                    }}
+                }}
+
+                /// Replaces a whole mipmap level of the texture with compressed data.
+                ///
+                /// `format` must be the format of the texture and `data` must contain exactly the
+                /// number of bytes of the level.
+                ///
+                /// Note that this may cause a synchronization if you use the texture right before
+                /// or right after this call.
+                pub fn write_compressed_level(&self, level: u32, format: {format}, data: &[u8])
+                                              -> Result<(), CompressedLevelError>
+                {{
+                    let mipmap = match self.0.mipmap(level) {{
+                        Some(mipmap) => mipmap,
+                        None => return Err(CompressedLevelError::LevelOutOfRange {{
+                            level: level,
+                            levels: self.0.get_mipmap_levels(),
+                        }}),
+                    }};
+
+                    mipmap.upload_compressed_level({client_format_any}(format), data)
+                }}
+            "#, format = relevant_format, client_format_any = client_format_any_ty)).unwrap();
+    }
+
     // `resident_if_supported`
     (write!(dest, r#"
             /// Turns the texture into a `ResidentTexture`.
@@ -1096,7 +1366,8 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                     ///
                     /// ## Panic
                     ///
-                    /// Panics if the the dimensions of `data` don't match the `Rect`.
+                    /// - Panics if the the dimensions of `data` don't match the `Rect`.
+                    /// - Panics if the origin of the `Rect` is negative.
                     {compressed_restrictions}
                     pub fn write<'a, T>(&self, rect: Rect, data: T) where T: {data_source_trait}<'a> {{
                         let RawImage2d {{ data, width, height, format: client_format }} =
@@ -1104,10 +1375,11 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
 
                         assert_eq!(width, rect.width);
                         assert_eq!(height, rect.height);
+                        assert!(rect.left >= 0 && rect.bottom >= 0, "The rectangle is out of bounds");
 
                         let client_format = ClientFormatAny::ClientFormat(client_format);
 
-                        self.0.upload_texture(rect.left, rect.bottom, 0, (client_format, data),
+                        self.0.upload_texture(rect.left as u32, rect.bottom as u32, 0, (client_format, data),
                                               width, Some(height), None, true).unwrap()
                     }}
                 "#, data_source_trait = data_source_trait,
@@ -1126,7 +1398,8 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                     ///
                     /// ## Panic
                     ///
-                    /// Panics if the the dimensions of `data` don't match the `Rect`.
+                    /// Panics if the the dimensions of `data` don't match the `Rect`, or if the origin
+                    /// of the `Rect` is negative.
                     ///
                     /// Calling this will result in a panic of type INVALID_OPERATION error if `Rect::width`
                     /// or `Rect::height` is not equal to 0 (border), or if the written dimensions do not match
@@ -1142,11 +1415,12 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
 
                         assert_eq!(width, rect.width);
                         assert_eq!(height, rect.height);
+                        assert!(rect.left >= 0 && rect.bottom >= 0, "The rectangle is out of bounds");
 
                         let data = Cow::Borrowed(data.as_ref());
                         let client_format = {client_format_any}(format);
 
-                        self.0.upload_texture(rect.left, rect.bottom, 0, (client_format, data),
+                        self.0.upload_texture(rect.left as u32, rect.bottom as u32, 0, (client_format, data),
                                               width, Some(height), None, false)
                     }}
                 "#, format = relevant_format, client_format_any = client_format_any_ty)).unwrap();
@@ -1219,7 +1493,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                             }}
                         ", name = name)).unwrap();
                 },
-                TextureType::Srgb => {
+                TextureType::Srgb | TextureType::Integral | TextureType::Unsigned => {
                     (writeln!(dest, "
                             impl<'t> ::framebuffer::ToColorAttachment<'t> for {name}Mipmap<'t> {{
                                 #[inline]
@@ -1301,7 +1575,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                             }}
                         ", name = name)).unwrap();
                 },
-                TextureType::Srgb => {
+                TextureType::Srgb | TextureType::Integral | TextureType::Unsigned => {
                     (writeln!(dest, "
                             impl<'t> ::framebuffer::ToColorAttachment<'t> for {name}LayerMipmap<'t> {{
                                 #[inline]
@@ -1373,7 +1647,7 @@ fn build_texture<W: Write>(mut dest: &mut W, ty: TextureType, dimensions: Textur
                         }}
                     ", name = name)).unwrap();
             },
-            TextureType::Srgb => {
+            TextureType::Srgb | TextureType::Integral | TextureType::Unsigned => {
                 (writeln!(dest, "
                         impl<'t> ::framebuffer::ToColorAttachment<'t> for {name}Image<'t> {{
                             #[inline]
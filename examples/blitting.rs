@@ -47,8 +47,8 @@ fn main() {
         if rand::random::<f64>() <= 0.016666 {
             let (left, bottom, dimensions): (f32, f32, f32) = rand::random();
             let dest_rect = glium::BlitTarget {
-                left: (left * dest_texture.get_width() as f32) as u32,
-                bottom: (bottom * dest_texture.get_height().unwrap() as f32) as u32,
+                left: (left * dest_texture.get_width() as f32) as i32,
+                bottom: (bottom * dest_texture.get_height().unwrap() as f32) as i32,
                 width: (dimensions * dest_texture.get_width() as f32) as i32,
                 height: (dimensions * dest_texture.get_height().unwrap() as f32) as i32,
             };
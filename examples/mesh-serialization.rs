@@ -0,0 +1,158 @@
+#[macro_use]
+extern crate glium;
+
+mod support;
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::mem;
+use std::path::Path;
+use std::slice;
+
+use glium::Surface;
+use glium::glutin;
+use glium::index::PrimitiveType;
+
+/// This is what an offline mesh compiler would do: it writes the vertex format followed by
+/// the raw vertex data in a file.
+fn save_mesh(path: &Path) {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let vertices = [
+        Vertex { position: [-0.5, -0.5], color: [0.0, 1.0, 0.0] },
+        Vertex { position: [ 0.0,  0.5], color: [0.0, 0.0, 1.0] },
+        Vertex { position: [ 0.5, -0.5], color: [1.0, 0.0, 0.0] },
+    ];
+
+    let format = glium::vertex::serialize_vertex_format(&<Vertex as glium::Vertex>::build_bindings());
+    let data = unsafe {
+        slice::from_raw_parts(vertices.as_ptr() as *const u8,
+                              vertices.len() * mem::size_of::<Vertex>())
+    };
+
+    let mut file = File::create(path).unwrap();
+    write_u32(&mut file, format.len() as u32);
+    file.write_all(&format).unwrap();
+    write_u32(&mut file, mem::size_of::<Vertex>() as u32);
+    file.write_all(data).unwrap();
+}
+
+/// This is what the runtime does: it loads the mesh without knowing its layout in advance.
+fn load_mesh(display: &glium::Display, path: &Path) -> glium::vertex::VertexBufferAny {
+    let mut content = Vec::new();
+    File::open(path).unwrap().read_to_end(&mut content).unwrap();
+
+    let format_len = read_u32(&content[0 .. 4]) as usize;
+    let format = glium::vertex::deserialize_vertex_format(&content[4 .. 4 + format_len]).unwrap();
+    let elements_size = read_u32(&content[4 + format_len .. 8 + format_len]) as usize;
+    let data = &content[8 + format_len ..];
+
+    unsafe {
+        glium::vertex::VertexBufferAny::new_raw(display, data, format, elements_size).unwrap()
+    }
+}
+
+fn write_u32(file: &mut File, value: u32) {
+    let bytes = [value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8];
+    file.write_all(&bytes).unwrap();
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24
+}
+
+fn main() {
+    use glium::DisplayBuild;
+
+    let path = std::env::temp_dir().join("glium-mesh-serialization.bin");
+    save_mesh(&path);
+
+    // building the display, ie. the main object
+    let display = glutin::WindowBuilder::new()
+        .build_glium()
+        .unwrap();
+
+    // loading the mesh that we have just saved
+    let vertex_buffer = load_mesh(&display, &path);
+    let indices = glium::index::NoIndices(PrimitiveType::TrianglesList);
+
+    // compiling shaders and linking them together
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec3 color;
+
+                out vec3 vColor;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    vColor = color;
+                }
+            ",
+
+            fragment: "
+                #version 140
+                in vec3 vColor;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(vColor, 1.0);
+                }
+            "
+        },
+
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 vColor;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    vColor = color;
+                }
+            ",
+
+            fragment: "
+                #version 110
+                varying vec3 vColor;
+
+                void main() {
+                    gl_FragColor = vec4(vColor, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    // the main loop
+    support::start_loop(|| {
+        // drawing a frame
+        let mut target = display.draw();
+        target.clear_color(0.0, 0.0, 0.0, 0.0);
+        target.draw(&vertex_buffer, &indices, &program, &glium::uniforms::EmptyUniforms,
+                    &Default::default()).unwrap();
+        target.finish().unwrap();
+
+        // polling and handling the events received by the window
+        for event in display.poll_events() {
+            match event {
+                glutin::Event::Closed => return support::Action::Stop,
+                _ => ()
+            }
+        }
+
+        support::Action::Continue
+    });
+}
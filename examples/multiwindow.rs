@@ -0,0 +1,125 @@
+#[macro_use]
+extern crate glium;
+
+mod support;
+
+use glium::Surface;
+use glium::glutin;
+use glium::index::PrimitiveType;
+
+fn main() {
+    use glium::DisplayBuild;
+
+    // building the main window
+    let display = glutin::WindowBuilder::new()
+        .with_title(format!("Main window"))
+        .build_glium()
+        .unwrap();
+
+    // building a second window whose context shares its objects with the first one
+    let preview = display.build_shared_window(glutin::WindowBuilder::new()
+                                                  .with_title(format!("Preview"))
+                                                  .with_dimensions(256, 256))
+                         .unwrap();
+
+    // the vertex buffer, index buffer and program are only created once
+    let vertex_buffer = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Vertex, position, color);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-0.5, -0.5], color: [0.0, 1.0, 0.0] },
+                Vertex { position: [ 0.0,  0.5], color: [0.0, 0.0, 1.0] },
+                Vertex { position: [ 0.5, -0.5], color: [1.0, 0.0, 0.0] },
+            ]
+        ).unwrap()
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                               &[0u16, 1, 2]).unwrap();
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec3 color;
+
+                out vec3 vColor;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    vColor = color;
+                }
+            ",
+
+            fragment: "
+                #version 140
+                in vec3 vColor;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(vColor, 1.0);
+                }
+            "
+        },
+
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 vColor;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    vColor = color;
+                }
+            ",
+
+            fragment: "
+                #version 110
+                varying vec3 vColor;
+
+                void main() {
+                    gl_FragColor = vec4(vColor, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    // the main loop
+    support::start_loop(|| {
+        // drawing the same objects on both windows
+        let mut target = display.draw();
+        target.clear_color(0.0, 0.0, 0.0, 0.0);
+        target.draw(&vertex_buffer, &index_buffer, &program, &glium::uniforms::EmptyUniforms,
+                    &Default::default()).unwrap();
+        target.finish().unwrap();
+
+        let mut target = preview.draw();
+        target.clear_color(0.2, 0.2, 0.2, 0.0);
+        target.draw(&vertex_buffer, &index_buffer, &program, &glium::uniforms::EmptyUniforms,
+                    &Default::default()).unwrap();
+        target.finish().unwrap();
+
+        // polling and handling the events received by the windows
+        for event in display.poll_events().chain(preview.poll_events()) {
+            match event {
+                glutin::Event::Closed => return support::Action::Stop,
+                _ => ()
+            }
+        }
+
+        support::Action::Continue
+    });
+}
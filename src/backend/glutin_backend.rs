@@ -12,6 +12,8 @@ extern crate glutin;
 
 use libc;
 
+use CaptureError;
+use ContextExt;
 use DisplayBuild;
 use Frame;
 use GliumCreationError;
@@ -19,15 +21,32 @@ use SwapBuffersError;
 
 use debug;
 use context;
+use ops;
+use program;
+use texture::RawImage2d;
 use backend;
 use backend::Context;
 use backend::Backend;
+use backend::SwapInterval;
 
+use std::mem;
+use std::ffi::CStr;
 use std::cell::{RefCell, Ref};
+use std::collections::VecDeque;
 use std::rc::Rc;
 use std::ops::Deref;
 
 /// Facade implementation for glutin. Wraps both glium and glutin.
+///
+/// # Destruction
+///
+/// Every buffer, texture, program or other object created with the facade holds a reference to
+/// the context, which itself holds the window. Destroying the facade only releases its own
+/// reference and the objects that glium uses internally. The context and the window are
+/// destroyed once the last object that uses them is destroyed.
+///
+/// This means that the objects can be destroyed in any order. In particular it is safe to
+/// destroy the facade before the resources that have been created with it.
 #[derive(Clone)]
 pub struct GlutinFacade {
     // contains everything related to the current context and its state
@@ -35,6 +54,9 @@ pub struct GlutinFacade {
 
     // contains the window
     backend: Rc<Option<RefCell<Rc<GlutinWindowBackend>>>>,
+
+    // captures started with `capture_async` that haven't been delivered yet
+    async_captures: ops::AsyncCaptureQueue,
 }
 
 impl backend::Facade for GlutinFacade {
@@ -96,6 +118,35 @@ impl<'a> Deref for WinRef<'a> {
 }
 
 impl GlutinFacade {
+    /// Builds a facade from any object that implements the `Backend` trait, for example a
+    /// wrapper around an SDL2 or Qt window.
+    ///
+    /// The resulting facade behaves like one built by glutin, except that it has no window.
+    /// `get_window` returns `None` and `poll_events`/`wait_events` don't produce any event. You
+    /// must handle events with the library that provides the backend.
+    ///
+    /// The dimensions of the framebuffer are queried from the backend every time `draw` is
+    /// called, which means that resizing the window is automatically handled.
+    ///
+    /// See `Context::new` for the meaning of `check_current_context`.
+    ///
+    /// # Safety
+    ///
+    /// The OpenGL context of the backend must be newly-created. See `Context::new`.
+    pub unsafe fn from_backend<B>(backend: B, check_current_context: bool)
+                                  -> Result<GlutinFacade, GliumCreationError<()>>
+                                  where B: Backend + 'static
+    {
+        let context = try!(context::Context::new(backend, check_current_context,
+                                                 Default::default()));
+
+        Ok(GlutinFacade {
+            context: context,
+            backend: Rc::new(None),
+            async_captures: Rc::new(RefCell::new(VecDeque::new())),
+        })
+    }
+
     /// Reads all events received by the window.
     ///
     /// This iterator polls for events and can be exhausted.
@@ -120,15 +171,177 @@ impl GlutinFacade {
         Option::as_ref(&self.backend).map(|w| WinRef(w.borrow()))
     }
 
+    /// Builds a new invisible window whose OpenGL context shares its objects with this one.
+    ///
+    /// The returned backend can be moved to a loader thread and turned into a secondary facade
+    /// with `from_backend`. Textures, buffers and programs created there are visible to the
+    /// OpenGL context of this facade as well. Use a `LinearSyncFence` to make sure that the
+    /// upload has finished before you use the objects.
+    ///
+    /// Returns `glutin::CreationError::NotSupported` if this facade is headless.
+    pub fn build_shared_backend(&self)
+                                -> Result<GlutinWindowBackend, GliumCreationError<glutin::CreationError>>
+    {
+        let window = match Option::as_ref(&self.backend) {
+            Some(window) => window.borrow(),
+            None => return Err(GliumCreationError::BackendCreationError(
+                                                        glutin::CreationError::NotSupported)),
+        };
+
+        window.rebuild(glutin::WindowBuilder::new().with_visibility(false))
+    }
+
+    /// Builds an `UploadContext` whose objects are shared with this facade.
+    ///
+    /// Returns `glutin::CreationError::NotSupported` if this facade is headless.
+    pub fn build_upload_context(&self)
+                                -> Result<UploadContext, GliumCreationError<glutin::CreationError>>
+    {
+        let backend = Rc::new(try!(self.build_shared_backend()));
+
+        let context = try!(unsafe {
+            context::Context::new_shared(backend, true, Default::default(), &self.context)
+        });
+
+        Ok(UploadContext {
+            context: context,
+        })
+    }
+
+    /// Builds a new window whose OpenGL context shares its objects with this one.
+    ///
+    /// Buffers, textures and programs created with one of the two facades can be used to draw
+    /// on both windows. Each facade keeps track of the state of its own OpenGL context, and
+    /// glium automatically switches between the two contexts.
+    ///
+    /// # Panic
+    ///
+    /// Panics if this facade is headless.
+    pub fn build_shared_window(&self, builder: glutin::WindowBuilder<'static>)
+                               -> Result<GlutinFacade, GliumCreationError<glutin::CreationError>>
+    {
+        let backend = {
+            let window = Option::as_ref(&self.backend)
+                                .expect("can't share the lists of a headless display").borrow();
+            Rc::new(try!(window.rebuild(builder)))
+        };
+
+        let context = try!(unsafe {
+            context::Context::new_shared(backend.clone(), true, Default::default(), &self.context)
+        });
+
+        Ok(GlutinFacade {
+            context: context,
+            backend: Rc::new(Some(RefCell::new(backend))),
+            async_captures: Rc::new(RefCell::new(VecDeque::new())),
+        })
+    }
+
+    /// Prewarms a list of programs, draw parameters and vertex formats.
+    ///
+    /// Returns an iterator that calls `Program::prewarm` on one element of the list each
+    /// time `next` is called, which lets you update a progress bar between two elements.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+    /// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+    /// # let format: glium::vertex::VertexFormat = unsafe { std::mem::uninitialized() };
+    /// let params = Default::default();
+    /// let list = vec![(&program, &params, &format)];
+    ///
+    /// for (num, result) in display.prewarm_many(list.into_iter()).enumerate() {
+    ///     result.unwrap();
+    ///     println!("{} programs prewarmed", num + 1);
+    /// }
+    /// ```
+    #[inline]
+    pub fn prewarm_many<'a, I>(&self, list: I) -> program::PrewarmIter<'a, I> {
+        program::PrewarmIter::new(self.context.clone(), list)
+    }
+
     /// Start drawing on the backbuffer.
     ///
     /// This function returns a `Frame`, which can be used to draw on it. When the `Frame` is
     /// destroyed, the buffers are swapped.
     ///
     /// Note that destroying a `Frame` is immediate, even if vsync is enabled.
+    ///
+    /// The dimensions of the framebuffer are queried every time, and the callbacks registered
+    /// with `on_framebuffer_resize` are called before the `Frame` is returned if they have
+    /// changed.
+    ///
+    /// If a limit has been set with `set_max_frames_in_flight`, this function first blocks
+    /// until there are fewer frames in flight than the limit.
     #[inline]
     pub fn draw(&self) -> Frame {
-        Frame::new(self.context.clone(), self.get_framebuffer_dimensions())
+        self.context.wait_frames_in_flight();
+        Frame::with_async_captures(self.context.clone(),
+                                   self.context.update_framebuffer_dimensions(),
+                                   self.async_captures.clone())
+    }
+
+    /// Pushes a debug group that is popped when the returned object is destroyed.
+    ///
+    /// See `debug::DebugScope`.
+    #[inline]
+    pub fn debug_scope(&self, name: &str) -> debug::DebugScope {
+        debug::DebugScope::new(&self.context, name)
+    }
+
+    /// Starts reading the content of the back buffer without waiting for the operation to
+    /// finish.
+    ///
+    /// Call this function before `finish` to capture the frame that you are drawing. The pixels
+    /// are transferred to a pixel buffer in the background, and `callback` is called with the
+    /// same image as `Surface::capture` would return once the transfer has finished. This is
+    /// done by `Frame::finish` or `poll_async_operations`, which never block. Several captures
+    /// can be in progress at the same time, in which case their callbacks are called in the
+    /// order in which the captures have been started.
+    ///
+    /// If the `Display` and all its frames are destroyed while captures are in progress, their
+    /// callbacks are dropped without being called. If the pixels of a capture can't be read
+    /// back, for example because the context has been lost, the callback receives the error.
+    ///
+    /// Returns `CaptureError::NotSupported` if the backend doesn't support pixel buffers and
+    /// sync fences, or another error if the capture couldn't be started.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+    /// let target = display.draw();
+    /// // draw the scene...
+    /// display.capture_async(|image| {
+    ///     match image {
+    ///         Ok(image) => println!("captured a {}x{} frame", image.width, image.height),
+    ///         Err(err) => println!("the capture failed: {}", err),
+    ///     }
+    /// }).unwrap();
+    /// target.finish().unwrap();
+    /// ```
+    pub fn capture_async<F>(&self, callback: F) -> Result<(), CaptureError>
+                            where F: FnOnce(Result<RawImage2d<'static, u8>, CaptureError>) + Send
+                                     + 'static
+    {
+        let mut callback = Some(callback);
+        let callback = Box::new(move |image: Result<RawImage2d<'static, u8>, CaptureError>| {
+            callback.take().unwrap()(image)
+        });
+
+        let capture = try!(ops::capture_async(&self.context, None,
+                                              self.get_framebuffer_dimensions(), callback));
+        self.async_captures.borrow_mut().push_back(capture);
+        Ok(())
+    }
+
+    /// Calls the callbacks of the captures started with `capture_async` that have finished.
+    ///
+    /// This function never blocks. It is automatically called by `Frame::finish`.
+    #[inline]
+    pub fn poll_async_operations(&self) {
+        ops::poll_async_captures(&self.async_captures);
     }
 }
 
@@ -141,6 +354,50 @@ impl Deref for GlutinFacade {
     }
 }
 
+/// A hidden OpenGL context that shares its objects with a `GlutinFacade`.
+///
+/// Creating textures, buffers and programs with an `UploadContext` instead of the facade
+/// doesn't modify the state of the OpenGL context that is used for drawing. The objects can
+/// then be used to draw with the facade. Create a `LinearSyncFence` with the upload context
+/// and call `wait_server` with the facade to make sure that the uploads are finished before
+/// the objects are used.
+///
+/// ## Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+/// let upload = display.build_upload_context().unwrap();
+///
+/// let texture = glium::texture::Texture2d::new(&upload, vec![vec![(0u8, 0u8, 0u8, 0u8)]])
+///                                          .unwrap();
+///
+/// if let Ok(fence) = glium::LinearSyncFence::new(&upload) {
+///     fence.wait_server(&display);
+/// }
+///
+/// // `texture` can now be used to draw on `display`
+/// ```
+pub struct UploadContext {
+    // the context holds the hidden window
+    context: Rc<context::Context>,
+}
+
+impl backend::Facade for UploadContext {
+    #[inline]
+    fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+}
+
+impl Deref for UploadContext {
+    type Target = Context;
+
+    #[inline]
+    fn deref(&self) -> &Context {
+        &self.context
+    }
+}
+
 impl DisplayBuild for glutin::WindowBuilder<'static> {
     type Facade = GlutinFacade;
     type Err = GliumCreationError<glutin::CreationError>;
@@ -154,6 +411,7 @@ impl DisplayBuild for glutin::WindowBuilder<'static> {
         let display = GlutinFacade {
             context: context,
             backend: Rc::new(Some(RefCell::new(backend))),
+            async_captures: Rc::new(RefCell::new(VecDeque::new())),
         };
 
         Ok(display)
@@ -168,6 +426,7 @@ impl DisplayBuild for glutin::WindowBuilder<'static> {
         let display = GlutinFacade {
             context: context,
             backend: Rc::new(Some(RefCell::new(backend))),
+            async_captures: Rc::new(RefCell::new(VecDeque::new())),
         };
 
         Ok(display)
@@ -189,11 +448,12 @@ impl<'a> DisplayBuild for glutin::HeadlessRendererBuilder<'a> {
 
     fn build_glium_debug(self, debug: debug::DebugCallbackBehavior) -> Result<GlutinFacade, GliumCreationError<glutin::CreationError>> {
         let backend = Rc::new(try!(backend::glutin_backend::GlutinHeadlessBackend::new(self)));
-        let context = try!(unsafe { context::Context::new(backend.clone(), true, Default::default()) });
+        let context = try!(unsafe { context::Context::new(backend.clone(), true, debug) });
 
         let display = GlutinFacade {
             context: context,
             backend: Rc::new(None),
+            async_captures: Rc::new(RefCell::new(VecDeque::new())),
         };
 
         Ok(display)
@@ -201,11 +461,12 @@ impl<'a> DisplayBuild for glutin::HeadlessRendererBuilder<'a> {
 
     unsafe fn build_glium_unchecked_debug(self, debug: debug::DebugCallbackBehavior) -> Result<GlutinFacade, GliumCreationError<glutin::CreationError>> {
         let backend = Rc::new(try!(backend::glutin_backend::GlutinHeadlessBackend::new(self)));
-        let context = try!(context::Context::new(backend.clone(), true, Default::default()));
+        let context = try!(context::Context::new(backend.clone(), true, debug));
 
         let display = GlutinFacade {
             context: context,
             backend: Rc::new(None),
+            async_captures: Rc::new(RefCell::new(VecDeque::new())),
         };
 
         Ok(display)
@@ -243,6 +504,11 @@ unsafe impl Backend for GlutinWindowBackend {
         ((width as f32 * scale) as u32, (height as f32 * scale) as u32)
     }
 
+    #[inline]
+    fn get_window_dimensions(&self) -> (u32, u32) {
+        self.window.get_inner_size().unwrap_or((800, 600))
+    }
+
     #[inline]
     fn is_current(&self) -> bool {
         self.window.is_current()
@@ -252,6 +518,82 @@ unsafe impl Backend for GlutinWindowBackend {
     unsafe fn make_current(&self) {
         self.window.make_current().unwrap();
     }
+
+    unsafe fn set_swap_interval(&self, interval: SwapInterval) -> bool {
+        let value = match interval {
+            SwapInterval::Immediate => 0,
+            SwapInterval::Vsync => 1,
+            SwapInterval::AdaptiveVsync => -1,
+        };
+
+        // glutin doesn't provide a way to change the swap interval after the window has been
+        // created, so we load the platform-specific functions ourselves
+        let wgl = self.window.get_proc_address("wglSwapIntervalEXT");
+        if !wgl.is_null() {
+            let wgl: extern "system" fn(libc::c_int) -> libc::c_int = mem::transmute(wgl);
+            return wgl(value) != 0;
+        }
+
+        // `glXGetProcAddress` returns a pointer even for the functions that the implementation
+        // doesn't support, so we have to check the list of extensions first
+        if let Some(extensions) = self.get_glx_extensions() {
+            let has_extension = |name: &str| extensions.split(' ').any(|e| e == name);
+
+            if has_extension("GLX_EXT_swap_control") &&
+               (value >= 0 || has_extension("GLX_EXT_swap_control_tear"))
+            {
+                let get_display = self.window.get_proc_address("glXGetCurrentDisplay");
+                let get_drawable = self.window.get_proc_address("glXGetCurrentDrawable");
+                let glx = self.window.get_proc_address("glXSwapIntervalEXT");
+
+                if !get_display.is_null() && !get_drawable.is_null() && !glx.is_null() {
+                    let get_display: extern "system" fn() -> *mut libc::c_void =
+                        mem::transmute(get_display);
+                    let get_drawable: extern "system" fn() -> libc::c_ulong =
+                        mem::transmute(get_drawable);
+                    let glx: extern "system" fn(*mut libc::c_void, libc::c_ulong, libc::c_int) =
+                        mem::transmute(glx);
+                    glx(get_display(), get_drawable(), value);
+                    return true;
+                }
+            }
+
+            // the MESA and SGI variants don't support negative values
+            if value < 0 {
+                return false;
+            }
+
+            for &(extension, name) in &[("GLX_SGI_swap_control", "glXSwapIntervalSGI"),
+                                        ("GLX_MESA_swap_control", "glXSwapIntervalMESA")]
+            {
+                let glx = self.window.get_proc_address(name);
+                if has_extension(extension) && !glx.is_null() {
+                    let glx: extern "system" fn(libc::c_int) -> libc::c_int = mem::transmute(glx);
+                    return glx(value) == 0;
+                }
+            }
+
+            return false;
+        }
+
+        // `eglSwapInterval` is a core function, and is only returned by `eglGetProcAddress` if
+        // the implementation supports `EGL_KHR_get_all_proc_addresses`
+        let get_display = self.window.get_proc_address("eglGetCurrentDisplay");
+        let egl = self.window.get_proc_address("eglSwapInterval");
+        if !get_display.is_null() && !egl.is_null() {
+            // EGL doesn't have an equivalent of `EXT_swap_control_tear`
+            if value < 0 {
+                return false;
+            }
+
+            let get_display: extern "system" fn() -> *mut libc::c_void =
+                mem::transmute(get_display);
+            let egl: extern "system" fn(*mut libc::c_void, i32) -> u32 = mem::transmute(egl);
+            return egl(get_display(), value) != 0;
+        }
+
+        false
+    }
 }
 
 #[allow(missing_docs)]
@@ -291,6 +633,53 @@ impl GlutinWindowBackend {
             window: window,
         })
     }
+
+    /// Returns the list of GLX extensions supported by the screen of the current context, or
+    /// `None` if the context doesn't use GLX.
+    ///
+    /// Supposes that the context has been made current before this function is called.
+    unsafe fn get_glx_extensions(&self) -> Option<String> {
+        // value of `GLX_SCREEN`
+        const SCREEN: libc::c_int = 0x800C;
+
+        let get_display = self.window.get_proc_address("glXGetCurrentDisplay");
+        let get_context = self.window.get_proc_address("glXGetCurrentContext");
+        let query_context = self.window.get_proc_address("glXQueryContext");
+        let query_extensions = self.window.get_proc_address("glXQueryExtensionsString");
+
+        if get_display.is_null() || get_context.is_null() || query_context.is_null() ||
+           query_extensions.is_null()
+        {
+            return None;
+        }
+
+        let get_display: extern "system" fn() -> *mut libc::c_void = mem::transmute(get_display);
+        let get_context: extern "system" fn() -> *mut libc::c_void = mem::transmute(get_context);
+        let query_context: extern "system" fn(*mut libc::c_void, *mut libc::c_void, libc::c_int,
+                                              *mut libc::c_int) -> libc::c_int =
+            mem::transmute(query_context);
+        let query_extensions: extern "system" fn(*mut libc::c_void, libc::c_int)
+                                                 -> *const libc::c_char =
+            mem::transmute(query_extensions);
+
+        let display = get_display();
+        let context = get_context();
+        if display.is_null() || context.is_null() {
+            return None;
+        }
+
+        let mut screen = 0;
+        if query_context(display, context, SCREEN, &mut screen) != 0 {
+            return None;
+        }
+
+        let extensions = query_extensions(display, screen);
+        if extensions.is_null() {
+            return None;
+        }
+
+        Some(CStr::from_ptr(extensions).to_string_lossy().into_owned())
+    }
 }
 
 /// An implementation of the `Backend` trait for a glutin headless context.
@@ -11,9 +11,15 @@ There are three concepts in play:
  - The `Facade` trait. Calling functions like `VertexBuffer::new` requires passing an object
    that implements this trait. It is implemented on `Rc<Context>`.
 
+If you want to use glium with your own windowing library, implement the `Backend` trait and
+either call `Context::new` or `Display::from_backend`. The glutin backend is just one
+implementation of this trait.
+
 */
 use std::rc::Rc;
 use std::ops::Deref;
+use std::fmt;
+use std::error::Error;
 
 use libc;
 use CapabilitiesSource;
@@ -24,7 +30,14 @@ use context::ExtensionsList;
 use version::Version;
 
 pub use context::Context;
+pub use context::GlCallbackData;
+pub use context::ClearStatistics;
+pub use context::DriverWorkarounds;
+pub use context::FrameStatistics;
+pub use context::{SyncStatistics, WaitStatistics};
+pub use context::MemoryUsage;
 pub use context::ReleaseBehavior;
+pub use context::{StateSnapshot, StateEntry, StateValue};
 
 #[cfg(feature = "glutin")]
 pub mod glutin_backend;
@@ -45,11 +58,78 @@ pub unsafe trait Backend {
     /// Returns the dimensions of the window, or screen, etc.
     fn get_framebuffer_dimensions(&self) -> (u32, u32);
 
+    /// Returns the dimensions of the window in logical units, which are the units of the
+    /// positions of the mouse.
+    ///
+    /// This is different from the dimensions of the framebuffer on HiDPI screens. The default
+    /// implementation returns `get_framebuffer_dimensions()`.
+    #[inline]
+    fn get_window_dimensions(&self) -> (u32, u32) {
+        self.get_framebuffer_dimensions()
+    }
+
     /// Returns true if the OpenGL context is the current one in the thread.
     fn is_current(&self) -> bool;
 
     /// Makes the OpenGL context the current context in the current thread.
     unsafe fn make_current(&self);
+
+    /// Changes the number of vertical blanks to wait for when swapping buffers.
+    ///
+    /// Returns `true` if the request has been honored. The default implementation doesn't
+    /// do anything and returns `false`.
+    ///
+    /// Supposes that the context has been made current before this function is called.
+    #[inline]
+    unsafe fn set_swap_interval(&self, _interval: SwapInterval) -> bool {
+        false
+    }
+
+    /// Returns true if the default framebuffer has been requested without a back buffer.
+    ///
+    /// With single-buffered contexts, `swap_buffers` is never called and glium calls `glFlush`
+    /// at the end of each frame instead. If this returns `true` but desktop OpenGL reports that
+    /// the context has a back buffer anyway, creating the context fails with
+    /// `GliumCreationError::SingleBufferingNotSupported`. OpenGL ES can't report it, so the
+    /// value returned here is trusted. The default implementation returns `false`.
+    #[inline]
+    fn is_single_buffered(&self) -> bool {
+        false
+    }
+}
+
+/// Describes how swapping buffers should be synchronized with the vertical blank of the screen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SwapInterval {
+    /// Buffers are swapped immediately. Can cause tearing.
+    Immediate,
+
+    /// Swapping buffers waits for the next vertical blank.
+    Vsync,
+
+    /// Swapping buffers waits for the next vertical blank, except if the frame is late in which
+    /// case the buffers are swapped immediately.
+    ///
+    /// Requires the `WGL_EXT_swap_control_tear` or `GLX_EXT_swap_control_tear` extension. EGL
+    /// doesn't have an equivalent extension.
+    AdaptiveVsync,
+}
+
+/// Error that happens when the backend doesn't support changing the swap interval.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SwapIntervalNotSupportedError;
+
+impl fmt::Display for SwapIntervalNotSupportedError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for SwapIntervalNotSupportedError {
+    #[inline]
+    fn description(&self) -> &str {
+        "Changing the swap interval is not supported by the backend"
+    }
 }
 
 unsafe impl<T> Backend for Rc<T> where T: Backend {
@@ -65,6 +145,10 @@ unsafe impl<T> Backend for Rc<T> where T: Backend {
         self.deref().get_framebuffer_dimensions()
     }
 
+    fn get_window_dimensions(&self) -> (u32, u32) {
+        self.deref().get_window_dimensions()
+    }
+
     fn is_current(&self) -> bool {
         self.deref().is_current()
     }
@@ -72,6 +156,14 @@ unsafe impl<T> Backend for Rc<T> where T: Backend {
     unsafe fn make_current(&self) {
         self.deref().make_current();
     }
+
+    unsafe fn set_swap_interval(&self, interval: SwapInterval) -> bool {
+        self.deref().set_swap_interval(interval)
+    }
+
+    fn is_single_buffered(&self) -> bool {
+        self.deref().is_single_buffered()
+    }
 }
 
 /// Trait for types that provide a safe access for glium functions.
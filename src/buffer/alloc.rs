@@ -1,12 +1,15 @@
 use backend::Facade;
 use context::CommandContext;
 use context::Context;
+use context::WaitKind;
+use context::ObjectKind;
 use version::Version;
 use CapabilitiesSource;
 use ContextExt;
 use gl;
 use libc;
-use std::{fmt, mem, ptr};
+use std::{cmp, fmt, io, mem, ptr, slice};
+use std::error::Error;
 use std::cell::Cell;
 use std::rc::Rc;
 use std::ops::{Deref, DerefMut, Range};
@@ -29,6 +32,67 @@ pub enum ReadError {
     ContextLost,
 }
 
+impl fmt::Display for ReadError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for ReadError {
+    fn description(&self) -> &str {
+        match self {
+            &ReadError::NotSupported => "The backend doesn't support reading from a buffer",
+            &ReadError::ContextLost => "The context has been lost",
+        }
+    }
+}
+
+/// Error that can happen when mapping a buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MapError {
+    /// The backend doesn't support mapping buffers.
+    NotSupported,
+
+    /// The context has been lost and the backend couldn't map the buffer.
+    ContextLost,
+
+    /// The backend returned a null pointer for an unknown reason.
+    MappingFailed,
+
+    /// Failed to create the temporary buffer that is used to map an immutable buffer.
+    TemporaryBufferCreation(BufferCreationError),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &MapError::TemporaryBufferCreation(ref err) => {
+                write!(formatter, "{}: {}", self.description(), err)
+            },
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for MapError {
+    fn description(&self) -> &str {
+        match self {
+            &MapError::NotSupported => "The backend doesn't support mapping buffers",
+            &MapError::ContextLost => "The context has been lost",
+            &MapError::MappingFailed => "The backend failed to map the buffer",
+            &MapError::TemporaryBufferCreation(_) => "Failed to create the temporary buffer \
+                                                      used for the mapping",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &MapError::TemporaryBufferCreation(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 /// Error that can happen when copying data between buffers.
 #[derive(Debug, Copy, Clone)]
 pub enum CopyError {
@@ -36,6 +100,159 @@ pub enum CopyError {
     NotSupported,
 }
 
+impl fmt::Display for CopyError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for CopyError {
+    fn description(&self) -> &str {
+        match self {
+            &CopyError::NotSupported => "The backend doesn't support copying between buffers",
+        }
+    }
+}
+
+/// Error that can happen when checking the state of a buffer against OpenGL.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The size of the buffer reported by OpenGL is different from the size glium expects.
+    ///
+    /// This happens if some external code has re-specified the storage of the buffer.
+    SizeMismatch {
+        /// Size in bytes that glium expects.
+        expected: usize,
+        /// Size in bytes reported by OpenGL.
+        obtained: usize,
+    },
+
+    /// The backend doesn't support querying the parameters of a buffer.
+    NotSupported,
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &VerificationError::SizeMismatch { expected, obtained } => {
+                write!(formatter, "{} (expected {} bytes, OpenGL reports {} bytes)",
+                       self.description(), expected, obtained)
+            },
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for VerificationError {
+    fn description(&self) -> &str {
+        match self {
+            &VerificationError::SizeMismatch { .. } => "The size of the buffer has been modified \
+                                                        outside of glium",
+            &VerificationError::NotSupported => "The backend doesn't support querying the \
+                                                 parameters of a buffer",
+        }
+    }
+}
+
+/// Error that can happen when clearing a buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClearError {
+    /// The length of the value must be 1, 2, 4, 8 or 16 bytes.
+    InvalidValueSize,
+
+    /// The offset and the size of the range to clear must be multiples of the length of the
+    /// value.
+    RangeNotAligned,
+
+    /// `glClearBufferSubData` isn't supported and uploading the data from the CPU failed.
+    Upload(WriteError),
+}
+
+impl fmt::Display for ClearError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &ClearError::Upload(ref err) => write!(formatter, "{}: {}", self.description(), err),
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for ClearError {
+    fn description(&self) -> &str {
+        match self {
+            &ClearError::InvalidValueSize => "The length of the value must be 1, 2, 4, 8 or 16 \
+                                              bytes",
+            &ClearError::RangeNotAligned => "The range to clear is not a multiple of the length \
+                                             of the value",
+            &ClearError::Upload(_) => "Failed to upload the data to the buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &ClearError::Upload(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Error that can happen when writing data to a buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WriteError {
+    /// The length of the data doesn't match the length of the buffer or slice.
+    LengthMismatch {
+        /// Length in bytes of the buffer or slice.
+        expected: usize,
+        /// Length in bytes of the data.
+        obtained: usize,
+    },
+
+    /// Failed to create the temporary buffer that is used to upload data to an immutable buffer.
+    TemporaryBufferCreation(BufferCreationError),
+
+    /// The backend doesn't support copying between buffers, which is required to upload data
+    /// to an immutable buffer.
+    CopyNotSupported,
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &WriteError::LengthMismatch { expected, obtained } => {
+                write!(formatter, "{} (expected {} bytes, obtained {} bytes)",
+                       self.description(), expected, obtained)
+            },
+            &WriteError::TemporaryBufferCreation(ref err) => {
+                write!(formatter, "{}: {}", self.description(), err)
+            },
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for WriteError {
+    fn description(&self) -> &str {
+        match self {
+            &WriteError::LengthMismatch { .. } => "The length of the data doesn't match the \
+                                                   length of the buffer",
+            &WriteError::TemporaryBufferCreation(_) => "Failed to create the temporary buffer",
+            &WriteError::CopyNotSupported => "The backend doesn't support copying between \
+                                              buffers",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &WriteError::TemporaryBufferCreation(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Maximum number of bytes that are held in memory when streaming the content of a buffer
+/// from a reader or to a writer.
+pub const TRANSFER_CHUNK_SIZE: usize = 1024 * 1024;
+
 /// A buffer in the graphics card's memory.
 pub struct Alloc {
     context: Rc<Context>,
@@ -69,6 +286,9 @@ pub struct Alloc {
 
     /// ID of the draw call where the buffer was last written as an SSBO.
     latest_shader_write: Cell<u64>,
+
+    /// True if the buffer has been created with a streamed upload that has been interrupted.
+    incomplete: Cell<bool>,
 }
 
 impl Alloc {
@@ -86,6 +306,9 @@ impl Alloc {
             create_buffer(&mut ctxt, size, Some(data), ty, mode)
         });
 
+        ctxt.live_objects.add(ObjectKind::Buffer, id as usize);
+        facade.get_context().get_memory_usage_counters().add_buffer(size);
+
         Ok(Alloc {
             context: facade.get_context().clone(),
             id: id,
@@ -97,6 +320,7 @@ impl Alloc {
             creation_mode: mode,
             mapped: Cell::new(false),
             latest_shader_write: Cell::new(0),
+            incomplete: Cell::new(false),
         })
     }
 
@@ -110,6 +334,9 @@ impl Alloc {
             create_buffer::<()>(&mut ctxt, size, None, ty, mode)
         });
 
+        ctxt.live_objects.add(ObjectKind::Buffer, id as usize);
+        facade.get_context().get_memory_usage_counters().add_buffer(size);
+
         Ok(Alloc {
             context: facade.get_context().clone(),
             id: id,
@@ -121,6 +348,7 @@ impl Alloc {
             creation_mode: mode,
             mapped: Cell::new(false),
             latest_shader_write: Cell::new(0),
+            incomplete: Cell::new(false),
         })
     }
 
@@ -130,6 +358,44 @@ impl Alloc {
         &self.context
     }
 
+    /// Turns the buffer into a `DetachedAlloc`, without destroying the OpenGL buffer.
+    pub fn detach(self) -> DetachedAlloc {
+        let detached = DetachedAlloc {
+            id: self.id,
+            ty: self.ty,
+            size: self.size,
+            persistent_mapping: self.persistent_mapping,
+            immutable: self.immutable,
+            creation_mode: self.creation_mode,
+            created_with_buffer_storage: self.created_with_buffer_storage,
+            latest_shader_write: self.latest_shader_write.get(),
+            incomplete: self.incomplete.get(),
+        };
+
+        // releasing the context without running the destructor
+        unsafe { drop(ptr::read(&self.context)) };
+        mem::forget(self);
+
+        detached
+    }
+
+    /// Builds back a buffer from a `DetachedAlloc`.
+    pub fn attach(detached: DetachedAlloc, context: &Rc<Context>) -> Alloc {
+        Alloc {
+            context: context.clone(),
+            id: detached.id,
+            ty: detached.ty,
+            size: detached.size,
+            persistent_mapping: detached.persistent_mapping,
+            immutable: detached.immutable,
+            created_with_buffer_storage: detached.created_with_buffer_storage,
+            creation_mode: detached.creation_mode,
+            mapped: Cell::new(false),
+            latest_shader_write: Cell::new(detached.latest_shader_write),
+            incomplete: Cell::new(detached.incomplete),
+        }
+    }
+
     /// Returns the total size in bytes of this buffer.
     #[inline]
     pub fn get_size(&self) -> usize {
@@ -142,6 +408,84 @@ impl Alloc {
         self.persistent_mapping.is_some()
     }
 
+    /// Returns the mode the buffer was created with.
+    #[inline]
+    pub fn get_creation_mode(&self) -> BufferMode {
+        self.creation_mode
+    }
+
+    /// Returns true if the buffer can be mapped in memory.
+    #[inline]
+    pub fn is_mapping_supported(&self) -> bool {
+        self.persistent_mapping.is_some() || is_buffer_mapping_supported(&*self.context)
+    }
+
+    /// Returns true if the buffer is currently mapped in memory by OpenGL.
+    #[inline]
+    pub fn is_mapped(&self) -> bool {
+        self.persistent_mapping.is_some() || self.mapped.get()
+    }
+
+    /// Returns false if the content of the buffer is partially undefined because its upload has
+    /// been interrupted.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        !self.incomplete.get()
+    }
+
+    /// Marks the content of the buffer as partially undefined, or as entirely defined.
+    #[inline]
+    pub fn set_complete(&self, complete: bool) {
+        self.incomplete.set(!complete);
+    }
+
+    /// Queries `GL_BUFFER_SIZE` and compares it with the size of the buffer.
+    pub fn verify_against_gl(&self) -> Result<(), VerificationError> {
+        let mut ctxt = self.context.make_current();
+        let mut obtained: gl::types::GLint = 0;
+
+        unsafe {
+            if ctxt.capabilities.direct_state_access {
+                ctxt.gl.GetNamedBufferParameteriv(self.id, gl::BUFFER_SIZE, &mut obtained);
+
+            } else if ctxt.extensions.gl_ext_direct_state_access {
+                ctxt.gl.GetNamedBufferParameterivEXT(self.id, gl::BUFFER_SIZE, &mut obtained);
+
+            } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                      ctxt.version >= &Version(Api::GlEs, 2, 0)
+            {
+                let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
+                ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_SIZE, &mut obtained);
+
+            } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+                let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
+                ctxt.gl.GetBufferParameterivARB(bind, gl::BUFFER_SIZE, &mut obtained);
+
+            } else {
+                return Err(VerificationError::NotSupported);
+            }
+        }
+
+        // zero-sized buffers are created with a size of one byte, see `create_buffer`
+        let expected = if self.size == 0 { 1 } else { self.size };
+
+        if obtained as usize != expected {
+            return Err(VerificationError::SizeMismatch {
+                expected: expected,
+                obtained: obtained as usize,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the buffer is persistently mapped with `GL_MAP_COHERENT_BIT`, in which
+    /// case modifications don't need to be flushed.
+    #[inline]
+    fn is_coherent(&self) -> bool {
+        self.persistent_mapping.is_some() && self.creation_mode == BufferMode::PersistentCoherent
+    }
+
     /// Changes the type of the buffer. Returns `Err` if this is forbidden.
     pub fn set_type(mut self, ty: BufferType) -> Result<Alloc, Alloc> {
         // FIXME: return Err for GLES2
@@ -249,7 +593,7 @@ impl Alloc {
     }
 
     /// Makes sure that the buffer is binded to the `GL_QUERY_BUFFER` and calls
-    /// `glMemoryBarrier(GL_PIXEL_BUFFER_BARRIER_BIT)` if necessary.
+    /// `glMemoryBarrier(GL_QUERY_BUFFER_BARRIER_BIT)` if necessary.
     pub fn prepare_and_bind_for_query(&self, mut ctxt: &mut CommandContext) {
         assert!(ctxt.version >= &Version(Api::Gl, 4, 4) ||
                 ctxt.extensions.gl_arb_query_buffer_object ||
@@ -258,7 +602,7 @@ impl Alloc {
         self.assert_unmapped(ctxt);
         self.assert_not_transform_feedback(ctxt);
 
-        if self.latest_shader_write.get() >= ctxt.state.latest_memory_barrier_pixel_buffer {
+        if self.latest_shader_write.get() >= ctxt.state.latest_memory_barrier_query_buffer {
             unsafe { ctxt.gl.MemoryBarrier(gl::QUERY_BUFFER_BARRIER_BIT); }
             ctxt.state.latest_memory_barrier_query_buffer = ctxt.state.next_draw_call_id;
         }
@@ -376,7 +720,8 @@ impl Alloc {
 
     /// Uploads data in the buffer.
     ///
-    /// The data must fit inside the buffer.
+    /// The data must fit inside the buffer. Returns an error if the buffer is immutable and the
+    /// temporary buffer used for the upload couldn't be created.
     ///
     /// # Panic
     ///
@@ -388,12 +733,19 @@ impl Alloc {
     /// synchronization.
     ///
     pub unsafe fn upload<D: ?Sized>(&self, offset_bytes: usize, data: &D)
-                                    where D: Content
+                                    -> Result<(), WriteError> where D: Content
     {
         assert!(offset_bytes + mem::size_of_val(data) <= self.size);
 
+        self.context.get_frame_statistics_counters().update(|s| {
+            s.buffer_uploads += 1;
+            s.buffer_bytes_uploaded += mem::size_of_val(data);
+        });
+
         if self.persistent_mapping.is_some() {
-            let mapping = Mapping { mapping: self.map_shared(offset_bytes .. offset_bytes + mem::size_of_val(data), false, true) };
+            // mapping a persistent-mapped buffer can't fail
+            let range = offset_bytes .. offset_bytes + mem::size_of_val(data);
+            let mapping = Mapping { mapping: self.map_shared(range, false, true).unwrap() };
             ptr::copy_nonoverlapping(data.to_void_ptr() as *const u8, <D as Content>::to_void_ptr(&mapping) as *mut u8, mem::size_of_val(data));
 
         } else if self.immutable {
@@ -403,12 +755,21 @@ impl Alloc {
             self.assert_unmapped(&mut ctxt);
             self.assert_not_transform_feedback(&mut ctxt);
 
-            let (tmp_buffer, _, _, _) = create_buffer(&mut ctxt, mem::size_of_val(data), Some(data),
-                                                      BufferType::CopyReadBuffer,
-                                                      BufferMode::Dynamic).unwrap();
-            copy_buffer(&mut ctxt, tmp_buffer, 0, self.id, offset_bytes, mem::size_of_val(data)).unwrap();
+            let tmp_buffer = match create_buffer(&mut ctxt, mem::size_of_val(data), Some(data),
+                                                 BufferType::CopyReadBuffer, BufferMode::Dynamic)
+            {
+                Ok((tmp_buffer, _, _, _)) => tmp_buffer,
+                Err(err) => return Err(WriteError::TemporaryBufferCreation(err)),
+            };
+
+            let result = copy_buffer(&mut ctxt, tmp_buffer, 0, self.id, offset_bytes,
+                                     mem::size_of_val(data));
             destroy_buffer(&mut ctxt, tmp_buffer);
 
+            if let Err(CopyError::NotSupported) = result {
+                return Err(WriteError::CopyNotSupported);
+            }
+
         } else {
             assert!(offset_bytes < self.size);
 
@@ -420,13 +781,14 @@ impl Alloc {
             self.assert_unmapped(&mut ctxt);
             self.assert_not_transform_feedback(&mut ctxt);
 
-            if invalidate_all && (ctxt.version >= &Version(Api::Gl, 4, 3) ||
+            if invalidate_all && !ctxt.workarounds.no_buffer_invalidation &&
+               (ctxt.version >= &Version(Api::Gl, 4, 3) ||
                 ctxt.extensions.gl_arb_invalidate_subdata)
             {
                 ctxt.gl.InvalidateBufferData(self.id);
             }
 
-            if ctxt.version >= &Version(Api::Gl, 4, 5) {
+            if ctxt.capabilities.direct_state_access {
                 ctxt.gl.NamedBufferSubData(self.id, offset_bytes as gl::types::GLintptr,
                                            mem::size_of_val(data) as gl::types::GLsizeiptr,
                                            data.to_void_ptr() as *const libc::c_void)
@@ -439,13 +801,13 @@ impl Alloc {
             } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                 ctxt.version >= &Version(Api::GlEs, 2, 0)
             {
-                let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
                 ctxt.gl.BufferSubData(bind, offset_bytes as gl::types::GLintptr,
                                       mem::size_of_val(data) as gl::types::GLsizeiptr,
                                       data.to_void_ptr() as *const libc::c_void);
 
             } else if ctxt.extensions.gl_arb_vertex_buffer_object {
-                let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
                 ctxt.gl.BufferSubDataARB(bind, offset_bytes as gl::types::GLintptr,
                                          mem::size_of_val(data) as gl::types::GLsizeiptr,
                                          data.to_void_ptr() as *const libc::c_void);
@@ -454,6 +816,74 @@ impl Alloc {
                 unreachable!();
             }
         }
+
+        Ok(())
+    }
+
+    /// Fills a part of the buffer with copies of `value`.
+    ///
+    /// `range` is in bytes. Uses `glClearBufferSubData` if supported, and uploads the data from
+    /// the CPU otherwise.
+    ///
+    /// # Unsafety
+    ///
+    /// If the buffer uses persistent mapping, the caller of this function must handle
+    /// synchronization and create a sync fence *after* this operation.
+    ///
+    /// # Panic
+    ///
+    /// Panics if out of range.
+    ///
+    pub unsafe fn clear(&self, range: Range<usize>, value: &[u8]) -> Result<(), ClearError> {
+        assert!(range.end >= range.start);
+        assert!(range.end <= self.size);
+
+        let (internal_format, format, ty) = match value.len() {
+            1 => (gl::R8UI, gl::RED_INTEGER, gl::UNSIGNED_BYTE),
+            2 => (gl::R16UI, gl::RED_INTEGER, gl::UNSIGNED_SHORT),
+            4 => (gl::R32UI, gl::RED_INTEGER, gl::UNSIGNED_INT),
+            8 => (gl::RG32UI, gl::RG_INTEGER, gl::UNSIGNED_INT),
+            16 => (gl::RGBA32UI, gl::RGBA_INTEGER, gl::UNSIGNED_INT),
+            _ => return Err(ClearError::InvalidValueSize)
+        };
+
+        if range.start % value.len() != 0 || range.end % value.len() != 0 {
+            return Err(ClearError::RangeNotAligned);
+        }
+
+        if range.start == range.end {
+            return Ok(());
+        }
+
+        if self.context.get_version() >= &Version(Api::Gl, 4, 3) ||
+           self.context.get_extensions().gl_arb_clear_buffer_object
+        {
+            let mut ctxt = self.context.make_current();
+            self.barrier_for_buffer_update(&mut ctxt);
+            self.assert_unmapped(&mut ctxt);
+            self.assert_not_transform_feedback(&mut ctxt);
+
+            if ctxt.capabilities.direct_state_access {
+                ctxt.gl.ClearNamedBufferSubData(self.id, internal_format,
+                                                range.start as gl::types::GLintptr,
+                                                (range.end - range.start) as gl::types::GLsizeiptr,
+                                                format, ty, value.as_ptr() as *const libc::c_void);
+
+            } else {
+                let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
+                ctxt.gl.ClearBufferSubData(bind, internal_format,
+                                           range.start as gl::types::GLintptr,
+                                           (range.end - range.start) as gl::types::GLsizeiptr,
+                                           format, ty, value.as_ptr() as *const libc::c_void);
+            }
+
+        } else {
+            let data = value.iter().cloned().cycle().take(range.end - range.start)
+                            .collect::<Vec<u8>>();
+            try!(self.upload(range.start, &data[..]).map_err(ClearError::Upload));
+        }
+
+        Ok(())
     }
 
     /// Invalidates the content of the buffer. The data becomes undefined.
@@ -473,7 +903,7 @@ impl Alloc {
         self.assert_unmapped(&mut ctxt);
         self.assert_not_transform_feedback(&mut ctxt);
 
-        if self.persistent_mapping.is_none() &&
+        if self.persistent_mapping.is_none() && !ctxt.workarounds.no_buffer_invalidation &&
            (ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.extensions.gl_arb_invalidate_subdata)
         {
             if is_whole_buffer {
@@ -487,21 +917,28 @@ impl Alloc {
             if is_whole_buffer {
                 let flags = match self.creation_mode {
                     BufferMode::Default | BufferMode::Immutable => gl::STATIC_DRAW,
-                    BufferMode::Persistent | BufferMode::Dynamic => gl::DYNAMIC_DRAW,
+                    BufferMode::Persistent | BufferMode::PersistentCoherent |
+                    BufferMode::Dynamic => gl::DYNAMIC_DRAW,
                 };
 
-                if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                if ctxt.capabilities.direct_state_access {
+                    unsafe {
+                        ctxt.gl.NamedBufferData(self.id, size as gl::types::GLsizeiptr,
+                                                ptr::null(), flags);
+                    }
+
+                } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                     ctxt.version >= &Version(Api::GlEs, 2, 0)
                 {
                     unsafe {
-                        let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                        let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
                         ctxt.gl.BufferData(bind, size as gl::types::GLsizeiptr,
                                            ptr::null(), flags);
                     }
 
                 } else if ctxt.extensions.gl_arb_vertex_buffer_object {
                     unsafe {
-                        let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                        let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
                         ctxt.gl.BufferDataARB(bind, size as gl::types::GLsizeiptr,
                                               ptr::null(), flags);
                     }
@@ -530,6 +967,9 @@ impl Alloc {
     ///
     /// Contrary to `map_mut`, this function only requires a `&self`.
     ///
+    /// Persistent-mapped buffers can always be mapped. For the other buffers, returns an error
+    /// if mapping is not supported or if the backend fails to map the buffer.
+    ///
     /// # Panic
     ///
     /// Panicks if the `bytes_range` is not aligned to a mappable slice.
@@ -543,7 +983,7 @@ impl Alloc {
     /// `false` for `write`, you **must not** write the returned buffer.
     ///
     unsafe fn map_shared<D: ?Sized>(&self, bytes_range: Range<usize>, read: bool, write: bool)
-                                    -> MappingImpl<D> where D: Content
+                                    -> Result<MappingImpl<D>, MapError> where D: Content
     {
         if let Some(existing_mapping) = self.persistent_mapping.clone() {
             // TODO: optimize so that it's not always necessary to make the context current
@@ -554,12 +994,12 @@ impl Alloc {
             let data = Content::ref_from_ptr(data as *mut (),
                                              bytes_range.end - bytes_range.start).unwrap();
 
-            MappingImpl::PersistentMapping {
+            Ok(MappingImpl::PersistentMapping {
                 buffer: self,
                 offset_bytes: bytes_range.start,
                 data: data,
                 needs_flushing: write,
-            }
+            })
 
         } else {
             let size_bytes = bytes_range.end - bytes_range.start;
@@ -569,11 +1009,12 @@ impl Alloc {
             // we have to construct a temporary buffer that we will map in memory
             // then after the Mapping is destroyed, we will copy from the temporary buffer to the
             // real one
-            let temporary_buffer = {
-                let (temporary_buffer, _, _, _) = create_buffer::<D>(&mut ctxt, size_bytes,
-                                                                     None, BufferType::CopyWriteBuffer,
-                                                                     BufferMode::Dynamic).unwrap();
-                temporary_buffer
+            let temporary_buffer = match create_buffer::<D>(&mut ctxt, size_bytes, None,
+                                                            BufferType::CopyWriteBuffer,
+                                                            BufferMode::Dynamic)
+            {
+                Ok((temporary_buffer, _, _, _)) => temporary_buffer,
+                Err(err) => return Err(MapError::TemporaryBufferCreation(err)),
             };
 
             let ptr = {
@@ -581,12 +1022,25 @@ impl Alloc {
                 self.assert_not_transform_feedback(&mut ctxt);
 
                 if read {
-                    copy_buffer(&mut ctxt, self.id, bytes_range.start,
-                                temporary_buffer, 0, size_bytes).unwrap();
+                    if let Err(CopyError::NotSupported) = copy_buffer(&mut ctxt, self.id,
+                                                                      bytes_range.start,
+                                                                      temporary_buffer, 0,
+                                                                      size_bytes)
+                    {
+                        destroy_buffer(&mut ctxt, temporary_buffer);
+                        return Err(MapError::NotSupported);
+                    }
                 }
 
-                map_buffer(&mut ctxt, temporary_buffer, self.ty, 0 .. size_bytes, true, true)
-                                    .expect("Buffer mapping is not supported by the backend")
+                match map_buffer(&mut ctxt, temporary_buffer, self.ty, 0 .. size_bytes,
+                                 true, true)
+                {
+                    Ok(ptr) => ptr,
+                    Err(err) => {
+                        destroy_buffer(&mut ctxt, temporary_buffer);
+                        return Err(err);
+                    }
+                }
             };
 
             let data = match Content::ref_from_ptr(ptr, bytes_range.end - bytes_range.start) {
@@ -597,13 +1051,13 @@ impl Alloc {
                 }
             };
 
-            MappingImpl::TemporaryBuffer {
+            Ok(MappingImpl::TemporaryBuffer {
                 original_buffer: self,
                 original_buffer_offset: bytes_range.start,
                 temporary_buffer: temporary_buffer,
                 temporary_buffer_data: data,
                 needs_flushing: write,
-            }
+            })
         }
     }
 
@@ -631,8 +1085,15 @@ impl Alloc {
     /// `false` for `write`, you **must not** write the returned buffer.
     ///
     unsafe fn map_impl<D: ?Sized>(&mut self, bytes_range: Range<usize>, read: bool, write: bool)
-                                  -> MappingImpl<D> where D: Content
+                                  -> Result<MappingImpl<D>, MapError> where D: Content
     {
+        self.context.get_frame_statistics_counters().update(|s| s.buffer_maps += 1);
+
+        // the content of the buffer is lost with the context, even if the backend could map it
+        if self.context.make_current().state.lost_context {
+            return Err(MapError::ContextLost);
+        }
+
         if self.persistent_mapping.is_some() || self.immutable {
             self.map_shared(bytes_range, read, write)
 
@@ -644,9 +1105,8 @@ impl Alloc {
                     self.assert_unmapped(&mut ctxt);
                     self.assert_not_transform_feedback(&mut ctxt);
                     self.barrier_for_buffer_update(&mut ctxt);
-                    let ptr = map_buffer(&mut ctxt, self.id, self.ty, bytes_range.clone(),
-                                         read, write)
-                                        .expect("Buffer mapping is not supported by the backend");
+                    let ptr = try!(map_buffer(&mut ctxt, self.id, self.ty, bytes_range.clone(),
+                                              read, write));
                     self.mapped.set(true);
                     ptr
                 };
@@ -660,11 +1120,11 @@ impl Alloc {
                 }
             };
 
-            MappingImpl::RegularMapping {
+            Ok(MappingImpl::RegularMapping {
                 buffer: self,
                 data: data,
                 needs_flushing: write,
-            }
+            })
         }
     }
 
@@ -681,11 +1141,9 @@ impl Alloc {
     ///
     #[inline]
     pub unsafe fn map<D: ?Sized>(&mut self, bytes_range: Range<usize>)
-                                 -> Mapping<D> where D: Content
+                                 -> Result<Mapping<D>, MapError> where D: Content
     {
-        Mapping {
-            mapping: self.map_impl(bytes_range, true, true)
-        }
+        self.map_impl(bytes_range, true, true).map(|mapping| Mapping { mapping: mapping })
     }
 
     /// Returns a read-only mapping in memory of the content of the buffer.
@@ -701,11 +1159,9 @@ impl Alloc {
     ///
     #[inline]
     pub unsafe fn map_read<D: ?Sized>(&mut self, bytes_range: Range<usize>)
-                                      -> ReadMapping<D> where D: Content
+                                      -> Result<ReadMapping<D>, MapError> where D: Content
     {
-        ReadMapping {
-            mapping: self.map_impl(bytes_range, true, false)
-        }
+        self.map_impl(bytes_range, true, false).map(|mapping| ReadMapping { mapping: mapping })
     }
 
     /// Returns a write-only mapping in memory of the content of the buffer.
@@ -721,11 +1177,9 @@ impl Alloc {
     ///
     #[inline]
     pub unsafe fn map_write<D: ?Sized>(&mut self, bytes_range: Range<usize>)
-                                       -> WriteMapping<D> where D: Content
+                                       -> Result<WriteMapping<D>, MapError> where D: Content
     {
-        WriteMapping {
-            mapping: self.map_impl(bytes_range, false, true)
-        }
+        self.map_impl(bytes_range, false, true).map(|mapping| WriteMapping { mapping: mapping })
     }
 
     /// Reads the content of the buffer.
@@ -744,9 +1198,11 @@ impl Alloc {
                                   where D: Content
     {
         let size_to_read = range.end - range.start;
+        self.context.get_frame_statistics_counters().update(|s| s.buffer_bytes_read += size_to_read);
 
         if self.persistent_mapping.is_some() {
-            let mapping = ReadMapping { mapping: self.map_shared(range, true, false) };
+            // mapping a persistent-mapped buffer can't fail
+            let mapping = ReadMapping { mapping: self.map_shared(range, true, false).unwrap() };
             <D as Content>::read(size_to_read, |output| {
                 ptr::copy_nonoverlapping(<D as Content>::to_void_ptr(&mapping) as *const u8, output as *mut D as *mut u8, size_to_read);
                 Ok(())
@@ -762,20 +1218,21 @@ impl Alloc {
             self.assert_unmapped(&mut ctxt);
             self.barrier_for_buffer_update(&mut ctxt);
 
-            <D as Content>::read(size_to_read, |output| {
-                if ctxt.version >= &Version(Api::Gl, 4, 5) {
+            let wait = ctxt.frame_statistics.start_wait();
+            let result = <D as Content>::read(size_to_read, |output| {
+                if ctxt.capabilities.direct_state_access {
                     ctxt.gl.GetNamedBufferSubData(self.id, range.start as gl::types::GLintptr,
                                                   size_to_read as gl::types::GLsizeiptr,
                                                   output as *mut _ as *mut libc::c_void);
 
                 } else if ctxt.version >= &Version(Api::Gl, 1, 5) {
-                    let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                    let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
                     ctxt.gl.GetBufferSubData(bind, range.start as gl::types::GLintptr,
                                              size_to_read as gl::types::GLsizeiptr,
                                              output as *mut _ as *mut libc::c_void);
 
                 } else if ctxt.extensions.gl_arb_vertex_buffer_object {
-                    let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                    let bind = bind_buffer_for_edit(&mut ctxt, self.id, self.ty);
                     ctxt.gl.GetBufferSubDataARB(bind, range.start as gl::types::GLintptr,
                                                 size_to_read as gl::types::GLsizeiptr,
                                                 output as *mut _ as *mut libc::c_void);
@@ -788,10 +1245,97 @@ impl Alloc {
                 }
 
                 Ok(())
-            })
+            });
+            ctxt.frame_statistics.end_wait(wait, WaitKind::Read);
+            result
         }
     }
 
+    /// Reads a part of the buffer chunk by chunk and writes it to `writer`.
+    ///
+    /// Contrary to `read`, this function never holds more than `TRANSFER_CHUNK_SIZE` bytes
+    /// in memory at once.
+    ///
+    /// # Panic
+    ///
+    /// Panics if out of range.
+    ///
+    /// # Unsafety
+    ///
+    /// If the buffer uses persistent mapping, the caller of this function must handle
+    /// synchronization.
+    ///
+    pub unsafe fn read_to_writer<W: ?Sized>(&self, range: Range<usize>, writer: &mut W)
+                                            -> io::Result<()> where W: io::Write
+    {
+        assert!(range.start <= range.end && range.end <= self.size);
+
+        // ES doesn't have `glGetBufferSubData`, but we can map the buffer
+        let use_mapping = self.persistent_mapping.is_some() || {
+            let ctxt = self.context.make_current();
+            !(ctxt.version >= &Version(Api::Gl, 1, 5) ||
+              ctxt.extensions.gl_arb_vertex_buffer_object) &&
+            is_buffer_mapping_supported(&ctxt)
+        };
+
+        let mut start = range.start;
+        while start < range.end {
+            let end = cmp::min(start + TRANSFER_CHUNK_SIZE, range.end);
+
+            if use_mapping {
+                self.context.get_frame_statistics_counters()
+                            .update(|s| s.buffer_bytes_read += end - start);
+                let mapping = match self.map_shared(start .. end, true, false) {
+                    Ok(mapping) => ReadMapping::<[u8]> { mapping: mapping },
+                    Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err)),
+                };
+                try!(writer.write_all(&mapping));
+            } else {
+                match self.read::<[u8]>(start .. end) {
+                    Ok(data) => try!(writer.write_all(&data)),
+                    Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err)),
+                }
+            }
+
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    /// Fills a part of the buffer with data from `reader`, chunk by chunk.
+    ///
+    /// Contrary to `upload`, this function never holds more than `TRANSFER_CHUNK_SIZE` bytes
+    /// in memory at once. Returns an error if the reader doesn't contain enough data.
+    ///
+    /// # Panic
+    ///
+    /// Panics if out of range.
+    ///
+    /// # Unsafety
+    ///
+    /// If the buffer uses persistent mapping, the caller of this function must handle
+    /// synchronization.
+    ///
+    pub unsafe fn upload_from_reader<R: ?Sized>(&self, range: Range<usize>, reader: &mut R)
+                                                -> io::Result<()> where R: io::Read
+    {
+        assert!(range.start <= range.end && range.end <= self.size);
+
+        let mut chunk = vec![0u8; cmp::min(TRANSFER_CHUNK_SIZE, range.end - range.start)];
+
+        let mut start = range.start;
+        while start < range.end {
+            let len = cmp::min(TRANSFER_CHUNK_SIZE, range.end - start);
+            try!(read_exact(reader, &mut chunk[.. len]));
+            try!(self.upload(start, &chunk[.. len])
+                     .map_err(|err| io::Error::new(io::ErrorKind::Other, err)));
+            start += len;
+        }
+
+        Ok(())
+    }
+
     /// Copies data from this buffer to another one.
     ///
     /// With persistent-mapped buffers you must create a sync fence *after* this operation.
@@ -817,6 +1361,40 @@ impl Alloc {
                         range.end - range.start)
         }
     }
+
+    /// Builds a new buffer with the same type, mode and content as this one.
+    ///
+    /// The content is copied with `glCopyBufferSubData` if possible, and read back then
+    /// uploaded otherwise.
+    ///
+    /// With persistent-mapped buffers you must wait for the fences of this buffer before calling
+    /// this function, and create a sync fence on the new buffer *after* this operation.
+    pub fn duplicate<F>(&self, facade: &F) -> Result<Alloc, BufferCreationError>
+                        where F: Facade
+    {
+        let new = try!(Alloc::empty(facade, self.ty, self.size, self.creation_mode));
+        new.set_complete(self.is_complete());
+
+        if self.size == 0 {
+            return Ok(new);
+        }
+
+        match self.copy_to(0 .. self.size, &new, 0) {
+            Ok(()) => Ok(new),
+            Err(CopyError::NotSupported) => {
+                let data = match unsafe { self.read::<[u8]>(0 .. self.size) } {
+                    Ok(data) => data,
+                    Err(_) => return Err(BufferCreationError::CopyNotSupported),
+                };
+
+                match unsafe { new.upload(0, &data[..]) } {
+                    Ok(()) => Ok(new),
+                    Err(WriteError::TemporaryBufferCreation(err)) => Err(err),
+                    Err(_) => Err(BufferCreationError::CopyNotSupported),
+                }
+            },
+        }
+    }
 }
 
 impl fmt::Debug for Alloc {
@@ -828,15 +1406,67 @@ impl fmt::Debug for Alloc {
 impl Drop for Alloc {
     fn drop(&mut self) {
         unsafe {
-            let mut ctxt = self.context.make_current();
+            let mut ctxt = match self.context.make_current_for_drop() {
+                Some(ctxt) => ctxt,
+                None => return,
+            };
             self.assert_unmapped(&mut ctxt);
             self.assert_not_transform_feedback(&mut ctxt);
-            VertexAttributesSystem::purge_buffer(&mut ctxt, self.id);
-            destroy_buffer(&mut ctxt, self.id);
         }
+
+        destroy_alloc(&self.context, self.id, self.size);
     }
 }
 
+/// Content of an `Alloc` without the reference to its context, used to send buffers to other
+/// threads. Doesn't destroy the OpenGL buffer when dropped.
+#[doc(hidden)]
+pub struct DetachedAlloc {
+    id: gl::types::GLuint,
+    ty: BufferType,
+    size: usize,
+    persistent_mapping: Option<*mut libc::c_void>,
+    immutable: bool,
+    creation_mode: BufferMode,
+    created_with_buffer_storage: bool,
+    latest_shader_write: u64,
+    incomplete: bool,
+}
+
+// the persistent mapping is only accessed once the buffer has been attached again in the thread
+// of its context
+unsafe impl Send for DetachedAlloc {}
+
+impl DetachedAlloc {
+    /// Destroys the OpenGL buffer. Must be called in the thread of the context.
+    #[inline]
+    pub fn destroy(self, context: &Context) {
+        destroy_alloc(context, self.id, self.size);
+    }
+}
+
+/// Destroys a buffer and removes it from the caches of all the contexts that share it.
+fn destroy_alloc(context: &Context, id: gl::types::GLuint, size: usize) {
+    // the counters are updated even if the buffer is leaked, so that they stay in sync with
+    // the buffers that are alive from the user's point of view
+    context.get_memory_usage_counters().remove_buffer(size);
+
+    unsafe {
+        let mut ctxt = match context.make_current_for_drop() {
+            Some(ctxt) => ctxt,
+            None => return,
+        };
+        VertexAttributesSystem::purge_buffer(&mut ctxt, id);
+        destroy_buffer(&mut ctxt, id);
+        ctxt.live_objects.remove(ObjectKind::Buffer, id as usize);
+    }
+
+    context.for_each_shared_context(|ctxt| {
+        VertexAttributesSystem::purge_buffer(ctxt, id);
+        forget_buffer_bindings(ctxt, id);
+    });
+}
+
 impl GlObject for Alloc {
     type Id = gl::types::GLuint;
 
@@ -878,7 +1508,7 @@ impl<'a, D: ?Sized> Drop for MappingImpl<'a, D> {
             &mut MappingImpl::PersistentMapping { buffer, offset_bytes, data, needs_flushing } => {
                 let mut ctxt = buffer.context.make_current();
                 unsafe {
-                    if needs_flushing {
+                    if needs_flushing && !buffer.is_coherent() {
                         flush_range(&mut ctxt, buffer.id, buffer.ty,
                                     offset_bytes .. offset_bytes + mem::size_of_val(&*data));
                     }
@@ -924,11 +1554,146 @@ impl<'a, D: ?Sized> Drop for MappingImpl<'a, D> {
     }
 }
 
+impl<'a, D: ?Sized> MappingImpl<'a, D> {
+    /// Flushes a range of the mapping, in bytes relative to the start of the mapping, and
+    /// disables the automatic flush of the whole mapping on drop.
+    fn flush_range(&mut self, offset: usize, len: usize) {
+        match self {
+            &mut MappingImpl::PersistentMapping { buffer, offset_bytes, data,
+                                                  ref mut needs_flushing } =>
+            {
+                assert!(offset + len <= unsafe { mem::size_of_val(&*data) });
+                *needs_flushing = false;
+
+                if !buffer.is_coherent() && len != 0 {
+                    let mut ctxt = buffer.context.make_current();
+                    unsafe {
+                        flush_range(&mut ctxt, buffer.id, buffer.ty,
+                                    offset_bytes + offset .. offset_bytes + offset + len);
+                    }
+                }
+            },
+
+            // the content of the temporary buffer is always copied entirely when the mapping
+            // is destroyed
+            &mut MappingImpl::TemporaryBuffer { temporary_buffer_data, .. } => {
+                assert!(offset + len <= unsafe { mem::size_of_val(&*temporary_buffer_data) });
+            },
+
+            &mut MappingImpl::RegularMapping { ref buffer, data, ref mut needs_flushing } => {
+                assert!(offset + len <= unsafe { mem::size_of_val(&*data) });
+                *needs_flushing = false;
+
+                if len != 0 {
+                    let mut ctxt = buffer.context.make_current();
+                    unsafe {
+                        flush_range(&mut ctxt, buffer.id, buffer.ty, offset .. offset + len);
+                    }
+                }
+            },
+        }
+    }
+}
+
 /// A mapping of a buffer for reading and writing.
+///
+/// When the mapping is destroyed, the whole mapped range is flushed so that the GPU sees the
+/// modifications, unless `flush_range` has been called.
+///
+/// Reading from the mapping of a persistent buffer can be very slow. If you only need to write,
+/// use a `WriteMapping` instead.
 pub struct Mapping<'b, D: ?Sized> where D: Content {
     mapping: MappingImpl<'b, D>,
 }
 
+impl<'a, D: ?Sized> Mapping<'a, D> where D: Content {
+    /// Makes the modifications of a range of the mapping visible to the GPU.
+    ///
+    /// `offset` and `len` are in bytes and relative to the start of the mapping. Once you have
+    /// called this function, the mapping is no longer flushed automatically when it is destroyed
+    /// and you are responsible for flushing all the ranges that you have modified.
+    ///
+    /// This is a no-op for buffers created with `BufferMode::PersistentCoherent`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the range is out of the bounds of the mapping.
+    #[inline]
+    pub fn flush_range(&mut self, offset: usize, len: usize) {
+        self.mapping.flush_range(offset, len)
+    }
+
+    /// Returns the content of the mapping as bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        let data: &D = self;
+        let len = mem::size_of_val(data);
+        if len == 0 { return &[]; }
+        unsafe { slice::from_raw_parts(data.to_void_ptr() as *const u8, len) }
+    }
+
+    /// Returns the content of the mapping as bytes that can be modified.
+    ///
+    /// This is useful to copy raw data, for example a decoded asset, into a buffer.
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        let data: &mut D = self;
+        let len = mem::size_of_val(data);
+        if len == 0 { return &mut []; }
+        unsafe { slice::from_raw_parts_mut(data.to_void_ptr() as *mut u8, len) }
+    }
+
+    /// Returns the content of the mapping as a slice of another type.
+    ///
+    /// The slice borrows the mapping, so it can't outlive it or be used at the same time as
+    /// another view of the mapping.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the size of the mapping is not a multiple of the size of `U`, if `U` has a size
+    /// of zero, or if the mapping is not correctly aligned for `U`.
+    ///
+    /// # Unsafety
+    ///
+    /// Any combination of bits must be a valid value of `U`, which is the case for example
+    /// for integers and floats.
+    pub unsafe fn reinterpret<U>(&mut self) -> &mut [U] where U: Copy {
+        let bytes = self.as_bytes_mut();
+
+        assert!(mem::size_of::<U>() != 0);
+        assert!(bytes.len() % mem::size_of::<U>() == 0);
+        assert!(bytes.as_ptr() as usize % mem::align_of::<U>() == 0);
+
+        slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut U, bytes.len() / mem::size_of::<U>())
+    }
+}
+
+impl<'a, D> Mapping<'a, [D]> where [D]: Content, D: Copy {
+    /// Returns the number of elements in the mapping.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let data: &[D] = self;
+        data.len()
+    }
+
+    /// Divides the mapping into two slices that can be modified independently.
+    ///
+    /// The first slice contains the elements `[0, mid)` and the second one the elements
+    /// `[mid, len)`. The two slices don't overlap and can be sent to other threads, for
+    /// example with scoped threads, as long as `D` is `Send`. The mapping itself stays in the
+    /// current thread and must outlive the slices. Once both slices are dropped, the mapping
+    /// can be used again and the modifications are flushed as usual when it is destroyed.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `mid > len`.
+    #[inline]
+    pub fn split_at_mut(&mut self, mid: usize) -> (&mut [D], &mut [D]) {
+        let data: &mut [D] = self;
+        data.split_at_mut(mid)
+    }
+}
+
 impl<'a, D: ?Sized> Deref for Mapping<'a, D> where D: Content {
     type Target = D;
 
@@ -996,10 +1761,37 @@ impl<'a, D: ?Sized> Deref for ReadMapping<'a, D> where D: Content {
 }
 
 /// A mapping of a buffer for write only.
+///
+/// When the mapping is destroyed, the whole mapped range is flushed so that the GPU sees the
+/// modifications, unless `flush_range` has been called.
+///
+/// Contrary to `Mapping`, this type doesn't give access to the content of the buffer. The memory
+/// of persistent mappings is often write-combined, which means that reading from it, even
+/// through an innocent `+=`, is extremely slow. Prefer this type whenever you don't need to
+/// read the content.
 pub struct WriteMapping<'b, D: ?Sized> where D: Content {
     mapping: MappingImpl<'b, D>,
 }
 
+impl<'a, D: ?Sized> WriteMapping<'a, D> where D: Content {
+    /// Makes the modifications of a range of the mapping visible to the GPU.
+    ///
+    /// See `Mapping::flush_range`.
+    #[inline]
+    pub fn flush_range(&mut self, offset: usize, len: usize) {
+        self.mapping.flush_range(offset, len)
+    }
+
+    /// Returns a pointer to the mapped memory.
+    ///
+    /// The pointer is valid as long as the mapping is alive. You should only write through it,
+    /// for the same reasons as the other methods of `WriteMapping`.
+    #[inline]
+    pub fn as_write_ptr(&mut self) -> *mut D {
+        self.get_slice() as *mut D
+    }
+}
+
 impl<'b, D: ?Sized> WriteMapping<'b, D> where D: Content {
     #[inline]
     fn get_slice(&mut self) -> &mut D {
@@ -1050,11 +1842,47 @@ impl<'b, D> WriteMapping<'b, [D]> where [D]: Content, D: Copy {
         let slice = self.get_slice();
         slice[index] = value;
     }
+
+    /// Changes the elements of the mapping starting at `offset` to the content of `data`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if out of range.
+    ///
+    #[inline]
+    pub fn set_range(&mut self, offset: usize, data: &[D]) {
+        self.copy_from_slice(offset, data);
+    }
+
+    /// Copies the content of `data` to the mapping, starting at element `offset`.
+    ///
+    /// This is done with a single memory copy and never reads from the mapping.
+    ///
+    /// # Panic
+    ///
+    /// Panics if out of range.
+    ///
+    #[inline]
+    pub fn copy_from_slice(&mut self, offset: usize, data: &[D]) {
+        let slice = &mut self.get_slice()[offset .. offset + data.len()];
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), slice.as_mut_ptr(), data.len()) };
+    }
+}
+
+/// Returns true if mapping a buffer in memory is supported by the backend.
+///
+/// Buffers that are persistently mapped can always be mapped, even if this function
+/// returns false.
+pub fn is_buffer_mapping_supported<C>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    ctxt.get_version() >= &Version(Api::Gl, 3, 0) ||
+    ctxt.get_version() >= &Version(Api::GlEs, 3, 0) ||
+    ctxt.get_extensions().gl_arb_map_buffer_range ||
+    (ctxt.get_extensions().gl_ext_map_buffer_range && ctxt.get_extensions().gl_oes_mapbuffer)
 }
 
 /// Returns true if reading from a buffer is supported by the backend.
 pub fn is_buffer_read_supported<C>(ctxt: &C) -> bool where C: CapabilitiesSource {
-    if ctxt.get_version() >= &Version(Api::Gl, 4, 5) {
+    if ctxt.get_capabilities().direct_state_access {
         true
 
     } else if ctxt.get_version() >= &Version(Api::Gl, 1, 5) {
@@ -1093,7 +1921,7 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
     // creating the id of the buffer
     let id = {
         let mut id: gl::types::GLuint = mem::uninitialized();
-        if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+        if ctxt.capabilities.direct_state_access {
             ctxt.gl.CreateBuffers(1, &mut id);
         } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
             ctxt.version >= &Version(Api::GlEs, 2, 0)
@@ -1127,7 +1955,8 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
 
     // the flags to use in the case where only `glBufferData` is supported
     let mutable_storage_flags = match mode {
-        BufferMode::Persistent | BufferMode::Dynamic => gl::DYNAMIC_DRAW,
+        BufferMode::Persistent | BufferMode::PersistentCoherent |
+        BufferMode::Dynamic => gl::DYNAMIC_DRAW,
         BufferMode::Default | BufferMode::Immutable => gl::STATIC_DRAW,
     };
 
@@ -1136,6 +1965,8 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
         BufferMode::Default => gl::DYNAMIC_STORAGE_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
         BufferMode::Dynamic => gl::DYNAMIC_STORAGE_BIT | gl::CLIENT_STORAGE_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
         BufferMode::Persistent => gl::MAP_PERSISTENT_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        BufferMode::PersistentCoherent => gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT |
+                                          gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
         BufferMode::Immutable => 0,
     };
 
@@ -1143,7 +1974,7 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
     // function calls
     let could_be_immutable = match mode {
         BufferMode::Default | BufferMode::Dynamic => false,
-        BufferMode::Immutable | BufferMode::Persistent => true,
+        BufferMode::Immutable | BufferMode::Persistent | BufferMode::PersistentCoherent => true,
     };
 
     // will store the actual size of the buffer so that we can compare it with the expected size
@@ -1156,7 +1987,7 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
     // whether the buffer was created with `glBufferStorage`
     let created_with_buffer_storage: bool;
 
-    if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+    if ctxt.capabilities.direct_state_access {
         ctxt.gl.NamedBufferStorage(id, size as gl::types::GLsizeiptr,
                                    data_ptr as *const libc::c_void,
                                    immutable_storage_flags);
@@ -1177,7 +2008,7 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
     } else if ctxt.version >= &Version(Api::Gl, 4, 4) ||
               ctxt.extensions.gl_arb_buffer_storage
     {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         ctxt.gl.BufferStorage(bind, size as gl::types::GLsizeiptr,
                               data_ptr as *const libc::c_void,
                               immutable_storage_flags);
@@ -1186,7 +2017,7 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
         created_with_buffer_storage = true;
 
     } else if ctxt.extensions.gl_ext_buffer_storage {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         ctxt.gl.BufferStorageEXT(bind, size as gl::types::GLsizeiptr,
                                  data_ptr as *const libc::c_void,
                                  immutable_storage_flags);
@@ -1197,7 +2028,7 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
     } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
         ctxt.version >= &Version(Api::GlEs, 2, 0)
     {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         ctxt.gl.BufferData(bind, size as gl::types::GLsizeiptr,
                            data_ptr as *const libc::c_void, mutable_storage_flags);
         ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_SIZE, &mut obtained_size);
@@ -1205,7 +2036,7 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
         created_with_buffer_storage = false;
 
     } else if ctxt.extensions.gl_arb_vertex_buffer_object {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         ctxt.gl.BufferDataARB(bind, size as gl::types::GLsizeiptr,
                               data_ptr as *const libc::c_void, mutable_storage_flags);
         ctxt.gl.GetBufferParameterivARB(bind, gl::BUFFER_SIZE, &mut obtained_size);
@@ -1230,20 +2061,26 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
         return Err(BufferCreationError::OutOfMemory);
     }
 
-    let persistent_mapping = if let BufferMode::Persistent = mode {
+    let persistent_mapping = if mode == BufferMode::Persistent ||
+                                mode == BufferMode::PersistentCoherent
+    {
         if immutable {
-            let ptr = if ctxt.version >= &Version(Api::Gl, 4, 5) {
-                ctxt.gl.MapNamedBufferRange(id, 0, size as gl::types::GLsizeiptr,
-                                            gl::MAP_READ_BIT | gl::MAP_WRITE_BIT |
-                                            gl::MAP_PERSISTENT_BIT | gl::MAP_FLUSH_EXPLICIT_BIT)
+            let access = if mode == BufferMode::PersistentCoherent {
+                gl::MAP_READ_BIT | gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT |
+                gl::MAP_COHERENT_BIT
+            } else {
+                gl::MAP_READ_BIT | gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT |
+                gl::MAP_FLUSH_EXPLICIT_BIT
+            };
+
+            let ptr = if ctxt.capabilities.direct_state_access {
+                ctxt.gl.MapNamedBufferRange(id, 0, size as gl::types::GLsizeiptr, access)
 
             } else if ctxt.version >= &Version(Api::Gl, 3, 0) ||
                       ctxt.extensions.gl_arb_map_buffer_range
             {
-                let bind = bind_buffer(&mut ctxt, id, ty);
-                ctxt.gl.MapBufferRange(bind, 0, size as gl::types::GLsizeiptr,
-                                       gl::MAP_READ_BIT | gl::MAP_WRITE_BIT |
-                                       gl::MAP_PERSISTENT_BIT | gl::MAP_FLUSH_EXPLICIT_BIT)
+                let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
+                ctxt.gl.MapBufferRange(bind, 0, size as gl::types::GLsizeiptr, access)
             } else {
                 unreachable!();
             };
@@ -1321,6 +2158,16 @@ fn is_buffer_type_supported(ctxt: &mut CommandContext, ty: BufferType) -> bool {
     }
 }
 
+/// Same as `bind_buffer`, but for the functions that bind a buffer only in order to modify or
+/// read it. Updates the `edit_binds` statistics.
+#[inline]
+unsafe fn bind_buffer_for_edit(ctxt: &mut CommandContext, id: gl::types::GLuint, ty: BufferType)
+                               -> gl::types::GLenum
+{
+    ctxt.frame_statistics.update(|s| s.edit_binds += 1);
+    bind_buffer(ctxt, id, ty)
+}
+
 /// Binds a buffer of the given type, and returns the GLenum of the bind point.
 /// `id` can be 0.
 ///
@@ -1485,7 +2332,7 @@ unsafe fn copy_buffer(ctxt: &mut CommandContext, source: gl::types::GLuint,
                       source_offset: usize, dest: gl::types::GLuint, dest_offset: usize,
                       size: usize) -> Result<(), CopyError>
 {
-    if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+    if ctxt.capabilities.direct_state_access {
         ctxt.gl.CopyNamedBufferSubData(source, dest, source_offset as gl::types::GLintptr,
                                        dest_offset as gl::types::GLintptr,
                                        size as gl::types::GLsizeiptr);
@@ -1524,16 +2371,16 @@ unsafe fn copy_buffer(ctxt: &mut CommandContext, source: gl::types::GLuint,
                 // if the source is not binded and the destination is binded to COPY_READ,
                 // we bind the source to COPY_WRITE instead, to avoid a state change
                 if ctxt.state.copy_read_buffer_binding == dest {
-                    bind_buffer(ctxt, source, BufferType::CopyWriteBuffer)
+                    bind_buffer_for_edit(ctxt, source, BufferType::CopyWriteBuffer)
                 } else {
-                    bind_buffer(ctxt, source, BufferType::CopyReadBuffer)
+                    bind_buffer_for_edit(ctxt, source, BufferType::CopyReadBuffer)
                 }
             }
         };
 
         let dest_bind_point = match find_bind_point(ctxt, dest) {
             Some(p) => p,
-            None => bind_buffer(ctxt, dest, BufferType::CopyWriteBuffer)
+            None => bind_buffer_for_edit(ctxt, dest, BufferType::CopyWriteBuffer)
         };
 
         if ctxt.version >= &Version(Api::Gl, 3, 1) || ctxt.version >= &Version(Api::GlEs, 3, 0)
@@ -1559,11 +2406,11 @@ unsafe fn copy_buffer(ctxt: &mut CommandContext, source: gl::types::GLuint,
     Ok(())
 }
 
-/// Destroys a buffer.
-unsafe fn destroy_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint) {
-    // FIXME: uncomment this and move it from Buffer's destructor
-    //self.context.vertex_array_objects.purge_buffer(&mut ctxt, id);
-
+/// Removes a buffer from the bind points of the state cache.
+///
+/// This must be called for each context that shares the buffer when it is destroyed, otherwise
+/// a new buffer with the same id could be considered as already binded.
+fn forget_buffer_bindings(ctxt: &mut CommandContext, id: gl::types::GLuint) {
     if ctxt.state.array_buffer_binding == id {
         ctxt.state.array_buffer_binding = 0;
     }
@@ -1636,6 +2483,14 @@ unsafe fn destroy_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint) {
             point.buffer = 0;
         }
     }
+}
+
+/// Destroys a buffer.
+unsafe fn destroy_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    // FIXME: uncomment this and move it from Buffer's destructor
+    //self.context.vertex_array_objects.purge_buffer(&mut ctxt, id);
+
+    forget_buffer_bindings(ctxt, id);
 
     if ctxt.version >= &Version(Api::Gl, 1, 5) ||
         ctxt.version >= &Version(Api::GlEs, 2, 0)
@@ -1652,7 +2507,7 @@ unsafe fn destroy_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint) {
 unsafe fn flush_range(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty: BufferType,
                       range: Range<usize>)
 {
-    if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+    if ctxt.capabilities.direct_state_access {
         ctxt.gl.FlushMappedNamedBufferRange(id, range.start as gl::types::GLintptr,
                                             (range.end - range.start) as gl::types::GLsizeiptr);
 
@@ -1664,10 +2519,15 @@ unsafe fn flush_range(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty:
               ctxt.version >= &Version(Api::GlEs, 3, 0) ||
               ctxt.extensions.gl_arb_map_buffer_range
     {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         ctxt.gl.FlushMappedBufferRange(bind, range.start as gl::types::GLintptr,
                                        (range.end - range.start) as gl::types::GLsizeiptr)
 
+    } else if ctxt.extensions.gl_ext_map_buffer_range {
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
+        ctxt.gl.FlushMappedBufferRangeEXT(bind, range.start as gl::types::GLintptr,
+                                          (range.end - range.start) as gl::types::GLsizeiptr)
+
     } else {
         unreachable!();
     }
@@ -1676,8 +2536,41 @@ unsafe fn flush_range(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty:
 /// Maps a range of a buffer.
 ///
 /// *Warning*: always passes `GL_MAP_FLUSH_EXPLICIT_BIT`.
-unsafe fn map_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty: BufferType,
-                     range: Range<usize>, read: bool, write: bool) -> Option<*mut ()>
+///
+/// Returns an error if mapping is not supported or if the backend returns a null pointer,
+/// which happens for example if the context has been lost.
+unsafe fn map_buffer(ctxt: &mut CommandContext, id: gl::types::GLuint, ty: BufferType,
+                     range: Range<usize>, read: bool, write: bool) -> Result<*mut (), MapError>
+{
+    // mapping an empty range is an error, so we map one byte instead and return a pointer to
+    // the position of the empty range ; zero-sized buffers have a size of one byte in OpenGL
+    if range.start == range.end {
+        let (start, offset) = if range.start == 0 { (0, 0) } else { (range.start - 1, 1) };
+        let ptr = map_buffer(ctxt, id, ty, start .. start + 1, read, write);
+        return ptr.map(|ptr| (ptr as *mut u8).offset(offset) as *mut ());
+    }
+
+    let ptr = match map_buffer_impl(ctxt, id, ty, range, read, write) {
+        Some(ptr) => ptr,
+        None => return Err(MapError::NotSupported),
+    };
+
+    if ptr.is_null() {
+        if ctxt.state.lost_context {
+            return Err(MapError::ContextLost);
+        }
+
+        // clearing the error so that it isn't reported by the next operation
+        ::get_gl_error(ctxt);
+        return Err(MapError::MappingFailed);
+    }
+
+    Ok(ptr)
+}
+
+/// Calls the right function to map a range of a buffer. The result can be null.
+unsafe fn map_buffer_impl(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty: BufferType,
+                          range: Range<usize>, read: bool, write: bool) -> Option<*mut ()>
 {
     let flags = match (read, write) {
         (true, true) => gl::MAP_FLUSH_EXPLICIT_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
@@ -1686,7 +2579,7 @@ unsafe fn map_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty: B
         (false, false) => 0,
     };
 
-    if ctxt.version >= &Version(Api::Gl, 4, 5) {
+    if ctxt.capabilities.direct_state_access {
         Some(ctxt.gl.MapNamedBufferRange(id, range.start as gl::types::GLintptr,
                                          (range.end - range.start) as gl::types::GLsizeiptr,
                                          flags) as *mut ())
@@ -1695,35 +2588,61 @@ unsafe fn map_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty: B
         ctxt.version >= &Version(Api::GlEs, 3, 0) ||
         ctxt.extensions.gl_arb_map_buffer_range
     {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         Some(ctxt.gl.MapBufferRange(bind, range.start as gl::types::GLintptr,
                                     (range.end - range.start) as gl::types::GLsizeiptr,
                                     flags) as *mut ())
 
+    } else if ctxt.extensions.gl_ext_map_buffer_range && ctxt.extensions.gl_oes_mapbuffer {
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
+        Some(ctxt.gl.MapBufferRangeEXT(bind, range.start as gl::types::GLintptr,
+                                       (range.end - range.start) as gl::types::GLsizeiptr,
+                                       flags) as *mut ())
+
     } else {
-        None       // FIXME:
+        None
     }
 }
 
+/// Fills `buf` with data from `reader`.
+// TODO: use `Read::read_exact` once it is stable
+fn read_exact<R: ?Sized>(reader: &mut R, mut buf: &mut [u8]) -> io::Result<()> where R: io::Read {
+    while !buf.is_empty() {
+        match reader.read(buf) {
+            Ok(0) => return Err(io::Error::new(io::ErrorKind::Other,
+                                               "Not enough data in the reader")),
+            Ok(n) => { let tmp = buf; buf = &mut tmp[n ..]; },
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
+
 /// Unmaps a previously-mapped buffer.
 ///
 /// # Safety
 ///
 /// Assumes that the buffer exists, that it is of the right type, and that it is already mapped.
 unsafe fn unmap_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty: BufferType) {
-    if ctxt.version >= &Version(Api::Gl, 4, 5) {
+    if ctxt.capabilities.direct_state_access {
         ctxt.gl.UnmapNamedBuffer(id);
 
     } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
               ctxt.version >= &Version(Api::GlEs, 3, 0)
     {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         ctxt.gl.UnmapBuffer(bind);
 
     } else if ctxt.extensions.gl_arb_vertex_buffer_object {
-        let bind = bind_buffer(&mut ctxt, id, ty);
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
         ctxt.gl.UnmapBufferARB(bind);
 
+    } else if ctxt.extensions.gl_oes_mapbuffer {
+        let bind = bind_buffer_for_edit(&mut ctxt, id, ty);
+        ctxt.gl.UnmapBufferOES(bind);
+
     } else {
         unreachable!();
     }
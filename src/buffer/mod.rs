@@ -10,6 +10,8 @@
 //!  - An `Alloc` corresponds to an OpenGL buffer object and is unsafe to use.
 //!    This type is not public.
 //!  - A `Buffer` wraps around an `Alloc` and provides safety by handling the data type and fences.
+//!    It doesn't carry any information about how its content is used, and can be used as a plain
+//!    GPU array (for example with compute shaders). `TypedBuffer` is an alias for it.
 //!  - The `VertexBuffer`, `IndexBuffer`, `UniformBuffer`, `PixelBuffer`, etc. types are
 //!    abstractions over a `Buffer` indicating their specific purpose. They implement `Deref`
 //!    for the `Buffer`. These types are in the `vertex`, `index`, etc. modules.
@@ -44,11 +46,20 @@
 //! # }
 //! ```
 //!
-pub use self::view::{Buffer, BufferAny, BufferMutSlice};
+pub use self::view::{Buffer, BufferAny, BufferMutSlice, StreamingWrite};
 pub use self::view::{BufferSlice, BufferAnySlice};
-pub use self::alloc::{Mapping, WriteMapping, ReadMapping, ReadError, CopyError};
-pub use self::alloc::{is_buffer_read_supported};
+pub use self::alloc::{Mapping, WriteMapping, ReadMapping, ReadError, CopyError, ClearError};
+pub use self::alloc::{MapError, WriteError};
+#[doc(hidden)]
+pub use self::alloc::DetachedAlloc;
+pub use self::alloc::VerificationError;
+pub use self::alloc::TRANSFER_CHUNK_SIZE;
+pub use self::alloc::{is_buffer_read_supported, is_buffer_mapping_supported};
 pub use self::fences::Inserter;
+pub use self::ring::{RingBuffer, RingSlice};
+
+/// A buffer that contains data of a specific type, without any binding information.
+pub type TypedBuffer<T> = Buffer<T>;
 
 /// DEPRECATED. Only here for backward compatibility.
 pub use self::view::Buffer as BufferView;
@@ -69,6 +80,7 @@ use std::slice;
 
 mod alloc;
 mod fences;
+mod ring;
 mod view;
 
 /// Trait for types of data that can be put inside buffers.
@@ -172,13 +184,18 @@ unsafe impl<T> Content for [T] where T: Copy {
 }
 
 /// Error that can happen when creating a buffer.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum BufferCreationError {
     /// Not enough memory to create the buffer.
     OutOfMemory,
 
     /// This type of buffer is not supported.
     BufferTypeNotSupported,
+
+    /// The content of the source buffer can't be copied or read back by the backend.
+    ///
+    /// Only returned when duplicating an existing buffer.
+    CopyNotSupported,
 }
 
 impl fmt::Display for BufferCreationError {
@@ -192,6 +209,8 @@ impl Error for BufferCreationError {
         match self {
             &BufferCreationError::OutOfMemory => "Not enough memory to create the buffer",
             &BufferCreationError::BufferTypeNotSupported => "This type of buffer is not supported",
+            &BufferCreationError::CopyNotSupported => "The content of the buffer can't be copied \
+                                                       by the backend",
         }
     }
 }
@@ -245,15 +264,33 @@ pub enum BufferMode {
     /// and draw the first third, then modify and draw the second third, then the last part, then
     /// go back to the first third, etc.
     ///
+    /// Modifications made through a mapping are flushed explicitly, either when the mapping is
+    /// destroyed or by calling `flush_range` on it. On some drivers this is faster than
+    /// `PersistentCoherent` for buffers that are mostly written to.
+    ///
     /// # Implementation
     ///
-    /// Tries to use `glBufferStorage` with `GL_MAP_PERSISTENT_BIT`. Sync fences are automatically
-    /// managed by glium.
+    /// Tries to use `glBufferStorage` with `GL_MAP_PERSISTENT_BIT`, and maps the buffer with
+    /// `GL_MAP_FLUSH_EXPLICIT_BIT`. Sync fences are automatically managed by glium.
     ///
     /// If this function is not available, falls back to `glBufferData` with `GL_DYNAMIC_DRAW`.
     ///
     Persistent,
 
+    /// Same as `Persistent`, except that the buffer is mapped coherently.
+    ///
+    /// Modifications made through a mapping are visible to the GPU without having to be flushed,
+    /// and `flush_range` is a no-op.
+    ///
+    /// # Implementation
+    ///
+    /// Tries to use `glBufferStorage` with `GL_MAP_PERSISTENT_BIT` and `GL_MAP_COHERENT_BIT`.
+    /// Sync fences are automatically managed by glium.
+    ///
+    /// If this function is not available, falls back to `glBufferData` with `GL_DYNAMIC_DRAW`.
+    ///
+    PersistentCoherent,
+
     /// Optimized when you will never touch the content of the buffer.
     ///
     /// Immutable buffers should be created once and never touched again. Modifying their content
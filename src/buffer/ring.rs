@@ -0,0 +1,311 @@
+/*!
+
+A ring buffer that can be used to upload data that changes every frame.
+
+*/
+use std::cmp;
+use std::mem;
+use std::ops::Range;
+
+use backend::Facade;
+
+use buffer::{Buffer, BufferSlice, BufferAnySlice, BufferType, BufferMode, BufferCreationError};
+use buffer::WriteMapping;
+use sync::SyncFence;
+use vertex::{Vertex, VertexFormat, VerticesSource, IntoVerticesSource};
+
+/// A buffer that is split in regions that are used one after the other, and that is
+/// suitable for data that changes every frame.
+///
+/// Each call to `allocate` returns a slice of the buffer that follows the previous one. When the
+/// end of the buffer is reached, the allocation starts again from the beginning. You must call
+/// `next_frame` once per frame, after all the draw commands that use the ring buffer have been
+/// submitted.
+///
+/// If the buffer uses persistent mapping, a fence is inserted at each frame and the allocation
+/// blocks if the region is still in use by the GPU. The number of times this happened can be
+/// retreived with `get_stalls_count`. If this number keeps increasing, you should create a
+/// larger ring buffer. If persistent mapping is not supported, the buffer is orphaned whenever
+/// the allocation starts again from the beginning.
+///
+/// If the allocations of a single frame don't fit in the buffer, the regions that have already
+/// been used by the frame are protected by a fence as well, and reusing them blocks until the
+/// GPU has finished using them.
+///
+/// ## Example
+///
+/// ```no_run
+/// # #[macro_use] extern crate glium; fn main() {
+/// # use glium::Surface;
+/// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+/// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+/// # let mut frame = display.draw();
+/// #[derive(Copy, Clone)]
+/// struct Vertex { position: [f32; 2] }
+/// implement_vertex!(Vertex, position);
+///
+/// let mut ring = glium::buffer::RingBuffer::<Vertex>::vertex(&display, 4096).unwrap();
+///
+/// let slice = ring.allocate(3);
+/// slice.write(&[Vertex { position: [0.0, 0.0] }, Vertex { position: [0.0, 1.0] },
+///               Vertex { position: [1.0, 0.0] }]);
+/// frame.draw(slice, &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+///            &program, &uniform!{}, &Default::default()).unwrap();
+///
+/// ring.next_frame();
+/// # }
+/// ```
+pub struct RingBuffer<T> where T: Copy {
+    buffer: Buffer<[T]>,
+
+    /// Vertex format of the elements, if the ring buffer can be used as a vertex source.
+    bindings: Option<VertexFormat>,
+
+    /// Number of elements that offsets must be a multiple of.
+    alignment: usize,
+
+    /// Offset in elements of the next allocation.
+    cursor: usize,
+
+    /// Offset in elements where the current frame started.
+    frame_start: usize,
+
+    /// Regions used by the previous frames, with the fence that signals the end of their usage.
+    fences: Vec<(Range<usize>, SyncFence)>,
+
+    /// Number of times an allocation had to wait for the GPU.
+    stalls: usize,
+
+    /// Number of times a fence couldn't be created.
+    fence_failures: usize,
+}
+
+impl<T> RingBuffer<T> where T: Copy {
+    /// Builds a new ring buffer that can hold `len` elements.
+    ///
+    /// If `ty` is `BufferType::UniformBuffer`, allocations respect the value of
+    /// `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `T` is a zero-sized type.
+    pub fn new<F>(facade: &F, ty: BufferType, len: usize)
+                  -> Result<RingBuffer<T>, BufferCreationError> where F: Facade
+    {
+        assert!(mem::size_of::<T>() != 0, "Zero-sized types can't be stored in a ring buffer");
+
+        let buffer = try!(Buffer::empty_array(facade, ty, len, BufferMode::Persistent));
+
+        // some drivers report an alignment of 0, which means that there is no requirement
+        let alignment_bytes = if ty == BufferType::UniformBuffer {
+            cmp::max(facade.get_context().get_uniform_buffer_offset_alignment(), 1)
+        } else {
+            1
+        };
+
+        // finding the smallest number of elements whose size is a multiple of the alignment
+        let element_size = mem::size_of::<T>();
+        let mut alignment = 1;
+        while (alignment * element_size) % alignment_bytes != 0 {
+            alignment += 1;
+        }
+
+        Ok(RingBuffer {
+            buffer: buffer,
+            bindings: None,
+            alignment: alignment,
+            cursor: 0,
+            frame_start: 0,
+            fences: Vec::new(),
+            stalls: 0,
+            fence_failures: 0,
+        })
+    }
+
+    /// Returns the number of elements of the ring buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns true if the ring buffer uses persistent mapping.
+    #[inline]
+    pub fn is_persistent(&self) -> bool {
+        self.buffer.is_persistent()
+    }
+
+    /// Returns the number of times an allocation had to wait for the GPU to finish using a
+    /// region of the buffer.
+    #[inline]
+    pub fn get_stalls_count(&self) -> usize {
+        self.stalls
+    }
+
+    /// Returns the number of times a fence couldn't be created.
+    ///
+    /// When this happens, the ring buffer waits for the GPU to finish all its commands instead,
+    /// which is much slower.
+    #[inline]
+    pub fn get_fence_failures_count(&self) -> usize {
+        self.fence_failures
+    }
+
+    /// Allocates `count` elements in the ring buffer.
+    ///
+    /// The returned slice is valid until the end of the current frame.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `count` is larger than the length of the ring buffer.
+    pub fn allocate(&mut self, count: usize) -> RingSlice<T> {
+        assert!(count <= self.buffer.len(), "Allocation larger than the ring buffer");
+
+        let mut offset = (self.cursor + self.alignment - 1) / self.alignment * self.alignment;
+
+        if offset + count > self.buffer.len() {
+            // the regions used by the current frame must not be overwritten before the GPU has
+            // finished using them
+            let range = self.frame_start .. self.cursor;
+            self.insert_fence(range);
+
+            offset = 0;
+            self.frame_start = 0;
+
+            // without persistent mapping, orphaning the buffer lets the driver handle the
+            // synchronization for us
+            if !self.buffer.is_persistent() {
+                self.buffer.invalidate();
+            }
+        }
+
+        self.wait_for_range(offset .. offset + count);
+        self.cursor = offset + count;
+
+        RingSlice {
+            buffer: &mut self.buffer,
+            bindings: self.bindings.as_ref(),
+            range: offset .. offset + count,
+        }
+    }
+
+    /// Indicates that the current frame is over.
+    ///
+    /// Must be called after all the commands that use the slices of this frame have been
+    /// submitted.
+    pub fn next_frame(&mut self) {
+        let range = self.frame_start .. self.cursor;
+        self.insert_fence(range);
+        self.frame_start = self.cursor;
+    }
+
+    /// Inserts a fence that signals the end of the usage of the given range of elements.
+    ///
+    /// If the fence can't be created, waits for the GPU to finish all its commands instead.
+    fn insert_fence(&mut self, range: Range<usize>) {
+        if !self.buffer.is_persistent() || range.start == range.end {
+            return;
+        }
+
+        match SyncFence::new(self.buffer.get_context()) {
+            Ok(fence) => self.fences.push((range, fence)),
+            Err(_) => {
+                self.fence_failures += 1;
+                self.buffer.get_context().finish();
+            },
+        }
+    }
+
+    /// Blocks until the GPU no longer uses the given range of elements.
+    fn wait_for_range(&mut self, range: Range<usize>) {
+        let mut remaining = Vec::with_capacity(self.fences.len());
+
+        for (r, fence) in mem::replace(&mut self.fences, Vec::new()).into_iter() {
+            if r.start < range.end && range.start < r.end {
+                self.stalls += 1;
+                fence.wait();
+            } else {
+                remaining.push((r, fence));
+            }
+        }
+
+        self.fences = remaining;
+    }
+}
+
+impl<T> RingBuffer<T> where T: Vertex {
+    /// Builds a new ring buffer of vertices that can hold `len` elements.
+    ///
+    /// The slices returned by `allocate` can be used as a vertex source.
+    #[inline]
+    pub fn vertex<F>(facade: &F, len: usize) -> Result<RingBuffer<T>, BufferCreationError>
+                     where F: Facade
+    {
+        let mut ring = try!(RingBuffer::new(facade, BufferType::ArrayBuffer, len));
+        ring.bindings = Some(<T as Vertex>::build_bindings());
+        Ok(ring)
+    }
+}
+
+/// A region of a `RingBuffer` that has been allocated for the current frame.
+pub struct RingSlice<'a, T> where T: Copy + 'a {
+    buffer: &'a mut Buffer<[T]>,
+    bindings: Option<&'a VertexFormat>,
+    range: Range<usize>,
+}
+
+impl<'a, T> RingSlice<'a, T> where T: Copy + 'a {
+    /// Returns the number of elements in this slice.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.range.end - self.range.start
+    }
+
+    /// Returns the offset in elements of this slice within the ring buffer.
+    #[inline]
+    pub fn get_offset(&self) -> usize {
+        self.range.start
+    }
+
+    /// Uploads some data in this slice.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is different from the length of this slice.
+    #[inline]
+    pub fn write(&self, data: &[T]) {
+        self.as_slice().write(data);
+    }
+
+    /// Maps the slice in memory for writing.
+    #[inline]
+    pub fn map_write(&mut self) -> WriteMapping<[T]> {
+        self.buffer.slice_mut(self.range.clone()).unwrap().map_write()
+    }
+
+    /// Returns a slice of the underlying buffer.
+    #[inline]
+    pub fn as_slice(&self) -> BufferSlice<[T]> {
+        self.buffer.slice(self.range.clone()).unwrap()
+    }
+
+    /// Returns a slice of the underlying buffer without any type information. This can be
+    /// used for example to bind a region of a uniform buffer.
+    #[inline]
+    pub fn as_slice_any(&self) -> BufferAnySlice {
+        self.as_slice().as_slice_any()
+    }
+}
+
+impl<'a, T> IntoVerticesSource<'a> for RingSlice<'a, T> where T: Vertex + 'a {
+    /// # Panic
+    ///
+    /// Panics if the ring buffer wasn't created with `RingBuffer::vertex`.
+    #[inline]
+    fn into_vertices_source(self) -> VerticesSource<'a> {
+        let bindings = self.bindings.expect("The ring buffer wasn't created with \
+                                             `RingBuffer::vertex`");
+        let buffer: &'a Buffer<[T]> = self.buffer;
+        let slice = buffer.slice(self.range).unwrap();
+        VerticesSource::VertexBuffer(slice.as_slice_any(), bindings, false)
+    }
+}
@@ -1,16 +1,22 @@
+use std::cmp;
 use std::fmt;
+use std::io;
 use std::mem;
 use std::borrow::Cow;
 use utils::range::RangeArgument;
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 
 use texture::{PixelValue, Texture1dDataSink};
 use gl;
 
 use backend::Facade;
 use BufferExt;
+use BufferAnyExt;
 use BufferSliceExt;
 use GlObject;
+use StreamingWriteStatus;
+use sync::{DeferredDestroy, DeferredObject};
 
 use context::Context;
 use context::CommandContext;
@@ -24,11 +30,17 @@ use buffer::Content;
 use buffer::fences::Fences;
 use buffer::fences::Inserter;
 use buffer::alloc::Alloc;
+use buffer::alloc::DetachedAlloc;
 use buffer::alloc::Mapping;
 use buffer::alloc::ReadMapping;
 use buffer::alloc::WriteMapping;
+use buffer::alloc::MapError;
+use buffer::alloc::WriteError;
 use buffer::alloc::ReadError;
 use buffer::alloc::CopyError;
+use buffer::alloc::ClearError;
+use buffer::alloc::VerificationError;
+use buffer::alloc::TRANSFER_CHUNK_SIZE;
 
 /// Represents a view of a buffer.
 pub struct Buffer<T: ?Sized> where T: Content {
@@ -90,6 +102,44 @@ impl<T: ?Sized> Buffer<T> where T: Content {
         self.alloc.as_ref().unwrap().uses_persistent_mapping()
     }
 
+    /// Returns the mode this buffer was created with.
+    #[inline]
+    pub fn get_usage(&self) -> BufferMode {
+        self.alloc.as_ref().unwrap().get_creation_mode()
+    }
+
+    /// Returns true if this buffer is currently mapped in memory by OpenGL.
+    ///
+    /// This is always the case for persistent-mapped buffers. For other buffers, this only
+    /// happens if a mapping has been leaked with `mem::forget`.
+    #[inline]
+    pub fn is_mapped(&self) -> bool {
+        self.alloc.as_ref().unwrap().is_mapped()
+    }
+
+    /// Returns false if the buffer has been created with `new_streamed` and the upload has been
+    /// interrupted by the progress callback. Drawing with such a buffer returns an error.
+    ///
+    /// Writing the whole buffer with `write` makes it complete again.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.alloc.as_ref().unwrap().is_complete()
+    }
+
+    /// Checks that the size of the buffer as reported by OpenGL matches the size glium expects.
+    ///
+    /// Glium's own records are used everywhere else, so if some external code re-specifies the
+    /// storage of the buffer, `get_size` and `len` become wrong. This function is meant to help
+    /// debugging this kind of problem.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glGetBufferParameteriv` with `GL_BUFFER_SIZE`.
+    #[inline]
+    pub fn verify_against_gl(&self) -> Result<(), VerificationError> {
+        self.alloc.as_ref().unwrap().verify_against_gl()
+    }
+
     /// Uploads some data in this buffer.
     ///
     /// # Implementation
@@ -102,13 +152,28 @@ impl<T: ?Sized> Buffer<T> where T: Content {
     ///
     /// # Panic
     ///
-    /// Panics if the length of `data` is different from the length of this buffer.
+    /// Panics if the length of `data` is different from the length of this buffer, or if the
+    /// upload fails. Use `try_write` to handle these cases.
     pub fn write(&self, data: &T) {
-        assert!(mem::size_of_val(data) == self.get_size());
+        if let Err(err) = self.try_write(data) {
+            panic!("Failed to write to the buffer: {}", err);
+        }
+    }
+
+    /// Same as `write`, but returns an error instead of panicking.
+    pub fn try_write(&self, data: &T) -> Result<(), WriteError> {
+        if mem::size_of_val(data) != self.get_size() {
+            return Err(WriteError::LengthMismatch {
+                expected: self.get_size(),
+                obtained: mem::size_of_val(data),
+            });
+        }
 
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
                                           0 .. self.get_size());
-        unsafe { self.alloc.as_ref().unwrap().upload(0, data); }
+        unsafe { try!(self.alloc.as_ref().unwrap().upload(0, data)); }
+        self.alloc.as_ref().unwrap().set_complete(true);
+        Ok(())
     }
 
     /// Invalidates the content of the buffer. The data becomes undefined.
@@ -130,6 +195,29 @@ impl<T: ?Sized> Buffer<T> where T: Content {
         self.alloc.as_ref().unwrap().invalidate(0, self.get_size());
     }
 
+    /// Fills the buffer with zeroes.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glClearBufferData` if supported. Otherwise, uploads zeroes from the CPU.
+    #[inline]
+    pub fn clear_to_zero(&self) {
+        self.as_slice().clear_to_zero()
+    }
+
+    /// Fills the buffer with copies of `value`.
+    ///
+    /// The length of `value` must be 1, 2, 4, 8 or 16 bytes, and the size of the buffer must be
+    /// a multiple of it.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glClearBufferData` if supported. Otherwise, uploads the data from the CPU.
+    #[inline]
+    pub fn clear_with_value(&self, value: &[u8]) -> Result<(), ClearError> {
+        self.as_slice().clear_with_value(value)
+    }
+
     /// Reads the content of the buffer.
     pub fn read(&self) -> Result<T::Owned, ReadError> {
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
@@ -140,6 +228,46 @@ impl<T: ?Sized> Buffer<T> where T: Content {
         }
     }
 
+    /// Writes the raw content of the buffer to `writer`.
+    ///
+    /// Contrary to `read`, the content is streamed in chunks of at most
+    /// `TRANSFER_CHUNK_SIZE` bytes, so the whole buffer is never held in memory at once.
+    /// The bytes are written in the native endianness of the machine.
+    ///
+    /// If reading the buffer fails, the `ReadError` is returned as an `io::Error` of kind
+    /// `Other`.
+    ///
+    /// # Implementation
+    ///
+    /// Reads from the mapping for persistent-mapped buffers and on OpenGL ES, where
+    /// `glGetBufferSubData` doesn't exist. Otherwise calls `glGetBufferSubData` for each chunk.
+    pub fn download_to_writer<W: ?Sized>(&self, writer: &mut W) -> io::Result<()>
+                                          where W: io::Write
+    {
+        self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
+                                          0 .. self.get_size());
+
+        unsafe {
+            self.alloc.as_ref().unwrap().read_to_writer(0 .. self.get_size(), writer)
+        }
+    }
+
+    /// Fills the buffer with raw bytes read from `reader`.
+    ///
+    /// Exactly `get_size()` bytes are read, in chunks of at most `TRANSFER_CHUNK_SIZE` bytes.
+    /// The bytes must be in the native endianness of the machine. Returns an error if the reader
+    /// doesn't contain enough data, in which case the content of the buffer is undefined.
+    pub fn upload_from_reader<R: ?Sized>(&self, reader: &mut R) -> io::Result<()>
+                                         where R: io::Read
+    {
+        self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
+                                          0 .. self.get_size());
+
+        unsafe {
+            self.alloc.as_ref().unwrap().upload_from_reader(0 .. self.get_size(), reader)
+        }
+    }
+
     /// Maps the buffer in memory for both reading and writing.
     ///
     /// # Implementation
@@ -151,7 +279,23 @@ impl<T: ?Sized> Buffer<T> where T: Content {
     ///   to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
     ///
+    /// The memory of persistent-mapped buffers is usually write-combined, and reading from it
+    /// through the mapping is very slow. Use `map_write` if you don't need to read the data.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the buffer can't be mapped, for example because mapping is not supported or
+    /// because the context has been lost. Use `try_map` to handle these cases.
+    ///
     pub fn map(&mut self) -> Mapping<T> {
+        match self.try_map() {
+            Ok(mapping) => mapping,
+            Err(err) => panic!("Failed to map the buffer: {}", err),
+        }
+    }
+
+    /// Same as `map`, but returns an error if the buffer can't be mapped.
+    pub fn try_map(&mut self) -> Result<Mapping<T>, MapError> {
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
                                           0 .. self.get_size());
         let size = self.get_size();
@@ -168,7 +312,20 @@ impl<T: ?Sized> Buffer<T> where T: Content {
     ///   maps it.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
     ///
+    /// # Panic
+    ///
+    /// Panics if the buffer can't be mapped, for example because mapping is not supported or
+    /// because the context has been lost. Use `try_map_read` to handle these cases.
+    ///
     pub fn map_read(&mut self) -> ReadMapping<T> {
+        match self.try_map_read() {
+            Ok(mapping) => mapping,
+            Err(err) => panic!("Failed to map the buffer: {}", err),
+        }
+    }
+
+    /// Same as `map_read`, but returns an error if the buffer can't be mapped.
+    pub fn try_map_read(&mut self) -> Result<ReadMapping<T>, MapError> {
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
                                           0 .. self.get_size());
         let size = self.get_size();
@@ -186,13 +343,53 @@ impl<T: ?Sized> Buffer<T> where T: Content {
     ///   to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
     ///
+    /// # Panic
+    ///
+    /// Panics if the buffer can't be mapped, for example because mapping is not supported or
+    /// because the context has been lost. Use `try_map_write` to handle these cases.
+    ///
     pub fn map_write(&mut self) -> WriteMapping<T> {
+        match self.try_map_write() {
+            Ok(mapping) => mapping,
+            Err(err) => panic!("Failed to map the buffer: {}", err),
+        }
+    }
+
+    /// Same as `map_write`, but returns an error if the buffer can't be mapped.
+    pub fn try_map_write(&mut self) -> Result<WriteMapping<T>, MapError> {
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
                                           0 .. self.get_size());
         let size = self.get_size();
         unsafe { self.alloc.as_mut().unwrap().map_write(0 .. size) }
     }
 
+    /// Returns true if the buffer can be mapped in memory.
+    ///
+    /// This is always the case except on OpenGL ES 2 without the `GL_EXT_map_buffer_range`
+    /// extension.
+    #[inline]
+    pub fn is_mapping_supported(&self) -> bool {
+        self.alloc.as_ref().unwrap().is_mapping_supported()
+    }
+
+    /// Same as `map`, but returns `None` if mapping is not supported by the backend.
+    #[inline]
+    pub fn map_if_supported(&mut self) -> Option<Mapping<T>> {
+        if self.is_mapping_supported() { Some(self.map()) } else { None }
+    }
+
+    /// Same as `map_read`, but returns `None` if mapping is not supported by the backend.
+    #[inline]
+    pub fn map_read_if_supported(&mut self) -> Option<ReadMapping<T>> {
+        if self.is_mapping_supported() { Some(self.map_read()) } else { None }
+    }
+
+    /// Same as `map_write`, but returns `None` if mapping is not supported by the backend.
+    #[inline]
+    pub fn map_write_if_supported(&mut self) -> Option<WriteMapping<T>> {
+        if self.is_mapping_supported() { Some(self.map_write()) } else { None }
+    }
+
     /// Copies the content of the buffer to another buffer.
     ///
     /// # Panic
@@ -220,6 +417,47 @@ impl<T: ?Sized> Buffer<T> where T: Content {
         Ok(())
     }
 
+    /// Builds a new buffer with the same type, mode and content as this one.
+    ///
+    /// The copy observes all the previous writes to this buffer. If this buffer uses persistent
+    /// mapping, the new one will use persistent mapping too if the backend supports it.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glCopyBufferSubData` if supported. Otherwise, reads the content of the buffer and
+    /// uploads it in the new one.
+    pub fn clone_buffer<F>(&self, facade: &F) -> Result<Buffer<T>, BufferCreationError>
+                           where F: Facade
+    {
+        let alloc = self.alloc.as_ref().unwrap();
+
+        self.fence.as_ref().unwrap().wait(&mut alloc.get_context().make_current(),
+                                          0 .. self.get_size());
+
+        let buffer = Buffer {
+            alloc: Some(try!(alloc.duplicate(facade))),
+            fence: Some(Fences::new()),
+            marker: PhantomData,
+        };
+
+        buffer.insert_fence();
+        Ok(buffer)
+    }
+
+    /// Inserts a fence that covers the whole buffer.
+    ///
+    /// Mapping, reading or writing the buffer will then wait until all the commands submitted
+    /// before this call have been executed by the GPU. Glium already does this automatically when
+    /// it uses the buffer, so you only need this if you access the buffer with raw OpenGL
+    /// commands.
+    ///
+    /// This is a no-op if the buffer doesn't use persistent mapping, as the synchronization is
+    /// then handled by the OpenGL implementation.
+    #[inline]
+    pub fn insert_fence(&self) {
+        self.as_slice().insert_fence();
+    }
+
     /// Builds a slice that contains an element from inside the buffer.
     ///
     /// This method builds an object that represents a slice of the buffer. No actual operation
@@ -345,6 +583,55 @@ impl<T> Buffer<[T]> where [T]: Content, T: Copy {
             })
     }
 
+    /// Builds a new buffer containing the given data, by uploading it in chunks of at most
+    /// `chunk_bytes` bytes.
+    ///
+    /// Contrary to `new`, the driver never needs a staging area as large as the data. This is
+    /// useful for very large sources, like a memory-mapped file.
+    ///
+    /// After each chunk, `progress` is called with the number of bytes that have been uploaded
+    /// and the total number of bytes. If it returns `false`, the upload stops and the buffer is
+    /// returned as it is. The rest of its content is undefined, `is_complete` returns false and
+    /// drawing with it returns `DrawError::IncompleteBuffer`.
+    ///
+    /// # Implementation
+    ///
+    /// For each chunk, maps the corresponding range of the buffer, copies the chunk to it then
+    /// unmaps it, which flushes the range. If mapping is not supported, calls `glBufferSubData`
+    /// for each chunk instead.
+    pub fn new_streamed<F, P>(facade: &F, data: &[T], ty: BufferType, chunk_bytes: usize,
+                              mut progress: P) -> Result<Buffer<[T]>, BufferCreationError>
+                              where F: Facade, P: FnMut(usize, usize) -> bool
+    {
+        let mut buffer = try!(Buffer::empty_array(facade, ty, data.len(), BufferMode::Default));
+
+        let element_size = cmp::max(mem::size_of::<T>(), 1);
+        let chunk_len = cmp::max(chunk_bytes / element_size, 1);
+        let total_bytes = data.len() * mem::size_of::<T>();
+        let mapping_supported = buffer.is_mapping_supported();
+
+        let mut start = 0;
+        while start < data.len() {
+            let end = cmp::min(start + chunk_len, data.len());
+
+            if mapping_supported {
+                let mut mapping = buffer.slice_mut(start .. end).unwrap().map_write();
+                mapping.copy_from_slice(0, &data[start .. end]);
+            } else {
+                buffer.write_at(start, &data[start .. end]);
+            }
+
+            start = end;
+
+            if !progress(start * mem::size_of::<T>(), total_bytes) && start != data.len() {
+                buffer.alloc.as_ref().unwrap().set_complete(false);
+                break;
+            }
+        }
+
+        Ok(buffer)
+    }
+
     /// Returns the number of elements in this buffer.
     #[inline]
     pub fn len(&self) -> usize {
@@ -368,6 +655,96 @@ impl<T> Buffer<[T]> where [T]: Content, T: Copy {
     pub fn slice_mut<R: RangeArgument<usize>>(&mut self, range: R) -> Option<BufferMutSlice<[T]>> {
         self.as_mut_slice().slice(range)
     }
+
+    /// Uploads some data in this buffer, starting at the element `offset`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `offset + data.len()` is greater than the length of this buffer.
+    #[inline]
+    pub fn write_at(&self, offset: usize, data: &[T]) {
+        self.as_slice().write_at(offset, data)
+    }
+
+    /// Starts uploading `data` to the buffer incrementally.
+    ///
+    /// Nothing is uploaded by this function. Call `advance` on the returned object regularly,
+    /// for example once per frame, until it returns `StreamingWriteStatus::Done`. This spreads
+    /// the upload of a large amount of data over several frames.
+    ///
+    /// Until the upload is done, `is_complete` returns false and drawing with the buffer
+    /// returns `DrawError::IncompleteBuffer`. If the `StreamingWrite` is dropped before the
+    /// end, the buffer stays incomplete and the part that hasn't been uploaded is undefined.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is different from the length of this buffer.
+    pub fn begin_streaming_write(&self, data: Vec<T>) -> StreamingWrite<T> {
+        assert_eq!(data.len(), self.len());
+
+        if !data.is_empty() {
+            self.alloc.as_ref().unwrap().set_complete(false);
+        }
+
+        StreamingWrite {
+            buffer: self,
+            data: data,
+            uploaded: 0,
+        }
+    }
+}
+
+/// Incremental upload of data to a buffer. Built with `Buffer::begin_streaming_write`.
+pub struct StreamingWrite<'a, T> where [T]: Content, T: Copy + 'a {
+    buffer: &'a Buffer<[T]>,
+    data: Vec<T>,
+    /// Number of elements that have already been uploaded.
+    uploaded: usize,
+}
+
+impl<'a, T> StreamingWrite<'a, T> where [T]: Content, T: Copy + 'a {
+    /// Uploads chunks of at most `TRANSFER_CHUNK_SIZE` bytes until `budget` has elapsed or until
+    /// all the data has been uploaded.
+    ///
+    /// At least one chunk is uploaded by each call, so a call can take a bit longer than
+    /// `budget`. Once `Done` has been returned, further calls do nothing.
+    pub fn advance(&mut self, budget: Duration) -> StreamingWriteStatus {
+        let start_time = Instant::now();
+        let chunk_len = cmp::max(TRANSFER_CHUNK_SIZE / cmp::max(mem::size_of::<T>(), 1), 1);
+
+        while self.uploaded < self.data.len() {
+            let end = cmp::min(self.uploaded + chunk_len, self.data.len());
+            self.buffer.write_at(self.uploaded, &self.data[self.uploaded .. end]);
+            self.uploaded = end;
+
+            if self.uploaded == self.data.len() {
+                self.buffer.alloc.as_ref().unwrap().set_complete(true);
+                break;
+            }
+
+            if start_time.elapsed() >= budget {
+                break;
+            }
+        }
+
+        self.get_status()
+    }
+
+    /// Returns whether all the data has been uploaded.
+    #[inline]
+    pub fn get_status(&self) -> StreamingWriteStatus {
+        if self.uploaded == self.data.len() {
+            StreamingWriteStatus::Done
+        } else {
+            StreamingWriteStatus::InProgress
+        }
+    }
+
+    /// Returns the number of bytes that have been uploaded and the total number of bytes.
+    #[inline]
+    pub fn get_progress(&self) -> (usize, usize) {
+        (self.uploaded * mem::size_of::<T>(), self.data.len() * mem::size_of::<T>())
+    }
 }
 
 impl<T> Buffer<[T]> where T: PixelValue {
@@ -390,11 +767,42 @@ impl<T: ?Sized> Drop for Buffer<T> where T: Content {
     #[inline]
     fn drop(&mut self) {
         if let (Some(alloc), Some(mut fence)) = (self.alloc.take(), self.fence.take()) {
-            fence.clean(&mut alloc.get_context().make_current());
+            if let Some(mut ctxt) = alloc.get_context().make_current_for_drop() {
+                fence.clean(&mut ctxt);
+            }
         }
     }
 }
 
+impl<T: ?Sized> DeferredDestroy for Buffer<T> where T: Content {
+    type Detached = DetachedAlloc;
+
+    fn detach(mut self) -> DetachedAlloc {
+        let alloc = self.alloc.take().unwrap();
+        let mut fence = self.fence.take().unwrap();
+
+        if let Some(mut ctxt) = alloc.get_context().make_current_for_drop() {
+            fence.clean(&mut ctxt);
+        }
+
+        alloc.detach()
+    }
+
+    #[inline]
+    fn attach(detached: DetachedAlloc, context: &Rc<Context>) -> Buffer<T> {
+        Buffer {
+            alloc: Some(Alloc::attach(detached, context)),
+            fence: Some(Fences::new()),
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn into_deferred_object(detached: DetachedAlloc) -> DeferredObject {
+        DeferredObject::Buffer(detached)
+    }
+}
+
 impl<T: ?Sized> BufferExt for Buffer<T> where T: Content {
     #[inline]
     fn get_offset_bytes(&self) -> usize {
@@ -524,13 +932,26 @@ impl<'a, T: ?Sized> BufferSlice<'a, T> where T: Content + 'a {
     ///
     /// # Panic
     ///
-    /// Panics if the length of `data` is different from the length of this buffer.
+    /// Panics if the length of `data` is different from the length of this buffer, or if the
+    /// upload fails. Use `try_write` to handle these cases.
     pub fn write(&self, data: &T) {
-        assert_eq!(mem::size_of_val(data), self.get_size());
+        if let Err(err) = self.try_write(data) {
+            panic!("Failed to write to the buffer: {}", err);
+        }
+    }
+
+    /// Same as `write`, but returns an error instead of panicking.
+    pub fn try_write(&self, data: &T) -> Result<(), WriteError> {
+        if mem::size_of_val(data) != self.get_size() {
+            return Err(WriteError::LengthMismatch {
+                expected: self.get_size(),
+                obtained: mem::size_of_val(data),
+            });
+        }
 
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
-        unsafe { self.alloc.upload(self.bytes_start, data); }
+        unsafe { self.alloc.upload(self.bytes_start, data) }
     }
 
     /// Invalidates the content of the slice. The data becomes undefined.
@@ -547,6 +968,33 @@ impl<'a, T: ?Sized> BufferSlice<'a, T> where T: Content + 'a {
         self.alloc.invalidate(self.bytes_start, self.get_size());
     }
 
+    /// Fills the slice with zeroes.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glClearBufferSubData` if supported. Otherwise, uploads zeroes from the CPU.
+    #[inline]
+    pub fn clear_to_zero(&self) {
+        self.clear_with_value(&[0]).unwrap()
+    }
+
+    /// Fills the slice with copies of `value`.
+    ///
+    /// The length of `value` must be 1, 2, 4, 8 or 16 bytes, and the offset and size of the
+    /// slice must be multiples of it.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glClearBufferSubData` if supported. Otherwise, uploads the data from the CPU.
+    pub fn clear_with_value(&self, value: &[u8]) -> Result<(), ClearError> {
+        self.fence.wait(&mut self.alloc.get_context().make_current(),
+                        self.bytes_start .. self.bytes_end);
+
+        try!(unsafe { self.alloc.clear(self.bytes_start .. self.bytes_end, value) });
+        self.insert_fence();
+        Ok(())
+    }
+
     /// Reads the content of the buffer.
     pub fn read(&self) -> Result<T::Owned, ReadError> {
         self.fence.wait(&mut self.alloc.get_context().make_current(),
@@ -557,6 +1005,19 @@ impl<'a, T: ?Sized> BufferSlice<'a, T> where T: Content + 'a {
         }
     }
 
+    /// Inserts a fence that covers this slice.
+    ///
+    /// Mapping, reading or writing this part of the buffer will then wait until all the commands
+    /// submitted before this call have been executed by the GPU.
+    ///
+    /// This is a no-op if the buffer doesn't use persistent mapping.
+    pub fn insert_fence(&self) {
+        if let Some(inserter) = self.add_fence() {
+            let mut ctxt = self.alloc.get_context().make_current();
+            inserter.insert(&mut ctxt);
+        }
+    }
+
     /// Copies the content of this slice to another slice.
     ///
     /// # Panic
@@ -671,6 +1132,38 @@ impl<'a, T> BufferSlice<'a, [T]> where [T]: Content + 'a {
             marker: PhantomData,
         })
     }
+
+    /// Builds a slice that contains a single element of this slice. Returns `None` if out
+    /// of range.
+    ///
+    /// This method builds an object that represents a slice of the buffer. No actual operation
+    /// OpenGL is performed.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<BufferSlice<'a, T>> where T: Content {
+        if index >= self.len() {
+            return None;
+        }
+
+        Some(BufferSlice {
+            alloc: self.alloc,
+            bytes_start: self.bytes_start + index * mem::size_of::<T>(),
+            bytes_end: self.bytes_start + (index + 1) * mem::size_of::<T>(),
+            fence: self.fence,
+            marker: PhantomData,
+        })
+    }
+
+    /// Uploads some data in this slice, starting at the element `offset`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `offset + data.len()` is greater than the length of this slice.
+    pub fn write_at(&self, offset: usize, data: &[T]) {
+        match self.slice(offset .. offset + data.len()) {
+            Some(slice) => slice.write(data),
+            None => panic!("Out of range write in a buffer")
+        }
+    }
 }
 
 impl<'a, T> BufferSlice<'a, [T]> where T: PixelValue + 'a {
@@ -795,7 +1288,7 @@ impl<'a, T: ?Sized> BufferExt for BufferSlice<'a, T> where T: Content {
 
     #[inline]
     fn prepare_and_bind_for_uniform(&self, ctxt: &mut CommandContext, index: gl::types::GLuint) {
-        self.alloc.prepare_and_bind_for_uniform(ctxt, index, 0 .. self.alloc.get_size());
+        self.alloc.prepare_and_bind_for_uniform(ctxt, index, self.bytes_start .. self.bytes_end);
     }
 
     #[inline]
@@ -836,8 +1329,22 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
     ///   to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
     ///
+    /// # Panic
+    ///
+    /// Panics if the buffer can't be mapped, for example because mapping is not supported or
+    /// because the context has been lost. Use `try_map` to handle these cases.
+    ///
     #[inline]
     pub fn map(self) -> Mapping<'a, T> {
+        match self.try_map() {
+            Ok(mapping) => mapping,
+            Err(err) => panic!("Failed to map the buffer: {}", err),
+        }
+    }
+
+    /// Same as `map`, but returns an error if the buffer can't be mapped.
+    #[inline]
+    pub fn try_map(self) -> Result<Mapping<'a, T>, MapError> {
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
         unsafe { self.alloc.map(self.bytes_start .. self.bytes_end) }
@@ -853,8 +1360,22 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
     ///   maps it.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
     ///
+    /// # Panic
+    ///
+    /// Panics if the buffer can't be mapped, for example because mapping is not supported or
+    /// because the context has been lost. Use `try_map_read` to handle these cases.
+    ///
     #[inline]
     pub fn map_read(self) -> ReadMapping<'a, T> {
+        match self.try_map_read() {
+            Ok(mapping) => mapping,
+            Err(err) => panic!("Failed to map the buffer: {}", err),
+        }
+    }
+
+    /// Same as `map_read`, but returns an error if the buffer can't be mapped.
+    #[inline]
+    pub fn try_map_read(self) -> Result<ReadMapping<'a, T>, MapError> {
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
         unsafe { self.alloc.map_read(self.bytes_start .. self.bytes_end) }
@@ -870,13 +1391,45 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
     ///   is destroyed, copies the content of the temporary buffer to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
     ///
+    /// # Panic
+    ///
+    /// Panics if the buffer can't be mapped, for example because mapping is not supported or
+    /// because the context has been lost. Use `try_map_write` to handle these cases.
+    ///
     #[inline]
     pub fn map_write(self) -> WriteMapping<'a, T> {
+        match self.try_map_write() {
+            Ok(mapping) => mapping,
+            Err(err) => panic!("Failed to map the buffer: {}", err),
+        }
+    }
+
+    /// Same as `map_write`, but returns an error if the buffer can't be mapped.
+    #[inline]
+    pub fn try_map_write(self) -> Result<WriteMapping<'a, T>, MapError> {
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
         unsafe { self.alloc.map_write(self.bytes_start .. self.bytes_end) }
     }
 
+    /// Same as `map`, but returns `None` if mapping is not supported by the backend.
+    #[inline]
+    pub fn map_if_supported(self) -> Option<Mapping<'a, T>> {
+        if self.alloc.is_mapping_supported() { Some(self.map()) } else { None }
+    }
+
+    /// Same as `map_read`, but returns `None` if mapping is not supported by the backend.
+    #[inline]
+    pub fn map_read_if_supported(self) -> Option<ReadMapping<'a, T>> {
+        if self.alloc.is_mapping_supported() { Some(self.map_read()) } else { None }
+    }
+
+    /// Same as `map_write`, but returns `None` if mapping is not supported by the backend.
+    #[inline]
+    pub fn map_write_if_supported(self) -> Option<WriteMapping<'a, T>> {
+        if self.alloc.is_mapping_supported() { Some(self.map_write()) } else { None }
+    }
+
     /// Uploads some data in this buffer.
     ///
     /// # Implementation
@@ -889,12 +1442,27 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
     ///
     /// # Panic
     ///
-    /// Panics if the length of `data` is different from the length of this buffer.
+    /// Panics if the length of `data` is different from the length of this buffer, or if the
+    /// upload fails. Use `try_write` to handle these cases.
     #[inline]
     pub fn write(&self, data: &T) {
+        if let Err(err) = self.try_write(data) {
+            panic!("Failed to write to the buffer: {}", err);
+        }
+    }
+
+    /// Same as `write`, but returns an error instead of panicking.
+    pub fn try_write(&self, data: &T) -> Result<(), WriteError> {
+        if mem::size_of_val(data) != self.get_size() {
+            return Err(WriteError::LengthMismatch {
+                expected: self.get_size(),
+                obtained: mem::size_of_val(data),
+            });
+        }
+
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
-        unsafe { self.alloc.upload(self.bytes_start, data); }
+        unsafe { self.alloc.upload(self.bytes_start, data) }
     }
 
     /// Invalidates the content of the slice. The data becomes undefined.
@@ -1119,6 +1687,13 @@ impl BufferAny {
         self.size
     }
 
+    /// Returns false if the upload of the buffer has been interrupted. See
+    /// `Buffer::new_streamed`.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.alloc.is_complete()
+    }
+
     /// Invalidates the content of the buffer. The data becomes undefined.
     ///
     /// This operation is a no-op if the backend doesn't support it and for persistent-mapped
@@ -1143,6 +1718,38 @@ impl BufferAny {
         self.fence.wait(&mut self.alloc.get_context().make_current(), 0 .. self.get_size());
         self.alloc.read::<T>(0 .. self.get_size())
     }
+
+    /// Writes the raw content of the buffer to `writer`.
+    ///
+    /// Contrary to `read`, the content is streamed in chunks of at most
+    /// `TRANSFER_CHUNK_SIZE` bytes, so the whole buffer is never held in memory at once.
+    /// The bytes are written in the native endianness of the machine.
+    ///
+    /// If reading the buffer fails, the `ReadError` is returned as an `io::Error` of kind
+    /// `Other`.
+    ///
+    /// # Implementation
+    ///
+    /// Reads from the mapping for persistent-mapped buffers and on OpenGL ES, where
+    /// `glGetBufferSubData` doesn't exist. Otherwise calls `glGetBufferSubData` for each chunk.
+    pub fn download_to_writer<W: ?Sized>(&self, writer: &mut W) -> io::Result<()>
+                                          where W: io::Write
+    {
+        self.fence.wait(&mut self.alloc.get_context().make_current(), 0 .. self.get_size());
+        unsafe { self.alloc.read_to_writer(0 .. self.get_size(), writer) }
+    }
+
+    /// Fills the buffer with raw bytes read from `reader`.
+    ///
+    /// Exactly `get_size()` bytes are read, in chunks of at most `TRANSFER_CHUNK_SIZE` bytes.
+    /// The bytes must be in the native endianness of the machine. Returns an error if the reader
+    /// doesn't contain enough data, in which case the content of the buffer is undefined.
+    pub fn upload_from_reader<R: ?Sized>(&self, reader: &mut R) -> io::Result<()>
+                                         where R: io::Read
+    {
+        self.fence.wait(&mut self.alloc.get_context().make_current(), 0 .. self.get_size());
+        unsafe { self.alloc.upload_from_reader(0 .. self.get_size(), reader) }
+    }
 }
 
 impl<T: ?Sized> From<Buffer<T>> for BufferAny where T: Content + Send + 'static {
@@ -1162,7 +1769,9 @@ impl<T: ?Sized> From<Buffer<T>> for BufferAny where T: Content + Send + 'static
 impl Drop for BufferAny {
     #[inline]
     fn drop(&mut self) {
-        self.fence.clean(&mut self.alloc.get_context().make_current());
+        if let Some(mut ctxt) = self.alloc.get_context().make_current_for_drop() {
+            self.fence.clean(&mut ctxt);
+        }
     }
 }
 
@@ -1173,6 +1782,14 @@ impl fmt::Debug for BufferAny {
     }
 }
 
+impl BufferAnyExt for BufferAny {
+    #[inline]
+    fn set_elements_size(&mut self, elements_size: usize) {
+        assert!(elements_size != 0 && self.size % elements_size == 0);
+        self.elements_size = elements_size;
+    }
+}
+
 impl BufferExt for BufferAny {
     #[inline]
     fn get_offset_bytes(&self) -> usize {
@@ -1300,6 +1917,13 @@ impl<'a> BufferAnySlice<'a> {
     pub fn get_context(&self) -> &Rc<Context> {
         self.alloc.get_context()
     }
+
+    /// Returns false if the upload of the buffer has been interrupted. See
+    /// `Buffer::new_streamed`.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.alloc.is_complete()
+    }
 }
 
 impl<'a> fmt::Debug for BufferAnySlice<'a> {
@@ -1388,7 +2012,7 @@ impl<'a> BufferExt for BufferAnySlice<'a> {
 
     #[inline]
     fn prepare_and_bind_for_uniform(&self, ctxt: &mut CommandContext, index: gl::types::GLuint) {
-        self.alloc.prepare_and_bind_for_uniform(ctxt, index, 0 .. self.alloc.get_size());
+        self.alloc.prepare_and_bind_for_uniform(ctxt, index, self.bytes_start .. self.bytes_end);
     }
 
     #[inline]
@@ -0,0 +1,247 @@
+use std::mem;
+use std::rc::Rc;
+
+use smallvec::SmallVec;
+
+use backend::Facade;
+use context::Context;
+use index::IndicesSource;
+use ops;
+use uniforms::{Uniforms, UniformValue};
+use vertex::{MultiVerticesSource, VerticesSource};
+
+use CommandListExt;
+use DrawError;
+use DrawParameters;
+use Program;
+
+/// A list of draw commands that is recorded once and can then be executed multiple times.
+///
+/// Each call to `draw` is validated when it is recorded. Executing the list with
+/// `Surface::draw_command_list` then runs the commands in the same order as if `Surface::draw`
+/// had been called for each of them, but skips the state changes that are known to be
+/// redundant.
+///
+/// The list only keeps references to the buffers, textures, programs and draw parameters that
+/// are used by the commands. Modifying the content of a buffer between two executions is
+/// therefore taken into account. The values of the uniforms, however, are copied when they are
+/// recorded.
+///
+/// ## Example
+///
+/// ```no_run
+/// # #[macro_use]
+/// # extern crate glium;
+/// # fn main() {
+/// # use glium::Surface;
+/// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+/// # let program: glium::Program = unsafe { ::std::mem::uninitialized() };
+/// # let vertex_buffer: glium::vertex::VertexBufferAny = unsafe { ::std::mem::uninitialized() };
+/// # let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+/// let uniforms = uniform! { color: [1.0, 0.0, 0.0f32] };
+/// let params = Default::default();
+///
+/// let mut list = glium::CommandList::new(&display);
+/// list.draw(&vertex_buffer, &indices, &program, &uniforms, &params).unwrap();
+///
+/// loop {
+///     let mut target = display.draw();
+///     target.clear_color(0.0, 0.0, 0.0, 0.0);
+///     target.draw_command_list(&list).unwrap();
+///     target.finish().unwrap();
+/// }
+/// # }
+/// ```
+pub struct CommandList<'a> {
+    context: Rc<Context>,
+    commands: Vec<DrawCommand<'a>>,
+}
+
+/// A command that has been recorded in a `CommandList`.
+pub struct DrawCommand<'a> {
+    /// The sources of vertices.
+    pub vertices: SmallVec<[VerticesSource<'a>; 2]>,
+
+    /// The source of indices.
+    pub indices: IndicesSource<'a>,
+
+    /// The program to draw with.
+    pub program: &'a Program,
+
+    /// The values of the uniforms.
+    pub uniforms: Rc<UniformsSnapshot<'a>>,
+
+    /// The draw parameters.
+    pub draw_parameters: &'a DrawParameters<'a>,
+
+    /// Number of vertices per patch if tessellation is used.
+    pub vertices_per_patch: Option<u16>,
+
+    /// True if the program is different from the one of the previous command.
+    pub program_changed: bool,
+
+    /// True if the uniforms must be bound again after the previous command.
+    pub uniforms_changed: bool,
+
+    /// True if the draw parameters must be synchronized again after the previous command.
+    pub parameters_changed: bool,
+
+    /// Address and size of the uniforms object that has been used for recording.
+    uniforms_source: (usize, usize),
+}
+
+/// Copy of the values of some uniforms.
+pub struct UniformsSnapshot<'a> {
+    values: Vec<(String, UniformValue<'a>)>,
+}
+
+impl<'a> Uniforms for UniformsSnapshot<'a> {
+    #[inline]
+    fn visit_values<'b, F: FnMut(&str, UniformValue<'b>)>(&'b self, mut output: F) {
+        for &(ref name, value) in self.values.iter() {
+            output(name, value);
+        }
+    }
+}
+
+impl<'a> CommandList<'a> {
+    /// Builds a new empty list of commands.
+    #[inline]
+    pub fn new<F>(facade: &F) -> CommandList<'a> where F: Facade {
+        CommandList {
+            context: facade.get_context().clone(),
+            commands: Vec::new(),
+        }
+    }
+
+    /// Records a draw command at the end of the list.
+    ///
+    /// The parameters are the same as `Surface::draw`. Returns an error if the command couldn't
+    /// be executed, in which case nothing is recorded. Some errors depend on the surface and
+    /// are only returned when the list is executed.
+    ///
+    /// Passing the same uniforms object as the previous command allows the list to skip
+    /// binding the uniforms again.
+    pub fn draw<V, I, U>(&mut self, vertex_buffer: V, index_buffer: I, program: &'a Program,
+                         uniforms: &'a U, draw_parameters: &'a DrawParameters<'a>)
+                         -> Result<(), DrawError>
+                         where V: MultiVerticesSource<'a>, I: Into<IndicesSource<'a>>,
+                               U: Uniforms
+    {
+        let indices = index_buffer.into();
+        let vertices_per_patch = try!(ops::check_program(&self.context, program, &indices,
+                                                         draw_parameters));
+
+        let mut vertices = SmallVec::new();
+        for src in vertex_buffer.iter() {
+            vertices.push(src);
+        }
+        try!(ops::check_buffers_context(&self.context, &vertices, &indices));
+
+        let (vertices_count, _) = try!(ops::count_vertices(&vertices, &*self.context));
+        if let IndicesSource::NoIndices { .. } = indices {
+            if vertices_count.is_none() {
+                return Err(DrawError::VerticesSourcesLengthMismatch);
+            }
+        }
+
+        let uniforms_source = (uniforms as *const U as usize, mem::size_of::<U>());
+
+        let (program_changed, uniforms, uniforms_changed, parameters_changed) = {
+            let previous = self.commands.last();
+
+            let program_changed = previous.map(|p| {
+                p.program as *const Program != program as *const Program
+            }).unwrap_or(true);
+
+            let (uniforms, uniforms_changed) = match previous {
+                Some(p) if !program_changed && p.uniforms_source == uniforms_source => {
+                    (p.uniforms.clone(), false)
+                },
+                _ => {
+                    let snapshot = try!(UniformsSnapshot::new(uniforms, program));
+                    (Rc::new(snapshot), true)
+                },
+            };
+
+            let parameters_changed = program_changed || previous.map(|p| {
+                p.draw_parameters as *const DrawParameters != draw_parameters as *const _ ||
+                p.indices.get_primitives_type() != indices.get_primitives_type()
+            }).unwrap_or(true);
+
+            (program_changed, uniforms, uniforms_changed, parameters_changed)
+        };
+
+        self.commands.push(DrawCommand {
+            vertices: vertices,
+            indices: indices,
+            program: program,
+            uniforms: uniforms,
+            draw_parameters: draw_parameters,
+            vertices_per_patch: vertices_per_patch,
+            program_changed: program_changed,
+            uniforms_changed: uniforms_changed,
+            parameters_changed: parameters_changed,
+            uniforms_source: uniforms_source,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the number of commands in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if the list doesn't contain any command.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Removes all the commands from the list.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+}
+
+impl<'a> CommandListExt<'a> for CommandList<'a> {
+    #[inline]
+    fn get_commands(&self) -> &[DrawCommand<'a>] {
+        &self.commands
+    }
+}
+
+impl<'a> UniformsSnapshot<'a> {
+    /// Copies the values of the uniforms and checks their types against the program.
+    fn new<U>(uniforms: &'a U, program: &Program) -> Result<UniformsSnapshot<'a>, DrawError>
+              where U: Uniforms
+    {
+        let mut values = Vec::new();
+        let mut result = Ok(());
+
+        uniforms.visit_values(|name, value| {
+            if result.is_err() { return; }
+
+            if let Some(uniform) = program.get_uniform(name) {
+                if !value.is_usable_with(&uniform.ty) {
+                    result = Err(DrawError::UniformTypeMismatch {
+                        name: name.to_owned(),
+                        expected: uniform.ty,
+                    });
+                    return;
+                }
+            }
+
+            values.push((name.to_owned(), value));
+        });
+
+        try!(result);
+
+        Ok(UniformsSnapshot {
+            values: values,
+        })
+    }
+}
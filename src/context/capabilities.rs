@@ -29,12 +29,20 @@ pub struct Capabilities {
     /// True if it is possible for the OpenGL context to be lost.
     pub can_lose_context: bool,
 
+    /// True if buffers, textures and framebuffers can be created and modified without being
+    /// bound (`GL_ARB_direct_state_access`).
+    pub direct_state_access: bool,
+
     /// What happens when you change the current OpenGL context.
     pub release_behavior: ReleaseBehavior,
 
     /// Whether the context supports left and right buffers.
     pub stereo: bool,
 
+    /// True if the default framebuffer has a back buffer. Single-buffered contexts only have a
+    /// front buffer, and finishing a frame flushes the commands instead of swapping buffers.
+    pub double_buffer: bool,
+
     /// True if the default framebuffer is in sRGB.
     pub srgb: bool,
 
@@ -63,12 +71,28 @@ pub struct Capabilities {
     /// Maximum size of a buffer texture. `None` if this is not supported.
     pub max_texture_buffer_size: Option<gl::types::GLint>,
 
+    /// Maximum width and height of a 1D or 2D texture.
+    pub max_texture_size: gl::types::GLint,
+
+    /// Maximum width, height and depth of a 3D texture. `0` if 3D textures are not supported.
+    pub max_3d_texture_size: gl::types::GLint,
+
+    /// Maximum width and height of a cubemap.
+    pub max_cube_map_texture_size: gl::types::GLint,
+
+    /// Maximum number of layers of an array texture. `0` if array textures are not supported.
+    pub max_array_texture_layers: gl::types::GLint,
+
     /// Maximum width and height of `glViewport`.
     pub max_viewport_dims: (gl::types::GLint, gl::types::GLint),
 
     /// Maximum number of elements that can be passed with `glDrawBuffers`.
     pub max_draw_buffers: gl::types::GLint,
 
+    /// Maximum number of draw buffers when dual-source blending is used. `0` if dual-source
+    /// blending is not supported.
+    pub max_dual_source_draw_buffers: gl::types::GLint,
+
     /// Maximum number of vertices per patch. `None` if tessellation is not supported.
     pub max_patch_vertices: Option<gl::types::GLint>,
 
@@ -84,12 +108,19 @@ pub struct Capabilities {
     /// Number of available buffer bind points for `GL_UNIFORM_BUFFER`.
     pub max_indexed_uniform_buffer: gl::types::GLint,
 
+    /// Value of `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`. Offsets of buffers bound to uniform
+    /// blocks must be a multiple of this value. `1` if uniform buffers are not supported.
+    pub uniform_buffer_offset_alignment: gl::types::GLint,
+
     /// Number of work groups for compute shaders.
     pub max_compute_work_group_count: (gl::types::GLint, gl::types::GLint, gl::types::GLint),
 
     /// Maximum number of color attachment bind points.
     pub max_color_attachments: gl::types::GLint,
 
+    /// Maximum number of clip distances that can be enabled. `0` if not supported.
+    pub max_clip_distances: gl::types::GLint,
+
     /// Maximum width of an empty framebuffer. `None` if not supported.
     pub max_framebuffer_width: Option<gl::types::GLint>,
 
@@ -186,6 +217,9 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             false
         },
 
+        direct_state_access: version >= &Version(Api::Gl, 4, 5) ||
+                             extensions.gl_arb_direct_state_access,
+
         release_behavior: if extensions.gl_khr_context_flush_control {
             let mut val = mem::uninitialized();
             gl.GetIntegerv(gl::CONTEXT_RELEASE_BEHAVIOR, &mut val);
@@ -210,6 +244,17 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             }
         },
 
+        // OpenGL ES can't tell, the backend is asked instead when creating the context
+        double_buffer: {
+            if version >= &Version(Api::Gl, 1, 0) {
+                let mut val: gl::types::GLboolean = mem::uninitialized();
+                gl.GetBooleanv(gl::DOUBLEBUFFER, &mut val);
+                val != 0
+            } else {
+                true
+            }
+        },
+
         srgb: {
             // `glGetFramebufferAttachmentParameteriv` incorrectly returns GL_INVALID_ENUM on some
             // drivers, so we prefer using `glGetIntegerv` if possible.
@@ -345,6 +390,42 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             (val[0], val[1])
         },
 
+        max_texture_size: {
+            let mut val = 64;
+            gl.GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut val);
+            val
+        },
+
+        max_3d_texture_size: {
+            if version >= &Version(Api::Gl, 1, 2) || version >= &Version(Api::GlEs, 3, 0) ||
+               extensions.gl_oes_texture_3d
+            {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_3D_TEXTURE_SIZE, &mut val);
+                val
+            } else {
+                0
+            }
+        },
+
+        max_cube_map_texture_size: {
+            let mut val = 16;
+            gl.GetIntegerv(gl::MAX_CUBE_MAP_TEXTURE_SIZE, &mut val);
+            val
+        },
+
+        max_array_texture_layers: {
+            if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+               extensions.gl_ext_texture_array
+            {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_ARRAY_TEXTURE_LAYERS, &mut val);
+                val
+            } else {
+                0
+            }
+        },
+
         max_draw_buffers: {
             if version >= &Version(Api::Gl, 2, 0) ||
                 version >= &Version(Api::GlEs, 3, 0) ||
@@ -358,6 +439,18 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             }
         },
 
+        max_dual_source_draw_buffers: {
+            if version >= &Version(Api::Gl, 3, 3) || extensions.gl_arb_blend_func_extended ||
+               extensions.gl_ext_blend_func_extended
+            {
+                let mut val = 1;
+                gl.GetIntegerv(gl::MAX_DUAL_SOURCE_DRAW_BUFFERS, &mut val);
+                val
+            } else {
+                0
+            }
+        },
+
         max_patch_vertices: if version >= &Version(Api::Gl, 4, 0) ||
             extensions.gl_arb_tessellation_shader
         {
@@ -413,6 +506,18 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             }
         },
 
+        uniform_buffer_offset_alignment: {
+            if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+               extensions.gl_arb_uniform_buffer_object
+            {
+                let mut val = 1;
+                gl.GetIntegerv(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT, &mut val);
+                val
+            } else {
+                1
+            }
+        },
+
         max_compute_work_group_count: if version >= &Version(Api::Gl, 4, 3) ||
                                          version >= &Version(Api::GlEs, 3, 1) ||
                                          extensions.gl_arb_compute_shader
@@ -445,6 +550,19 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             }
         },
 
+        max_clip_distances: {
+            if version >= &Version(Api::Gl, 1, 0) || extensions.gl_ext_clip_cull_distance ||
+               extensions.gl_apple_clip_distance
+            {
+                // `GL_MAX_CLIP_DISTANCES` has the same value as the old `GL_MAX_CLIP_PLANES`
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_CLIP_DISTANCES, &mut val);
+                val
+            } else {
+                0
+            }
+        },
+
         max_framebuffer_width: {
             if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 1) ||
                extensions.gl_arb_framebuffer_no_attachments
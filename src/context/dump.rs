@@ -0,0 +1,604 @@
+use std::ffi::CStr;
+use std::fmt;
+
+use gl;
+use Handle;
+
+use context::CommandContext;
+use version::Api;
+use version::Version;
+
+/// A snapshot of the state of a context, returned by `Context::dump_state`.
+///
+/// The `Display` implementation of this struct produces a text that can be attached to a bug
+/// report.
+#[derive(Debug, Clone)]
+pub struct StateSnapshot {
+    /// Version of the OpenGL context.
+    pub version: Version,
+
+    /// Value of `GL_VENDOR`.
+    pub vendor: String,
+
+    /// Value of `GL_RENDERER`.
+    pub renderer: String,
+
+    /// Versions of GLSL supported by the compiler.
+    pub supported_glsl_versions: Vec<Version>,
+
+    /// Names of the extensions that are supported by the backend and used by glium.
+    pub extensions: Vec<&'static str>,
+
+    /// True if glium has detected that the context has been lost.
+    pub lost_context: bool,
+
+    /// The elements of the state that glium keeps track of.
+    pub entries: Vec<StateEntry>,
+}
+
+impl StateSnapshot {
+    /// Returns the entries whose value returned by OpenGL is different from the one that glium
+    /// has cached.
+    #[inline]
+    pub fn get_divergences(&self) -> Vec<&StateEntry> {
+        self.entries.iter().filter(|e| e.is_divergent()).collect()
+    }
+}
+
+impl fmt::Display for StateSnapshot {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let api = |version: &Version| match version.0 {
+            Api::Gl => "OpenGL",
+            Api::GlEs => "OpenGL ES",
+        };
+
+        try!(writeln!(formatter, "{} {}.{}", api(&self.version), self.version.1,
+                      self.version.2));
+        try!(writeln!(formatter, "Vendor: {}", self.vendor));
+        try!(writeln!(formatter, "Renderer: {}", self.renderer));
+
+        try!(write!(formatter, "GLSL versions:"));
+        for version in self.supported_glsl_versions.iter() {
+            try!(write!(formatter, " {} {}.{}", api(version), version.1, version.2));
+        }
+        try!(writeln!(formatter, ""));
+
+        try!(writeln!(formatter, "Extensions: {}", self.extensions.join(" ")));
+
+        if self.lost_context {
+            try!(writeln!(formatter, "The context has been lost"));
+        }
+
+        try!(writeln!(formatter, ""));
+
+        for entry in self.entries.iter() {
+            try!(write!(formatter, "{} = {}", entry.name, entry.cached));
+
+            if entry.is_divergent() {
+                try!(write!(formatter, " (DIVERGENT: OpenGL returned {})",
+                            entry.actual.as_ref().unwrap()));
+            }
+
+            try!(writeln!(formatter, ""));
+        }
+
+        Ok(())
+    }
+}
+
+/// An element of the state of a context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateEntry {
+    /// Name of the element. This is usually the name of the constant passed to `glGet`.
+    pub name: String,
+
+    /// The value that glium has cached.
+    pub cached: StateValue,
+
+    /// The value returned by OpenGL. `None` if the state hasn't been cross-checked, or if this
+    /// element can't be queried on this context.
+    pub actual: Option<StateValue>,
+}
+
+impl StateEntry {
+    /// Returns true if the value returned by OpenGL is different from the one that glium has
+    /// cached.
+    ///
+    /// This means that there is either a bug in glium, or that OpenGL functions have been
+    /// called without glium knowing about it.
+    #[inline]
+    pub fn is_divergent(&self) -> bool {
+        match self.actual {
+            Some(ref actual) => !self.cached.matches(actual),
+            None => false,
+        }
+    }
+}
+
+/// Value of an element of the state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateValue {
+    /// A boolean, for example whether a capability is enabled.
+    Bool(bool),
+
+    /// One or more integers, for example the ID of an object or a rectangle.
+    Integers(Vec<i64>),
+
+    /// One or more floats, for example a color.
+    Floats(Vec<f32>),
+
+    /// A `GLenum`.
+    Enum(gl::types::GLenum),
+
+    /// glium doesn't know the value and sets it every time it needs it.
+    Unknown,
+}
+
+impl StateValue {
+    /// Returns true if the cached value `self` is compatible with the value returned by
+    /// OpenGL.
+    fn matches(&self, actual: &StateValue) -> bool {
+        match (self, actual) {
+            (&StateValue::Unknown, _) => true,
+            (&StateValue::Floats(ref a), &StateValue::Floats(ref b)) => {
+                // some values like the clear color are stored in a lower precision
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 0.01)
+            },
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl fmt::Display for StateValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &StateValue::Bool(value) => write!(formatter, "{}", value),
+            &StateValue::Integers(ref values) if values.len() == 1 => {
+                write!(formatter, "{}", values[0])
+            },
+            &StateValue::Integers(ref values) => write!(formatter, "{:?}", values),
+            &StateValue::Floats(ref values) if values.len() == 1 => {
+                write!(formatter, "{}", values[0])
+            },
+            &StateValue::Floats(ref values) => write!(formatter, "{:?}", values),
+            &StateValue::Enum(value) => write!(formatter, "0x{:04x}", value),
+            &StateValue::Unknown => write!(formatter, "unknown"),
+        }
+    }
+}
+
+/// How to retreive the actual value of an element of the state.
+#[derive(Copy, Clone)]
+enum Query {
+    /// `glIsEnabled`.
+    Enabled(gl::types::GLenum),
+    /// `glGetIntegerv` returning the given number of values.
+    Integers(gl::types::GLenum, usize),
+    /// `glGetIntegerv` returning an unsigned value, like a mask.
+    Unsigned(gl::types::GLenum),
+    /// `glGetIntegerv` returning a `GLenum`.
+    Enum(gl::types::GLenum),
+    /// `glGetFloatv` returning the given number of values.
+    Floats(gl::types::GLenum, usize),
+    /// `glGetBooleanv` returning the given number of values.
+    Booleans(gl::types::GLenum, usize),
+    /// `glGetIntegeri_v`.
+    Indexed(gl::types::GLenum, gl::types::GLuint),
+}
+
+impl Query {
+    unsafe fn execute(&self, gl: &gl::Gl) -> StateValue {
+        match *self {
+            Query::Enabled(cap) => StateValue::Bool(gl.IsEnabled(cap) != 0),
+
+            Query::Integers(pname, num) => {
+                let mut values = [0; 4];
+                gl.GetIntegerv(pname, values.as_mut_ptr());
+                StateValue::Integers(values[.. num].iter().map(|&v| v as i64).collect())
+            },
+
+            Query::Unsigned(pname) => {
+                let mut value = 0;
+                gl.GetIntegerv(pname, &mut value);
+                StateValue::Integers(vec![value as gl::types::GLuint as i64])
+            },
+
+            Query::Enum(pname) => {
+                let mut value = 0;
+                gl.GetIntegerv(pname, &mut value);
+                StateValue::Enum(value as gl::types::GLenum)
+            },
+
+            Query::Floats(pname, num) => {
+                let mut values = [0.0; 4];
+                gl.GetFloatv(pname, values.as_mut_ptr());
+                StateValue::Floats(values[.. num].to_vec())
+            },
+
+            Query::Booleans(pname, num) if num == 1 => {
+                let mut value = 0;
+                gl.GetBooleanv(pname, &mut value);
+                StateValue::Bool(value != 0)
+            },
+
+            Query::Booleans(pname, num) => {
+                let mut values = [0; 4];
+                gl.GetBooleanv(pname, values.as_mut_ptr());
+                StateValue::Integers(values[.. num].iter().map(|&v| v as i64).collect())
+            },
+
+            Query::Indexed(pname, index) => {
+                let mut value = 0;
+                gl.GetIntegeri_v(pname, index, &mut value);
+                StateValue::Integers(vec![value as i64])
+            },
+        }
+    }
+}
+
+/// Builds the list of entries.
+struct EntriesBuilder<'a> {
+    gl: &'a gl::Gl,
+    cross_check: bool,
+    entries: Vec<StateEntry>,
+}
+
+impl<'a> EntriesBuilder<'a> {
+    /// Adds an entry. `query` is `None` if the element can't be queried on this context.
+    fn add<S>(&mut self, name: S, cached: StateValue, query: Option<Query>)
+              where S: Into<String>
+    {
+        let actual = match query {
+            Some(query) if self.cross_check => Some(unsafe { query.execute(self.gl) }),
+            _ => None,
+        };
+
+        self.entries.push(StateEntry {
+            name: name.into(),
+            cached: cached,
+            actual: actual,
+        });
+    }
+}
+
+#[inline]
+fn int(value: i64) -> StateValue {
+    StateValue::Integers(vec![value])
+}
+
+#[inline]
+fn when(condition: bool, query: Query) -> Option<Query> {
+    if condition { Some(query) } else { None }
+}
+
+/// Builds a snapshot of the state of the context.
+///
+/// If `cross_check` is true, every element of the state that glium keeps track of and that
+/// can be queried is compared with the value returned by `glGet*`.
+pub fn take_snapshot(ctxt: &CommandContext, cross_check: bool) -> StateSnapshot {
+    let version = ctxt.version;
+    let extensions = ctxt.extensions;
+    let state = &ctxt.state;
+
+    let get_string = |name| unsafe {
+        let s = ctxt.gl.GetString(name);
+        if s.is_null() {
+            String::new()
+        } else {
+            String::from_utf8_lossy(CStr::from_ptr(s as *const _).to_bytes()).into_owned()
+        }
+    };
+
+    let vendor = get_string(gl::VENDOR);
+    let renderer = get_string(gl::RENDERER);
+
+    let desktop = version.0 == Api::Gl;
+    let gl2 = version >= &Version(Api::Gl, 2, 0) || version >= &Version(Api::GlEs, 2, 0);
+    let gl3 = version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0);
+    let gl31 = version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 0);
+    let gl32 = version >= &Version(Api::Gl, 3, 2);
+    let gl40 = version >= &Version(Api::Gl, 4, 0) || version >= &Version(Api::GlEs, 3, 1);
+    let gl42 = version >= &Version(Api::Gl, 4, 2) || version >= &Version(Api::GlEs, 3, 1);
+    let gl43 = version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 1);
+    let gl44 = version >= &Version(Api::Gl, 4, 4);
+    let vao = gl3 || extensions.gl_arb_vertex_array_object;
+    let pbo = version >= &Version(Api::Gl, 2, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+              extensions.gl_arb_pixel_buffer_object;
+    let fbo = gl3 || extensions.gl_arb_framebuffer_object;
+    let renderbuffers = fbo || version >= &Version(Api::GlEs, 2, 0);
+
+    let mut b = EntriesBuilder {
+        gl: ctxt.gl,
+        cross_check: cross_check,
+        entries: Vec::new(),
+    };
+
+    // program and vertex array
+    let program = match state.program {
+        Handle::Id(id) => id as i64,
+        Handle::Handle(handle) => handle as usize as i64,
+    };
+    b.add("GL_CURRENT_PROGRAM", int(program), when(gl2, Query::Integers(gl::CURRENT_PROGRAM, 1)));
+    b.add("GL_VERTEX_ARRAY_BINDING", int(state.vertex_array as i64),
+          when(vao, Query::Integers(gl::VERTEX_ARRAY_BINDING, 1)));
+
+    // buffers
+    let buffers = [
+        ("GL_ARRAY_BUFFER_BINDING", state.array_buffer_binding, gl::ARRAY_BUFFER_BINDING, true),
+        ("GL_PIXEL_PACK_BUFFER_BINDING", state.pixel_pack_buffer_binding,
+         gl::PIXEL_PACK_BUFFER_BINDING, pbo),
+        ("GL_PIXEL_UNPACK_BUFFER_BINDING", state.pixel_unpack_buffer_binding,
+         gl::PIXEL_UNPACK_BUFFER_BINDING, pbo),
+        ("GL_UNIFORM_BUFFER_BINDING", state.uniform_buffer_binding,
+         gl::UNIFORM_BUFFER_BINDING, gl31),
+        ("GL_COPY_READ_BUFFER_BINDING", state.copy_read_buffer_binding,
+         gl::COPY_READ_BUFFER_BINDING, gl31),
+        ("GL_COPY_WRITE_BUFFER_BINDING", state.copy_write_buffer_binding,
+         gl::COPY_WRITE_BUFFER_BINDING, gl31),
+        ("GL_DRAW_INDIRECT_BUFFER_BINDING", state.draw_indirect_buffer_binding,
+         gl::DRAW_INDIRECT_BUFFER_BINDING, gl40),
+        ("GL_ATOMIC_COUNTER_BUFFER_BINDING", state.atomic_counter_buffer_binding,
+         gl::ATOMIC_COUNTER_BUFFER_BINDING, gl42),
+        ("GL_DISPATCH_INDIRECT_BUFFER_BINDING", state.dispatch_indirect_buffer_binding,
+         gl::DISPATCH_INDIRECT_BUFFER_BINDING, gl43),
+        ("GL_SHADER_STORAGE_BUFFER_BINDING", state.shader_storage_buffer_binding,
+         gl::SHADER_STORAGE_BUFFER_BINDING, gl43),
+        ("GL_QUERY_BUFFER_BINDING", state.query_buffer_binding,
+         gl::QUERY_BUFFER_BINDING, gl44),
+    ];
+
+    for &(name, cached, pname, supported) in buffers.iter() {
+        b.add(name, int(cached as i64), when(supported, Query::Integers(pname, 1)));
+    }
+
+    // the texture buffer binding is queried with different names depending on the version
+    b.add("GL_TEXTURE_BUFFER_BINDING", int(state.texture_buffer_binding as i64), None);
+
+    let indexed = [
+        ("GL_UNIFORM_BUFFER_BINDING", &state.indexed_uniform_buffer_bindings[..],
+         gl::UNIFORM_BUFFER_BINDING, gl31),
+        ("GL_ATOMIC_COUNTER_BUFFER_BINDING", &state.indexed_atomic_counter_buffer_bindings[..],
+         gl::ATOMIC_COUNTER_BUFFER_BINDING, gl42),
+        ("GL_SHADER_STORAGE_BUFFER_BINDING", &state.indexed_shader_storage_buffer_bindings[..],
+         gl::SHADER_STORAGE_BUFFER_BINDING, gl43),
+        ("GL_TRANSFORM_FEEDBACK_BUFFER_BINDING",
+         &state.indexed_transform_feedback_buffer_bindings[..],
+         gl::TRANSFORM_FEEDBACK_BUFFER_BINDING, gl3),
+    ];
+
+    for &(name, bindings, pname, supported) in indexed.iter() {
+        for (index, binding) in bindings.iter().enumerate() {
+            b.add(format!("{}[{}]", name, index), int(binding.buffer as i64),
+                  when(supported, Query::Indexed(pname, index as gl::types::GLuint)));
+        }
+    }
+
+    // framebuffers
+    if fbo {
+        b.add("GL_DRAW_FRAMEBUFFER_BINDING", int(state.draw_framebuffer as i64),
+              Some(Query::Integers(gl::DRAW_FRAMEBUFFER_BINDING, 1)));
+        b.add("GL_READ_FRAMEBUFFER_BINDING", int(state.read_framebuffer as i64),
+              Some(Query::Integers(gl::READ_FRAMEBUFFER_BINDING, 1)));
+    } else {
+        // there is only one binding point, and glium updates both values when it changes
+        let query = when(renderbuffers, Query::Integers(gl::FRAMEBUFFER_BINDING, 1));
+        b.add("GL_DRAW_FRAMEBUFFER_BINDING", int(state.draw_framebuffer as i64), query);
+        b.add("GL_READ_FRAMEBUFFER_BINDING", int(state.read_framebuffer as i64), query);
+    }
+
+    b.add("GL_RENDERBUFFER_BINDING", int(state.renderbuffer as i64),
+          when(renderbuffers, Query::Integers(gl::RENDERBUFFER_BINDING, 1)));
+
+    b.add("GL_READ_BUFFER (default framebuffer)",
+          state.default_framebuffer_read.map(StateValue::Enum).unwrap_or(StateValue::Unknown),
+          None);
+
+    // texture units
+    b.add("GL_ACTIVE_TEXTURE", StateValue::Enum(gl::TEXTURE0 + state.active_texture),
+          Some(Query::Enum(gl::ACTIVE_TEXTURE)));
+
+    // checking the textures would require changing the active texture unit, and glium doesn't
+    // remember the target that each texture is bound to
+    for (unit, s) in state.texture_units.iter().enumerate() {
+        if s.texture != 0 || s.sampler != 0 {
+            b.add(format!("texture unit {} (texture, sampler)", unit),
+                  StateValue::Integers(vec![s.texture as i64, s.sampler as i64]), None);
+        }
+    }
+
+    // capabilities
+    let capabilities = [
+        ("GL_BLEND", state.enabled_blend, gl::BLEND, true),
+        ("GL_COLOR_LOGIC_OP", state.enabled_color_logic_op, gl::COLOR_LOGIC_OP, desktop),
+        ("GL_CULL_FACE", state.enabled_cull_face, gl::CULL_FACE, true),
+        ("GL_DEPTH_TEST", state.enabled_depth_test, gl::DEPTH_TEST, true),
+        ("GL_DITHER", state.enabled_dither, gl::DITHER, true),
+        ("GL_FRAMEBUFFER_SRGB", state.enabled_framebuffer_srgb, gl::FRAMEBUFFER_SRGB,
+         desktop && gl3),
+        ("GL_MULTISAMPLE", state.enabled_multisample, gl::MULTISAMPLE, desktop),
+        ("GL_POLYGON_OFFSET_FILL", state.enabled_polygon_offset_fill, gl::POLYGON_OFFSET_FILL,
+         true),
+        ("GL_RASTERIZER_DISCARD", state.enabled_rasterizer_discard, gl::RASTERIZER_DISCARD,
+         gl3),
+        ("GL_SAMPLE_ALPHA_TO_COVERAGE", state.enabled_sample_alpha_to_coverage,
+         gl::SAMPLE_ALPHA_TO_COVERAGE, true),
+        ("GL_SAMPLE_COVERAGE", state.enabled_sample_coverage, gl::SAMPLE_COVERAGE, true),
+        ("GL_SCISSOR_TEST", state.enabled_scissor_test, gl::SCISSOR_TEST, true),
+        ("GL_STENCIL_TEST", state.enabled_stencil_test, gl::STENCIL_TEST, true),
+        ("GL_LINE_SMOOTH", state.enabled_line_smooth, gl::LINE_SMOOTH, desktop),
+        ("GL_POLYGON_SMOOTH", state.enabled_polygon_smooth, gl::POLYGON_SMOOTH, desktop),
+        ("GL_PROGRAM_POINT_SIZE", state.enabled_program_point_size, gl::PROGRAM_POINT_SIZE,
+         gl32),
+    ];
+
+    for &(name, cached, cap, supported) in capabilities.iter() {
+        b.add(name, StateValue::Bool(cached), when(supported, Query::Enabled(cap)));
+    }
+
+    // `GL_DEPTH_CLAMP` can only be queried when both sides are clamped the same way
+    let depth_clamp = gl32 || extensions.gl_arb_depth_clamp;
+    b.add("GL_DEPTH_CLAMP (near, far)",
+          StateValue::Integers(vec![state.enabled_depth_clamp_near as i64,
+                                    state.enabled_depth_clamp_far as i64]), None);
+    if state.enabled_depth_clamp_near == state.enabled_depth_clamp_far {
+        b.add("GL_DEPTH_CLAMP", StateValue::Bool(state.enabled_depth_clamp_near),
+              when(depth_clamp, Query::Enabled(gl::DEPTH_CLAMP)));
+    }
+
+    for index in 0 .. 32 {
+        if state.enabled_clip_planes & (1 << index) != 0 {
+            b.add(format!("GL_CLIP_DISTANCE{}", index), StateValue::Bool(true),
+                  Some(Query::Enabled(gl::CLIP_DISTANCE0 + index)));
+        }
+    }
+
+    // clear values and masks
+    let (r, g, b_, a) = state.clear_color;
+    b.add("GL_COLOR_CLEAR_VALUE", StateValue::Floats(vec![r, g, b_, a]),
+          Some(Query::Floats(gl::COLOR_CLEAR_VALUE, 4)));
+    b.add("GL_DEPTH_CLEAR_VALUE", StateValue::Floats(vec![state.clear_depth]),
+          Some(Query::Floats(gl::DEPTH_CLEAR_VALUE, 1)));
+    b.add("GL_STENCIL_CLEAR_VALUE", int(state.clear_stencil as i64),
+          Some(Query::Integers(gl::STENCIL_CLEAR_VALUE, 1)));
+
+    let (r, g, b_, a) = state.color_mask;
+    b.add("GL_COLOR_WRITEMASK",
+          StateValue::Integers(vec![r as i64, g as i64, b_ as i64, a as i64]),
+          Some(Query::Booleans(gl::COLOR_WRITEMASK, 4)));
+
+    // blending
+    b.add("GL_BLEND_EQUATION_RGB", StateValue::Enum(state.blend_equation.0),
+          Some(Query::Enum(gl::BLEND_EQUATION_RGB)));
+    b.add("GL_BLEND_EQUATION_ALPHA", StateValue::Enum(state.blend_equation.1),
+          Some(Query::Enum(gl::BLEND_EQUATION_ALPHA)));
+    b.add("GL_BLEND_SRC_RGB", StateValue::Enum(state.blend_func.0),
+          Some(Query::Enum(gl::BLEND_SRC_RGB)));
+    b.add("GL_BLEND_DST_RGB", StateValue::Enum(state.blend_func.1),
+          Some(Query::Enum(gl::BLEND_DST_RGB)));
+    b.add("GL_BLEND_SRC_ALPHA", StateValue::Enum(state.blend_func.2),
+          Some(Query::Enum(gl::BLEND_SRC_ALPHA)));
+    b.add("GL_BLEND_DST_ALPHA", StateValue::Enum(state.blend_func.3),
+          Some(Query::Enum(gl::BLEND_DST_ALPHA)));
+    let (r, g, b_, a) = state.blend_color;
+    b.add("GL_BLEND_COLOR", StateValue::Floats(vec![r, g, b_, a]),
+          Some(Query::Floats(gl::BLEND_COLOR, 4)));
+    b.add("GL_LOGIC_OP_MODE", StateValue::Enum(state.logic_op),
+          when(desktop, Query::Enum(gl::LOGIC_OP_MODE)));
+
+    // depth
+    b.add("GL_DEPTH_FUNC", StateValue::Enum(state.depth_func),
+          Some(Query::Enum(gl::DEPTH_FUNC)));
+    b.add("GL_DEPTH_WRITEMASK", StateValue::Bool(state.depth_mask),
+          Some(Query::Booleans(gl::DEPTH_WRITEMASK, 1)));
+    b.add("GL_DEPTH_RANGE", StateValue::Floats(vec![state.depth_range.0, state.depth_range.1]),
+          Some(Query::Floats(gl::DEPTH_RANGE, 2)));
+
+    // stencil
+    let stencil = [
+        ("GL_STENCIL_FUNC", StateValue::Enum(state.stencil_func_front.0),
+         Query::Enum(gl::STENCIL_FUNC)),
+        ("GL_STENCIL_REF", int(state.stencil_func_front.1 as i64),
+         Query::Integers(gl::STENCIL_REF, 1)),
+        ("GL_STENCIL_VALUE_MASK", int(state.stencil_func_front.2 as i64),
+         Query::Unsigned(gl::STENCIL_VALUE_MASK)),
+        ("GL_STENCIL_BACK_FUNC", StateValue::Enum(state.stencil_func_back.0),
+         Query::Enum(gl::STENCIL_BACK_FUNC)),
+        ("GL_STENCIL_BACK_REF", int(state.stencil_func_back.1 as i64),
+         Query::Integers(gl::STENCIL_BACK_REF, 1)),
+        ("GL_STENCIL_BACK_VALUE_MASK", int(state.stencil_func_back.2 as i64),
+         Query::Unsigned(gl::STENCIL_BACK_VALUE_MASK)),
+        ("GL_STENCIL_WRITEMASK", int(state.stencil_mask_front as i64),
+         Query::Unsigned(gl::STENCIL_WRITEMASK)),
+        ("GL_STENCIL_BACK_WRITEMASK", int(state.stencil_mask_back as i64),
+         Query::Unsigned(gl::STENCIL_BACK_WRITEMASK)),
+        ("GL_STENCIL_FAIL", StateValue::Enum(state.stencil_op_front.0),
+         Query::Enum(gl::STENCIL_FAIL)),
+        ("GL_STENCIL_PASS_DEPTH_FAIL", StateValue::Enum(state.stencil_op_front.1),
+         Query::Enum(gl::STENCIL_PASS_DEPTH_FAIL)),
+        ("GL_STENCIL_PASS_DEPTH_PASS", StateValue::Enum(state.stencil_op_front.2),
+         Query::Enum(gl::STENCIL_PASS_DEPTH_PASS)),
+        ("GL_STENCIL_BACK_FAIL", StateValue::Enum(state.stencil_op_back.0),
+         Query::Enum(gl::STENCIL_BACK_FAIL)),
+        ("GL_STENCIL_BACK_PASS_DEPTH_FAIL", StateValue::Enum(state.stencil_op_back.1),
+         Query::Enum(gl::STENCIL_BACK_PASS_DEPTH_FAIL)),
+        ("GL_STENCIL_BACK_PASS_DEPTH_PASS", StateValue::Enum(state.stencil_op_back.2),
+         Query::Enum(gl::STENCIL_BACK_PASS_DEPTH_PASS)),
+    ];
+
+    for &(name, ref cached, query) in stencil.iter() {
+        b.add(name, cached.clone(), when(gl2, query));
+    }
+
+    // rasterization
+    let rect = |r: Option<(i32, i32, i32, i32)>| match r {
+        Some((x, y, w, h)) => StateValue::Integers(vec![x as i64, y as i64, w as i64, h as i64]),
+        None => StateValue::Unknown,
+    };
+
+    b.add("GL_VIEWPORT", rect(state.viewport), Some(Query::Integers(gl::VIEWPORT, 4)));
+    b.add("GL_SCISSOR_BOX", rect(state.scissor), Some(Query::Integers(gl::SCISSOR_BOX, 4)));
+    b.add("GL_LINE_WIDTH", StateValue::Floats(vec![state.line_width]),
+          Some(Query::Floats(gl::LINE_WIDTH, 1)));
+    b.add("GL_POINT_SIZE", StateValue::Floats(vec![state.point_size]),
+          when(desktop, Query::Floats(gl::POINT_SIZE, 1)));
+    b.add("GL_CULL_FACE_MODE", StateValue::Enum(state.cull_face),
+          Some(Query::Enum(gl::CULL_FACE_MODE)));
+    // `GL_POLYGON_MODE` returns one or two values depending on the profile
+    b.add("GL_POLYGON_MODE", StateValue::Enum(state.polygon_mode), None);
+    b.add("GL_LINE_SMOOTH_HINT", StateValue::Enum(state.smooth.0),
+          when(desktop, Query::Enum(gl::LINE_SMOOTH_HINT)));
+    b.add("GL_POLYGON_SMOOTH_HINT", StateValue::Enum(state.smooth.1),
+          when(desktop, Query::Enum(gl::POLYGON_SMOOTH_HINT)));
+    b.add("GL_FRAGMENT_SHADER_DERIVATIVE_HINT", StateValue::Enum(state.derivative_hint),
+          when(gl2 && (desktop || gl3), Query::Enum(gl::FRAGMENT_SHADER_DERIVATIVE_HINT)));
+    b.add("GL_PROVOKING_VERTEX", StateValue::Enum(state.provoking_vertex),
+          when(gl32, Query::Enum(gl::PROVOKING_VERTEX)));
+    b.add("GL_PATCH_VERTICES", int(state.patch_patch_vertices as i64),
+          when(desktop && gl40, Query::Integers(gl::PATCH_VERTICES, 1)));
+
+    // pixel store
+    let gl12 = version >= &Version(Api::Gl, 1, 2);
+    b.add("GL_UNPACK_ALIGNMENT", int(state.pixel_store_unpack_alignment as i64),
+          Some(Query::Integers(gl::UNPACK_ALIGNMENT, 1)));
+    b.add("GL_UNPACK_ROW_LENGTH", int(state.pixel_store_unpack_row_length as i64),
+          when(desktop || gl3, Query::Integers(gl::UNPACK_ROW_LENGTH, 1)));
+    b.add("GL_UNPACK_SKIP_ROWS", int(state.pixel_store_unpack_skip_rows as i64),
+          when(desktop || gl3, Query::Integers(gl::UNPACK_SKIP_ROWS, 1)));
+    b.add("GL_UNPACK_SKIP_PIXELS", int(state.pixel_store_unpack_skip_pixels as i64),
+          when(desktop || gl3, Query::Integers(gl::UNPACK_SKIP_PIXELS, 1)));
+    b.add("GL_UNPACK_IMAGE_HEIGHT", int(state.pixel_store_unpack_image_height as i64),
+          when(gl12 || gl3, Query::Integers(gl::UNPACK_IMAGE_HEIGHT, 1)));
+    b.add("GL_UNPACK_SKIP_IMAGES", int(state.pixel_store_unpack_skip_images as i64),
+          when(gl12 || gl3, Query::Integers(gl::UNPACK_SKIP_IMAGES, 1)));
+    b.add("GL_PACK_ALIGNMENT", int(state.pixel_store_pack_alignment as i64),
+          Some(Query::Integers(gl::PACK_ALIGNMENT, 1)));
+    b.add("GL_PACK_ROW_LENGTH", int(state.pixel_store_pack_row_length as i64),
+          when(desktop || gl3, Query::Integers(gl::PACK_ROW_LENGTH, 1)));
+    b.add("GL_PACK_SKIP_ROWS", int(state.pixel_store_pack_skip_rows as i64),
+          when(desktop || gl3, Query::Integers(gl::PACK_SKIP_ROWS, 1)));
+    b.add("GL_PACK_SKIP_PIXELS", int(state.pixel_store_pack_skip_pixels as i64),
+          when(desktop || gl3, Query::Integers(gl::PACK_SKIP_PIXELS, 1)));
+    b.add("GL_PACK_IMAGE_HEIGHT", int(state.pixel_store_pack_image_height as i64),
+          when(gl12, Query::Integers(gl::PACK_IMAGE_HEIGHT, 1)));
+    b.add("GL_PACK_SKIP_IMAGES", int(state.pixel_store_pack_skip_images as i64),
+          when(gl12, Query::Integers(gl::PACK_SKIP_IMAGES, 1)));
+
+    // state that only exists in glium
+    b.add("pending clear", StateValue::Bool(state.pending_clear.is_some()), None);
+    b.add("debug group depth", int(state.debug_group_depth as i64), None);
+    b.add("transform feedback",
+          state.transform_feedback_enabled.map(StateValue::Enum)
+                                          .unwrap_or(StateValue::Bool(false)), None);
+    b.add("conditional render query",
+          int(state.conditional_render.map(|(q, _)| q as i64).unwrap_or(0)), None);
+
+    StateSnapshot {
+        version: *version,
+        vendor: vendor,
+        renderer: renderer,
+        supported_glsl_versions: ctxt.capabilities.supported_glsl_versions.clone(),
+        extensions: extensions.get_supported_names(),
+        lost_context: state.lost_context,
+        entries: b.entries,
+    }
+}
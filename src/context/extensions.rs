@@ -44,6 +44,17 @@ macro_rules! extensions {
 
             extensions
         }
+
+        impl ExtensionsList {
+            /// Returns the names of the extensions of this list that are supported.
+            pub fn get_supported_names(&self) -> Vec<&'static str> {
+                let mut names = Vec::new();
+                $(
+                    if self.$field { names.push($string); }
+                )+
+                names
+            }
+        }
     }
 }
 
@@ -51,11 +62,15 @@ extensions! {
     "GL_AMD_depth_clamp_separate" => gl_amd_depth_clamp_separate,
     "GL_AMD_query_buffer_object" => gl_amd_query_buffer_object,
     "GL_ANGLE_framebuffer_multisample" => gl_angle_framebuffer_multisample,
+    "GL_APPLE_clip_distance" => gl_apple_clip_distance,
     "GL_APPLE_framebuffer_multisample" => gl_apple_framebuffer_multisample,
     "GL_APPLE_sync" => gl_apple_sync,
     "GL_APPLE_vertex_array_object" => gl_apple_vertex_array_object,
     "GL_ARB_bindless_texture" => gl_arb_bindless_texture,
+    "GL_ARB_blend_func_extended" => gl_arb_blend_func_extended,
     "GL_ARB_buffer_storage" => gl_arb_buffer_storage,
+    "GL_ARB_clear_buffer_object" => gl_arb_clear_buffer_object,
+    "GL_ARB_color_buffer_float" => gl_arb_color_buffer_float,
     "GL_ARB_compute_shader" => gl_arb_compute_shader,
     "GL_ARB_copy_buffer" => gl_arb_copy_buffer,
     "GL_ARB_debug_output" => gl_arb_debug_output,
@@ -64,6 +79,7 @@ extensions! {
     "GL_ARB_direct_state_access" => gl_arb_direct_state_access,
     "GL_ARB_draw_buffers" => gl_arb_draw_buffers,
     "GL_ARB_draw_elements_base_vertex" => gl_arb_draw_elements_base_vertex,
+    "GL_ARB_draw_instanced" => gl_arb_draw_instanced,
     "GL_ARB_compatibility" => gl_arb_compatibility,
     "GL_ARB_ES2_compatibility" => gl_arb_es2_compatibility,
     "GL_ARB_ES3_compatibility" => gl_arb_es3_compatibility,
@@ -79,6 +95,7 @@ extensions! {
     "GL_ARB_gpu_shader_int64" => gl_arb_gpu_shader_int64,
     "GL_ARB_instanced_arrays" => gl_arb_instanced_arrays,
     "GL_ARB_internalformat_query" => gl_arb_internalformat_query,
+    "GL_ARB_internalformat_query2" => gl_arb_internalformat_query2,
     "GL_ARB_invalidate_subdata" => gl_arb_invalidate_subdata,
     "GL_ARB_occlusion_query" => gl_arb_occlusion_query,
     "GL_ARB_occlusion_query2" => gl_arb_occlusion_query2,
@@ -86,6 +103,7 @@ extensions! {
     "GL_ARB_program_interface_query" => gl_arb_program_interface_query,
     "GL_ARB_query_buffer_object" => gl_arb_query_buffer_object,
     "GL_ARB_map_buffer_range" => gl_arb_map_buffer_range,
+    "GL_ARB_multi_bind" => gl_arb_multi_bind,
     "GL_ARB_multi_draw_indirect" => gl_arb_multi_draw_indirect,
     "GL_ARB_provoking_vertex" => gl_arb_provoking_vertex,
     "GL_ARB_robustness" => gl_arb_robustness,
@@ -96,6 +114,7 @@ extensions! {
     "GL_ARB_shader_storage_buffer_object" => gl_arb_shader_storage_buffer_object,
     "GL_ARB_sync" => gl_arb_sync,
     "GL_ARB_tessellation_shader" => gl_arb_tessellation_shader,
+    "GL_ARB_texture_barrier" => gl_arb_texture_barrier,
     "GL_ARB_texture_buffer_object" => gl_arb_texture_buffer_object,
     "GL_ARB_texture_buffer_object_rgb32" => gl_arb_texture_buffer_object_rgb32,
     "GL_ARB_texture_compression_bptc" => gl_arb_texture_compression_bptc,
@@ -112,6 +131,7 @@ extensions! {
     "GL_ARB_transform_feedback3" => gl_arb_transform_feedback3,
     "GL_ARB_uniform_buffer_object" => gl_arb_uniform_buffer_object,
     "GL_ARB_vertex_array_object" => gl_arb_vertex_array_object,
+    "GL_ARB_vertex_attrib_binding" => gl_arb_vertex_attrib_binding,
     "GL_ARB_vertex_buffer_object" => gl_arb_vertex_buffer_object,
     "GL_ARB_vertex_half_float" => gl_arb_vertex_half_float,
     "GL_ARB_vertex_shader" => gl_arb_vertex_shader,
@@ -121,18 +141,24 @@ extensions! {
     "GL_ATI_meminfo" => gl_ati_meminfo,
     "GL_ATI_draw_buffers" => gl_ati_draw_buffers,
     "GL_ATI_texture_float" => gl_ati_texture_float,
+    "GL_EXT_blend_func_extended" => gl_ext_blend_func_extended,
     "GL_EXT_blend_minmax" => gl_ext_blend_minmax,
     "GL_EXT_buffer_storage" => gl_ext_buffer_storage,
+    "GL_EXT_clip_cull_distance" => gl_ext_clip_cull_distance,
+    "GL_EXT_color_buffer_float" => gl_ext_color_buffer_float,
+    "GL_EXT_color_buffer_half_float" => gl_ext_color_buffer_half_float,
     "GL_EXT_debug_marker" => gl_ext_debug_marker,
     "GL_EXT_direct_state_access" => gl_ext_direct_state_access,
     "GL_EXT_disjoint_timer_query" => gl_ext_disjoint_timer_query,
     "GL_EXT_framebuffer_blit" => gl_ext_framebuffer_blit,
     "GL_EXT_framebuffer_object" => gl_ext_framebuffer_object,
     "GL_EXT_framebuffer_multisample" => gl_ext_framebuffer_multisample,
+    "GL_EXT_float_blend" => gl_ext_float_blend,
     "GL_EXT_framebuffer_sRGB" => gl_ext_framebuffer_srgb,
     "GL_EXT_geometry_shader" => gl_ext_geometry_shader,
     "GL_EXT_geometry_shader4" => gl_ext_geometry_shader4,
     "GL_EXT_gpu_shader4" => gl_ext_gpu_shader4,
+    "GL_EXT_map_buffer_range" => gl_ext_map_buffer_range,
     "GL_EXT_multi_draw_indirect" => gl_ext_multi_draw_indirect,
     "GL_EXT_multisampled_render_to_texture" => gl_ext_multisampled_render_to_texture,
     "GL_EXT_occlusion_query_boolean" => gl_ext_occlusion_query_boolean,
@@ -152,6 +178,7 @@ extensions! {
     "GL_EXT_texture_filter_anisotropic" => gl_ext_texture_filter_anisotropic,
     "GL_EXT_texture_integer" => gl_ext_texture_integer,
     "GL_EXT_texture_shared_exponent" => gl_ext_texture_shared_exponent,
+    "GL_EXT_texture_norm16" => gl_ext_texture_norm16,
     "GL_EXT_texture_snorm" => gl_ext_texture_snorm,
     "GL_EXT_texture_sRGB" => gl_ext_texture_srgb,
     "GL_EXT_transform_feedback" => gl_ext_transform_feedback,
@@ -179,9 +206,11 @@ extensions! {
     "GL_OES_element_index_uint" => gl_oes_element_index_uint,
     "GL_OES_fixed_point" => gl_oes_fixed_point,
     "GL_OES_geometry_shader" => gl_oes_geometry_shader,
+    "GL_OES_mapbuffer" => gl_oes_mapbuffer,
     "GL_OES_packed_depth_stencil" => gl_oes_packed_depth_stencil,
     "GL_OES_primitive_bounding_box" => gl_oes_primitive_bounding_box,
     "GL_OES_rgb8_rgba8" => gl_oes_rgb8_rgba8,
+    "GL_OES_standard_derivatives" => gl_oes_standard_derivatives,
     "GL_OES_stencil1" => gl_oes_stencil1,
     "GL_OES_stencil4" => gl_oes_stencil4,
     "GL_OES_tessellation_shader" => gl_oes_tessellation_shader,
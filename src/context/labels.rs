@@ -0,0 +1,82 @@
+//! Labels of the OpenGL objects that glium creates for its own needs.
+//!
+//! Glium creates framebuffer objects, vertex array objects, samplers and a few buffers and
+//! programs behind the scenes. When labelling is enabled, each of these objects receives a
+//! sequence number and a description. The description is attached to the object with
+//! `glObjectLabel` if `GL_KHR_debug` is supported, so that it shows up in tools such as
+//! apitrace.
+
+use std::cell::Cell;
+use std::fmt;
+
+use gl;
+use context::CommandContext;
+use context::truncate_debug_string;
+use version::Api;
+use version::Version;
+
+/// Keeps track of the labelling of internal objects.
+pub struct InternalObjectLabels {
+    /// True if the internal objects must be labelled.
+    enabled: Cell<bool>,
+
+    /// Sequence number of the next object.
+    next_sequence: Cell<u64>,
+}
+
+impl InternalObjectLabels {
+    /// Builds a new `InternalObjectLabels`, with labelling disabled.
+    #[inline]
+    pub fn new() -> InternalObjectLabels {
+        InternalObjectLabels {
+            enabled: Cell::new(false),
+            next_sequence: Cell::new(0),
+        }
+    }
+
+    /// Enables or disables the labelling.
+    #[inline]
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    /// Restarts the sequence numbers from zero.
+    #[inline]
+    pub fn reset_sequence(&self) {
+        self.next_sequence.set(0);
+    }
+}
+
+/// Must be called right after glium has created an object for its own needs.
+///
+/// `identifier` is the namespace of the object as expected by `glObjectLabel`, for example
+/// `GL_FRAMEBUFFER`. Does nothing if labelling is disabled. The description is passed with
+/// `format_args!` so that it is only formatted when needed.
+pub fn label_internal_object(ctxt: &CommandContext, identifier: gl::types::GLenum,
+                             id: gl::types::GLuint, description: fmt::Arguments)
+{
+    let labels = ctxt.internal_object_labels;
+    if !labels.enabled.get() {
+        return;
+    }
+
+    let sequence = labels.next_sequence.get();
+    labels.next_sequence.set(sequence + 1);
+
+    let label = format!("glium #{}: {}", sequence, description);
+    let label = truncate_debug_string(&label).as_bytes();
+
+    unsafe {
+        if ctxt.version >= &Version(Api::Gl, 4, 3) ||
+           ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+           (ctxt.version >= &Version(Api::Gl, 1, 0) && ctxt.extensions.gl_khr_debug)
+        {
+            ctxt.gl.ObjectLabel(identifier, id, label.len() as gl::types::GLsizei,
+                                label.as_ptr() as *const _);
+
+        } else if ctxt.version >= &Version(Api::GlEs, 2, 0) && ctxt.extensions.gl_khr_debug {
+            ctxt.gl.ObjectLabelKHR(identifier, id, label.len() as gl::types::GLsizei,
+                                   label.as_ptr() as *const _);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Amount of memory used by the objects of a context.
+///
+/// The values for buffers and textures are computed by glium and are an estimate of the amount
+/// of video memory that the driver needs to allocate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Total size in bytes of all the buffers that are alive.
+    pub buffers_bytes: usize,
+
+    /// Total size in bytes of all the textures that are alive, including their mipmaps.
+    pub textures_bytes: usize,
+
+    /// Number of buffers that are alive.
+    pub buffer_count: usize,
+
+    /// Number of textures that are alive.
+    pub texture_count: usize,
+
+    /// Total amount of video memory in bytes, as reported by the driver.
+    ///
+    /// Requires the `GL_NVX_gpu_memory_info` extension.
+    pub driver_total_bytes: Option<usize>,
+
+    /// Amount of video memory in bytes that is still available, as reported by the driver.
+    ///
+    /// Requires the `GL_NVX_gpu_memory_info` or the `GL_ATI_meminfo` extension.
+    pub driver_available_bytes: Option<usize>,
+}
+
+/// Counters that are updated whenever a buffer or a texture is created or destroyed.
+pub struct MemoryUsageCounters {
+    buffers_bytes: AtomicUsize,
+    textures_bytes: AtomicUsize,
+    buffer_count: AtomicUsize,
+    texture_count: AtomicUsize,
+}
+
+impl MemoryUsageCounters {
+    /// Builds counters that are all zero.
+    #[inline]
+    pub fn new() -> MemoryUsageCounters {
+        MemoryUsageCounters {
+            buffers_bytes: AtomicUsize::new(0),
+            textures_bytes: AtomicUsize::new(0),
+            buffer_count: AtomicUsize::new(0),
+            texture_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Must be called when a buffer is created.
+    #[inline]
+    pub fn add_buffer(&self, bytes: usize) {
+        self.buffers_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.buffer_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Must be called when a buffer is destroyed.
+    #[inline]
+    pub fn remove_buffer(&self, bytes: usize) {
+        self.buffers_bytes.fetch_sub(bytes, Ordering::Relaxed);
+        self.buffer_count.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Must be called when a texture is created.
+    #[inline]
+    pub fn add_texture(&self, bytes: usize) {
+        self.textures_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.texture_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Must be called when a texture is destroyed.
+    #[inline]
+    pub fn remove_texture(&self, bytes: usize) {
+        self.textures_bytes.fetch_sub(bytes, Ordering::Relaxed);
+        self.texture_count.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Builds a `MemoryUsage` from the current values of the counters.
+    #[inline]
+    pub fn to_memory_usage(&self, driver_total_bytes: Option<usize>,
+                           driver_available_bytes: Option<usize>) -> MemoryUsage
+    {
+        MemoryUsage {
+            buffers_bytes: self.buffers_bytes.load(Ordering::Relaxed),
+            textures_bytes: self.textures_bytes.load(Ordering::Relaxed),
+            buffer_count: self.buffer_count.load(Ordering::Relaxed),
+            texture_count: self.texture_count.load(Ordering::Relaxed),
+            driver_total_bytes: driver_total_bytes,
+            driver_available_bytes: driver_available_bytes,
+        }
+    }
+}
@@ -4,6 +4,7 @@ use gl;
 use libc;
 use backtrace;
 
+use std::io;
 use std::mem;
 use std::ptr;
 use std::borrow::Cow;
@@ -11,32 +12,59 @@ use std::collections::HashMap;
 use std::cell::{Cell, RefCell, RefMut};
 use std::marker::PhantomData;
 use std::ffi::CStr;
+use std::fs::File;
+use std::path::Path;
 use std::rc::Rc;
 
 use GliumCreationError;
 use SwapBuffersError;
 use CapabilitiesSource;
 use ContextExt;
+use Rect;
 use backend::Backend;
+use backend::SwapInterval;
+use backend::SwapIntervalNotSupportedError;
 use version;
 use version::Api;
 use version::Version;
 
 use debug;
+use draw_parameters::ProvokingVertex;
 use fbo;
+use global_uniform_blocks::GlobalUniformBlocks;
+use draw_uniforms::DrawUniformsRing;
 use ops;
 use sampler_object;
+use sync;
 use texture;
 use uniforms;
+use utils::coords;
 use vertex_array_object;
 
 pub use self::capabilities::{ReleaseBehavior, Capabilities};
+pub use self::dump::{StateSnapshot, StateEntry, StateValue};
 pub use self::extensions::ExtensionsList;
-pub use self::state::GlState;
+pub use self::labels::{InternalObjectLabels, label_internal_object};
+pub use self::memory::{MemoryUsage, MemoryUsageCounters};
+pub use self::objects::{LiveObjectCounts, ObjectKind, ObjectLimit, ObjectRegistry};
+pub use self::statistics::{FrameStatistics, FrameStatisticsCounters};
+pub use self::statistics::{SyncStatistics, WaitStatistics, WaitKind};
+use self::pacing::FramePacer;
+pub use self::state::{GlState, PendingClear};
+pub use self::tracing::{Tracer, begin_trace_scope, end_trace_scope};
+pub use self::workarounds::DriverWorkarounds;
 
 mod capabilities;
+mod dump;
 mod extensions;
+mod labels;
+mod memory;
+mod objects;
+mod pacing;
 mod state;
+mod statistics;
+mod tracing;
+mod workarounds;
 
 /// Stores the state and information required for glium to execute commands. Most public glium
 /// functions require passing a `Rc<Context>`.
@@ -58,6 +86,9 @@ pub struct Context {
     /// of texture units, maximum size of the viewport, etc.
     capabilities: Capabilities,
 
+    /// Workarounds for the bugs of the driver. Can be modified by the user.
+    workarounds: Cell<DriverWorkarounds>,
+
     /// Glue between glium and the code that handles windowing. Contains functions that allows
     /// you to swap buffers, retrieve the size of the framebuffer, etc.
     backend: RefCell<Box<Backend>>,
@@ -92,6 +123,107 @@ pub struct Context {
     /// List of images handles that are resident. We need to call `MakeImageHandleResidentARB`
     /// when rebuilding the context.
     resident_image_handles: RefCell<Vec<(gl::types::GLuint64, gl::types::GLenum)>>,
+
+    /// Uniform buffers that are bound to the blocks of the same name of all programs.
+    global_uniform_blocks: GlobalUniformBlocks,
+
+    /// Buffer that receives the content of the `glium_DrawUniforms` blocks.
+    draw_uniforms: DrawUniformsRing,
+
+    /// Number of clear commands that have been executed.
+    clear_statistics: Cell<ClearStatistics>,
+
+    /// Statistics about the operations of the current frame.
+    frame_statistics: FrameStatisticsCounters,
+
+    /// Dimensions of the default framebuffer the last time they have been checked by
+    /// `update_framebuffer_dimensions`.
+    framebuffer_dimensions: Cell<(u32, u32)>,
+
+    /// Callbacks to call when the dimensions of the default framebuffer change.
+    resize_callbacks: RefCell<Vec<Box<FnMut((u32, u32))>>>,
+
+    /// The list of contexts that share their objects with this one, including this one.
+    share_group: Rc<ShareGroup>,
+
+
+    /// The objects that are alive in this context. Only tracked in debug builds.
+    live_objects: ObjectRegistry,
+
+    /// Labels of the objects that glium creates for its own needs.
+    internal_object_labels: InternalObjectLabels,
+
+    /// Timestamps of the debug scopes, if tracing is enabled.
+    tracer: Tracer,
+
+    /// Fences of the frames that may still be executed by the GPU.
+    frame_pacer: RefCell<FramePacer<sync::LinearSyncFence>>,
+
+    /// Objects used by `Surface::fill_with_texture` and `Surface::fill_with_program`. Created
+    /// the first time they are needed.
+    fill_resources: RefCell<Option<ops::FillResources>>,
+
+    /// True if a `CommandContext` currently exists.
+    command_context_active: Cell<bool>,
+}
+
+/// Informations passed to the closure of `Context::exec_in_context`.
+pub struct GlCallbackData<'a> {
+    context: &'a Context,
+}
+
+impl<'a> GlCallbackData<'a> {
+    /// Returns the address of an OpenGL function, using the same loader as glium.
+    #[inline]
+    pub unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+        self.context.get_proc_address(symbol)
+    }
+
+    /// Returns the version of OpenGL of the context.
+    #[inline]
+    pub fn get_version(&self) -> &Version {
+        &self.context.version
+    }
+
+    /// Returns the list of extensions supported by the context.
+    #[inline]
+    pub fn get_extensions(&self) -> &ExtensionsList {
+        &self.context.extensions
+    }
+
+    /// Returns the dimensions of the default framebuffer.
+    #[inline]
+    pub fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.context.get_framebuffer_dimensions()
+    }
+}
+
+/// Number of OpenGL commands that have been executed to clear framebuffers.
+///
+/// Consecutive clears of the color, depth and stencil buffers of the same surface are merged
+/// into a single `glClear`, and the clear values are only set when they change.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ClearStatistics {
+    /// Number of calls to `glClear`.
+    pub clear_calls: usize,
+
+    /// Number of calls to `glClearColor`, `glClearDepth` and `glClearStencil`.
+    pub clear_value_calls: usize,
+}
+
+/// List of contexts whose OpenGL contexts share their objects.
+///
+/// Each context registers itself at creation and unregisters itself in its destructor, so the
+/// pointers are always valid.
+struct ShareGroup {
+    contexts: RefCell<Vec<*const Context>>,
+
+    /// Objects that have been dropped in another thread and that must be destroyed.
+    deferred_drops: sync::DeferredDropQueue,
+
+    /// Amount of memory used by the buffers and textures of the group. Objects can be destroyed
+    /// by any context of the group, so the counters can't be per-context.
+    memory_usage: MemoryUsageCounters,
 }
 
 /// This struct is a guard that is returned when you want to access the OpenGL backend.
@@ -112,6 +244,9 @@ pub struct CommandContext<'a> {
     /// Capabilities of the backend.
     pub capabilities: &'a Capabilities,
 
+    /// Workarounds for the bugs of the driver.
+    pub workarounds: DriverWorkarounds,
+
     /// Whether or not errors triggered by ARB_debug_output (and similar extensions) should be
     /// reported to the user (by panicking).
     pub report_debug_output_errors: &'a Cell<bool>,
@@ -131,12 +266,54 @@ pub struct CommandContext<'a> {
     /// List of image handles and their access that need to be made resident.
     pub resident_image_handles: RefMut<'a, Vec<(gl::types::GLuint64, gl::types::GLenum)>>,
 
+    /// Uniform buffers that are bound to the blocks of the same name of all programs.
+    pub global_uniform_blocks: &'a GlobalUniformBlocks,
+
+    /// Buffer that receives the content of the `glium_DrawUniforms` blocks.
+    pub draw_uniforms: &'a DrawUniformsRing,
+
+    /// Number of clear commands that have been executed.
+    pub clear_statistics: &'a Cell<ClearStatistics>,
+
+    /// Statistics about the operations of the current frame.
+    pub frame_statistics: &'a FrameStatisticsCounters,
+
+    /// The objects that are alive in this context. Only tracked in debug builds.
+    pub live_objects: &'a ObjectRegistry,
+
+    /// Labels of the objects that glium creates for its own needs.
+    pub internal_object_labels: &'a InternalObjectLabels,
+
+    /// Timestamps of the debug scopes, if tracing is enabled.
+    pub tracer: &'a Tracer,
+
+    /// Resets `Context::command_context_active` when the `CommandContext` is destroyed.
+    active_guard: CommandContextGuard<'a>,
+
     /// This marker is here to prevent `CommandContext` from implementing `Send`
     // TODO: use this when possible
     //impl<'a, 'b> !Send for CommandContext<'a, 'b> {}
     marker: PhantomData<*mut u8>,
 }
 
+/// Sets its flag to `false` when destroyed.
+struct CommandContextGuard<'a>(&'a Cell<bool>);
+
+impl<'a> CommandContextGuard<'a> {
+    #[inline]
+    fn new(flag: &'a Cell<bool>) -> CommandContextGuard<'a> {
+        flag.set(true);
+        CommandContextGuard(flag)
+    }
+}
+
+impl<'a> Drop for CommandContextGuard<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
 impl Context {
     /// Builds a new context.
     ///
@@ -154,6 +331,39 @@ impl Context {
                             callback_behavior: DebugCallbackBehavior)
                             -> Result<Rc<Context>, GliumCreationError<E>>
                             where B: Backend + 'static
+    {
+        let share_group = Rc::new(ShareGroup {
+            contexts: RefCell::new(Vec::new()),
+            deferred_drops: sync::new_deferred_drop_queue(),
+            memory_usage: MemoryUsageCounters::new(),
+        });
+        Context::new_impl(backend, check_current_context, callback_behavior, share_group)
+    }
+
+    /// Builds a new context whose objects are shared with an existing context.
+    ///
+    /// Buffers, textures, programs, etc. created with one of the two contexts can be used
+    /// with the other one. Each context keeps its own state cache.
+    ///
+    /// # Safety
+    ///
+    /// Same as `new`. In addition, the OpenGL context of the backend **must** share its lists
+    /// with the OpenGL context of `shared_with`.
+    pub unsafe fn new_shared<B, E>(backend: B, check_current_context: bool,
+                                   callback_behavior: DebugCallbackBehavior,
+                                   shared_with: &Context)
+                                   -> Result<Rc<Context>, GliumCreationError<E>>
+                                   where B: Backend + 'static
+    {
+        let share_group = shared_with.share_group.clone();
+        Context::new_impl(backend, check_current_context, callback_behavior, share_group)
+    }
+
+    unsafe fn new_impl<B, E>(backend: B, check_current_context: bool,
+                             callback_behavior: DebugCallbackBehavior,
+                             share_group: Rc<ShareGroup>)
+                             -> Result<Rc<Context>, GliumCreationError<E>>
+                             where B: Backend + 'static
     {
         backend.make_current();
 
@@ -164,7 +374,16 @@ impl Context {
         let extensions = extensions::get_extensions(&gl, &version);
         try!(check_gl_compatibility(&version, &extensions));
 
-        let capabilities = capabilities::get_capabilities(&gl, &version, &extensions);
+        let mut capabilities = capabilities::get_capabilities(&gl, &version, &extensions);
+        if backend.is_single_buffered() {
+            if version >= Version(Api::GlEs, 2, 0) {
+                capabilities.double_buffer = false;
+            } else if capabilities.double_buffer {
+                return Err(GliumCreationError::SingleBufferingNotSupported);
+            }
+        }
+
+        let workarounds = Cell::new(workarounds::get_workarounds(&gl));
         let report_debug_output_errors = Cell::new(true);
 
         let vertex_array_objects = vertex_array_object::VertexAttributesSystem::new();
@@ -186,12 +405,15 @@ impl Context {
             },
         };
 
+        let framebuffer_dimensions = backend.get_framebuffer_dimensions();
+
         let context = Rc::new(Context {
             gl: gl,
             state: gl_state,
             version: version,
             extensions: extensions,
             capabilities: capabilities,
+            workarounds: workarounds,
             debug_callback: debug_callback,
             report_debug_output_errors: report_debug_output_errors,
             backend: RefCell::new(Box::new(backend)),
@@ -201,8 +423,23 @@ impl Context {
             samplers: samplers,
             resident_texture_handles: resident_texture_handles,
             resident_image_handles: resident_image_handles,
+            global_uniform_blocks: GlobalUniformBlocks::new(),
+            draw_uniforms: DrawUniformsRing::new(),
+            clear_statistics: Cell::new(Default::default()),
+            frame_statistics: FrameStatisticsCounters::new(),
+            framebuffer_dimensions: Cell::new(framebuffer_dimensions),
+            resize_callbacks: RefCell::new(Vec::new()),
+            share_group: share_group,
+            live_objects: ObjectRegistry::new(),
+            internal_object_labels: InternalObjectLabels::new(),
+            tracer: Tracer::new(),
+            frame_pacer: RefCell::new(FramePacer::new()),
+            fill_resources: RefCell::new(None),
+            command_context_active: Cell::new(false),
         });
 
+        context.share_group.contexts.borrow_mut().push(&*context as *const Context);
+
         if context.debug_callback.is_some() {
             init_debug_callback(&context, synchronous);
         }
@@ -222,12 +459,96 @@ impl Context {
         Ok(context)
     }
 
+    /// Returns true if this context and `other` share their objects, in other words if
+    /// buffers, textures, programs, etc. created with one context can be used with the other.
+    #[inline]
+    pub fn is_sharing_objects_with(&self, other: &Context) -> bool {
+        &*self.share_group as *const ShareGroup == &*other.share_group as *const ShareGroup
+    }
+
     /// Calls `get_framebuffer_dimensions` on the backend object stored by this context.
     #[inline]
     pub fn get_framebuffer_dimensions(&self) -> (u32, u32) {
         self.backend.borrow().get_framebuffer_dimensions()
     }
 
+    /// Calls `get_window_dimensions` on the backend object stored by this context.
+    #[inline]
+    pub fn get_window_dimensions(&self) -> (u32, u32) {
+        self.backend.borrow().get_window_dimensions()
+    }
+
+    /// Converts a position in the window, for example the position of the mouse, into the
+    /// coordinates of the pixel of the default framebuffer that contains it.
+    ///
+    /// Window positions are in logical units with the origin at the top-left corner, while
+    /// framebuffer coordinates are in pixels with the origin at the bottom-left corner. The
+    /// HiDPI factor is the ratio between the dimensions of the framebuffer and the dimensions
+    /// of the window.
+    ///
+    /// The result is outside of the framebuffer if the position is outside of the window.
+    #[inline]
+    pub fn window_to_framebuffer_coords(&self, position: (f64, f64)) -> (i32, i32) {
+        coords::window_to_framebuffer(self.get_window_dimensions(),
+                                      self.get_framebuffer_dimensions(), position)
+    }
+
+    /// Returns a rectangle of `size` pixels of the default framebuffer centered on a position
+    /// in the window, for example to read the pixels under the mouse.
+    ///
+    /// The rectangle is clamped to the dimensions of the framebuffer, and is empty if it is
+    /// entirely outside. See `window_to_framebuffer_coords` for how the position is converted.
+    #[inline]
+    pub fn pick_rect_at(&self, position: (f64, f64), size: (u32, u32)) -> Rect {
+        coords::pick_rect(self.get_window_dimensions(), self.get_framebuffer_dimensions(),
+                          position, size)
+    }
+
+    /// Registers a callback that is called with the new dimensions of the default framebuffer
+    /// whenever they change.
+    ///
+    /// The dimensions are checked by `update_framebuffer_dimensions`, which `Display::draw`
+    /// calls before returning the `Frame`. This lets you rebuild your projection matrix or
+    /// your offscreen render targets before drawing. The dimensions are in pixels, so a change
+    /// of the HiDPI factor of the window is reported as well.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// display.on_framebuffer_resize(|(width, height)| {
+    ///     println!("the window is now {}x{}", width, height);
+    /// });
+    /// ```
+    pub fn on_framebuffer_resize<F>(&self, callback: F) where F: FnMut((u32, u32)) + 'static {
+        self.resize_callbacks.borrow_mut().push(Box::new(callback));
+    }
+
+    /// Queries the dimensions of the default framebuffer from the backend, and calls the
+    /// callbacks registered with `on_framebuffer_resize` if they have changed since the
+    /// last call.
+    ///
+    /// Returns the new dimensions. This is automatically called by `Display::draw`.
+    pub fn update_framebuffer_dimensions(&self) -> (u32, u32) {
+        let dimensions = self.get_framebuffer_dimensions();
+
+        if self.framebuffer_dimensions.get() != dimensions {
+            self.framebuffer_dimensions.set(dimensions);
+
+            // the callbacks are allowed to register other callbacks
+            let mut callbacks = mem::replace(&mut *self.resize_callbacks.borrow_mut(), Vec::new());
+            for callback in callbacks.iter_mut() {
+                callback(dimensions);
+            }
+
+            let mut list = self.resize_callbacks.borrow_mut();
+            let added = mem::replace(&mut *list, callbacks);
+            list.extend(added.into_iter());
+        }
+
+        dimensions
+    }
+
     /// Changes the OpenGL context associated with this context.
     ///
     /// The new context **must** have lists shared with the old one.
@@ -264,12 +585,120 @@ impl Context {
         Ok(())
     }
 
+    /// Destroys the objects that glium creates for its own needs, like the ones used by
+    /// `Surface::fill_with_texture`. They are created again the next time they are needed.
+    ///
+    /// These objects are automatically destroyed with the context.
+    pub fn release_internal_resources(&self) {
+        // the borrow must be released before the objects are destroyed
+        let resources = self.fill_resources.borrow_mut().take();
+
+        if let Some(resources) = resources {
+            resources.destroy(self);
+        }
+    }
+
+    /// Destroys all the objects that glium creates and caches for its own needs: framebuffer
+    /// objects, vertex array objects, sampler objects, the buffer of the `glium_DrawUniforms`
+    /// blocks and the objects destroyed by `release_internal_resources`. They are created again
+    /// the next time they are needed.
+    ///
+    /// The sequence numbers of the internal objects also restart from zero. Call this function
+    /// before starting a capture with a tool such as apitrace, so that the captures of two runs
+    /// that execute the same commands create the same objects in the same order.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// display.set_internal_object_labels(true);
+    /// display.reset_internal_caches();
+    /// // start the capture here
+    /// ```
+    pub fn reset_internal_caches(&self) {
+        self.release_internal_resources();
+
+        let mut ctxt = self.make_current();
+        fbo::FramebuffersContainer::purge_all(&mut ctxt);
+        vertex_array_object::VertexAttributesSystem::purge_all(&mut ctxt);
+        sampler_object::purge_all(&mut ctxt);
+        DrawUniformsRing::purge_all(&mut ctxt);
+        ctxt.internal_object_labels.reset_sequence();
+    }
+
+    /// Enables or disables the labelling of the objects that glium creates for its own needs.
+    ///
+    /// When enabled, each framebuffer object, vertex array object, sampler object, buffer or
+    /// program that glium creates internally receives a sequence number and a description.
+    /// They are attached to the object with `glObjectLabel` if `GL_KHR_debug` is supported,
+    /// which makes them visible in debugging tools. Disabled by default.
+    #[inline]
+    pub fn set_internal_object_labels(&self, enabled: bool) {
+        self.internal_object_labels.set_enabled(enabled);
+    }
+
+    /// Records the CPU and GPU timestamps of the start and of the end of each debug scope, and
+    /// keeps the scopes that started during the last `frames` frames. A value of `0` disables
+    /// the tracing and discards what has been recorded. This is the default.
+    ///
+    /// The GPU timestamps are retrieved with timestamp queries that are reused from one scope
+    /// to the next. Their results are collected when the buffers are swapped, once they are
+    /// available, which means that tracing never makes the CPU wait for the GPU. A scope that
+    /// is still alive when the buffers are swapped belongs to the frame during which it started.
+    /// If the backend doesn't support timestamp queries, only the CPU timestamps are recorded.
+    ///
+    /// See `export_trace` and `debug::DebugScope`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// display.set_tracing(3);
+    ///
+    /// for _ in 0 .. 3 {
+    ///     let frame = display.draw();
+    ///     {
+    ///         let _scope = frame.debug_scope("scene");
+    ///         // draw here
+    ///     }
+    ///     frame.finish().unwrap();
+    /// }
+    ///
+    /// display.export_trace("trace.json").unwrap();
+    /// ```
+    pub fn set_tracing(&self, frames: usize) {
+        let mut ctxt = self.make_current();
+        tracing::set_tracing(&mut ctxt, frames);
+    }
+
+    /// Writes the debug scopes recorded since `set_tracing` has been called to a file, in the
+    /// JSON format that can be loaded by `chrome://tracing`.
+    ///
+    /// The CPU durations are shown on a thread named `CPU` and the GPU durations on a thread
+    /// named `GPU`. The scopes whose GPU timestamps aren't available yet are not included.
+    pub fn export_trace<P>(&self, path: P) -> io::Result<()> where P: AsRef<Path> {
+        let mut file = try!(File::create(path));
+        let mut ctxt = self.make_current();
+        tracing::export(&mut ctxt, &mut file)
+    }
+
     /// Swaps the buffers in the backend.
+    ///
+    /// Checks whether the context has been lost beforehand. See `is_context_lost`.
+    ///
+    /// If the context is single-buffered, calls `glFlush` instead. See `is_double_buffered`.
     pub fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
-        if self.state.borrow().lost_context {
+        self.flush_deferred_drops();
+
+        if self.is_context_lost() {
             return Err(SwapBuffersError::ContextLost);
         }
 
+        // executing the clear that may be pending
+        if self.state.borrow().pending_clear.is_some() {
+            let _ctxt = self.make_current();
+        }
+
         // Note: This is a work-around for the FRAPS software.
         //       The Fraps software calls `glClear` with scissoring on the current framebuffer.
         //       Therefore we need to bind the default framebuffer before swapping.
@@ -301,14 +730,111 @@ impl Context {
             }
         }
 
-        // swapping
-        let err = backend.swap_buffers();
+        // swapping, or submitting the commands if there is nothing to swap
+        let err = if self.capabilities.double_buffer {
+            let wait = self.frame_statistics.start_wait();
+            let err = backend.swap_buffers();
+            self.frame_statistics.end_wait(wait, WaitKind::Swap);
+            err
+        } else {
+            unsafe { self.gl.Flush(); }
+            Ok(())
+        };
+
         if let Err(SwapBuffersError::ContextLost) = err {
             self.state.borrow_mut().lost_context = true;
         }
+
+        if err.is_ok() && self.frame_pacer.borrow().is_enabled() {
+            let mut ctxt = self.make_current();
+            if let Ok(fence) = unsafe { sync::new_linear_sync_fence(&mut ctxt) } {
+                if let Some(old) = self.frame_pacer.borrow_mut().end_frame(fence) {
+                    unsafe { sync::destroy_linear_sync_fence(&mut ctxt, old) };
+                }
+            }
+        }
+
+        if err.is_ok() && self.tracer.is_enabled() {
+            tracing::end_frame(&mut self.make_current());
+        }
+
         err
     }
 
+    /// Limits the number of frames that the GPU can execute behind the CPU.
+    ///
+    /// Drivers usually let the CPU queue the commands of several frames in advance, which
+    /// adds latency between the user inputs and what is displayed. When `max` is not zero, a
+    /// fence is inserted every time the buffers are swapped, and `wait_frames_in_flight`,
+    /// which is called by `Display::draw`, blocks until the frame that has been finished `max`
+    /// frames before has been executed. A value of `1` gives the lowest latency, while higher
+    /// values let the CPU and the GPU work in parallel. The time spent waiting is counted in
+    /// `SyncStatistics::frame_pacing`.
+    ///
+    /// A value of `0` removes the limit. This is the default.
+    ///
+    /// Returns an error if the backend doesn't support sync fences. The limit is then left
+    /// unchanged.
+    pub fn set_max_frames_in_flight(&self, max: u32) -> Result<(), sync::SyncNotSupportedError> {
+        let mut ctxt = self.make_current();
+
+        if max != 0 && !sync::is_supported(&ctxt) {
+            return Err(sync::SyncNotSupportedError);
+        }
+
+        for fence in self.frame_pacer.borrow_mut().set_max_frames_in_flight(max) {
+            unsafe { sync::destroy_linear_sync_fence(&mut ctxt, fence) };
+        }
+
+        Ok(())
+    }
+
+    /// Returns the value passed to `set_max_frames_in_flight`.
+    #[inline]
+    pub fn get_max_frames_in_flight(&self) -> u32 {
+        self.frame_pacer.borrow().get_max_frames_in_flight()
+    }
+
+    /// Blocks until there are fewer frames in flight than the limit set with
+    /// `set_max_frames_in_flight`. Does nothing if there is no limit.
+    ///
+    /// This function is called by `Display::draw`. You only need to call it yourself if you
+    /// swap the buffers without using a `Frame`.
+    pub fn wait_frames_in_flight(&self) {
+        let fence = self.frame_pacer.borrow_mut().start_frame();
+
+        if let Some(fence) = fence {
+            let mut ctxt = self.make_current();
+            unsafe { sync::wait_frame_fence_and_drop(fence, &mut ctxt) };
+        }
+    }
+
+    /// Changes the swap interval of the backend.
+    ///
+    /// Returns the swap interval that is now in use, or an error if the backend doesn't support
+    /// changing it. If `AdaptiveVsync` is requested but isn't supported, then `Vsync` is used
+    /// instead.
+    pub fn set_swap_interval(&self, interval: SwapInterval)
+                             -> Result<SwapInterval, SwapIntervalNotSupportedError>
+    {
+        let _ctxt = self.make_current();
+        let backend = self.backend.borrow();
+
+        unsafe {
+            if backend.set_swap_interval(interval) {
+                return Ok(interval);
+            }
+
+            if interval == SwapInterval::AdaptiveVsync &&
+               backend.set_swap_interval(SwapInterval::Vsync)
+            {
+                return Ok(SwapInterval::Vsync);
+            }
+        }
+
+        Err(SwapIntervalNotSupportedError)
+    }
+
     /// DEPRECATED. Use `get_opengl_version` instead.
     #[inline]
     pub fn get_version(&self) -> &Version {
@@ -342,6 +868,16 @@ impl Context {
         self.capabilities().robustness
     }
 
+    /// Returns true if the default framebuffer has a back buffer.
+    ///
+    /// Single-buffered contexts draw directly to the front buffer, which is visible. Finishing
+    /// a `Frame` calls `glFlush` instead of swapping buffers, and avoiding flickering is up to
+    /// you. See `Backend::is_single_buffered`.
+    #[inline]
+    pub fn is_double_buffered(&self) -> bool {
+        self.capabilities().double_buffer
+    }
+
     /// Returns true if a context loss is possible.
     #[inline]
     pub fn is_context_loss_possible(&self) -> bool {
@@ -350,6 +886,15 @@ impl Context {
 
     /// Returns true if the context has been lost and needs to be recreated.
     ///
+    /// This is automatically checked every time a `Frame` is finished. Once the context is lost,
+    /// draw operations return `DrawError::ContextLost`, reading buffers returns
+    /// `ReadError::ContextLost` and swapping buffers returns `SwapBuffersError::ContextLost`.
+    /// All the objects of this context are invalid and must be recreated.
+    ///
+    /// Detecting a loss requires the OpenGL context to be created with the
+    /// "lose context on reset" robustness strategy. Without it, this function always returns
+    /// false.
+    ///
     /// # Implementation
     ///
     /// If it has been determined that the context has been lost before, then the function
@@ -404,6 +949,79 @@ impl Context {
         (d.0 as u32, d.1 as u32)
     }
 
+    /// Returns the value that the offset of a buffer slice bound to a uniform block must be a
+    /// multiple of.
+    ///
+    /// Returns `1` if uniform buffers are not supported.
+    #[inline]
+    pub fn get_uniform_buffer_offset_alignment(&self) -> usize {
+        self.capabilities().uniform_buffer_offset_alignment as usize
+    }
+
+    /// Rounds `size` up to the next multiple of the uniform buffer offset alignment.
+    ///
+    /// This is useful when storing the data of multiple objects in a single buffer: if each
+    /// object uses an aligned size, then each object can be bound to a uniform block.
+    #[inline]
+    pub fn align_uniform_buffer_size(&self, size: usize) -> usize {
+        let alignment = self.get_uniform_buffer_offset_alignment();
+        (size + alignment - 1) / alignment * alignment
+    }
+
+    /// Binds a uniform buffer to the uniform blocks named `name` of all the programs.
+    ///
+    /// Draw commands don't need to pass the block in their uniforms anymore. If they do, the
+    /// block passed to the draw command is used instead of the global one.
+    ///
+    /// Each name reserves a binding point, starting from the last one, which can't be used by
+    /// the blocks passed to draw commands. The layout of the content of the buffer is checked
+    /// against the block of each program when drawing, and a `UniformBlockLayoutMismatch` error
+    /// is returned if they don't match.
+    ///
+    /// The buffer must outlive its use as a global block. If it is destroyed, drawing with a
+    /// program that uses the block returns a `GlobalUniformBlockUnbound` error until the block
+    /// is set again or removed. Unlike buffers passed to draw commands, global blocks don't
+    /// create fences, so be careful when writing to a persistently-mapped buffer.
+    pub fn set_global_uniform_block<B>(&self, name: &str, block: &B)
+                                       -> Result<(), uniforms::GlobalUniformBlockError>
+                                       where B: uniforms::AsUniformValue
+    {
+        let mut ctxt = self.make_current();
+        self.global_uniform_blocks.set(&mut ctxt, name, block.as_uniform_value())
+    }
+
+    /// Stops binding a buffer to the uniform blocks named `name`.
+    ///
+    /// Returns `false` if no buffer was set for this name.
+    #[inline]
+    pub fn remove_global_uniform_block(&self, name: &str) -> bool {
+        self.global_uniform_blocks.remove(name)
+    }
+
+    /// Returns true if the backend allows choosing `ProvokingVertex::FirstVertex` in the draw
+    /// parameters.
+    ///
+    /// This requires OpenGL 3.2, `GL_ARB_provoking_vertex` or `GL_EXT_provoking_vertex`.
+    #[inline]
+    pub fn is_provoking_vertex_supported(&self) -> bool {
+        self.get_version() >= &Version(Api::Gl, 3, 2) ||
+            self.get_extensions().gl_arb_provoking_vertex ||
+            self.get_extensions().gl_ext_provoking_vertex
+    }
+
+    /// Returns the provoking vertex convention that is currently in use by the backend.
+    ///
+    /// This is the value that was used by the latest draw command, or `LastVertex` if nothing
+    /// has been drawn yet.
+    pub fn get_provoking_vertex(&self) -> ProvokingVertex {
+        let ctxt = self.make_current();
+
+        match ctxt.state.provoking_vertex {
+            gl::FIRST_VERTEX_CONVENTION => ProvokingVertex::FirstVertex,
+            _ => ProvokingVertex::LastVertex,
+        }
+    }
+
     /// Releases the shader compiler, indicating that no new programs will be created for a while.
     ///
     /// This method is a no-op if it's not available in the implementation.
@@ -445,6 +1063,139 @@ impl Context {
         }
     }
 
+    /// Returns the number of OpenGL commands that have been executed to clear framebuffers.
+    ///
+    /// This is mostly useful for tests.
+    pub fn get_clear_statistics(&self) -> ClearStatistics {
+        // executing the clear that may be pending
+        let _ctxt = self.make_current();
+        self.clear_statistics.get()
+    }
+
+    /// Enables or disables the collection of `FrameStatistics`. Disabled by default.
+    ///
+    /// The statistics are reset when this function is called.
+    #[inline]
+    pub fn set_frame_statistics_enabled(&self, enabled: bool) {
+        self.frame_statistics.set_enabled(enabled);
+    }
+
+    /// Returns the statistics of the current frame.
+    ///
+    /// The statistics are reset when a `Frame` is finished. Everything that happens between
+    /// two calls to `Frame::finish` is counted, including the operations that don't target
+    /// the frame.
+    #[inline]
+    pub fn get_frame_statistics(&self) -> FrameStatistics {
+        self.frame_statistics.get()
+    }
+
+    /// Returns the statistics of the current frame and resets them.
+    #[inline]
+    pub fn take_frame_statistics(&self) -> FrameStatistics {
+        self.frame_statistics.take()
+    }
+
+    /// Returns the workarounds for driver bugs that are currently in use.
+    #[inline]
+    pub fn get_driver_workarounds(&self) -> DriverWorkarounds {
+        self.workarounds.get()
+    }
+
+    /// Overrides the workarounds for driver bugs that glium uses.
+    ///
+    /// The workarounds are automatically chosen when the context is created, depending on
+    /// the driver. You can use this function to force-enable or disable some of them.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// let mut workarounds = display.get_driver_workarounds();
+    /// workarounds.no_buffer_invalidation = true;
+    /// display.set_driver_workarounds(workarounds);
+    /// ```
+    #[inline]
+    pub fn set_driver_workarounds(&self, workarounds: DriverWorkarounds) {
+        self.workarounds.set(workarounds);
+    }
+
+    /// Returns the amount of memory used by the buffers and textures of this context.
+    ///
+    /// Objects are shared between all the contexts created with `new_shared`, so the counters
+    /// include the objects of these contexts as well.
+    ///
+    /// If the backend supports it, the driver's view of the video memory is reported as well.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// let usage = display.get_memory_usage();
+    /// println!("{} textures use {} bytes", usage.texture_count, usage.textures_bytes);
+    /// ```
+    pub fn get_memory_usage(&self) -> MemoryUsage {
+        let (total, available) = unsafe {
+            let ctxt = self.make_current();
+
+            let mut value: [gl::types::GLint; 4] = mem::uninitialized();
+
+            if ctxt.extensions.gl_nvx_gpu_memory_info {
+                ctxt.gl.GetIntegerv(gl::GPU_MEMORY_INFO_TOTAL_AVAILABLE_MEMORY_NVX,
+                                    &mut value[0]);
+                let total = value[0] as usize * 1024;
+                ctxt.gl.GetIntegerv(gl::GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX,
+                                    &mut value[0]);
+                (Some(total), Some(value[0] as usize * 1024))
+
+            } else if ctxt.extensions.gl_ati_meminfo {
+                ctxt.gl.GetIntegerv(gl::TEXTURE_FREE_MEMORY_ATI, &mut value[0]);
+                (None, Some(value[0] as usize * 1024))
+
+            } else {
+                (None, None)
+            }
+        };
+
+        self.memory_usage.to_memory_usage(total, available)
+    }
+
+    /// Returns the number of OpenGL objects of each kind that are alive in this context.
+    ///
+    /// Objects are only tracked in debug builds. Returns `None` in release builds.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// if let Some(counts) = display.get_live_object_counts() {
+    ///     println!("{} buffers are alive", counts.buffers);
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_live_object_counts(&self) -> Option<LiveObjectCounts> {
+        self.live_objects.get_counts()
+    }
+
+    /// Sets a soft limit to the number of objects of a kind that can be alive at the same time.
+    ///
+    /// Exceeding the limit prints a warning or panics depending on the limit. This is useful
+    /// to detect code that creates an object every frame and never destroys it.
+    ///
+    /// Objects are only tracked in debug builds. This function does nothing in release builds.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+    /// use glium::context::{ObjectKind, ObjectLimit};
+    /// display.set_live_object_limit(ObjectKind::Buffer, ObjectLimit::Panic(10000));
+    /// ```
+    #[inline]
+    pub fn set_live_object_limit(&self, kind: ObjectKind, limit: ObjectLimit) {
+        self.live_objects.set_limit(kind, limit);
+    }
+
     /// Reads the content of the front buffer.
     ///
     /// You will only see the data that has finished being drawn.
@@ -469,23 +1220,84 @@ impl Context {
         let rect = ::Rect { left: 0, bottom: 0, width: dimensions.0, height: dimensions.1 };
 
         let mut data = Vec::with_capacity(0);
-        ops::read(&mut ctxt, ops::Source::DefaultFramebuffer(gl::FRONT_LEFT), &rect, &mut data,
-                  false);
+        ops::read(&mut ctxt, ops::Source::DefaultFramebuffer(gl::FRONT_LEFT, dimensions), &rect,
+                  &mut data, false).unwrap();
+        T::from_raw(Cow::Owned(data), dimensions.0, dimensions.1)
+    }
+
+    /// Reads the content of the front buffer, with the rows in top-to-bottom order.
+    ///
+    /// Same as `read_front_buffer`, except that the first row of the result is the top of
+    /// the window instead of the bottom.
+    pub fn read_front_buffer_flipped<T>(&self) -> T
+                                        where T: texture::Texture2dDataSink<(u8, u8, u8, u8)>
+    {
+        let mut ctxt = self.make_current();
+        let dimensions = self.get_framebuffer_dimensions();
+        let rect = ::Rect { left: 0, bottom: 0, width: dimensions.0, height: dimensions.1 };
+
+        let mut data = Vec::with_capacity(0);
+        ops::read(&mut ctxt, ops::Source::DefaultFramebuffer(gl::FRONT_LEFT, dimensions), &rect,
+                  &mut data, false).unwrap();
+        ops::flip_rows(&mut data, dimensions.0 as usize, dimensions.1 as usize);
         T::from_raw(Cow::Owned(data), dimensions.0, dimensions.1)
     }
 
     /// Execute an arbitrary closure with the OpenGL context active. Useful if another
-    /// component needs to directly manipulate OpenGL state.
+    /// component, for example a C library, needs to directly manipulate OpenGL state.
     ///
-    /// **If `action` manipulates any OpenGL state, it must be restored before `action`
-    /// completes.**
-    #[inline]
+    /// After `action` returns, glium considers that its knowledge of the OpenGL state is stale.
+    /// The current program, vertex array, buffer and framebuffer bindings, texture units, pixel
+    /// store parameters and draw parameters are reset to their default values, and the vertex
+    /// array objects and framebuffer objects that glium keeps in cache are destroyed. The next
+    /// draw command will re-apply everything it needs.
+    ///
+    /// The pixel store parameters (`GL_UNPACK_ALIGNMENT`, `GL_PACK_ROW_LENGTH`, etc.) have their
+    /// default values when `action` is called. Glium sets all the ones that it needs before each
+    /// upload or download, so `action` doesn't need to restore them.
+    ///
+    /// `action` must not delete or modify the objects that belong to glium, and must leave the
+    /// context in a valid state (for example no pending `glBegin` or transform feedback).
     pub unsafe fn exec_in_context<'a, T, F>(&self, action: F) -> T
                                             where T: Send + 'static,
-                                            F: FnOnce() -> T + 'a
+                                            F: FnOnce(&GlCallbackData) -> T + 'a
     {
-        let _ctxt = self.make_current();
-        action()
+        let result = {
+            let mut ctxt = self.make_current();
+            // a previous upload may have changed `GL_UNPACK_ALIGNMENT` for example
+            ops::set_default_pixel_store(&mut ctxt);
+            action(&GlCallbackData { context: self })
+        };
+
+        let mut ctxt = self.make_current();
+        reset_state(&mut ctxt);
+        fbo::FramebuffersContainer::purge_all(&mut ctxt);
+        vertex_array_object::VertexAttributesSystem::purge_all(&mut ctxt);
+
+        result
+    }
+
+    /// Returns the address of an OpenGL function, using the same loader as glium.
+    ///
+    /// This can be used by other libraries that need to call OpenGL functions on this context.
+    #[inline]
+    pub unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+        self.backend.borrow().get_proc_address(symbol)
+    }
+
+    /// Returns a snapshot of the version, the capabilities and the state of the context, to
+    /// attach to bug reports.
+    ///
+    /// The state contains the values that glium has cached: the bound program, vertex array,
+    /// buffers, framebuffers and textures, and the draw parameters that have been applied last.
+    ///
+    /// If `cross_check` is true, each of these values that can be queried is compared with the
+    /// value returned by `glGet*`, which is slow. A difference means that there is either a bug
+    /// in glium, or that OpenGL functions have been called without glium knowing about it. See
+    /// `StateSnapshot::get_divergences`.
+    pub fn dump_state(&self, cross_check: bool) -> StateSnapshot {
+        let ctxt = self.make_current();
+        dump::take_snapshot(&ctxt, cross_check)
     }
 
     /// Asserts that there are no OpenGL errors pending.
@@ -518,7 +1330,9 @@ impl Context {
     #[inline]
     pub fn finish(&self) {
         let ctxt = self.make_current();
+        let wait = ctxt.frame_statistics.start_wait();
         unsafe { ctxt.gl.Finish(); }
+        ctxt.frame_statistics.end_wait(wait, WaitKind::Finish);
     }
 
     /// Calls `glFlush()`. This starts executing the commands that you have issued if it is not
@@ -536,6 +1350,30 @@ impl Context {
         unsafe { ctxt.gl.Flush(); }
     }
 
+    /// Returns true if `texture_barrier` is supported by the backend.
+    ///
+    /// Requires OpenGL 4.5 or `GL_ARB_texture_barrier`.
+    #[inline]
+    pub fn is_texture_barrier_supported(&self) -> bool {
+        &self.version >= &Version(Api::Gl, 4, 5) || self.extensions.gl_arb_texture_barrier
+    }
+
+    /// Calls `glTextureBarrier()`. This ensures that the texels written by the previous draw
+    /// commands are visible to the next draw commands that sample the same texture.
+    ///
+    /// This is only useful with `SimpleFrameBuffer::draw_with_feedback_loops`.
+    ///
+    /// Returns `Err` if the backend doesn't support this functionality.
+    pub fn texture_barrier(&self) -> Result<(), ()> {
+        if !self.is_texture_barrier_supported() {
+            return Err(());
+        }
+
+        let ctxt = self.make_current();
+        unsafe { ctxt.gl.TextureBarrier(); }
+        Ok(())
+    }
+
     /// Inserts a debugging string in the commands queue. If you use an OpenGL debugger, you will
     /// be able to see that string.
     ///
@@ -545,8 +1383,28 @@ impl Context {
     /// to call `.unwrap()` if you want to make sure that it works, or `.ok()` if you don't care.
     pub fn insert_debug_marker(&self, marker: &str) -> Result<(), ()> {
         let ctxt = self.make_current();
+        let marker = truncate_debug_string(marker);
+
+        if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+           (ctxt.version >= &Version(Api::Gl, 1, 0) && ctxt.extensions.gl_khr_debug)
+        {
+            let marker = marker.as_bytes();
+            unsafe { ctxt.gl.DebugMessageInsert(gl::DEBUG_SOURCE_APPLICATION, gl::DEBUG_TYPE_MARKER,
+                                                0, gl::DEBUG_SEVERITY_NOTIFICATION,
+                                                marker.len() as gl::types::GLsizei,
+                                                marker.as_ptr() as *const _) };
+            Ok(())
+
+        } else if ctxt.version >= &Version(Api::GlEs, 2, 0) && ctxt.extensions.gl_khr_debug {
+            let marker = marker.as_bytes();
+            unsafe { ctxt.gl.DebugMessageInsertKHR(gl::DEBUG_SOURCE_APPLICATION,
+                                                   gl::DEBUG_TYPE_MARKER, 0,
+                                                   gl::DEBUG_SEVERITY_NOTIFICATION,
+                                                   marker.len() as gl::types::GLsizei,
+                                                   marker.as_ptr() as *const _) };
+            Ok(())
 
-        if ctxt.extensions.gl_gremedy_string_marker {
+        } else if ctxt.extensions.gl_gremedy_string_marker {
             let marker = marker.as_bytes();
             unsafe { ctxt.gl.StringMarkerGREMEDY(marker.len() as gl::types::GLsizei,
                                                  marker.as_ptr() as *const _) };
@@ -582,6 +1440,14 @@ impl ContextExt for Context {
     }
 
     fn make_current(&self) -> CommandContext {
+        let mut ctxt = self.make_current_for_clear();
+        // the pending clear must be executed before any other command, otherwise it would
+        // be reordered after them
+        ops::flush_pending_clear(&mut ctxt);
+        ctxt
+    }
+
+    fn make_current_for_clear(&self) -> CommandContext {
         if self.check_current_context {
             let backend = self.backend.borrow();
             if !backend.is_current() {
@@ -596,20 +1462,82 @@ impl ContextExt for Context {
             version: &self.version,
             extensions: &self.extensions,
             capabilities: &self.capabilities,
+            workarounds: self.workarounds.get(),
             report_debug_output_errors: &self.report_debug_output_errors,
             vertex_array_objects: &self.vertex_array_objects,
             framebuffer_objects: self.framebuffer_objects.as_ref().unwrap(),
             samplers: self.samplers.borrow_mut(),
             resident_texture_handles: self.resident_texture_handles.borrow_mut(),
             resident_image_handles: self.resident_image_handles.borrow_mut(),
+            global_uniform_blocks: &self.global_uniform_blocks,
+            draw_uniforms: &self.draw_uniforms,
+            clear_statistics: &self.clear_statistics,
+            frame_statistics: &self.frame_statistics,
+            live_objects: &self.live_objects,
+            internal_object_labels: &self.internal_object_labels,
+            tracer: &self.tracer,
+            active_guard: CommandContextGuard::new(&self.command_context_active),
             marker: PhantomData,
         }
     }
 
+    fn make_current_for_drop(&self) -> Option<CommandContext> {
+        if self.command_context_active.get() {
+            if cfg!(debug_assertions) {
+                println!("glium warning: an OpenGL object is destroyed while its context is in \
+                          use, which usually means that a panic happened during an operation; \
+                          the object is leaked");
+            }
+
+            return None;
+        }
+
+        Some(self.make_current())
+    }
+
     #[inline]
     fn capabilities(&self) -> &Capabilities {
         &self.capabilities
     }
+
+    fn for_each_shared_context<F>(&self, mut f: F) where F: FnMut(&mut CommandContext) {
+        let contexts = self.share_group.contexts.borrow();
+
+        for &other in contexts.iter() {
+            if other == self as *const Context {
+                continue;
+            }
+
+            let other = unsafe { &*other };
+            let mut ctxt = other.make_current();
+            f(&mut ctxt);
+        }
+    }
+
+    #[inline]
+    fn get_memory_usage_counters(&self) -> &MemoryUsageCounters {
+        &self.share_group.memory_usage
+    }
+
+    #[inline]
+    fn get_frame_statistics_counters(&self) -> &FrameStatisticsCounters {
+        &self.frame_statistics
+    }
+
+    #[inline]
+    fn get_deferred_drop_queue(&self) -> &sync::DeferredDropQueue {
+        &self.share_group.deferred_drops
+    }
+
+    #[inline]
+    fn flush_deferred_drops(&self) {
+        sync::destroy_deferred_drops(&self.share_group.deferred_drops, self);
+    }
+
+    #[inline]
+    fn get_fill_resources(&self) -> &RefCell<Option<ops::FillResources>> {
+        &self.fill_resources
+    }
 }
 
 impl CapabilitiesSource for Context {
@@ -631,6 +1559,19 @@ impl CapabilitiesSource for Context {
 
 impl Drop for Context {
     fn drop(&mut self) {
+        // destroying the objects that have been dropped in other threads and the internal
+        // objects while the context is still registered in its share group
+        self.flush_deferred_drops();
+        self.release_internal_resources();
+
+        self.share_group.contexts.borrow_mut().retain(|&c| c != self as *const Context);
+
+        // if this was the last context of the group, objects dropped from now on can't be
+        // destroyed anymore and are simply forgotten by their `DeferredDrop`
+        if self.share_group.contexts.borrow().is_empty() {
+            sync::close_deferred_drop_queue(&self.share_group.deferred_drops);
+        }
+
         unsafe {
             // this is the code of make_current duplicated here because we can't borrow
             // `self` twice
@@ -647,22 +1588,38 @@ impl Drop for Context {
                 version: &self.version,
                 extensions: &self.extensions,
                 capabilities: &self.capabilities,
+                workarounds: self.workarounds.get(),
                 report_debug_output_errors: &self.report_debug_output_errors,
                 vertex_array_objects: &self.vertex_array_objects,
                 framebuffer_objects: self.framebuffer_objects.as_ref().unwrap(),
                 samplers: self.samplers.borrow_mut(),
                 resident_texture_handles: self.resident_texture_handles.borrow_mut(),
                 resident_image_handles: self.resident_image_handles.borrow_mut(),
+                global_uniform_blocks: &self.global_uniform_blocks,
+                draw_uniforms: &self.draw_uniforms,
+                clear_statistics: &self.clear_statistics,
+                frame_statistics: &self.frame_statistics,
+                live_objects: &self.live_objects,
+                internal_object_labels: &self.internal_object_labels,
+                tracer: &self.tracer,
+                active_guard: CommandContextGuard::new(&self.command_context_active),
                 marker: PhantomData,
             };
 
             fbo::FramebuffersContainer::cleanup(&mut ctxt);
             vertex_array_object::VertexAttributesSystem::cleanup(&mut ctxt);
 
-            for (_, s) in mem::replace(&mut *ctxt.samplers, HashMap::with_capacity(0)) {
-                s.destroy(&mut ctxt);
+            sampler_object::purge_all(&mut ctxt);
+            DrawUniformsRing::purge_all(&mut ctxt);
+            tracing::purge_all(&mut ctxt);
+
+            for fence in self.frame_pacer.borrow_mut().drain() {
+                sync::destroy_linear_sync_fence(&mut ctxt, fence);
             }
 
+            // all the objects created by the user should have been destroyed by now
+            self.live_objects.report_leaks();
+
             // disabling callback
             if ctxt.state.enabled_debug_output != Some(false) {
                 if ctxt.version >= &Version(Api::Gl, 4,5) || ctxt.extensions.gl_khr_debug {
@@ -696,7 +1653,247 @@ impl<'a> CapabilitiesSource for CommandContext<'a> {
     }
 }
 
+/// Resets the OpenGL state to the values of `GlState::default()`, and resets the state cache.
+///
+/// Used after external code has modified the state behind our back.
+unsafe fn reset_state(ctxt: &mut CommandContext) {
+    let gl = ctxt.gl;
+    let version = ctxt.version;
+    let extensions = ctxt.extensions;
+
+    // program and vertex array
+    if version >= &Version(Api::Gl, 2, 0) || version >= &Version(Api::GlEs, 2, 0) {
+        gl.UseProgram(0);
+    } else if extensions.gl_arb_shader_objects {
+        gl.UseProgramObjectARB(0 as gl::types::GLhandleARB);
+    }
+
+    if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+       extensions.gl_arb_vertex_array_object
+    {
+        gl.BindVertexArray(0);
+    } else if extensions.gl_oes_vertex_array_object {
+        gl.BindVertexArrayOES(0);
+    } else if extensions.gl_apple_vertex_array_object {
+        gl.BindVertexArrayAPPLE(0);
+    }
+
+    // buffers
+    gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+    if version >= &Version(Api::Gl, 2, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+       extensions.gl_arb_pixel_buffer_object
+    {
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+        gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+    }
+    if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 0) {
+        gl.BindBuffer(gl::UNIFORM_BUFFER, 0);
+        gl.BindBuffer(gl::COPY_READ_BUFFER, 0);
+        gl.BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+    }
+    if version >= &Version(Api::Gl, 3, 1) {
+        gl.BindBuffer(gl::TEXTURE_BUFFER, 0);
+    }
+    if version >= &Version(Api::Gl, 4, 0) || version >= &Version(Api::GlEs, 3, 1) {
+        gl.BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+    }
+    if version >= &Version(Api::Gl, 4, 2) || version >= &Version(Api::GlEs, 3, 1) {
+        gl.BindBuffer(gl::ATOMIC_COUNTER_BUFFER, 0);
+    }
+    if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 1) {
+        gl.BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, 0);
+        gl.BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+    }
+    if version >= &Version(Api::Gl, 4, 4) {
+        gl.BindBuffer(gl::QUERY_BUFFER, 0);
+    }
+
+    // framebuffers
+    if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 2, 0) ||
+       extensions.gl_arb_framebuffer_object
+    {
+        gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl.BindRenderbuffer(gl::RENDERBUFFER, 0);
+    } else if extensions.gl_ext_framebuffer_object {
+        gl.BindFramebufferEXT(gl::FRAMEBUFFER_EXT, 0);
+        gl.BindRenderbufferEXT(gl::RENDERBUFFER_EXT, 0);
+    }
+
+    // texture units ; the textures themselves don't need to be unbound, as we always bind the
+    // textures that we use, but a leftover sampler would change the way they are sampled
+    gl.ActiveTexture(gl::TEXTURE0);
+    if version >= &Version(Api::Gl, 3, 3) || extensions.gl_arb_sampler_objects {
+        for unit in 0 .. ctxt.capabilities.max_combined_texture_image_units {
+            gl.BindSampler(unit as gl::types::GLuint, 0);
+        }
+    }
+
+    // draw parameters
+    gl.Disable(gl::BLEND);
+    gl.Disable(gl::CULL_FACE);
+    gl.Disable(gl::DEPTH_TEST);
+    gl.Disable(gl::DITHER);
+    gl.Disable(gl::POLYGON_OFFSET_FILL);
+    gl.Disable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+    gl.Disable(gl::SAMPLE_COVERAGE);
+    gl.Disable(gl::SCISSOR_TEST);
+    gl.Disable(gl::STENCIL_TEST);
+
+    if version >= &Version(Api::Gl, 1, 0) {
+        gl.Enable(gl::MULTISAMPLE);
+        gl.Disable(gl::LINE_SMOOTH);
+        gl.Disable(gl::POLYGON_SMOOTH);
+        gl.Hint(gl::LINE_SMOOTH_HINT, gl::DONT_CARE);
+        gl.Hint(gl::POLYGON_SMOOTH_HINT, gl::DONT_CARE);
+        gl.PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+        gl.PointSize(1.0);
+    }
+
+    if version >= &Version(Api::Gl, 1, 1) {
+        gl.Disable(gl::COLOR_LOGIC_OP);
+        gl.LogicOp(gl::COPY);
+    }
+
+    if version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_depth_clamp ||
+       extensions.gl_nv_depth_clamp
+    {
+        gl.Disable(gl::DEPTH_CLAMP);
+    }
+    if extensions.gl_amd_depth_clamp_separate {
+        gl.Disable(gl::DEPTH_CLAMP_NEAR_AMD);
+        gl.Disable(gl::DEPTH_CLAMP_FAR_AMD);
+    }
+
+    if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) {
+        gl.Disable(gl::RASTERIZER_DISCARD);
+    } else if extensions.gl_ext_transform_feedback {
+        gl.Disable(gl::RASTERIZER_DISCARD_EXT);
+    }
+
+    if version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_framebuffer_srgb ||
+       extensions.gl_ext_framebuffer_srgb || extensions.gl_ext_srgb_write_control
+    {
+        gl.Disable(gl::FRAMEBUFFER_SRGB);
+    }
+
+    if version >= &Version(Api::Gl, 3, 2) {
+        gl.Disable(gl::PROGRAM_POINT_SIZE);
+    }
+
+    for plane in 0 .. ctxt.capabilities.max_clip_distances {
+        gl.Disable(gl::CLIP_DISTANCE0 + plane as gl::types::GLenum);
+    }
+
+    if version >= &Version(Api::Gl, 3, 0) {
+        gl.ClampColor(gl::CLAMP_READ_COLOR, gl::FIXED_ONLY);
+    }
+
+    if version >= &Version(Api::Gl, 4, 0) {
+        gl.PatchParameteri(gl::PATCH_VERTICES, 3);
+    }
+
+    if version >= &Version(Api::Gl, 3, 2) || extensions.gl_arb_provoking_vertex {
+        gl.ProvokingVertex(gl::LAST_VERTEX_CONVENTION);
+    } else if extensions.gl_ext_provoking_vertex {
+        gl.ProvokingVertexEXT(gl::LAST_VERTEX_CONVENTION);
+    }
+
+    gl.BlendEquation(gl::FUNC_ADD);
+    gl.BlendFunc(gl::ONE, gl::ZERO);
+    gl.BlendColor(0.0, 0.0, 0.0, 0.0);
+    gl.DepthFunc(gl::LESS);
+    gl.DepthMask(gl::TRUE);
+    gl.DepthRange(0.0, 1.0);
+    if version >= &Version(Api::Gl, 1, 0) {
+        gl.ClearDepth(1.0);
+    } else {
+        gl.ClearDepthf(1.0);
+    }
+    gl.StencilFunc(gl::ALWAYS, 0, 0xffffffff);
+    gl.StencilMask(0xffffffff);
+    gl.StencilOp(gl::KEEP, gl::KEEP, gl::KEEP);
+    gl.ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+    gl.ClearColor(0.0, 0.0, 0.0, 0.0);
+    gl.ClearStencil(0);
+    gl.LineWidth(1.0);
+    gl.CullFace(gl::BACK);
+    gl.PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+    gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
+    if version >= &Version(Api::Gl, 1, 0) || version >= &Version(Api::GlEs, 3, 0) {
+        gl.PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+        gl.PixelStorei(gl::UNPACK_SKIP_ROWS, 0);
+        gl.PixelStorei(gl::UNPACK_SKIP_PIXELS, 0);
+        gl.PixelStorei(gl::PACK_ROW_LENGTH, 0);
+        gl.PixelStorei(gl::PACK_SKIP_ROWS, 0);
+        gl.PixelStorei(gl::PACK_SKIP_PIXELS, 0);
+    }
+    if version >= &Version(Api::Gl, 1, 2) || version >= &Version(Api::GlEs, 3, 0) {
+        gl.PixelStorei(gl::UNPACK_IMAGE_HEIGHT, 0);
+        gl.PixelStorei(gl::UNPACK_SKIP_IMAGES, 0);
+    }
+    if version >= &Version(Api::Gl, 1, 2) {
+        gl.PixelStorei(gl::PACK_IMAGE_HEIGHT, 0);
+        gl.PixelStorei(gl::PACK_SKIP_IMAGES, 0);
+    }
+
+    // queries, conditional rendering and transform feedback are not supposed to be active
+    // when the state is handed to the external code, so we keep their values
+    let lost_context = ctxt.state.lost_context;
+    let enabled_debug_output = ctxt.state.enabled_debug_output;
+    let enabled_debug_output_synchronous = ctxt.state.enabled_debug_output_synchronous;
+    let next_draw_call_id = ctxt.state.next_draw_call_id;
+    let samples_passed_query = ctxt.state.samples_passed_query;
+    let any_samples_passed_query = ctxt.state.any_samples_passed_query;
+    let any_samples_passed_conservative_query = ctxt.state.any_samples_passed_conservative_query;
+    let primitives_generated_query = ctxt.state.primitives_generated_query;
+    let tf_primitives_written_query = ctxt.state.transform_feedback_primitives_written_query;
+    let time_elapsed_query = ctxt.state.time_elapsed_query;
+    let conditional_render = ctxt.state.conditional_render;
+    let transform_feedback_enabled = ctxt.state.transform_feedback_enabled;
+    let transform_feedback_paused = ctxt.state.transform_feedback_paused;
+    let primitive_bounding_box = ctxt.state.primitive_bounding_box;
+
+    *ctxt.state = GlState {
+        lost_context: lost_context,
+        enabled_debug_output: enabled_debug_output,
+        enabled_debug_output_synchronous: enabled_debug_output_synchronous,
+        samples_passed_query: samples_passed_query,
+        any_samples_passed_query: any_samples_passed_query,
+        any_samples_passed_conservative_query: any_samples_passed_conservative_query,
+        primitives_generated_query: primitives_generated_query,
+        transform_feedback_primitives_written_query: tf_primitives_written_query,
+        time_elapsed_query: time_elapsed_query,
+        conditional_render: conditional_render,
+        transform_feedback_enabled: transform_feedback_enabled,
+        transform_feedback_paused: transform_feedback_paused,
+        primitive_bounding_box: primitive_bounding_box,
+        next_draw_call_id: next_draw_call_id,
+        .. Default::default()
+    };
+}
+
 /// Checks whether the backend supports glium. Returns an `Err` if it doesn't.
+/// Maximum length in bytes of the strings passed to the debug functions of OpenGL. Longer
+/// strings are truncated.
+const MAX_DEBUG_STRING_LENGTH: usize = 255;
+
+/// Truncates a string so that it can be passed to the debug functions of OpenGL.
+///
+/// The length of the string is always passed explicitly, so it doesn't need to be
+/// null-terminated.
+pub fn truncate_debug_string(s: &str) -> &str {
+    if s.len() <= MAX_DEBUG_STRING_LENGTH {
+        return s;
+    }
+
+    let mut end = MAX_DEBUG_STRING_LENGTH;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    &s[.. end]
+}
+
 fn check_gl_compatibility<T>(version: &Version, extensions: &ExtensionsList)
                              -> Result<(), GliumCreationError<T>>
 {
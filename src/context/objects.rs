@@ -0,0 +1,285 @@
+//! Registry of the OpenGL objects that are alive in a context.
+//!
+//! The registry only exists in debug builds. In release builds all its methods are no-ops and
+//! are optimized away.
+
+#[cfg(debug_assertions)]
+use std::cell::RefCell;
+#[cfg(debug_assertions)]
+use std::collections::HashMap;
+
+#[cfg(all(debug_assertions, feature = "object_backtraces"))]
+use backtrace;
+
+/// Kind of OpenGL object tracked by the registry of live objects.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    /// A buffer object.
+    Buffer,
+    /// A texture, including buffer textures.
+    Texture,
+    /// A render buffer.
+    RenderBuffer,
+    /// A framebuffer object created by glium.
+    Framebuffer,
+    /// A program.
+    Program,
+}
+
+impl ObjectKind {
+    #[cfg(debug_assertions)]
+    #[inline]
+    fn index(&self) -> usize {
+        match self {
+            &ObjectKind::Buffer => 0,
+            &ObjectKind::Texture => 1,
+            &ObjectKind::RenderBuffer => 2,
+            &ObjectKind::Framebuffer => 3,
+            &ObjectKind::Program => 4,
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[inline]
+    fn name(&self) -> &'static str {
+        match self {
+            &ObjectKind::Buffer => "buffer",
+            &ObjectKind::Texture => "texture",
+            &ObjectKind::RenderBuffer => "render buffer",
+            &ObjectKind::Framebuffer => "framebuffer",
+            &ObjectKind::Program => "program",
+        }
+    }
+}
+
+/// Number of objects of each kind that are alive in a context.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct LiveObjectCounts {
+    /// Number of buffers that are alive.
+    pub buffers: usize,
+    /// Number of textures that are alive.
+    pub textures: usize,
+    /// Number of render buffers that are alive.
+    pub render_buffers: usize,
+    /// Number of framebuffer objects that are alive.
+    ///
+    /// Framebuffer objects are created and cached by glium when you draw to a texture or to a
+    /// render buffer.
+    pub framebuffers: usize,
+    /// Number of programs that are alive.
+    pub programs: usize,
+}
+
+/// Soft limit of the number of live objects of a kind.
+///
+/// Exceeding a limit usually means that an object is created every frame and never destroyed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ObjectLimit {
+    /// There is no limit.
+    Unlimited,
+
+    /// A warning is printed when the number of live objects exceeds this value.
+    Log(usize),
+
+    /// Glium panics when the number of live objects exceeds this value.
+    Panic(usize),
+}
+
+/// Keeps track of the objects that are alive in a context.
+///
+/// Objects must be added with `add` when they are created and removed with `remove` when they
+/// are destroyed.
+pub struct ObjectRegistry {
+    #[cfg(debug_assertions)]
+    inner: RefCell<RegistryInner>,
+    #[cfg(not(debug_assertions))]
+    _private: (),
+}
+
+#[cfg(debug_assertions)]
+struct RegistryInner {
+    /// Every live object, associated with the instruction pointers of its creation site if
+    /// backtraces are enabled.
+    objects: HashMap<(ObjectKind, usize), Option<Vec<usize>>>,
+
+    /// Number of live objects, indexed by `ObjectKind::index`.
+    counts: [usize; 5],
+
+    /// Limits, indexed by `ObjectKind::index`.
+    limits: [ObjectLimit; 5],
+}
+
+#[cfg(debug_assertions)]
+impl ObjectRegistry {
+    /// Builds an empty registry.
+    #[inline]
+    pub fn new() -> ObjectRegistry {
+        ObjectRegistry {
+            inner: RefCell::new(RegistryInner {
+                objects: HashMap::new(),
+                counts: [0; 5],
+                limits: [ObjectLimit::Unlimited; 5],
+            }),
+        }
+    }
+
+    /// Must be called when an object is created.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the number of objects of this kind exceeds a limit of type
+    /// `ObjectLimit::Panic`.
+    pub fn add(&self, kind: ObjectKind, id: usize) {
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.objects.insert((kind, id), capture_creation_site()).is_none() {
+            inner.counts[kind.index()] += 1;
+        }
+
+        let count = inner.counts[kind.index()];
+        match inner.limits[kind.index()] {
+            ObjectLimit::Unlimited => (),
+            ObjectLimit::Log(limit) => {
+                // only printing the warning when the limit is crossed to avoid flooding the output
+                if count == limit + 1 {
+                    println!("glium warning: {} objects of type {} are alive, which exceeds \
+                              the limit of {}", count, kind.name(), limit);
+                }
+            },
+            ObjectLimit::Panic(limit) => {
+                if count > limit {
+                    drop(inner);
+                    panic!("{} objects of type {} are alive, which exceeds the limit of {}",
+                           count, kind.name(), limit);
+                }
+            },
+        }
+    }
+
+    /// Must be called when an object is destroyed.
+    #[inline]
+    pub fn remove(&self, kind: ObjectKind, id: usize) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.objects.remove(&(kind, id)).is_some() {
+            inner.counts[kind.index()] -= 1;
+        }
+    }
+
+    /// Returns the number of live objects of each kind.
+    #[inline]
+    pub fn get_counts(&self) -> Option<LiveObjectCounts> {
+        let inner = self.inner.borrow();
+
+        Some(LiveObjectCounts {
+            buffers: inner.counts[ObjectKind::Buffer.index()],
+            textures: inner.counts[ObjectKind::Texture.index()],
+            render_buffers: inner.counts[ObjectKind::RenderBuffer.index()],
+            framebuffers: inner.counts[ObjectKind::Framebuffer.index()],
+            programs: inner.counts[ObjectKind::Program.index()],
+        })
+    }
+
+    /// Changes the limit of the number of live objects of a kind.
+    #[inline]
+    pub fn set_limit(&self, kind: ObjectKind, limit: ObjectLimit) {
+        self.inner.borrow_mut().limits[kind.index()] = limit;
+    }
+
+    /// Prints a warning for each object that is still alive.
+    ///
+    /// Must be called when the context is destroyed, after glium has destroyed its own objects.
+    pub fn report_leaks(&self) {
+        let inner = self.inner.borrow();
+
+        for (&(kind, id), creation_site) in inner.objects.iter() {
+            println!("glium warning: {} #{} is still alive while its context is being destroyed",
+                     kind.name(), id);
+
+            if let &Some(ref creation_site) = creation_site {
+                print_creation_site(creation_site);
+            }
+        }
+    }
+}
+
+#[cfg(not(debug_assertions))]
+impl ObjectRegistry {
+    /// Builds an empty registry.
+    #[inline]
+    pub fn new() -> ObjectRegistry {
+        ObjectRegistry { _private: () }
+    }
+
+    /// Must be called when an object is created.
+    #[inline]
+    pub fn add(&self, _: ObjectKind, _: usize) {
+    }
+
+    /// Must be called when an object is destroyed.
+    #[inline]
+    pub fn remove(&self, _: ObjectKind, _: usize) {
+    }
+
+    /// Returns the number of live objects of each kind.
+    #[inline]
+    pub fn get_counts(&self) -> Option<LiveObjectCounts> {
+        None
+    }
+
+    /// Changes the limit of the number of live objects of a kind.
+    #[inline]
+    pub fn set_limit(&self, _: ObjectKind, _: ObjectLimit) {
+    }
+
+    /// Prints a warning for each object that is still alive.
+    #[inline]
+    pub fn report_leaks(&self) {
+    }
+}
+
+/// Returns the instruction pointers of the current call stack.
+#[cfg(all(debug_assertions, feature = "object_backtraces"))]
+fn capture_creation_site() -> Option<Vec<usize>> {
+    let mut ips = Vec::new();
+    backtrace::trace(&mut |frame| {
+        ips.push(frame.ip() as usize);
+        true
+    });
+    Some(ips)
+}
+
+#[cfg(all(debug_assertions, not(feature = "object_backtraces")))]
+#[inline]
+fn capture_creation_site() -> Option<Vec<usize>> {
+    None
+}
+
+/// Prints the call stack that has been captured by `capture_creation_site`.
+#[cfg(all(debug_assertions, feature = "object_backtraces"))]
+fn print_creation_site(ips: &[usize]) {
+    for (frame_id, &ip) in ips.iter().enumerate() {
+        print!("{:>#4} - {:p}", frame_id + 1, ip as *mut ());
+
+        backtrace::resolve(ip as *mut _, &mut |symbol| {
+            let name = String::from_utf8(symbol.name()
+                                               .unwrap_or(&b"<unknown>"[..])
+                                               .to_owned())
+                            .unwrap_or_else(|_| "<not-utf8>".to_owned());
+            let filename = String::from_utf8(symbol.filename()
+                                                   .unwrap_or(&b"<unknown>"[..])
+                                                   .to_owned())
+                                .unwrap_or_else(|_| "<not-utf8>".to_owned());
+            let line = symbol.lineno().map(|l| l.to_string())
+                                      .unwrap_or_else(|| "??".to_owned());
+
+            print!("\n         {} at {}:{}", name, filename, line);
+        });
+
+        println!("");
+    }
+}
+
+#[cfg(all(debug_assertions, not(feature = "object_backtraces")))]
+#[inline]
+fn print_creation_site(_: &[usize]) {
+}
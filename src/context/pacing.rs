@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+use std::mem;
+
+/// Keeps track of the fences inserted at the end of the frames, in order to limit the number
+/// of frames that the GPU can execute behind the CPU.
+///
+/// The type of fence is a template parameter so that the bookkeeping doesn't depend on the
+/// backend.
+pub struct FramePacer<F> {
+    max_frames_in_flight: u32,
+    /// Fences of the last frames, from the oldest to the most recent. Never contains more
+    /// than `max_frames_in_flight` elements.
+    fences: VecDeque<F>,
+}
+
+impl<F> FramePacer<F> {
+    /// Builds a disabled `FramePacer`.
+    #[inline]
+    pub fn new() -> FramePacer<F> {
+        FramePacer {
+            max_frames_in_flight: 0,
+            fences: VecDeque::new(),
+        }
+    }
+
+    /// Returns the maximum number of frames in flight, or `0` if there is no limit.
+    #[inline]
+    pub fn get_max_frames_in_flight(&self) -> u32 {
+        self.max_frames_in_flight
+    }
+
+    /// Returns true if a fence must be passed to `end_frame` at the end of each frame.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.max_frames_in_flight != 0
+    }
+
+    /// Changes the maximum number of frames in flight.
+    ///
+    /// Returns the fences that are no longer needed. They must be destroyed by the caller.
+    pub fn set_max_frames_in_flight(&mut self, max: u32) -> Vec<F> {
+        self.max_frames_in_flight = max;
+
+        let mut unused = Vec::new();
+        while self.fences.len() > max as usize {
+            unused.push(self.fences.pop_front().unwrap());
+        }
+        unused
+    }
+
+    /// Registers the fence inserted at the end of a frame.
+    ///
+    /// Returns the oldest fence if there are too many of them, for example if the buffers have
+    /// been swapped several times without `start_frame` being called. It must be destroyed by
+    /// the caller.
+    pub fn end_frame(&mut self, fence: F) -> Option<F> {
+        if !self.is_enabled() {
+            return Some(fence);
+        }
+
+        self.fences.push_back(fence);
+
+        if self.fences.len() > self.max_frames_in_flight as usize {
+            self.fences.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the fence that must be waited upon before starting a new frame, if any.
+    ///
+    /// This is the fence of the frame that has been finished `max_frames_in_flight` frames
+    /// ago. The caller must wait for it, then destroy it.
+    pub fn start_frame(&mut self) -> Option<F> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        if self.fences.len() >= self.max_frames_in_flight as usize {
+            self.fences.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Removes all the fences. They must be destroyed by the caller.
+    #[inline]
+    pub fn drain(&mut self) -> Vec<F> {
+        mem::replace(&mut self.fences, VecDeque::new()).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FramePacer;
+
+    /// Simulates the main loop. `frame` is the number of the frame that is being started,
+    /// and each fence is the number of the frame it was inserted after.
+    fn run_frames(pacer: &mut FramePacer<u32>, frames: u32) -> Vec<Option<u32>> {
+        let mut waits = Vec::new();
+
+        for frame in 0 .. frames {
+            waits.push(pacer.start_frame());
+            assert!(pacer.end_frame(frame).is_none());
+        }
+
+        waits
+    }
+
+    #[test]
+    fn disabled() {
+        let mut pacer = FramePacer::new();
+        assert!(!pacer.is_enabled());
+        assert_eq!(pacer.end_frame(0), Some(0));
+        assert_eq!(pacer.start_frame(), None);
+    }
+
+    #[test]
+    fn one_frame_in_flight() {
+        let mut pacer = FramePacer::new();
+        assert!(pacer.set_max_frames_in_flight(1).is_empty());
+
+        let waits = run_frames(&mut pacer, 4);
+        assert_eq!(waits, vec![None, Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn two_frames_in_flight() {
+        let mut pacer = FramePacer::new();
+        pacer.set_max_frames_in_flight(2);
+
+        let waits = run_frames(&mut pacer, 5);
+        assert_eq!(waits, vec![None, None, Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn swaps_without_start() {
+        let mut pacer = FramePacer::new();
+        pacer.set_max_frames_in_flight(2);
+
+        assert_eq!(pacer.end_frame(0), None);
+        assert_eq!(pacer.end_frame(1), None);
+        assert_eq!(pacer.end_frame(2), Some(0));
+        assert_eq!(pacer.start_frame(), Some(1));
+    }
+
+    #[test]
+    fn reduce_limit() {
+        let mut pacer = FramePacer::new();
+        pacer.set_max_frames_in_flight(3);
+        run_frames(&mut pacer, 3);
+
+        assert_eq!(pacer.set_max_frames_in_flight(1), vec![0, 1]);
+        assert_eq!(pacer.start_frame(), Some(2));
+
+        pacer.end_frame(3);
+        assert_eq!(pacer.set_max_frames_in_flight(0), vec![3]);
+        assert!(pacer.drain().is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use Handle;
+use draw_parameters::DepthClamp;
 use gl;
 use smallvec::SmallVec;
 
@@ -36,6 +37,9 @@ pub struct GlState {
     /// Whether GL_DITHER is enabled
     pub enabled_dither: bool,
 
+    /// Whether GL_COLOR_LOGIC_OP is enabled
+    pub enabled_color_logic_op: bool,
+
     /// Whether GL_FRAMEBUFFER_SRGB is enabled
     pub enabled_framebuffer_srgb: bool,
 
@@ -85,6 +89,12 @@ pub struct GlState {
     /// The latest value passed to `glClearStencil`.
     pub clear_stencil: gl::types::GLint,
 
+    /// A `glClear` that hasn't been executed yet, so that it can be merged with the next one.
+    pub pending_clear: Option<PendingClear>,
+
+    /// Number of debug groups that have been pushed and not popped yet.
+    pub debug_group_depth: u32,
+
     /// The latest values passed to ``glColorMask`.
     pub color_mask: (gl::types::GLboolean, gl::types::GLboolean,
                      gl::types::GLboolean, gl::types::GLboolean),
@@ -161,6 +171,9 @@ pub struct GlState {
     pub blend_color: (gl::types::GLclampf, gl::types::GLclampf,
                       gl::types::GLclampf, gl::types::GLclampf),
 
+    /// The latest value passed to `glLogicOp`.
+    pub logic_op: gl::types::GLenum,
+
     /// The latest value passed to `glDepthFunc`.
     pub depth_func: gl::types::GLenum,
 
@@ -211,15 +224,51 @@ pub struct GlState {
     /// The latest value passed to `glHint` for smoothing.
     pub smooth: (gl::types::GLenum, gl::types::GLenum),
 
+    /// The latest value passed to `glHint` for `GL_FRAGMENT_SHADER_DERIVATIVE_HINT`.
+    pub derivative_hint: gl::types::GLenum,
+
     /// The latest value passed to `glProvokingVertex`.
     pub provoking_vertex: gl::types::GLenum,
 
+    /// Bitmask of the `GL_CLIP_DISTANCEi` that are currently enabled.
+    pub enabled_clip_planes: u32,
+
     /// The latest value passed to `glPixelStore` with `GL_UNPACK_ALIGNMENT`.
     pub pixel_store_unpack_alignment: gl::types::GLint,
 
+    /// The latest value passed to `glPixelStore` with `GL_UNPACK_ROW_LENGTH`.
+    pub pixel_store_unpack_row_length: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_UNPACK_SKIP_ROWS`.
+    pub pixel_store_unpack_skip_rows: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_UNPACK_SKIP_PIXELS`.
+    pub pixel_store_unpack_skip_pixels: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_UNPACK_IMAGE_HEIGHT`.
+    pub pixel_store_unpack_image_height: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_UNPACK_SKIP_IMAGES`.
+    pub pixel_store_unpack_skip_images: gl::types::GLint,
+
     /// The latest value passed to `glPixelStore` with `GL_PACK_ALIGNMENT`.
     pub pixel_store_pack_alignment: gl::types::GLint,
 
+    /// The latest value passed to `glPixelStore` with `GL_PACK_ROW_LENGTH`.
+    pub pixel_store_pack_row_length: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_PACK_SKIP_ROWS`.
+    pub pixel_store_pack_skip_rows: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_PACK_SKIP_PIXELS`.
+    pub pixel_store_pack_skip_pixels: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_PACK_IMAGE_HEIGHT`.
+    pub pixel_store_pack_image_height: gl::types::GLint,
+
+    /// The latest value passed to `glPixelStore` with `GL_PACK_SKIP_IMAGES`.
+    pub pixel_store_pack_skip_images: gl::types::GLint,
+
     /// The latest value passed to `glClampColor`.
     pub clamp_color: gl::types::GLenum,
 
@@ -266,6 +315,13 @@ pub struct GlState {
     /// The latest value passed to `glPrimitiveBoundingBox`.
     pub primitive_bounding_box: (f32, f32, f32, f32, f32, f32, f32, f32),
 
+    /// Program, depth range and depth clamping of the latest draw command that looked like the
+    /// first pass of a depth pre-pass. Only used to print warnings in debug builds.
+    pub depth_prepass: Option<(Handle, (f32, f32), DepthClamp)>,
+
+    /// True if a warning has already been printed about a mismatch with `depth_prepass`.
+    pub depth_prepass_warned: bool,
+
     /// Current draw call ID.
     /// We maintain a counter that is incremented at each draw call.
     pub next_draw_call_id: u64,
@@ -350,6 +406,25 @@ pub struct IndexedBufferState {
     pub size: gl::types::GLsizeiptr,
 }
 
+/// A call to `glClear` whose execution has been delayed.
+///
+/// The state of the context is the one required by the clear, except for the framebuffer
+/// binding which may have changed when building another framebuffer object.
+#[derive(Copy, Clone, Debug)]
+pub struct PendingClear {
+    /// Id of the framebuffer to clear.
+    pub framebuffer: gl::types::GLuint,
+
+    /// Scissor box, or `None` if the whole framebuffer is cleared.
+    pub rect: Option<(gl::types::GLint, gl::types::GLint, gl::types::GLsizei, gl::types::GLsizei)>,
+
+    /// Whether the clear color is already in sRGB.
+    pub color_srgb: bool,
+
+    /// The mask to pass to `glClear`.
+    pub mask: gl::types::GLbitfield,
+}
+
 /// Builds the `GlState` corresponding to a newly-created OpenGL context.
 impl Default for GlState {
     fn default() -> GlState {
@@ -370,6 +445,7 @@ impl Default for GlState {
             enabled_depth_clamp_near: false,
             enabled_depth_clamp_far: false,
             enabled_dither: false,
+            enabled_color_logic_op: false,
             enabled_framebuffer_srgb: false,
             enabled_multisample: true,
             enabled_polygon_offset_fill: false,
@@ -387,6 +463,8 @@ impl Default for GlState {
             clear_color: (0.0, 0.0, 0.0, 0.0),
             clear_depth: 1.0,
             clear_stencil: 0,
+            pending_clear: None,
+            debug_group_depth: 0,
             color_mask: (1, 1, 1, 1),
             array_buffer_binding: 0,
             pixel_pack_buffer_binding: 0,
@@ -420,6 +498,7 @@ impl Default for GlState {
             blend_equation: (gl::FUNC_ADD, gl::FUNC_ADD),
             blend_func: (gl::ONE, gl::ZERO, gl::ONE, gl::ZERO),
             blend_color: (0.0, 0.0, 0.0, 0.0),
+            logic_op: gl::COPY,
             viewport: None,
             scissor: None,
             line_width: 1.0,
@@ -427,9 +506,21 @@ impl Default for GlState {
             cull_face: gl::BACK,
             polygon_mode: gl::FILL,
             smooth: (gl::DONT_CARE, gl::DONT_CARE),
+            derivative_hint: gl::DONT_CARE,
             provoking_vertex: gl::LAST_VERTEX_CONVENTION,
+            enabled_clip_planes: 0,
             pixel_store_unpack_alignment: 4,
+            pixel_store_unpack_row_length: 0,
+            pixel_store_unpack_skip_rows: 0,
+            pixel_store_unpack_skip_pixels: 0,
+            pixel_store_unpack_image_height: 0,
+            pixel_store_unpack_skip_images: 0,
             pixel_store_pack_alignment: 4,
+            pixel_store_pack_row_length: 0,
+            pixel_store_pack_skip_rows: 0,
+            pixel_store_pack_skip_pixels: 0,
+            pixel_store_pack_image_height: 0,
+            pixel_store_pack_skip_images: 0,
             clamp_color: gl::FIXED_ONLY,
             patch_patch_vertices: 3,
             active_texture: 0,
@@ -444,6 +535,8 @@ impl Default for GlState {
             transform_feedback_enabled: None,
             transform_feedback_paused: false,
             primitive_bounding_box: (-1.0, -1.0, -1.0, -1.0, 1.0, 1.0, 1.0, 1.0),
+            depth_prepass: None,
+            depth_prepass_warned: false,
 
             next_draw_call_id: 1,
             latest_memory_barrier_vertex_attrib_array: 1,
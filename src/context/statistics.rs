@@ -0,0 +1,194 @@
+use std::cell::Cell;
+use std::cmp;
+use std::time::{Duration, Instant};
+
+/// Number of operations that have been executed by glium during the current frame.
+///
+/// The statistics are only collected after they have been enabled with
+/// `Context::set_frame_statistics_enabled`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct FrameStatistics {
+    /// Number of draw commands, including the ones of command lists.
+    pub draw_calls: usize,
+
+    /// Number of times a surface has been cleared.
+    pub clears: usize,
+
+    /// Number of blit operations.
+    pub blits: usize,
+
+    /// Number of times the content of a surface has been read.
+    pub reads: usize,
+
+    /// Number of times the current program has been changed.
+    pub program_changes: usize,
+
+    /// Number of times the current framebuffer has been changed.
+    pub framebuffer_changes: usize,
+
+    /// Number of times a texture has been bound to a texture unit. If `glBindTextures` is
+    /// supported, binding all the textures of a draw command counts as one.
+    pub texture_binds: usize,
+
+    /// Number of times data has been uploaded to a buffer.
+    pub buffer_uploads: usize,
+
+    /// Number of bytes uploaded to buffers.
+    pub buffer_bytes_uploaded: usize,
+
+    /// Number of times a buffer has been mapped.
+    pub buffer_maps: usize,
+
+    /// Number of bytes read from buffers.
+    pub buffer_bytes_read: usize,
+
+    /// Number of times a buffer or a texture had to be bound only in order to be modified or
+    /// read. This stays at zero for buffer operations if `Capabilities::direct_state_access`
+    /// is true.
+    pub edit_binds: usize,
+
+    /// Time spent by glium waiting for the GPU.
+    pub sync: SyncStatistics,
+}
+
+/// Times where glium had to block the current thread until the GPU had finished some work.
+///
+/// Like the other statistics, these are only measured after they have been enabled with
+/// `Context::set_frame_statistics_enabled`. Otherwise the clock is never read.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SyncStatistics {
+    /// Waits on a fence, for example before a buffer that is still in use by the GPU is
+    /// mapped or modified, or when calling `SyncFence::wait`.
+    pub fence_waits: WaitStatistics,
+
+    /// Reads of the content of a surface, a texture or a buffer into client memory. The GPU
+    /// must finish writing the data before it can be read.
+    pub reads: WaitStatistics,
+
+    /// Buffer swaps at the end of a frame. This includes the time spent waiting for the
+    /// vertical synchronization.
+    pub swaps: WaitStatistics,
+
+    /// Calls to `glFinish`.
+    pub finishes: WaitStatistics,
+
+    /// Waits at the start of a frame for the GPU to finish an older frame. See
+    /// `Context::set_max_frames_in_flight`.
+    pub frame_pacing: WaitStatistics,
+}
+
+/// Number and duration of the waits of one category.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct WaitStatistics {
+    /// Number of waits.
+    pub count: usize,
+
+    /// Total time spent waiting.
+    pub total: Duration,
+
+    /// Duration of the longest wait.
+    pub max: Duration,
+}
+
+impl WaitStatistics {
+    /// Adds a wait to the statistics.
+    #[inline]
+    fn add(&mut self, duration: Duration) {
+        self.count += 1;
+        self.total = self.total + duration;
+        self.max = cmp::max(self.max, duration);
+    }
+}
+
+/// Category of a wait. See `SyncStatistics`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WaitKind {
+    /// See `SyncStatistics::fence_waits`.
+    Fence,
+    /// See `SyncStatistics::reads`.
+    Read,
+    /// See `SyncStatistics::swaps`.
+    Swap,
+    /// See `SyncStatistics::finishes`.
+    Finish,
+    /// See `SyncStatistics::frame_pacing`.
+    FramePacing,
+}
+
+/// Counters that are updated by the operations of a context.
+pub struct FrameStatisticsCounters {
+    enabled: Cell<bool>,
+    statistics: Cell<FrameStatistics>,
+}
+
+impl FrameStatisticsCounters {
+    /// Builds disabled counters.
+    #[inline]
+    pub fn new() -> FrameStatisticsCounters {
+        FrameStatisticsCounters {
+            enabled: Cell::new(false),
+            statistics: Cell::new(Default::default()),
+        }
+    }
+
+    /// Enables or disables the collection of statistics. Resets the counters.
+    #[inline]
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+        self.statistics.set(Default::default());
+    }
+
+    /// Updates the statistics. Does nothing if the collection is disabled.
+    #[inline]
+    pub fn update<F>(&self, f: F) where F: FnOnce(&mut FrameStatistics) {
+        if self.enabled.get() {
+            let mut statistics = self.statistics.get();
+            f(&mut statistics);
+            self.statistics.set(statistics);
+        }
+    }
+
+    /// Starts measuring a wait. Returns `None` if the collection is disabled.
+    #[inline]
+    pub fn start_wait(&self) -> Option<Instant> {
+        if self.enabled.get() {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Adds the time elapsed since `start_wait` has been called to the statistics.
+    #[inline]
+    pub fn end_wait(&self, start: Option<Instant>, kind: WaitKind) {
+        let start = match start {
+            Some(start) => start,
+            None => return,
+        };
+
+        let duration = start.elapsed();
+        self.update(|s| {
+            match kind {
+                WaitKind::Fence => s.sync.fence_waits.add(duration),
+                WaitKind::Read => s.sync.reads.add(duration),
+                WaitKind::Swap => s.sync.swaps.add(duration),
+                WaitKind::Finish => s.sync.finishes.add(duration),
+                WaitKind::FramePacing => s.sync.frame_pacing.add(duration),
+            }
+        });
+    }
+
+    /// Returns the current statistics.
+    #[inline]
+    pub fn get(&self) -> FrameStatistics {
+        self.statistics.get()
+    }
+
+    /// Returns the current statistics and resets the counters.
+    #[inline]
+    pub fn take(&self) -> FrameStatistics {
+        let statistics = self.statistics.get();
+        self.statistics.set(Default::default());
+        statistics
+    }
+}
@@ -0,0 +1,657 @@
+//! Records the CPU and GPU duration of the debug scopes, and exports them in the JSON format
+//! of `chrome://tracing`.
+//!
+//! Each debug scope uses two timestamp queries, that are taken from a pool and given back to
+//! it once their results have been retrieved. The results are only retrieved when they are
+//! available, usually a few frames after the scope has ended, so that tracing never stalls.
+
+use std::cell::RefCell;
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::time::Instant;
+
+use gl;
+use context::CommandContext;
+use version::Api;
+use version::Version;
+
+/// The tracing state of a context.
+pub struct Tracer {
+    recorder: RefCell<TraceRecorder<gl::types::GLuint>>,
+
+    /// Origin of the CPU timestamps.
+    origin: Instant,
+}
+
+impl Tracer {
+    /// Builds a new `Tracer`, with tracing disabled.
+    #[inline]
+    pub fn new() -> Tracer {
+        Tracer {
+            recorder: RefCell::new(TraceRecorder::new()),
+            origin: Instant::now(),
+        }
+    }
+
+    /// Returns true if the debug scopes are recorded.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.recorder.borrow().is_enabled()
+    }
+
+    /// Returns the number of nanoseconds since the creation of the tracer.
+    fn cpu_time(&self) -> u64 {
+        let elapsed = self.origin.elapsed();
+        elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64
+    }
+}
+
+/// A debug scope whose timestamps are known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// Name of the scope.
+    pub name: String,
+
+    /// Number of the frame during which the scope started.
+    pub frame: u64,
+
+    /// Number of scopes that were active when this one started.
+    pub depth: u32,
+
+    /// Number of draw commands that have been executed while the scope was active.
+    pub draw_calls: u64,
+
+    /// CPU timestamps of the start and of the end of the scope, in nanoseconds.
+    pub cpu: (u64, u64),
+
+    /// GPU timestamps of the start and of the end of the scope, in nanoseconds and converted
+    /// to the CPU timeline, or `None` if the backend doesn't support timestamp queries.
+    pub gpu: Option<(u64, u64)>,
+}
+
+/// A debug scope whose timestamps haven't all been retrieved yet.
+struct PendingScope<Q> {
+    id: u64,
+    name: String,
+    frame: u64,
+    depth: u32,
+    cpu_begin: u64,
+
+    /// Identifier of the next draw command when the scope started.
+    draw_call_begin: u64,
+
+    /// CPU timestamp and identifier of the next draw command when the scope ended, or `None`
+    /// if the scope hasn't ended yet.
+    end: Option<(u64, u64)>,
+
+    /// Queries that receive the GPU timestamps of the start and of the end of the scope.
+    queries: Option<(Q, Q)>,
+}
+
+/// Bookkeeping of the tracing, independent of the backend.
+///
+/// The type of query is a template parameter so that the bookkeeping can be tested without
+/// an OpenGL context.
+pub struct TraceRecorder<Q> {
+    /// Number of frames whose scopes are kept, or `0` if tracing is disabled.
+    frames: usize,
+
+    /// Queries that can be reused.
+    free_queries: Vec<Q>,
+
+    /// Scopes that haven't ended or whose GPU timestamps aren't available yet, in the order
+    /// in which they started.
+    pending: Vec<PendingScope<Q>>,
+
+    /// Scopes of the last frames whose timestamps are known.
+    completed: Vec<TraceEvent>,
+
+    /// Number of the current frame.
+    current_frame: u64,
+
+    /// Identifier of the next scope.
+    next_id: u64,
+
+    /// Number of scopes that have started and not ended.
+    depth: u32,
+
+    /// Value to add to the GPU timestamps to convert them to the CPU timeline.
+    gpu_offset: i64,
+}
+
+impl<Q> TraceRecorder<Q> {
+    /// Builds a disabled recorder.
+    #[inline]
+    pub fn new() -> TraceRecorder<Q> {
+        TraceRecorder {
+            frames: 0,
+            free_queries: Vec::new(),
+            pending: Vec::new(),
+            completed: Vec::new(),
+            current_frame: 0,
+            next_id: 0,
+            depth: 0,
+            gpu_offset: 0,
+        }
+    }
+
+    /// Returns true if the scopes are recorded.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.frames != 0
+    }
+
+    /// Changes the number of frames whose scopes are kept. `0` disables the tracing and
+    /// removes everything that has been recorded.
+    ///
+    /// Returns the queries that are no longer needed. They must be destroyed by the caller.
+    pub fn set_frames(&mut self, frames: usize) -> Vec<Q> {
+        self.frames = frames;
+
+        if frames != 0 {
+            self.trim();
+            return Vec::new();
+        }
+
+        self.completed.clear();
+        self.depth = 0;
+        self.drain()
+    }
+
+    /// Sets the value to add to the GPU timestamps to convert them to the CPU timeline.
+    #[inline]
+    pub fn set_gpu_offset(&mut self, offset: i64) {
+        self.gpu_offset = offset;
+    }
+
+    /// Returns a query of the pool, if there is one.
+    #[inline]
+    pub fn take_query(&mut self) -> Option<Q> {
+        self.free_queries.pop()
+    }
+
+    /// Registers the start of a scope and returns its identifier.
+    ///
+    /// `draw_call_id` is the identifier of the next draw command. `queries` are the queries
+    /// that receive the GPU timestamps. The first one must have been issued by the caller.
+    pub fn begin_scope(&mut self, name: &str, cpu_time: u64, draw_call_id: u64,
+                       queries: Option<(Q, Q)>) -> u64
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.pending.push(PendingScope {
+            id: id,
+            name: name.to_owned(),
+            frame: self.current_frame,
+            depth: self.depth,
+            cpu_begin: cpu_time,
+            draw_call_begin: draw_call_id,
+            end: None,
+            queries: queries,
+        });
+
+        self.depth += 1;
+        id
+    }
+
+    /// Registers the end of a scope.
+    ///
+    /// Returns the query that must receive the GPU timestamp of the end, if any. Returns `None`
+    /// if the scope has been discarded in the meantime, for example because tracing has been
+    /// disabled.
+    pub fn end_scope(&mut self, id: u64, cpu_time: u64, draw_call_id: u64)
+                     -> Option<Option<&Q>>
+    {
+        let scope = match self.pending.iter_mut().find(|s| s.id == id) {
+            Some(s) => s,
+            None => return None,
+        };
+
+        self.depth = self.depth.saturating_sub(1);
+        scope.end = Some((cpu_time, draw_call_id));
+        Some(scope.queries.as_ref().map(|q| &q.1))
+    }
+
+    /// Moves the scopes whose timestamps are available to the list of completed scopes. Never
+    /// blocks.
+    ///
+    /// `is_available` must return true if the result of a query is available, and `get` must
+    /// return the result. The queries of the completed scopes are put back in the pool.
+    pub fn harvest<A, G>(&mut self, mut is_available: A, mut get: G)
+                         where A: FnMut(&Q) -> bool, G: FnMut(&Q) -> u64
+    {
+        let mut remaining = Vec::with_capacity(self.pending.len());
+
+        for scope in mem::replace(&mut self.pending, Vec::new()).into_iter() {
+            // the end query is executed after the begin query
+            let available = scope.queries.as_ref().map(|q| is_available(&q.1)).unwrap_or(true);
+
+            let (cpu_end, draw_call_end) = match scope.end {
+                Some(end) if available => end,
+                _ => { remaining.push(scope); continue; }
+            };
+
+            let offset = self.gpu_offset;
+            let gpu = scope.queries.as_ref().map(|&(ref begin, ref end)| {
+                ((get(begin) as i64 + offset) as u64, (get(end) as i64 + offset) as u64)
+            });
+
+            if let Some((begin, end)) = scope.queries {
+                self.free_queries.push(begin);
+                self.free_queries.push(end);
+            }
+
+            let event = TraceEvent {
+                name: scope.name,
+                frame: scope.frame,
+                depth: scope.depth,
+                draw_calls: draw_call_end.saturating_sub(scope.draw_call_begin),
+                cpu: (scope.cpu_begin, cpu_end),
+                gpu: gpu,
+            };
+
+            // the frame of a scope that lasted for too long may be outside of the window
+            if self.is_in_window(event.frame) {
+                self.completed.push(event);
+            }
+        }
+
+        self.pending = remaining;
+    }
+
+    /// Indicates that the current frame is over.
+    pub fn end_frame(&mut self) {
+        self.current_frame += 1;
+        self.trim();
+    }
+
+    /// Returns the scopes of the last frames, ordered by frame and then by starting time.
+    pub fn events(&self) -> Vec<&TraceEvent> {
+        let mut events: Vec<&TraceEvent> = self.completed.iter().collect();
+        events.sort_by(|a, b| (a.frame, a.cpu.0).cmp(&(b.frame, b.cpu.0)));
+        events
+    }
+
+    /// Removes all the queries. They must be destroyed by the caller.
+    pub fn drain(&mut self) -> Vec<Q> {
+        let mut queries = mem::replace(&mut self.free_queries, Vec::new());
+
+        for scope in mem::replace(&mut self.pending, Vec::new()).into_iter() {
+            if let Some((begin, end)) = scope.queries {
+                queries.push(begin);
+                queries.push(end);
+            }
+        }
+
+        queries
+    }
+
+    /// Returns true if the scopes that started during `frame` must be kept. These are the
+    /// scopes of the current frame and of the `frames` frames before.
+    #[inline]
+    fn is_in_window(&self, frame: u64) -> bool {
+        frame + self.frames as u64 >= self.current_frame
+    }
+
+    /// Removes the scopes of the frames that are outside of the window.
+    fn trim(&mut self) {
+        let current_frame = self.current_frame;
+        let frames = self.frames as u64;
+        self.completed.retain(|e| e.frame + frames >= current_frame);
+    }
+}
+
+/// Writes the scopes in the JSON format of `chrome://tracing`.
+///
+/// The CPU durations are on the first thread and the GPU durations on the second one.
+pub fn write_chrome_trace<W>(events: &[&TraceEvent], output: &mut W) -> io::Result<()>
+                             where W: Write
+{
+    try!(write!(output, "{{\"traceEvents\":[\n"));
+    try!(write!(output, "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":1,\
+                         \"args\":{{\"name\":\"CPU\"}}}},\n"));
+    try!(write!(output, "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":2,\
+                         \"args\":{{\"name\":\"GPU\"}}}}"));
+
+    for event in events.iter() {
+        try!(write_event(output, event, 1, event.cpu));
+
+        if let Some(gpu) = event.gpu {
+            try!(write_event(output, event, 2, gpu));
+        }
+    }
+
+    write!(output, "\n],\"displayTimeUnit\":\"ns\"}}\n")
+}
+
+/// Writes a complete event. The timestamps are written in microseconds.
+fn write_event<W>(output: &mut W, event: &TraceEvent, thread: u32, (begin, end): (u64, u64))
+                  -> io::Result<()> where W: Write
+{
+    let duration = end.saturating_sub(begin);
+
+    write!(output, ",\n{{\"name\":\"{}\",\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{}.{:03},\
+                    \"dur\":{}.{:03},\"args\":{{\"frame\":{},\"draw_calls\":{}}}}}",
+           escape_json(&event.name), thread, begin / 1000, begin % 1000,
+           duration / 1000, duration % 1000, event.frame, event.draw_calls)
+}
+
+/// Escapes a string so that it can be written between quotes in a JSON document.
+fn escape_json(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Returns true if the backend supports timestamp queries.
+fn timestamps_supported(ctxt: &CommandContext) -> bool {
+    ctxt.version >= &Version(Api::Gl, 3, 3) || ctxt.extensions.gl_arb_timer_query ||
+    ctxt.extensions.gl_ext_disjoint_timer_query
+}
+
+/// Returns true if the `EXT` version of the functions must be used.
+#[inline]
+fn use_ext(ctxt: &CommandContext) -> bool {
+    !(ctxt.version >= &Version(Api::Gl, 3, 3) || ctxt.extensions.gl_arb_timer_query)
+}
+
+/// Changes the number of frames whose scopes are recorded. See `Context::set_tracing`.
+pub fn set_tracing(ctxt: &mut CommandContext, frames: usize) {
+    let tracer = ctxt.tracer;
+    let mut recorder = tracer.recorder.borrow_mut();
+
+    let was_enabled = recorder.is_enabled();
+    let unused = recorder.set_frames(frames);
+    unsafe { delete_queries(ctxt, unused) };
+
+    // the GPU timestamps are converted to the CPU timeline with the offset between the two
+    // clocks when tracing starts ; without `glGetInteger64v` the GPU timestamps keep their own
+    // origin
+    if !was_enabled && frames != 0 && timestamps_supported(ctxt) &&
+       (ctxt.version >= &Version(Api::Gl, 3, 2) || ctxt.version >= &Version(Api::GlEs, 3, 0))
+    {
+        let gpu_time = unsafe {
+            let mut value: gl::types::GLint64 = 0;
+            ctxt.gl.GetInteger64v(gl::TIMESTAMP, &mut value);
+            value
+        };
+
+        recorder.set_gpu_offset(tracer.cpu_time() as i64 - gpu_time);
+    }
+}
+
+/// Starts a scope if tracing is enabled, and returns its identifier.
+pub fn begin_trace_scope(ctxt: &mut CommandContext, name: &str) -> Option<u64> {
+    let tracer = ctxt.tracer;
+    let mut recorder = tracer.recorder.borrow_mut();
+
+    if !recorder.is_enabled() {
+        return None;
+    }
+
+    let queries = if timestamps_supported(ctxt) {
+        let begin = recorder.take_query().unwrap_or_else(|| unsafe { gen_query(ctxt) });
+        let end = recorder.take_query().unwrap_or_else(|| unsafe { gen_query(ctxt) });
+        unsafe { query_counter(ctxt, begin) };
+        Some((begin, end))
+    } else {
+        None
+    };
+
+    let draw_call_id = ctxt.state.next_draw_call_id;
+    Some(recorder.begin_scope(name, tracer.cpu_time(), draw_call_id, queries))
+}
+
+/// Ends a scope returned by `begin_trace_scope`.
+pub fn end_trace_scope(ctxt: &mut CommandContext, id: u64) {
+    let tracer = ctxt.tracer;
+    let mut recorder = tracer.recorder.borrow_mut();
+
+    let draw_call_id = ctxt.state.next_draw_call_id;
+    if let Some(Some(&query)) = recorder.end_scope(id, tracer.cpu_time(), draw_call_id) {
+        unsafe { query_counter(ctxt, query) };
+    }
+}
+
+/// Must be called when the buffers are swapped. Retrieves the timestamps that are available.
+pub fn end_frame(ctxt: &mut CommandContext) {
+    let tracer = ctxt.tracer;
+    let mut recorder = tracer.recorder.borrow_mut();
+
+    if !recorder.is_enabled() {
+        return;
+    }
+
+    recorder.end_frame();
+    harvest(ctxt, &mut recorder);
+}
+
+/// Writes the scopes of the last frames in the JSON format of `chrome://tracing`.
+pub fn export<W>(ctxt: &mut CommandContext, output: &mut W) -> io::Result<()> where W: Write {
+    let tracer = ctxt.tracer;
+    let mut recorder = tracer.recorder.borrow_mut();
+    harvest(ctxt, &mut recorder);
+
+    let events = recorder.events();
+    write_chrome_trace(&events, output)
+}
+
+/// Destroys all the queries. Must be called when the context is destroyed.
+pub fn purge_all(ctxt: &mut CommandContext) {
+    let queries = ctxt.tracer.recorder.borrow_mut().drain();
+    unsafe { delete_queries(ctxt, queries) };
+}
+
+fn harvest(ctxt: &mut CommandContext, recorder: &mut TraceRecorder<gl::types::GLuint>) {
+    let ext = use_ext(ctxt);
+
+    recorder.harvest(|&query| unsafe {
+        let mut value = 0;
+        if ext {
+            ctxt.gl.GetQueryObjectivEXT(query, gl::QUERY_RESULT_AVAILABLE_EXT, &mut value);
+        } else {
+            ctxt.gl.GetQueryObjectiv(query, gl::QUERY_RESULT_AVAILABLE, &mut value);
+        }
+        value != 0
+    }, |&query| unsafe {
+        let mut value = 0;
+        if ext {
+            ctxt.gl.GetQueryObjectui64vEXT(query, gl::QUERY_RESULT_EXT, &mut value);
+        } else {
+            ctxt.gl.GetQueryObjectui64v(query, gl::QUERY_RESULT, &mut value);
+        }
+        value
+    });
+}
+
+unsafe fn gen_query(ctxt: &mut CommandContext) -> gl::types::GLuint {
+    let mut id = 0;
+    if use_ext(ctxt) {
+        ctxt.gl.GenQueriesEXT(1, &mut id);
+    } else {
+        ctxt.gl.GenQueries(1, &mut id);
+    }
+    id
+}
+
+unsafe fn query_counter(ctxt: &mut CommandContext, query: gl::types::GLuint) {
+    if use_ext(ctxt) {
+        ctxt.gl.QueryCounterEXT(query, gl::TIMESTAMP_EXT);
+    } else {
+        ctxt.gl.QueryCounter(query, gl::TIMESTAMP);
+    }
+}
+
+unsafe fn delete_queries(ctxt: &mut CommandContext, queries: Vec<gl::types::GLuint>) {
+    if queries.is_empty() {
+        return;
+    }
+
+    if use_ext(ctxt) {
+        ctxt.gl.DeleteQueriesEXT(queries.len() as gl::types::GLsizei, queries.as_ptr());
+    } else {
+        ctxt.gl.DeleteQueries(queries.len() as gl::types::GLsizei, queries.as_ptr());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TraceEvent, TraceRecorder, write_chrome_trace};
+
+    /// Queries are simulated by their GPU timestamp, and are always available.
+    fn harvest(recorder: &mut TraceRecorder<u64>) {
+        recorder.harvest(|_| true, |&q| q);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut recorder = TraceRecorder::<u64>::new();
+        assert!(!recorder.is_enabled());
+        recorder.end_frame();
+        assert!(recorder.events().is_empty());
+    }
+
+    #[test]
+    fn scopes_of_one_frame() {
+        let mut recorder = TraceRecorder::new();
+        recorder.set_frames(1);
+
+        let outer = recorder.begin_scope("outer", 10, 1, Some((100, 140)));
+        let inner = recorder.begin_scope("inner", 12, 2, Some((110, 120)));
+        assert_eq!(recorder.end_scope(inner, 15, 5), Some(Some(&120)));
+        assert_eq!(recorder.end_scope(outer, 20, 6), Some(Some(&140)));
+        recorder.end_frame();
+        harvest(&mut recorder);
+
+        let events = recorder.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], &TraceEvent {
+            name: "outer".to_owned(), frame: 0, depth: 0, draw_calls: 5, cpu: (10, 20),
+            gpu: Some((100, 140)),
+        });
+        assert_eq!(events[1].name, "inner");
+        assert_eq!(events[1].depth, 1);
+        assert_eq!(events[1].draw_calls, 3);
+
+        // the queries are recycled
+        assert!(recorder.take_query().is_some());
+    }
+
+    #[test]
+    fn results_not_available() {
+        let mut recorder = TraceRecorder::new();
+        recorder.set_frames(4);
+
+        let scope = recorder.begin_scope("scope", 0, 0, Some((1, 2)));
+        recorder.end_scope(scope, 5, 0);
+        recorder.end_frame();
+
+        recorder.harvest(|_| false, |_| panic!());
+        assert!(recorder.events().is_empty());
+        assert!(recorder.take_query().is_none());
+
+        recorder.end_frame();
+        harvest(&mut recorder);
+        assert_eq!(recorder.events().len(), 1);
+        assert_eq!(recorder.events()[0].frame, 0);
+    }
+
+    #[test]
+    fn scope_spanning_frames() {
+        let mut recorder = TraceRecorder::new();
+        recorder.set_frames(2);
+
+        let scope = recorder.begin_scope("loading", 0, 0, Some((0, 50)));
+        recorder.end_frame();
+        harvest(&mut recorder);
+        assert!(recorder.events().is_empty());
+
+        recorder.end_scope(scope, 30, 0);
+        recorder.end_frame();
+        harvest(&mut recorder);
+
+        let events = recorder.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].frame, 0);
+        assert_eq!(events[0].cpu, (0, 30));
+    }
+
+    #[test]
+    fn old_frames_removed() {
+        let mut recorder = TraceRecorder::new();
+        recorder.set_frames(2);
+
+        for frame in 0 .. 5 {
+            let scope = recorder.begin_scope("frame", frame * 10, 0, None);
+            recorder.end_scope(scope, frame * 10 + 5, 0);
+            recorder.end_frame();
+            harvest(&mut recorder);
+        }
+
+        let frames: Vec<u64> = recorder.events().iter().map(|e| e.frame).collect();
+        assert_eq!(frames, vec![3, 4]);
+    }
+
+    #[test]
+    fn disabling_returns_queries() {
+        let mut recorder = TraceRecorder::new();
+        recorder.set_frames(1);
+
+        let scope = recorder.begin_scope("scope", 0, 0, Some((1, 2)));
+        let mut queries = recorder.set_frames(0);
+        queries.sort_by(|a, b| a.cmp(b));
+        assert_eq!(queries, vec![1, 2]);
+
+        assert_eq!(recorder.end_scope(scope, 5, 0), None);
+    }
+
+    #[test]
+    fn gpu_offset() {
+        let mut recorder = TraceRecorder::new();
+        recorder.set_frames(1);
+        recorder.set_gpu_offset(-1000);
+
+        let scope = recorder.begin_scope("scope", 0, 0, Some((1500, 2500)));
+        recorder.end_scope(scope, 5, 0);
+        recorder.end_frame();
+        harvest(&mut recorder);
+
+        assert_eq!(recorder.events()[0].gpu, Some((500, 1500)));
+    }
+
+    #[test]
+    fn chrome_trace() {
+        let event = TraceEvent {
+            name: "shadow \"pass\"".to_owned(),
+            frame: 3,
+            depth: 0,
+            draw_calls: 12,
+            cpu: (1500, 4250),
+            gpu: Some((2000, 3000)),
+        };
+
+        let mut output = Vec::new();
+        write_chrome_trace(&[&event], &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("{\"traceEvents\":["));
+        assert!(output.contains("{\"name\":\"shadow \\\"pass\\\"\",\"ph\":\"X\",\"pid\":1,\
+                                 \"tid\":1,\"ts\":1.500,\"dur\":2.750,\
+                                 \"args\":{\"frame\":3,\"draw_calls\":12}}"));
+        assert!(output.contains("\"tid\":2,\"ts\":2.000,\"dur\":1.000"));
+    }
+}
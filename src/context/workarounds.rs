@@ -0,0 +1,113 @@
+use gl;
+
+use std::ffi::CStr;
+
+/// List of workarounds for driver bugs that are used by glium.
+///
+/// The workarounds are chosen when the context is created, by matching the values of
+/// `GL_VENDOR`, `GL_RENDERER` and `GL_VERSION` against a list of drivers that are known
+/// to be buggy. You can override them with `Context::set_driver_workarounds`, for example
+/// to check whether a bug is still there.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DriverWorkarounds {
+    /// Never call `glInvalidateBufferData` or `glInvalidateBufferSubData`. Invalidating a
+    /// buffer then does nothing, or orphans the buffer if possible.
+    pub no_buffer_invalidation: bool,
+
+    /// Disable `GL_FRAMEBUFFER_SRGB` before calling `glBlitFramebuffer`, so that the driver
+    /// doesn't apply any sRGB conversion when blitting.
+    pub no_srgb_blits: bool,
+}
+
+/// A driver that is known to be buggy.
+struct KnownBug {
+    /// Substring that must be found in `GL_VENDOR`. Empty to match any vendor.
+    vendor: &'static str,
+    /// Substring that must be found in `GL_RENDERER`. Empty to match any renderer.
+    renderer: &'static str,
+    /// Substring that must be found in `GL_VERSION`. Empty to match any version.
+    version: &'static str,
+    /// Enables the workaround.
+    apply: fn(&mut DriverWorkarounds),
+}
+
+/// The list of drivers that are known to be buggy.
+static KNOWN_BUGS: &'static [KnownBug] = &[
+    // invalidating a buffer corrupts the persistent mappings of other buffers
+    KnownBug {
+        vendor: "",
+        renderer: "",
+        version: "Mesa 11.0",
+        apply: no_buffer_invalidation,
+    },
+
+    // sRGB conversion is applied twice when blitting to an sRGB framebuffer
+    KnownBug {
+        vendor: "Qualcomm",
+        renderer: "Adreno",
+        version: "",
+        apply: no_srgb_blits,
+    },
+];
+
+fn no_buffer_invalidation(w: &mut DriverWorkarounds) { w.no_buffer_invalidation = true; }
+fn no_srgb_blits(w: &mut DriverWorkarounds) { w.no_srgb_blits = true; }
+
+/// Returns the workarounds that must be used for a driver.
+pub fn find_workarounds(vendor: &str, renderer: &str, version: &str) -> DriverWorkarounds {
+    let mut workarounds = DriverWorkarounds::default();
+
+    for bug in KNOWN_BUGS.iter() {
+        if vendor.contains(bug.vendor) && renderer.contains(bug.renderer) &&
+           version.contains(bug.version)
+        {
+            (bug.apply)(&mut workarounds);
+        }
+    }
+
+    workarounds
+}
+
+/// Queries the strings of the driver and returns the workarounds to use.
+///
+/// *Safety*: the OpenGL context corresponding to `gl` must be current in the thread.
+pub unsafe fn get_workarounds(gl: &gl::Gl) -> DriverWorkarounds {
+    let get = |name| {
+        let s = gl.GetString(name);
+        if s.is_null() {
+            String::new()
+        } else {
+            String::from_utf8_lossy(CStr::from_ptr(s as *const i8).to_bytes()).into_owned()
+        }
+    };
+
+    find_workarounds(&get(gl::VENDOR), &get(gl::RENDERER), &get(gl::VERSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_workarounds;
+    use super::DriverWorkarounds;
+
+    #[test]
+    fn unknown_driver() {
+        assert_eq!(find_workarounds("NVIDIA Corporation", "GeForce GTX 970/PCIe/SSE2",
+                                    "4.5.0 NVIDIA 358.16"),
+                   DriverWorkarounds::default());
+    }
+
+    #[test]
+    fn mesa() {
+        let w = find_workarounds("Intel Open Source Technology Center",
+                                 "Mesa DRI Intel(R) Haswell Mobile", "3.0 Mesa 11.0.2");
+        assert!(w.no_buffer_invalidation);
+        assert!(!w.no_srgb_blits);
+    }
+
+    #[test]
+    fn adreno() {
+        let w = find_workarounds("Qualcomm", "Adreno (TM) 330", "OpenGL ES 3.0 V@84.0");
+        assert!(!w.no_buffer_invalidation);
+        assert!(w.no_srgb_blits);
+    }
+}
@@ -4,6 +4,7 @@
 */
 
 use backend::Facade;
+use context;
 use context::Context;
 use ContextExt;
 use version::Api;
@@ -213,3 +214,137 @@ impl TimestampQuery {
         }
     }
 }
+
+/// Groups the commands that are executed while it is alive in OpenGL debuggers.
+///
+/// A debug group is pushed when the scope is created and popped when it is destroyed, even when
+/// unwinding. Scopes can be nested. If the backend supports neither `GL_KHR_debug` nor
+/// `GL_EXT_debug_marker`, this does nothing.
+///
+/// The name is truncated if it is longer than 255 bytes.
+///
+/// If tracing has been enabled with `Context::set_tracing`, the CPU and GPU timestamps of the
+/// start and of the end of the scope are recorded, even if debug groups aren't supported.
+///
+/// ## Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+/// let mut frame = display.draw();
+///
+/// {
+///     let _scope = frame.debug_scope("shadow pass");
+///     // draw the shadows here
+/// }
+///
+/// frame.finish().unwrap();
+/// ```
+pub struct DebugScope {
+    context: Rc<Context>,
+    method: Option<DebugGroupMethod>,
+
+    /// Identifier of the scope in the tracer, if tracing is enabled.
+    trace: Option<u64>,
+}
+
+/// Function that has been used to push a debug group.
+#[derive(Copy, Clone)]
+enum DebugGroupMethod {
+    Khr,
+    KhrEs,
+    Ext,
+}
+
+/// Maximum number of debug groups that are pushed at the same time. The minimum value of
+/// `GL_MAX_DEBUG_GROUP_STACK_DEPTH` is 64, and the implementation may have pushed one group.
+const MAX_DEBUG_GROUP_DEPTH: u32 = 63;
+
+impl DebugScope {
+    /// Pushes a debug group and returns an object that pops it when destroyed.
+    pub fn new<F>(facade: &F, name: &str) -> DebugScope where F: Facade {
+        let mut ctxt = facade.get_context().make_current();
+        let trace = context::begin_trace_scope(&mut ctxt, name);
+        let name = context::truncate_debug_string(name).as_bytes();
+
+        let method = if ctxt.state.debug_group_depth >= MAX_DEBUG_GROUP_DEPTH {
+            None
+
+        } else if ctxt.version >= &Version(Api::Gl, 4, 3) ||
+                  ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+                  (ctxt.version >= &Version(Api::Gl, 1, 0) && ctxt.extensions.gl_khr_debug)
+        {
+            unsafe {
+                ctxt.gl.PushDebugGroup(gl::DEBUG_SOURCE_APPLICATION, 0,
+                                       name.len() as gl::types::GLsizei,
+                                       name.as_ptr() as *const _);
+            }
+            Some(DebugGroupMethod::Khr)
+
+        } else if ctxt.version >= &Version(Api::GlEs, 2, 0) && ctxt.extensions.gl_khr_debug {
+            unsafe {
+                ctxt.gl.PushDebugGroupKHR(gl::DEBUG_SOURCE_APPLICATION, 0,
+                                          name.len() as gl::types::GLsizei,
+                                          name.as_ptr() as *const _);
+            }
+            Some(DebugGroupMethod::KhrEs)
+
+        } else if ctxt.extensions.gl_ext_debug_marker {
+            unsafe {
+                ctxt.gl.PushGroupMarkerEXT(name.len() as gl::types::GLsizei,
+                                           name.as_ptr() as *const _);
+            }
+            Some(DebugGroupMethod::Ext)
+
+        } else {
+            None
+        };
+
+        if method.is_some() {
+            ctxt.state.debug_group_depth += 1;
+        }
+
+        DebugScope {
+            context: facade.get_context().clone(),
+            method: method,
+            trace: trace,
+        }
+    }
+
+    /// Returns `true` if a debug group has been pushed.
+    ///
+    /// Returns `false` if the backend doesn't support debug groups, or if too many groups
+    /// are nested.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.method.is_some()
+    }
+}
+
+impl Drop for DebugScope {
+    fn drop(&mut self) {
+        if self.method.is_none() && self.trace.is_none() {
+            return;
+        }
+
+        let mut ctxt = match self.context.make_current_for_drop() {
+            Some(ctxt) => ctxt,
+            None => return,
+        };
+
+        if let Some(method) = self.method {
+            unsafe {
+                match method {
+                    DebugGroupMethod::Khr => ctxt.gl.PopDebugGroup(),
+                    DebugGroupMethod::KhrEs => ctxt.gl.PopDebugGroupKHR(),
+                    DebugGroupMethod::Ext => ctxt.gl.PopGroupMarkerEXT(),
+                }
+            }
+
+            ctxt.state.debug_group_depth -= 1;
+        }
+
+        if let Some(trace) = self.trace {
+            context::end_trace_scope(&mut ctxt, trace);
+        }
+    }
+}
@@ -38,6 +38,29 @@ impl Blend {
             constant_value: (0.0, 0.0, 0.0, 0.0)
         }
     }
+
+    /// Returns true if one of the blending factors uses the second output of the fragment
+    /// shader.
+    ///
+    /// Dual-source blending limits the number of color attachments of the target to
+    /// `Capabilities::max_dual_source_draw_buffers`, which is usually `1`.
+    pub fn uses_dual_source(&self) -> bool {
+        fn factors(function: BlendingFunction) -> Option<(LinearBlendingFactor,
+                                                          LinearBlendingFactor)>
+        {
+            match function {
+                BlendingFunction::Addition { source, destination } |
+                BlendingFunction::Subtraction { source, destination } |
+                BlendingFunction::ReverseSubtraction { source, destination } => {
+                    Some((source, destination))
+                },
+                _ => None,
+            }
+        }
+
+        [self.color, self.alpha].iter().filter_map(|&f| factors(f))
+                                .any(|(s, d)| s.is_dual_source() || d.is_dual_source())
+    }
 }
 
 impl Default for Blend {
@@ -178,9 +201,40 @@ pub enum LinearBlendingFactor {
     /// Multiply the source or destination componet by `1.0` minus the alpha value of
     /// `Blend::const_value`.
     OneMinusConstantAlpha,
+
+    /// Multiply the source or destination component by its corresponding value in the second
+    /// output of the fragment shader.
+    ///
+    /// This is called dual-source blending. The second output is the one bound to the index
+    /// `1` of the first color attachment, either with `layout(location = 0, index = 1)` or with
+    /// `ProgramCreationInput::SourceCode::dual_source_output`. Requires OpenGL 3.3 or the
+    /// `GL_ARB_blend_func_extended` or `GL_EXT_blend_func_extended` extensions.
+    Source1Color,
+
+    /// Equivalent to `1 - Source1Color`.
+    OneMinusSource1Color,
+
+    /// Multiply the source or destination component by the alpha value of the second output of
+    /// the fragment shader. See `Source1Color`.
+    Source1Alpha,
+
+    /// Equivalent to `1 - Source1Alpha`.
+    OneMinusSource1Alpha,
 }
 
 impl LinearBlendingFactor {
+    /// Returns true if the factor uses the second output of the fragment shader.
+    #[inline]
+    pub fn is_dual_source(&self) -> bool {
+        match *self {
+            LinearBlendingFactor::Source1Color |
+            LinearBlendingFactor::OneMinusSource1Color |
+            LinearBlendingFactor::Source1Alpha |
+            LinearBlendingFactor::OneMinusSource1Alpha => true,
+            _ => false,
+        }
+    }
+
     fn to_glenum(&self) -> gl::types::GLenum {
         match *self {
             LinearBlendingFactor::Zero => gl::ZERO,
@@ -198,6 +252,10 @@ impl LinearBlendingFactor {
             LinearBlendingFactor::OneMinusConstantColor => gl::ONE_MINUS_CONSTANT_COLOR,
             LinearBlendingFactor::ConstantAlpha => gl::CONSTANT_ALPHA,
             LinearBlendingFactor::OneMinusConstantAlpha => gl::ONE_MINUS_CONSTANT_ALPHA,
+            LinearBlendingFactor::Source1Color => gl::SRC1_COLOR,
+            LinearBlendingFactor::OneMinusSource1Color => gl::ONE_MINUS_SRC1_COLOR,
+            LinearBlendingFactor::Source1Alpha => gl::SRC1_ALPHA,
+            LinearBlendingFactor::OneMinusSource1Alpha => gl::ONE_MINUS_SRC1_ALPHA,
         }
     }
 }
@@ -0,0 +1,281 @@
+use std::ops::Range;
+
+use Rect;
+use vertex::TransformFeedbackSession;
+
+use draw_parameters::{DrawParameters, BackfaceCullingMode, PolygonMode, Smooth, ProvokingVertex};
+use draw_parameters::{DerivativeHint, LogicOp};
+use draw_parameters::{ConditionalRendering, SamplesQueryParam};
+use draw_parameters::{Blend, Depth, DepthTest, DepthClamp, Stencil};
+use draw_parameters::{TimeElapsedQuery, PrimitivesGeneratedQuery};
+use draw_parameters::TransformFeedbackPrimitivesWrittenQuery;
+
+/// Builds a `DrawParameters` one field at a time.
+///
+/// The fields that are not set keep the value of `DrawParameters::default()`.
+///
+/// # Example
+///
+/// ```
+/// use glium::{DrawParameters, DepthTest, BackfaceCullingMode};
+///
+/// let params = DrawParameters::builder()
+///                     .depth_test(DepthTest::IfLess)
+///                     .depth_write(true)
+///                     .backface_culling(BackfaceCullingMode::CullClockwise)
+///                     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct DrawParametersBuilder<'a> {
+    params: DrawParameters<'a>,
+}
+
+impl<'a> DrawParametersBuilder<'a> {
+    /// Builds a new builder whose fields have their default values.
+    #[inline]
+    pub fn new() -> DrawParametersBuilder<'a> {
+        DrawParametersBuilder {
+            params: Default::default(),
+        }
+    }
+
+    /// Returns the parameters.
+    #[inline]
+    pub fn build(self) -> DrawParameters<'a> {
+        self.params
+    }
+
+    /// Sets the `depth` field.
+    #[inline]
+    pub fn depth(mut self, depth: Depth) -> DrawParametersBuilder<'a> {
+        self.params.depth = depth;
+        self
+    }
+
+    /// Sets `depth.test`.
+    #[inline]
+    pub fn depth_test(mut self, test: DepthTest) -> DrawParametersBuilder<'a> {
+        self.params.depth.test = test;
+        self
+    }
+
+    /// Sets `depth.write`.
+    #[inline]
+    pub fn depth_write(mut self, write: bool) -> DrawParametersBuilder<'a> {
+        self.params.depth.write = write;
+        self
+    }
+
+    /// Sets `depth.range`.
+    #[inline]
+    pub fn depth_range(mut self, near: f32, far: f32) -> DrawParametersBuilder<'a> {
+        self.params.depth.range = (near, far);
+        self
+    }
+
+    /// Sets `depth.clamp`.
+    #[inline]
+    pub fn depth_clamp(mut self, clamp: DepthClamp) -> DrawParametersBuilder<'a> {
+        self.params.depth.clamp = clamp;
+        self
+    }
+
+    /// Sets the `stencil` field.
+    #[inline]
+    pub fn stencil(mut self, stencil: Stencil) -> DrawParametersBuilder<'a> {
+        self.params.stencil = stencil;
+        self
+    }
+
+    /// Sets the `blend` field.
+    #[inline]
+    pub fn blend(mut self, blend: Blend) -> DrawParametersBuilder<'a> {
+        self.params.blend = blend;
+        self
+    }
+
+    /// Sets the `color_mask` field.
+    #[inline]
+    pub fn color_mask(mut self, red: bool, green: bool, blue: bool, alpha: bool)
+                      -> DrawParametersBuilder<'a>
+    {
+        self.params.color_mask = (red, green, blue, alpha);
+        self
+    }
+
+    /// Sets the `line_width` field.
+    #[inline]
+    pub fn line_width(mut self, width: f32) -> DrawParametersBuilder<'a> {
+        self.params.line_width = Some(width);
+        self
+    }
+
+    /// Sets the `point_size` field.
+    #[inline]
+    pub fn point_size(mut self, size: f32) -> DrawParametersBuilder<'a> {
+        self.params.point_size = Some(size);
+        self
+    }
+
+    /// Sets the `backface_culling` field.
+    #[inline]
+    pub fn backface_culling(mut self, mode: BackfaceCullingMode) -> DrawParametersBuilder<'a> {
+        self.params.backface_culling = mode;
+        self
+    }
+
+    /// Sets the `polygon_mode` field.
+    #[inline]
+    pub fn polygon_mode(mut self, mode: PolygonMode) -> DrawParametersBuilder<'a> {
+        self.params.polygon_mode = mode;
+        self
+    }
+
+    /// Sets the `multisampling` field.
+    #[inline]
+    pub fn multisampling(mut self, multisampling: bool) -> DrawParametersBuilder<'a> {
+        self.params.multisampling = multisampling;
+        self
+    }
+
+    /// Sets the `logic_op` field.
+    #[inline]
+    pub fn logic_op(mut self, logic_op: LogicOp) -> DrawParametersBuilder<'a> {
+        self.params.logic_op = Some(logic_op);
+        self
+    }
+
+    /// Sets the `dithering` field.
+    #[inline]
+    pub fn dithering(mut self, dithering: bool) -> DrawParametersBuilder<'a> {
+        self.params.dithering = dithering;
+        self
+    }
+
+    /// Sets the `viewport` field.
+    #[inline]
+    pub fn viewport(mut self, viewport: Rect) -> DrawParametersBuilder<'a> {
+        self.params.viewport = Some(viewport);
+        self
+    }
+
+    /// Sets the `scissor` field.
+    #[inline]
+    pub fn scissor(mut self, scissor: Rect) -> DrawParametersBuilder<'a> {
+        self.params.scissor = Some(scissor);
+        self
+    }
+
+    /// Sets the `draw_primitives` field.
+    #[inline]
+    pub fn draw_primitives(mut self, draw_primitives: bool) -> DrawParametersBuilder<'a> {
+        self.params.draw_primitives = draw_primitives;
+        self
+    }
+
+    /// Sets the `samples_passed_query` field.
+    #[inline]
+    pub fn samples_passed_query<Q>(mut self, query: Q) -> DrawParametersBuilder<'a>
+                                   where Q: Into<SamplesQueryParam<'a>>
+    {
+        self.params.samples_passed_query = Some(query.into());
+        self
+    }
+
+    /// Sets the `time_elapsed_query` field.
+    #[inline]
+    pub fn time_elapsed_query(mut self, query: &'a TimeElapsedQuery)
+                              -> DrawParametersBuilder<'a>
+    {
+        self.params.time_elapsed_query = Some(query);
+        self
+    }
+
+    /// Sets the `primitives_generated_query` field.
+    #[inline]
+    pub fn primitives_generated_query(mut self, query: &'a PrimitivesGeneratedQuery)
+                                      -> DrawParametersBuilder<'a>
+    {
+        self.params.primitives_generated_query = Some(query);
+        self
+    }
+
+    /// Sets the `transform_feedback_primitives_written_query` field.
+    #[inline]
+    pub fn transform_feedback_primitives_written_query(mut self,
+                                        query: &'a TransformFeedbackPrimitivesWrittenQuery)
+                                        -> DrawParametersBuilder<'a>
+    {
+        self.params.transform_feedback_primitives_written_query = Some(query);
+        self
+    }
+
+    /// Sets the `condition` field.
+    #[inline]
+    pub fn condition(mut self, condition: ConditionalRendering<'a>) -> DrawParametersBuilder<'a> {
+        self.params.condition = Some(condition);
+        self
+    }
+
+    /// Sets the `transform_feedback` field.
+    #[inline]
+    pub fn transform_feedback(mut self, session: &'a TransformFeedbackSession<'a>)
+                              -> DrawParametersBuilder<'a>
+    {
+        self.params.transform_feedback = Some(session);
+        self
+    }
+
+    /// Sets the `smooth` field.
+    #[inline]
+    pub fn smooth(mut self, smooth: Smooth) -> DrawParametersBuilder<'a> {
+        self.params.smooth = Some(smooth);
+        self
+    }
+
+    /// Sets the `derivative_hint` field.
+    #[inline]
+    pub fn derivative_hint(mut self, hint: DerivativeHint) -> DrawParametersBuilder<'a> {
+        self.params.derivative_hint = hint;
+        self
+    }
+
+    /// Sets the `provoking_vertex` field.
+    #[inline]
+    pub fn provoking_vertex(mut self, vertex: ProvokingVertex) -> DrawParametersBuilder<'a> {
+        self.params.provoking_vertex = vertex;
+        self
+    }
+
+    /// Sets the `primitive_bounding_box` field.
+    #[inline]
+    pub fn primitive_bounding_box(mut self, x: Range<f32>, y: Range<f32>, z: Range<f32>,
+                                  w: Range<f32>) -> DrawParametersBuilder<'a>
+    {
+        self.params.primitive_bounding_box = (x, y, z, w);
+        self
+    }
+
+    /// Sets the `clip_planes_bitmask` field.
+    #[inline]
+    pub fn clip_planes_bitmask(mut self, bitmask: u32) -> DrawParametersBuilder<'a> {
+        self.params.clip_planes_bitmask = bitmask;
+        self
+    }
+}
+
+impl<'a> Default for DrawParametersBuilder<'a> {
+    #[inline]
+    fn default() -> DrawParametersBuilder<'a> {
+        DrawParametersBuilder::new()
+    }
+}
+
+impl<'a> From<DrawParameters<'a>> for DrawParametersBuilder<'a> {
+    #[inline]
+    fn from(params: DrawParameters<'a>) -> DrawParametersBuilder<'a> {
+        DrawParametersBuilder {
+            params: params,
+        }
+    }
+}
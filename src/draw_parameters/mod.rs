@@ -61,6 +61,46 @@
 //!
 //! For the same reasons, as soon as you call `is_ready` on a query it will stop being usable.
 //!
+//! # Depth pre-pass
+//!
+//! A depth pre-pass consists in drawing the scene twice. The first pass only fills the depth
+//! buffer, and the second pass runs the expensive fragment shaders only for the fragments whose
+//! depth is equal to the one in the depth buffer, in other words the visible ones.
+//!
+//! ```no_run
+//! # use glium::Surface;
+//! # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+//! # let vertex_buffer: glium::vertex::VertexBufferAny = unsafe { ::std::mem::uninitialized() };
+//! # let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+//! # let program: glium::Program = unsafe { ::std::mem::uninitialized() };
+//! # let uniforms = glium::uniforms::EmptyUniforms;
+//! let mut target = display.draw();
+//! target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+//!
+//! target.draw(&vertex_buffer, &indices, &program, &uniforms,
+//!             &glium::DrawParameters::depth_prepass()).unwrap();
+//! target.draw(&vertex_buffer, &indices, &program, &uniforms,
+//!             &glium::DrawParameters::depth_prepass_main()).unwrap();
+//!
+//! target.finish().unwrap();
+//! ```
+//!
+//! The `IfEqual` depth test of the second pass only works if both passes compute exactly the
+//! same depth values:
+//!
+//! - Both passes must use the same vertex processing. The simplest way is to use the same
+//!   program and the same vertex source. If you use a cheaper program for the first pass, its
+//!   position must be computed with the exact same operations and inputs, and it should be
+//!   declared `invariant gl_Position;` in both programs.
+//! - The fragment shaders must not write to `gl_FragDepth`, and must `discard` the same
+//!   fragments. Otherwise the two passes disagree about which fragment is the closest one.
+//! - The depth range, the depth clamping and the viewport must be the same in both passes.
+//!   Glium never enables polygon offset, so it can't introduce a difference.
+//!
+//! In debug builds, glium prints a warning if the second pass uses a different program than
+//! the latest first pass, or a different depth range or depth clamping. The draw command is
+//! executed anyway, as glium can't know whether the two programs compute the same positions.
+//!
 use gl;
 use context;
 use context::Context;
@@ -79,13 +119,15 @@ use vertex::TransformFeedbackSession;
 use std::ops::Range;
 
 pub use self::blend::{Blend, BlendingFunction, LinearBlendingFactor};
+pub use self::builder::DrawParametersBuilder;
 pub use self::depth::{Depth, DepthTest, DepthClamp};
-pub use self::query::{QueryCreationError};
+pub use self::query::{QueryCreationError, ToBufferError};
 pub use self::query::{SamplesPassedQuery, TimeElapsedQuery, PrimitivesGeneratedQuery};
 pub use self::query::{AnySamplesPassedQuery, TransformFeedbackPrimitivesWrittenQuery};
 pub use self::stencil::{StencilTest, StencilOperation, Stencil};
 
 mod blend;
+mod builder;
 mod depth;
 mod query;
 mod stencil;
@@ -215,7 +257,41 @@ impl ToGlEnum for Smooth {
     }
 }
 
+/// Specifies a hint for the accuracy of the derivatives computed in fragment shaders.
+///
+/// This affects `dFdx`, `dFdy` and `fwidth`, and the level of detail computed by texture
+/// lookups. Note that this is just a hint and the driver may disregard it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerivativeHint {
+    /// The most efficient option should be chosen.
+    Fastest,
+
+    /// The most correct, or highest quality, option should be chosen.
+    Nicest,
+
+    /// No preference.
+    DontCare,
+}
+
+impl ToGlEnum for DerivativeHint {
+    #[inline]
+    fn to_glenum(&self) -> gl::types::GLenum {
+        match *self {
+            DerivativeHint::Fastest => gl::FASTEST,
+            DerivativeHint::Nicest => gl::NICEST,
+            DerivativeHint::DontCare => gl::DONT_CARE,
+        }
+    }
+}
+
 /// The vertex to use for flat shading.
+///
+/// The convention matters most for strips. With `LastVertex`, triangle `i` of a triangle strip
+/// takes its flat values from vertex `i + 2`, while with `FirstVertex` it takes them from
+/// vertex `i`. Quads don't exist in core profiles, so `GL_QUADS_FOLLOW_PROVOKING_VERTEX_CONVENTION`
+/// is irrelevant.
+///
+/// Use `Context::is_provoking_vertex_supported` to know whether `FirstVertex` is available.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ProvokingVertex {
     /// Use the last vertex of each primitive.
@@ -227,6 +303,71 @@ pub enum ProvokingVertex {
     FirstVertex,
 }
 
+/// A bitwise operation between the color of the fragment and the color in the framebuffer.
+///
+/// In the descriptions below, `s` is the value written by the fragment shader and `d` is the
+/// value that is already in the framebuffer. The operation is applied to each bit of each
+/// component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogicOp {
+    /// `0`
+    Clear,
+    /// `s & d`
+    And,
+    /// `s & !d`
+    AndReverse,
+    /// `s`
+    Copy,
+    /// `!s & d`
+    AndInverted,
+    /// `d`
+    Noop,
+    /// `s ^ d`
+    Xor,
+    /// `s | d`
+    Or,
+    /// `!(s | d)`
+    Nor,
+    /// `!(s ^ d)`
+    Equiv,
+    /// `!d`
+    Invert,
+    /// `s | !d`
+    OrReverse,
+    /// `!s`
+    CopyInverted,
+    /// `!s | d`
+    OrInverted,
+    /// `!(s & d)`
+    Nand,
+    /// All the bits set to `1`.
+    Set,
+}
+
+impl ToGlEnum for LogicOp {
+    #[inline]
+    fn to_glenum(&self) -> gl::types::GLenum {
+        match *self {
+            LogicOp::Clear => gl::CLEAR,
+            LogicOp::And => gl::AND,
+            LogicOp::AndReverse => gl::AND_REVERSE,
+            LogicOp::Copy => gl::COPY,
+            LogicOp::AndInverted => gl::AND_INVERTED,
+            LogicOp::Noop => gl::NOOP,
+            LogicOp::Xor => gl::XOR,
+            LogicOp::Or => gl::OR,
+            LogicOp::Nor => gl::NOR,
+            LogicOp::Equiv => gl::EQUIV,
+            LogicOp::Invert => gl::INVERT,
+            LogicOp::OrReverse => gl::OR_REVERSE,
+            LogicOp::CopyInverted => gl::COPY_INVERTED,
+            LogicOp::OrInverted => gl::OR_INVERTED,
+            LogicOp::Nand => gl::NAND,
+            LogicOp::Set => gl::SET,
+        }
+    }
+}
+
 /// Represents the parameters to use when drawing.
 ///
 /// Example:
@@ -254,6 +395,22 @@ pub struct DrawParameters<'a> {
     /// being written.
     pub blend: Blend,
 
+    /// A bitwise operation to apply between the fragment and the framebuffer, instead of
+    /// blending.
+    ///
+    /// Logic operations only apply to the color attachments that contain normalized or
+    /// integral values. Floating-point and sRGB attachments are written as if this was `None`.
+    ///
+    /// Logic operations and blending are mutually exclusive. OpenGL ignores `blend` when a logic
+    /// operation is enabled, and a warning is printed if both are set when compiling with
+    /// `debug_assertions`.
+    ///
+    /// OpenGL ES doesn't support logic operations, and drawing returns a
+    /// `LogicOpNotSupported` error if this is `Some`.
+    ///
+    /// The default value is `None`.
+    pub logic_op: Option<LogicOp>,
+
     /// Allows you to disable some color components.
     ///
     /// This affects all attachments to the framebuffer. It's at the same level as the
@@ -355,6 +512,15 @@ pub struct DrawParameters<'a> {
     /// Note that blending needs to be enabled for this to work.
     pub smooth: Option<Smooth>,
 
+    /// Hint for the accuracy of the derivatives computed in the fragment shader.
+    ///
+    /// The value is applied with `glHint(GL_FRAGMENT_SHADER_DERIVATIVE_HINT)`, which is
+    /// available on OpenGL 2.0, OpenGL ES 3.0 or with `GL_OES_standard_derivatives`. Since this
+    /// is only a hint, it is ignored if the backend doesn't support it.
+    ///
+    /// The default value is `DontCare`.
+    pub derivative_hint: DerivativeHint,
+
     /// In your vertex shader or geometry shader, you have the possibility to mark some output
     /// varyings as `flat`. If this is the case, the value of one of the vertices will be used
     /// for the whole primitive. This variable allows you to specify which vertex.
@@ -373,11 +539,31 @@ pub struct DrawParameters<'a> {
     /// The GPU is free not to draw samples outside of the bounding box. Whether the samples are
     /// drawn is implementation-specific.
     ///
-    /// This field is useless if you're not using a geometry shader or tessellation shader.
+    /// This field is useless if you're not using a geometry shader or tessellation shader. If
+    /// you set it with a program that has neither, a warning is printed when compiling with
+    /// `debug_assertions`.
+    ///
+    /// The value is applied with `glPrimitiveBoundingBox` on OpenGL ES 3.2, or if
+    /// `GL_ARB_ES3_2_compatibility`, `GL_OES_primitive_bounding_box` or
+    /// `GL_EXT_primitive_bounding_box` is supported. Since this is purely an optimization, this
+    /// parameter is ignored if the backend doesn't support it.
     ///
-    /// Since this is purely an optimization, this parameter is ignored if the backend doesn't
-    /// support it.
+    /// The default value is `-1.0 .. 1.0` for each coordinate, which is the default of OpenGL
+    /// and covers the whole viewport. Each draw command sets the value of its own parameters,
+    /// so a bounding box never leaks into the following draw commands.
     pub primitive_bounding_box: (Range<f32>, Range<f32>, Range<f32>, Range<f32>),
+
+    /// Bitmask of the clip planes to enable. Bit `n` enables `GL_CLIP_DISTANCEn`.
+    ///
+    /// When a clip plane is enabled, the vertex, tessellation or geometry shader must write
+    /// the corresponding element of `gl_ClipDistance`. Parts of the primitive where the
+    /// interpolated distance is negative are not drawn.
+    ///
+    /// Enabling a clip plane whose index is superior or equal to the value returned by
+    /// `max_clip_distances` in the capabilities produces a `ClipPlaneIndexOutOfBounds` error.
+    ///
+    /// The default value is `0`, which disables all clip planes.
+    pub clip_planes_bitmask: u32,
 }
 
 /// Condition whether to render or not.
@@ -424,6 +610,7 @@ impl<'a> Default for DrawParameters<'a> {
             depth: Depth::default(),
             stencil: Default::default(),
             blend: Default::default(),
+            logic_op: None,
             color_mask: (true, true, true, true),
             line_width: None,
             point_size: None,
@@ -441,8 +628,131 @@ impl<'a> Default for DrawParameters<'a> {
             condition: None,
             transform_feedback: None,
             smooth: None,
+            derivative_hint: DerivativeHint::DontCare,
             provoking_vertex: ProvokingVertex::LastVertex,
             primitive_bounding_box: (-1.0 .. 1.0, -1.0 .. 1.0, -1.0 .. 1.0, -1.0 .. 1.0),
+            clip_planes_bitmask: 0,
+        }
+    }
+}
+
+impl<'a> DrawParameters<'a> {
+    /// Returns a builder that allows setting the parameters one at a time.
+    ///
+    /// The fields that are not set keep their default value.
+    #[inline]
+    pub fn builder() -> DrawParametersBuilder<'a> {
+        DrawParametersBuilder::new()
+    }
+
+    /// Parameters for drawing opaque three-dimensional geometry.
+    ///
+    /// - The depth test is `IfLess` and depth values are written (`GL_DEPTH_TEST` enabled,
+    ///   `glDepthFunc(GL_LESS)`, `glDepthMask(GL_TRUE)`).
+    /// - Clockwise triangles are culled (`GL_CULL_FACE` enabled, `glCullFace(GL_BACK)`), which
+    ///   means that the front faces must be counter-clockwise.
+    /// - Blending is disabled.
+    ///
+    /// All the other fields have their default value.
+    pub fn opaque_3d() -> DrawParameters<'a> {
+        DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLess,
+                write: true,
+                .. Default::default()
+            },
+            backface_culling: BackfaceCullingMode::CullClockwise,
+            .. Default::default()
+        }
+    }
+
+    /// Parameters for drawing transparent two-dimensional elements over the existing content,
+    /// in back-to-front order.
+    ///
+    /// - The depth buffer is neither tested nor written (`GL_DEPTH_TEST` disabled,
+    ///   `glDepthMask(GL_FALSE)`).
+    /// - Colors are blended with `Blend::alpha_blending()` (`GL_BLEND` enabled,
+    ///   `glBlendFunc(GL_SRC_ALPHA, GL_ONE_MINUS_SRC_ALPHA)`).
+    /// - No triangle is culled (`GL_CULL_FACE` disabled).
+    ///
+    /// All the other fields have their default value.
+    pub fn alpha_blended_ui() -> DrawParameters<'a> {
+        DrawParameters {
+            depth: Depth {
+                test: DepthTest::Overwrite,
+                write: false,
+                .. Default::default()
+            },
+            blend: Blend::alpha_blending(),
+            backface_culling: BackfaceCullingMode::CullingDisabled,
+            .. Default::default()
+        }
+    }
+
+    /// Parameters for rendering the depth of objects into a shadow map.
+    ///
+    /// - The depth test is `IfLess` and depth values are written (`GL_DEPTH_TEST` enabled,
+    ///   `glDepthFunc(GL_LESS)`, `glDepthMask(GL_TRUE)`).
+    /// - No color is written (`glColorMask(GL_FALSE, GL_FALSE, GL_FALSE, GL_FALSE)`).
+    /// - Counter-clockwise triangles are culled (`GL_CULL_FACE` enabled, `glCullFace(GL_FRONT)`),
+    ///   so that only the back faces are rendered. This reduces self-shadowing artifacts.
+    ///
+    /// All the other fields have their default value.
+    pub fn shadow_caster() -> DrawParameters<'a> {
+        DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLess,
+                write: true,
+                .. Default::default()
+            },
+            color_mask: (false, false, false, false),
+            backface_culling: BackfaceCullingMode::CullCounterClockwise,
+            .. Default::default()
+        }
+    }
+
+    /// Parameters for the first pass of a depth pre-pass, which fills the depth buffer
+    /// without writing any color.
+    ///
+    /// - The depth test is `IfLess` and depth values are written (`GL_DEPTH_TEST` enabled,
+    ///   `glDepthFunc(GL_LESS)`, `glDepthMask(GL_TRUE)`).
+    /// - No color is written (`glColorMask(GL_FALSE, GL_FALSE, GL_FALSE, GL_FALSE)`).
+    /// - Clockwise triangles are culled, like with `opaque_3d`.
+    ///
+    /// All the other fields have their default value. The geometry must then be drawn again
+    /// with `depth_prepass_main`. See the module-level documentation for the requirements.
+    pub fn depth_prepass() -> DrawParameters<'a> {
+        DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLess,
+                write: true,
+                .. Default::default()
+            },
+            color_mask: (false, false, false, false),
+            backface_culling: BackfaceCullingMode::CullClockwise,
+            .. Default::default()
+        }
+    }
+
+    /// Parameters for the second pass of a depth pre-pass, which only shades the fragments
+    /// that are visible.
+    ///
+    /// - The depth test is `IfEqual` and depth values are not written (`GL_DEPTH_TEST` enabled,
+    ///   `glDepthFunc(GL_EQUAL)`, `glDepthMask(GL_FALSE)`).
+    /// - All colors are written.
+    /// - Clockwise triangles are culled, like with `opaque_3d`.
+    ///
+    /// All the other fields have their default value. The depth buffer must have been filled
+    /// with `depth_prepass` beforehand. See the module-level documentation for the requirements.
+    pub fn depth_prepass_main() -> DrawParameters<'a> {
+        DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfEqual,
+                write: false,
+                .. Default::default()
+            },
+            backface_culling: BackfaceCullingMode::CullClockwise,
+            .. Default::default()
         }
     }
 }
@@ -471,6 +781,7 @@ pub fn sync(ctxt: &mut context::CommandContext, draw_parameters: &DrawParameters
     try!(depth::sync_depth(ctxt, &draw_parameters.depth));
     stencil::sync_stencil(ctxt, &draw_parameters.stencil);
     try!(blend::sync_blending(ctxt, draw_parameters.blend));
+    try!(sync_logic_op(ctxt, draw_parameters.logic_op, &draw_parameters.blend));
     sync_color_mask(ctxt, draw_parameters.color_mask);
     sync_line_width(ctxt, draw_parameters.line_width);
     sync_point_size(ctxt, draw_parameters.point_size);
@@ -486,8 +797,51 @@ pub fn sync(ctxt: &mut context::CommandContext, draw_parameters: &DrawParameters
                       draw_parameters.transform_feedback_primitives_written_query));
     sync_conditional_render(ctxt, draw_parameters.condition);
     try!(sync_smooth(ctxt, draw_parameters.smooth, primitives_types));
+    sync_derivative_hint(ctxt, draw_parameters.derivative_hint);
     try!(sync_provoking_vertex(ctxt, draw_parameters.provoking_vertex));
     sync_primitive_bounding_box(ctxt, &draw_parameters.primitive_bounding_box);
+    try!(sync_clip_planes(ctxt, draw_parameters.clip_planes_bitmask));
+
+    Ok(())
+}
+
+fn sync_logic_op(ctxt: &mut context::CommandContext, logic_op: Option<LogicOp>, blend: &Blend)
+                 -> Result<(), DrawError>
+{
+    let logic_op = match logic_op {
+        Some(op) => op.to_glenum(),
+        None => {
+            if ctxt.state.enabled_color_logic_op {
+                unsafe { ctxt.gl.Disable(gl::COLOR_LOGIC_OP); }
+                ctxt.state.enabled_color_logic_op = false;
+            }
+
+            return Ok(());
+        },
+    };
+
+    if !(ctxt.version >= &Version(Api::Gl, 1, 1)) {
+        return Err(DrawError::LogicOpNotSupported);
+    }
+
+    // OpenGL silently ignores the blending function when a logic operation is enabled
+    if cfg!(debug_assertions) &&
+       (blend.color, blend.alpha) != (BlendingFunction::AlwaysReplace,
+                                      BlendingFunction::AlwaysReplace)
+    {
+        println!("glium warning: both a logic operation and blending are enabled, blending is \
+                  ignored");
+    }
+
+    if !ctxt.state.enabled_color_logic_op {
+        unsafe { ctxt.gl.Enable(gl::COLOR_LOGIC_OP); }
+        ctxt.state.enabled_color_logic_op = true;
+    }
+
+    if ctxt.state.logic_op != logic_op {
+        unsafe { ctxt.gl.LogicOp(logic_op); }
+        ctxt.state.logic_op = logic_op;
+    }
 
     Ok(())
 }
@@ -824,6 +1178,24 @@ fn sync_smooth(ctxt: &mut context::CommandContext,
     Ok(())
 }
 
+fn sync_derivative_hint(ctxt: &mut context::CommandContext, hint: DerivativeHint) {
+    if !(ctxt.version >= &Version(Api::Gl, 2, 0)) &&
+       !(ctxt.version >= &Version(Api::GlEs, 3, 0)) &&
+       !ctxt.extensions.gl_oes_standard_derivatives
+    {
+        return;
+    }
+
+    let hint = hint.to_glenum();
+
+    if ctxt.state.derivative_hint != hint {
+        unsafe {
+            ctxt.gl.Hint(gl::FRAGMENT_SHADER_DERIVATIVE_HINT, hint);
+        }
+        ctxt.state.derivative_hint = hint;
+    }
+}
+
 fn sync_provoking_vertex(ctxt: &mut context::CommandContext, value: ProvokingVertex)
                          -> Result<(), DrawError>
 {
@@ -851,6 +1223,38 @@ fn sync_provoking_vertex(ctxt: &mut context::CommandContext, value: ProvokingVer
     Ok(())
 }
 
+fn sync_clip_planes(ctxt: &mut context::CommandContext, bitmask: u32) -> Result<(), DrawError> {
+    if ctxt.state.enabled_clip_planes == bitmask {
+        return Ok(());
+    }
+
+    let max = ctxt.capabilities.max_clip_distances;
+    if max < 32 && (bitmask >> max) != 0 {
+        return Err(DrawError::ClipPlaneIndexOutOfBounds);
+    }
+
+    let changed = ctxt.state.enabled_clip_planes ^ bitmask;
+
+    for plane in 0 .. 32 {
+        if changed & (1 << plane) == 0 {
+            continue;
+        }
+
+        let cap = gl::CLIP_DISTANCE0 + plane as gl::types::GLenum;
+
+        unsafe {
+            if bitmask & (1 << plane) != 0 {
+                ctxt.gl.Enable(cap);
+            } else {
+                ctxt.gl.Disable(cap);
+            }
+        }
+    }
+
+    ctxt.state.enabled_clip_planes = bitmask;
+    Ok(())
+}
+
 fn sync_primitive_bounding_box(ctxt: &mut context::CommandContext,
                                bb: &(Range<f32>, Range<f32>, Range<f32>, Range<f32>))
 {
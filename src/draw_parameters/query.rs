@@ -8,12 +8,15 @@ use GlObject;
 use QueryExt;
 
 use std::cell::Cell;
+use std::error::Error;
 use std::fmt;
 use std::mem;
 use std::rc::Rc;
 
 use buffer::Buffer;
 use buffer::BufferSlice;
+use buffer::BufferAnySlice;
+use buffer::Content;
 use BufferExt;
 use BufferSliceExt;
 
@@ -65,11 +68,53 @@ pub enum QueryCreationError {
     NotSupported,
 }
 
+impl fmt::Display for QueryCreationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for QueryCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &QueryCreationError::NotSupported => "The given query type is not supported",
+        }
+    }
+}
+
 /// Error that can happen when writing the value of a query to a buffer.
 #[derive(Copy, Clone, Debug)]
 pub enum ToBufferError {
     /// Writing the result to a buffer is not supported.
     NotSupported,
+
+    /// The offset in the buffer is not a multiple of the size of the result.
+    MisalignedOffset,
+
+    /// The result doesn't fit in the buffer at the requested offset.
+    BufferTooSmall,
+
+    /// The query has never been used by a draw command, so it doesn't have a result.
+    QueryNotUsed,
+}
+
+impl fmt::Display for ToBufferError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for ToBufferError {
+    fn description(&self) -> &str {
+        match self {
+            &ToBufferError::NotSupported => "Writing the result of a query to a buffer is not \
+                                             supported",
+            &ToBufferError::MisalignedOffset => "The offset in the buffer is not a multiple of \
+                                                 the size of the result",
+            &ToBufferError::BufferTooSmall => "The result of the query doesn't fit in the buffer",
+            &ToBufferError::QueryNotUsed => "The query has never been used",
+        }
+    }
 }
 
 impl RawQuery {
@@ -96,9 +141,7 @@ impl RawQuery {
                     _ => return Err(QueryCreationError::NotSupported)
                 };
 
-                if ctxt.version >= &Version(Api:: Gl, 4, 5) ||
-                   ctxt.extensions.gl_arb_direct_state_access
-                {
+                if ctxt.capabilities.direct_state_access {
                     ctxt.gl.CreateQueries(ty.to_glenum(), 1, &mut id);
                 } else {
                     ctxt.gl.GenQueries(1, &mut id);
@@ -224,7 +267,18 @@ impl RawQuery {
     }
 
     /// Writes the value of the query to a buffer.
+    #[inline]
     pub fn write_u32_to_buffer(&self, target: BufferSlice<u32>) -> Result<(), ToBufferError> {
+        self.write_to_buffer(target.as_slice_any(), 0, false)
+    }
+
+    /// Writes the value of the query to a buffer, at `offset` bytes from the start of `target`.
+    ///
+    /// The value is written as a 64 bits integer if `as_u64` is true, and as a 32 bits integer
+    /// otherwise.
+    pub fn write_to_buffer(&self, target: BufferAnySlice, offset: usize, as_u64: bool)
+                           -> Result<(), ToBufferError>
+    {
         let mut ctxt = self.context.make_current();
 
         if !(ctxt.version >= &Version(Api::Gl, 4, 4) || ctxt.extensions.gl_arb_query_buffer_object ||
@@ -233,16 +287,38 @@ impl RawQuery {
             return Err(ToBufferError::NotSupported);
         }
 
+        if as_u64 && !(ctxt.version >= &Version(Api::Gl, 3, 3)) {
+            return Err(ToBufferError::NotSupported);
+        }
+
+        let value_size = if as_u64 { 8 } else { 4 };
+
+        if offset + value_size > target.get_size() {
+            return Err(ToBufferError::BufferTooSmall);
+        }
+
+        let offset = target.get_offset_bytes() + offset;
+        if offset % value_size != 0 {
+            return Err(ToBufferError::MisalignedOffset);
+        }
+
         self.deactivate(&mut ctxt);
 
         if !self.has_been_used.get() {
-            panic!();
+            return Err(ToBufferError::QueryNotUsed);
         }
 
-        assert!(target.get_offset_bytes() % 4 == 0);
-
+        // the GL orders the write of the result before the commands that are submitted
+        // afterwards, so no memory barrier is needed for the commands that read the buffer
         target.prepare_and_bind_for_query(&mut ctxt);
-        unsafe { self.raw_get_u32(&mut ctxt, target.get_offset_bytes() as *mut _); }
+
+        unsafe {
+            if as_u64 {
+                self.raw_get_u64(&mut ctxt, offset as *mut _).unwrap();
+            } else {
+                self.raw_get_u32(&mut ctxt, offset as *mut _);
+            }
+        }
 
         if let Some(fence) = target.add_fence() {
             fence.insert(&mut ctxt);
@@ -349,7 +425,10 @@ impl RawQuery {
 
 impl Drop for RawQuery {
     fn drop(&mut self) {
-        let mut ctxt = self.context.make_current();
+        let mut ctxt = match self.context.make_current_for_drop() {
+            Some(ctxt) => ctxt,
+            None => return,
+        };
         self.deactivate(&mut ctxt);
 
         if let Some((id, _)) = ctxt.state.conditional_render {
@@ -691,7 +770,7 @@ unsafe fn raw_end_query(ctxt: &mut CommandContext, ty: gl::types::GLenum) {
 }
 
 macro_rules! impl_helper {
-    ($name:ident, $ret:ty, $get_fn:ident) => {
+    ($name:ident, $ret:ty, $get_fn:ident, $write_u64:expr) => {
         impl $name {
             /// Queries the counter to see if the result is already available.
             #[inline]
@@ -725,6 +804,28 @@ macro_rules! impl_helper {
             {
                 self.query.write_u32_to_buffer(target)
             }
+
+            /// Writes the result of the query to a buffer when it is available, at `offset`
+            /// bytes from the start of the buffer.
+            ///
+            /// Like `to_buffer_u32`, this function doesn't block and the CPU never sees the
+            /// result. The commands that are submitted afterwards, like an indirect draw
+            /// command or a compute shader that reads the buffer, see the written value.
+            ///
+            /// The result is written as a 32 bits unsigned integer, except for
+            /// `TimeElapsedQuery` which writes a 64 bits unsigned integer. The offset must be a
+            /// multiple of the size of the result.
+            ///
+            /// Requires OpenGL 4.4 or `GL_ARB_query_buffer_object`. Returns an error if this
+            /// is not supported, if the offset is misaligned, if the result doesn't fit in the
+            /// buffer or if the query has never been used.
+            #[inline]
+            pub fn write_result_to_buffer<T: ?Sized>(&self, buffer: &Buffer<T>, offset: usize)
+                                                     -> Result<(), ToBufferError>
+                                                     where T: Content
+            {
+                self.query.write_to_buffer(buffer.as_slice_any(), offset, $write_u64)
+            }
         }
 
         impl GlObject for $name {
@@ -798,7 +899,7 @@ impl SamplesPassedQuery {
     }
 }
 
-impl_helper!(SamplesPassedQuery, u32, get_u32);
+impl_helper!(SamplesPassedQuery, u32, get_u32, false);
 
 /// A query that allows you to know the number of nanoseconds that have elapsed
 /// during the draw operations.
@@ -817,7 +918,7 @@ impl TimeElapsedQuery {
     }
 }
 
-impl_helper!(TimeElapsedQuery, u32, get_u32);
+impl_helper!(TimeElapsedQuery, u32, get_u32, true);
 
 /// A query type that allows you to know whether any sample has been written to the output during
 /// the operations executed with this query.
@@ -830,6 +931,45 @@ impl_helper!(TimeElapsedQuery, u32, get_u32);
 /// However if the backend doesn't support conservative queries, glium will automatically fall
 /// back to a non-conservative query. If the backend doesn't support either types but supports
 /// `GL_SAMPLES_PASSED`, then glium will automatically use a `GL_SAMPLES_PASSED` query instead.
+///
+/// ## Occlusion culling without reading back the result
+///
+/// The result of the query can be written in the `instance_count` field of an indirect draw
+/// command. The object is then drawn only if its bounding box was visible, without the CPU
+/// ever waiting for the result.
+///
+/// ```no_run
+/// # use glium::Surface;
+/// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+/// # let bounding_box: glium::VertexBuffer<u8> = unsafe { std::mem::uninitialized() };
+/// # let object: glium::VertexBuffer<u8> = unsafe { std::mem::uninitialized() };
+/// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+/// # let mut frame = display.draw();
+/// let query = glium::draw_parameters::AnySamplesPassedQuery::new(&display, true).unwrap();
+/// let commands = glium::index::DrawCommandsNoIndicesBuffer::empty(&display, 1).unwrap();
+/// commands.write(&[glium::index::DrawCommandNoIndices {
+///     count: object.len() as u32, instance_count: 0, first_index: 0, base_instance: 0,
+/// }]);
+///
+/// // drawing the bounding box without writing anything
+/// let params = glium::DrawParameters {
+///     color_mask: (false, false, false, false),
+///     samples_passed_query: Some((&query).into()),
+///     .. Default::default()
+/// };
+/// frame.draw(&bounding_box, &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+///            &program, &glium::uniforms::EmptyUniforms, &params).unwrap();
+///
+/// // `instance_count` is at offset 4 in the command
+/// query.write_result_to_buffer(&*commands, 4).unwrap();
+///
+/// let indices = commands.with_primitive_type(glium::index::PrimitiveType::TrianglesList);
+/// frame.draw(&object, indices, &program, &glium::uniforms::EmptyUniforms,
+///            &Default::default()).unwrap();
+/// ```
+///
+/// Note that if glium falls back to a `GL_SAMPLES_PASSED` query, the result is the number of
+/// samples and not `1`.
 #[derive(Debug)]
 pub struct AnySamplesPassedQuery {
     query: RawQuery,
@@ -860,7 +1000,7 @@ impl AnySamplesPassedQuery {
     }
 }
 
-impl_helper!(AnySamplesPassedQuery, bool, get_bool);
+impl_helper!(AnySamplesPassedQuery, bool, get_bool, false);
 
 /// Query that allows you to know the number of primitives generated by the geometry shader.
 /// Will stay at `0` if you use it without any active geometry shader.
@@ -880,7 +1020,7 @@ impl PrimitivesGeneratedQuery {
     }
 }
 
-impl_helper!(PrimitivesGeneratedQuery, u32, get_u32);
+impl_helper!(PrimitivesGeneratedQuery, u32, get_u32, false);
 
 /// Query that allows you to know the number of primitives generated by transform feedback.
 #[derive(Debug)]
@@ -899,4 +1039,4 @@ impl TransformFeedbackPrimitivesWrittenQuery {
     }
 }
 
-impl_helper!(TransformFeedbackPrimitivesWrittenQuery, u32, get_u32);
+impl_helper!(TransformFeedbackPrimitivesWrittenQuery, u32, get_u32, false);
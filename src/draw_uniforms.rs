@@ -0,0 +1,328 @@
+/*!
+
+Uniform blocks that are filled by glium with the values of the uniforms of each draw command.
+
+When a program declares a block named `glium_DrawUniforms`, the values passed to a draw command
+whose names match members of this block are written in a temporary buffer at the offsets
+reported by the program, instead of being set one by one with `glUniform`. The buffer is then
+bound with a single call to `glBindBufferRange`.
+
+The temporary buffers of all the draw commands are sub-allocated from a single buffer that
+belongs to the context and that is used as a ring. When its end is reached, the buffer is
+orphaned and the allocations start again from the beginning, so that the driver keeps the
+previous storage alive for as long as the GPU uses it.
+
+*/
+use gl;
+
+use std::cell::RefCell;
+use std::cmp;
+use std::mem;
+use std::ptr;
+
+use context::CommandContext;
+use context::label_internal_object;
+use program::BlockLayout;
+use uniforms::UniformType;
+use uniforms::UniformValue;
+use version::Api;
+use version::Version;
+
+/// Name of the uniform block that receives the values of the uniforms of each draw command.
+pub const DRAW_UNIFORMS_BLOCK: &'static str = "glium_DrawUniforms";
+
+/// Minimal size in bytes of the ring buffer.
+const MIN_RING_SIZE: usize = 256 * 1024;
+
+/// The ring buffer of a context.
+pub struct DrawUniformsRing {
+    ring: RefCell<Option<Ring>>,
+}
+
+struct Ring {
+    /// Id of the buffer.
+    id: gl::types::GLuint,
+
+    /// Size in bytes of the buffer.
+    size: usize,
+
+    /// Offset in bytes of the end of the last allocation.
+    cursor: usize,
+}
+
+impl DrawUniformsRing {
+    /// Builds a new ring. The buffer is only created the first time it is needed.
+    #[inline]
+    pub fn new() -> DrawUniformsRing {
+        DrawUniformsRing {
+            ring: RefCell::new(None),
+        }
+    }
+
+    /// Copies `data` in the ring buffer, then binds it to the uniform buffer binding point
+    /// `bind_point`.
+    pub fn upload_and_bind(&self, ctxt: &mut CommandContext, data: &[u8],
+                           bind_point: gl::types::GLuint)
+    {
+        let mut ring = self.ring.borrow_mut();
+
+        if ring.as_ref().map(|r| r.size < data.len()).unwrap_or(true) {
+            if let Some(old) = ring.take() {
+                unsafe { destroy_buffer(ctxt, old.id) };
+            }
+
+            let size = cmp::max(MIN_RING_SIZE, data.len().next_power_of_two());
+            *ring = Some(unsafe { create_buffer(ctxt, size) });
+        }
+
+        let ring = ring.as_mut().unwrap();
+
+        let alignment = cmp::max(ctxt.capabilities.uniform_buffer_offset_alignment, 1) as usize;
+        let mut offset = (ring.cursor + alignment - 1) / alignment * alignment;
+
+        unsafe {
+            bind_buffer(ctxt, ring.id);
+
+            if offset + data.len() > ring.size {
+                ctxt.gl.BufferData(gl::UNIFORM_BUFFER, ring.size as gl::types::GLsizeiptr,
+                                   ptr::null(), gl::STREAM_DRAW);
+                offset = 0;
+            }
+
+            write(ctxt, offset, data);
+            bind_range(ctxt, ring.id, bind_point, offset, data.len());
+        }
+
+        ring.cursor = offset + data.len();
+
+        ctxt.frame_statistics.update(|s| {
+            s.buffer_uploads += 1;
+            s.buffer_bytes_uploaded += data.len();
+        });
+    }
+
+    /// Destroys the ring buffer. It is created again the next time it is needed.
+    pub fn purge_all(ctxt: &mut CommandContext) {
+        let ring = ctxt.draw_uniforms.ring.borrow_mut().take();
+
+        if let Some(ring) = ring {
+            unsafe { destroy_buffer(ctxt, ring.id) };
+        }
+    }
+}
+
+/// Writes a value at the position of the member `name` of a block, following the `std140`
+/// rules for the layout of the matrices and of the arrays.
+///
+/// Returns `None` if the block doesn't have a member named `name` that can receive a single
+/// value. Returns an error containing the type of the member if the value has a different type.
+pub fn write_member(layout: &BlockLayout, name: &str, value: &UniformValue, data: &mut [u8])
+                    -> Option<Result<(), UniformType>>
+{
+    let members = match layout {
+        &BlockLayout::Struct { ref members } => members,
+        _ => return None,
+    };
+
+    let member = match members.iter().find(|m| m.0 == name) {
+        Some(m) => &m.1,
+        None => return None,
+    };
+
+    match member {
+        &BlockLayout::BasicType { ty, offset_in_buffer } => {
+            if !value.is_usable_with(&ty) {
+                return Some(Err(ty));
+            }
+
+            write_value(value, offset_in_buffer, data);
+            Some(Ok(()))
+        },
+
+        &BlockLayout::Array { ref content, length } => {
+            let (ty, offset) = match **content {
+                BlockLayout::BasicType { ty, offset_in_buffer } => (ty, offset_in_buffer),
+                _ => return None,
+            };
+
+            if !value.is_usable_with(&ty) {
+                return Some(Err(ty));
+            }
+
+            // each column of a matrix is aligned like a `vec4`
+            match *value {
+                UniformValue::Mat3Array(matrices) => {
+                    for (num, matrix) in matrices.iter().take(length).enumerate() {
+                        write_columns(matrix, offset + num * 48, 16, data);
+                    }
+                },
+                UniformValue::Mat4Array(matrices) => {
+                    for (num, matrix) in matrices.iter().take(length).enumerate() {
+                        write_columns(matrix, offset + num * 64, 16, data);
+                    }
+                },
+                _ => return Some(Err(ty)),
+            }
+
+            Some(Ok(()))
+        },
+
+        _ => None,
+    }
+}
+
+/// Writes a value that isn't an array at `offset`.
+fn write_value(value: &UniformValue, offset: usize, data: &mut [u8]) {
+    match *value {
+        UniformValue::Bool(val) => put(data, offset, val as u32),
+        UniformValue::SignedInt(val) => put(data, offset, val),
+        UniformValue::UnsignedInt(val) => put(data, offset, val),
+        UniformValue::Float(val) => put(data, offset, val),
+        UniformValue::Mat2(ref val) => write_columns(val, offset, 16, data),
+        UniformValue::Mat3(ref val) => write_columns(val, offset, 16, data),
+        UniformValue::Mat4(ref val) => write_columns(val, offset, 16, data),
+        UniformValue::Vec2(val) => put(data, offset, val),
+        UniformValue::Vec3(val) => put(data, offset, val),
+        UniformValue::Vec4(val) => put(data, offset, val),
+        UniformValue::IntVec2(val) => put(data, offset, val),
+        UniformValue::IntVec3(val) => put(data, offset, val),
+        UniformValue::IntVec4(val) => put(data, offset, val),
+        UniformValue::UnsignedIntVec2(val) => put(data, offset, val),
+        UniformValue::UnsignedIntVec3(val) => put(data, offset, val),
+        UniformValue::UnsignedIntVec4(val) => put(data, offset, val),
+        UniformValue::BoolVec2(val) => put(data, offset, [val[0] as u32, val[1] as u32]),
+        UniformValue::BoolVec3(val) => {
+            put(data, offset, [val[0] as u32, val[1] as u32, val[2] as u32])
+        },
+        UniformValue::BoolVec4(val) => {
+            put(data, offset, [val[0] as u32, val[1] as u32, val[2] as u32, val[3] as u32])
+        },
+        UniformValue::Double(val) => put(data, offset, val),
+        UniformValue::DoubleVec2(val) => put(data, offset, val),
+        UniformValue::DoubleVec3(val) => put(data, offset, val),
+        UniformValue::DoubleVec4(val) => put(data, offset, val),
+        // the columns of `dmat3` and `dmat4` are aligned like a `dvec4`
+        UniformValue::DoubleMat2(ref val) => write_columns(val, offset, 16, data),
+        UniformValue::DoubleMat3(ref val) => write_columns(val, offset, 32, data),
+        UniformValue::DoubleMat4(ref val) => write_columns(val, offset, 32, data),
+        UniformValue::Int64(val) => put(data, offset, val),
+        UniformValue::Int64Vec2(val) => put(data, offset, val),
+        UniformValue::Int64Vec3(val) => put(data, offset, val),
+        UniformValue::Int64Vec4(val) => put(data, offset, val),
+        UniformValue::UnsignedInt64(val) => put(data, offset, val),
+        UniformValue::UnsignedInt64Vec2(val) => put(data, offset, val),
+        UniformValue::UnsignedInt64Vec3(val) => put(data, offset, val),
+        UniformValue::UnsignedInt64Vec4(val) => put(data, offset, val),
+        // textures, buffers and arrays are rejected by `is_usable_with` beforehand
+        _ => unreachable!(),
+    }
+}
+
+/// Writes the columns of a matrix, each column starting `stride` bytes after the previous one.
+fn write_columns<T>(columns: &[T], offset: usize, stride: usize, data: &mut [u8]) where T: Copy {
+    for (num, column) in columns.iter().enumerate() {
+        put(data, offset + num * stride, *column);
+    }
+}
+
+/// Copies the bytes of `value` at `offset`.
+///
+/// # Panic
+///
+/// Panics if the value doesn't fit in `data`, which happens if the block doesn't use the
+/// `std140` layout.
+fn put<T>(data: &mut [u8], offset: usize, value: T) where T: Copy {
+    let size = mem::size_of::<T>();
+    let target = &mut data[offset .. offset + size];
+
+    unsafe {
+        ptr::copy_nonoverlapping(&value as *const T as *const u8, target.as_mut_ptr(), size);
+    }
+}
+
+/// Creates a buffer of `size` bytes.
+unsafe fn create_buffer(ctxt: &mut CommandContext, size: usize) -> Ring {
+    let mut id: gl::types::GLuint = mem::uninitialized();
+    ctxt.gl.GenBuffers(1, &mut id);
+
+    bind_buffer(ctxt, id);
+    ctxt.gl.BufferData(gl::UNIFORM_BUFFER, size as gl::types::GLsizeiptr, ptr::null(),
+                       gl::STREAM_DRAW);
+
+    label_internal_object(ctxt, gl::BUFFER, id,
+                          format_args!("ring buffer of the glium_DrawUniforms blocks"));
+
+    Ring {
+        id: id,
+        size: size,
+        cursor: 0,
+    }
+}
+
+/// Destroys the buffer and removes it from the state cache.
+unsafe fn destroy_buffer(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    if ctxt.state.uniform_buffer_binding == id {
+        ctxt.state.uniform_buffer_binding = 0;
+    }
+
+    for binding in ctxt.state.indexed_uniform_buffer_bindings.iter_mut() {
+        if binding.buffer == id {
+            *binding = Default::default();
+        }
+    }
+
+    ctxt.gl.DeleteBuffers(1, &id);
+}
+
+/// Binds the buffer to `GL_UNIFORM_BUFFER`.
+unsafe fn bind_buffer(ctxt: &mut CommandContext, id: gl::types::GLuint) {
+    if ctxt.state.uniform_buffer_binding != id {
+        ctxt.state.uniform_buffer_binding = id;
+        ctxt.gl.BindBuffer(gl::UNIFORM_BUFFER, id);
+    }
+}
+
+/// Writes `data` at `offset` in the buffer bound to `GL_UNIFORM_BUFFER`.
+///
+/// The range is mapped without synchronization, as it hasn't been used since the buffer has
+/// been orphaned for the last time.
+unsafe fn write(ctxt: &mut CommandContext, offset: usize, data: &[u8]) {
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+       ctxt.extensions.gl_arb_map_buffer_range
+    {
+        let mapping = ctxt.gl.MapBufferRange(gl::UNIFORM_BUFFER,
+                                             offset as gl::types::GLintptr,
+                                             data.len() as gl::types::GLsizeiptr,
+                                             gl::MAP_WRITE_BIT | gl::MAP_UNSYNCHRONIZED_BIT |
+                                             gl::MAP_INVALIDATE_RANGE_BIT);
+
+        if !mapping.is_null() {
+            ptr::copy_nonoverlapping(data.as_ptr(), mapping as *mut u8, data.len());
+            ctxt.gl.UnmapBuffer(gl::UNIFORM_BUFFER);
+            return;
+        }
+    }
+
+    ctxt.gl.BufferSubData(gl::UNIFORM_BUFFER, offset as gl::types::GLintptr,
+                          data.len() as gl::types::GLsizeiptr, data.as_ptr() as *const _);
+}
+
+/// Binds a range of the buffer to an indexed uniform buffer binding point.
+unsafe fn bind_range(ctxt: &mut CommandContext, id: gl::types::GLuint,
+                     bind_point: gl::types::GLuint, offset: usize, size: usize)
+{
+    let offset = offset as gl::types::GLintptr;
+    let size = size as gl::types::GLsizeiptr;
+
+    while ctxt.state.indexed_uniform_buffer_bindings.len() <= bind_point as usize {
+        ctxt.state.indexed_uniform_buffer_bindings.push(Default::default());
+    }
+
+    let binding = &mut ctxt.state.indexed_uniform_buffer_bindings[bind_point as usize];
+    if binding.buffer != id || binding.offset != offset || binding.size != size {
+        binding.buffer = id;
+        binding.offset = offset;
+        binding.size = size;
+        ctxt.gl.BindBufferRange(gl::UNIFORM_BUFFER, bind_point, id, offset, size);
+    }
+}
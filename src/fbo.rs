@@ -51,7 +51,10 @@ If a layered image is attached to one attachment, then all attachments must be l
 */
 use std::collections::HashMap;
 use std::cmp;
+use std::error::Error;
+use std::fmt;
 use std::mem;
+use std::ops::Range;
 use std::cell::RefCell;
 use std::marker::PhantomData;
 
@@ -63,12 +66,18 @@ use GlObject;
 use TextureExt;
 
 use texture::CubeLayer;
+use texture::TextureFormat;
+use texture::TextureAny;
 use texture::TextureAnyImage;
+use texture::{InternalFormat, InternalFormatType};
 use texture::TextureAnyMipmap;
 use framebuffer::RenderBufferAny;
 
 use gl;
+use context;
+use context::Context;
 use context::CommandContext;
+use context::ObjectKind;
 use version::Version;
 use version::Api;
 
@@ -111,6 +120,17 @@ pub enum RegularAttachment<'a> {
     RenderBuffer(&'a RenderBufferAny),
 }
 
+impl<'a> RegularAttachment<'a> {
+    /// Returns the format of the attachment, or `None` if it isn't known.
+    #[inline]
+    fn get_format(&self) -> Option<TextureFormat> {
+        match self {
+            &RegularAttachment::Texture(ref tex) => tex.get_texture().get_requested_format(),
+            &RegularAttachment::RenderBuffer(buffer) => Some(buffer.get_format()),
+        }
+    }
+}
+
 /// Describes a single layered framebuffer attachment.
 #[derive(Copy, Clone)]
 pub struct LayeredAttachment<'a>(TextureAnyMipmap<'a>);
@@ -145,7 +165,57 @@ pub struct FramebufferSpecificAttachments<T> {
     pub depth_stencil: DepthStencilAttachments<T>,
 }
 
+impl<T> FramebufferSpecificAttachments<T> {
+    /// Calls a closure for each attachment.
+    fn for_each<F>(&self, mut f: F) where F: FnMut(&T) {
+        for &(_, ref attachment) in self.colors.iter() {
+            f(attachment);
+        }
+
+        match self.depth_stencil {
+            DepthStencilAttachments::None => (),
+            DepthStencilAttachments::DepthAttachment(ref a) => f(a),
+            DepthStencilAttachments::StencilAttachment(ref a) => f(a),
+            DepthStencilAttachments::DepthAndStencilAttachments(ref d, ref s) => { f(d); f(s); },
+            DepthStencilAttachments::DepthStencilAttachment(ref a) => f(a),
+        }
+    }
+}
+
 impl<'a> FramebufferAttachments<'a> {
+    /// Checks that all the attachments can be used with the given context, in other words
+    /// that they have been created with a context that shares its objects with it.
+    pub fn check_context(&self, context: &Context) -> Result<(), ValidationError> {
+        let mut result = Ok(());
+
+        match self {
+            &FramebufferAttachments::Regular(ref attachments) => {
+                attachments.for_each(|attachment| {
+                    let attachment_context = match attachment {
+                        &RegularAttachment::Texture(ref tex) => tex.get_texture().get_context(),
+                        &RegularAttachment::RenderBuffer(buffer) => buffer.get_context(),
+                    };
+
+                    if !attachment_context.is_sharing_objects_with(context) {
+                        result = Err(ValidationError::WrongContext);
+                    }
+                });
+            },
+
+            &FramebufferAttachments::Layered(ref attachments) => {
+                attachments.for_each(|attachment| {
+                    if !attachment.0.get_texture().get_context().is_sharing_objects_with(context) {
+                        result = Err(ValidationError::WrongContext);
+                    }
+                });
+            },
+
+            &FramebufferAttachments::Empty { .. } => (),
+        }
+
+        result
+    }
+
     /// After building a `FramebufferAttachments` struct, you must use this function
     /// to "compile" the attachments and make sure that they are valid together.
     #[inline]
@@ -192,6 +262,13 @@ impl<'a> FramebufferAttachments<'a> {
                         layers: layers,
                         depth_buffer_bits: None,
                         stencil_buffer_bits: None,
+                        depth_format: None,
+                        stencil_format: None,
+                        samples: Some(samples.unwrap_or(0)),
+                        float32_color: false,
+                        color_types: Vec::new(),
+                        rendered_textures: Vec::new(),
+                        external_id: None,
                         marker: PhantomData,
                     })
 
@@ -283,7 +360,12 @@ impl<'a> FramebufferAttachments<'a> {
         let mut dimensions = None;
         let mut depth_bits = None;
         let mut stencil_bits = None;
+        let mut depth_format = None;
+        let mut stencil_format = None;
         let mut samples = None;     // contains `0` if not multisampling and `None` if unknown
+        let mut float32_color = false;
+        let mut color_types = Vec::new();
+        let mut rendered_textures = Vec::new();
 
         for &(index, LayeredAttachment(ref attachment)) in colors.iter() {
             if index >= max_color_attachments as u32 {
@@ -292,25 +374,39 @@ impl<'a> FramebufferAttachments<'a> {
                     obtained: index as usize,
                 });
             }
+            try!(check_float_color_attachment(context, attachment.get_texture(),
+                                              &mut float32_color));
+            color_types.push(get_color_attachment_type(attachment.get_texture()));
+            if attachment.get_level() == 0 &&
+               attachment.get_texture().get_regenerate_mipmaps_after_render()
+            {
+                rendered_textures.push(attachment.get_texture());
+            }
             raw_attachments.color.push((index, handle_tex!(attachment, dimensions, samples)));
         }
 
         match depth_stencil {
             DepthStencilAttachments::None => (),
             DepthStencilAttachments::DepthAttachment(LayeredAttachment(ref d)) => {
+                depth_format = d.get_texture().get_requested_format();
                 raw_attachments.depth = Some(handle_tex!(d, dimensions, samples, depth_bits));
             },
             DepthStencilAttachments::StencilAttachment(LayeredAttachment(ref s)) => {
+                stencil_format = s.get_texture().get_requested_format();
                 raw_attachments.stencil = Some(handle_tex!(s, dimensions, samples, stencil_bits));
             },
             DepthStencilAttachments::DepthAndStencilAttachments(LayeredAttachment(ref d),
                                                                  LayeredAttachment(ref s))
             => {
+                depth_format = d.get_texture().get_requested_format();
+                stencil_format = s.get_texture().get_requested_format();
                 raw_attachments.depth = Some(handle_tex!(d, dimensions, samples, depth_bits));
                 raw_attachments.stencil = Some(handle_tex!(s, dimensions, samples, stencil_bits));
             },
             DepthStencilAttachments::DepthStencilAttachment(LayeredAttachment(ref ds)) => {
                 // FIXME: bits count
+                depth_format = ds.get_texture().get_requested_format();
+                stencil_format = depth_format;
                 raw_attachments.depth_stencil = Some(handle_tex!(ds, dimensions, samples));
             },
         }
@@ -328,6 +424,13 @@ impl<'a> FramebufferAttachments<'a> {
             layers: None,       // FIXME: count layers
             depth_buffer_bits: depth_bits,
             stencil_buffer_bits: stencil_bits,
+            depth_format: depth_format,
+            stencil_format: stencil_format,
+            samples: samples,
+            float32_color: float32_color,
+            color_types: color_types,
+            rendered_textures: rendered_textures,
+            external_id: None,
             marker: PhantomData,
         })
     }
@@ -464,7 +567,12 @@ impl<'a> FramebufferAttachments<'a> {
         let mut dimensions = None;
         let mut depth_bits = None;
         let mut stencil_bits = None;
+        let mut depth_format = None;
+        let mut stencil_format = None;
         let mut samples = None;     // contains `0` if not multisampling and `None` if unknown
+        let mut float32_color = false;
+        let mut color_types = Vec::new();
+        let mut rendered_textures = Vec::new();
 
         for &(index, ref attachment) in colors.iter() {
             if index >= max_color_attachments as u32 {
@@ -473,23 +581,39 @@ impl<'a> FramebufferAttachments<'a> {
                     obtained: index as usize,
                 });
             }
+            if let &RegularAttachment::Texture(ref tex) = attachment {
+                try!(check_float_color_attachment(context, tex.get_texture(),
+                                                  &mut float32_color));
+                color_types.push(get_color_attachment_type(tex.get_texture()));
+                if tex.get_level() == 0 && tex.get_texture().get_regenerate_mipmaps_after_render() {
+                    rendered_textures.push(tex.get_texture());
+                }
+            } else {
+                color_types.push(ColorAttachmentType::Float);
+            }
             raw_attachments.color.push((index, handle_atch!(attachment, dimensions, samples)));
         }
 
         match depth_stencil {
             DepthStencilAttachments::None => (),
             DepthStencilAttachments::DepthAttachment(ref d) => {
+                depth_format = d.get_format();
                 raw_attachments.depth = Some(handle_atch!(d, dimensions, samples, depth_bits));
             },
             DepthStencilAttachments::StencilAttachment(ref s) => {
+                stencil_format = s.get_format();
                 raw_attachments.stencil = Some(handle_atch!(s, dimensions, samples, stencil_bits));
             },
             DepthStencilAttachments::DepthAndStencilAttachments(ref d, ref s) => {
+                depth_format = d.get_format();
+                stencil_format = s.get_format();
                 raw_attachments.depth = Some(handle_atch!(d, dimensions, samples, depth_bits));
                 raw_attachments.stencil = Some(handle_atch!(s, dimensions, samples, stencil_bits));
             },
             DepthStencilAttachments::DepthStencilAttachment(ref ds) => {
                 // FIXME: bits count
+                depth_format = ds.get_format();
+                stencil_format = depth_format;
                 raw_attachments.depth_stencil = Some(handle_atch!(ds, dimensions, samples));
             },
         }
@@ -507,6 +631,13 @@ impl<'a> FramebufferAttachments<'a> {
             layers: None,
             depth_buffer_bits: depth_bits,
             stencil_buffer_bits: stencil_bits,
+            depth_format: depth_format,
+            stencil_format: stencil_format,
+            samples: samples,
+            float32_color: float32_color,
+            color_types: color_types,
+            rendered_textures: rendered_textures,
+            external_id: None,
             marker: PhantomData,
         })
     }
@@ -520,9 +651,61 @@ pub struct ValidatedAttachments<'a> {
     layers: Option<u32>,
     depth_buffer_bits: Option<u16>,
     stencil_buffer_bits: Option<u16>,
+    // formats of the depth and stencil attachments, or `None` if unknown
+    depth_format: Option<TextureFormat>,
+    stencil_format: Option<TextureFormat>,
+    // number of samples, `0` if not multisampled and `None` if unknown
+    samples: Option<u32>,
+    float32_color: bool,
+    // type of each color attachment, in the order of the draw buffers
+    color_types: Vec<ColorAttachmentType>,
+    // textures whose main level is attached and whose mipmaps must be regenerated after render
+    rendered_textures: Vec<&'a TextureAny>,
+    // id of a framebuffer object that hasn't been created by glium
+    external_id: Option<gl::types::GLuint>,
     marker: PhantomData<&'a ()>,
 }
 
+impl ValidatedAttachments<'static> {
+    /// Builds attachments that correspond to a framebuffer object that has been created
+    /// outside of glium.
+    ///
+    /// # Unsafety
+    ///
+    /// The id must be a valid framebuffer object whose characteristics match the parameters.
+    pub unsafe fn from_external_fbo(id: gl::types::GLuint, dimensions: (u32, u32),
+                                    depth_buffer_bits: Option<u16>,
+                                    stencil_buffer_bits: Option<u16>)
+                                    -> ValidatedAttachments<'static>
+    {
+        ValidatedAttachments {
+            raw: RawAttachments {
+                color: Vec::new(),
+                depth: None,
+                stencil: None,
+                depth_stencil: None,
+                default_width: None,
+                default_height: None,
+                default_layers: None,
+                default_samples: None,
+                default_samples_fixed: None,
+            },
+            dimensions: dimensions,
+            layers: None,
+            depth_buffer_bits: depth_buffer_bits,
+            stencil_buffer_bits: stencil_buffer_bits,
+            depth_format: None,
+            stencil_format: None,
+            samples: None,
+            float32_color: false,
+            color_types: Vec::new(),
+            rendered_textures: Vec::new(),
+            external_id: Some(id),
+            marker: PhantomData,
+        }
+    }
+}
+
 impl<'a> ValidatedAttachments<'a> {
     /// Returns `true` if the framebuffer is layered.
     #[inline]
@@ -549,6 +732,197 @@ impl<'a> ValidatedAttachments<'a> {
     pub fn get_stencil_buffer_bits(&self) -> Option<u16> {
         self.stencil_buffer_bits
     }
+
+    /// Returns the format of the depth attachment, or `None` if there is no depth attachment or
+    /// if its format isn't known. Also works for depth-stencil attachments.
+    #[inline]
+    pub fn get_depth_format(&self) -> Option<TextureFormat> {
+        self.depth_format
+    }
+
+    /// Returns the format of the stencil attachment, or `None` if there is no stencil
+    /// attachment or if its format isn't known. Also works for depth-stencil attachments.
+    #[inline]
+    pub fn get_stencil_format(&self) -> Option<TextureFormat> {
+        self.stencil_format
+    }
+
+    /// Returns `true` if the attachments correspond to a framebuffer object that hasn't been
+    /// created by glium.
+    #[inline]
+    pub fn is_external(&self) -> bool {
+        self.external_id.is_some()
+    }
+
+    /// Returns the number of samples of the attachments, `0` if they are not multisampled, or
+    /// `None` if it isn't known.
+    #[inline]
+    pub fn get_samples(&self) -> Option<u32> {
+        self.samples
+    }
+
+    /// Returns `true` if one of the color attachments is a texture with 32 bits floating-point
+    /// components.
+    #[inline]
+    pub fn has_float32_color_attachment(&self) -> bool {
+        self.float32_color
+    }
+
+    /// Returns the type of the values of each color attachment, in the order of the draw
+    /// buffers.
+    #[inline]
+    pub fn get_color_attachment_types(&self) -> &[ColorAttachmentType] {
+        &self.color_types
+    }
+
+    /// Returns `true` if at least one of the attachments is a texture.
+    #[inline]
+    pub fn has_texture_attachments(&self) -> bool {
+        self.find_raw(|a| match a {
+            &RawAttachment::Texture { .. } => true,
+            &RawAttachment::RenderBuffer(_) => false,
+        }).is_some()
+    }
+
+    /// Marks the mipmaps of the attached textures as outdated, for the textures that have been
+    /// created with `MipmapsOption::AutoGeneratedAfterRender`.
+    ///
+    /// Must be called after each command that writes to the framebuffer.
+    #[inline]
+    pub fn mark_rendered(&self) {
+        for texture in self.rendered_textures.iter() {
+            texture.mark_rendered();
+        }
+    }
+
+    /// Returns the attachment point where one of the given mipmap levels of a texture is
+    /// attached, if any.
+    pub fn find_texture_attachment(&self, texture: gl::types::GLuint, levels: Range<u32>)
+                                   -> Option<AttachmentPoint>
+    {
+        self.find_raw(|a| match a {
+            &RawAttachment::Texture { texture: id, level, .. } => {
+                id == texture && level >= levels.start && level < levels.end
+            },
+            &RawAttachment::RenderBuffer(_) => false,
+        })
+    }
+
+    /// Returns the attachment point of the first attachment that matches the predicate.
+    fn find_raw<F>(&self, mut predicate: F) -> Option<AttachmentPoint>
+                   where F: FnMut(&RawAttachment) -> bool
+    {
+        for &(index, ref attachment) in self.raw.color.iter() {
+            if predicate(attachment) {
+                return Some(AttachmentPoint::Color(index));
+            }
+        }
+
+        let others = [(&self.raw.depth, AttachmentPoint::Depth),
+                      (&self.raw.stencil, AttachmentPoint::Stencil),
+                      (&self.raw.depth_stencil, AttachmentPoint::DepthStencil)];
+
+        for &(attachment, point) in others.iter() {
+            if let &Some(ref attachment) = attachment {
+                if predicate(attachment) {
+                    return Some(point);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Point of a framebuffer where an image is attached.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttachmentPoint {
+    /// The color attachment with the given index.
+    Color(u32),
+    /// The depth attachment.
+    Depth,
+    /// The stencil attachment.
+    Stencil,
+    /// The depth-stencil attachment.
+    DepthStencil,
+}
+
+/// Checks that a texture with floating-point components can be used as a color attachment.
+///
+/// Type of the values stored in a color attachment.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorAttachmentType {
+    /// Floating-point or normalized values. Cleared with `glClear`.
+    Float,
+    /// Signed integers. Cleared with `glClearBufferiv`.
+    SignedInteger,
+    /// Unsigned integers. Cleared with `glClearBufferuiv`.
+    UnsignedInteger,
+}
+
+/// Returns the type of the values of a texture used as a color attachment.
+pub fn get_color_attachment_type(texture: &TextureAny) -> ColorAttachmentType {
+    let ty = match texture.get_internal_format() {
+        Ok(InternalFormat::OneComponent { ty1, .. }) => ty1,
+        Ok(InternalFormat::TwoComponents { ty1, .. }) => ty1,
+        Ok(InternalFormat::ThreeComponents { ty1, .. }) => ty1,
+        Ok(InternalFormat::FourComponents { ty1, .. }) => ty1,
+        Err(_) => return ColorAttachmentType::Float,
+    };
+
+    match ty {
+        InternalFormatType::Int => ColorAttachmentType::SignedInteger,
+        InternalFormatType::UnsignedInt => ColorAttachmentType::UnsignedInteger,
+        _ => ColorAttachmentType::Float,
+    }
+}
+
+/// Sets `float32` to `true` if the texture has 32 bits floating-point components.
+fn check_float_color_attachment<C>(context: &C, texture: &TextureAny, float32: &mut bool)
+                                   -> Result<(), ValidationError> where C: CapabilitiesSource
+{
+    let components = match texture.get_internal_format() {
+        Ok(InternalFormat::OneComponent { ty1, bits1 }) => vec![(ty1, bits1)],
+        Ok(InternalFormat::TwoComponents { ty1, bits1, ty2, bits2 }) => {
+            vec![(ty1, bits1), (ty2, bits2)]
+        },
+        Ok(InternalFormat::ThreeComponents { ty1, bits1, ty2, bits2, ty3, bits3 }) => {
+            vec![(ty1, bits1), (ty2, bits2), (ty3, bits3)]
+        },
+        Ok(InternalFormat::FourComponents { ty1, bits1, ty2, bits2, ty3, bits3, ty4, bits4 }) => {
+            vec![(ty1, bits1), (ty2, bits2), (ty3, bits3), (ty4, bits4)]
+        },
+        Err(_) => return Ok(()),
+    };
+
+    let bits = match components.iter().filter(|&&(ty, _)| ty == InternalFormatType::Float)
+                                      .map(|&(_, bits)| bits).max()
+    {
+        Some(bits) => bits,
+        None => return Ok(())
+    };
+
+    let version = context.get_version();
+    let extensions = context.get_extensions();
+
+    let supported = if version >= &Version(Api::Gl, 1, 0) {
+        version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_color_buffer_float
+    } else if bits <= 16 {
+        version >= &Version(Api::GlEs, 3, 2) || extensions.gl_ext_color_buffer_float ||
+        extensions.gl_ext_color_buffer_half_float
+    } else {
+        version >= &Version(Api::GlEs, 3, 2) || extensions.gl_ext_color_buffer_float
+    };
+
+    if !supported {
+        return Err(ValidationError::FloatColorAttachmentNotSupported);
+    }
+
+    if bits >= 32 {
+        *float32 = true;
+    }
+
+    Ok(())
 }
 
 /// An error that can happen while validating attachments.
@@ -569,6 +943,13 @@ pub enum ValidationError {
     /// All attachments must have the same number of samples.
     SamplesCountMismatch,
 
+    /// One of the color attachments has a floating-point format, but the backend doesn't support
+    /// rendering to floating-point color buffers.
+    ///
+    /// This requires OpenGL 3.0 or `GL_ARB_color_buffer_float`, or OpenGL ES 3.2 or
+    /// `GL_EXT_color_buffer_float`.
+    FloatColorAttachmentNotSupported,
+
     /// Backends only support a certain number of color attachments.
     TooManyColorAttachments {
         /// Maximum number of attachments.
@@ -576,6 +957,46 @@ pub enum ValidationError {
         /// Number of attachments that were given.
         obtained: usize,
     },
+
+    /// One of the attachments has been created with a context that doesn't share its objects
+    /// with the context of the framebuffer.
+    WrongContext,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &ValidationError::TooManyColorAttachments { maximum, obtained } => {
+                write!(formatter, "{} ({} given, the maximum is {})", self.description(),
+                       obtained, maximum)
+            },
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for ValidationError {
+    fn description(&self) -> &str {
+        match self {
+            &ValidationError::EmptyFramebufferObjectsNotSupported => "Empty framebuffer objects \
+                                                                      are not supported",
+            &ValidationError::EmptyFramebufferUnsupportedDimensions => "The dimensions of the \
+                                                                        empty framebuffer \
+                                                                        object are not \
+                                                                        supported",
+            &ValidationError::DimensionsMismatchNotSupported => "The backend doesn't support \
+                                                                 attachments with various \
+                                                                 dimensions",
+            &ValidationError::SamplesCountMismatch => "All attachments must have the same \
+                                                       number of samples",
+            &ValidationError::FloatColorAttachmentNotSupported => "Rendering to floating-point \
+                                                                   color attachments is not \
+                                                                   supported",
+            &ValidationError::TooManyColorAttachments { .. } => "Too many color attachments",
+            &ValidationError::WrongContext => "One of the attachments belongs to a context that \
+                                               doesn't share its objects with the framebuffer",
+        }
+    }
 }
 
 /// Data structure stored in the hashmap.
@@ -639,9 +1060,7 @@ impl FramebuffersContainer {
         let mut other = HashMap::new();
         mem::swap(&mut *ctxt.framebuffer_objects.framebuffers.borrow_mut(), &mut other);
 
-        for (_, obj) in other.into_iter() {
-            obj.destroy(ctxt);
-        }
+        destroy_in_order(ctxt, other.into_iter().map(|(_, obj)| obj).collect());
     }
 
     /// Destroys all framebuffer objects that contain a precise texture.
@@ -696,9 +1115,9 @@ impl FramebuffersContainer {
             }
         }
 
-        for atch in attachments.into_iter() {
-            framebuffers.remove(&atch).unwrap().destroy(ctxt);
-        }
+        let objects = attachments.into_iter().map(|atch| framebuffers.remove(&atch).unwrap())
+                                 .collect();
+        destroy_in_order(ctxt, objects);
     }
 
     /// Destroys all framebuffer objects.
@@ -709,9 +1128,7 @@ impl FramebuffersContainer {
         let mut other = HashMap::with_capacity(0);
         mem::swap(&mut *ctxt.framebuffer_objects.framebuffers.borrow_mut(), &mut other);
 
-        for (_, obj) in other.into_iter() {
-            obj.destroy(ctxt);
-        }
+        destroy_in_order(ctxt, other.into_iter().map(|(_, obj)| obj).collect());
     }
 
     ///
@@ -769,6 +1186,21 @@ impl FramebuffersContainer {
         ctxt.gl.ReadBuffer(gl::COLOR_ATTACHMENT0);     // TODO: cache
     }
 
+    /// Binds the framebuffer object that corresponds to some attachments so that its first color
+    /// attachment becomes the source of `glReadPixels`, `glCopyTexSubImage2D`, etc.
+    ///
+    /// # Unsafety
+    ///
+    /// After calling this function, you **must** make sure to call `purge_texture`
+    /// and/or `purge_renderbuffer` when one of the attachment is destroyed.
+    pub unsafe fn bind_attachments_for_reading(ctxt: &mut CommandContext,
+                                               attachments: &ValidatedAttachments)
+    {
+        let framebuffer = FramebuffersContainer::get_framebuffer(ctxt, attachments);
+        bind_framebuffer(ctxt, framebuffer, false, true);
+        ctxt.gl.ReadBuffer(gl::COLOR_ATTACHMENT0);     // TODO: cache
+    }
+
     ///
     /// # Unsafety
     ///
@@ -777,6 +1209,10 @@ impl FramebuffersContainer {
     fn get_framebuffer(ctxt: &mut CommandContext, attachments: &ValidatedAttachments)
                        -> gl::types::GLuint
     {
+        if let Some(id) = attachments.external_id {
+            return id;
+        }
+
         // TODO: use entries API
         let mut framebuffers = ctxt.framebuffer_objects.framebuffers.borrow_mut();
         if let Some(value) = framebuffers.get(&attachments.raw) {
@@ -790,6 +1226,19 @@ impl FramebuffersContainer {
     }
 }
 
+/// Destroys framebuffer objects by increasing identifier.
+///
+/// The iteration order of the hash map changes between runs. Destroying the objects in a fixed
+/// order makes the identifiers that the implementation gives to the next objects deterministic,
+/// which is required to compare the captures of tools such as apitrace.
+fn destroy_in_order(ctxt: &mut CommandContext, mut objects: Vec<FrameBufferObject>) {
+    objects.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for obj in objects {
+        obj.destroy(ctxt);
+    }
+}
+
 impl Drop for FramebuffersContainer {
     #[inline]
     fn drop(&mut self) {
@@ -822,9 +1271,7 @@ impl FrameBufferObject {
         let id = unsafe {
             let mut id = mem::uninitialized();
 
-            if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                ctxt.extensions.gl_arb_direct_state_access
-            {
+            if ctxt.capabilities.direct_state_access {
                 ctxt.gl.CreateFramebuffers(1, &mut id);
 
             } else if ctxt.version >= &Version(Api::Gl, 3, 0) ||
@@ -846,6 +1293,11 @@ impl FrameBufferObject {
             id
         };
 
+        ctxt.live_objects.add(ObjectKind::Framebuffer, id as usize);
+        context::label_internal_object(ctxt, gl::FRAMEBUFFER, id,
+                                       format_args!("framebuffer object with {} color \
+                                                     attachment(s)", attachments.color.len()));
+
         // framebuffer parameters
         // TODO: DSA
         if let Some(width) = attachments.default_width {
@@ -936,9 +1388,7 @@ impl FrameBufferObject {
 
         // calling `glDrawBuffers` if necessary
         if raw_attachments != &[gl::COLOR_ATTACHMENT0] {
-            if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-               ctxt.extensions.gl_arb_direct_state_access
-            {
+            if ctxt.capabilities.direct_state_access {
                 unsafe {
                     ctxt.gl.NamedFramebufferDrawBuffers(id, raw_attachments.len()
                                                         as gl::types::GLsizei,
@@ -1003,6 +1453,8 @@ impl FrameBufferObject {
         } else {
             unreachable!();
         }
+
+        ctxt.live_objects.remove(ObjectKind::Framebuffer, self.id as usize);
     }
 }
 
@@ -1015,6 +1467,20 @@ impl GlObject for FrameBufferObject {
     }
 }
 
+/// Returns the color buffer of the default framebuffer that is drawn upon.
+///
+/// This is the back buffer, except with single-buffered contexts on desktop OpenGL where only
+/// the front buffer exists. OpenGL ES calls the only buffer of single-buffered surfaces
+/// `GL_BACK` too.
+#[inline]
+pub fn default_color_buffer(ctxt: &CommandContext) -> gl::types::GLenum {
+    if ctxt.capabilities.double_buffer || ctxt.version >= &Version(Api::GlEs, 2, 0) {
+        gl::BACK
+    } else {
+        gl::FRONT
+    }
+}
+
 /// Binds a framebuffer object, either for drawing, reading, or both.
 ///
 /// # Safety
@@ -1024,6 +1490,12 @@ impl GlObject for FrameBufferObject {
 pub unsafe fn bind_framebuffer(ctxt: &mut CommandContext, fbo_id: gl::types::GLuint,
                                draw: bool, read: bool)
 {
+    if (draw && ctxt.state.draw_framebuffer != fbo_id) ||
+       (read && ctxt.state.read_framebuffer != fbo_id)
+    {
+        ctxt.frame_statistics.update(|s| s.framebuffer_changes += 1);
+    }
+
     if draw && read {
         if ctxt.state.draw_framebuffer != fbo_id || ctxt.state.read_framebuffer != fbo_id {
             if ctxt.version >= &Version(Api::Gl, 3, 0) ||
@@ -1086,6 +1558,37 @@ pub unsafe fn bind_framebuffer(ctxt: &mut CommandContext, fbo_id: gl::types::GLu
     }
 }
 
+/// Returns the value of `glCheckFramebufferStatus` for a framebuffer object.
+///
+/// # Safety
+///
+/// The id of the FBO must be valid.
+///
+pub unsafe fn check_framebuffer_status(ctxt: &mut CommandContext, fbo_id: gl::types::GLuint)
+                                       -> gl::types::GLenum
+{
+    if ctxt.version >= &Version(Api::Gl, 4, 5) {
+        ctxt.gl.CheckNamedFramebufferStatus(fbo_id, gl::DRAW_FRAMEBUFFER)
+
+    } else if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+              ctxt.extensions.gl_arb_framebuffer_object
+    {
+        bind_framebuffer(ctxt, fbo_id, true, false);
+        ctxt.gl.CheckFramebufferStatus(gl::DRAW_FRAMEBUFFER)
+
+    } else if ctxt.version >= &Version(Api::GlEs, 2, 0) {
+        bind_framebuffer(ctxt, fbo_id, true, false);
+        ctxt.gl.CheckFramebufferStatus(gl::FRAMEBUFFER)
+
+    } else if ctxt.extensions.gl_ext_framebuffer_object {
+        bind_framebuffer(ctxt, fbo_id, true, false);
+        ctxt.gl.CheckFramebufferStatusEXT(gl::FRAMEBUFFER_EXT)
+
+    } else {
+        unreachable!()
+    }
+}
+
 /// Attaches something to a framebuffer object.
 ///
 /// # Panic
@@ -1111,9 +1614,7 @@ unsafe fn attach(ctxt: &mut CommandContext, slot: gl::types::GLenum,
                     assert_eq!(layer, Some(0));
                     debug_assert!(cubemap_layer.is_none());
 
-                    if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                       ctxt.extensions.gl_arb_direct_state_access
-                    {
+                    if ctxt.capabilities.direct_state_access {
                         ctxt.gl.NamedFramebufferTexture(id, slot, tex_id,
                                                         level as gl::types::GLint);
 
@@ -1187,9 +1688,7 @@ unsafe fn attach(ctxt: &mut CommandContext, slot: gl::types::GLenum,
                         layer.unwrap()
                     };
 
-                    if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                       ctxt.extensions.gl_arb_direct_state_access
-                    {
+                    if ctxt.capabilities.direct_state_access {
                         ctxt.gl.NamedFramebufferTextureLayer(id, slot, tex_id,
                                                              level as gl::types::GLint,
                                                              layer as gl::types::GLint);
@@ -1255,9 +1754,7 @@ unsafe fn attach(ctxt: &mut CommandContext, slot: gl::types::GLenum,
                 gl::TEXTURE_1D_ARRAY | gl::TEXTURE_2D_ARRAY | gl::TEXTURE_2D_MULTISAMPLE_ARRAY |
                 gl::TEXTURE_3D | gl::TEXTURE_CUBE_MAP_ARRAY if layer.is_none() =>
                 {
-                    if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                       ctxt.extensions.gl_arb_direct_state_access
-                    {
+                    if ctxt.capabilities.direct_state_access {
                         ctxt.gl.NamedFramebufferTexture(id, slot, tex_id,
                                                         level as gl::types::GLint);
 
@@ -1310,9 +1807,7 @@ unsafe fn attach(ctxt: &mut CommandContext, slot: gl::types::GLenum,
 
                 // layered cubemaps
                 gl::TEXTURE_CUBE_MAP if layer.is_none() => {
-                    if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                       ctxt.extensions.gl_arb_direct_state_access
-                    {
+                    if ctxt.capabilities.direct_state_access {
                         ctxt.gl.NamedFramebufferTexture(id, slot, tex_id,
                                                         level as gl::types::GLint);
 
@@ -1339,9 +1834,7 @@ unsafe fn attach(ctxt: &mut CommandContext, slot: gl::types::GLenum,
 
         // renderbuffers are straight-forward
         RawAttachment::RenderBuffer(renderbuffer) => {
-            if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-               ctxt.extensions.gl_arb_direct_state_access
-            {
+            if ctxt.capabilities.direct_state_access {
                 ctxt.gl.NamedFramebufferRenderbuffer(id, slot, gl::RENDERBUFFER, renderbuffer);
 
             } else if ctxt.extensions.gl_ext_direct_state_access &&
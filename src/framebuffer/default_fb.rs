@@ -9,17 +9,23 @@ use context::Context;
 use DrawParameters;
 use FboAttachments;
 use Rect;
+use texture::{RawImage2d, Texture2d};
 use BlitTarget;
+use BlitMask;
+use BlitError;
 use ContextExt;
 use ToGlEnum;
 use ops;
 use uniforms;
 
-use {Program, Surface};
+use {CaptureError, CaptureSurface, CommandList, Program, Surface};
+use CommandListExt;
 use DrawError;
 
-use {fbo, gl};
+use fbo;
 use framebuffer;
+use framebuffer::ReadError;
+use gl;
 use index;
 use vertex;
 
@@ -60,7 +66,34 @@ impl Surface for DefaultFramebuffer {
              depth: Option<f32>, stencil: Option<i32>)
     {
         // TODO: wrong attachment
-        ops::clear(&self.context, None, None, color, color_srgb, depth, stencil);
+        ops::clear(&self.context, None, rect, color, color_srgb, depth, stencil);
+    }
+
+    #[inline]
+    fn clear_color_integer(&mut self, attachment_index: u32, color: [i32; 4]) {
+        ops::clear_color_buffer(&self.context, None, None, attachment_index,
+                                ops::ClearBufferData::SignedInt(color));
+    }
+
+    #[inline]
+    fn clear_color_unsigned(&mut self, attachment_index: u32, color: [u32; 4]) {
+        ops::clear_color_buffer(&self.context, None, None, attachment_index,
+                                ops::ClearBufferData::UnsignedInt(color));
+    }
+
+    fn copy_to_texture(&self, texture: &Texture2d, source_rect: &Rect, dest_offset: (u32, u32),
+                       level: u32) -> Result<(), ReadError>
+    {
+        let read_buffer = match self.attachment {
+            DefaultFramebufferAttachment::BackLeft => gl::BACK_LEFT,
+            DefaultFramebufferAttachment::BackRight => gl::BACK_RIGHT,
+            DefaultFramebufferAttachment::FrontLeft => gl::FRONT_LEFT,
+            DefaultFramebufferAttachment::FrontRight => gl::FRONT_RIGHT,
+        };
+
+        let mut ctxt = self.context.make_current();
+        let source = ops::Source::DefaultFramebuffer(read_buffer, self.get_dimensions());
+        ops::copy_to_texture(&mut ctxt, source, source_rect, texture, dest_offset, level)
     }
 
     fn get_dimensions(&self) -> (u32, u32) {
@@ -102,7 +135,13 @@ impl Surface for DefaultFramebuffer {
 
         // TODO: wrong attachment
         ops::draw(&self.context, None, vertex_buffer, index_buffer.into(), program,
-                  uniforms, draw_parameters, self.get_dimensions())
+                  uniforms, draw_parameters, self.get_dimensions(), true)
+    }
+
+    #[inline]
+    fn draw_command_list(&mut self, list: &CommandList) -> Result<(), DrawError> {
+        ops::replay(&self.context, None, list.get_commands(), self.get_dimensions(),
+                    self.has_depth_buffer())
     }
 
     #[inline]
@@ -113,29 +152,60 @@ impl Surface for DefaultFramebuffer {
     }
 
     #[inline]
-    fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
-                       filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
+                       filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                       -> Result<(), BlitError> where S: Surface
+    {
+        target.blit_buffers_from_frame(source_rect, target_rect, filter, mask)
+    }
+
+    #[inline]
+    fn blit_buffers_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
+                               filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                               -> Result<(), BlitError>
     {
         ops::blit(&self.context, None, self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_simple_framebuffer(&self, source: &framebuffer::SimpleFrameBuffer,
+                                            source_rect: &Rect, target_rect: &BlitTarget,
+                                            filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                                            -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_simple_framebuffer(&self, source: &framebuffer::SimpleFrameBuffer,
-                                    source_rect: &Rect, target_rect: &BlitTarget,
-                                    filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_multioutput_framebuffer(&self,
+                                                 source: &framebuffer::MultiOutputFrameBuffer,
+                                                 source_rect: &Rect, target_rect: &BlitTarget,
+                                                 filter: uniforms::MagnifySamplerFilter,
+                                                 mask: BlitMask)
+                                                 -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_multioutput_framebuffer(&self, source: &framebuffer::MultiOutputFrameBuffer,
-                                         source_rect: &Rect, target_rect: &BlitTarget,
-                                         filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_external_framebuffer(&self, source: &framebuffer::ExternalFramebuffer,
+                                              source_rect: &Rect, target_rect: &BlitTarget,
+                                              filter: uniforms::MagnifySamplerFilter,
+                                              mask: BlitMask)
+                                              -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+}
+
+impl CaptureSurface for DefaultFramebuffer {
+    #[inline]
+    fn capture(&self) -> Result<RawImage2d<'static, u8>, CaptureError> {
+        ops::capture(&self.context, None, self.context.get_framebuffer_dimensions())
     }
 }
 
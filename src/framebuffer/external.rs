@@ -0,0 +1,256 @@
+//! Contains everything related to framebuffers that have been created outside of glium.
+
+use std::rc::Rc;
+
+use backend::Facade;
+use context::Context;
+
+use FboAttachments;
+use Rect;
+use texture::{RawImage2d, Texture2d};
+use BlitTarget;
+use BlitMask;
+use BlitError;
+use ContextExt;
+use ToGlEnum;
+use ops;
+use uniforms;
+
+use {CaptureError, CaptureSurface, CommandList, Surface};
+use CommandListExt;
+use DrawError;
+
+use {fbo, gl};
+use framebuffer;
+use framebuffer::ReadError;
+use version::{Api, Version};
+
+/// A framebuffer object that has been created outside of glium, for example by a VR
+/// runtime or a video encoding library.
+///
+/// Glium doesn't know the attachments of this framebuffer, and doesn't destroy it when
+/// this object is dropped. The framebuffer must stay alive as long as this object exists.
+///
+/// ## Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+/// # let fbo_id = 1;
+/// use glium::Surface;
+///
+/// let mut framebuffer = unsafe {
+///     glium::framebuffer::ExternalFramebuffer::from_raw_fbo(&display, fbo_id, (1024, 1024),
+///                                                           Some(24), None)
+/// };
+///
+/// if !framebuffer.is_complete() {
+///     panic!("The framebuffer is not complete");
+/// }
+///
+/// framebuffer.clear_color(0.0, 0.0, 0.0, 1.0);
+/// ```
+pub struct ExternalFramebuffer {
+    context: Rc<Context>,
+    attachments: fbo::ValidatedAttachments<'static>,
+}
+
+impl ExternalFramebuffer {
+    /// Builds an `ExternalFramebuffer` from the id of a framebuffer object.
+    ///
+    /// `dimensions` are the dimensions of the attachments of the framebuffer, and
+    /// `depth_buffer_bits` and `stencil_buffer_bits` are the number of bits of its depth and
+    /// stencil buffers, or `None` if it doesn't have any.
+    ///
+    /// The framebuffer isn't checked for completeness. Call `is_complete` if you need to.
+    ///
+    /// # Unsafety
+    ///
+    /// The id must be a valid framebuffer object of the context of the facade, and the
+    /// parameters must match its attachments.
+    pub unsafe fn from_raw_fbo<F>(facade: &F, id: gl::types::GLuint, dimensions: (u32, u32),
+                                  depth_buffer_bits: Option<u16>,
+                                  stencil_buffer_bits: Option<u16>) -> ExternalFramebuffer
+                                  where F: Facade
+    {
+        assert!(id != 0, "Use the `Frame` or `DefaultFramebuffer` to draw on the default \
+                          framebuffer");
+
+        ExternalFramebuffer {
+            context: facade.get_context().clone(),
+            attachments: fbo::ValidatedAttachments::from_external_fbo(id, dimensions,
+                                                                      depth_buffer_bits,
+                                                                      stencil_buffer_bits),
+        }
+    }
+
+    /// Returns true if the framebuffer is complete, ie. if it can be drawn upon.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glCheckFramebufferStatus`.
+    pub fn is_complete(&self) -> bool {
+        let mut ctxt = self.context.make_current();
+        let id = fbo::FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt,
+                                                                         Some(&self.attachments));
+        let status = unsafe { fbo::check_framebuffer_status(&mut ctxt, id) };
+
+        if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+           ctxt.version >= &Version(Api::GlEs, 2, 0) ||
+           ctxt.extensions.gl_arb_framebuffer_object
+        {
+            status == gl::FRAMEBUFFER_COMPLETE
+        } else {
+            status == gl::FRAMEBUFFER_COMPLETE_EXT
+        }
+    }
+}
+
+impl Surface for ExternalFramebuffer {
+    #[inline]
+    fn clear(&mut self, rect: Option<&Rect>, color: Option<(f32, f32, f32, f32)>, color_srgb: bool,
+             depth: Option<f32>, stencil: Option<i32>)
+    {
+        ops::clear(&self.context, Some(&self.attachments), rect, color, color_srgb, depth, stencil);
+    }
+
+    #[inline]
+    fn clear_color_integer(&mut self, attachment_index: u32, color: [i32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.attachments), None, attachment_index,
+                                ops::ClearBufferData::SignedInt(color));
+    }
+
+    #[inline]
+    fn clear_color_unsigned(&mut self, attachment_index: u32, color: [u32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.attachments), None, attachment_index,
+                                ops::ClearBufferData::UnsignedInt(color));
+    }
+
+    #[inline]
+    fn copy_to_texture(&self, texture: &Texture2d, source_rect: &Rect, dest_offset: (u32, u32),
+                       level: u32) -> Result<(), ReadError>
+    {
+        let mut ctxt = self.context.make_current();
+        ops::copy_to_texture(&mut ctxt, ops::Source::Framebuffer(&self.attachments), source_rect,
+                             texture, dest_offset, level)
+    }
+
+    #[inline]
+    fn get_dimensions(&self) -> (u32, u32) {
+        self.attachments.get_dimensions()
+    }
+
+    #[inline]
+    fn get_depth_buffer_bits(&self) -> Option<u16> {
+        self.attachments.get_depth_buffer_bits()
+    }
+
+    #[inline]
+    fn get_stencil_buffer_bits(&self) -> Option<u16> {
+        self.attachments.get_stencil_buffer_bits()
+    }
+
+    fn draw<'b, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
+        uniforms: &U, draw_parameters: &::DrawParameters) -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
+        V: ::vertex::MultiVerticesSource<'v>
+    {
+        if !self.has_depth_buffer() && (draw_parameters.depth.test.requires_depth_buffer() ||
+                        draw_parameters.depth.write)
+        {
+            return Err(DrawError::NoDepthBuffer);
+        }
+
+        if let Some(viewport) = draw_parameters.viewport {
+            if viewport.width > self.context.capabilities().max_viewport_dims.0
+                    as u32
+            {
+                return Err(DrawError::ViewportTooLarge);
+            }
+            if viewport.height > self.context.capabilities().max_viewport_dims.1
+                    as u32
+            {
+                return Err(DrawError::ViewportTooLarge);
+            }
+        }
+
+        ops::draw(&self.context, Some(&self.attachments), vb,
+                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions(), true)
+    }
+
+    #[inline]
+    fn draw_command_list(&mut self, list: &CommandList) -> Result<(), DrawError> {
+        ops::replay(&self.context, Some(&self.attachments), list.get_commands(),
+                    self.get_dimensions(), self.has_depth_buffer())
+    }
+
+    #[inline]
+    fn blit_color<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
+                     filter: uniforms::MagnifySamplerFilter) where S: Surface
+    {
+        target.blit_from_external_framebuffer(self, source_rect, target_rect, filter)
+    }
+
+    #[inline]
+    fn blit_buffers<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
+                       filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                       -> Result<(), BlitError> where S: Surface
+    {
+        target.blit_buffers_from_external_framebuffer(self, source_rect, target_rect, filter, mask)
+    }
+
+    #[inline]
+    fn blit_buffers_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
+                               filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                               -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, None, self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_simple_framebuffer(&self, source: &framebuffer::SimpleFrameBuffer,
+                                            source_rect: &Rect, target_rect: &BlitTarget,
+                                            filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                                            -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_multioutput_framebuffer(&self,
+                                                 source: &framebuffer::MultiOutputFrameBuffer,
+                                                 source_rect: &Rect, target_rect: &BlitTarget,
+                                                 filter: uniforms::MagnifySamplerFilter,
+                                                 mask: BlitMask)
+                                                 -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_external_framebuffer(&self, source: &ExternalFramebuffer,
+                                              source_rect: &Rect, target_rect: &BlitTarget,
+                                              filter: uniforms::MagnifySamplerFilter,
+                                              mask: BlitMask)
+                                              -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+}
+
+impl CaptureSurface for ExternalFramebuffer {
+    #[inline]
+    fn capture(&self) -> Result<RawImage2d<'static, u8>, CaptureError> {
+        ops::capture(&self.context, Some(&self.attachments), self.attachments.get_dimensions())
+    }
+}
+
+impl FboAttachments for ExternalFramebuffer {
+    #[inline]
+    fn get_attachments(&self) -> Option<&fbo::ValidatedAttachments> {
+        Some(&self.attachments)
+    }
+}
@@ -54,6 +54,14 @@ Some restrictions apply when you use framebuffers:
    framebuffer that you are using. This is not enforced by glium as it depends on your shader's
    source code.
 
+# Lifetime of the attachments
+
+Framebuffers borrow their attachments, therefore a texture or a render buffer can't be destroyed
+while a framebuffer uses it. The framebuffer objects that glium creates are kept in a cache, and
+the ones that contain a texture or a render buffer are destroyed at the same time as it, including
+in the contexts that share objects with its context. Textures, render buffers and framebuffers
+keep the context alive, so the `Display` can be dropped before them.
+
 # Empty framebuffers
 
 Modern OpenGL implementations support empty framebuffers. This is handled by glium with the
@@ -61,6 +69,11 @@ Modern OpenGL implementations support empty framebuffers. This is handled by gli
 
 You can check whether they are supported by calling `EmptyFrameBuffer::is_supported(&display)`.
 
+# External framebuffers
+
+If a framebuffer object has been created by some other code, for example by a VR runtime, you
+can draw on it by wrapping its id in an `ExternalFramebuffer`.
+
 # Layered framebuffers
 
 Not yet supported
@@ -71,6 +84,7 @@ use smallvec::SmallVec;
 
 use texture::Texture2d;
 use texture::TextureAnyImage;
+use texture::RawImage2d;
 use TextureExt;
 
 use backend::Facade;
@@ -82,24 +96,31 @@ use version::Api;
 use FboAttachments;
 use Rect;
 use BlitTarget;
+use BlitMask;
+use BlitError;
 use ContextExt;
 use ToGlEnum;
 use ops;
 use uniforms;
 
-use {Program, Surface};
+use {CaptureError, CaptureSurface, CommandList, Program, Surface};
+use CommandListExt;
 use DrawError;
 
-use {fbo, gl};
+use fbo;
 
 pub use self::default_fb::{DefaultFramebufferAttachment, DefaultFramebuffer};
+pub use self::external::ExternalFramebuffer;
 pub use self::render_buffer::{RenderBuffer, RenderBufferAny, DepthRenderBuffer};
 pub use self::render_buffer::{StencilRenderBuffer, DepthStencilRenderBuffer};
 pub use self::render_buffer::CreationError as RenderBufferCreationError;
 pub use fbo::is_dimensions_mismatch_supported;
 pub use fbo::ValidationError;
+pub use ops::ReadError;
+pub use fbo::AttachmentPoint;
 
 mod default_fb;
+mod external;
 mod render_buffer;
 
 /// A framebuffer which has only one color attachment.
@@ -210,6 +231,7 @@ impl<'a> SimpleFrameBuffer<'a> {
             }
         });
 
+        try!(attachments.check_context(facade.get_context()));
         let attachments = try!(attachments.validate(facade));
 
         Ok(SimpleFrameBuffer {
@@ -217,6 +239,56 @@ impl<'a> SimpleFrameBuffer<'a> {
             attachments: attachments,
         })
     }
+
+    /// Same as `draw`, but doesn't return a `FeedbackLoop` error if a texture sampled by the
+    /// program is attached to this framebuffer.
+    ///
+    /// # Safety
+    ///
+    /// Reading a texture while rendering to it produces undefined values. You must make sure
+    /// that each fragment only reads the texels that it writes, and call
+    /// `Context::texture_barrier` between two draw commands if the second one reads what the
+    /// first one has written.
+    #[inline]
+    pub unsafe fn draw_with_feedback_loops<'b, 'v, V, I, U>(&mut self, vb: V, ib: I,
+                                                            program: &::Program, uniforms: &U,
+                                                            draw_parameters: &::DrawParameters)
+                                                            -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
+        V: ::vertex::MultiVerticesSource<'v>
+    {
+        self.draw_impl(vb, ib, program, uniforms, draw_parameters, false)
+    }
+
+    fn draw_impl<'b, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
+                                  uniforms: &U, draw_parameters: &::DrawParameters,
+                                  check_feedback_loops: bool) -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
+        V: ::vertex::MultiVerticesSource<'v>
+    {
+        if !self.has_depth_buffer() && (draw_parameters.depth.test.requires_depth_buffer() ||
+                        draw_parameters.depth.write)
+        {
+            return Err(DrawError::NoDepthBuffer);
+        }
+
+        if let Some(viewport) = draw_parameters.viewport {
+            if viewport.width > self.context.capabilities().max_viewport_dims.0
+                    as u32
+            {
+                return Err(DrawError::ViewportTooLarge);
+            }
+            if viewport.height > self.context.capabilities().max_viewport_dims.1
+                    as u32
+            {
+                return Err(DrawError::ViewportTooLarge);
+            }
+        }
+
+        ops::draw(&self.context, Some(&self.attachments), vb,
+                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions(),
+                  check_feedback_loops)
+    }
 }
 
 impl<'a> Surface for SimpleFrameBuffer<'a> {
@@ -227,6 +299,27 @@ impl<'a> Surface for SimpleFrameBuffer<'a> {
         ops::clear(&self.context, Some(&self.attachments), rect, color, color_srgb, depth, stencil);
     }
 
+    #[inline]
+    fn clear_color_integer(&mut self, attachment_index: u32, color: [i32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.attachments), None, attachment_index,
+                                ops::ClearBufferData::SignedInt(color));
+    }
+
+    #[inline]
+    fn clear_color_unsigned(&mut self, attachment_index: u32, color: [u32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.attachments), None, attachment_index,
+                                ops::ClearBufferData::UnsignedInt(color));
+    }
+
+    #[inline]
+    fn copy_to_texture(&self, texture: &Texture2d, source_rect: &Rect, dest_offset: (u32, u32),
+                       level: u32) -> Result<(), ReadError>
+    {
+        let mut ctxt = self.context.make_current();
+        ops::copy_to_texture(&mut ctxt, ops::Source::Framebuffer(&self.attachments), source_rect,
+                             texture, dest_offset, level)
+    }
+
     #[inline]
     fn get_dimensions(&self) -> (u32, u32) {
         self.attachments.get_dimensions()
@@ -242,32 +335,19 @@ impl<'a> Surface for SimpleFrameBuffer<'a> {
         self.attachments.get_stencil_buffer_bits()
     }
 
+    #[inline]
     fn draw<'b, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
         uniforms: &U, draw_parameters: &::DrawParameters) -> Result<(), DrawError>
         where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
         V: ::vertex::MultiVerticesSource<'v>
     {
-        if !self.has_depth_buffer() && (draw_parameters.depth.test.requires_depth_buffer() ||
-                        draw_parameters.depth.write)
-        {
-            return Err(DrawError::NoDepthBuffer);
-        }
-
-        if let Some(viewport) = draw_parameters.viewport {
-            if viewport.width > self.context.capabilities().max_viewport_dims.0
-                    as u32
-            {
-                return Err(DrawError::ViewportTooLarge);
-            }
-            if viewport.height > self.context.capabilities().max_viewport_dims.1
-                    as u32
-            {
-                return Err(DrawError::ViewportTooLarge);
-            }
-        }
+        self.draw_impl(vb, ib, program, uniforms, draw_parameters, true)
+    }
 
-        ops::draw(&self.context, Some(&self.attachments), vb,
-                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions())
+    #[inline]
+    fn draw_command_list(&mut self, list: &CommandList) -> Result<(), DrawError> {
+        ops::replay(&self.context, Some(&self.attachments), list.get_commands(),
+                    self.get_dimensions(), self.has_depth_buffer())
     }
 
     #[inline]
@@ -278,29 +358,59 @@ impl<'a> Surface for SimpleFrameBuffer<'a> {
     }
 
     #[inline]
-    fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
-                       filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
+                       filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                       -> Result<(), BlitError> where S: Surface
+    {
+        target.blit_buffers_from_simple_framebuffer(self, source_rect, target_rect, filter, mask)
+    }
+
+    #[inline]
+    fn blit_buffers_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
+                               filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                               -> Result<(), BlitError>
     {
         ops::blit(&self.context, None, self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_simple_framebuffer(&self, source: &SimpleFrameBuffer,
+                                            source_rect: &Rect, target_rect: &BlitTarget,
+                                            filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                                            -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_simple_framebuffer(&self, source: &SimpleFrameBuffer,
-                                    source_rect: &Rect, target_rect: &BlitTarget,
-                                    filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_multioutput_framebuffer(&self, source: &MultiOutputFrameBuffer,
+                                                 source_rect: &Rect, target_rect: &BlitTarget,
+                                                 filter: uniforms::MagnifySamplerFilter,
+                                                 mask: BlitMask)
+                                                 -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_multioutput_framebuffer(&self, source: &MultiOutputFrameBuffer,
-                                         source_rect: &Rect, target_rect: &BlitTarget,
-                                         filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_external_framebuffer(&self, source: &ExternalFramebuffer,
+                                              source_rect: &Rect, target_rect: &BlitTarget,
+                                              filter: uniforms::MagnifySamplerFilter,
+                                              mask: BlitMask)
+                                              -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+}
+
+impl<'a> CaptureSurface for SimpleFrameBuffer<'a> {
+    #[inline]
+    fn capture(&self) -> Result<RawImage2d<'static, u8>, CaptureError> {
+        ops::capture(&self.context, Some(&self.attachments), self.attachments.get_dimensions())
     }
 }
 
@@ -387,7 +497,7 @@ impl<'a> MultiOutputFrameBuffer<'a> {
             DepthStencilAttachment::RenderBuffer(buffer) => fbo::RegularAttachment::RenderBuffer(buffer),
         });*/       // TODO:
 
-        let example_attachments = try!(fbo::FramebufferAttachments::Regular(fbo::FramebufferSpecificAttachments {
+        let example_attachments = fbo::FramebufferAttachments::Regular(fbo::FramebufferSpecificAttachments {
             colors: example_color,
             depth_stencil: if let (Some(depth), Some(stencil)) = (depth, stencil) {
                 fbo::DepthStencilAttachments::DepthAndStencilAttachments(depth, stencil)
@@ -400,7 +510,10 @@ impl<'a> MultiOutputFrameBuffer<'a> {
             } else {
                 fbo::DepthStencilAttachments::None
             }
-        }).validate(facade));
+        });
+
+        try!(example_attachments.check_context(facade.get_context()));
+        let example_attachments = try!(example_attachments.validate(facade));
 
         Ok(MultiOutputFrameBuffer {
             context: facade.get_context().clone(),
@@ -432,6 +545,53 @@ impl<'a> MultiOutputFrameBuffer<'a> {
             },
         }).validate(&self.context).unwrap()
     }
+
+    /// Same as `draw`, but doesn't return a `FeedbackLoop` error if a texture sampled by the
+    /// program is attached to this framebuffer.
+    ///
+    /// # Safety
+    ///
+    /// See `SimpleFrameBuffer::draw_with_feedback_loops`.
+    #[inline]
+    pub unsafe fn draw_with_feedback_loops<'i, 'v, V, I, U>(&mut self, vb: V, ib: I,
+                                                            program: &::Program, uniforms: &U,
+                                                            draw_parameters: &::DrawParameters)
+                                                            -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'i>>,
+        U: ::uniforms::Uniforms, V: ::vertex::MultiVerticesSource<'v>
+    {
+        self.draw_impl(vb, ib, program, uniforms, draw_parameters, false)
+    }
+
+    fn draw_impl<'i, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
+                                  uniforms: &U, draw_parameters: &::DrawParameters,
+                                  check_feedback_loops: bool) -> Result<(), DrawError>
+        where I: Into<::index::IndicesSource<'i>>,
+        U: ::uniforms::Uniforms, V: ::vertex::MultiVerticesSource<'v>
+    {
+        if !self.has_depth_buffer() && (draw_parameters.depth.test.requires_depth_buffer() ||
+                draw_parameters.depth.write)
+        {
+            return Err(DrawError::NoDepthBuffer);
+        }
+
+        if let Some(viewport) = draw_parameters.viewport {
+            if viewport.width > self.context.capabilities().max_viewport_dims.0
+                    as u32
+            {
+                return Err(DrawError::ViewportTooLarge);
+            }
+            if viewport.height > self.context.capabilities().max_viewport_dims.1
+                    as u32
+            {
+                return Err(DrawError::ViewportTooLarge);
+            }
+        }
+
+        ops::draw(&self.context, Some(&self.build_attachments(program)), vb,
+                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions(),
+                  check_feedback_loops)
+    }
 }
 
 impl<'a> Surface for MultiOutputFrameBuffer<'a> {
@@ -443,6 +603,27 @@ impl<'a> Surface for MultiOutputFrameBuffer<'a> {
                    color, color_srgb, depth, stencil);
     }
 
+    #[inline]
+    fn clear_color_integer(&mut self, attachment_index: u32, color: [i32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.example_attachments), None, attachment_index,
+                                ops::ClearBufferData::SignedInt(color));
+    }
+
+    #[inline]
+    fn clear_color_unsigned(&mut self, attachment_index: u32, color: [u32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.example_attachments), None, attachment_index,
+                                ops::ClearBufferData::UnsignedInt(color));
+    }
+
+    #[inline]
+    fn copy_to_texture(&self, texture: &Texture2d, source_rect: &Rect, dest_offset: (u32, u32),
+                       level: u32) -> Result<(), ReadError>
+    {
+        let mut ctxt = self.context.make_current();
+        ops::copy_to_texture(&mut ctxt, ops::Source::Framebuffer(&self.example_attachments),
+                             source_rect, texture, dest_offset, level)
+    }
+
     #[inline]
     fn get_dimensions(&self) -> (u32, u32) {
         self.example_attachments.get_dimensions()
@@ -458,32 +639,36 @@ impl<'a> Surface for MultiOutputFrameBuffer<'a> {
         self.example_attachments.get_stencil_buffer_bits()
     }
 
+    #[inline]
     fn draw<'i, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
         uniforms: &U, draw_parameters: &::DrawParameters) -> Result<(), DrawError>
         where I: Into<::index::IndicesSource<'i>>,
         U: ::uniforms::Uniforms, V: ::vertex::MultiVerticesSource<'v>
     {
-        if !self.has_depth_buffer() && (draw_parameters.depth.test.requires_depth_buffer() ||
-                draw_parameters.depth.write)
-        {
-            return Err(DrawError::NoDepthBuffer);
-        }
+        self.draw_impl(vb, ib, program, uniforms, draw_parameters, true)
+    }
 
-        if let Some(viewport) = draw_parameters.viewport {
-            if viewport.width > self.context.capabilities().max_viewport_dims.0
-                    as u32
-            {
-                return Err(DrawError::ViewportTooLarge);
-            }
-            if viewport.height > self.context.capabilities().max_viewport_dims.1
-                    as u32
-            {
-                return Err(DrawError::ViewportTooLarge);
-            }
+    fn draw_command_list(&mut self, list: &CommandList) -> Result<(), DrawError> {
+        // the attachments depend on the program, so the commands are executed by groups of
+        // commands that use the same program
+        let commands = list.get_commands();
+        let mut start = 0;
+
+        while start < commands.len() {
+            let program = commands[start].program;
+            let end = commands[start ..].iter()
+                                        .position(|c| c.program as *const Program !=
+                                                      program as *const Program)
+                                        .map(|p| start + p).unwrap_or(commands.len());
+
+            try!(ops::replay(&self.context, Some(&self.build_attachments(program)),
+                             &commands[start .. end], self.get_dimensions(),
+                             self.has_depth_buffer()));
+
+            start = end;
         }
 
-        ops::draw(&self.context, Some(&self.build_attachments(program)), vb,
-                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions())
+        Ok(())
     }
 
     #[inline]
@@ -494,29 +679,60 @@ impl<'a> Surface for MultiOutputFrameBuffer<'a> {
     }
 
     #[inline]
-    fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
-                       filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
+                       filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                       -> Result<(), BlitError> where S: Surface
+    {
+        target.blit_buffers_from_multioutput_framebuffer(self, source_rect, target_rect, filter, mask)
+    }
+
+    #[inline]
+    fn blit_buffers_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
+                               filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                               -> Result<(), BlitError>
     {
         ops::blit(&self.context, None, self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_simple_framebuffer(&self, source: &SimpleFrameBuffer,
-                                    source_rect: &Rect, target_rect: &BlitTarget,
-                                    filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_simple_framebuffer(&self, source: &SimpleFrameBuffer,
+                                            source_rect: &Rect, target_rect: &BlitTarget,
+                                            filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                                            -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_multioutput_framebuffer(&self, source: &MultiOutputFrameBuffer,
-                                         source_rect: &Rect, target_rect: &BlitTarget,
-                                         filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_multioutput_framebuffer(&self, source: &MultiOutputFrameBuffer,
+                                                 source_rect: &Rect, target_rect: &BlitTarget,
+                                                 filter: uniforms::MagnifySamplerFilter,
+                                                 mask: BlitMask)
+                                                 -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_external_framebuffer(&self, source: &ExternalFramebuffer,
+                                              source_rect: &Rect, target_rect: &BlitTarget,
+                                              filter: uniforms::MagnifySamplerFilter,
+                                              mask: BlitMask)
+                                              -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+}
+
+impl<'a> CaptureSurface for MultiOutputFrameBuffer<'a> {
+    #[inline]
+    fn capture(&self) -> Result<RawImage2d<'static, u8>, CaptureError> {
+        ops::capture(&self.context, Some(&self.example_attachments),
+                     self.example_attachments.get_dimensions())
     }
 }
 
@@ -612,6 +828,25 @@ impl Surface for EmptyFrameBuffer {
         ops::clear(&self.context, Some(&self.attachments), rect, color, color_srgb, depth, stencil);
     }
 
+    #[inline]
+    fn clear_color_integer(&mut self, attachment_index: u32, color: [i32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.attachments), None, attachment_index,
+                                ops::ClearBufferData::SignedInt(color));
+    }
+
+    #[inline]
+    fn clear_color_unsigned(&mut self, attachment_index: u32, color: [u32; 4]) {
+        ops::clear_color_buffer(&self.context, Some(&self.attachments), None, attachment_index,
+                                ops::ClearBufferData::UnsignedInt(color));
+    }
+
+    #[inline]
+    fn copy_to_texture(&self, _: &Texture2d, _: &Rect, _: (u32, u32), _: u32)
+                       -> Result<(), ReadError>
+    {
+        Err(ReadError::IncompatibleDestination)
+    }
+
     #[inline]
     fn get_dimensions(&self) -> (u32, u32) {
         self.attachments.get_dimensions()
@@ -652,7 +887,13 @@ impl Surface for EmptyFrameBuffer {
         }
 
         ops::draw(&self.context, Some(&self.attachments), vb,
-                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions())
+                  ib.into(), program, uniforms, draw_parameters, self.get_dimensions(), true)
+    }
+
+    #[inline]
+    fn draw_command_list(&mut self, list: &CommandList) -> Result<(), DrawError> {
+        ops::replay(&self.context, Some(&self.attachments), list.get_commands(),
+                    self.get_dimensions(), self.has_depth_buffer())
     }
 
     #[inline]
@@ -663,29 +904,52 @@ impl Surface for EmptyFrameBuffer {
     }
 
     #[inline]
-    fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
-                       filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers<S>(&self, _: &Rect, _: &S, _: &BlitTarget, _: uniforms::MagnifySamplerFilter,
+                       _: BlitMask) -> Result<(), BlitError> where S: Surface
+    {
+        // an empty framebuffer has no buffer to copy from
+        Err(BlitError::NoAttachment)
+    }
+
+    #[inline]
+    fn blit_buffers_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
+                               filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                               -> Result<(), BlitError>
     {
         ops::blit(&self.context, None, self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_simple_framebuffer(&self, source: &SimpleFrameBuffer,
+                                            source_rect: &Rect, target_rect: &BlitTarget,
+                                            filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                                            -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_simple_framebuffer(&self, source: &SimpleFrameBuffer,
-                                    source_rect: &Rect, target_rect: &BlitTarget,
-                                    filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_multioutput_framebuffer(&self, source: &MultiOutputFrameBuffer,
+                                                 source_rect: &Rect, target_rect: &BlitTarget,
+                                                 filter: uniforms::MagnifySamplerFilter,
+                                                 mask: BlitMask)
+                                                 -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_multioutput_framebuffer(&self, source: &MultiOutputFrameBuffer,
-                                         source_rect: &Rect, target_rect: &BlitTarget,
-                                         filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_external_framebuffer(&self, source: &ExternalFramebuffer,
+                                              source_rect: &Rect, target_rect: &BlitTarget,
+                                              filter: uniforms::MagnifySamplerFilter,
+                                              mask: BlitMask)
+                                              -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 }
 
@@ -7,8 +7,11 @@ You should prefer render buffers over textures when you know that you don't need
 the data of the render buffer.
 
 */
+use std::error::Error;
+use std::fmt;
 use std::rc::Rc;
 use std::ops::{Deref, DerefMut};
+use std::borrow::Cow;
 use std::mem;
 
 use framebuffer::{ColorAttachment, ToColorAttachment};
@@ -16,15 +19,22 @@ use framebuffer::{DepthAttachment, ToDepthAttachment};
 use framebuffer::{StencilAttachment, ToStencilAttachment};
 use framebuffer::{DepthStencilAttachment, ToDepthStencilAttachment};
 use texture::{UncompressedFloatFormat, DepthFormat, StencilFormat, DepthStencilFormat};
+use texture::Texture2dDataSink;
 
 use image_format;
 
 use gl;
 use GlObject;
+use fbo;
 use fbo::FramebuffersContainer;
 use backend::Facade;
 use context::Context;
+use context::ObjectKind;
 use ContextExt;
+use CapabilitiesSource;
+use ops;
+use ops::ReadError;
+use Rect;
 use version::Version;
 use version::Api;
 
@@ -33,6 +43,26 @@ use version::Api;
 pub enum CreationError {
     /// The requested format is not supported.
     FormatNotSupported,
+
+    /// Multisampling is not supported by the backend, or the requested number of samples is not
+    /// supported for this format.
+    MultisamplingNotSupported,
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::FormatNotSupported => "The requested format is not supported",
+            &CreationError::MultisamplingNotSupported => "The requested number of samples is \
+                                                          not supported",
+        }
+    }
 }
 
 impl From<image_format::FormatNotSupportedError> for CreationError {
@@ -53,13 +83,89 @@ impl RenderBuffer {
     pub fn new<F>(facade: &F, format: UncompressedFloatFormat, width: u32, height: u32)
                   -> Result<RenderBuffer, CreationError> where F: Facade
     {
-        let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::UncompressedFloat(format));
-        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), format, image_format::RequestType::Renderbuffer));
+        let format = image_format::TextureFormat::UncompressedFloat(format);
+        let request = image_format::TextureFormatRequest::Specific(format);
+        let glenum = try!(image_format::format_request_to_glenum(&facade.get_context(), request, image_format::RequestType::Renderbuffer));
 
         Ok(RenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, None)
         })
     }
+
+    /// Builds a new multisampled render buffer.
+    pub fn new_multisample<F>(facade: &F, format: UncompressedFloatFormat, width: u32,
+                              height: u32, samples: u32)
+                              -> Result<RenderBuffer, CreationError> where F: Facade
+    {
+        let format = image_format::TextureFormat::UncompressedFloat(format);
+        let glenum = try!(multisample_format(facade, format, samples));
+
+        Ok(RenderBuffer {
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, Some(samples))
+        })
+    }
+
+    /// Returns true if the backend supports creating render buffers of this format.
+    ///
+    /// The result is determined from the version and the extensions of the backend. If the
+    /// backend supports `glGetInternalformativ` with `GL_INTERNALFORMAT_SUPPORTED` (OpenGL 4.3 or
+    /// `GL_ARB_internalformat_query2`), the implementation is queried as well.
+    pub fn is_format_supported<F>(facade: &F, format: UncompressedFloatFormat) -> bool
+                                  where F: Facade
+    {
+        let context = facade.get_context();
+
+        let format = image_format::TextureFormat::UncompressedFloat(format);
+        if !format.is_supported_for_renderbuffers(context) {
+            return false;
+        }
+
+        let format = image_format::TextureFormatRequest::Specific(format);
+        let format = match image_format::format_request_to_glenum(context, format,
+                                                                  image_format::RequestType::Renderbuffer)
+        {
+            Ok(f) => f,
+            Err(_) => return false,
+        };
+
+        if context.get_version() >= &Version(Api::Gl, 4, 3) ||
+           context.get_extensions().gl_arb_internalformat_query2
+        {
+            let ctxt = context.make_current();
+            let mut supported = 0;
+            unsafe {
+                ctxt.gl.GetInternalformativ(gl::RENDERBUFFER, format,
+                                            gl::INTERNALFORMAT_SUPPORTED, 1, &mut supported);
+            }
+            supported == gl::TRUE as gl::types::GLint
+
+        } else {
+            true
+        }
+    }
+
+    /// Reads the content of the render buffer.
+    ///
+    /// Returns `ReadError::MultisampleSource` if the render buffer is multisampled. Blit it to a
+    /// regular render buffer or texture first.
+    pub fn read<T>(&self) -> Result<T, ReadError> where T: Texture2dDataSink<(u8, u8, u8, u8)> {
+        if self.buffer.samples.is_some() {
+            return Err(ReadError::MultisampleSource);
+        }
+
+        let rect = Rect {
+            left: 0,
+            bottom: 0,
+            width: self.buffer.width,
+            height: self.buffer.height,
+        };
+
+        let mut ctxt = self.buffer.context.make_current();
+        let mut data = Vec::with_capacity(0);
+        try!(ops::read(&mut ctxt, &fbo::RegularAttachment::RenderBuffer(&self.buffer), &rect,
+                       &mut data, false));
+        Ok(T::from_raw(Cow::Owned(data), rect.width, rect.height))
+    }
 }
 
 impl<'a> ToColorAttachment<'a> for &'a RenderBuffer {
@@ -106,11 +212,24 @@ impl DepthRenderBuffer {
     pub fn new<F>(facade: &F, format: DepthFormat, width: u32, height: u32)
                   -> Result<DepthRenderBuffer, CreationError> where F: Facade
     {
-        let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::DepthFormat(format));
-        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), format, image_format::RequestType::Renderbuffer));
+        let format = image_format::TextureFormat::DepthFormat(format);
+        let request = image_format::TextureFormatRequest::Specific(format);
+        let glenum = try!(image_format::format_request_to_glenum(&facade.get_context(), request, image_format::RequestType::Renderbuffer));
+
+        Ok(DepthRenderBuffer {
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, None)
+        })
+    }
+
+    /// Builds a new multisampled render buffer.
+    pub fn new_multisample<F>(facade: &F, format: DepthFormat, width: u32, height: u32, samples: u32)
+                              -> Result<DepthRenderBuffer, CreationError> where F: Facade
+    {
+        let format = image_format::TextureFormat::DepthFormat(format);
+        let glenum = try!(multisample_format(facade, format, samples));
 
         Ok(DepthRenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, Some(samples))
         })
     }
 }
@@ -158,11 +277,24 @@ impl StencilRenderBuffer {
     pub fn new<F>(facade: &F, format: StencilFormat, width: u32, height: u32)
                   -> Result<StencilRenderBuffer, CreationError> where F: Facade
     {
-        let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::StencilFormat(format));
-        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), format, image_format::RequestType::Renderbuffer));
+        let format = image_format::TextureFormat::StencilFormat(format);
+        let request = image_format::TextureFormatRequest::Specific(format);
+        let glenum = try!(image_format::format_request_to_glenum(&facade.get_context(), request, image_format::RequestType::Renderbuffer));
+
+        Ok(StencilRenderBuffer {
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, None)
+        })
+    }
+
+    /// Builds a new multisampled render buffer.
+    pub fn new_multisample<F>(facade: &F, format: StencilFormat, width: u32, height: u32, samples: u32)
+                              -> Result<StencilRenderBuffer, CreationError> where F: Facade
+    {
+        let format = image_format::TextureFormat::StencilFormat(format);
+        let glenum = try!(multisample_format(facade, format, samples));
 
         Ok(StencilRenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, Some(samples))
         })
     }
 }
@@ -211,11 +343,24 @@ impl DepthStencilRenderBuffer {
     pub fn new<F>(facade: &F, format: DepthStencilFormat, width: u32, height: u32)
                   -> Result<DepthStencilRenderBuffer, CreationError> where F: Facade
     {
-        let format = image_format::TextureFormatRequest::Specific(image_format::TextureFormat::DepthStencilFormat(format));
-        let format = try!(image_format::format_request_to_glenum(&facade.get_context(), format, image_format::RequestType::Renderbuffer));
+        let format = image_format::TextureFormat::DepthStencilFormat(format);
+        let request = image_format::TextureFormatRequest::Specific(format);
+        let glenum = try!(image_format::format_request_to_glenum(&facade.get_context(), request, image_format::RequestType::Renderbuffer));
+
+        Ok(DepthStencilRenderBuffer {
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, None)
+        })
+    }
+
+    /// Builds a new multisampled render buffer.
+    pub fn new_multisample<F>(facade: &F, format: DepthStencilFormat, width: u32, height: u32, samples: u32)
+                              -> Result<DepthStencilRenderBuffer, CreationError> where F: Facade
+    {
+        let format = image_format::TextureFormat::DepthStencilFormat(format);
+        let glenum = try!(multisample_format(facade, format, samples));
 
         Ok(DepthStencilRenderBuffer {
-            buffer: RenderBufferAny::new(facade, format, width, height, None)
+            buffer: RenderBufferAny::new(facade, format, glenum, width, height, Some(samples))
         })
     }
 }
@@ -252,6 +397,38 @@ impl GlObject for DepthStencilRenderBuffer {
     }
 }
 
+/// Checks that a multisampled render buffer of this format can be created, and returns the
+/// corresponding `GLenum`.
+fn multisample_format<F>(facade: &F, format: image_format::TextureFormat, samples: u32)
+                         -> Result<gl::types::GLenum, CreationError> where F: Facade
+{
+    let context = facade.get_context();
+    let version = context.get_version();
+    let extensions = context.get_extensions();
+
+    if !(version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+         extensions.gl_arb_direct_state_access || extensions.gl_apple_framebuffer_multisample ||
+         extensions.gl_angle_framebuffer_multisample ||
+         extensions.gl_ext_multisampled_render_to_texture ||
+         extensions.gl_nv_framebuffer_multisample ||
+         (extensions.gl_ext_framebuffer_object && extensions.gl_ext_framebuffer_multisample))
+    {
+        return Err(CreationError::MultisamplingNotSupported);
+    }
+
+    if let Some(infos) = context.get_capabilities().internal_formats_renderbuffers.get(&format) {
+        if let Some(ref multisamples) = infos.multisamples {
+            if !multisamples.iter().any(|&s| s as u32 == samples) {
+                return Err(CreationError::MultisamplingNotSupported);
+            }
+        }
+    }
+
+    let format = image_format::TextureFormatRequest::Specific(format);
+    Ok(try!(image_format::format_request_to_glenum(context, format,
+                                                   image_format::RequestType::Renderbuffer)))
+}
+
 /// A RenderBuffer of indeterminate type.
 pub struct RenderBufferAny {
     context: Rc<Context>,
@@ -259,12 +436,14 @@ pub struct RenderBufferAny {
     width: u32,
     height: u32,
     samples: Option<u32>,
+    format: image_format::TextureFormat,
 }
 
 impl RenderBufferAny {
     /// Builds a new render buffer.
-    fn new<F>(facade: &F, format: gl::types::GLenum, width: u32, height: u32, samples: Option<u32>)
-              -> RenderBufferAny where F: Facade
+    fn new<F>(facade: &F, format: image_format::TextureFormat, gl_format: gl::types::GLenum,
+              width: u32, height: u32, samples: Option<u32>) -> RenderBufferAny
+              where F: Facade
     {
         unsafe {
             // TODO: check that dimensions don't exceed GL_MAX_RENDERBUFFER_SIZE
@@ -272,16 +451,15 @@ impl RenderBufferAny {
             let mut ctxt = facade.get_context().make_current();
             let mut id = mem::uninitialized();
 
-            if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-               ctxt.extensions.gl_arb_direct_state_access
-            {
+            if ctxt.capabilities.direct_state_access {
                 ctxt.gl.CreateRenderbuffers(1, &mut id);
                 if let Some(samples) = samples {
                     ctxt.gl.NamedRenderbufferStorageMultisample(id, samples as gl::types::GLsizei,
-                                                                format, width as gl::types::GLsizei,
+                                                                gl_format,
+                                                                width as gl::types::GLsizei,
                                                                 height as gl::types::GLsizei);
                 } else {
-                    ctxt.gl.NamedRenderbufferStorage(id, format, width as gl::types::GLsizei,
+                    ctxt.gl.NamedRenderbufferStorage(id, gl_format, width as gl::types::GLsizei,
                                                      height as gl::types::GLsizei);
                 }
 
@@ -303,35 +481,35 @@ impl RenderBufferAny {
                 {
                     ctxt.gl.RenderbufferStorageMultisample(gl::RENDERBUFFER, 
                                                            samples as gl::types::GLsizei,
-                                                           format,
+                                                           gl_format,
                                                            width as gl::types::GLsizei,
                                                            height as gl::types::GLsizei);
 
                 } else if ctxt.extensions.gl_apple_framebuffer_multisample {
                     ctxt.gl.RenderbufferStorageMultisampleAPPLE(gl::RENDERBUFFER,
                                                                 samples as gl::types::GLsizei,
-                                                                format,
+                                                                gl_format,
                                                                 width as gl::types::GLsizei,
                                                                 height as gl::types::GLsizei);
 
                 } else if ctxt.extensions.gl_angle_framebuffer_multisample {
                     ctxt.gl.RenderbufferStorageMultisampleANGLE(gl::RENDERBUFFER,
                                                                 samples as gl::types::GLsizei,
-                                                                format,
+                                                                gl_format,
                                                                 width as gl::types::GLsizei,
                                                                 height as gl::types::GLsizei);
 
                 } else if ctxt.extensions.gl_ext_multisampled_render_to_texture {
                     ctxt.gl.RenderbufferStorageMultisampleEXT(gl::RENDERBUFFER,
                                                               samples as gl::types::GLsizei,
-                                                              format,
+                                                              gl_format,
                                                               width as gl::types::GLsizei,
                                                               height as gl::types::GLsizei);
 
                 } else if ctxt.extensions.gl_nv_framebuffer_multisample {
                     ctxt.gl.RenderbufferStorageMultisampleNV(gl::RENDERBUFFER,
                                                              samples as gl::types::GLsizei,
-                                                             format,
+                                                             gl_format,
                                                              width as gl::types::GLsizei,
                                                              height as gl::types::GLsizei);
 
@@ -345,7 +523,7 @@ impl RenderBufferAny {
                 ctxt.gl.GenRenderbuffers(1, &mut id);
                 ctxt.gl.BindRenderbuffer(gl::RENDERBUFFER, id);
                 ctxt.state.renderbuffer = id;
-                ctxt.gl.RenderbufferStorage(gl::RENDERBUFFER, format,
+                ctxt.gl.RenderbufferStorage(gl::RENDERBUFFER, gl_format,
                                             width as gl::types::GLsizei,
                                             height as gl::types::GLsizei);
 
@@ -359,7 +537,7 @@ impl RenderBufferAny {
                 let samples = samples.unwrap();
                 ctxt.gl.RenderbufferStorageMultisampleEXT(gl::RENDERBUFFER_EXT,
                                                           samples as gl::types::GLsizei,
-                                                          format,
+                                                          gl_format,
                                                           width as gl::types::GLsizei,
                                                           height as gl::types::GLsizei);
 
@@ -367,7 +545,7 @@ impl RenderBufferAny {
                 ctxt.gl.GenRenderbuffersEXT(1, &mut id);
                 ctxt.gl.BindRenderbufferEXT(gl::RENDERBUFFER_EXT, id);
                 ctxt.state.renderbuffer = id;
-                ctxt.gl.RenderbufferStorageEXT(gl::RENDERBUFFER_EXT, format,
+                ctxt.gl.RenderbufferStorageEXT(gl::RENDERBUFFER_EXT, gl_format,
                                                width as gl::types::GLsizei,
                                                height as gl::types::GLsizei);
 
@@ -375,12 +553,15 @@ impl RenderBufferAny {
                 unreachable!();
             }
 
+            ctxt.live_objects.add(ObjectKind::RenderBuffer, id as usize);
+
             RenderBufferAny {
                 context: facade.get_context().clone(),
                 id: id,
                 width: width,
                 height: height,
                 samples: samples,
+                format: format,
             }
         }
     }
@@ -398,6 +579,12 @@ impl RenderBufferAny {
         self.samples
     }
 
+    /// Returns the format of the render buffer.
+    #[inline]
+    pub fn get_format(&self) -> image_format::TextureFormat {
+        self.format
+    }
+
     /// Returns the context used to create this renderbuffer.
     #[inline]
     pub fn get_context(&self) -> &Rc<Context> {
@@ -408,7 +595,10 @@ impl RenderBufferAny {
 impl Drop for RenderBufferAny {
     fn drop(&mut self) {
         unsafe {
-            let mut ctxt = self.context.make_current();
+            let mut ctxt = match self.context.make_current_for_drop() {
+                Some(ctxt) => ctxt,
+                None => return,
+            };
 
             // removing FBOs which contain this buffer
             FramebuffersContainer::purge_renderbuffer(&mut ctxt, self.id);
@@ -432,7 +622,19 @@ impl Drop for RenderBufferAny {
             } else {
                 unreachable!();
             }
+
+            ctxt.live_objects.remove(ObjectKind::RenderBuffer, self.id as usize);
         }
+
+        // cleaning the caches of the contexts that share this renderbuffer
+        let id = self.id;
+        self.context.for_each_shared_context(|ctxt| {
+            FramebuffersContainer::purge_renderbuffer(ctxt, id);
+
+            if ctxt.state.renderbuffer == id {
+                ctxt.state.renderbuffer = 0;
+            }
+        });
     }
 }
 
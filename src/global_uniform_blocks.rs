@@ -0,0 +1,177 @@
+/*!
+
+Uniform buffers that are shared by all the programs of a context.
+
+Each name of block is associated to a binding point that is reserved for it, starting from the
+last binding point. The buffer is bound to this point once, when the block is set, and each
+program only needs to be told which binding point its block uses.
+
+*/
+use gl;
+
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::HashMap;
+
+use context::CommandContext;
+use program;
+use uniforms::GlobalUniformBlockError;
+use uniforms::LayoutMismatchError;
+use uniforms::UniformValue;
+use utils::bitsfield::Bitsfield;
+use version::Api;
+use version::Version;
+
+use BufferExt;
+use DrawError;
+use ProgramExt;
+
+/// List of the global uniform blocks of a context.
+pub struct GlobalUniformBlocks {
+    blocks: RefCell<HashMap<String, GlobalUniformBlock>>,
+}
+
+struct GlobalUniformBlock {
+    /// Binding point reserved for this name.
+    bind_point: gl::types::GLuint,
+
+    /// Id, offset and size of the buffer that has been bound, or `None` if the block has
+    /// been removed.
+    binding: Option<(gl::types::GLuint, usize, usize)>,
+
+    /// Checks the layout of a block of a program against the content of the buffer.
+    layout: fn(&program::UniformBlock) -> Result<(), LayoutMismatchError>,
+}
+
+impl GlobalUniformBlocks {
+    /// Builds an empty list.
+    #[inline]
+    pub fn new() -> GlobalUniformBlocks {
+        GlobalUniformBlocks {
+            blocks: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Binds the buffer to the binding point reserved for `name`.
+    pub fn set(&self, ctxt: &mut CommandContext, name: &str, value: UniformValue)
+               -> Result<(), GlobalUniformBlockError>
+    {
+        if !(ctxt.version >= &Version(Api::Gl, 3, 1) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+             ctxt.extensions.gl_arb_uniform_buffer_object)
+        {
+            return Err(GlobalUniformBlockError::NotSupported);
+        }
+
+        let (buffer, layout) = match value {
+            UniformValue::Block(buffer, layout) => (buffer, layout),
+            _ => return Err(GlobalUniformBlockError::NotABuffer),
+        };
+
+        let alignment = ctxt.capabilities.uniform_buffer_offset_alignment as usize;
+        if buffer.get_offset_bytes() % alignment != 0 {
+            return Err(GlobalUniformBlockError::OffsetNotAligned);
+        }
+
+        let mut blocks = self.blocks.borrow_mut();
+
+        let bind_point = match blocks.get(name) {
+            Some(block) => block.bind_point,
+            None => {
+                // the bitsfield used when drawing can't hold more than 256 binding points
+                let max = cmp::min(ctxt.capabilities.max_indexed_uniform_buffer, 256);
+                let max = cmp::max(max, 0) as usize;
+                if blocks.len() >= max {
+                    return Err(GlobalUniformBlockError::TooManyBlocks);
+                }
+                (max - 1 - blocks.len()) as gl::types::GLuint
+            },
+        };
+
+        buffer.prepare_and_bind_for_uniform(ctxt, bind_point);
+
+        blocks.insert(name.to_owned(), GlobalUniformBlock {
+            bind_point: bind_point,
+            binding: Some((buffer.get_buffer_id(), buffer.get_offset_bytes(), buffer.get_size())),
+            layout: layout,
+        });
+
+        Ok(())
+    }
+
+    /// Removes the buffer of a block. The binding point stays reserved for this name.
+    ///
+    /// Returns `false` if there was no block with this name.
+    pub fn remove(&self, name: &str) -> bool {
+        match self.blocks.borrow_mut().get_mut(name) {
+            Some(block) => {
+                let was_set = block.binding.is_some();
+                block.binding = None;
+                was_set
+            },
+            None => false,
+        }
+    }
+
+    /// Marks the binding points that are reserved for global blocks as used.
+    pub fn reserve_bind_points(&self, bind_points: &mut Bitsfield) {
+        for block in self.blocks.borrow().values() {
+            bind_points.set_used(block.bind_point as u16);
+        }
+    }
+
+    /// Tells the program to use the global blocks for the uniform blocks whose id is not
+    /// in `bound_blocks`.
+    ///
+    /// The binding of the program is cached, so this is cheap when the program has already been
+    /// used with the same blocks.
+    pub fn bind<P>(&self, ctxt: &mut CommandContext, program: &P, bound_blocks: &[i32])
+                   -> Result<(), DrawError> where P: ProgramExt
+    {
+        let blocks = self.blocks.borrow();
+        if blocks.is_empty() {
+            return Ok(());
+        }
+
+        for (name, block) in program.get_uniform_blocks().iter() {
+            if bound_blocks.contains(&block.id) {
+                continue;
+            }
+
+            let global = match blocks.get(name) {
+                Some(g) => g,
+                None => continue,
+            };
+
+            let binding = match global.binding {
+                Some(b) => b,
+                None => continue,
+            };
+
+            if let Err(e) = (global.layout)(block) {
+                return Err(DrawError::UniformBlockLayoutMismatch {
+                    name: name.clone(),
+                    err: e,
+                });
+            }
+
+            // the buffer is unbound when it is destroyed, and the state may have been
+            // reset by `exec_in_context`
+            let still_bound = match ctxt.state.indexed_uniform_buffer_bindings
+                                              .get(global.bind_point as usize)
+            {
+                Some(state) => state.buffer == binding.0 && state.offset as usize == binding.1 &&
+                               state.size as usize == binding.2,
+                None => false,
+            };
+
+            if !still_bound {
+                return Err(DrawError::GlobalUniformBlockUnbound { name: name.clone() });
+            }
+
+            program.set_uniform_block_binding(ctxt, block.id as gl::types::GLuint,
+                                              global.bind_point);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//! Half-precision floating-point numbers.
+//!
+//! This module is only available with the `half` feature.
+
+use std::fmt;
+
+use vertex;
+
+/// A 16-bits floating-point number, as defined by IEEE 754.
+///
+/// This type has the same representation as the `GL_HALF_FLOAT` type of OpenGL and can be used
+/// as a pixel value or as a vertex attribute. Arithmetic is not supported: convert the value
+/// to an `f32` with `to_f32` first.
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Default)]
+pub struct f16(u16);
+
+impl f16 {
+    /// Builds a half-precision number from its raw bits.
+    #[inline]
+    pub fn from_bits(bits: u16) -> f16 {
+        f16(bits)
+    }
+
+    /// Returns the raw bits of the number.
+    #[inline]
+    pub fn to_bits(&self) -> u16 {
+        self.0
+    }
+
+    /// Converts an `f32` to the nearest half-precision number.
+    ///
+    /// Values that are too large to be represented become infinite, and NaN stays NaN.
+    #[inline]
+    pub fn from_f32(value: f32) -> f16 {
+        f16(vertex::pack_f16(value))
+    }
+
+    /// Converts the number to an `f32`. This conversion is exact.
+    #[inline]
+    pub fn to_f32(&self) -> f32 {
+        vertex::unpack_f16(self.0)
+    }
+}
+
+impl From<f32> for f16 {
+    #[inline]
+    fn from(value: f32) -> f16 {
+        f16::from_f32(value)
+    }
+}
+
+impl From<f16> for f32 {
+    #[inline]
+    fn from(value: f16) -> f32 {
+        value.to_f32()
+    }
+}
+
+impl PartialEq for f16 {
+    #[inline]
+    fn eq(&self, other: &f16) -> bool {
+        self.to_f32() == other.to_f32()
+    }
+}
+
+impl fmt::Debug for f16 {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Debug::fmt(&self.to_f32(), formatter)
+    }
+}
+
+impl fmt::Display for f16 {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.to_f32(), formatter)
+    }
+}
@@ -9,10 +9,25 @@ use CapabilitiesSource;
 use ToGlEnum;
 use version::{Api, Version};
 
+use std::error::Error;
+use std::fmt;
+
 /// Error that is returned if the format is not supported by OpenGL.
 #[derive(Copy, Clone, Debug)]
 pub struct FormatNotSupportedError;
 
+impl fmt::Display for FormatNotSupportedError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for FormatNotSupportedError {
+    fn description(&self) -> &str {
+        "The format is not supported by the backend"
+    }
+}
+
 /// Texture format request.
 #[derive(Copy, Clone, Debug)]
 pub enum TextureFormatRequest {
@@ -389,12 +404,12 @@ impl UncompressedFloatFormat {
                     extensions.gl_ext_texture_snorm
             },
             &UncompressedFloatFormat::U16 => {
-                version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
-                    extensions.gl_arb_texture_rg
+                version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_texture_rg ||
+                    extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::I16 => {
-                version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 0) ||
-                    extensions.gl_ext_texture_snorm
+                version >= &Version(Api::Gl, 3, 2) || extensions.gl_ext_texture_snorm ||
+                    extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::U8U8 => {
                 version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
@@ -405,12 +420,12 @@ impl UncompressedFloatFormat {
                     extensions.gl_ext_texture_snorm
             },
             &UncompressedFloatFormat::U16U16 => {
-                version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
-                    extensions.gl_arb_texture_rg
+                version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_texture_rg ||
+                    extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::I16I16 => {
-                version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 0) ||
-                    extensions.gl_ext_texture_snorm
+                version >= &Version(Api::Gl, 3, 2) || extensions.gl_ext_texture_snorm ||
+                    extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::U3U3U2 => {
                 version >= &Version(Api::Gl, 1, 1) || version >= &Version(Api::GlEs, 3, 0)
@@ -435,11 +450,11 @@ impl UncompressedFloatFormat {
                 version >= &Version(Api::Gl, 1, 1) || version >= &Version(Api::GlEs, 3, 0)
             },
             &UncompressedFloatFormat::U16U16U16 => {
-                version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0)
+                version >= &Version(Api::Gl, 3, 0) || extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::I16I16I16 => {
-                version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 0) ||
-                    extensions.gl_ext_texture_snorm
+                version >= &Version(Api::Gl, 3, 2) || extensions.gl_ext_texture_snorm ||
+                    extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::U2U2U2U2 => {
                 version >= &Version(Api::Gl, 1, 1) || version >= &Version(Api::GlEs, 3, 0)
@@ -464,11 +479,11 @@ impl UncompressedFloatFormat {
                 version >= &Version(Api::Gl, 1, 1) || version >= &Version(Api::GlEs, 3, 0)
             },
             &UncompressedFloatFormat::U16U16U16U16 => {
-                version >= &Version(Api::Gl, 1, 1) || version >= &Version(Api::GlEs, 3, 0)
+                version >= &Version(Api::Gl, 1, 1) || extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::I16I16I16I16 => {
-                version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 0) ||
-                    extensions.gl_ext_texture_snorm
+                version >= &Version(Api::Gl, 3, 2) || extensions.gl_ext_texture_snorm ||
+                    extensions.gl_ext_texture_norm16
             },
             &UncompressedFloatFormat::F16 => {
                 version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
@@ -513,6 +528,21 @@ impl UncompressedFloatFormat {
         }
     }
 
+    /// Returns true if this format is a 16-bits normalized format.
+    ///
+    /// On OpenGL ES, these formats require the `GL_EXT_texture_norm16` extension.
+    #[inline]
+    pub fn is_norm16(&self) -> bool {
+        match self {
+            &UncompressedFloatFormat::U16 | &UncompressedFloatFormat::I16 |
+            &UncompressedFloatFormat::U16U16 | &UncompressedFloatFormat::I16I16 |
+            &UncompressedFloatFormat::U16U16U16 | &UncompressedFloatFormat::I16I16I16 |
+            &UncompressedFloatFormat::U16U16U16U16 |
+            &UncompressedFloatFormat::I16I16I16I16 => true,
+            _ => false,
+        }
+    }
+
     /// Returns true if a texture or renderbuffer with this format can be used as a framebuffer
     /// attachment.
     pub fn is_color_renderable<C>(&self, context: &C) -> bool where C: CapabilitiesSource {
@@ -529,9 +559,19 @@ impl UncompressedFloatFormat {
         let version = context.get_version();
         let extensions = context.get_extensions();
 
-        // if we have OpenGL, everything here is color-renderable
+        // if we have OpenGL, everything here is color-renderable, except floating-point formats
+        // that require OpenGL 3 or `GL_ARB_color_buffer_float`
         if version >= &Version(Api::Gl, 1, 0) {
-            return true;
+            return match self {
+                &UncompressedFloatFormat::F16 | &UncompressedFloatFormat::F16F16 |
+                &UncompressedFloatFormat::F16F16F16 | &UncompressedFloatFormat::F16F16F16F16 |
+                &UncompressedFloatFormat::F32 | &UncompressedFloatFormat::F32F32 |
+                &UncompressedFloatFormat::F32F32F32 | &UncompressedFloatFormat::F32F32F32F32 |
+                &UncompressedFloatFormat::F11F11F10 => {
+                    version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_color_buffer_float
+                },
+                _ => true
+            };
         }
 
         // if we have OpenGL ES, it depends
@@ -554,13 +594,21 @@ impl UncompressedFloatFormat {
                 extensions.gl_oes_rgb8_rgba8
             },
             &UncompressedFloatFormat::U10U10U10U2 => version >= &Version(Api::GlEs, 3, 0),
-            &UncompressedFloatFormat::F16 => version >= &Version(Api::GlEs, 3, 2),
-            &UncompressedFloatFormat::F16F16 => version >= &Version(Api::GlEs, 3, 2),
-            &UncompressedFloatFormat::F16F16F16F16 => version >= &Version(Api::GlEs, 3, 2),
-            &UncompressedFloatFormat::F32 => version >= &Version(Api::GlEs, 3, 2),
-            &UncompressedFloatFormat::F32F32 => version >= &Version(Api::GlEs, 3, 2),
-            &UncompressedFloatFormat::F32F32F32F32 => version >= &Version(Api::GlEs, 3, 2),
-            &UncompressedFloatFormat::F11F11F10 => version >= &Version(Api::GlEs, 3, 2),
+            &UncompressedFloatFormat::U16 |
+            &UncompressedFloatFormat::U16U16 |
+            &UncompressedFloatFormat::U16U16U16U16 => extensions.gl_ext_texture_norm16,
+            &UncompressedFloatFormat::F16 |
+            &UncompressedFloatFormat::F16F16 |
+            &UncompressedFloatFormat::F16F16F16F16 => {
+                version >= &Version(Api::GlEs, 3, 2) || extensions.gl_ext_color_buffer_float ||
+                extensions.gl_ext_color_buffer_half_float
+            },
+            &UncompressedFloatFormat::F32 |
+            &UncompressedFloatFormat::F32F32 |
+            &UncompressedFloatFormat::F32F32F32F32 |
+            &UncompressedFloatFormat::F11F11F10 => {
+                version >= &Version(Api::GlEs, 3, 2) || extensions.gl_ext_color_buffer_float
+            },
             _ => false
         }
     }
@@ -1417,6 +1465,104 @@ impl TextureFormat {
         .collect()
     }
 
+    /// Returns the number of bits used by each texel of this format.
+    ///
+    /// For compressed formats, this is the size of a block divided by the number of texels in
+    /// a block. The value is an estimate, as drivers are free to use a larger representation.
+    pub fn get_bits_per_texel(&self) -> usize {
+        match *self {
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U8) => 8,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I8) => 8,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U16) => 16,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I16) => 16,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U8U8) => 16,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I8I8) => 16,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U16U16) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I16I16) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U3U3U2) => 8,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U4U4U4) => 12,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U5U5U5) => 15,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U8U8U8) => 24,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I8I8I8) => 24,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U10U10U10) => 30,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U12U12U12) => 36,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U16U16U16) => 48,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I16I16I16) => 48,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U2U2U2U2) => 8,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U4U4U4U4) => 16,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U5U5U5U1) => 16,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U8U8U8U8) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I8I8I8I8) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U10U10U10U2) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U12U12U12U12) => 48,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::U16U16U16U16) => 64,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::I16I16I16I16) => 64,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F16) => 16,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F16F16) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F16F16F16) => 48,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F16F16F16F16) => 64,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F32) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F32F32) => 64,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F32F32F32) => 96,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F32F32F32F32) => 128,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F11F11F10) => 32,
+            TextureFormat::UncompressedFloat(UncompressedFloatFormat::F9F9F9) => 32,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I8) => 8,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I16) => 16,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I32) => 32,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I8I8) => 16,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I16I16) => 32,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I32I32) => 64,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I8I8I8) => 24,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I16I16I16) => 48,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I32I32I32) => 96,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I8I8I8I8) => 32,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I16I16I16I16) => 64,
+            TextureFormat::UncompressedIntegral(UncompressedIntFormat::I32I32I32I32) => 128,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U8) => 8,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U16) => 16,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U32) => 32,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U8U8) => 16,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U16U16) => 32,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U32U32) => 64,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U8U8U8) => 24,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U16U16U16) => 48,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U32U32U32) => 96,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U8U8U8U8) => 32,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U16U16U16U16) => 64,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U32U32U32U32) => 128,
+            TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U10U10U10U2) => 32,
+            TextureFormat::Srgb(SrgbFormat::U8U8U8) => 24,
+            TextureFormat::Srgb(SrgbFormat::U8U8U8U8) => 32,
+            TextureFormat::CompressedFormat(CompressedFormat::RgtcFormatU) => 4,
+            TextureFormat::CompressedFormat(CompressedFormat::RgtcFormatI) => 4,
+            TextureFormat::CompressedFormat(CompressedFormat::RgtcFormatUU) => 8,
+            TextureFormat::CompressedFormat(CompressedFormat::RgtcFormatII) => 8,
+            TextureFormat::CompressedFormat(CompressedFormat::BptcUnorm4) => 8,
+            TextureFormat::CompressedFormat(CompressedFormat::BptcSignedFloat3) => 8,
+            TextureFormat::CompressedFormat(CompressedFormat::BptcUnsignedFloat3) => 8,
+            TextureFormat::CompressedFormat(CompressedFormat::S3tcDxt1NoAlpha) => 4,
+            TextureFormat::CompressedFormat(CompressedFormat::S3tcDxt1Alpha) => 4,
+            TextureFormat::CompressedFormat(CompressedFormat::S3tcDxt3Alpha) => 8,
+            TextureFormat::CompressedFormat(CompressedFormat::S3tcDxt5Alpha) => 8,
+            TextureFormat::CompressedSrgbFormat(CompressedSrgbFormat::Bptc) => 8,
+            TextureFormat::CompressedSrgbFormat(CompressedSrgbFormat::S3tcDxt1NoAlpha) => 4,
+            TextureFormat::CompressedSrgbFormat(CompressedSrgbFormat::S3tcDxt1Alpha) => 4,
+            TextureFormat::CompressedSrgbFormat(CompressedSrgbFormat::S3tcDxt3Alpha) => 8,
+            TextureFormat::CompressedSrgbFormat(CompressedSrgbFormat::S3tcDxt5Alpha) => 8,
+            TextureFormat::DepthFormat(DepthFormat::I16) => 16,
+            TextureFormat::DepthFormat(DepthFormat::I24) => 24,
+            TextureFormat::DepthFormat(DepthFormat::I32) => 32,
+            TextureFormat::DepthFormat(DepthFormat::F32) => 32,
+            TextureFormat::StencilFormat(StencilFormat::I1) => 1,
+            TextureFormat::StencilFormat(StencilFormat::I4) => 4,
+            TextureFormat::StencilFormat(StencilFormat::I8) => 8,
+            TextureFormat::StencilFormat(StencilFormat::I16) => 16,
+            TextureFormat::DepthStencilFormat(DepthStencilFormat::I24I8) => 32,
+            TextureFormat::DepthStencilFormat(DepthStencilFormat::F32I8) => 64,
+        }
+    }
+
     /// Returns true if this format is supported by the backend for textures.
     #[inline]
     pub fn is_supported_for_textures<C>(&self, c: &C) -> bool where C: CapabilitiesSource {
@@ -1567,6 +1713,18 @@ impl ClientFormatAny {
         }
     }
 
+    /// Returns the width and height in pixels of the blocks of this format. Uncompressed formats
+    /// have blocks of one pixel.
+    #[inline]
+    pub fn get_block_dimensions(&self) -> (u32, u32) {
+        match *self {
+            ClientFormatAny::ClientFormat(_) => (1, 1),
+            // all the compressed formats that we support use 4x4 blocks
+            ClientFormatAny::CompressedFormat(_) => (4, 4),
+            ClientFormatAny::CompressedSrgbFormat(_) => (4, 4),
+        }
+    }
+
     #[inline]
     pub fn get_num_components(&self) -> u8 {
         match *self {
@@ -1685,6 +1843,8 @@ pub fn format_request_to_glenum(context: &Context, format: TextureFormatRequest,
 
             } else if version >= &Version(Api::GlEs, 2, 0) {
                 match (rq_ty, size) {
+                    (RequestType::TexImage(_), Some(1)) => gl::LUMINANCE,
+                    (RequestType::TexImage(_), Some(2)) => gl::LUMINANCE_ALPHA,
                     (RequestType::TexImage(_), Some(3)) => gl::RGB,
                     (_, Some(3)) => {
                         if extensions.gl_oes_rgb8_rgba8 {
@@ -2112,7 +2272,7 @@ pub fn client_format_to_glenum(context: &Context, client: ClientFormatAny,
         TextureFormatRequest::Specific(TextureFormat::CompressedFormat(_)) |
         TextureFormatRequest::Specific(TextureFormat::CompressedSrgbFormat(_)) =>
         {
-            match client {
+            let value = match client {
                 ClientFormatAny::ClientFormat(ClientFormat::U8) => Ok((gl::RED, gl::UNSIGNED_BYTE)),
                 ClientFormatAny::ClientFormat(ClientFormat::U8U8) => Ok((gl::RG, gl::UNSIGNED_BYTE)),
                 ClientFormatAny::ClientFormat(ClientFormat::U8U8U8) => Ok((gl::RGB, gl::UNSIGNED_BYTE)),
@@ -2155,6 +2315,30 @@ pub fn client_format_to_glenum(context: &Context, client: ClientFormatAny,
                 // client is compressed.
                 ClientFormatAny::CompressedFormat(_) => unreachable!(),
                 ClientFormatAny::CompressedSrgbFormat(_) => unreachable!(),
+            };
+
+            // OpenGL ES 2 doesn't have sized internal formats, and requires the format of the
+            // data to be the same as the internal format
+            let version = context.get_version();
+            if version.0 == Api::GlEs && version < &Version(Api::GlEs, 3, 0) {
+                value.and_then(|(format, ty)| {
+                    let format = match format {
+                        gl::RED => gl::LUMINANCE,
+                        gl::RG => gl::LUMINANCE_ALPHA,
+                        gl::RGB => gl::RGB,
+                        gl::RGBA => gl::RGBA,
+                        _ => return Err(FormatNotSupportedError)
+                    };
+
+                    match ty {
+                        gl::UNSIGNED_BYTE | gl::UNSIGNED_SHORT_5_6_5 |
+                        gl::UNSIGNED_SHORT_4_4_4_4 | gl::UNSIGNED_SHORT_5_5_5_1 => Ok((format, ty)),
+                        _ => Err(FormatNotSupportedError)
+                    }
+                })
+
+            } else {
+                value
             }
         },
 
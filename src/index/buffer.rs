@@ -1,17 +1,26 @@
 use buffer::{Buffer, BufferSlice, BufferMutSlice, BufferAny, BufferType};
 use buffer::{BufferMode, BufferCreationError};
+use buffer::DetachedAlloc;
 use gl;
 use BufferExt;
 use GlObject;
 
 use backend::Facade;
+use context::Context;
+use sync::{DeferredDestroy, DeferredObject};
 
 use index::IndicesSource;
 use index::Index;
 use index::IndexType;
 use index::PrimitiveType;
+use index::winding::{self, FlipWindingError};
 
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::io;
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 use utils::range::RangeArgument;
 
 /// Error that can happen while creating an index buffer.
@@ -25,6 +34,47 @@ pub enum CreationError {
 
     /// An error happened while creating the buffer.
     BufferCreationError(BufferCreationError),
+
+    /// The iterator passed to `from_iter` didn't produce the number of elements that it
+    /// reported.
+    IteratorLengthMismatch {
+        /// Number of elements reported by the iterator.
+        expected: usize,
+        /// Number of elements that have been produced, or `expected + 1` if the iterator
+        /// produced more elements than expected.
+        obtained: usize,
+    },
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &CreationError::BufferCreationError(ref err) => err.fmt(formatter),
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::IndexTypeNotSupported => "The type of index is not supported by the \
+                                                      backend",
+            &CreationError::PrimitiveTypeNotSupported => "The type of primitives is not \
+                                                          supported by the backend",
+            &CreationError::BufferCreationError(_) => "Error while creating the index buffer",
+            &CreationError::IteratorLengthMismatch { .. } => "The iterator didn't produce the \
+                                                              number of elements that it \
+                                                              reported",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
 }
 
 impl From<BufferCreationError> for CreationError {
@@ -97,6 +147,95 @@ impl<T> IndexBuffer<T> where T: Index {
         })
     }
 
+    /// Builds a new index buffer by uploading the indices in chunks of at most `chunk_bytes`
+    /// bytes.
+    ///
+    /// See `VertexBuffer::new_streamed`.
+    #[inline]
+    pub fn new_streamed<F>(facade: &F, prim: PrimitiveType, data: &[T], chunk_bytes: usize)
+                           -> Result<IndexBuffer<T>, CreationError>
+                           where F: Facade
+    {
+        IndexBuffer::new_streamed_with_progress(facade, prim, data, chunk_bytes, |_, _| true)
+    }
+
+    /// Same as `new_streamed`, but calls `progress` after each chunk with the number of bytes
+    /// that have been uploaded and the total number of bytes.
+    ///
+    /// See `VertexBuffer::new_streamed_with_progress`.
+    pub fn new_streamed_with_progress<F, P>(facade: &F, prim: PrimitiveType, data: &[T],
+                                            chunk_bytes: usize, progress: P)
+                                            -> Result<IndexBuffer<T>, CreationError>
+                                            where F: Facade, P: FnMut(usize, usize) -> bool
+    {
+        if !prim.is_supported(facade) {
+            return Err(CreationError::PrimitiveTypeNotSupported);
+        }
+
+        if !T::is_supported(facade) {
+            return Err(CreationError::IndexTypeNotSupported);
+        }
+
+        Ok(IndexBuffer {
+            buffer: try!(Buffer::new_streamed(facade, data, BufferType::ElementArrayBuffer,
+                                              chunk_bytes, progress)),
+            primitives: prim,
+        })
+    }
+
+    /// Builds a new index buffer from an iterator of indices and a primitive type.
+    ///
+    /// The indices are written directly to a mapping of the buffer, without being collected in
+    /// a `Vec` first. If the buffer can't be mapped, they are collected instead.
+    ///
+    /// Returns `IteratorLengthMismatch` if the iterator produces less or more elements than
+    /// its `len()`. The buffer is then destroyed.
+    pub fn from_iter<F, I>(facade: &F, prim: PrimitiveType, iter: I)
+                           -> Result<IndexBuffer<T>, CreationError>
+                           where F: Facade, I: IntoIterator<Item = T>,
+                                 I::IntoIter: ExactSizeIterator
+    {
+        let mut iter = iter.into_iter();
+        let len = iter.len();
+
+        let mut buffer = try!(IndexBuffer::empty_impl(facade, prim, len, BufferMode::Default));
+
+        if !buffer.is_mapping_supported() {
+            let data = iter.collect::<Vec<_>>();
+            if data.len() != len {
+                return Err(CreationError::IteratorLengthMismatch {
+                    expected: len,
+                    obtained: cmp::min(data.len(), len + 1),
+                });
+            }
+
+            buffer.write(&data);
+            return Ok(buffer);
+        }
+
+        {
+            let mut mapping = buffer.map_write();
+            for index in 0 .. len {
+                match iter.next() {
+                    Some(value) => mapping.set(index, value),
+                    None => return Err(CreationError::IteratorLengthMismatch {
+                        expected: len,
+                        obtained: index,
+                    }),
+                }
+            }
+        }
+
+        if iter.next().is_some() {
+            return Err(CreationError::IteratorLengthMismatch {
+                expected: len,
+                obtained: len + 1,
+            });
+        }
+
+        Ok(buffer)
+    }
+
     /// Builds a new empty index buffer.
     #[inline]
     pub fn empty<F>(facade: &F, prim: PrimitiveType, len: usize)
@@ -153,6 +292,26 @@ impl<T> IndexBuffer<T> where T: Index {
         })
     }
 
+    /// Builds a new index buffer of `len` indices whose raw bytes are read from `reader`.
+    ///
+    /// The data is streamed in chunks of at most `TRANSFER_CHUNK_SIZE` bytes, so the whole
+    /// content is never held in memory at once. The indices must be in the native endianness
+    /// of the machine.
+    ///
+    /// Errors while creating the buffer are returned as an `io::Error` of kind `Other`.
+    pub fn upload_from_reader<F, R: ?Sized>(facade: &F, reader: &mut R, prim: PrimitiveType,
+                                            len: usize) -> io::Result<IndexBuffer<T>>
+                                            where F: Facade, R: io::Read
+    {
+        let buffer = match IndexBuffer::empty(facade, prim, len) {
+            Ok(b) => b,
+            Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err)),
+        };
+
+        try!(buffer.buffer.upload_from_reader(reader));
+        Ok(buffer)
+    }
+
     /// Returns the type of primitives associated with this index buffer.
     #[inline]
     pub fn get_primitives_type(&self) -> PrimitiveType {
@@ -175,6 +334,51 @@ impl<T> IndexBuffer<T> where T: Index {
             }
         })
     }
+
+    /// Builds a new index buffer with the same mode, primitive type and content as this one.
+    #[inline]
+    pub fn clone_buffer<F>(&self, facade: &F) -> Result<IndexBuffer<T>, BufferCreationError>
+                           where F: Facade
+    {
+        Ok(IndexBuffer {
+            buffer: try!(self.buffer.clone_buffer(facade)),
+            primitives: self.primitives,
+        })
+    }
+
+    /// Builds a new index buffer whose triangles have the opposite winding order.
+    ///
+    /// The content of the buffer is read back and modified with `winding::flip_winding`.
+    /// Returns an error if the primitives are not triangles or if the buffer can't be read.
+    pub fn flip_winding<F>(&self, facade: &F) -> Result<IndexBuffer<T>, FlipWindingError>
+                           where F: Facade, T: Into<u32>
+    {
+        let mut indices = try!(self.buffer.read());
+        try!(winding::flip_winding(self.primitives, &mut indices));
+        Ok(try!(IndexBuffer::new(facade, self.primitives, &indices)))
+    }
+}
+
+impl<T> DeferredDestroy for IndexBuffer<T> where T: Index {
+    type Detached = (DetachedAlloc, PrimitiveType);
+
+    #[inline]
+    fn detach(self) -> (DetachedAlloc, PrimitiveType) {
+        (self.buffer.detach(), self.primitives)
+    }
+
+    #[inline]
+    fn attach(detached: (DetachedAlloc, PrimitiveType), context: &Rc<Context>) -> IndexBuffer<T> {
+        IndexBuffer {
+            buffer: Buffer::attach(detached.0, context),
+            primitives: detached.1,
+        }
+    }
+
+    #[inline]
+    fn into_deferred_object(detached: (DetachedAlloc, PrimitiveType)) -> DeferredObject {
+        DeferredObject::Buffer(detached.0)
+    }
 }
 
 impl<T> Deref for IndexBuffer<T> where T: Index {
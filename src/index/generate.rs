@@ -0,0 +1,122 @@
+/*!
+Helpers that generate the indices of common shapes.
+
+The functions of this module return iterators instead of `Vec`s, so that they can be passed
+to `IndexBuffer::from_iter` without building the whole list of indices in memory.
+
+```no_run
+# let display: glium::Display = unsafe { std::mem::uninitialized() };
+use glium::index::{generate, IndexBuffer, PrimitiveType};
+
+let indices = IndexBuffer::from_iter(&display, PrimitiveType::TrianglesList,
+                                     generate::grid_triangles(64, 64)).unwrap();
+```
+
+*/
+
+/// Position of the corners of the two triangles of a quad, relative to its first corner.
+///
+/// The corners of a quad are in counter-clockwise order, so the triangles keep the winding of
+/// the quad.
+const QUAD_CORNERS: [(u32, u32); 6] = [(0, 0), (1, 0), (1, 1), (0, 0), (1, 1), (0, 1)];
+
+/// Returns the indices of a list of independent quads drawn as a `TrianglesList`.
+///
+/// Each quad must be made of four consecutive vertices in counter-clockwise order. Quad `n`
+/// uses the vertices `4 * n` to `4 * n + 3` and produces the triangles `(0, 1, 2)` and
+/// `(0, 2, 3)`.
+///
+/// The iterator produces `6 * quad_count` indices.
+#[inline]
+pub fn quad_list_indices(quad_count: u32) -> QuadListIndices {
+    QuadListIndices {
+        next: 0,
+        len: quad_count * 6,
+    }
+}
+
+/// Returns the indices of a grid of `width` by `height` quads drawn as a `TrianglesList`.
+///
+/// The grid is made of `(width + 1) * (height + 1)` vertices stored row by row, which means
+/// that the vertex at position `(x, y)` has the index `y * (width + 1) + x`. The rows go
+/// towards the top and the columns go towards the right, so that the triangles are in
+/// counter-clockwise order.
+///
+/// The iterator produces `6 * width * height` indices.
+#[inline]
+pub fn grid_triangles(width: u32, height: u32) -> GridTriangles {
+    GridTriangles {
+        width: width,
+        next: 0,
+        len: width * height * 6,
+    }
+}
+
+/// Iterator returned by `quad_list_indices`.
+#[derive(Debug, Clone)]
+pub struct QuadListIndices {
+    next: u32,
+    len: u32,
+}
+
+impl Iterator for QuadListIndices {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        if self.next >= self.len {
+            return None;
+        }
+
+        let quad = self.next / 6;
+        let (x, y) = QUAD_CORNERS[(self.next % 6) as usize];
+        self.next += 1;
+
+        // the corners of the quad are (0, 0), (1, 0), (1, 1), (0, 1)
+        let corner = if y == 0 { x } else { 3 - x };
+        Some(quad * 4 + corner)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.len - self.next) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for QuadListIndices {}
+
+/// Iterator returned by `grid_triangles`.
+#[derive(Debug, Clone)]
+pub struct GridTriangles {
+    width: u32,
+    next: u32,
+    len: u32,
+}
+
+impl Iterator for GridTriangles {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        if self.next >= self.len {
+            return None;
+        }
+
+        let cell = self.next / 6;
+        let (x, y) = QUAD_CORNERS[(self.next % 6) as usize];
+        self.next += 1;
+
+        let cell_x = cell % self.width;
+        let cell_y = cell / self.width;
+        Some((cell_y + y) * (self.width + 1) + cell_x + x)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.len - self.next) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for GridTriangles {}
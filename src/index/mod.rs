@@ -43,6 +43,10 @@ pub use self::buffer::{IndexBuffer, IndexBufferSlice, IndexBufferAny};
 pub use self::buffer::CreationError as BufferCreationError;
 pub use self::multidraw::{DrawCommandsNoIndicesBuffer, DrawCommandNoIndices};
 pub use self::multidraw::{DrawCommandsIndicesBuffer, DrawCommandIndices};
+pub use self::winding::FlipWindingError;
+
+pub mod generate;
+pub mod winding;
 
 mod buffer;
 mod multidraw;
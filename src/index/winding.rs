@@ -0,0 +1,177 @@
+/*!
+Helpers that reverse the winding order of triangles.
+
+Some modelling tools export meshes whose triangles are in clockwise order. With backface
+culling enabled, or when the normals are computed from the winding, these meshes are rendered
+inside out. The functions of this module reverse the order of the vertices of each triangle
+without changing the vertices themselves.
+
+```no_run
+# let display: glium::Display = unsafe { std::mem::uninitialized() };
+# let mut indices: Vec<u16> = unsafe { std::mem::uninitialized() };
+use glium::index::{winding, IndexBuffer, PrimitiveType};
+
+winding::flip_winding(PrimitiveType::TrianglesList, &mut indices).unwrap();
+let indices = IndexBuffer::new(&display, PrimitiveType::TrianglesList, &indices).unwrap();
+```
+
+# Primitive restart
+
+The maximum value of the index type (`0xff`, `0xffff` or `0xffffffff`) is considered as a
+primitive restart index. These indices are kept at the same place and each group of indices
+between them is flipped independently.
+
+*/
+use std::error::Error;
+use std::fmt;
+
+use buffer::ReadError;
+
+use index::Index;
+use index::IndexType;
+use index::PrimitiveType;
+use index::BufferCreationError;
+
+/// Error that can happen while reversing the winding order of a list of indices.
+#[derive(Debug, Copy, Clone)]
+pub enum FlipWindingError {
+    /// The primitives are not triangles, or are triangle strips with adjacency.
+    PrimitiveTypeNotSupported,
+
+    /// The indices couldn't be read from the index buffer.
+    ReadError(ReadError),
+
+    /// An error happened while creating the new index buffer.
+    BufferCreationError(BufferCreationError),
+}
+
+impl fmt::Display for FlipWindingError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &FlipWindingError::ReadError(ref err) => err.fmt(formatter),
+            &FlipWindingError::BufferCreationError(ref err) => err.fmt(formatter),
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for FlipWindingError {
+    fn description(&self) -> &str {
+        match self {
+            &FlipWindingError::PrimitiveTypeNotSupported => "The winding order of this type of \
+                                                             primitives can't be reversed",
+            &FlipWindingError::ReadError(_) => "Error while reading the index buffer",
+            &FlipWindingError::BufferCreationError(_) => "Error while creating the index \
+                                                          buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &FlipWindingError::ReadError(ref err) => Some(err),
+            &FlipWindingError::BufferCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ReadError> for FlipWindingError {
+    #[inline]
+    fn from(err: ReadError) -> FlipWindingError {
+        FlipWindingError::ReadError(err)
+    }
+}
+
+impl From<BufferCreationError> for FlipWindingError {
+    #[inline]
+    fn from(err: BufferCreationError) -> FlipWindingError {
+        FlipWindingError::BufferCreationError(err)
+    }
+}
+
+/// Reverses the winding order of the triangles described by `indices`.
+///
+/// - `TrianglesList`: the last two indices of each triangle are swapped.
+/// - `TrianglesListAdjacency`: the vertices and the adjacent vertices of each triangle are
+///   reordered so that each edge keeps its adjacent vertex.
+/// - `TriangleFan`: all the indices but the first one are reversed.
+/// - `TriangleStrip`: the first index of each strip is duplicated. This inserts a degenerate
+///   triangle at the start of the strip, which shifts the parity of all the other triangles.
+///   The list of indices is one element longer per strip.
+///
+/// Incomplete triangles at the end of a list are left untouched. Returns an error for the
+/// other types of primitives.
+pub fn flip_winding<T>(primitives: PrimitiveType, indices: &mut Vec<T>)
+                       -> Result<(), FlipWindingError> where T: Index + Into<u32>
+{
+    match primitives {
+        PrimitiveType::TrianglesList => {
+            for_each_group(indices, |group| {
+                for triangle in group.chunks_mut(3).filter(|t| t.len() == 3) {
+                    triangle.swap(1, 2);
+                }
+            });
+        },
+
+        PrimitiveType::TrianglesListAdjacency => {
+            // the triangle `0, 2, 4` becomes `0, 4, 2` and the vertex adjacent to each edge
+            // must follow, which is the same as reversing everything but the first vertex
+            for_each_group(indices, |group| {
+                for triangle in group.chunks_mut(6).filter(|t| t.len() == 6) {
+                    triangle[1 ..].reverse();
+                }
+            });
+        },
+
+        PrimitiveType::TriangleFan => {
+            for_each_group(indices, |group| {
+                if group.len() >= 3 {
+                    group[1 ..].reverse();
+                }
+            });
+        },
+
+        PrimitiveType::TriangleStrip => {
+            let mut result = Vec::with_capacity(indices.len() + 1);
+            let mut strip_start = true;
+
+            for &index in indices.iter() {
+                if is_primitive_restart(index) {
+                    strip_start = true;
+                } else if strip_start {
+                    result.push(index);
+                    strip_start = false;
+                }
+
+                result.push(index);
+            }
+
+            *indices = result;
+        },
+
+        _ => return Err(FlipWindingError::PrimitiveTypeNotSupported),
+    };
+
+    Ok(())
+}
+
+/// Returns true if the index is the primitive restart index of its type.
+#[inline]
+fn is_primitive_restart<T>(index: T) -> bool where T: Index + Into<u32> {
+    let restart = match T::get_type() {
+        IndexType::U8 => 0xff,
+        IndexType::U16 => 0xffff,
+        IndexType::U32 => 0xffffffff,
+    };
+
+    index.into() == restart
+}
+
+/// Calls the closure with each group of indices between two primitive restart indices.
+fn for_each_group<T, F>(indices: &mut [T], mut callback: F)
+                        where T: Index + Into<u32>, F: FnMut(&mut [T])
+{
+    for group in indices.split_mut(|&index| is_primitive_restart(index)) {
+        callback(group);
+    }
+}
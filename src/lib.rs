@@ -30,6 +30,10 @@ meaning that you can't pass it to another thread.
 The display has ownership of the window, and also provides some methods related to domains such
 as events handling.
 
+All the objects that belong to a display (buffers, textures, programs, etc.) don't implement
+`Send` either, because they must be destroyed in the thread where the display lives. If you need
+to move them to other threads, wrap them inside a `DeferredDrop`.
+
 # Overview
 
 OpenGL is similar to a drawing software: you draw something, then draw over it, then over it
@@ -105,18 +109,25 @@ extern crate smallvec;
 
 #[cfg(feature = "glutin")]
 pub use backend::glutin_backend::glutin;
+pub use command_list::CommandList;
 pub use draw_parameters::{Blend, BlendingFunction, LinearBlendingFactor, BackfaceCullingMode};
 pub use draw_parameters::{Depth, DepthTest, PolygonMode, DrawParameters, StencilTest, StencilOperation};
-pub use draw_parameters::{Smooth};
+pub use draw_parameters::{Smooth, DerivativeHint, LogicOp, DrawParametersBuilder};
 pub use index::IndexBuffer;
 pub use vertex::{VertexBuffer, Vertex, VertexFormat};
 pub use program::{Program, ProgramCreationError};
 pub use program::ProgramCreationError::{CompilationError, LinkingError, ShaderTypeNotSupported};
-pub use sync::{LinearSyncFence, SyncFence};
+pub use sync::{DeferredDrop, DeferredDestroy};
+pub use sync::{LinearSyncFence, SyncFence, SyncNotSupportedError};
 pub use texture::Texture2d;
 pub use version::{Api, Version, get_supported_glsl_version};
 
-use std::collections::HashMap;
+#[cfg(feature = "half")]
+pub use half::f16;
+
+use std::cmp;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::rc::Rc;
 use std::thread;
 
@@ -138,8 +149,13 @@ pub mod uniforms;
 pub mod vertex;
 pub mod texture;
 
+mod command_list;
 mod context;
 mod fbo;
+mod draw_uniforms;
+mod global_uniform_blocks;
+#[cfg(feature = "half")]
+mod half;
 mod image_format;
 mod ops;
 mod sampler_object;
@@ -258,6 +274,14 @@ trait BufferExt {
     fn bind_to_transform_feedback(&self, &mut CommandContext, index: gl::types::GLuint);
 }
 
+/// Internal trait for buffers whose content is unknown.
+trait BufferAnyExt {
+    /// Changes the number of bytes of each element of the buffer.
+    ///
+    /// The size of the buffer must be a multiple of the new size.
+    fn set_elements_size(&mut self, elements_size: usize);
+}
+
 /// Internal trait for subbuffer slices.
 trait BufferSliceExt<'a> {
     /// Tries to get an object where to write a fence.
@@ -266,20 +290,69 @@ trait BufferSliceExt<'a> {
     fn add_fence(&self) -> Option<buffer::Inserter<'a>>;
 }
 
+/// Internal trait for command lists.
+trait CommandListExt<'a> {
+    /// Returns the list of recorded commands.
+    fn get_commands(&self) -> &[command_list::DrawCommand<'a>];
+}
+
 /// Internal trait for contexts.
 trait ContextExt {
     /// Sets whether the context's debug output callback should take errors into account.
     fn set_report_debug_output_errors(&self, value: bool);
 
     /// Start executing OpenGL commands by checking the current context.
+    ///
+    /// Executes the `glClear` that may be pending.
     fn make_current(&self) -> context::CommandContext;
 
+    /// Same as `make_current`, but doesn't execute the pending `glClear` so that it can be
+    /// merged with another one.
+    ///
+    /// Must only be used by the clear operations. Everything else goes through `make_current`,
+    /// which is what guarantees that the commands are submitted in the order in which they
+    /// were requested.
+    fn make_current_for_clear(&self) -> context::CommandContext;
+
+    /// Same as `make_current`, but must be used by the destructors of OpenGL objects.
+    ///
+    /// Returns `None` if a `CommandContext` already exists. This happens when an object is
+    /// destroyed while unwinding from a panic that occurred in the middle of an operation. In
+    /// this situation the object must be leaked instead of panicking a second time, which would
+    /// abort the process.
+    fn make_current_for_drop(&self) -> Option<context::CommandContext>;
+
     /// Returns the capabilities of the backend.
     fn capabilities(&self) -> &context::Capabilities;
+
+    /// Calls the closure once for each other context that shares its objects with this one.
+    ///
+    /// This is used to clean the caches of the other contexts when an object is destroyed. The
+    /// other contexts are made current one by one, so you must not hold a `CommandContext`
+    /// when calling this function.
+    fn for_each_shared_context<F>(&self, F) where F: FnMut(&mut context::CommandContext);
+
+    /// Returns the counters that must be updated when a buffer or a texture is created or
+    /// destroyed.
+    fn get_memory_usage_counters(&self) -> &context::MemoryUsageCounters;
+
+    /// Returns the counters of the statistics of the current frame.
+    fn get_frame_statistics_counters(&self) -> &context::FrameStatisticsCounters;
+
+    /// Returns the queue of objects that have been dropped in other threads.
+    fn get_deferred_drop_queue(&self) -> &sync::DeferredDropQueue;
+
+    /// Destroys the objects that have been dropped in other threads.
+    ///
+    /// Must not be called while holding a `CommandContext`.
+    fn flush_deferred_drops(&self);
+
+    /// Returns the objects used to fill surfaces, if they have been created.
+    fn get_fill_resources(&self) -> &RefCell<Option<ops::FillResources>>;
 }
 
 /// Internal trait for programs.
-trait ProgramExt {
+trait ProgramExt: GlObject<Id = Handle> {
     /// Calls `glUseProgram` and enables/disables `GL_PROGRAM_POINT_SIZE` and
     /// `GL_FRAMEBUFFER_SRGB`.
     fn use_program(&self, ctxt: &mut context::CommandContext);
@@ -288,6 +361,11 @@ trait ProgramExt {
     fn set_uniform(&self, ctxt: &mut context::CommandContext, uniform_location: gl::types::GLint,
                    value: &RawUniformValue);
 
+    /// Changes the value of a uniform array of `dimension`x`dimension` matrices with a single
+    /// call. `data` contains the column-major matrices one after another.
+    fn set_uniform_matrix_array(&self, ctxt: &mut context::CommandContext,
+                                uniform_location: gl::types::GLint, dimension: u8, data: &[f32]);
+
     /// Changes the uniform block binding of the program.
     fn set_uniform_block_binding(&self, ctxt: &mut context::CommandContext,
                                  block_location: gl::types::GLuint, value: gl::types::GLuint);
@@ -302,6 +380,33 @@ trait ProgramExt {
     fn get_uniform_blocks(&self) -> &HashMap<String, program::UniformBlock>;
 
     fn get_shader_storage_blocks(&self) -> &HashMap<String, program::UniformBlock>;
+
+    /// Returns the number of times the program has been reloaded.
+    #[inline]
+    fn get_generation(&self) -> u32 {
+        0
+    }
+
+    /// Returns the context associated to this program.
+    fn get_context(&self) -> &Rc<Context>;
+
+    /// Returns true if the warning hasn't been printed for this program yet, and marks it as
+    /// printed.
+    #[inline]
+    fn should_print_warning(&self, _: ProgramWarning) -> bool {
+        true
+    }
+}
+
+/// Warnings that are printed at most once per program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ProgramWarning {
+    /// Clip planes are enabled but the program doesn't write to `gl_ClipDistance`.
+    ClipDistance = 1,
+    /// The program uses early fragment tests and writes to `gl_FragDepth`.
+    EarlyFragmentTests = 2,
+    /// `primitive_bounding_box` is set but the program has no geometry or tessellation shader.
+    PrimitiveBoundingBox = 4,
 }
 
 /// Internal trait for queries.
@@ -338,6 +443,31 @@ trait TextureExt {
     /// Makes sure that the texture is binded to the current texture unit and returns the
     /// bind point to use to access the texture (eg. `GL_TEXTURE_2D`, `GL_TEXTURE_3D`, etc.).
     fn bind_to_current(&self, &mut CommandContext) -> gl::types::GLenum;
+
+    /// Checks whether the texture is complete when sampled with the given sampler, or with the
+    /// filters chosen when the texture was created if `None`.
+    ///
+    /// Only uses the informations that are cached in the texture object.
+    fn check_sampling_completeness(&self, &CommandContext, Option<&uniforms::SamplerBehavior>)
+                                   -> Result<(), uniforms::IncompleteTextureReason>;
+
+    /// Returns the mipmap levels that can be read when the texture is sampled with the given
+    /// sampler, or with the filters chosen when the texture was created if `None`.
+    fn get_sampled_levels(&self, Option<&uniforms::SamplerBehavior>) -> std::ops::Range<u32>;
+
+    /// Returns false if a streaming write to the texture is in progress or has been interrupted.
+    fn is_upload_complete(&self) -> bool;
+
+    /// Returns true if the mipmaps must be regenerated after the main level has been rendered to.
+    fn get_regenerate_mipmaps_after_render(&self) -> bool;
+
+    /// Indicates that the main level of the texture has been rendered to.
+    fn mark_rendered(&self);
+
+    /// Must be called before the texture is bound in order to be sampled with the given sampler.
+    ///
+    /// Regenerates the mipmaps if they are outdated and used by the sampler.
+    fn prepare_for_sampling(&self, &mut CommandContext, Option<&uniforms::SamplerBehavior>);
 }
 
 /// Internal trait for textures.
@@ -351,6 +481,20 @@ trait TextureMipmapExt {
                              where P: Send + Copy + Clone + 'a;
 
     fn download_compressed_data(&self) -> Option<(image_format::ClientFormatAny, Vec<u8>)>;
+
+    /// Reads the whole mipmap level without decompressing it.
+    fn download_compressed_level(&self)
+                                 -> Result<(image_format::ClientFormatAny, Vec<u8>),
+                                           texture::CompressedLevelError>;
+
+    /// Replaces the whole mipmap level with compressed data.
+    fn upload_compressed_level(&self, format: image_format::ClientFormatAny, data: &[u8])
+                               -> Result<(), texture::CompressedLevelError>;
+
+    /// Copies a rectangle of the framebuffer bound to `GL_READ_FRAMEBUFFER` into the mipmap,
+    /// at the given offset.
+    unsafe fn copy_from_read_framebuffer(&self, ctxt: &mut CommandContext, rect: &Rect,
+                                         offset: (u32, u32));
 }
 
 /// Internal trait for transform feedback sessions.
@@ -374,6 +518,12 @@ trait UniformsExt {
     /// Will replace texture and buffer bind points.
     fn bind_uniforms<'a, P>(&'a self, &mut CommandContext, &P, &mut Vec<buffer::Inserter<'a>>)
                             -> Result<(), DrawError> where P: ProgramExt;
+
+    /// Checks that none of the textures sampled by the program is attached to the framebuffer.
+    ///
+    /// If `depth_read_only` is true, the texture attached as depth buffer can be sampled.
+    fn check_feedback_loops<P>(&self, &P, &fbo::ValidatedAttachments, depth_read_only: bool)
+                               -> Result<(), DrawError> where P: ProgramExt;
 }
 
 
@@ -423,37 +573,138 @@ enum RawUniformValue {
 /// Area of a surface in pixels.
 ///
 /// In the OpenGL ecosystem, the (0,0) coordinate is at the bottom-left hand corner of the images.
+///
+/// This type is used everywhere glium needs a rectangle: when reading, blitting, clearing,
+/// writing to textures, and for the scissor box and the viewport.
+///
+/// The origin can be negative, which means that the rectangle can be partially outside of the
+/// surface. The viewport and the scissor box are passed as they are to OpenGL. Reads and blits
+/// only use the part of the rectangle that is inside the source surface.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Rect {
     /// Number of pixels between the left border of the surface and the left border of
-    /// the rectangle.
-    pub left: u32,
+    /// the rectangle. Can be negative.
+    pub left: i32,
     /// Number of pixels between the bottom border of the surface and the bottom border
-    /// of the rectangle.
-    pub bottom: u32,
+    /// of the rectangle. Can be negative.
+    pub bottom: i32,
     /// Width of the area in pixels.
     pub width: u32,
     /// Height of the area in pixels.
     pub height: u32,
 }
 
+impl Rect {
+    /// Returns the part of the rectangle that is inside a surface of the given dimensions.
+    ///
+    /// The width or the height of the result is `0` if the rectangle is entirely outside of
+    /// the surface.
+    pub fn clip(&self, (width, height): (u32, u32)) -> Rect {
+        let clip = |origin: i32, len: u32, max: u32| {
+            let start = cmp::min(cmp::max(origin as i64, 0), max as i64);
+            let end = cmp::max(cmp::min(origin as i64 + len as i64, max as i64), start);
+            (start as i32, (end - start) as u32)
+        };
+
+        let (left, width) = clip(self.left, self.width, width);
+        let (bottom, height) = clip(self.bottom, self.height, height);
+
+        Rect {
+            left: left,
+            bottom: bottom,
+            width: width,
+            height: height,
+        }
+    }
+}
+
+impl From<(i32, i32, u32, u32)> for Rect {
+    /// Builds a `Rect` from a `(left, bottom, width, height)` tuple.
+    #[inline]
+    fn from((left, bottom, width, height): (i32, i32, u32, u32)) -> Rect {
+        Rect {
+            left: left,
+            bottom: bottom,
+            width: width,
+            height: height,
+        }
+    }
+}
+
+/// Status of an incremental upload, returned by `buffer::StreamingWrite::advance` and
+/// `texture::StreamingWrite::advance`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StreamingWriteStatus {
+    /// Some data remains to be uploaded.
+    InProgress,
+    /// All the data has been uploaded.
+    Done,
+}
+
 /// Area of a surface in pixels. Similar to a `Rect` except that dimensions can be negative.
 ///
 /// In the OpenGL ecosystem, the (0,0) coordinate is at the bottom-left hand corner of the images.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct BlitTarget {
     /// Number of pixels between the left border of the surface and the left border of
-    /// the rectangle.
-    pub left: u32,
+    /// the rectangle. Can be negative.
+    pub left: i32,
     /// Number of pixels between the bottom border of the surface and the bottom border
-    /// of the rectangle.
-    pub bottom: u32,
+    /// of the rectangle. Can be negative.
+    pub bottom: i32,
     /// Width of the area in pixels. Can be negative.
     pub width: i32,
     /// Height of the area in pixels. Can be negative.
     pub height: i32,
 }
 
+/// Buffers of a surface that are copied by `Surface::blit_buffers`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlitMask {
+    /// Copy the color buffer.
+    pub color: bool,
+    /// Copy the depth buffer.
+    pub depth: bool,
+    /// Copy the stencil buffer.
+    pub stencil: bool,
+}
+
+impl BlitMask {
+    /// Copies only the color buffer.
+    #[inline]
+    pub fn color() -> BlitMask {
+        BlitMask { color: true, depth: false, stencil: false }
+    }
+
+    /// Copies only the depth buffer.
+    #[inline]
+    pub fn depth() -> BlitMask {
+        BlitMask { color: false, depth: true, stencil: false }
+    }
+
+    /// Copies only the stencil buffer.
+    #[inline]
+    pub fn stencil() -> BlitMask {
+        BlitMask { color: false, depth: false, stencil: true }
+    }
+
+    /// Copies the depth and the stencil buffers.
+    #[inline]
+    pub fn depth_stencil() -> BlitMask {
+        BlitMask { color: false, depth: true, stencil: true }
+    }
+
+    /// Returns the mask to pass to `glBlitFramebuffer`.
+    #[inline]
+    fn to_glbitfield(&self) -> gl::types::GLbitfield {
+        let mut mask = 0;
+        if self.color { mask |= gl::COLOR_BUFFER_BIT; }
+        if self.depth { mask |= gl::DEPTH_BUFFER_BIT; }
+        if self.stencil { mask |= gl::STENCIL_BUFFER_BIT; }
+        mask
+    }
+}
+
 /// Object that can be drawn upon.
 ///
 /// # What does the GPU do when you draw?
@@ -668,6 +919,28 @@ pub trait Surface {
     fn clear(&mut self, rect: Option<&Rect>, color: Option<(f32, f32, f32, f32)>, color_srgb: bool,
              depth: Option<f32>, stencil: Option<i32>);
 
+    /// Clears a color attachment that contains signed integers.
+    ///
+    /// `attachment_index` is the position of the attachment in the list of color attachments
+    /// of the framebuffer. Calling `clear` or `clear_color` on a framebuffer with integer
+    /// attachments converts each component of the color to an integer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the attachment doesn't exist or doesn't contain signed integers.
+    fn clear_color_integer(&mut self, attachment_index: u32, color: [i32; 4]);
+
+    /// Clears a color attachment that contains unsigned integers.
+    ///
+    /// `attachment_index` is the position of the attachment in the list of color attachments
+    /// of the framebuffer. Calling `clear` or `clear_color` on a framebuffer with integer
+    /// attachments converts each component of the color to an integer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the attachment doesn't exist or doesn't contain unsigned integers.
+    fn clear_color_unsigned(&mut self, attachment_index: u32, color: [u32; 4]);
+
     /// Clears the color attachment of the target.
     fn clear_color(&mut self, red: f32, green: f32, blue: f32, alpha: f32) {
         self.clear(None, Some((red, green, blue, alpha)), false, None, None);
@@ -714,6 +987,9 @@ pub trait Surface {
     }
 
     /// Clears the color, depth and stencil attachments of the target.
+    ///
+    /// This is done with a single call to `glClear`, which is faster than clearing each
+    /// attachment separately.
     fn clear_all(&mut self, color: (f32, f32, f32, f32), depth: f32, stencil: i32) {
         self.clear(None, Some(color), false, Some(depth), Some(stencil));
     }
@@ -758,19 +1034,92 @@ pub trait Surface {
         V: vertex::MultiVerticesSource<'b>, I: Into<index::IndicesSource<'a>>,
         U: uniforms::Uniforms;
 
+    /// Executes the commands of a `CommandList`.
+    ///
+    /// This is equivalent to calling `draw` with the parameters of each command, in the order
+    /// in which they have been recorded.
+    fn draw_command_list(&mut self, list: &CommandList) -> Result<(), DrawError>;
+
     /// Blits from the default framebuffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the blit is not possible. See `blit_buffers_from_frame`.
+    #[inline]
     fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
-                       filter: uniforms::MagnifySamplerFilter);
+                       filter: uniforms::MagnifySamplerFilter)
+    {
+        self.blit_buffers_from_frame(source_rect, target_rect, filter, BlitMask::color())
+            .unwrap()
+    }
 
     /// Blits from a simple framebuffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the blit is not possible. See `blit_buffers_from_simple_framebuffer`.
+    #[inline]
     fn blit_from_simple_framebuffer(&self, source: &framebuffer::SimpleFrameBuffer,
                                     source_rect: &Rect, target_rect: &BlitTarget,
-                                    filter: uniforms::MagnifySamplerFilter);
+                                    filter: uniforms::MagnifySamplerFilter)
+    {
+        self.blit_buffers_from_simple_framebuffer(source, source_rect, target_rect, filter,
+                                                  BlitMask::color()).unwrap()
+    }
 
     /// Blits from a multi-output framebuffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the blit is not possible. See `blit_buffers_from_multioutput_framebuffer`.
+    #[inline]
     fn blit_from_multioutput_framebuffer(&self, source: &framebuffer::MultiOutputFrameBuffer,
                                          source_rect: &Rect, target_rect: &BlitTarget,
-                                         filter: uniforms::MagnifySamplerFilter);
+                                         filter: uniforms::MagnifySamplerFilter)
+    {
+        self.blit_buffers_from_multioutput_framebuffer(source, source_rect, target_rect, filter,
+                                                       BlitMask::color()).unwrap()
+    }
+
+    /// Blits from a framebuffer that has been created outside of glium.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the blit is not possible. See `blit_buffers_from_external_framebuffer`.
+    #[inline]
+    fn blit_from_external_framebuffer(&self, source: &framebuffer::ExternalFramebuffer,
+                                      source_rect: &Rect, target_rect: &BlitTarget,
+                                      filter: uniforms::MagnifySamplerFilter)
+    {
+        self.blit_buffers_from_external_framebuffer(source, source_rect, target_rect, filter,
+                                                    BlitMask::color()).unwrap()
+    }
+
+    /// Copies the buffers of the default framebuffer that are enabled in `mask` to this surface.
+    fn blit_buffers_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
+                               filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                               -> Result<(), BlitError>;
+
+    /// Copies the buffers of a simple framebuffer that are enabled in `mask` to this surface.
+    fn blit_buffers_from_simple_framebuffer(&self, source: &framebuffer::SimpleFrameBuffer,
+                                            source_rect: &Rect, target_rect: &BlitTarget,
+                                            filter: uniforms::MagnifySamplerFilter,
+                                            mask: BlitMask) -> Result<(), BlitError>;
+
+    /// Copies the buffers of a multi-output framebuffer that are enabled in `mask` to this
+    /// surface.
+    fn blit_buffers_from_multioutput_framebuffer(&self,
+                                                 source: &framebuffer::MultiOutputFrameBuffer,
+                                                 source_rect: &Rect, target_rect: &BlitTarget,
+                                                 filter: uniforms::MagnifySamplerFilter,
+                                                 mask: BlitMask) -> Result<(), BlitError>;
+
+    /// Copies the buffers of a framebuffer that has been created outside of glium that are
+    /// enabled in `mask` to this surface.
+    fn blit_buffers_from_external_framebuffer(&self, source: &framebuffer::ExternalFramebuffer,
+                                              source_rect: &Rect, target_rect: &BlitTarget,
+                                              filter: uniforms::MagnifySamplerFilter,
+                                              mask: BlitMask) -> Result<(), BlitError>;
 
     /// Copies a rectangle of pixels from this surface to another surface.
     ///
@@ -782,11 +1131,53 @@ pub trait Surface {
     /// It is possible for the source and the target to be the same surface. However if the
     /// rectangles overlap, then the behavior is undefined.
     ///
+    /// The source rectangle is clipped to the dimensions of the source surface, and the target
+    /// rectangle is reduced by the same proportion. The parts of the target rectangle that are
+    /// outside of the target surface are ignored.
+    ///
     /// Note that there is no alpha blending, depth/stencil checking, etc. This function just
     /// copies pixels.
     fn blit_color<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
                      filter: uniforms::MagnifySamplerFilter) where S: Surface;
 
+    /// Copies a rectangle of the color, depth and/or stencil buffers of this surface to another
+    /// surface.
+    ///
+    /// This is the same as `blit_color`, except that you choose which buffers are copied.
+    /// The compatibility between the two surfaces is checked before anything is copied:
+    ///
+    /// - Depth and stencil buffers can only be copied between attachments of the same format,
+    ///   and only with the `Nearest` filter.
+    /// - Color buffers with integral values can only be copied to color buffers whose values
+    ///   have the same signedness, and only with the `Nearest` filter.
+    /// - If both surfaces are multisampled, they must have the same number of samples. If one
+    ///   of them is multisampled, the source and target rectangles must have the same
+    ///   dimensions.
+    ///
+    /// The formats of the default framebuffer and of framebuffers that have been created
+    /// outside of glium are unknown, so only the filter is checked for them.
+    fn blit_buffers<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
+                       filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                       -> Result<(), BlitError> where S: Surface;
+
+    /// Copies the whole depth buffer of `source` to the depth buffer of this surface.
+    ///
+    /// This is typically used to give the depth of an opaque scene to a framebuffer that
+    /// renders soft particles. The two depth buffers must have the same format. See
+    /// `blit_buffers`.
+    fn copy_depth_from<S>(&self, source: &S) -> Result<(), BlitError> where S: Surface {
+        if !source.has_depth_buffer() || !self.has_depth_buffer() {
+            return Err(BlitError::NoDepthBuffer);
+        }
+
+        let src_dim = source.get_dimensions();
+        let src_rect = Rect { left: 0, bottom: 0, width: src_dim.0 as u32, height: src_dim.1 as u32 };
+        let target_dim = self.get_dimensions();
+        let target_rect = BlitTarget { left: 0, bottom: 0, width: target_dim.0 as i32, height: target_dim.1 as i32 };
+        source.blit_buffers(&src_rect, self, &target_rect, uniforms::MagnifySamplerFilter::Nearest,
+                            BlitMask::depth())
+    }
+
     /// Copies the entire surface to a target surface. See `blit_color`.
     #[inline]
     fn blit_whole_color_to<S>(&self, target: &S, target_rect: &BlitTarget,
@@ -806,6 +1197,161 @@ pub trait Surface {
         let target_rect = BlitTarget { left: 0, bottom: 0, width: target_dim.0 as i32, height: target_dim.1 as i32 };
         self.blit_color(&src_rect, target, &target_rect, filter)
     }
+
+    /// Draws a texture over the whole surface.
+    ///
+    /// Contrary to `fill` and the blitting functions, this goes through the regular drawing
+    /// pipeline. The texture doesn't need to be renderable, and `draw_parameters` can for
+    /// example enable blending. The texture is stretched to the dimensions of the viewport,
+    /// and `filter` is used when it is magnified.
+    ///
+    /// The program doesn't output sRGB values. If the surface is in sRGB format, the colors of
+    /// the texture are converted like with any other program.
+    ///
+    /// The vertex buffer and the program that are used are created once for each context, the
+    /// first time this function is called.
+    fn fill_with_texture(&mut self, texture: &texture::Texture2d,
+                         filter: uniforms::MagnifySamplerFilter,
+                         draw_parameters: &DrawParameters) -> Result<(), DrawError>
+                         where Self: Sized
+    {
+        ops::fill_with_texture(self, texture, filter, draw_parameters)
+    }
+
+    /// Draws a single triangle that covers the whole viewport with your own program.
+    ///
+    /// This is typically used for post-processing. The vertex shader receives two attributes
+    /// of type `vec2`: `position`, in normalized device coordinates, and `tex_coords`, which
+    /// goes from `(0.0, 0.0)` at the bottom-left corner of the viewport to `(1.0, 1.0)` at its
+    /// top-right corner.
+    fn fill_with_program<U>(&mut self, program: &Program, uniforms: &U,
+                            draw_parameters: &DrawParameters) -> Result<(), DrawError>
+                            where U: uniforms::Uniforms, Self: Sized
+    {
+        ops::fill_with_program(self, program, uniforms, draw_parameters)
+    }
+
+    /// Copies a texture to this surface without any scaling, so that each texel corresponds
+    /// to exactly one pixel.
+    ///
+    /// `dest_origin` is the position of the top-left corner of the texture, in pixels from the
+    /// top-left corner of the surface. This is the convention of window coordinates, with the
+    /// Y axis going down. The content of the texture isn't flipped: its last row ends up at the
+    /// top of the destination rectangle, like when the texture is drawn with a regular quad.
+    ///
+    /// The destination rectangle can be partially or totally outside of the surface, in which
+    /// case the texture is clipped.
+    ///
+    /// This uses a blit with nearest filtering, which means that there is no alpha blending
+    /// and that the texture must be usable as a surface.
+    fn draw_texture_1to1(&self, texture: &texture::Texture2d, dest_origin: (i32, i32))
+                         where Self: Sized
+    {
+        let (surface_width, surface_height) = self.get_dimensions();
+        let (surface_width, surface_height) = (surface_width as i64, surface_height as i64);
+        let (tex_width, tex_height) = (texture.get_width() as i64,
+                                       texture.get_height().unwrap_or(1) as i64);
+
+        // destination rectangle, in OpenGL coordinates
+        let left = dest_origin.0 as i64;
+        let bottom = surface_height - dest_origin.1 as i64 - tex_height;
+
+        // clipping both rectangles by the same amount
+        let clip_left = if left < 0 { -left } else { 0 };
+        let clip_bottom = if bottom < 0 { -bottom } else { 0 };
+        let clip_right = if left + tex_width > surface_width {
+            left + tex_width - surface_width
+        } else {
+            0
+        };
+        let clip_top = if bottom + tex_height > surface_height {
+            bottom + tex_height - surface_height
+        } else {
+            0
+        };
+
+        let width = tex_width - clip_left - clip_right;
+        let height = tex_height - clip_bottom - clip_top;
+
+        if width <= 0 || height <= 0 {
+            return;
+        }
+
+        let source_rect = Rect {
+            left: clip_left as i32,
+            bottom: clip_bottom as i32,
+            width: width as u32,
+            height: height as u32,
+        };
+
+        let target_rect = BlitTarget {
+            left: (left + clip_left) as i32,
+            bottom: (bottom + clip_bottom) as i32,
+            width: width as i32,
+            height: height as i32,
+        };
+
+        texture.as_surface().blit_color(&source_rect, self, &target_rect,
+                                        uniforms::MagnifySamplerFilter::Nearest);
+    }
+
+    /// Copies a rectangle of the color buffer of the surface into a level of a texture, without
+    /// going through the CPU. This uses `glCopyTexSubImage2D`.
+    ///
+    /// `dest_offset` is the position in the level where the bottom-left corner of `source_rect`
+    /// is written. The rectangle isn't clipped: an error is returned if it isn't entirely inside
+    /// of the surface and of the level. The values of the surface and of the texture must be of
+    /// the same kind, and the surface can't be multisampled. Blit a multisampled surface to a
+    /// regular framebuffer first to resolve it.
+    ///
+    /// For the default framebuffer, the back buffer is read. For framebuffers with multiple
+    /// color attachments, the first attachment is read.
+    fn copy_to_texture(&self, texture: &texture::Texture2d, source_rect: &Rect,
+                       dest_offset: (u32, u32), level: u32) -> Result<(), framebuffer::ReadError>;
+}
+
+/// A surface whose color buffer can be read back in order to take screenshots.
+///
+/// This is implemented for the `Frame` and for the framebuffers that have a color attachment.
+/// `EmptyFrameBuffer` doesn't implement it, as it doesn't have any color buffer.
+pub trait CaptureSurface: Surface {
+    /// Reads the content of the color buffer of the surface.
+    ///
+    /// Contrary to the other functions that read images, the rows of the result are in
+    /// top-to-bottom order, which is what image encoders expect. The values are the ones that
+    /// are stored in the surface, which means that if the surface is sRGB the result matches
+    /// what is shown on the screen. Multisampled surfaces are resolved first.
+    ///
+    /// For the default framebuffer, the content of the back buffer is read, which means that
+    /// you should call this function before `finish`.
+    ///
+    /// Returns an error if a multisampled surface couldn't be resolved.
+    fn capture(&self) -> Result<texture::RawImage2d<'static, u8>, CaptureError>;
+
+    /// Same as `capture`, but sets the alpha component of each pixel to 255.
+    ///
+    /// The alpha channel of windows often contains garbage values.
+    fn capture_opaque(&self) -> Result<texture::RawImage2d<'static, u8>, CaptureError> {
+        let mut image = try!(self.capture());
+
+        for pixel in image.data.to_mut().chunks_mut(4) {
+            pixel[3] = 255;
+        }
+
+        Ok(image)
+    }
+
+    /// Reads the content of the color buffer of the surface into an image, with the alpha
+    /// component of each pixel set to 255.
+    ///
+    /// See `capture_opaque`.
+    #[cfg(feature = "image")]
+    fn capture_to_image(&self) -> Result<image::RgbaImage, CaptureError> {
+        let image = try!(self.capture_opaque());
+        // the size of the data always matches the dimensions
+        Ok(image::ImageBuffer::from_raw(image.width, image.height, image.data.into_owned())
+                              .unwrap())
+    }
 }
 
 /// Private trait for framebuffer-like objects that provide attachments.
@@ -856,6 +1402,13 @@ pub enum DrawError {
         name: String,
     },
 
+    /// The offset of the buffer slice bound to a uniform block is not a multiple of
+    /// `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`.
+    UniformBufferOffsetNotAligned {
+        /// Name of the block you are trying to bind.
+        name: String,
+    },
+
     /// The layout of the content of the uniform buffer does not match the layout of the block.
     UniformBlockLayoutMismatch {
         /// Name of the block you are trying to bind.
@@ -870,6 +1423,18 @@ pub enum DrawError {
     /// Trying to use tessellation, but this is not supported by the underlying hardware.
     TessellationNotSupported,
 
+    /// A texture bound to a uniform can't be sampled because it is incomplete.
+    ///
+    /// This is only checked when compiling with `debug_assertions`, except for textures whose
+    /// streaming write is not finished. Sampling an incomplete texture returns black or garbage
+    /// depending on the driver.
+    IncompleteTexture {
+        /// Name of the uniform the texture is bound to.
+        name: String,
+        /// The reason why the texture is incomplete.
+        reason: uniforms::IncompleteTextureReason,
+    },
+
     /// Using a program which contains tessellation shaders, but without submitting patches.
     TessellationWithoutPatches,
 
@@ -879,6 +1444,10 @@ pub enum DrawError {
     /// When you use instancing, all vertices sources must have the same size.
     InstancesCountMismatch,
 
+    /// One of the attributes of a per-vertex source has an instance divisor. Divisors can only
+    /// be used with sources that are used per instance.
+    DivisorOnPerVertexSource,
+
     /// If you don't use indices, then all vertices sources must have the same size.
     VerticesSourcesLengthMismatch,
 
@@ -894,14 +1463,105 @@ pub enum DrawError {
     /// The requested provoking vertex is not supported by the backend.
     ProvokingVertexNotSupported,
 
+    /// One of the clip planes enabled in `clip_planes_bitmask` is superior or equal to the
+    /// maximum number of clip distances supported by the backend.
+    ClipPlaneIndexOutOfBounds,
+
     /// Discarding rasterizer output isn't supported by the backend.
     RasterizerDiscardNotSupported,
 
     /// Depth clamping isn't supported by the backend.
     DepthClampNotSupported,
 
+    /// A logic operation has been requested, but logic operations are not supported by the
+    /// backend. This is the case of OpenGL ES.
+    LogicOpNotSupported,
+
     /// One of the blending parameters is not supported by the backend.
     BlendingParameterNotSupported,
+
+    /// The OpenGL context has been lost. All the objects associated to the display must be
+    /// destroyed and recreated. See `SwapBuffersError::ContextLost`.
+    ContextLost,
+
+    /// One of the objects used for drawing belongs to a context that doesn't share its
+    /// objects with the context of the target.
+    WrongContext {
+        /// Kind of object, like `"program"`, `"vertex buffer"` or `"texture"`.
+        object_kind: &'static str,
+    },
+
+    /// One of the buffers used for drawing has been created with a streamed upload that has
+    /// been interrupted, or is the target of a streaming write that is not finished, and its
+    /// content is partially undefined.
+    IncompleteBuffer {
+        /// Kind of buffer, like `"vertex buffer"` or `"index buffer"`.
+        object_kind: &'static str,
+    },
+
+    /// Trying to draw multiple instances, but instancing is not supported by the backend.
+    ///
+    /// Requires OpenGL 3.1, OpenGL ES 3.0 or the `GL_ARB_draw_instanced` extension.
+    InstancingNotSupported,
+
+    /// Blending is enabled, but the backend doesn't support blending on the 32 bits
+    /// floating-point color attachments of the target.
+    ///
+    /// On OpenGL ES, this requires the `GL_EXT_float_blend` extension.
+    FloatBlendingNotSupported,
+
+    /// One of the blending factors uses the second output of the fragment shader, but
+    /// dual-source blending is not supported by the backend.
+    ///
+    /// Requires OpenGL 3.3 or the `GL_ARB_blend_func_extended` or `GL_EXT_blend_func_extended`
+    /// extensions.
+    DualSourceBlendingNotSupported,
+
+    /// Dual-source blending is used, but the target has more color attachments than the
+    /// backend supports with dual-source blending.
+    TooManyDualSourceAttachments {
+        /// Number of color attachments of the target.
+        attachments: u32,
+        /// Maximum number of color attachments with dual-source blending.
+        max: u32,
+    },
+
+    /// The program uses a global uniform block whose buffer has been destroyed.
+    ///
+    /// See `Context::set_global_uniform_block`.
+    GlobalUniformBlockUnbound {
+        /// Name of the block.
+        name: String,
+    },
+
+    /// A texture sampled by the program is also attached to the framebuffer that is drawn
+    /// upon, and the mipmap levels that can be sampled include the attached level.
+    ///
+    /// Reading from a texture while rendering to it produces undefined values. See
+    /// `SimpleFrameBuffer::draw_with_feedback_loops` if this is intended.
+    FeedbackLoop {
+        /// Name of the uniform the texture is bound to.
+        texture: String,
+        /// Where the texture is attached to the framebuffer.
+        attachment: framebuffer::AttachmentPoint,
+    },
+
+    /// A `UniformHandle` has been obtained from a program other than the one used to draw, or
+    /// before the program was reloaded.
+    UniformHandleProgramMismatch {
+        /// Name of the uniform.
+        name: String,
+    },
+
+    /// The vertex buffer that glium uses to fill surfaces couldn't be created.
+    ///
+    /// See `Surface::fill_with_texture` and `Surface::fill_with_program`.
+    FillVertexBufferCreation(vertex::BufferCreationError),
+
+    /// The program that glium uses to fill surfaces with a texture couldn't be created.
+    ///
+    /// See `Surface::fill_with_texture`.
+    FillProgramCreation(program::ProgramChooserCreationError),
 }
 
 impl std::fmt::Display for DrawError {
@@ -928,6 +1588,10 @@ impl std::fmt::Display for DrawError {
             &DrawError::UniformValueToBlock { ref name } => {
                 write!(fmt, "Tried to bind a single uniform value to a uniform block.")
             },
+            &DrawError::UniformBufferOffsetNotAligned { .. } => {
+                write!(fmt, "The offset of the buffer bound to a uniform block is not a multiple \
+                             of the uniform buffer offset alignment.")
+            },
             &DrawError::UniformBlockLayoutMismatch { ref name, .. } => {
                 write!(fmt, "The layout of the content of the uniform buffer does not match \
                              the layout of the block.")
@@ -938,6 +1602,9 @@ impl std::fmt::Display for DrawError {
             &DrawError::TessellationNotSupported => write!(fmt, "Trying to use tessellation, but \
                                                                  this is not supported by the \
                                                                  underlying hardware."),
+            &DrawError::IncompleteTexture { ref name, ref reason } => {
+                write!(fmt, "The texture bound to the uniform `{}` is incomplete: {}", name, reason)
+            },
             &DrawError::TessellationWithoutPatches => write!(fmt, "Using a program which contains \
                                                                    tessellation shaders, but \
                                                                    without submitting patches."),
@@ -946,6 +1613,9 @@ impl std::fmt::Display for DrawError {
             &DrawError::InstancesCountMismatch => write!(fmt, "When you use instancing, all \
                                                                vertices sources must have the \
                                                                same size"),
+            &DrawError::DivisorOnPerVertexSource => write!(fmt, "An attribute of a per-vertex \
+                                                                 source has an instance \
+                                                                 divisor."),
             &DrawError::VerticesSourcesLengthMismatch => write!(fmt, "If you don't use indices, \
                                                                       then all vertices sources \
                                                                       must have the same size."),
@@ -958,13 +1628,298 @@ impl std::fmt::Display for DrawError {
             &DrawError::ProvokingVertexNotSupported => write!(fmt, "Trying to set the provoking \
                                                                     vertex, but this is not \
                                                                     supported by the backend."),
+            &DrawError::ClipPlaneIndexOutOfBounds => write!(fmt, "One of the requested clip \
+                                                                  planes is out of the range \
+                                                                  supported by the backend."),
             &DrawError::RasterizerDiscardNotSupported => write!(fmt, "Discarding rasterizer \
                                                                       output is not supported by
                                                                       the backend."),
             &DrawError::DepthClampNotSupported => write!(fmt, "The depth clamp mode is not \
                                                                supported by the backend."),
+            &DrawError::LogicOpNotSupported => write!(fmt, "Logic operations are not supported \
+                                                            by the backend."),
             &DrawError::BlendingParameterNotSupported => write!(fmt, "One the blending parameters is not \
                                                                       supported by the backend."),
+            &DrawError::ContextLost => write!(fmt, "The OpenGL context has been lost."),
+            &DrawError::WrongContext { object_kind } => write!(fmt, "A {} belongs to a context \
+                                                                     that doesn't share its \
+                                                                     objects with the target.",
+                                                                    object_kind),
+            &DrawError::IncompleteBuffer { object_kind } => write!(fmt, "The upload of a {} is \
+                                                                         not finished or has \
+                                                                         been interrupted.",
+                                                                        object_kind),
+            &DrawError::InstancingNotSupported => write!(fmt, "Instancing is not supported by \
+                                                               the backend."),
+            &DrawError::FloatBlendingNotSupported => write!(fmt, "Blending on floating-point \
+                                                                  attachments is not supported \
+                                                                  by the backend."),
+            &DrawError::DualSourceBlendingNotSupported => {
+                write!(fmt, "Dual-source blending is not supported by the backend.")
+            },
+            &DrawError::TooManyDualSourceAttachments { attachments, max } => {
+                write!(fmt, "The target has {} color attachments, but only {} are supported \
+                             with dual-source blending.", attachments, max)
+            },
+            &DrawError::GlobalUniformBlockUnbound { ref name } => {
+                write!(fmt, "The buffer of the global uniform block `{}` has been destroyed.",
+                       name)
+            },
+            &DrawError::FeedbackLoop { ref texture, ref attachment } => {
+                write!(fmt, "The texture bound to the uniform `{}` is also attached to the \
+                             target ({:?}).", texture, attachment)
+            },
+            &DrawError::UniformHandleProgramMismatch { ref name } => {
+                write!(fmt, "The handle of the uniform `{}` belongs to another program or \
+                             has been obtained before the program was reloaded.", name)
+            },
+            &DrawError::FillVertexBufferCreation(ref err) => {
+                write!(fmt, "Failed to create the vertex buffer used to fill surfaces: {}", err)
+            },
+            &DrawError::FillProgramCreation(ref err) => {
+                write!(fmt, "Failed to create the program used to fill surfaces: {}", err)
+            },
+        }
+    }
+}
+
+impl std::error::Error for DrawError {
+    fn description(&self) -> &str {
+        match self {
+            &DrawError::NoDepthBuffer => {
+                "A depth function has been requested but no depth buffer is available"
+            },
+            &DrawError::AttributeTypeMismatch => {
+                "The type of a vertex attribute in the vertices source doesn't match what the \
+                 program requires"
+            },
+            &DrawError::AttributeMissing => {
+                "One of the attributes required by the program is missing from the vertex format"
+            },
+            &DrawError::ViewportTooLarge => {
+                "The viewport's dimensions are not supported by the backend"
+            },
+            &DrawError::InvalidDepthRange => "The depth range is outside of the `(0, 1)` range",
+            &DrawError::UniformTypeMismatch { .. } => {
+                "The type of a uniform doesn't match what the program requires"
+            },
+            &DrawError::UniformBufferToValue { .. } => {
+                "Tried to bind a uniform buffer to a single uniform value"
+            },
+            &DrawError::UniformValueToBlock { .. } => {
+                "Tried to bind a single uniform value to a uniform block"
+            },
+            &DrawError::UniformBufferOffsetNotAligned { .. } => {
+                "The offset of the buffer bound to a uniform block is not a multiple of the \
+                 uniform buffer offset alignment"
+            },
+            &DrawError::UniformBlockLayoutMismatch { .. } => {
+                "The layout of the content of the uniform buffer does not match the layout of \
+                 the block"
+            },
+            &DrawError::UnsupportedVerticesPerPatch => {
+                "The number of vertices per patch that has been requested is not supported"
+            },
+            &DrawError::TessellationNotSupported => {
+                "Trying to use tessellation, but this is not supported by the underlying hardware"
+            },
+            &DrawError::IncompleteTexture { .. } => "A texture bound to a uniform is incomplete",
+            &DrawError::TessellationWithoutPatches => {
+                "Using a program which contains tessellation shaders, but without submitting \
+                 patches"
+            },
+            &DrawError::SamplersNotSupported => {
+                "Trying to use a sampler, but they are not supported by the backend"
+            },
+            &DrawError::InstancesCountMismatch => {
+                "When you use instancing, all vertices sources must have the same size"
+            },
+            &DrawError::DivisorOnPerVertexSource => {
+                "An attribute of a per-vertex source has an instance divisor"
+            },
+            &DrawError::VerticesSourcesLengthMismatch => {
+                "If you don't use indices, then all vertices sources must have the same size"
+            },
+            &DrawError::TransformFeedbackNotSupported => {
+                "Requested not to draw primitives, but this is not supported by the backend"
+            },
+            &DrawError::WrongQueryOperation => "Wrong query operation",
+            &DrawError::SmoothingNotSupported => {
+                "Trying to use smoothing, but this is not supported by the backend"
+            },
+            &DrawError::ProvokingVertexNotSupported => {
+                "Trying to set the provoking vertex, but this is not supported by the backend"
+            },
+            &DrawError::ClipPlaneIndexOutOfBounds => {
+                "One of the requested clip planes is out of the range supported by the backend"
+            },
+            &DrawError::RasterizerDiscardNotSupported => {
+                "Discarding rasterizer output is not supported by the backend"
+            },
+            &DrawError::DepthClampNotSupported => {
+                "The depth clamp mode is not supported by the backend"
+            },
+            &DrawError::LogicOpNotSupported => "Logic operations are not supported by the backend",
+            &DrawError::BlendingParameterNotSupported => {
+                "One the blending parameters is not supported by the backend"
+            },
+            &DrawError::ContextLost => "The OpenGL context has been lost",
+            &DrawError::WrongContext { .. } => {
+                "An object belongs to a context that doesn't share its objects with the target"
+            },
+            &DrawError::IncompleteBuffer { .. } => {
+                "The upload of a buffer is not finished or has been interrupted"
+            },
+            &DrawError::InstancingNotSupported => "Instancing is not supported by the backend",
+            &DrawError::FloatBlendingNotSupported => {
+                "Blending on floating-point attachments is not supported by the backend"
+            },
+            &DrawError::DualSourceBlendingNotSupported => {
+                "Dual-source blending is not supported by the backend"
+            },
+            &DrawError::TooManyDualSourceAttachments { .. } => {
+                "The target has too many color attachments for dual-source blending"
+            },
+            &DrawError::GlobalUniformBlockUnbound { .. } => {
+                "The buffer of a global uniform block has been destroyed"
+            },
+            &DrawError::FeedbackLoop { .. } => {
+                "A texture bound to a uniform is also attached to the target"
+            },
+            &DrawError::UniformHandleProgramMismatch { .. } => {
+                "The handle of a uniform belongs to another program or has been obtained before \
+                 the program was reloaded"
+            },
+            &DrawError::FillVertexBufferCreation(_) => {
+                "Failed to create the vertex buffer used to fill surfaces"
+            },
+            &DrawError::FillProgramCreation(_) => {
+                "Failed to create the program used to fill surfaces"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&std::error::Error> {
+        match self {
+            &DrawError::UniformBlockLayoutMismatch { ref err, .. } => Some(err),
+            &DrawError::IncompleteTexture { ref reason, .. } => Some(reason),
+            &DrawError::FillVertexBufferCreation(ref err) => Some(err),
+            &DrawError::FillProgramCreation(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Error that can happen while blitting.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlitError {
+    /// The source or the target doesn't have a depth buffer.
+    NoDepthBuffer,
+
+    /// The depth buffers of the source and of the target don't have the same format.
+    DepthFormatMismatch {
+        /// Format of the depth buffer of the source.
+        source: texture::TextureFormat,
+        /// Format of the depth buffer of the target.
+        target: texture::TextureFormat,
+    },
+
+    /// The stencil buffers of the source and of the target don't have the same format.
+    StencilFormatMismatch {
+        /// Format of the stencil buffer of the source.
+        source: texture::TextureFormat,
+        /// Format of the stencil buffer of the target.
+        target: texture::TextureFormat,
+    },
+
+    /// The source color buffer contains signed integers and one of the target color buffers
+    /// doesn't, or the other way around. Same for unsigned integers.
+    IntegerColorMismatch,
+
+    /// The source and the target are both multisampled but don't have the same number of
+    /// samples.
+    SamplesCountMismatch {
+        /// Number of samples of the source.
+        source: u32,
+        /// Number of samples of the target.
+        target: u32,
+    },
+
+    /// The source or the target is multisampled, and the source and target rectangles don't
+    /// have the same dimensions.
+    MultisampleRectMismatch,
+
+    /// The `Linear` filter has been requested while copying a depth or stencil buffer or a
+    /// color buffer that contains integers.
+    LinearFilterNotSupported,
+
+    /// The source doesn't have an attachment for one of the requested buffers.
+    NoAttachment,
+
+    /// The framebuffer of the source or of the target couldn't be built.
+    FramebufferValidation(framebuffer::ValidationError),
+}
+
+impl std::fmt::Display for BlitError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        use std::error::Error;
+
+        match self {
+            &BlitError::DepthFormatMismatch { source, target } => {
+                write!(fmt, "{} (source: {:?}, target: {:?})", self.description(), source, target)
+            },
+            &BlitError::StencilFormatMismatch { source, target } => {
+                write!(fmt, "{} (source: {:?}, target: {:?})", self.description(), source, target)
+            },
+            &BlitError::SamplesCountMismatch { source, target } => {
+                write!(fmt, "{} (source: {}, target: {})", self.description(), source, target)
+            },
+            &BlitError::FramebufferValidation(ref err) => {
+                write!(fmt, "{}: {}", self.description(), err)
+            },
+            _ => fmt.write_str(self.description()),
+        }
+    }
+}
+
+impl std::error::Error for BlitError {
+    fn description(&self) -> &str {
+        match self {
+            &BlitError::NoDepthBuffer => "The source or the target doesn't have a depth buffer",
+            &BlitError::DepthFormatMismatch { .. } => {
+                "The depth buffers of the source and the target don't have the same format"
+            },
+            &BlitError::StencilFormatMismatch { .. } => {
+                "The stencil buffers of the source and the target don't have the same format"
+            },
+            &BlitError::IntegerColorMismatch => {
+                "The color buffers of the source and the target don't contain the same kind \
+                 of integers"
+            },
+            &BlitError::SamplesCountMismatch { .. } => {
+                "The source and the target don't have the same number of samples"
+            },
+            &BlitError::MultisampleRectMismatch => {
+                "When blitting from or to a multisampled surface, the source and target \
+                 rectangles must have the same dimensions"
+            },
+            &BlitError::LinearFilterNotSupported => {
+                "The linear filter can't be used to copy depth, stencil or integral values"
+            },
+            &BlitError::NoAttachment => {
+                "The source doesn't have an attachment for the requested buffer"
+            },
+            &BlitError::FramebufferValidation(_) => {
+                "The framebuffer of the source or of the target couldn't be built"
+            },
+        }
+    }
+
+    fn cause(&self) -> Option<&std::error::Error> {
+        match self {
+            &BlitError::FramebufferValidation(ref err) => Some(err),
+            _ => None,
         }
     }
 }
@@ -982,56 +1937,476 @@ pub enum SwapBuffersError {
     /// A context loss usually happens on mobile devices when the user puts the application on
     /// sleep and wakes it up later. However any OpenGL implementation can theoretically lose the
     /// context at any time. Can only happen if calling `is_context_loss_possible()` returns true.
-    ContextLost,
-    /// The buffers have already been swapped.
     ///
-    /// This error can be returned when `set_finish()` is called multiple times, or `finish()` is
-    /// called after `set_finish()`.
-    AlreadySwapped,
+    /// In order to recover, drop every glium object (including the `Display`) and build
+    /// everything again. Dropping objects after a context loss is harmless.
+    ContextLost,
+}
+
+impl std::fmt::Display for SwapBuffersError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let self_error = self as &std::error::Error;
+        formatter.write_str(self_error.description())
+    }
+}
+
+impl std::error::Error for SwapBuffersError {
+    #[inline]
+    fn description(&self) -> &str {
+        match self {
+            &SwapBuffersError::ContextLost => "The OpenGL context has been lost",
+        }
+    }
+}
+
+/// Error that can happen while capturing the content of a surface.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CaptureError {
+    /// Asynchronous captures are not supported by the backend. They require pixel buffers and
+    /// sync fences.
+    NotSupported,
+
+    /// Failed to create the pixel buffer that receives the pixels of an asynchronous capture.
+    BufferCreation(buffer::BufferCreationError),
+
+    /// Failed to create the temporary texture that a multisampled surface is resolved into.
+    TextureCreation(texture::TextureCreationError),
+
+    /// Failed to build the framebuffer of the temporary texture.
+    FramebufferValidation(framebuffer::ValidationError),
+
+    /// Failed to resolve a multisampled surface into the temporary texture.
+    Blit(BlitError),
+
+    /// Failed to map the pixel buffer of an asynchronous capture.
+    Map(buffer::MapError),
+}
+
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        use std::error::Error;
+
+        match self.cause() {
+            Some(cause) => write!(formatter, "{}: {}", self.description(), cause),
+            None => formatter.write_str(self.description()),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {
+    fn description(&self) -> &str {
+        match self {
+            &CaptureError::NotSupported => {
+                "Asynchronous captures are not supported by the backend"
+            },
+            &CaptureError::BufferCreation(_) => "Failed to create the pixel buffer of the capture",
+            &CaptureError::TextureCreation(_) => {
+                "Failed to create the texture used to resolve the multisampled surface"
+            },
+            &CaptureError::FramebufferValidation(_) => {
+                "Failed to create the framebuffer used to resolve the multisampled surface"
+            },
+            &CaptureError::Blit(_) => "Failed to resolve the multisampled surface",
+            &CaptureError::Map(_) => "Failed to map the pixel buffer of the capture",
+        }
+    }
+
+    fn cause(&self) -> Option<&std::error::Error> {
+        match self {
+            &CaptureError::NotSupported => None,
+            &CaptureError::BufferCreation(ref err) => Some(err),
+            &CaptureError::TextureCreation(ref err) => Some(err),
+            &CaptureError::FramebufferValidation(ref err) => Some(err),
+            &CaptureError::Blit(ref err) => Some(err),
+            &CaptureError::Map(ref err) => Some(err),
+        }
+    }
+}
+
+/// Any error that can be returned by glium.
+///
+/// All the error types of glium can be converted into this type, which allows you to use
+/// `try!` on the results of functions that return different kinds of errors.
+#[derive(Debug)]
+pub enum Error {
+    /// Error while creating a buffer.
+    BufferCreation(buffer::BufferCreationError),
+    /// Error while creating a vertex buffer.
+    VertexBufferCreation(vertex::BufferCreationError),
+    /// Error while creating an index buffer.
+    IndexBufferCreation(index::BufferCreationError),
+    /// Error while creating a texture.
+    TextureCreation(texture::TextureCreationError),
+    /// Error while creating a program.
+    ProgramCreation(ProgramCreationError),
+    /// Error while drawing.
+    Draw(DrawError),
+    /// Error while reading from a buffer.
+    BufferRead(buffer::ReadError),
+    /// Error while reading an image of a texture.
+    TextureRead(texture::ReadImageError),
+    /// Error while validating the attachments of a framebuffer.
+    FramebufferValidation(framebuffer::ValidationError),
+    /// Error while swapping buffers.
+    SwapBuffers(SwapBuffersError),
+    /// Error while mapping a buffer.
+    BufferMap(buffer::MapError),
+    /// Error while writing data to a buffer.
+    BufferWrite(buffer::WriteError),
+    /// Error while copying data between buffers.
+    BufferCopy(buffer::CopyError),
+    /// Error while clearing a buffer.
+    BufferClear(buffer::ClearError),
+    /// Error while writing rows of pixels to a texture.
+    TextureWriteRows(texture::WriteRowsError),
+    /// Error while reading or writing a compressed mipmap level.
+    CompressedLevel(texture::CompressedLevelError),
+    /// Error while reading the content of a surface.
+    FramebufferRead(framebuffer::ReadError),
+    /// Error while blitting between surfaces.
+    Blit(BlitError),
+    /// Error while writing the result of a query to a buffer.
+    QueryToBuffer(draw_parameters::ToBufferError),
+    /// Error while prewarming a program.
+    ProgramPrewarm(program::PrewarmError),
+    /// Error while setting a global uniform block.
+    GlobalUniformBlock(uniforms::GlobalUniformBlockError),
+    /// Error while flipping the winding of an index buffer.
+    FlipWinding(index::FlipWindingError),
+    /// Error while deserializing a vertex format.
+    VertexFormatDeserialization(vertex::VertexFormatDeserializationError),
+    /// Error while capturing the content of a surface.
+    Capture(CaptureError),
+    /// Error while changing the swap interval.
+    SwapIntervalNotSupported(backend::SwapIntervalNotSupportedError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            &Error::BufferCreation(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::VertexBufferCreation(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::IndexBufferCreation(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::TextureCreation(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::ProgramCreation(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::Draw(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::BufferRead(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::TextureRead(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::FramebufferValidation(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::SwapBuffers(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::BufferMap(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::BufferWrite(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::BufferCopy(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::BufferClear(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::TextureWriteRows(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::CompressedLevel(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::FramebufferRead(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::Blit(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::QueryToBuffer(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::ProgramPrewarm(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::GlobalUniformBlock(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::FlipWinding(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::VertexFormatDeserialization(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::Capture(ref err) => std::fmt::Display::fmt(err, formatter),
+            &Error::SwapIntervalNotSupported(ref err) => std::fmt::Display::fmt(err, formatter),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn description(&self) -> &str {
+        match self {
+            &Error::BufferCreation(ref err) => std::error::Error::description(err),
+            &Error::VertexBufferCreation(ref err) => std::error::Error::description(err),
+            &Error::IndexBufferCreation(ref err) => std::error::Error::description(err),
+            &Error::TextureCreation(ref err) => std::error::Error::description(err),
+            &Error::ProgramCreation(ref err) => std::error::Error::description(err),
+            &Error::Draw(ref err) => std::error::Error::description(err),
+            &Error::BufferRead(ref err) => std::error::Error::description(err),
+            &Error::TextureRead(ref err) => std::error::Error::description(err),
+            &Error::FramebufferValidation(ref err) => std::error::Error::description(err),
+            &Error::SwapBuffers(ref err) => std::error::Error::description(err),
+            &Error::BufferMap(ref err) => std::error::Error::description(err),
+            &Error::BufferWrite(ref err) => std::error::Error::description(err),
+            &Error::BufferCopy(ref err) => std::error::Error::description(err),
+            &Error::BufferClear(ref err) => std::error::Error::description(err),
+            &Error::TextureWriteRows(ref err) => std::error::Error::description(err),
+            &Error::CompressedLevel(ref err) => std::error::Error::description(err),
+            &Error::FramebufferRead(ref err) => std::error::Error::description(err),
+            &Error::Blit(ref err) => std::error::Error::description(err),
+            &Error::QueryToBuffer(ref err) => std::error::Error::description(err),
+            &Error::ProgramPrewarm(ref err) => std::error::Error::description(err),
+            &Error::GlobalUniformBlock(ref err) => std::error::Error::description(err),
+            &Error::FlipWinding(ref err) => std::error::Error::description(err),
+            &Error::VertexFormatDeserialization(ref err) => std::error::Error::description(err),
+            &Error::Capture(ref err) => std::error::Error::description(err),
+            &Error::SwapIntervalNotSupported(ref err) => std::error::Error::description(err),
+        }
+    }
+
+    fn cause(&self) -> Option<&std::error::Error> {
+        match self {
+            &Error::BufferCreation(ref err) => Some(err),
+            &Error::VertexBufferCreation(ref err) => Some(err),
+            &Error::IndexBufferCreation(ref err) => Some(err),
+            &Error::TextureCreation(ref err) => Some(err),
+            &Error::ProgramCreation(ref err) => Some(err),
+            &Error::Draw(ref err) => Some(err),
+            &Error::BufferRead(ref err) => Some(err),
+            &Error::TextureRead(ref err) => Some(err),
+            &Error::FramebufferValidation(ref err) => Some(err),
+            &Error::SwapBuffers(ref err) => Some(err),
+            &Error::BufferMap(ref err) => Some(err),
+            &Error::BufferWrite(ref err) => Some(err),
+            &Error::BufferCopy(ref err) => Some(err),
+            &Error::BufferClear(ref err) => Some(err),
+            &Error::TextureWriteRows(ref err) => Some(err),
+            &Error::CompressedLevel(ref err) => Some(err),
+            &Error::FramebufferRead(ref err) => Some(err),
+            &Error::Blit(ref err) => Some(err),
+            &Error::QueryToBuffer(ref err) => Some(err),
+            &Error::ProgramPrewarm(ref err) => Some(err),
+            &Error::GlobalUniformBlock(ref err) => Some(err),
+            &Error::FlipWinding(ref err) => Some(err),
+            &Error::VertexFormatDeserialization(ref err) => Some(err),
+            &Error::Capture(ref err) => Some(err),
+            &Error::SwapIntervalNotSupported(ref err) => Some(err),
+        }
+    }
+}
+
+macro_rules! impl_from_for_error {
+    ($ty:ty, $variant:ident) => (
+        impl std::convert::From<$ty> for Error {
+            #[inline]
+            fn from(err: $ty) -> Error {
+                Error::$variant(err)
+            }
+        }
+    )
+}
+
+impl_from_for_error!(buffer::BufferCreationError, BufferCreation);
+impl_from_for_error!(vertex::BufferCreationError, VertexBufferCreation);
+impl_from_for_error!(index::BufferCreationError, IndexBufferCreation);
+impl_from_for_error!(texture::TextureCreationError, TextureCreation);
+impl_from_for_error!(ProgramCreationError, ProgramCreation);
+impl_from_for_error!(DrawError, Draw);
+impl_from_for_error!(buffer::ReadError, BufferRead);
+impl_from_for_error!(texture::ReadImageError, TextureRead);
+impl_from_for_error!(framebuffer::ValidationError, FramebufferValidation);
+impl_from_for_error!(SwapBuffersError, SwapBuffers);
+impl_from_for_error!(buffer::MapError, BufferMap);
+impl_from_for_error!(buffer::WriteError, BufferWrite);
+impl_from_for_error!(buffer::CopyError, BufferCopy);
+impl_from_for_error!(buffer::ClearError, BufferClear);
+impl_from_for_error!(texture::WriteRowsError, TextureWriteRows);
+impl_from_for_error!(texture::CompressedLevelError, CompressedLevel);
+impl_from_for_error!(framebuffer::ReadError, FramebufferRead);
+impl_from_for_error!(BlitError, Blit);
+impl_from_for_error!(draw_parameters::ToBufferError, QueryToBuffer);
+impl_from_for_error!(program::PrewarmError, ProgramPrewarm);
+impl_from_for_error!(uniforms::GlobalUniformBlockError, GlobalUniformBlock);
+impl_from_for_error!(index::FlipWindingError, FlipWinding);
+impl_from_for_error!(vertex::VertexFormatDeserializationError, VertexFormatDeserialization);
+impl_from_for_error!(CaptureError, Capture);
+impl_from_for_error!(backend::SwapIntervalNotSupportedError, SwapIntervalNotSupported);
+
+/// Describes what a `Frame` does when it is dropped without `finish` having been called.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FinishBehavior {
+    /// The buffers are swapped and a warning is printed in debug builds. This is the default.
+    SwapOnDrop,
+
+    /// The buffers are not swapped and the content of the frame is discarded. Use this if you
+    /// decide to abandon rendering in the middle of a frame, for example because the window
+    /// has been minimized.
+    DiscardOnDrop,
+}
+
+impl Default for FinishBehavior {
+    #[inline]
+    fn default() -> FinishBehavior {
+        FinishBehavior::SwapOnDrop
+    }
 }
 
 /// Implementation of `Surface`, targeting the default framebuffer.
 ///
-/// The back- and front-buffers are swapped when you call `finish`.
+/// The back- and front-buffers are swapped when you call `finish`. Since `finish` consumes
+/// the `Frame`, it is not possible to use a frame after it has been finished.
+///
+/// If the `Frame` is dropped without `finish` being called, the buffers are swapped anyway and
+/// a warning is printed in debug builds. You can change this with `set_finish_behavior`. If the
+/// thread is panicking, the buffers are never swapped.
+///
+/// ## Ordering
+///
+/// The operations on a `Frame` (clears, draws, blits and reads) are submitted to OpenGL
+/// strictly in the order in which they are called, and overlapping draws are therefore blended
+/// in call order. The only operation that glium delays is a clear, so that it can be merged with
+/// the next clear of the same surface, but it is always submitted before any other operation
+/// that follows it.
 ///
-/// You **must** call either `finish` or `set_finish` or else the destructor will panic.
+/// Submitted commands are only guaranteed to start executing on the GPU when the frame is
+/// finished. Call `flush` to make them start earlier.
 pub struct Frame {
     context: Rc<Context>,
     dimensions: (u32, u32),
-    destroyed: bool,        // TODO: use a linear type instead.
+    destroyed: bool,
+    finish_behavior: FinishBehavior,
+    async_captures: ops::AsyncCaptureQueue,
 }
 
 impl Frame {
     /// Builds a new `Frame`. Use the `draw` function on `Display` instead of this function.
     #[inline]
     pub fn new(context: Rc<Context>, dimensions: (u32, u32)) -> Frame {
+        Frame::with_async_captures(context, dimensions, Rc::new(RefCell::new(VecDeque::new())))
+    }
+
+    /// Builds a new `Frame` that delivers the given asynchronous captures when it is finished.
+    #[inline]
+    fn with_async_captures(context: Rc<Context>, dimensions: (u32, u32),
+                           async_captures: ops::AsyncCaptureQueue) -> Frame
+    {
         Frame {
             context: context,
             dimensions: dimensions,
             destroyed: false,
+            finish_behavior: FinishBehavior::SwapOnDrop,
+            async_captures: async_captures,
         }
     }
 
-    /// Stop drawing, swap the buffers, and consume the Frame.
+    /// Starts executing the commands that have been submitted so far, without waiting for them
+    /// to finish.
     ///
-    /// See the documentation of `SwapBuffersError` about what is being returned.
+    /// This calls `glFlush`. It can be used in the middle of a long frame so that the GPU
+    /// starts working before `finish` is called.
+    #[inline]
+    pub fn flush(&self) {
+        self.context.flush();
+    }
+
+    /// Converts a position in the window, for example the position of the mouse, into the
+    /// coordinates of the pixel of this frame that contains it.
+    ///
+    /// Uses the dimensions of the frame, which don't change if the window is resized while
+    /// the frame is being drawn. See `Context::window_to_framebuffer_coords`.
     #[inline]
-    pub fn finish(mut self) -> Result<(), SwapBuffersError> {
-        self.set_finish()
+    pub fn window_to_framebuffer_coords(&self, position: (f64, f64)) -> (i32, i32) {
+        utils::coords::window_to_framebuffer(self.context.get_window_dimensions(),
+                                             self.dimensions, position)
     }
 
-    /// Stop drawing, swap the buffers.
+    /// Returns a rectangle of `size` pixels of this frame centered on a position in the
+    /// window, clamped to the dimensions of the frame. The rectangle can be passed to
+    /// `read_into`.
     ///
-    /// The Frame can now be dropped regularly.  Calling `finish()` or `set_finish()` again will
-    /// cause `Err(SwapBuffersError::AlreadySwapped)` to be returned.
+    /// See `Context::pick_rect_at`.
+    #[inline]
+    pub fn pick_rect_at(&self, position: (f64, f64), size: (u32, u32)) -> Rect {
+        utils::coords::pick_rect(self.context.get_window_dimensions(), self.dimensions,
+                                 position, size)
+    }
+
+    /// Reads a part of what has been drawn on this frame so far into an existing slice,
+    /// without allocating.
+    ///
+    /// The rectangle must be entirely inside of the frame and the length of `dest` must be
+    /// exactly `rect.width * rect.height * format.get_size()`, otherwise an error is returned.
+    /// This is useful to capture every frame into buffers that you reuse.
+    #[inline]
+    pub fn read_into(&self, rect: &Rect, dest: &mut [u8], format: texture::ClientFormat)
+                     -> Result<(), framebuffer::ReadError>
+    {
+        self.read_into_impl(rect, dest, format, false)
+    }
+
+    /// Same as `read_into`, except that the first row written to `dest` is the top of the
+    /// rectangle instead of the bottom.
     #[inline]
-    pub fn set_finish(&mut self) -> Result<(), SwapBuffersError> {
-        if self.destroyed {
-            return Err(SwapBuffersError::AlreadySwapped);
+    pub fn read_into_flipped(&self, rect: &Rect, dest: &mut [u8], format: texture::ClientFormat)
+                             -> Result<(), framebuffer::ReadError>
+    {
+        self.read_into_impl(rect, dest, format, true)
+    }
+
+    fn read_into_impl(&self, rect: &Rect, dest: &mut [u8], format: texture::ClientFormat,
+                      flip: bool) -> Result<(), framebuffer::ReadError>
+    {
+        let mut ctxt = self.context.make_current();
+        let read_buffer = fbo::default_color_buffer(&ctxt);
+
+        try!(ops::read::<_, _, u8>(&mut ctxt,
+                                   ops::Source::DefaultFramebuffer(read_buffer, self.dimensions),
+                                   rect, ops::Destination::Bytes(&mut *dest, format), false));
+
+        if flip {
+            ops::flip_rows(dest, rect.width as usize * format.get_size(), rect.height as usize);
         }
 
+        Ok(())
+    }
+
+    /// Stop drawing, swap the buffers, and consume the Frame.
+    ///
+    /// The callbacks of the captures started with `Display::capture_async` that have finished
+    /// are called after the buffers have been swapped.
+    ///
+    /// If the context is single-buffered, the commands are flushed with `glFlush` instead of
+    /// swapping buffers. See `Context::is_double_buffered`.
+    ///
+    /// See the documentation of `SwapBuffersError` about what is being returned.
+    #[inline]
+    pub fn finish(self) -> Result<(), SwapBuffersError> {
+        self.finish_with_statistics().map(|_| ())
+    }
+
+    /// Same as `finish`, but also returns the statistics of the frame and resets them.
+    ///
+    /// The statistics are only collected if they have been enabled with
+    /// `Context::set_frame_statistics_enabled`. Otherwise they are always zero.
+    pub fn finish_with_statistics(mut self) -> Result<context::FrameStatistics, SwapBuffersError> {
         self.destroyed = true;
-        self.context.swap_buffers()
+        let result = self.context.swap_buffers();
+        ops::poll_async_captures(&self.async_captures);
+        let statistics = self.context.take_frame_statistics();
+        result.map(|_| statistics)
+    }
+
+    /// Returns the time that glium has spent waiting for the GPU since the previous frame has
+    /// been finished.
+    ///
+    /// This doesn't include the swap of this frame, which happens in `finish`. Use
+    /// `finish_with_statistics` to obtain it. The statistics are only collected if they have
+    /// been enabled with `Context::set_frame_statistics_enabled`.
+    #[inline]
+    pub fn get_sync_statistics(&self) -> context::SyncStatistics {
+        self.context.get_frame_statistics().sync
+    }
+
+    /// Pushes a debug group that is popped when the returned object is destroyed.
+    ///
+    /// See `debug::DebugScope`.
+    #[inline]
+    pub fn debug_scope(&self, name: &str) -> debug::DebugScope {
+        debug::DebugScope::new(&self.context, name)
+    }
+
+    /// Inserts a debugging string in the commands queue.
+    ///
+    /// See `Context::insert_debug_marker`.
+    #[inline]
+    pub fn insert_debug_marker(&self, marker: &str) -> Result<(), ()> {
+        self.context.insert_debug_marker(marker)
+    }
+
+    /// Changes what happens if the `Frame` is dropped without `finish` being called.
+    #[inline]
+    pub fn set_finish_behavior(&mut self, behavior: FinishBehavior) {
+        self.finish_behavior = behavior;
     }
 }
 
@@ -1040,7 +2415,29 @@ impl Surface for Frame {
     fn clear(&mut self, rect: Option<&Rect>, color: Option<(f32, f32, f32, f32)>, color_srgb: bool,
              depth: Option<f32>, stencil: Option<i32>)
     {
-        ops::clear(&self.context, None, None, color, color_srgb, depth, stencil);
+        ops::clear(&self.context, None, rect, color, color_srgb, depth, stencil);
+    }
+
+    #[inline]
+    fn clear_color_integer(&mut self, attachment_index: u32, color: [i32; 4]) {
+        ops::clear_color_buffer(&self.context, None, None, attachment_index,
+                                ops::ClearBufferData::SignedInt(color));
+    }
+
+    #[inline]
+    fn clear_color_unsigned(&mut self, attachment_index: u32, color: [u32; 4]) {
+        ops::clear_color_buffer(&self.context, None, None, attachment_index,
+                                ops::ClearBufferData::UnsignedInt(color));
+    }
+
+    fn copy_to_texture(&self, texture: &texture::Texture2d, source_rect: &Rect,
+                       dest_offset: (u32, u32), level: u32) -> Result<(), framebuffer::ReadError>
+    {
+        let mut ctxt = self.context.make_current();
+        let read_buffer = fbo::default_color_buffer(&ctxt);
+        ops::copy_to_texture(&mut ctxt, ops::Source::DefaultFramebuffer(read_buffer,
+                                                                         self.dimensions),
+                             source_rect, texture, dest_offset, level)
     }
 
     fn get_dimensions(&self) -> (u32, u32) {
@@ -1081,7 +2478,13 @@ impl Surface for Frame {
         }
 
         ops::draw(&self.context, None, vertex_buffer, index_buffer.into(), program,
-                  uniforms, draw_parameters, (self.dimensions.0 as u32, self.dimensions.1 as u32))
+                  uniforms, draw_parameters, (self.dimensions.0 as u32, self.dimensions.1 as u32), true)
+    }
+
+    #[inline]
+    fn draw_command_list(&mut self, list: &CommandList) -> Result<(), DrawError> {
+        ops::replay(&self.context, None, list.get_commands(),
+                    (self.dimensions.0 as u32, self.dimensions.1 as u32), self.has_depth_buffer())
     }
 
     #[inline]
@@ -1092,29 +2495,60 @@ impl Surface for Frame {
     }
 
     #[inline]
-    fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
-                       filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers<S>(&self, source_rect: &Rect, target: &S, target_rect: &BlitTarget,
+                       filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                       -> Result<(), BlitError> where S: Surface
+    {
+        target.blit_buffers_from_frame(source_rect, target_rect, filter, mask)
+    }
+
+    #[inline]
+    fn blit_buffers_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
+                               filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                               -> Result<(), BlitError>
     {
         ops::blit(&self.context, None, self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_simple_framebuffer(&self, source: &framebuffer::SimpleFrameBuffer,
-                                    source_rect: &Rect, target_rect: &BlitTarget,
-                                    filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_simple_framebuffer(&self, source: &framebuffer::SimpleFrameBuffer,
+                                            source_rect: &Rect, target_rect: &BlitTarget,
+                                            filter: uniforms::MagnifySamplerFilter, mask: BlitMask)
+                                            -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
     }
 
     #[inline]
-    fn blit_from_multioutput_framebuffer(&self, source: &framebuffer::MultiOutputFrameBuffer,
-                                         source_rect: &Rect, target_rect: &BlitTarget,
-                                         filter: uniforms::MagnifySamplerFilter)
+    fn blit_buffers_from_multioutput_framebuffer(&self,
+                                                 source: &framebuffer::MultiOutputFrameBuffer,
+                                                 source_rect: &Rect, target_rect: &BlitTarget,
+                                                 filter: uniforms::MagnifySamplerFilter,
+                                                 mask: BlitMask)
+                                                 -> Result<(), BlitError>
+    {
+        ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+
+    #[inline]
+    fn blit_buffers_from_external_framebuffer(&self, source: &framebuffer::ExternalFramebuffer,
+                                              source_rect: &Rect, target_rect: &BlitTarget,
+                                              filter: uniforms::MagnifySamplerFilter,
+                                              mask: BlitMask)
+                                              -> Result<(), BlitError>
     {
         ops::blit(&self.context, source.get_attachments(), self.get_attachments(),
-                  gl::COLOR_BUFFER_BIT, source_rect, target_rect, filter.to_glenum())
+                  mask.to_glbitfield(), source_rect, target_rect, filter.to_glenum())
+    }
+}
+
+impl CaptureSurface for Frame {
+    #[inline]
+    fn capture(&self) -> Result<texture::RawImage2d<'static, u8>, CaptureError> {
+        ops::capture(&self.context, None, self.dimensions)
     }
 }
 
@@ -1126,12 +2560,23 @@ impl FboAttachments for Frame {
 }
 
 impl Drop for Frame {
-    #[inline]
     fn drop(&mut self) {
-        if !thread::panicking() {
-            assert!(self.destroyed, "The `Frame` object must be explicitly destroyed \
-                                     by calling `.finish()`");
+        if self.destroyed || thread::panicking() {
+            return;
         }
+
+        match self.finish_behavior {
+            FinishBehavior::SwapOnDrop => {
+                if cfg!(debug_assertions) {
+                    println!("glium warning: a `Frame` has been dropped without `finish` being \
+                              called. The buffers have been swapped automatically.");
+                }
+                let _ = self.context.swap_buffers();
+            },
+            FinishBehavior::DiscardOnDrop => (),
+        }
+
+        self.context.take_frame_statistics();
     }
 }
 
@@ -1184,6 +2629,10 @@ pub enum GliumCreationError<T> {
 
     /// The OpenGL implementation is too old.
     IncompatibleOpenGl(String),
+
+    /// The backend has requested a single-buffered context, but the default framebuffer that
+    /// has been created has a back buffer.
+    SingleBufferingNotSupported,
 }
 
 impl<T> std::fmt::Display for GliumCreationError<T> where T: std::error::Error {
@@ -1199,6 +2648,8 @@ impl<T> std::error::Error for GliumCreationError<T> where T: std::error::Error {
         match self {
             &GliumCreationError::BackendCreationError(_) => "Error while creating the backend",
             &GliumCreationError::IncompatibleOpenGl(_) => "The OpenGL implementation is too old to work with glium",
+            &GliumCreationError::SingleBufferingNotSupported => "Single-buffered contexts are \
+                                                                 not supported",
         }
     }
 
@@ -1207,6 +2658,7 @@ impl<T> std::error::Error for GliumCreationError<T> where T: std::error::Error {
         match self {
             &GliumCreationError::BackendCreationError(ref err) => Some(err as &std::error::Error),
             &GliumCreationError::IncompatibleOpenGl(_) => None,
+            &GliumCreationError::SingleBufferingNotSupported => None,
         }
     }
 }
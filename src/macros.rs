@@ -44,7 +44,26 @@ macro_rules! assert_no_gl_error {
 /// # fn main() {
 /// let uniforms = uniform! {
 ///     color: [1.0, 1.0, 0.0, 1.0],
-///     some_value: 12i32
+///     some_value: 12i32,
+/// };
+/// # }
+/// ```
+///
+/// Names that are only known at runtime can be passed between brackets, and the content of
+/// another object that implements `Uniforms` can be added with `..` at the end. Values that are
+/// passed explicitly have priority over the values of this object.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate glium;
+/// # fn main() {
+/// # let base = uniform! { color: [1.0, 1.0, 0.0, 1.0] };
+/// let name = format!("light{}", 3);
+///
+/// let uniforms = uniform! {
+///     some_value: 12i32,
+///     [name] => [0.0, 1.0, 0.0f32],
+///     ..base
 /// };
 /// # }
 /// ```
@@ -54,22 +73,70 @@ macro_rules! uniform {
         $crate::uniforms::EmptyUniforms
     };
 
-    ($field:ident: $value:expr) => {
-        $crate::uniforms::UniformsStorage::new(stringify!($field), $value)
+    (@build $uniforms:expr;) => {
+        $uniforms
     };
 
-    ($field1:ident: $value1:expr, $($field:ident: $value:expr),+) => {
-        {
-            let uniforms = $crate::uniforms::UniformsStorage::new(stringify!($field1), $value1);
-            $(
-                let uniforms = uniforms.add(stringify!($field), $value);
-            )+
-            uniforms
-        }
+    (@build $uniforms:expr; $field:ident: $value:expr) => {
+        $uniforms.add(stringify!($field), $value)
+    };
+
+    (@build $uniforms:expr; $field:ident: $value:expr, $($rest:tt)*) => {
+        uniform!(@build $uniforms.add(stringify!($field), $value); $($rest)*)
     };
 
-    ($($field:ident: $value:expr),*,) => {
-        uniform!($($field: $value),*)
+    (@build $uniforms:expr; [$name:expr] => $value:expr) => {
+        $uniforms.add_dynamic($name, $value)
+    };
+
+    (@build $uniforms:expr; [$name:expr] => $value:expr, $($rest:tt)*) => {
+        uniform!(@build $uniforms.add_dynamic($name, $value); $($rest)*)
+    };
+
+    (@build $uniforms:expr; ..$base:expr) => {
+        $crate::uniforms::UniformsChain::new($base, $uniforms)
+    };
+
+    (@build $uniforms:expr; ..$base:expr,) => {
+        $crate::uniforms::UniformsChain::new($base, $uniforms)
+    };
+
+    ($($rest:tt)+) => {
+        uniform!(@build $crate::uniforms::EmptyUniforms; $($rest)+)
+    };
+}
+
+/// Returns a `VertexDefaults` containing the values of the attributes that are not provided
+/// by the vertex buffers.
+///
+/// The syntax is the same as `uniform!`. Each value must be a scalar or an array of up to
+/// four `f32`, `i32` or `u32`.
+///
+/// ## Example
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate glium;
+/// # fn main() {
+/// let defaults = vertex_defaults! {
+///     color: [1.0, 1.0, 1.0, 1.0f32],
+///     bone_index: 0u32,
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! vertex_defaults {
+    () => {
+        $crate::vertex::VertexDefaults::new()
+    };
+
+    ($($field:ident: $value:expr),+) => {
+        $crate::vertex::VertexDefaults::new()
+            $(.add(stringify!($field), $value))+
+    };
+
+    ($($field:ident: $value:expr),+,) => {
+        vertex_defaults!($($field: $value),+)
     };
 }
 
@@ -77,6 +144,21 @@ macro_rules! uniform {
 ///
 /// The parameters must be the name of the struct and the names of its fields.
 ///
+/// Each field can optionally be followed by `type(...)`, which overrides the `AttributeType`
+/// that is deduced from the type of the field, and then by `location(...)`, which binds the
+/// field to the attribute at this location in the program instead of matching it by name.
+/// The overriding type must not be larger than the field.
+///
+/// Creating a vertex buffer returns an error if two fields have the same location.
+///
+/// A field can finally be followed by `divisor(...)`, in which case it advances once every
+/// this number of instances instead of once per instance. This is only allowed when the buffer
+/// is used per instance, otherwise drawing returns `DrawError::DivisorOnPerVertexSource`.
+///
+/// The fields must cover the whole struct. Building the bindings panics if the struct contains
+/// padding or if some of its fields are not passed to the macro. Add `#[repr(C)]` to the
+/// struct and turn any padding into an explicit field if this happens.
+///
 /// ## Example
 ///
 /// ```
@@ -90,38 +172,117 @@ macro_rules! uniform {
 /// }
 ///
 /// implement_vertex!(Vertex, position, tex_coords);
+///
+/// #[derive(Copy, Clone)]
+/// struct PackedVertex {
+///     position: [f32; 3],
+///     color: u32,
+/// }
+///
+/// implement_vertex!(PackedVertex, position location(0),
+///                   color type(U8U8U8U8Normalized) location(1));
+///
+/// // when used per instance, each color is shared by four consecutive instances
+/// #[derive(Copy, Clone)]
+/// struct Instance {
+///     transform: [[f32; 4]; 4],
+///     cluster_color: [f32; 3],
+/// }
+///
+/// implement_vertex!(Instance, transform, cluster_color divisor(4));
+///
+/// // the `glium::vertex::pack_*` functions build the values of the packed types
+/// #[derive(Copy, Clone)]
+/// struct CompactVertex {
+///     position: [u16; 4],
+///     normal: u32,
+/// }
+///
+/// implement_vertex!(CompactVertex, position type(F16F16F16F16),
+///                   normal type(I2I10I10I10ReversedNormalized));
+///
+/// let vertex = CompactVertex {
+///     position: [glium::vertex::pack_f16(0.5), 0, 0, glium::vertex::pack_f16(1.0)],
+///     normal: glium::vertex::pack_snorm_10_10_10_2([0.0, 1.0, 0.0], 1.0),
+/// };
+/// # let _ = vertex;
 /// # }
 /// ```
 ///
 #[macro_export]
 macro_rules! implement_vertex {
-    ($struct_name:ident, $($field_name:ident),+) => (
+    (@type $struct_name:ident, $field_name:ident) => ({
+        fn attr_type_of_val<T: $crate::vertex::Attribute>(_: &T)
+            -> $crate::vertex::AttributeType
+        {
+            <T as $crate::vertex::Attribute>::get_type()
+        }
+        let dummy: &$struct_name = unsafe { ::std::mem::transmute(0usize) };
+        attr_type_of_val(&dummy.$field_name)
+    });
+
+    (@type $struct_name:ident, $field_name:ident, $ty:ident) => ({
+        fn size_of_val<T>(_: &T) -> usize {
+            ::std::mem::size_of::<T>()
+        }
+        let ty = $crate::vertex::AttributeType::$ty;
+        let dummy: &$struct_name = unsafe { ::std::mem::transmute(0usize) };
+        assert!(ty.get_size_bytes() <= size_of_val(&dummy.$field_name),
+                "The type `{:?}` is larger than the field `{}`", ty, stringify!($field_name));
+        ty
+    });
+
+    (@offset $struct_name:ident, $field_name:ident) => ({
+        let dummy: $struct_name = unsafe { ::std::mem::uninitialized() };
+        let offset = {
+            let base = &dummy as *const $struct_name as usize;
+            let field = &dummy.$field_name as *const _ as usize;
+            field - base
+        };
+        ::std::mem::forget(dummy);
+        offset
+    });
+
+    (@location) => (None);
+
+    (@location $location:expr) => (Some($location));
+
+    (@divisor) => (None);
+
+    (@divisor $divisor:expr) => (Some($divisor));
+
+    ($struct_name:ident, $($field_name:ident $(type($ty:ident))* $(location($location:expr))*
+                                             $(divisor($divisor:expr))*),+) => (
         impl $crate::vertex::Vertex for $struct_name {
             #[inline]
             fn build_bindings() -> $crate::vertex::VertexFormat {
                 use std::borrow::Cow;
 
+                // checking that the offsets that we compute describe the whole struct
+                {
+                    fn size_of_val<T>(_: &T) -> usize {
+                        ::std::mem::size_of::<T>()
+                    }
+                    let dummy: &$struct_name = unsafe { ::std::mem::transmute(0usize) };
+                    let fields_size = 0 $(+ size_of_val(&dummy.$field_name))+;
+                    let struct_size = ::std::mem::size_of::<$struct_name>();
+                    assert!(fields_size == struct_size,
+                            "The fields passed to `implement_vertex!` only cover {} bytes out of \
+                             the {} bytes of `{}`. Add `#[repr(C)]` to the struct, make its \
+                             padding explicit and pass all its fields to the macro.",
+                            fields_size, struct_size, stringify!($struct_name));
+                }
+
                 // TODO: use a &'static [] if possible
 
                 Cow::Owned(vec![
                     $(
                         (
                             Cow::Borrowed(stringify!($field_name)),
-                            {
-                                let dummy: &$struct_name = unsafe { ::std::mem::transmute(0usize) };
-                                let dummy_field = &dummy.$field_name;
-                                let dummy_field: usize = unsafe { ::std::mem::transmute(dummy_field) };
-                                dummy_field
-                            },
-                            {
-                                fn attr_type_of_val<T: $crate::vertex::Attribute>(_: &T)
-                                    -> $crate::vertex::AttributeType
-                                {
-                                    <T as $crate::vertex::Attribute>::get_type()
-                                }
-                                let dummy: &$struct_name = unsafe { ::std::mem::transmute(0usize) };
-                                attr_type_of_val(&dummy.$field_name)
-                            },
+                            implement_vertex!(@offset $struct_name, $field_name),
+                            implement_vertex!(@type $struct_name, $field_name $(, $ty)*),
+                            implement_vertex!(@location $($location)*),
+                            implement_vertex!(@divisor $($divisor)*),
                         )
                     ),+
                 ])
@@ -129,8 +290,10 @@ macro_rules! implement_vertex {
         }
     );
 
-    ($struct_name:ident, $($field_name:ident),+,) => (
-        implement_vertex!($struct_name, $($field_name),+);
+    ($struct_name:ident, $($field_name:ident $(type($ty:ident))* $(location($location:expr))*
+                                             $(divisor($divisor:expr))*),+,) => (
+        implement_vertex!($struct_name, $($field_name $(type($ty))* $(location($location))*
+                                                      $(divisor($divisor))*),+);
     );
 }
 
@@ -485,11 +648,12 @@ macro_rules! program {
             let _fragment_shader: &str = "";
             let _outputs_srgb: bool = false;
             let _uses_point_size: bool = false;
+            let _dual_source_output: Option<&str> = None;
 
             $(
                 program!(_program_ty $ty, $src, _vertex_shader, _tessellation_control_shader,
                          _tessellation_evaluation_shader, _geometry_shader, _fragment_shader,
-                         _outputs_srgb, _uses_point_size);
+                         _outputs_srgb, _uses_point_size, _dual_source_output);
             )+
 
             let input = $crate::program::ProgramCreationInput::SourceCode {
@@ -501,6 +665,7 @@ macro_rules! program {
                 transform_feedback_varyings: None,
                 outputs_srgb: _outputs_srgb,
                 uses_point_size: _uses_point_size,
+                dual_source_output: _dual_source_output,
             };
 
             $crate::program::Program::new($context, input)
@@ -515,34 +680,38 @@ macro_rules! program {
         program!(_inner, $context, $vers, {$($ty:$src),+} $($rest)*);
     );
 
-    (_program_ty vertex, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident) => (
+    (_program_ty vertex, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
         let $vs = $src;
     );
 
-    (_program_ty tessellation_control, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident) => (
+    (_program_ty tessellation_control, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
         let $tcs = Some($src);
     );
 
-    (_program_ty tessellation_evaluation, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident) => (
+    (_program_ty tessellation_evaluation, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
         let $tes = Some($src);
     );
 
-    (_program_ty geometry, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident) => (
+    (_program_ty geometry, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
         let $gs = Some($src);
     );
 
-    (_program_ty fragment, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident) => (
+    (_program_ty fragment, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
         let $fs = $src;
     );
 
-    (_program_ty point_size, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident) => (
+    (_program_ty point_size, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
         let $ps = $src;
     );
 
-    (_program_ty outputs_srgb, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident) => (
+    (_program_ty outputs_srgb, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
         let $srgb = $src;
     );
 
+    (_program_ty dual_source_output, $src:expr, $vs:ident, $tcs:ident, $tes:ident, $gs:ident, $fs:ident, $srgb:ident, $ps:ident, $dso:ident) => (
+        let $dso = Some($src);
+    );
+
     (_parse_num_gl $num:expr) => (
         if $num == 100 {
             $crate::Version($crate::Api::GlEs, 1, 0)
@@ -575,6 +744,33 @@ mod tests {
         implement_vertex!(Foo, pos,);
     }
 
+    #[test]
+    fn implement_vertex_locations() {
+        #[derive(Copy, Clone)]
+        struct Foo {
+            pos: [f32; 2],
+            color: u32,
+        }
+
+        implement_vertex!(Foo, pos location(0), color type(U8U8U8U8Normalized) location(1),);
+    }
+
+    #[test]
+    fn implement_vertex_divisors() {
+        #[derive(Copy, Clone)]
+        struct Foo {
+            pos: [f32; 2],
+            color: u32,
+        }
+
+        implement_vertex!(Foo, pos divisor(1), color type(U8U8U8U8Normalized) location(1)
+                                                     divisor(4));
+
+        let bindings = <Foo as ::vertex::Vertex>::build_bindings();
+        assert_eq!(bindings[0].4, Some(1));
+        assert_eq!(bindings[1].4, Some(4));
+    }
+
     #[test]
     fn assert_no_error_macro() {
         struct Dummy;
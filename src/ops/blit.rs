@@ -1,9 +1,11 @@
+use BlitError;
 use BlitTarget;
 use Rect;
 
 use context::Context;
 use ContextExt;
 
+use fbo::ColorAttachmentType;
 use fbo::FramebuffersContainer;
 use fbo::ValidatedAttachments;
 
@@ -14,7 +16,24 @@ use version::Api;
 pub fn blit(context: &Context, source: Option<&ValidatedAttachments>,
             target: Option<&ValidatedAttachments>, mask: gl::types::GLbitfield,
             src_rect: &Rect, target_rect: &BlitTarget, filter: gl::types::GLenum)
+            -> Result<(), BlitError>
 {
+    try!(check_compatibility(source, target, mask, src_rect, target_rect, filter));
+
+    let source_dimensions = match source {
+        Some(attachments) => attachments.get_dimensions(),
+        None => context.get_framebuffer_dimensions(),
+    };
+
+    let (src_rect, target_rect) = match clip(src_rect, target_rect, source_dimensions) {
+        Some(rects) => rects,
+        None => return Ok(()),
+    };
+
+    if let (Some(target), true) = (target, mask & gl::COLOR_BUFFER_BIT != 0) {
+        target.mark_rendered();
+    }
+
     unsafe {
         let mut ctxt = context.make_current();
 
@@ -28,18 +47,24 @@ pub fn blit(context: &Context, source: Option<&ValidatedAttachments>,
             ctxt.state.enabled_scissor_test = false;
         }
 
+        // WORK-AROUND: some drivers apply the sRGB conversion when blitting
+        if ctxt.workarounds.no_srgb_blits && ctxt.state.enabled_framebuffer_srgb {
+            ctxt.gl.Disable(gl::FRAMEBUFFER_SRGB);
+            ctxt.state.enabled_framebuffer_srgb = false;
+        }
+
         // trying to do a named blit if possible
-        if ctxt.version >= &Version(Api::Gl, 4, 5) {
+        if ctxt.capabilities.direct_state_access {
             ctxt.gl.BlitNamedFramebuffer(source, target,
                 src_rect.left as gl::types::GLint,
                 src_rect.bottom as gl::types::GLint,
-                (src_rect.left + src_rect.width) as gl::types::GLint,
-                (src_rect.bottom + src_rect.height) as gl::types::GLint,
+                (src_rect.left + src_rect.width as i32) as gl::types::GLint,
+                (src_rect.bottom + src_rect.height as i32) as gl::types::GLint,
                 target_rect.left as gl::types::GLint, target_rect.bottom as gl::types::GLint,
-                (target_rect.left as i32 + target_rect.width) as gl::types::GLint,
-                (target_rect.bottom as i32 + target_rect.height) as gl::types::GLint, mask, filter);
+                (target_rect.left + target_rect.width) as gl::types::GLint,
+                (target_rect.bottom + target_rect.height) as gl::types::GLint, mask, filter);
 
-            return;
+            return Ok(());
         }
 
         // binding source framebuffer
@@ -70,20 +95,137 @@ pub fn blit(context: &Context, source: Option<&ValidatedAttachments>,
         if ctxt.version >= &Version(Api::Gl, 3, 0) {
             ctxt.gl.BlitFramebuffer(src_rect.left as gl::types::GLint,
                 src_rect.bottom as gl::types::GLint,
-                (src_rect.left + src_rect.width) as gl::types::GLint,
-                (src_rect.bottom + src_rect.height) as gl::types::GLint,
+                (src_rect.left + src_rect.width as i32) as gl::types::GLint,
+                (src_rect.bottom + src_rect.height as i32) as gl::types::GLint,
                 target_rect.left as gl::types::GLint, target_rect.bottom as gl::types::GLint,
-                (target_rect.left as i32 + target_rect.width) as gl::types::GLint,
-                (target_rect.bottom as i32 + target_rect.height) as gl::types::GLint, mask, filter);
+                (target_rect.left + target_rect.width) as gl::types::GLint,
+                (target_rect.bottom + target_rect.height) as gl::types::GLint, mask, filter);
 
         } else {
             ctxt.gl.BlitFramebufferEXT(src_rect.left as gl::types::GLint,
                 src_rect.bottom as gl::types::GLint,
-                (src_rect.left + src_rect.width) as gl::types::GLint,
-                (src_rect.bottom + src_rect.height) as gl::types::GLint,
+                (src_rect.left + src_rect.width as i32) as gl::types::GLint,
+                (src_rect.bottom + src_rect.height as i32) as gl::types::GLint,
                 target_rect.left as gl::types::GLint, target_rect.bottom as gl::types::GLint,
-                (target_rect.left as i32 + target_rect.width) as gl::types::GLint,
-                (target_rect.bottom as i32 + target_rect.height) as gl::types::GLint, mask, filter);
+                (target_rect.left + target_rect.width) as gl::types::GLint,
+                (target_rect.bottom + target_rect.height) as gl::types::GLint, mask, filter);
         }
+
+        ctxt.frame_statistics.update(|s| s.blits += 1);
     }
+
+    Ok(())
+}
+
+/// Checks that the buffers of `source` enabled in `mask` can be copied to `target`.
+///
+/// The formats of the default framebuffer and of external framebuffers are unknown, in which
+/// case only the filter is checked.
+fn check_compatibility(source: Option<&ValidatedAttachments>,
+                       target: Option<&ValidatedAttachments>, mask: gl::types::GLbitfield,
+                       src_rect: &Rect, target_rect: &BlitTarget, filter: gl::types::GLenum)
+                       -> Result<(), BlitError>
+{
+    if mask & (gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT) != 0 && filter != gl::NEAREST {
+        return Err(BlitError::LinearFilterNotSupported);
+    }
+
+    if mask & gl::COLOR_BUFFER_BIT != 0 && filter != gl::NEAREST {
+        let integral = source.and_then(|s| s.get_color_attachment_types().first())
+                             .map(|&ty| ty != ColorAttachmentType::Float).unwrap_or(false);
+        if integral {
+            return Err(BlitError::LinearFilterNotSupported);
+        }
+    }
+
+    let (source, target) = match (source, target) {
+        (Some(source), Some(target)) => (source, target),
+        _ => return Ok(()),
+    };
+
+    if mask & gl::DEPTH_BUFFER_BIT != 0 {
+        if let (Some(src), Some(dest)) = (source.get_depth_format(), target.get_depth_format()) {
+            if src != dest {
+                return Err(BlitError::DepthFormatMismatch { source: src, target: dest });
+            }
+        }
+    }
+
+    if mask & gl::STENCIL_BUFFER_BIT != 0 {
+        if let (Some(src), Some(dest)) = (source.get_stencil_format(),
+                                          target.get_stencil_format())
+        {
+            if src != dest {
+                return Err(BlitError::StencilFormatMismatch { source: src, target: dest });
+            }
+        }
+    }
+
+    // the color is read from the first attachment and written to all the draw buffers
+    if mask & gl::COLOR_BUFFER_BIT != 0 {
+        if let Some(&src) = source.get_color_attachment_types().first() {
+            for &dest in target.get_color_attachment_types() {
+                if src != dest {
+                    return Err(BlitError::IntegerColorMismatch);
+                }
+            }
+        }
+    }
+
+    match (source.get_samples(), target.get_samples()) {
+        (Some(src), Some(dest)) if src != 0 || dest != 0 => {
+            if src != 0 && dest != 0 && src != dest {
+                return Err(BlitError::SamplesCountMismatch { source: src, target: dest });
+            }
+
+            if src_rect.width as i32 != target_rect.width ||
+               src_rect.height as i32 != target_rect.height
+            {
+                return Err(BlitError::MultisampleRectMismatch);
+            }
+        },
+        _ => ()
+    }
+
+    Ok(())
+}
+
+/// Clips the source rectangle to the dimensions of the source, and reduces the target rectangle
+/// by the same proportion.
+///
+/// Returns `None` if there is nothing to copy.
+fn clip(src_rect: &Rect, target_rect: &BlitTarget, source_dimensions: (u32, u32))
+        -> Option<(Rect, BlitTarget)>
+{
+    let clipped = src_rect.clip(source_dimensions);
+
+    if clipped.width == 0 || clipped.height == 0 {
+        return None;
+    }
+
+    if clipped == *src_rect {
+        return Some((*src_rect, *target_rect));
+    }
+
+    // converts a coordinate in the source into a coordinate in the target
+    let convert = |pos: i32, src_origin: i32, src_len: u32, target_origin: i32, target_len: i32| {
+        (target_origin as i64 + (pos as i64 - src_origin as i64) * target_len as i64 /
+                                                                    src_len as i64) as i32
+    };
+
+    let left = convert(clipped.left, src_rect.left, src_rect.width,
+                       target_rect.left, target_rect.width);
+    let right = convert(clipped.left + clipped.width as i32, src_rect.left, src_rect.width,
+                        target_rect.left, target_rect.width);
+    let bottom = convert(clipped.bottom, src_rect.bottom, src_rect.height,
+                         target_rect.bottom, target_rect.height);
+    let top = convert(clipped.bottom + clipped.height as i32, src_rect.bottom, src_rect.height,
+                      target_rect.bottom, target_rect.height);
+
+    Some((clipped, BlitTarget {
+        left: left,
+        bottom: bottom,
+        width: right - left,
+        height: top - bottom,
+    }))
 }
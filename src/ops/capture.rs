@@ -0,0 +1,288 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ptr;
+use std::rc::Rc;
+
+use buffer::{Buffer, BufferAny, BufferMode, BufferType};
+use context::Context;
+use context::CommandContext;
+use fbo::{self, FramebuffersContainer, ValidatedAttachments};
+use framebuffer::{ColorAttachment, ToColorAttachment};
+use texture::{self, ClientFormat, MipmapsOption, RawImage2d};
+use texture::{SrgbFormat, UncompressedFloatFormat};
+use sync::{self, SyncFence};
+use BlitTarget;
+use BufferExt;
+use CaptureError;
+use ContextExt;
+use Rect;
+use gl;
+
+use ops;
+use version::Api;
+use version::Version;
+
+/// Reads the content of the first color attachment of a framebuffer, or of the back buffer of the
+/// default framebuffer if `framebuffer` is `None`.
+///
+/// The rows of the result are in top-to-bottom order. The values are the ones that are stored in
+/// the framebuffer, which means that they are already encoded if the framebuffer is sRGB.
+/// Multisampled framebuffers are first resolved into a temporary texture.
+pub fn capture(context: &Rc<Context>, framebuffer: Option<&ValidatedAttachments>,
+               dimensions: (u32, u32)) -> Result<RawImage2d<'static, u8>, CaptureError>
+{
+    let size = dimensions.0 as usize * dimensions.1 as usize * 4;
+    let mut data = Vec::with_capacity(size);
+
+    try!(read_framebuffer(context, framebuffer, dimensions, |ctxt| unsafe {
+        BufferAny::unbind_pixel_pack(ctxt);
+        read_pixels(ctxt, dimensions, data.as_mut_ptr() as *mut _);
+        data.set_len(size);
+    }));
+
+    Ok(into_image(data, dimensions))
+}
+
+/// A capture whose pixels are being transferred to a pixel buffer by the GPU.
+pub struct AsyncCapture {
+    buffer: Buffer<[u8]>,
+    fence: SyncFence,
+    dimensions: (u32, u32),
+    callback: Box<FnMut(Result<RawImage2d<'static, u8>, CaptureError>) + Send>,
+}
+
+/// List of captures that are in progress, in the order in which they have been started.
+pub type AsyncCaptureQueue = Rc<RefCell<VecDeque<AsyncCapture>>>;
+
+/// Same as `capture`, except that the pixels are read into a pixel buffer and that this function
+/// doesn't wait for the transfer to finish.
+///
+/// `poll_async_captures` must then be called regularly to call the callback with the result.
+pub fn capture_async(context: &Rc<Context>, framebuffer: Option<&ValidatedAttachments>,
+                     dimensions: (u32, u32),
+                     callback: Box<FnMut(Result<RawImage2d<'static, u8>, CaptureError>) + Send>)
+                     -> Result<AsyncCapture, CaptureError>
+{
+    {
+        let ctxt = context.make_current();
+        if !is_async_capture_supported(&ctxt) {
+            return Err(CaptureError::NotSupported);
+        }
+    }
+
+    let size = dimensions.0 as usize * dimensions.1 as usize * 4;
+    let buffer = try!(Buffer::empty_array(context, BufferType::PixelPackBuffer, size,
+                                          BufferMode::Default)
+                             .map_err(CaptureError::BufferCreation));
+
+    try!(read_framebuffer(context, framebuffer, dimensions, |ctxt| unsafe {
+        buffer.prepare_and_bind_for_pixel_pack(ctxt);
+        read_pixels(ctxt, dimensions, ptr::null_mut());
+    }));
+
+    // support for sync fences has been checked above
+    let fence = try!(SyncFence::new(context).map_err(|_| CaptureError::NotSupported));
+
+    Ok(AsyncCapture {
+        buffer: buffer,
+        fence: fence,
+        dimensions: dimensions,
+        callback: callback,
+    })
+}
+
+/// Calls the callbacks of the captures of the queue that have finished, in order.
+///
+/// Never blocks. Stops at the first capture that isn't finished yet, so that the callbacks are
+/// always called in the order in which the captures have been started. If the pixel buffer of
+/// a capture can't be mapped, its callback receives the error.
+pub fn poll_async_captures(queue: &RefCell<VecDeque<AsyncCapture>>) {
+    loop {
+        // the queue must not be borrowed while the callback runs, as it may start a new capture
+        let capture = {
+            let mut queue = queue.borrow_mut();
+            if !queue.front().map(|c| sync::is_signaled(&c.fence)).unwrap_or(false) {
+                return;
+            }
+            queue.pop_front().unwrap()
+        };
+
+        let AsyncCapture { mut buffer, dimensions, mut callback, .. } = capture;
+        let image = buffer.try_map_read().map(|data| into_image(data.to_vec(), dimensions))
+                          .map_err(CaptureError::Map);
+        callback(image);
+    }
+}
+
+/// Binds the framebuffer for reading, then calls `read` to read its pixels.
+///
+/// If the framebuffer is multisampled, it is first resolved into a temporary texture which is
+/// bound instead.
+fn read_framebuffer<F>(context: &Rc<Context>, framebuffer: Option<&ValidatedAttachments>,
+                       dimensions: (u32, u32), read: F) -> Result<(), CaptureError>
+                       where F: FnOnce(&mut CommandContext)
+{
+    // the temporary texture must outlive the read operation
+    let srgb_texture;
+    let regular_texture;
+
+    let (multisampled, srgb) = {
+        let mut ctxt = context.make_current();
+        let fbo_id = FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
+        unsafe { bind_for_reading(&mut ctxt, fbo_id) };
+        unsafe { get_read_framebuffer_infos(&mut ctxt, fbo_id) }
+    };
+
+    let mut ctxt = if multisampled {
+        // multisampled framebuffers can't be read with `glReadPixels`, so we resolve the
+        // framebuffer in a temporary texture of the same encoding
+        let attachment = if srgb {
+            srgb_texture = try!(texture::SrgbTexture2d::empty_with_format(context,
+                                    SrgbFormat::U8U8U8U8, MipmapsOption::NoMipmap,
+                                    dimensions.0, dimensions.1)
+                                .map_err(CaptureError::TextureCreation));
+            srgb_texture.to_color_attachment()
+        } else {
+            regular_texture = try!(texture::Texture2d::empty_with_format(context,
+                                       UncompressedFloatFormat::U8U8U8U8, MipmapsOption::NoMipmap,
+                                       dimensions.0, dimensions.1)
+                                   .map_err(CaptureError::TextureCreation));
+            regular_texture.to_color_attachment()
+        };
+
+        let attachment = match attachment {
+            ColorAttachment::Texture(tex) => fbo::RegularAttachment::Texture(tex),
+            ColorAttachment::RenderBuffer(buffer) => fbo::RegularAttachment::RenderBuffer(buffer),
+        };
+
+        let attachments = try!(fbo::FramebufferAttachments::Regular(
+            fbo::FramebufferSpecificAttachments {
+                colors: { let mut v = ::smallvec::SmallVec::new(); v.push((0, attachment)); v },
+                depth_stencil: fbo::DepthStencilAttachments::None,
+            }).validate(&**context).map_err(CaptureError::FramebufferValidation));
+
+        // disabling sRGB conversions so that the values are copied as they are
+        {
+            let mut ctxt = context.make_current();
+            disable_framebuffer_srgb(&mut ctxt);
+        }
+
+        let rect = Rect { left: 0, bottom: 0, width: dimensions.0, height: dimensions.1 };
+        let target = BlitTarget { left: 0, bottom: 0, width: dimensions.0 as i32,
+                                  height: dimensions.1 as i32 };
+        try!(ops::blit(context, framebuffer, Some(&attachments), gl::COLOR_BUFFER_BIT, &rect,
+                       &target, gl::NEAREST).map_err(CaptureError::Blit));
+
+        let mut ctxt = context.make_current();
+        let fbo_id = FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt,
+                                                                         Some(&attachments));
+        unsafe { bind_for_reading(&mut ctxt, fbo_id) };
+        ctxt
+
+    } else {
+        context.make_current()
+    };
+
+    read(&mut ctxt);
+    Ok(())
+}
+
+/// Turns pixels in bottom-to-top order into an image.
+fn into_image(mut data: Vec<u8>, dimensions: (u32, u32)) -> RawImage2d<'static, u8> {
+    ops::flip_rows(&mut data[..], dimensions.0 as usize * 4, dimensions.1 as usize);
+
+    RawImage2d {
+        data: Cow::Owned(data),
+        width: dimensions.0,
+        height: dimensions.1,
+        format: ClientFormat::U8U8U8U8,
+    }
+}
+
+/// Binds the framebuffer for both reading and drawing, and selects its first color buffer
+/// as the read buffer.
+unsafe fn bind_for_reading(ctxt: &mut CommandContext, fbo_id: gl::types::GLuint) {
+    fbo::bind_framebuffer(ctxt, fbo_id, true, true);
+
+    // OpenGL ES 2 doesn't have `glReadBuffer` and always reads from the only color buffer
+    if ctxt.version >= &Version(Api::Gl, 1, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) {
+        if fbo_id == 0 {
+            let read_buffer = fbo::default_color_buffer(ctxt);
+            ctxt.gl.ReadBuffer(read_buffer);
+        } else {
+            ctxt.gl.ReadBuffer(gl::COLOR_ATTACHMENT0);
+        }
+    }
+}
+
+/// Returns whether the framebuffer that is currently bound is multisampled and whether its
+/// first color buffer is sRGB.
+unsafe fn get_read_framebuffer_infos(ctxt: &mut CommandContext, fbo_id: gl::types::GLuint)
+                                     -> (bool, bool)
+{
+    let mut sample_buffers = 0;
+    if ctxt.version >= &Version(Api::Gl, 1, 3) || ctxt.version >= &Version(Api::GlEs, 2, 0) {
+        ctxt.gl.GetIntegerv(gl::SAMPLE_BUFFERS, &mut sample_buffers);
+    }
+
+    let mut encoding = gl::LINEAR as gl::types::GLint;
+    if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) {
+        let attachment = if fbo_id != 0 {
+            gl::COLOR_ATTACHMENT0
+        } else if ctxt.version >= &Version(Api::GlEs, 2, 0) {
+            gl::BACK
+        } else if ctxt.capabilities.double_buffer {
+            gl::BACK_LEFT
+        } else {
+            gl::FRONT_LEFT
+        };
+
+        ctxt.gl.GetFramebufferAttachmentParameteriv(gl::FRAMEBUFFER, attachment,
+                                                    gl::FRAMEBUFFER_ATTACHMENT_COLOR_ENCODING,
+                                                    &mut encoding);
+    }
+
+    (sample_buffers != 0, encoding as gl::types::GLenum == gl::SRGB)
+}
+
+/// Reads the pixels of the current read buffer as RGBA8, with tightly-packed rows.
+///
+/// `dest` is an offset in the buffer bound to `GL_PIXEL_PACK_BUFFER` if any, or a pointer to
+/// memory otherwise.
+unsafe fn read_pixels(ctxt: &mut CommandContext, dimensions: (u32, u32),
+                      dest: *mut gl::types::GLvoid)
+{
+    // rows are tightly packed, whatever their width
+    ops::set_pack_tightly_packed(ctxt);
+
+    ctxt.gl.ReadPixels(0, 0, dimensions.0 as gl::types::GLsizei,
+                       dimensions.1 as gl::types::GLsizei, gl::RGBA, gl::UNSIGNED_BYTE, dest);
+}
+
+/// Returns true if the backend supports pixel buffers and sync fences.
+fn is_async_capture_supported(ctxt: &CommandContext) -> bool {
+    let pixel_buffers = ctxt.version >= &Version(Api::Gl, 2, 1) ||
+                        ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+                        ctxt.extensions.gl_arb_pixel_buffer_object ||
+                        ctxt.extensions.gl_nv_pixel_buffer_object;
+
+    let sync = ctxt.version >= &Version(Api::Gl, 3, 2) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+               ctxt.extensions.gl_arb_sync || ctxt.extensions.gl_apple_sync;
+
+    pixel_buffers && sync
+}
+
+fn disable_framebuffer_srgb(ctxt: &mut CommandContext) {
+    if !(ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_framebuffer_srgb ||
+         ctxt.extensions.gl_ext_framebuffer_srgb || ctxt.extensions.gl_ext_srgb_write_control)
+    {
+        return;
+    }
+
+    if ctxt.state.enabled_framebuffer_srgb {
+        unsafe { ctxt.gl.Disable(gl::FRAMEBUFFER_SRGB) };
+        ctxt.state.enabled_framebuffer_srgb = false;
+    }
+}
@@ -1,6 +1,8 @@
-use fbo::{self, ValidatedAttachments};
+use fbo::{self, ValidatedAttachments, ColorAttachmentType};
 
+use context::CommandContext;
 use context::Context;
+use context::PendingClear;
 use ContextExt;
 use Rect;
 
@@ -18,77 +20,85 @@ pub fn clear(context: &Context, framebuffer: Option<&ValidatedAttachments>,
              rect: Option<&Rect>, color: Option<(f32, f32, f32, f32)>, color_srgb: bool,
              depth: Option<f32>, stencil: Option<i32>)
 {
+    let rect = rect.map(|rect| {
+        (rect.left as gl::types::GLint, rect.bottom as gl::types::GLint,
+         rect.width as gl::types::GLsizei, rect.height as gl::types::GLsizei)
+    });
+
+    // `glClear` leaves undefined values in integer attachments, so if there is one the color
+    // attachments are cleared one by one with `glClearBuffer*`
+    let color_types = framebuffer.map(|f| f.get_color_attachment_types()).unwrap_or(&[]);
+    let clear_buffers = color.is_some() &&
+                        color_types.iter().any(|&ty| ty != ColorAttachmentType::Float);
+
+    if let (Some(framebuffer), true) = (framebuffer, color.is_some()) {
+        framebuffer.mark_rendered();
+    }
+
+    let mut mask = 0;
+    if color.is_some() && !clear_buffers { mask |= gl::COLOR_BUFFER_BIT; }
+    if depth.is_some() { mask |= gl::DEPTH_BUFFER_BIT; }
+    if stencil.is_some() { mask |= gl::STENCIL_BUFFER_BIT; }
+
     unsafe {
-        let mut ctxt = context.make_current();
+        let mut ctxt = context.make_current_for_clear();
+        ctxt.frame_statistics.update(|s| s.clears += 1);
 
         let fbo_id = fbo::FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
-        fbo::bind_framebuffer(&mut ctxt, fbo_id, true, false);
-
-        if ctxt.state.enabled_rasterizer_discard {
-            ctxt.gl.Disable(gl::RASTERIZER_DISCARD);
-            ctxt.state.enabled_rasterizer_discard = false;
-        }
 
-        if ctxt.state.color_mask != (1, 1, 1, 1) {
-            ctxt.state.color_mask = (1, 1, 1, 1);
-            ctxt.gl.ColorMask(1, 1, 1, 1);
-        }
-    
-        if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_framebuffer_srgb ||
-           ctxt.extensions.gl_ext_framebuffer_srgb || ctxt.extensions.gl_ext_srgb_write_control
-        {
-            if !color_srgb && !ctxt.state.enabled_framebuffer_srgb {
-                ctxt.gl.Enable(gl::FRAMEBUFFER_SRGB);
-                ctxt.state.enabled_framebuffer_srgb = true;
+        // if the previous clear targets the same surface and other buffers, the two are merged
+        let merge = match ctxt.state.pending_clear {
+            Some(ref pending) => pending.framebuffer == fbo_id && pending.rect == rect &&
+                                 pending.color_srgb == color_srgb && pending.mask & mask == 0,
+            None => false,
+        };
 
-            } else if color_srgb && ctxt.state.enabled_framebuffer_srgb {
-                ctxt.gl.Disable(gl::FRAMEBUFFER_SRGB);
-                ctxt.state.enabled_framebuffer_srgb = false;
-            }
+        if !merge || clear_buffers {
+            flush_pending_clear(&mut ctxt);
         }
 
-        TimeElapsedQuery::end_conditional_render(&mut ctxt);
+        fbo::bind_framebuffer(&mut ctxt, fbo_id, true, false);
+        sync_clear_state(&mut ctxt, rect, Some(color_srgb));
 
-        if let Some(rect) = rect {
-            let rect = (rect.left as gl::types::GLint, rect.bottom as gl::types::GLint,
-                        rect.width as gl::types::GLsizei, rect.height as gl::types::GLsizei);
+        if clear_buffers {
+            let color = color.unwrap();
+            let color = [color.0, color.1, color.2, color.3];
 
-            if ctxt.state.scissor != Some(rect) {
-                ctxt.gl.Scissor(rect.0, rect.1, rect.2, rect.3);
-                ctxt.state.scissor = Some(rect);
-            }
+            for (index, &ty) in color_types.iter().enumerate() {
+                let data = match ty {
+                    ColorAttachmentType::Float => ClearBufferData::Float(color),
+                    ColorAttachmentType::SignedInteger => {
+                        ClearBufferData::SignedInt([color[0] as i32, color[1] as i32,
+                                                    color[2] as i32, color[3] as i32])
+                    },
+                    ColorAttachmentType::UnsignedInteger => {
+                        ClearBufferData::UnsignedInt([color[0] as u32, color[1] as u32,
+                                                      color[2] as u32, color[3] as u32])
+                    },
+                };
 
-            if !ctxt.state.enabled_scissor_test {
-                ctxt.gl.Enable(gl::SCISSOR_TEST);
-                ctxt.state.enabled_scissor_test = true;
+                clear_buffer(&mut ctxt, index as gl::types::GLint, data);
             }
 
-        } else {
-            if ctxt.state.enabled_scissor_test {
-                ctxt.gl.Disable(gl::SCISSOR_TEST);
-                ctxt.state.enabled_scissor_test = false;
+            if mask == 0 {
+                return;
             }
         }
 
-        let mut flags = 0;
-
-        if let Some(color) = color {
+        if let (Some(color), false) = (color, clear_buffers) {
             let color = (color.0 as gl::types::GLclampf, color.1 as gl::types::GLclampf,
                          color.2 as gl::types::GLclampf, color.3 as gl::types::GLclampf);
 
-            flags |= gl::COLOR_BUFFER_BIT;
-
             if ctxt.state.clear_color != color {
                 ctxt.gl.ClearColor(color.0, color.1, color.2, color.3);
                 ctxt.state.clear_color = color;
+                count_clear_value_call(&mut ctxt);
             }
         }
 
         if let Some(depth) = depth {
             let depth = depth as gl::types::GLclampf;
 
-            flags |= gl::DEPTH_BUFFER_BIT;
-
             if ctxt.state.clear_depth != depth {
                 if ctxt.version >= &Version(Api::Gl, 1, 0) {
                     ctxt.gl.ClearDepth(depth as gl::types::GLclampd);
@@ -99,6 +109,7 @@ pub fn clear(context: &Context, framebuffer: Option<&ValidatedAttachments>,
                 }
 
                 ctxt.state.clear_depth = depth;
+                count_clear_value_call(&mut ctxt);
             }
 
             if !ctxt.state.depth_mask {
@@ -110,14 +121,184 @@ pub fn clear(context: &Context, framebuffer: Option<&ValidatedAttachments>,
         if let Some(stencil) = stencil {
             let stencil = stencil as gl::types::GLint;
 
-            flags |= gl::STENCIL_BUFFER_BIT;
-
             if ctxt.state.clear_stencil != stencil {
                 ctxt.gl.ClearStencil(stencil);
                 ctxt.state.clear_stencil = stencil;
+                count_clear_value_call(&mut ctxt);
+            }
+
+            // the stencil write mask may have been changed by a previous draw call
+            if ctxt.state.stencil_mask_front != 0xffffffff ||
+               ctxt.state.stencil_mask_back != 0xffffffff
+            {
+                ctxt.gl.StencilMask(0xffffffff);
+                ctxt.state.stencil_mask_front = 0xffffffff;
+                ctxt.state.stencil_mask_back = 0xffffffff;
             }
         }
 
-        ctxt.gl.Clear(flags);
+        // the `glClear` is delayed until the next command, in case it can be merged with
+        // the next clear
+        match ctxt.state.pending_clear {
+            Some(ref mut pending) => pending.mask |= mask,
+            None => {
+                ctxt.state.pending_clear = Some(PendingClear {
+                    framebuffer: fbo_id,
+                    rect: rect,
+                    color_srgb: color_srgb,
+                    mask: mask,
+                });
+            },
+        }
+    }
+}
+
+/// Clears a single color attachment of a framebuffer with `glClearBuffer*`.
+///
+/// `index` is the position of the attachment in the list of draw buffers.
+///
+/// # Panic
+///
+/// Panics if the attachment doesn't exist or if its type doesn't match the type of the data.
+pub fn clear_color_buffer(context: &Context, framebuffer: Option<&ValidatedAttachments>,
+                          rect: Option<&Rect>, index: u32, data: ClearBufferData)
+{
+    let ty = framebuffer.and_then(|f| f.get_color_attachment_types().get(index as usize))
+                        .map(|&ty| ty);
+    let expected = match data {
+        ClearBufferData::Float(_) => ColorAttachmentType::Float,
+        ClearBufferData::SignedInt(_) => ColorAttachmentType::SignedInteger,
+        ClearBufferData::UnsignedInt(_) => ColorAttachmentType::UnsignedInteger,
+    };
+
+    match ty {
+        Some(ty) if ty == expected => (),
+        Some(ty) => panic!("Color attachment {} contains values of type {:?}", index, ty),
+        None => panic!("The framebuffer doesn't have an integer color attachment {}", index),
+    }
+
+    let rect = rect.map(|rect| {
+        (rect.left as gl::types::GLint, rect.bottom as gl::types::GLint,
+         rect.width as gl::types::GLsizei, rect.height as gl::types::GLsizei)
+    });
+
+    unsafe {
+        let mut ctxt = context.make_current();
+        ctxt.frame_statistics.update(|s| s.clears += 1);
+
+        let fbo_id = fbo::FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
+        fbo::bind_framebuffer(&mut ctxt, fbo_id, true, false);
+        sync_clear_state(&mut ctxt, rect, None);
+        clear_buffer(&mut ctxt, index as gl::types::GLint, data);
+    }
+
+    if let Some(framebuffer) = framebuffer {
+        framebuffer.mark_rendered();
     }
 }
+
+/// Value to pass to `glClearBuffer*`.
+#[derive(Debug, Copy, Clone)]
+pub enum ClearBufferData {
+    /// Calls `glClearBufferfv`.
+    Float([f32; 4]),
+    /// Calls `glClearBufferiv`.
+    SignedInt([i32; 4]),
+    /// Calls `glClearBufferuiv`.
+    UnsignedInt([u32; 4]),
+}
+
+/// Calls `glClearBuffer*` on a draw buffer of the current framebuffer.
+unsafe fn clear_buffer(ctxt: &mut CommandContext, index: gl::types::GLint, data: ClearBufferData) {
+    assert!(ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0),
+            "Clearing integer attachments is not supported by the backend");
+
+    match data {
+        ClearBufferData::Float(ref v) => ctxt.gl.ClearBufferfv(gl::COLOR, index, v.as_ptr()),
+        ClearBufferData::SignedInt(ref v) => ctxt.gl.ClearBufferiv(gl::COLOR, index, v.as_ptr()),
+        ClearBufferData::UnsignedInt(ref v) => ctxt.gl.ClearBufferuiv(gl::COLOR, index, v.as_ptr()),
+    }
+
+    let mut stats = ctxt.clear_statistics.get();
+    stats.clear_calls += 1;
+    ctxt.clear_statistics.set(stats);
+}
+
+/// Sets the state that affects clearing. `color_srgb` is `None` if the color is not
+/// converted.
+unsafe fn sync_clear_state(ctxt: &mut CommandContext,
+                           rect: Option<(gl::types::GLint, gl::types::GLint,
+                                         gl::types::GLsizei, gl::types::GLsizei)>,
+                           color_srgb: Option<bool>)
+{
+    if ctxt.state.enabled_rasterizer_discard {
+        ctxt.gl.Disable(gl::RASTERIZER_DISCARD);
+        ctxt.state.enabled_rasterizer_discard = false;
+    }
+
+    if ctxt.state.color_mask != (1, 1, 1, 1) {
+        ctxt.state.color_mask = (1, 1, 1, 1);
+        ctxt.gl.ColorMask(1, 1, 1, 1);
+    }
+
+    if let Some(color_srgb) = color_srgb {
+        if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_framebuffer_srgb ||
+           ctxt.extensions.gl_ext_framebuffer_srgb || ctxt.extensions.gl_ext_srgb_write_control
+        {
+            if !color_srgb && !ctxt.state.enabled_framebuffer_srgb {
+                ctxt.gl.Enable(gl::FRAMEBUFFER_SRGB);
+                ctxt.state.enabled_framebuffer_srgb = true;
+
+            } else if color_srgb && ctxt.state.enabled_framebuffer_srgb {
+                ctxt.gl.Disable(gl::FRAMEBUFFER_SRGB);
+                ctxt.state.enabled_framebuffer_srgb = false;
+            }
+        }
+    }
+
+    TimeElapsedQuery::end_conditional_render(ctxt);
+
+    // the scissor box is always explicitly set or disabled, as the previous value may come
+    // from another surface
+    if let Some(rect) = rect {
+        if ctxt.state.scissor != Some(rect) {
+            ctxt.gl.Scissor(rect.0, rect.1, rect.2, rect.3);
+            ctxt.state.scissor = Some(rect);
+        }
+
+        if !ctxt.state.enabled_scissor_test {
+            ctxt.gl.Enable(gl::SCISSOR_TEST);
+            ctxt.state.enabled_scissor_test = true;
+        }
+
+    } else {
+        if ctxt.state.enabled_scissor_test {
+            ctxt.gl.Disable(gl::SCISSOR_TEST);
+            ctxt.state.enabled_scissor_test = false;
+        }
+    }
+}
+
+/// Executes the `glClear` that has been delayed by `clear`, if any.
+pub fn flush_pending_clear(ctxt: &mut CommandContext) {
+    let pending = match ctxt.state.pending_clear.take() {
+        Some(p) => p,
+        None => return,
+    };
+
+    unsafe {
+        // the framebuffer may have been unbound in order to build another framebuffer object
+        fbo::bind_framebuffer(ctxt, pending.framebuffer, true, false);
+        ctxt.gl.Clear(pending.mask);
+    }
+
+    let mut stats = ctxt.clear_statistics.get();
+    stats.clear_calls += 1;
+    ctxt.clear_statistics.set(stats);
+}
+
+fn count_clear_value_call(ctxt: &mut CommandContext) {
+    let mut stats = ctxt.clear_statistics.get();
+    stats.clear_value_calls += 1;
+    ctxt.clear_statistics.set(stats);
+}
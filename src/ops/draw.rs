@@ -1,15 +1,21 @@
 use std::ptr;
 
+use smallvec::SmallVec;
+
 use BufferExt;
 use BufferSliceExt;
 use ProgramExt;
+use ProgramWarning;
 use DrawError;
 use UniformsExt;
 
 use context::Context;
 use ContextExt;
+use CapabilitiesSource;
 use TransformFeedbackSessionExt;
 
+use buffer::Inserter;
+use command_list::DrawCommand;
 use fbo::{self, ValidatedAttachments};
 
 use uniforms::Uniforms;
@@ -18,7 +24,7 @@ use index::{self, IndicesSource};
 use vertex::{MultiVerticesSource, VerticesSource, TransformFeedbackSession};
 use vertex_array_object::VertexAttributesSystem;
 
-use draw_parameters::DrawParameters;
+use draw_parameters::{BlendingFunction, DepthTest, DrawParameters};
 
 use libc;
 use {gl, context, draw_parameters};
@@ -26,18 +32,325 @@ use version::Version;
 use version::Api;
 
 /// Draws everything.
+///
+/// If `check_feedback_loops` is `false`, the textures sampled by the program can be attached
+/// to the framebuffer.
 pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachments>,
                       vertex_buffers: V, indices: IndicesSource,
                       program: &Program, uniforms: &U, draw_parameters: &DrawParameters,
-                      dimensions: (u32, u32)) -> Result<(), DrawError>
+                      dimensions: (u32, u32), check_feedback_loops: bool) -> Result<(), DrawError>
                       where U: Uniforms, V: MultiVerticesSource<'a>
 {
+    try!(check_framebuffer(context, framebuffer, draw_parameters));
+
+    if let (true, Some(framebuffer)) = (check_feedback_loops, framebuffer) {
+        // the depth buffer can be sampled if the draw command doesn't write to it
+        try!(uniforms.check_feedback_loops(program, framebuffer, !draw_parameters.depth.write));
+    }
+    let vertices_per_patch = try!(check_program(context, program, &indices, draw_parameters));
+
+    // handling vertices source
+    let vertex_buffers = {
+        let mut v: SmallVec<[VerticesSource; 4]> = SmallVec::new();
+        for src in vertex_buffers.iter() { v.push(src); }
+        v
+    };
+    try!(check_buffers_context(context, &vertex_buffers, &indices));
+
+    // a draw command that has nothing to draw doesn't touch the OpenGL state at all
+    let (vertices_count, instances_count) = try!(count_vertices(&vertex_buffers, context));
+    if draws_nothing(&indices, vertices_count, instances_count) {
+        return Ok(());
+    }
+
     // this contains the list of fences that will need to be fulfilled after the draw command
     // has started
     let mut fences = Vec::with_capacity(0);
 
+    // starting the state changes
+    let mut ctxt = context.make_current();
+
+    if ctxt.state.lost_context {
+        return Err(DrawError::ContextLost);
+    }
+
+    let (vertices_count, instances_count, base_vertex) =
+        try!(bind_vertices(&mut ctxt, program, &vertex_buffers, &indices, &mut fences));
+
+    // binding the FBO to draw upon
+    {
+        let fbo_id = fbo::FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
+        unsafe { fbo::bind_framebuffer(&mut ctxt, fbo_id, true, false) };
+    };
+
+    // binding the program and uniforms
+    program.use_program(&mut ctxt);
+    try!(uniforms.bind_uniforms(&mut ctxt, program, &mut fences));
+    check_depth_prepass(&mut ctxt, draw_parameters);
+
+    // sync-ing draw_parameters
+    unsafe {
+        try!(sync_parameters(&mut ctxt, draw_parameters, dimensions, &indices,
+                             vertices_per_patch));
+    }
+
+    // drawing
+    try!(draw_call(&mut ctxt, &indices, vertices_count, instances_count, base_vertex,
+                   &mut fences));
+
+    ctxt.state.next_draw_call_id += 1;
+
+    if let Some(framebuffer) = framebuffer {
+        framebuffer.mark_rendered();
+    }
+
+    // fulfilling the fences
+    for fence in fences.into_iter() {
+        fence.insert(&mut ctxt);
+    }
+
+    Ok(())
+}
+
+/// Replays a list of draw commands that have been recorded and validated beforehand.
+///
+/// The state changes that are known to be redundant are skipped.
+pub fn replay(context: &Context, framebuffer: Option<&ValidatedAttachments>,
+              commands: &[DrawCommand], dimensions: (u32, u32), has_depth_buffer: bool)
+              -> Result<(), DrawError>
+{
+    for command in commands {
+        // the target doesn't necessarily belong to the context of the list, and the upload of
+        // a buffer can have been interrupted since the command was recorded
+        try!(check_buffers_context(context, &command.vertices, &command.indices));
+
+        if !command.program.get_context().is_sharing_objects_with(context) {
+            return Err(DrawError::WrongContext { object_kind: "program" });
+        }
+
+        let draw_parameters = command.draw_parameters;
+
+        if !has_depth_buffer && (draw_parameters.depth.test.requires_depth_buffer() ||
+                                 draw_parameters.depth.write)
+        {
+            return Err(DrawError::NoDepthBuffer);
+        }
+
+        if let Some(viewport) = draw_parameters.viewport {
+            let max = context.capabilities().max_viewport_dims;
+            if viewport.width > max.0 as u32 || viewport.height > max.1 as u32 {
+                return Err(DrawError::ViewportTooLarge);
+            }
+        }
+
+        try!(check_framebuffer(context, framebuffer, draw_parameters));
+
+        if let Some(framebuffer) = framebuffer {
+            try!(command.uniforms.check_feedback_loops(command.program, framebuffer,
+                                                       !draw_parameters.depth.write));
+        }
+    }
+
+    let mut fences = Vec::with_capacity(0);
+
+    let mut ctxt = context.make_current();
+
+    if ctxt.state.lost_context {
+        return Err(DrawError::ContextLost);
+    }
+
+    // all the commands use the same framebuffer
+    {
+        let fbo_id = fbo::FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
+        unsafe { fbo::bind_framebuffer(&mut ctxt, fbo_id, true, false) };
+    };
+
+    // nothing else can modify the state between two commands, so the transitions that were
+    // computed when recording are valid except for the first command and for the command that
+    // follows a skipped one
+    let mut resync = true;
+
+    for command in commands {
+        let (vertices_count, instances_count) = try!(count_vertices(&command.vertices, &*ctxt));
+        if draws_nothing(&command.indices, vertices_count, instances_count) {
+            resync = true;
+            continue;
+        }
+
+        let first = resync;
+        resync = false;
+
+        let (vertices_count, instances_count, base_vertex) =
+            try!(bind_vertices(&mut ctxt, command.program, &command.vertices,
+                               &command.indices, &mut fences));
+
+        if first || command.program_changed {
+            command.program.use_program(&mut ctxt);
+        }
+
+        check_depth_prepass(&mut ctxt, command.draw_parameters);
+
+        if first || command.uniforms_changed {
+            try!(command.uniforms.bind_uniforms(&mut ctxt, command.program, &mut fences));
+        }
+
+        if first || command.parameters_changed {
+            unsafe {
+                try!(sync_parameters(&mut ctxt, command.draw_parameters, dimensions,
+                                     &command.indices, command.vertices_per_patch));
+            }
+        }
+
+        try!(draw_call(&mut ctxt, &command.indices, vertices_count, instances_count,
+                       base_vertex, &mut fences));
+
+        ctxt.state.next_draw_call_id += 1;
+    }
+
+    if let Some(framebuffer) = framebuffer {
+        framebuffer.mark_rendered();
+    }
+
+    // the fences are inserted after the last command, so that they are only signaled once all
+    // the commands that use the buffers have been executed
+    for fence in fences.into_iter() {
+        fence.insert(&mut ctxt);
+    }
+
+    Ok(())
+}
+
+/// Checks that the vertex and index buffers are visible from the context of the target, and
+/// that their upload hasn't been interrupted.
+pub fn check_buffers_context(context: &Context, vertex_buffers: &[VerticesSource],
+                             indices: &IndicesSource) -> Result<(), DrawError>
+{
+    for src in vertex_buffers {
+        if let &VerticesSource::VertexBuffer(ref buffer, _, _) = src {
+            if !buffer.get_context().is_sharing_objects_with(context) {
+                return Err(DrawError::WrongContext { object_kind: "vertex buffer" });
+            }
+
+            if !buffer.is_complete() {
+                return Err(DrawError::IncompleteBuffer { object_kind: "vertex buffer" });
+            }
+        }
+    }
+
+    let (commands, indices) = match *indices {
+        IndicesSource::IndexBuffer { ref buffer, .. } => (None, Some(buffer)),
+        IndicesSource::MultidrawArray { ref buffer, .. } => (Some(buffer), None),
+        IndicesSource::MultidrawElement { ref commands, ref indices, .. } => {
+            (Some(commands), Some(indices))
+        },
+        IndicesSource::NoIndices { .. } => (None, None),
+    };
+
+    if let Some(commands) = commands {
+        if !commands.get_context().is_sharing_objects_with(context) {
+            return Err(DrawError::WrongContext { object_kind: "draw commands buffer" });
+        }
+    }
+
+    if let Some(indices) = indices {
+        if !indices.get_context().is_sharing_objects_with(context) {
+            return Err(DrawError::WrongContext { object_kind: "index buffer" });
+        }
+
+        if !indices.is_complete() {
+            return Err(DrawError::IncompleteBuffer { object_kind: "index buffer" });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether the draw parameters can be used with the framebuffer.
+fn check_framebuffer(context: &Context, framebuffer: Option<&ValidatedAttachments>,
+                     draw_parameters: &DrawParameters) -> Result<(), DrawError>
+{
+    // blending on 32 bits floating-point attachments requires an extension on OpenGL ES
+    if let Some(framebuffer) = framebuffer {
+        let blending = match (draw_parameters.blend.color, draw_parameters.blend.alpha) {
+            (BlendingFunction::AlwaysReplace, BlendingFunction::AlwaysReplace) => false,
+            _ => true
+        };
+
+        if blending && framebuffer.has_float32_color_attachment() &&
+           context.get_version() >= &Version(Api::GlEs, 2, 0) &&
+           !context.get_extensions().gl_ext_float_blend
+        {
+            return Err(DrawError::FloatBlendingNotSupported);
+        }
+    }
+
+    // dual-source blending is only possible with a limited number of draw buffers, which is
+    // usually 1
+    if draw_parameters.blend.uses_dual_source() {
+        let max = context.get_capabilities().max_dual_source_draw_buffers as u32;
+        if max == 0 {
+            return Err(DrawError::DualSourceBlendingNotSupported);
+        }
+
+        let attachments = framebuffer.map(|f| f.get_color_attachment_types().len() as u32)
+                                     .unwrap_or(1);
+        if attachments > max {
+            return Err(DrawError::TooManyDualSourceAttachments {
+                attachments: attachments,
+                max: max,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether the program and the indices can be used for drawing.
+///
+/// Returns the number of vertices per patch if tessellation is used.
+pub fn check_program(context: &Context, program: &Program, indices: &IndicesSource,
+                     draw_parameters: &DrawParameters) -> Result<Option<u16>, DrawError>
+{
+    // the program must be visible from the context of the target
+    if !program.get_context().is_sharing_objects_with(context) {
+        return Err(DrawError::WrongContext { object_kind: "program" });
+    }
+
+    // enabling clip planes without writing `gl_ClipDistance` is undefined behavior
+    if cfg!(debug_assertions) && draw_parameters.clip_planes_bitmask != 0 &&
+       !program.uses_clip_distance() && program.should_print_warning(ProgramWarning::ClipDistance)
+    {
+        println!("glium warning: clip planes are enabled but the program doesn't write to \
+                  `gl_ClipDistance`");
+    }
+
+    // with early fragment tests, the depth test has already been performed when the fragment
+    // shader runs and the values written to `gl_FragDepth` are discarded, which affects both the
+    // depth test and the depth writes, but not draws that don't use the depth buffer at all
+    if cfg!(debug_assertions) && program.has_early_fragment_tests() &&
+       program.writes_frag_depth() &&
+       (draw_parameters.depth.test != DepthTest::Overwrite || draw_parameters.depth.write) &&
+       program.should_print_warning(ProgramWarning::EarlyFragmentTests)
+    {
+        println!("glium warning: the program uses early fragment tests, the values written to \
+                  `gl_FragDepth` are ignored");
+    }
+
+    // the bounding box is only a hint for the primitives generated by geometry or
+    // tessellation shaders
+    if cfg!(debug_assertions) && program.get_output_primitives().is_none() {
+        let bb = &draw_parameters.primitive_bounding_box;
+        if ((bb.0.start, bb.1.start, bb.2.start, bb.3.start) != (-1.0, -1.0, -1.0, -1.0) ||
+            (bb.0.end, bb.1.end, bb.2.end, bb.3.end) != (1.0, 1.0, 1.0, 1.0)) &&
+           program.should_print_warning(ProgramWarning::PrimitiveBoundingBox)
+        {
+            println!("glium warning: `primitive_bounding_box` has no effect with a program \
+                      that has neither a geometry shader nor tessellation shaders");
+        }
+    }
+
     // handling tessellation
-    let vertices_per_patch = match indices.get_primitives_type() {
+    match indices.get_primitives_type() {
         index::PrimitiveType::Patches { vertices_per_patch } => {
             if let Some(max) = context.capabilities().max_patch_vertices {
                 if vertices_per_patch == 0 || vertices_per_patch as gl::types::GLint > max {
@@ -53,7 +366,7 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                 panic!("Default tessellation level is not supported yet");
             }*/
 
-            Some(vertices_per_patch)
+            Ok(Some(vertices_per_patch))
         },
         _ => {
             // TODO: programs created from binaries have the wrong value
@@ -62,276 +375,373 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                 return Err(DrawError::TessellationWithoutPatches);
             }*/
 
-            None
+            Ok(None)
         },
-    };
-
-    // starting the state changes
-    let mut ctxt = context.make_current();
+    }
+}
 
-    // handling vertices source
-    let (vertices_count, instances_count, base_vertex) = {
-        let index_buffer = match indices {
-            IndicesSource::IndexBuffer { buffer, .. } => Some(buffer),
-            IndicesSource::MultidrawArray { .. } => None,
-            IndicesSource::MultidrawElement { indices, .. } => Some(indices),
-            IndicesSource::NoIndices { .. } => None,
-        };
+/// Counts the number of vertices and the number of instances in the vertices sources.
+///
+/// The number of vertices is `None` if the lengths of the per-vertex sources don't match, and
+/// the number of instances is `None` if there is no per-instance source.
+pub fn count_vertices<C>(vertex_buffers: &[VerticesSource], caps: &C)
+                         -> Result<(Option<usize>, Option<usize>), DrawError>
+                         where C: CapabilitiesSource
+{
+    // number of vertices in the vertices sources, or `None` if there is a mismatch
+    let mut vertices_count: Option<usize> = None;
+    let mut vertices_mismatch = false;
+    // number of instances to draw
+    let mut instances_count: Option<usize> = None;
+
+    for src in vertex_buffers {
+        let (len, per_instance) = match src {
+            &VerticesSource::VertexBuffer(ref buffer, format, per_instance) => {
+                if !per_instance && format.iter().any(|&(_, _, _, _, d)| d.is_some()) {
+                    return Err(DrawError::DivisorOnPerVertexSource);
+                }
 
-        // determining whether we can use the `base_vertex` variants for drawing
-        let use_base_vertex = match indices {
-            IndicesSource::MultidrawArray { .. } => false,
-            IndicesSource::MultidrawElement { .. } => false,
-            IndicesSource::NoIndices { .. } => true,
-            _ => ctxt.version >= &Version(Api::Gl, 3, 2) ||
-                 ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                 ctxt.extensions.gl_arb_draw_elements_base_vertex ||
-                 ctxt.extensions.gl_oes_draw_elements_base_vertex
+                (buffer.get_elements_count(), per_instance)
+            },
+            &VerticesSource::Marker { len, per_instance } => (len, per_instance),
+            &VerticesSource::Defaults(_) => continue,
         };
 
-        // object that is used to build the bindings
-        let mut binder = VertexAttributesSystem::start(&mut ctxt, program, index_buffer,
-                                                       use_base_vertex);
-        // number of vertices in the vertices sources, or `None` if there is a mismatch
-        let mut vertices_count: Option<usize> = None;
-        // number of instances to draw
-        let mut instances_count: Option<usize> = None;
-
-        for src in vertex_buffers.iter() {
-            match src {
-                VerticesSource::VertexBuffer(buffer, format, per_instance) => {
-                    // TODO: assert!(buffer.get_elements_size() == total_size(format));
-
-                    if let Some(fence) = buffer.add_fence() {
-                        fences.push(fence);
-                    }
-
-                    binder = binder.add(&buffer, format, if per_instance { Some(1) } else { None });
-                },
-                _ => {}
+        if per_instance {
+            if let Some(curr) = instances_count {
+                if curr != len {
+                    return Err(DrawError::InstancesCountMismatch);
+                }
+            } else {
+                instances_count = Some(len);
             }
 
-            match src {
-                VerticesSource::VertexBuffer(ref buffer, _, false) => {
-                    if let Some(curr) = vertices_count {
-                        if curr != buffer.get_elements_count() {
-                            vertices_count = None;
-                            break;
-                        }
-                    } else {
-                        vertices_count = Some(buffer.get_elements_count());
-                    }
-                },
-                VerticesSource::VertexBuffer(ref buffer, _, true) => {
-                    if let Some(curr) = instances_count {
-                        if curr != buffer.get_elements_count() {
-                            return Err(DrawError::InstancesCountMismatch);
-                        }
-                    } else {
-                        instances_count = Some(buffer.get_elements_count());
-                    }
-                },
-                VerticesSource::Marker { len, per_instance } if !per_instance => {
-                    if let Some(curr) = vertices_count {
-                        if curr != len {
-                            vertices_count = None;
-                            break;
-                        }
-                    } else {
-                        vertices_count = Some(len);
-                    }
-                },
-                VerticesSource::Marker { len, per_instance } if per_instance => {
-                    if let Some(curr) = instances_count {
-                        if curr != len {
-                            return Err(DrawError::InstancesCountMismatch);
-                        }
-                    } else {
-                        instances_count = Some(len);
-                    }
-                },
-                _ => ()
+        } else if let Some(curr) = vertices_count {
+            if curr != len {
+                vertices_mismatch = true;
             }
+        } else {
+            vertices_count = Some(len);
         }
+    }
+
+    // checking whether instancing is supported
+    if instances_count.is_some() && !(caps.get_version() >= &Version(Api::Gl, 3, 1) ||
+                                      caps.get_version() >= &Version(Api::GlEs, 3, 0) ||
+                                      caps.get_extensions().gl_arb_draw_instanced)
+    {
+        return Err(DrawError::InstancingNotSupported);
+    }
+
+    Ok((if vertices_mismatch { None } else { vertices_count }, instances_count))
+}
+
+/// Returns true if a draw command with these indices and these numbers of vertices and
+/// instances doesn't draw anything.
+///
+/// Empty buffers and slices are valid sources, and drawing zero instances is allowed.
+fn draws_nothing(indices: &IndicesSource, vertices_count: Option<usize>,
+                 instances_count: Option<usize>) -> bool
+{
+    if instances_count == Some(0) {
+        return true;
+    }
+
+    match *indices {
+        IndicesSource::IndexBuffer { ref buffer, .. } => buffer.get_elements_count() == 0,
+        IndicesSource::MultidrawArray { ref buffer, .. } => buffer.get_elements_count() == 0,
+        IndicesSource::MultidrawElement { ref commands, .. } => commands.get_elements_count() == 0,
+        IndicesSource::NoIndices { .. } => vertices_count == Some(0),
+    }
+}
 
-        (vertices_count, instances_count, binder.bind().unwrap_or(0))
+/// Binds the vertices sources and the indices.
+///
+/// Returns the number of vertices, the number of instances and the base vertex.
+fn bind_vertices<'a, 'f>(ctxt: &mut context::CommandContext, program: &Program,
+                         vertex_buffers: &[VerticesSource<'a>], indices: &IndicesSource,
+                         fences: &mut Vec<Inserter<'f>>)
+                         -> Result<(Option<usize>, Option<usize>, gl::types::GLint), DrawError>
+                         where 'a: 'f
+{
+    let index_buffer = match *indices {
+        IndicesSource::IndexBuffer { ref buffer, .. } => Some(buffer.clone()),
+        IndicesSource::MultidrawArray { .. } => None,
+        IndicesSource::MultidrawElement { ref indices, .. } => Some(indices.clone()),
+        IndicesSource::NoIndices { .. } => None,
     };
 
-    // binding the FBO to draw upon
-    {
-        let fbo_id = fbo::FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
-        unsafe { fbo::bind_framebuffer(&mut ctxt, fbo_id, true, false) };
+    // determining whether we can use the `base_vertex` variants for drawing
+    let use_base_vertex = match *indices {
+        IndicesSource::MultidrawArray { .. } => false,
+        IndicesSource::MultidrawElement { .. } => false,
+        IndicesSource::NoIndices { .. } => true,
+        _ => ctxt.version >= &Version(Api::Gl, 3, 2) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+             ctxt.extensions.gl_arb_draw_elements_base_vertex ||
+             ctxt.extensions.gl_oes_draw_elements_base_vertex
     };
 
-    // binding the program and uniforms
-    program.use_program(&mut ctxt);
-    try!(uniforms.bind_uniforms(&mut ctxt, program, &mut fences));
+    let (vertices_count, instances_count) = try!(count_vertices(vertex_buffers, &*ctxt));
 
-    // sync-ing draw_parameters
-    unsafe {
-        try!(draw_parameters::sync(&mut ctxt, draw_parameters, dimensions, indices.get_primitives_type()));
-        sync_vertices_per_patch(&mut ctxt, vertices_per_patch);
+    // object that is used to build the bindings
+    let mut binder = VertexAttributesSystem::start(ctxt, program, index_buffer,
+                                                   use_base_vertex);
 
-        // TODO: make sure that the program is the right one
-        // TODO: changing the current transform feedback requires pausing/unbinding before changing the program
-        if let Some(ref tf) = draw_parameters.transform_feedback {
-            tf.bind(&mut ctxt, indices.get_primitives_type());
-        } else {
-            TransformFeedbackSession::unbind(&mut ctxt);
+    for src in vertex_buffers {
+        match src {
+            &VerticesSource::VertexBuffer(ref buffer, format, per_instance) => {
+                // TODO: assert!(buffer.get_elements_size() == total_size(format));
+
+                if let Some(fence) = buffer.add_fence() {
+                    fences.push(fence);
+                }
+
+                binder = binder.add(buffer, format, if per_instance { Some(1) } else { None });
+            },
+            &VerticesSource::Defaults(defaults) => {
+                binder = binder.add_defaults(defaults);
+            },
+            &VerticesSource::Marker { .. } => ()
         }
     }
 
-    // drawing
-    // TODO: make this code more readable
+    Ok((vertices_count, instances_count, binder.bind().unwrap_or(0)))
+}
+
+/// In debug builds, prints a warning if the draw command is the second pass of a depth pre-pass
+/// and may not produce the same depth values as the latest first pass.
+///
+/// Must be called after the program has been bound.
+fn check_depth_prepass(ctxt: &mut context::CommandContext, draw_parameters: &DrawParameters) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let depth = &draw_parameters.depth;
+    let program = ctxt.state.program;
+
+    // the first pass writes the depth of the closest fragments without writing any color
+    if depth.write && draw_parameters.color_mask == (false, false, false, false) &&
+       (depth.test == DepthTest::IfLess || depth.test == DepthTest::IfLessOrEqual)
     {
-        match &indices {
-            &IndicesSource::IndexBuffer { ref buffer, data_type, primitives } => {
-                let ptr: *const u8 = ptr::null_mut();
-                let ptr = unsafe { ptr.offset(buffer.get_offset_bytes() as isize) };
+        let prepass = Some((program, depth.range, depth.clamp));
+        if ctxt.state.depth_prepass != prepass {
+            ctxt.state.depth_prepass = prepass;
+            ctxt.state.depth_prepass_warned = false;
+        }
+        return;
+    }
 
-                if let Some(fence) = buffer.add_fence() {
-                    fences.push(fence);
-                }
+    // the second pass only draws the fragments whose depth is equal
+    if depth.test != DepthTest::IfEqual || depth.write || ctxt.state.depth_prepass_warned {
+        return;
+    }
 
-                unsafe {
-                    if let Some(instances_count) = instances_count {
-                        if base_vertex != 0 {
-                            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
-                               ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                               ctxt.extensions.gl_arb_draw_elements_base_vertex
-                            {
-                                ctxt.gl.DrawElementsInstancedBaseVertex(primitives.to_glenum(),
-                                                                     buffer.get_elements_count() as
-                                                                        gl::types::GLsizei,
-                                                                        data_type.to_glenum(),
-                                                                        ptr as *const libc::c_void,
-                                                                        instances_count as
-                                                                        gl::types::GLsizei,
-                                                                        base_vertex);
-
-                            } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
-                                ctxt.gl.DrawElementsInstancedBaseVertexOES(primitives.to_glenum(),
-                                                                     buffer.get_elements_count() as
-                                                                           gl::types::GLsizei,
-                                                                           data_type.to_glenum(),
-                                                                        ptr as *const libc::c_void,
-                                                                           instances_count as
-                                                                           gl::types::GLsizei,
-                                                                           base_vertex);
-                            } else {
-                                unreachable!();
-                            }
+    let (prepass_program, range, clamp) = match ctxt.state.depth_prepass {
+        Some(p) => p,
+        None => return
+    };
 
+    if prepass_program != program {
+        println!("glium warning: the second pass of a depth pre-pass uses a different program \
+                  than the first pass, the `IfEqual` depth test only works if both programs \
+                  compute exactly the same positions");
+        ctxt.state.depth_prepass_warned = true;
+
+    } else if range != depth.range || clamp != depth.clamp {
+        println!("glium warning: the second pass of a depth pre-pass uses a different depth \
+                  range or depth clamping than the first pass, the `IfEqual` depth test will \
+                  fail");
+        ctxt.state.depth_prepass_warned = true;
+    }
+}
+
+/// Syncs the draw parameters and the transform feedback session.
+unsafe fn sync_parameters(ctxt: &mut context::CommandContext, draw_parameters: &DrawParameters,
+                          dimensions: (u32, u32), indices: &IndicesSource,
+                          vertices_per_patch: Option<u16>) -> Result<(), DrawError>
+{
+    try!(draw_parameters::sync(ctxt, draw_parameters, dimensions, indices.get_primitives_type()));
+    sync_vertices_per_patch(ctxt, vertices_per_patch);
+
+    // TODO: make sure that the program is the right one
+    // TODO: changing the current transform feedback requires pausing/unbinding before changing the program
+    if let Some(ref tf) = draw_parameters.transform_feedback {
+        tf.bind(ctxt, indices.get_primitives_type());
+    } else {
+        TransformFeedbackSession::unbind(ctxt);
+    }
+
+    Ok(())
+}
+
+/// Calls the draw function that corresponds to the indices.
+// TODO: make this code more readable
+fn draw_call<'a, 'f>(mut ctxt: &mut context::CommandContext, indices: &IndicesSource<'a>,
+                     vertices_count: Option<usize>, instances_count: Option<usize>,
+                     base_vertex: gl::types::GLint, fences: &mut Vec<Inserter<'f>>)
+                     -> Result<(), DrawError> where 'a: 'f
+{
+    match indices {
+        &IndicesSource::IndexBuffer { ref buffer, data_type, primitives } => {
+            let ptr: *const u8 = ptr::null_mut();
+            let ptr = unsafe { ptr.offset(buffer.get_offset_bytes() as isize) };
+
+            if let Some(fence) = buffer.add_fence() {
+                fences.push(fence);
+            }
+
+            unsafe {
+                if let Some(instances_count) = instances_count {
+                    if base_vertex != 0 {
+                        if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+                           ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+                           ctxt.extensions.gl_arb_draw_elements_base_vertex
+                        {
+                            ctxt.gl.DrawElementsInstancedBaseVertex(primitives.to_glenum(),
+                                                                 buffer.get_elements_count() as
+                                                                    gl::types::GLsizei,
+                                                                    data_type.to_glenum(),
+                                                                    ptr as *const libc::c_void,
+                                                                    instances_count as
+                                                                    gl::types::GLsizei,
+                                                                    base_vertex);
+
+                        } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
+                            ctxt.gl.DrawElementsInstancedBaseVertexOES(primitives.to_glenum(),
+                                                                 buffer.get_elements_count() as
+                                                                       gl::types::GLsizei,
+                                                                       data_type.to_glenum(),
+                                                                    ptr as *const libc::c_void,
+                                                                       instances_count as
+                                                                       gl::types::GLsizei,
+                                                                       base_vertex);
                         } else {
-                            ctxt.gl.DrawElementsInstanced(primitives.to_glenum(),
-                                                          buffer.get_elements_count() as
-                                                          gl::types::GLsizei,
-                                                          data_type.to_glenum(),
-                                                          ptr as *const libc::c_void,
-                                                          instances_count as gl::types::GLsizei);
+                            unreachable!();
                         }
 
+                    } else if ctxt.version >= &Version(Api::Gl, 3, 1) ||
+                              ctxt.version >= &Version(Api::GlEs, 3, 0)
+                    {
+                        ctxt.gl.DrawElementsInstanced(primitives.to_glenum(),
+                                                      buffer.get_elements_count() as
+                                                      gl::types::GLsizei,
+                                                      data_type.to_glenum(),
+                                                      ptr as *const libc::c_void,
+                                                      instances_count as gl::types::GLsizei);
+
                     } else {
-                        if base_vertex != 0 {
-                            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
-                               ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                               ctxt.extensions.gl_arb_draw_elements_base_vertex
-                            {
-                                ctxt.gl.DrawElementsBaseVertex(primitives.to_glenum(),
-                                                               buffer.get_elements_count() as
-                                                               gl::types::GLsizei,
-                                                               data_type.to_glenum(),
-                                                               ptr as *const libc::c_void,
-                                                               base_vertex);
-
-                            } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
-                                ctxt.gl.DrawElementsBaseVertexOES(primitives.to_glenum(),
-                                                                  buffer.get_elements_count() as
-                                                                  gl::types::GLsizei,
-                                                                  data_type.to_glenum(),
-                                                                  ptr as *const libc::c_void,
-                                                                  base_vertex);
-                            } else {
-                                unreachable!();
-                            }
+                        ctxt.gl.DrawElementsInstancedARB(primitives.to_glenum(),
+                                                         buffer.get_elements_count() as
+                                                         gl::types::GLsizei,
+                                                         data_type.to_glenum(),
+                                                         ptr as *const libc::c_void,
+                                                         instances_count as gl::types::GLsizei);
+                    }
 
+                } else {
+                    if base_vertex != 0 {
+                        if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+                           ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+                           ctxt.extensions.gl_arb_draw_elements_base_vertex
+                        {
+                            ctxt.gl.DrawElementsBaseVertex(primitives.to_glenum(),
+                                                           buffer.get_elements_count() as
+                                                           gl::types::GLsizei,
+                                                           data_type.to_glenum(),
+                                                           ptr as *const libc::c_void,
+                                                           base_vertex);
+
+                        } else if ctxt.extensions.gl_oes_draw_elements_base_vertex {
+                            ctxt.gl.DrawElementsBaseVertexOES(primitives.to_glenum(),
+                                                              buffer.get_elements_count() as
+                                                              gl::types::GLsizei,
+                                                              data_type.to_glenum(),
+                                                              ptr as *const libc::c_void,
+                                                              base_vertex);
                         } else {
-                            ctxt.gl.DrawElements(primitives.to_glenum(),
-                                                 buffer.get_elements_count() as gl::types::GLsizei,
-                                                 data_type.to_glenum(),
-                                                 ptr as *const libc::c_void);
+                            unreachable!();
                         }
+
+                    } else {
+                        ctxt.gl.DrawElements(primitives.to_glenum(),
+                                             buffer.get_elements_count() as gl::types::GLsizei,
+                                             data_type.to_glenum(),
+                                             ptr as *const libc::c_void);
                     }
                 }
-            },
-
-            &IndicesSource::MultidrawArray { ref buffer, primitives } => {
-                let ptr: *const u8 = ptr::null_mut();
-                let ptr = unsafe { ptr.offset(buffer.get_offset_bytes() as isize) };
+            }
+        },
 
-                debug_assert_eq!(base_vertex, 0);       // enforced earlier in this function
+        &IndicesSource::MultidrawArray { ref buffer, primitives } => {
+            let ptr: *const u8 = ptr::null_mut();
+            let ptr = unsafe { ptr.offset(buffer.get_offset_bytes() as isize) };
 
-                if let Some(fence) = buffer.add_fence() {
-                    fences.push(fence);
-                }
+            debug_assert_eq!(base_vertex, 0);       // enforced in `bind_vertices`
 
-                unsafe {
-                    buffer.prepare_and_bind_for_draw_indirect(&mut ctxt);
-                    ctxt.gl.MultiDrawArraysIndirect(primitives.to_glenum(), ptr as *const _,
-                                                    buffer.get_elements_count() as gl::types::GLsizei,
-                                                    0);
-                }
-            },
+            if let Some(fence) = buffer.add_fence() {
+                fences.push(fence);
+            }
 
-            &IndicesSource::MultidrawElement { ref commands, ref indices, data_type, primitives } => {
-                let cmd_ptr: *const u8 = ptr::null_mut();
-                let cmd_ptr = unsafe { cmd_ptr.offset(commands.get_offset_bytes() as isize) };
+            unsafe {
+                buffer.prepare_and_bind_for_draw_indirect(&mut ctxt);
+                ctxt.gl.MultiDrawArraysIndirect(primitives.to_glenum(), ptr as *const _,
+                                                buffer.get_elements_count() as gl::types::GLsizei,
+                                                0);
+            }
+        },
 
-                if let Some(fence) = commands.add_fence() {
-                    fences.push(fence);
-                }
+        &IndicesSource::MultidrawElement { ref commands, ref indices, data_type, primitives } => {
+            let cmd_ptr: *const u8 = ptr::null_mut();
+            let cmd_ptr = unsafe { cmd_ptr.offset(commands.get_offset_bytes() as isize) };
 
-                if let Some(fence) = indices.add_fence() {
-                    fences.push(fence);
-                }
+            if let Some(fence) = commands.add_fence() {
+                fences.push(fence);
+            }
 
-                unsafe {
-                    commands.prepare_and_bind_for_draw_indirect(&mut ctxt);
-                    debug_assert_eq!(base_vertex, 0);       // enforced earlier in this function
-                    ctxt.gl.MultiDrawElementsIndirect(primitives.to_glenum(), data_type.to_glenum(),
-                                                      cmd_ptr as *const _,
-                                                      commands.get_elements_count() as gl::types::GLsizei,
-                                                      0);
-                }
-            },
+            if let Some(fence) = indices.add_fence() {
+                fences.push(fence);
+            }
 
-            &IndicesSource::NoIndices { primitives } => {
-                let vertices_count = match vertices_count {
-                    Some(c) => c,
-                    None => return Err(DrawError::VerticesSourcesLengthMismatch)
-                };
+            unsafe {
+                commands.prepare_and_bind_for_draw_indirect(&mut ctxt);
+                debug_assert_eq!(base_vertex, 0);       // enforced in `bind_vertices`
+                ctxt.gl.MultiDrawElementsIndirect(primitives.to_glenum(), data_type.to_glenum(),
+                                                  cmd_ptr as *const _,
+                                                  commands.get_elements_count() as gl::types::GLsizei,
+                                                  0);
+            }
+        },
 
-                unsafe {
-                    if let Some(instances_count) = instances_count {
+        &IndicesSource::NoIndices { primitives } => {
+            let vertices_count = match vertices_count {
+                Some(c) => c,
+                None => return Err(DrawError::VerticesSourcesLengthMismatch)
+            };
+
+            unsafe {
+                if let Some(instances_count) = instances_count {
+                    if ctxt.version >= &Version(Api::Gl, 3, 1) ||
+                       ctxt.version >= &Version(Api::GlEs, 3, 0)
+                    {
                         ctxt.gl.DrawArraysInstanced(primitives.to_glenum(), base_vertex,
                                                     vertices_count as gl::types::GLsizei,
                                                     instances_count as gl::types::GLsizei);
                     } else {
-                        ctxt.gl.DrawArrays(primitives.to_glenum(), base_vertex,
-                                           vertices_count as gl::types::GLsizei);
+                        ctxt.gl.DrawArraysInstancedARB(primitives.to_glenum(), base_vertex,
+                                                       vertices_count as gl::types::GLsizei,
+                                                       instances_count as gl::types::GLsizei);
                     }
+                } else {
+                    ctxt.gl.DrawArrays(primitives.to_glenum(), base_vertex,
+                                       vertices_count as gl::types::GLsizei);
                 }
-            },
-        };
+            }
+        },
     };
 
-    ctxt.state.next_draw_call_id += 1;
-
-    // fulfilling the fences
-    for fence in fences.into_iter() {
-        fence.insert(&mut ctxt);
-    }
-
+    ctxt.frame_statistics.update(|s| s.draw_calls += 1);
     Ok(())
 }
 
@@ -0,0 +1,225 @@
+use std::rc::Rc;
+
+use buffer::DetachedAlloc;
+use context::Context;
+use context::label_internal_object;
+use index::{NoIndices, PrimitiveType};
+use program::DetachedProgram;
+use sync::DeferredDestroy;
+use texture::Texture2d;
+use uniforms::{MagnifySamplerFilter, Uniforms};
+use vertex::{VertexBuffer, VertexFormat};
+
+use gl;
+use BufferExt;
+use ContextExt;
+use DrawError;
+use DrawParameters;
+use GlObject;
+use Handle;
+use Program;
+use ProgramExt;
+use Surface;
+use TextureExt;
+
+/// Objects used by `Surface::fill_with_texture` and `Surface::fill_with_program`.
+///
+/// They are created the first time they are needed and stored in the context. They are kept
+/// detached from the context, otherwise they would keep it alive, and are only attached
+/// during the draw commands.
+pub struct FillResources {
+    /// A single triangle that covers the whole viewport.
+    triangle: (DetachedAlloc, VertexFormat),
+
+    /// Program that copies a texture, or `None` if it hasn't been needed yet.
+    program: Option<DetachedProgram>,
+}
+
+impl FillResources {
+    /// Destroys the OpenGL objects. Must be called in the thread of the context.
+    pub fn destroy(self, context: &Context) {
+        self.triangle.0.destroy(context);
+
+        if let Some(program) = self.program {
+            program.destroy(context);
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct FillVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+}
+
+implement_vertex!(FillVertex, position, tex_coords);
+
+/// Draws a texture over the whole surface.
+pub fn fill_with_texture<S>(surface: &mut S, texture: &Texture2d, filter: MagnifySamplerFilter,
+                            draw_parameters: &DrawParameters) -> Result<(), DrawError>
+                            where S: Surface
+{
+    let context = texture.get_context();
+    try!(build_resources(context, true));
+
+    let uniforms = uniform! {
+        tex: texture.sampled().magnify_filter(filter),
+    };
+
+    let (triangle, program) = take_resources(context);
+    let result = surface.draw(&triangle, NoIndices(PrimitiveType::TrianglesList),
+                              program.as_ref().unwrap(), &uniforms, draw_parameters);
+    store_resources(context, triangle, program);
+    result
+}
+
+/// Draws over the whole surface with a program supplied by the user.
+pub fn fill_with_program<S, U>(surface: &mut S, program: &Program, uniforms: &U,
+                               draw_parameters: &DrawParameters) -> Result<(), DrawError>
+                               where S: Surface, U: Uniforms
+{
+    let context = ProgramExt::get_context(program);
+    try!(build_resources(context, false));
+
+    let (triangle, fill_program) = take_resources(context);
+    let result = surface.draw(&triangle, NoIndices(PrimitiveType::TrianglesList), program,
+                              uniforms, draw_parameters);
+    store_resources(context, triangle, fill_program);
+    result
+}
+
+/// Takes the objects out of the context and attaches them to it.
+///
+/// The objects must have been created with `build_resources`. If they are dropped instead of
+/// being stored back, they are destroyed and created again the next time they are needed.
+fn take_resources(context: &Rc<Context>) -> (VertexBuffer<FillVertex>, Option<Program>) {
+    let resources = context.get_fill_resources().borrow_mut().take().unwrap();
+    let triangle = DeferredDestroy::attach(resources.triangle, context);
+    let program = resources.program.map(|p| Program::attach(p, context));
+    (triangle, program)
+}
+
+/// Detaches the objects from the context and stores them back in it.
+fn store_resources(context: &Context, triangle: VertexBuffer<FillVertex>,
+                   program: Option<Program>)
+{
+    *context.get_fill_resources().borrow_mut() = Some(FillResources {
+        triangle: DeferredDestroy::detach(triangle),
+        program: program.map(|p| p.detach()),
+    });
+}
+
+/// Creates the objects that are missing in the context.
+fn build_resources(context: &Rc<Context>, with_program: bool) -> Result<(), DrawError> {
+    if context.get_fill_resources().borrow().is_none() {
+        // the texture coordinates are in the range `[0.0, 1.0]` inside of the viewport
+        let triangle = try!(VertexBuffer::new(context, &[
+            FillVertex { position: [-1.0, -1.0], tex_coords: [0.0, 0.0] },
+            FillVertex { position: [ 3.0, -1.0], tex_coords: [2.0, 0.0] },
+            FillVertex { position: [-1.0,  3.0], tex_coords: [0.0, 2.0] },
+        ]).map_err(DrawError::FillVertexBufferCreation));
+
+        label_internal_object(&context.make_current(), gl::BUFFER, triangle.get_buffer_id(),
+                              format_args!("vertex buffer used to fill surfaces"));
+
+        store_resources(context, triangle, None);
+    }
+
+    let has_program = context.get_fill_resources().borrow().as_ref().unwrap().program.is_some();
+    if !with_program || has_program {
+        return Ok(());
+    }
+
+    // the program doesn't output sRGB, so that the texture is converted if the target is sRGB
+    let program = program!(context,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec2 tex_coords;
+                out vec2 v_tex_coords;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_tex_coords = tex_coords;
+                }
+            ",
+            fragment: "
+                #version 140
+
+                uniform sampler2D tex;
+                in vec2 v_tex_coords;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = texture(tex, v_tex_coords);
+                }
+            "
+        },
+
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec2 tex_coords;
+                varying vec2 v_tex_coords;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_tex_coords = tex_coords;
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform sampler2D tex;
+                varying vec2 v_tex_coords;
+
+                void main() {
+                    gl_FragColor = texture2D(tex, v_tex_coords);
+                }
+            "
+        },
+
+        100 es => {
+            vertex: "
+                #version 100
+
+                precision mediump float;
+
+                attribute vec2 position;
+                attribute vec2 tex_coords;
+                varying vec2 v_tex_coords;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_tex_coords = tex_coords;
+                }
+            ",
+            fragment: "
+                #version 100
+
+                precision mediump float;
+
+                uniform sampler2D tex;
+                varying vec2 v_tex_coords;
+
+                void main() {
+                    gl_FragColor = texture2D(tex, v_tex_coords);
+                }
+            "
+        },
+    );
+    let program = try!(program.map_err(DrawError::FillProgramCreation));
+
+    if let Handle::Id(id) = program.get_id() {
+        label_internal_object(&context.make_current(), gl::PROGRAM, id,
+                              format_args!("program used to fill surfaces"));
+    }
+
+    let (triangle, _) = take_resources(context);
+    store_resources(context, triangle, Some(program));
+    Ok(())
+}
@@ -1,9 +1,18 @@
 pub use self::blit::blit;
-pub use self::clear::clear;
-pub use self::draw::draw;
-pub use self::read::{read, ReadError, Source, Destination};
+pub use self::capture::{capture, capture_async, poll_async_captures};
+pub use self::capture::{AsyncCapture, AsyncCaptureQueue};
+pub use self::clear::{clear, clear_color_buffer, flush_pending_clear, ClearBufferData};
+pub use self::draw::{draw, replay, check_program, check_buffers_context, count_vertices};
+pub use self::fill::{fill_with_texture, fill_with_program, FillResources};
+pub use self::pixel_store::{set_pack_tightly_packed, set_unpack_tightly_packed};
+pub use self::pixel_store::set_default_pixel_store;
+pub use self::read::{read, flip_rows, ReadError, Source, Destination};
+pub use self::read::{client_format_to_gl_enum, integer_client_format, copy_to_texture};
 
 mod blit;
+mod capture;
 mod clear;
 mod draw;
+mod fill;
+mod pixel_store;
 mod read;
@@ -0,0 +1,80 @@
+//! Parameters of `glPixelStore` that are used by the pixel transfers.
+//!
+//! Glium never assumes that the pixel store parameters have their default values. Each upload
+//! and download sets all the parameters that affect the way the data is laid out in memory,
+//! so that a value left by a previous transfer or by external code has no effect.
+
+use gl;
+use context::CommandContext;
+use context::GlState;
+use version::Api;
+use version::Version;
+
+/// Sets the unpack parameters so that the data passed to `glTexImage*`, `glTexSubImage*` and
+/// `glCompressedTex*Image*` is read as tightly-packed rows, starting at the first pixel.
+#[inline]
+pub fn set_unpack_tightly_packed(ctxt: &mut CommandContext) {
+    unsafe { set_unpack_parameters(ctxt, 1) };
+}
+
+/// Sets the pack parameters so that the data written by `glReadPixels`, `glGetTexImage` and
+/// `glGetCompressedTexImage` is made of tightly-packed rows, starting at the first pixel.
+#[inline]
+pub fn set_pack_tightly_packed(ctxt: &mut CommandContext) {
+    unsafe { set_pack_parameters(ctxt, 1) };
+}
+
+/// Restores the default values of all the pack and unpack parameters, so that external code
+/// that assumes them isn't affected by the previous transfers.
+#[inline]
+pub fn set_default_pixel_store(ctxt: &mut CommandContext) {
+    unsafe {
+        set_unpack_parameters(ctxt, 4);
+        set_pack_parameters(ctxt, 4);
+    }
+}
+
+/// Sets `GL_UNPACK_ALIGNMENT` to `alignment` and the other unpack parameters to zero.
+unsafe fn set_unpack_parameters(ctxt: &mut CommandContext, alignment: gl::types::GLint) {
+    set(ctxt, gl::UNPACK_ALIGNMENT, alignment, |s| &mut s.pixel_store_unpack_alignment);
+
+    if ctxt.version >= &Version(Api::Gl, 1, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) {
+        set(ctxt, gl::UNPACK_ROW_LENGTH, 0, |s| &mut s.pixel_store_unpack_row_length);
+        set(ctxt, gl::UNPACK_SKIP_ROWS, 0, |s| &mut s.pixel_store_unpack_skip_rows);
+        set(ctxt, gl::UNPACK_SKIP_PIXELS, 0, |s| &mut s.pixel_store_unpack_skip_pixels);
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 1, 2) || ctxt.version >= &Version(Api::GlEs, 3, 0) {
+        set(ctxt, gl::UNPACK_IMAGE_HEIGHT, 0, |s| &mut s.pixel_store_unpack_image_height);
+        set(ctxt, gl::UNPACK_SKIP_IMAGES, 0, |s| &mut s.pixel_store_unpack_skip_images);
+    }
+}
+
+/// Sets `GL_PACK_ALIGNMENT` to `alignment` and the other pack parameters to zero.
+unsafe fn set_pack_parameters(ctxt: &mut CommandContext, alignment: gl::types::GLint) {
+    set(ctxt, gl::PACK_ALIGNMENT, alignment, |s| &mut s.pixel_store_pack_alignment);
+
+    if ctxt.version >= &Version(Api::Gl, 1, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) {
+        set(ctxt, gl::PACK_ROW_LENGTH, 0, |s| &mut s.pixel_store_pack_row_length);
+        set(ctxt, gl::PACK_SKIP_ROWS, 0, |s| &mut s.pixel_store_pack_skip_rows);
+        set(ctxt, gl::PACK_SKIP_PIXELS, 0, |s| &mut s.pixel_store_pack_skip_pixels);
+    }
+
+    // these two don't exist in OpenGL ES
+    if ctxt.version >= &Version(Api::Gl, 1, 2) {
+        set(ctxt, gl::PACK_IMAGE_HEIGHT, 0, |s| &mut s.pixel_store_pack_image_height);
+        set(ctxt, gl::PACK_SKIP_IMAGES, 0, |s| &mut s.pixel_store_pack_skip_images);
+    }
+}
+
+/// Calls `glPixelStorei` if the value in the state cache is different.
+unsafe fn set<F>(ctxt: &mut CommandContext, parameter: gl::types::GLenum,
+                 value: gl::types::GLint, field: F)
+                 where F: FnOnce(&mut GlState) -> &mut gl::types::GLint
+{
+    let cached = field(&mut *ctxt.state);
+    if *cached != value {
+        *cached = value;
+        ctxt.gl.PixelStorei(parameter, value);
+    }
+}
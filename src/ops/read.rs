@@ -1,8 +1,12 @@
+use std::error::Error;
+use std::fmt;
+use std::mem;
 use std::ptr;
 
 use pixel_buffer::PixelBuffer;
 use texture::ClientFormat;
 use texture::PixelValue;
+use texture::{Texture2d, TextureAny, TextureAnyMipmap};
 
 use fbo;
 use fbo::FramebuffersContainer;
@@ -10,7 +14,10 @@ use fbo::FramebuffersContainer;
 use buffer::BufferAny;
 use BufferExt;
 use Rect;
+use TextureMipmapExt;
 use context::CommandContext;
+use context::WaitKind;
+use ops::pixel_store;
 use gl;
 
 use version::Version;
@@ -20,8 +27,11 @@ use version::Api;
 pub enum Source<'a> {
     /// A regular framebuffer attachment.
     Attachment(&'a fbo::RegularAttachment<'a>),
+    /// A buffer of the default framebuffer, with the dimensions of the framebuffer.
     // TODO: use a Rust enum
-    DefaultFramebuffer(gl::types::GLenum),
+    DefaultFramebuffer(gl::types::GLenum, (u32, u32)),
+    /// The first color attachment of a framebuffer.
+    Framebuffer(&'a fbo::ValidatedAttachments<'a>),
 }
 
 impl<'a> From<&'a fbo::RegularAttachment<'a>> for Source<'a> {
@@ -35,7 +45,13 @@ impl<'a> From<&'a fbo::RegularAttachment<'a>> for Source<'a> {
 pub enum Destination<'a, P> where P: PixelValue {
     Memory(&'a mut Vec<P>),
     PixelBuffer(&'a PixelBuffer<P>),
-    // TODO: texture with glCopyTexSubImage2D
+    /// An existing slice whose length must match the rectangle exactly, and the format of the
+    /// pixels to write into it.
+    Bytes(&'a mut [u8], ClientFormat),
+    /// A level of a two-dimensional texture, and the position in the level where the bottom-left
+    /// corner of the rectangle is written. The pixels are copied with `glCopyTexSubImage2D`
+    /// without going through the CPU.
+    Texture(TextureAnyMipmap<'a>, (u32, u32)),
 }
 
 impl<'a, P> From<&'a mut Vec<P>> for Destination<'a, P> where P: PixelValue {
@@ -53,7 +69,7 @@ impl<'a, P> From<&'a PixelBuffer<P>> for Destination<'a, P> where P: PixelValue
 }
 
 /// Error that can happen while reading.
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ReadError {
     /// The implementation doesn't support converting to the requested output format.
     ///
@@ -70,31 +86,153 @@ pub enum ReadError {
     /// Clamping the values is not supported by the implementation.
     ClampingNotSupported,
 
+    /// The rectangle to read is not entirely inside of the source.
+    RectOutOfBounds,
+
+    /// The length of the destination doesn't match the size of the rectangle multiplied by the
+    /// size of a pixel.
+    WrongDestinationSize {
+        /// Number of bytes required by the rectangle.
+        expected: usize,
+        /// Length of the destination.
+        obtained: usize,
+    },
+
+    /// The destination texture doesn't have the requested mipmap level, or the rectangle
+    /// doesn't fit in it at the requested position.
+    DestinationOutOfBounds,
+
+    /// The source has integral values and the destination texture has floating-point values or
+    /// vice versa, or the source doesn't have a color buffer.
+    IncompatibleDestination,
+
+    /// The source is multisampled. Copying to a texture requires resolving it first, for
+    /// example by blitting it to a framebuffer that isn't multisampled.
+    MultisampleSource,
+
     // TODO: context lost
 }
 
+impl fmt::Display for ReadError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &ReadError::WrongDestinationSize { expected, obtained } => {
+                write!(formatter, "{} (expected {} bytes, got {})", self.description(),
+                       expected, obtained)
+            },
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn description(&self) -> &str {
+        match self {
+            &ReadError::OutputFormatNotSupported => {
+                "The implementation doesn't support converting to the requested output format"
+            },
+            &ReadError::AttachmentTypeNotSupported => {
+                "The implementation doesn't support reading this type of attachment"
+            },
+            &ReadError::ClampingNotSupported => {
+                "Clamping the values is not supported by the implementation"
+            },
+            &ReadError::RectOutOfBounds => {
+                "The rectangle to read is not entirely inside of the source"
+            },
+            &ReadError::WrongDestinationSize { .. } => {
+                "The length of the destination doesn't match the rectangle to read"
+            },
+            &ReadError::DestinationOutOfBounds => {
+                "The rectangle doesn't fit in the destination texture"
+            },
+            &ReadError::IncompatibleDestination => {
+                "The format of the destination texture isn't compatible with the source"
+            },
+            &ReadError::MultisampleSource => {
+                "The source is multisampled and must be resolved before being copied"
+            },
+        }
+    }
+}
+
 /// Reads pixels from the source into the destination.
 ///
-/// Panicks if the destination is not large enough.
+/// The rectangle is clipped to the dimensions of the source. Returns the rectangle that has
+/// actually been read.
+///
+/// Panicks if the destination is not large enough. With `Destination::Bytes`, the rectangle
+/// is not clipped and an error is returned instead if the rectangle or the slice are wrong.
 ///
 /// The `(u8, u8, u8, u8)` format is guaranteed to be supported.
 // TODO: differentiate between GL_* and GL_*_INTEGER
 #[inline]
 pub fn read<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect, dest: D,
-                         clamp: bool) -> Result<(), ReadError>
+                         clamp: bool) -> Result<Rect, ReadError>
                          where S: Into<Source<'a>>, D: Into<Destination<'a, T>>,
                                T: PixelValue
 {
     let source = source.into();
     let dest = dest.into();
-    let output_pixel_format = <T as PixelValue>::get_format();
+    let output_pixel_format = match dest {
+        Destination::Bytes(_, format) => format,
+        _ => <T as PixelValue>::get_format(),
+    };
+
+    // reading outside of the source returns undefined values
+    let source_dimensions = match source {
+        Source::Attachment(&fbo::RegularAttachment::Texture(ref tex)) => {
+            (tex.get_width(), tex.get_height().unwrap_or(1))
+        },
+        Source::Attachment(&fbo::RegularAttachment::RenderBuffer(rb)) => rb.get_dimensions(),
+        Source::DefaultFramebuffer(_, dimensions) => dimensions,
+        Source::Framebuffer(attachments) => attachments.get_dimensions(),
+    };
+
+    // the rectangle is not clipped when reading into a slice or into a texture
+    match dest {
+        Destination::Bytes(..) | Destination::Texture(..) => {
+            if rect.left < 0 || rect.bottom < 0 ||
+               rect.left as i64 + rect.width as i64 > source_dimensions.0 as i64 ||
+               rect.bottom as i64 + rect.height as i64 > source_dimensions.1 as i64
+            {
+                return Err(ReadError::RectOutOfBounds);
+            }
+        },
+        _ => (),
+    };
+
+    if let Destination::Texture(mipmap, offset) = dest {
+        if offset.0 as u64 + rect.width as u64 > mipmap.get_width() as u64 ||
+           offset.1 as u64 + rect.height as u64 > mipmap.get_height().unwrap_or(1) as u64
+        {
+            return Err(ReadError::DestinationOutOfBounds);
+        }
+
+        try!(copy_texture_sub_image(ctxt, source, rect, mipmap, offset));
+        return Ok(*rect);
+    }
+
+    if let Destination::Bytes(ref dest, format) = dest {
+        let expected = rect.width as usize * rect.height as usize * format.get_size();
+        if dest.len() != expected {
+            return Err(ReadError::WrongDestinationSize { expected: expected,
+                                                         obtained: dest.len() });
+        }
+    }
+
+    let rect = &rect.clip(source_dimensions);
 
     let pixels_to_read = rect.width * rect.height;
 
     // checking that the output format is supported
     // OpenGL supported everything, while OpenGL ES only supports U8U8U8U8 plus an additional
-    // implementation-defined format
-    if ctxt.version >= &Version(Api::GlEs, 2, 0) && output_pixel_format != ClientFormat::U8U8U8U8 {
+    // implementation-defined format, and `GL_EXT_texture_norm16` adds `RGBA16` for
+    // 16-bits normalized buffers
+    if ctxt.version >= &Version(Api::GlEs, 2, 0) && output_pixel_format != ClientFormat::U8U8U8U8 &&
+       !(ctxt.extensions.gl_ext_texture_norm16 &&
+         output_pixel_format == ClientFormat::U16U16U16U16)
+    {
         // TODO: GLES is guaranteed to support GL_RGBA and an implementation-defined format
         //       queried with GL_IMPLEMENTATION_COLOR_READ_FORMAT. We only handle GL_RGBA.
         return Err(ReadError::OutputFormatNotSupported);
@@ -112,6 +250,17 @@ pub fn read<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect,
                 ctxt.state.clamp_color = gl::FALSE as gl::types::GLenum;
             }
         }
+    } else if ctxt.extensions.gl_arb_color_buffer_float {
+        unsafe {
+            if clamp && ctxt.state.clamp_color != gl::TRUE as gl::types::GLenum {
+                ctxt.gl.ClampColorARB(gl::CLAMP_READ_COLOR_ARB, gl::TRUE as gl::types::GLenum);
+                ctxt.state.clamp_color = gl::TRUE as gl::types::GLenum;
+
+            } else if !clamp && ctxt.state.clamp_color != gl::FALSE as gl::types::GLenum {
+                ctxt.gl.ClampColorARB(gl::CLAMP_READ_COLOR_ARB, gl::FALSE as gl::types::GLenum);
+                ctxt.state.clamp_color = gl::FALSE as gl::types::GLenum;
+            }
+        }
     } else {
         if clamp {
             return Err(ReadError::ClampingNotSupported);
@@ -125,9 +274,12 @@ pub fn read<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect,
         Source::Attachment(attachment) => {
             unsafe { FramebuffersContainer::bind_framebuffer_for_reading(&mut ctxt, attachment) };
         },
-        Source::DefaultFramebuffer(read_buffer) => {
+        Source::DefaultFramebuffer(read_buffer, _) => {
             FramebuffersContainer::bind_default_framebuffer_for_reading(&mut ctxt, read_buffer);
         },
+        Source::Framebuffer(attachments) => {
+            unsafe { FramebuffersContainer::bind_attachments_for_reading(&mut ctxt, attachments) };
+        },
     };
 
     // determining what kind of data we are reading
@@ -143,9 +295,10 @@ pub fn read<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect,
                 },
             }
         },
-        Source::DefaultFramebuffer(read_buffer) => {
+        Source::DefaultFramebuffer(read_buffer, _) => {
             ReadSourceType::Color       // FIXME: wrong
         },
+        Source::Framebuffer(_) => ReadSourceType::Color,
     };
 
     // OpenGL ES doesn't support reading from depth, stencil or depth-stencil attachments by default
@@ -166,7 +319,29 @@ pub fn read<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect,
 
     // obtaining the client format and client type to be passed to `glReadPixels`
     let (format, gltype) = match read_src_type {
-        ReadSourceType::Color => client_format_to_gl_enum(&output_pixel_format),
+        ReadSourceType::Color => {
+            let (format, gltype) = client_format_to_gl_enum(&output_pixel_format);
+
+            // integer attachments must be read with an `*_INTEGER` format
+            let integer = match source {
+                Source::Attachment(&fbo::RegularAttachment::Texture(ref tex)) => {
+                    fbo::get_color_attachment_type(tex.get_texture()) !=
+                                                            fbo::ColorAttachmentType::Float
+                },
+                Source::Framebuffer(attachments) => {
+                    attachments.get_color_attachment_types().first()
+                               .map(|ty| *ty != fbo::ColorAttachmentType::Float)
+                               .unwrap_or(false)
+                },
+                _ => false,
+            };
+
+            if integer {
+                (integer_client_format(format), gltype)
+            } else {
+                (format, gltype)
+            }
+        },
         ReadSourceType::Depth => {
             unimplemented!()        // TODO: 
             // TODO: NV_depth_buffer_float2
@@ -187,48 +362,177 @@ pub fn read<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect,
 
                 BufferAny::unbind_pixel_pack(ctxt);
 
-                // adjusting data alignement
-                let ptr = buf.as_mut_ptr() as *mut D;
-                let ptr = ptr as usize;
-                if (ptr % 8) == 0 {
-                } else if (ptr % 4) == 0 && ctxt.state.pixel_store_pack_alignment != 4 {
-                    ctxt.state.pixel_store_pack_alignment = 4;
-                    ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
-                } else if (ptr % 2) == 0 && ctxt.state.pixel_store_pack_alignment > 2 {
-                    ctxt.state.pixel_store_pack_alignment = 2;
-                    ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 2);
-                } else if ctxt.state.pixel_store_pack_alignment != 1 {
-                    ctxt.state.pixel_store_pack_alignment = 1;
-                    ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
-                }
+                // `buf` contains exactly `pixels_to_read` elements, so the rows must be
+                // tightly packed whatever their width
+                pixel_store::set_pack_tightly_packed(ctxt);
 
+                let wait = ctxt.frame_statistics.start_wait();
                 ctxt.gl.ReadPixels(rect.left as gl::types::GLint, rect.bottom as gl::types::GLint,
                                    rect.width as gl::types::GLsizei,
                                    rect.height as gl::types::GLsizei, format, gltype,
                                    buf.as_mut_ptr() as *mut _);
+                ctxt.frame_statistics.end_wait(wait, WaitKind::Read);
                 buf.set_len(pixels_to_read as usize);
 
                 *dest = buf;
             },
 
+            Destination::Bytes(dest, _) => {
+                BufferAny::unbind_pixel_pack(ctxt);
+
+                // the rows of the slice are tightly packed
+                pixel_store::set_pack_tightly_packed(ctxt);
+
+                let wait = ctxt.frame_statistics.start_wait();
+                ctxt.gl.ReadPixels(rect.left as gl::types::GLint, rect.bottom as gl::types::GLint,
+                                   rect.width as gl::types::GLsizei,
+                                   rect.height as gl::types::GLsizei, format, gltype,
+                                   dest.as_mut_ptr() as *mut _);
+                ctxt.frame_statistics.end_wait(wait, WaitKind::Read);
+            },
+
             Destination::PixelBuffer(pixel_buffer) => {
                 assert!(pixel_buffer.len() >= pixels_to_read as usize);
 
                 pixel_buffer.prepare_and_bind_for_pixel_pack(&mut ctxt);
+                pixel_store::set_pack_tightly_packed(ctxt);
                 ctxt.gl.ReadPixels(rect.left as gl::types::GLint, rect.bottom as gl::types::GLint,
                                    rect.width as gl::types::GLsizei,
                                    rect.height as gl::types::GLsizei, format, gltype,
                                    ptr::null_mut());
 
                 ::pixel_buffer::store_infos(pixel_buffer, (rect.width, rect.height));
+            },
+
+            Destination::Texture(..) => unreachable!(),
+        }
+    };
+
+    ctxt.frame_statistics.update(|s| s.reads += 1);
+    Ok(*rect)
+}
+
+/// Copies a rectangle of a surface into a level of a texture. This is what
+/// `Surface::copy_to_texture` does.
+pub fn copy_to_texture(ctxt: &mut CommandContext, source: Source, rect: &Rect,
+                       texture: &Texture2d, offset: (u32, u32), level: u32)
+                       -> Result<(), ReadError>
+{
+    let mipmap = match TextureAny::mipmap(texture, level) {
+        Some(mipmap) => mipmap,
+        None => return Err(ReadError::DestinationOutOfBounds),
+    };
+
+    try!(read::<_, _, u8>(ctxt, source, rect, Destination::Texture(mipmap, offset), false));
+    Ok(())
+}
+
+/// Copies a rectangle of the source into a texture with `glCopyTexSubImage2D`.
+///
+/// The rectangle must already have been checked against the dimensions of the source and of the
+/// destination.
+fn copy_texture_sub_image(mut ctxt: &mut CommandContext, source: Source, rect: &Rect,
+                   mipmap: TextureAnyMipmap, offset: (u32, u32)) -> Result<(), ReadError>
+{
+    // the values of the source and of the destination must be of the same kind, `None` means
+    // that the type of the source is unknown
+    let source_type = match source {
+        Source::Attachment(&fbo::RegularAttachment::Texture(ref tex)) => {
+            Some(fbo::get_color_attachment_type(tex.get_texture()))
+        },
+        Source::Attachment(&fbo::RegularAttachment::RenderBuffer(_)) => {
+            Some(fbo::ColorAttachmentType::Float)
+        },
+        Source::DefaultFramebuffer(..) => Some(fbo::ColorAttachmentType::Float),
+        Source::Framebuffer(attachments) => {
+            match attachments.get_color_attachment_types().first() {
+                Some(ty) => Some(*ty),
+                None if attachments.is_external() => None,
+                None => return Err(ReadError::IncompatibleDestination),
             }
+        },
+    };
+
+    if let Some(source_type) = source_type {
+        if source_type != fbo::get_color_attachment_type(mipmap.get_texture()) {
+            return Err(ReadError::IncompatibleDestination);
         }
+    }
+
+    // number of samples of the source, `None` if unknown
+    let samples = match source {
+        Source::Attachment(&fbo::RegularAttachment::Texture(ref tex)) => {
+            Some(tex.get_samples().unwrap_or(0))
+        },
+        Source::Attachment(&fbo::RegularAttachment::RenderBuffer(rb)) => {
+            Some(rb.get_samples().unwrap_or(0))
+        },
+        Source::DefaultFramebuffer(..) => None,
+        Source::Framebuffer(attachments) => attachments.get_samples(),
+    };
+
+    if samples.map(|s| s >= 2).unwrap_or(false) {
+        return Err(ReadError::MultisampleSource);
+    }
+
+    match source {
+        Source::Attachment(attachment) => {
+            unsafe { FramebuffersContainer::bind_framebuffer_for_reading(&mut ctxt, attachment) };
+        },
+        Source::DefaultFramebuffer(read_buffer, _) => {
+            FramebuffersContainer::bind_default_framebuffer_for_reading(&mut ctxt, read_buffer);
+        },
+        Source::Framebuffer(attachments) => {
+            unsafe { FramebuffersContainer::bind_attachments_for_reading(&mut ctxt, attachments) };
+        },
     };
 
+    unsafe {
+        if samples.is_none() {
+            let mut sample_buffers = 0;
+            ctxt.gl.GetIntegerv(gl::SAMPLE_BUFFERS, &mut sample_buffers);
+            if sample_buffers != 0 {
+                return Err(ReadError::MultisampleSource);
+            }
+        }
+
+        mipmap.copy_from_read_framebuffer(ctxt, rect, offset);
+    }
+
     Ok(())
 }
 
-fn client_format_to_gl_enum(format: &ClientFormat) -> (gl::types::GLenum, gl::types::GLenum) {
+/// Reverses the order of the rows of an image, in order to turn the bottom-to-top order of
+/// OpenGL into a top-to-bottom order or vice versa.
+pub fn flip_rows<P>(data: &mut [P], width: usize, height: usize) {
+    assert_eq!(data.len(), width * height);
+
+    for row in 0 .. height / 2 {
+        let (top, bottom) = data.split_at_mut((height - row - 1) * width);
+        let top = &mut top[row * width .. (row + 1) * width];
+        let bottom = &mut bottom[.. width];
+
+        for (a, b) in top.iter_mut().zip(bottom.iter_mut()) {
+            mem::swap(a, b);
+        }
+    }
+}
+
+/// Turns a format returned by `client_format_to_gl_enum` into the corresponding format for
+/// reading integers.
+pub fn integer_client_format(format: gl::types::GLenum) -> gl::types::GLenum {
+    match format {
+        gl::RED => gl::RED_INTEGER,
+        gl::RG => gl::RG_INTEGER,
+        gl::RGB => gl::RGB_INTEGER,
+        gl::RGBA => gl::RGBA_INTEGER,
+        _ => format,
+    }
+}
+
+/// Returns the format and type to pass to `glReadPixels` or `glGetTexImage` for a client
+/// format.
+pub fn client_format_to_gl_enum(format: &ClientFormat) -> (gl::types::GLenum, gl::types::GLenum) {
     match *format {
         ClientFormat::U8 => (gl::RED, gl::UNSIGNED_BYTE),
         ClientFormat::U8U8 => (gl::RG, gl::UNSIGNED_BYTE),
@@ -2,9 +2,11 @@ use gl;
 use libc;
 
 use context::CommandContext;
+use context::Context;
 use backend::Facade;
 
 use std::fmt;
+use std::rc::Rc;
 use std::error::Error;
 use std::collections::hash_map::{self, HashMap};
 
@@ -23,6 +25,7 @@ use program::raw::RawProgram;
 
 use buffer::BufferSlice;
 use uniforms::Uniforms;
+use uniforms::UniformHandle;
 
 /// A combination of compute shaders linked together.
 pub struct ComputeShader {
@@ -46,7 +49,7 @@ impl ComputeShader {
         let shader = try!(build_shader(facade, gl::COMPUTE_SHADER, src));
 
         Ok(ComputeShader {
-            raw: try!(RawProgram::from_shaders(facade, &[shader], false, false, None))
+            raw: try!(RawProgram::from_shaders(facade, &[shader], false, false, None, None))
         })
     }
 
@@ -96,6 +99,15 @@ impl ComputeShader {
     pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
         self.raw.get_uniform(name)
     }
+
+    /// Returns a handle to a uniform variable, if it exists.
+    ///
+    /// Binding values with handles avoids looking up the names of the uniforms at each draw
+    /// command. See `PrecomputedUniforms`.
+    #[inline]
+    pub fn get_uniform_handle(&self, name: &str) -> Option<UniformHandle> {
+        self.raw.get_uniform_handle(name)
+    }
     
     /// Returns an iterator to the list of uniforms.
     ///
@@ -160,6 +172,11 @@ impl GlObject for ComputeShader {
 }
 
 impl ProgramExt for ComputeShader {
+    #[inline]
+    fn get_context(&self) -> &Rc<Context> {
+        self.raw.get_context()
+    }
+
     #[inline]
     fn use_program(&self, ctxt: &mut CommandContext) {
         self.raw.use_program(ctxt)
@@ -172,6 +189,13 @@ impl ProgramExt for ComputeShader {
         self.raw.set_uniform(ctxt, uniform_location, value)
     }
 
+    #[inline]
+    fn set_uniform_matrix_array(&self, ctxt: &mut CommandContext, uniform_location: gl::types::GLint,
+                                dimension: u8, data: &[f32])
+    {
+        self.raw.set_uniform_matrix_array(ctxt, uniform_location, dimension, data)
+    }
+
     #[inline]
     fn set_uniform_block_binding(&self, ctxt: &mut CommandContext, block_location: gl::types::GLuint,
                                  value: gl::types::GLuint)
@@ -8,7 +8,9 @@ use version::Api;
 use version::Version;
 
 pub use self::compute::{ComputeShader, ComputeCommand};
-pub use self::program::Program;
+pub use self::program::{Program, PrewarmError, PrewarmIter};
+#[doc(hidden)]
+pub use self::program::DetachedProgram;
 pub use self::reflection::{Uniform, UniformBlock, BlockLayout, OutputPrimitives};
 pub use self::reflection::{Attribute, TransformFeedbackVarying, TransformFeedbackBuffer, TransformFeedbackMode};
 
@@ -69,6 +71,10 @@ pub enum ProgramCreationError {
     /// You have requested point size setting from the shader, but it's not
     /// supported by the backend.
     PointSizeNotSupported,
+
+    /// You have requested an output for dual-source blending, but dual-source blending is not
+    /// supported by the backend.
+    DualSourceBlendingNotSupported,
 }
 
 impl fmt::Display for ProgramCreationError {
@@ -89,6 +95,9 @@ impl fmt::Display for ProgramCreationError {
             &ProgramCreationError::PointSizeNotSupported =>
                 formatter.write_str("You requested point size setting, but it's not \
                                      supported by the backend"),
+            &ProgramCreationError::DualSourceBlendingNotSupported =>
+                formatter.write_str("You requested an output for dual-source blending, but \
+                                     this feature is not supported by the backend"),
         }
     }
 }
@@ -107,6 +116,9 @@ impl Error for ProgramCreationError {
                                                                      supported by the backend.",
             &ProgramCreationError::PointSizeNotSupported => "Point size is not supported by \
                                                              the backend.",
+            &ProgramCreationError::DualSourceBlendingNotSupported => "Dual-source blending is \
+                                                                      not supported by the \
+                                                                      backend.",
         }
     }
 
@@ -165,6 +177,21 @@ pub enum GetBinaryError {
     NotSupported,
 }
 
+impl fmt::Display for GetBinaryError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for GetBinaryError {
+    fn description(&self) -> &str {
+        match self {
+            &GetBinaryError::NotSupported => "The backend doesn't support retreiving the binary \
+                                              of a program",
+        }
+    }
+}
+
 /// Input when creating a program.
 pub enum ProgramCreationInput<'a> {
     /// Use GLSL source code.
@@ -199,6 +226,14 @@ pub enum ProgramCreationInput<'a> {
 
         /// Whether the shader uses point size.
         uses_point_size: bool,
+
+        /// Name of an output of the fragment shader to bind to the index `1` of the first
+        /// color attachment, with `glBindFragDataLocationIndexed`.
+        ///
+        /// This output is the second input of dual-source blending, which is used by the
+        /// `Source1Color` and `Source1Alpha` blending factors. You can also use
+        /// `layout(location = 0, index = 1)` in the shader instead.
+        dual_source_output: Option<&'a str>,
     },
 
     /// Use a precompiled binary.
@@ -247,6 +282,7 @@ impl<'a> From<SourceCode<'a>> for ProgramCreationInput<'a> {
             transform_feedback_varyings: None,
             outputs_srgb: false,
             uses_point_size: false,
+            dual_source_output: None,
         }
     }
 }
@@ -1,6 +1,7 @@
 use gl;
 
 use context::CommandContext;
+use context::Context;
 use version::Version;
 use version::Api;
 
@@ -8,11 +9,14 @@ use backend::Facade;
 use CapabilitiesSource;
 
 use std::fmt;
+use std::rc::Rc;
+use std::cell::{Cell, RefCell};
 use std::error::Error;
 use std::collections::hash_map::{self, HashMap};
 
 use GlObject;
 use ProgramExt;
+use ProgramWarning;
 use Handle;
 use RawUniformValue;
 
@@ -23,15 +27,68 @@ use program::reflection::{Uniform, UniformBlock, OutputPrimitives};
 use program::reflection::{Attribute, TransformFeedbackBuffer};
 use program::shader::build_shader;
 
-use program::raw::RawProgram;
+use program::raw::{RawProgram, DetachedRawProgram};
 
 use vertex::VertexFormat;
+use vertex::VertexBufferAny;
+use vertex::BufferCreationError as VertexBufferCreationError;
+
+use index::{NoIndices, PrimitiveType};
+use uniforms::EmptyUniforms;
+use uniforms::UniformHandle;
+use ops;
+use ContextExt;
+use DrawError;
+use DrawParameters;
+use Rect;
 
 /// A combination of shaders linked together.
 pub struct Program {
+    // The last element is the current link of the program. The other elements are the links
+    // that have been replaced with `reload_from_source`. Their OpenGL objects have been
+    // destroyed, but they are kept alive because the references returned by the getters
+    // (like `get_uniform`) may still point to them. Elements are never modified or removed.
+    links: RefCell<Vec<Box<ProgramLink>>>,
+    generation: Cell<u32>,
+    // Bitmask of the `ProgramWarning`s that have already been printed.
+    printed_warnings: Cell<u8>,
+}
+
+/// Content of a `Program` without the reference to its context. Doesn't destroy the OpenGL
+/// program when dropped.
+#[doc(hidden)]
+pub struct DetachedProgram {
+    links: Vec<DetachedProgramLink>,
+    generation: u32,
+    printed_warnings: u8,
+}
+
+struct DetachedProgramLink {
+    raw: DetachedRawProgram,
+    outputs_srgb: bool,
+    uses_point_size: bool,
+    uses_clip_distance: bool,
+    has_early_fragment_tests: bool,
+    writes_frag_depth: bool,
+}
+
+impl DetachedProgram {
+    /// Destroys the OpenGL program. Must be called in the thread of the context.
+    pub fn destroy(self, context: &Context) {
+        for link in self.links {
+            link.raw.destroy(context);
+        }
+    }
+}
+
+/// The result of linking shaders.
+struct ProgramLink {
     raw: RawProgram,
     outputs_srgb: bool,
     uses_point_size: bool,
+    uses_clip_distance: bool,
+    has_early_fragment_tests: bool,
+    writes_frag_depth: bool,
 }
 
 impl Program {
@@ -39,78 +96,108 @@ impl Program {
     pub fn new<'a, F, I>(facade: &F, input: I) -> Result<Program, ProgramCreationError>
                          where I: Into<ProgramCreationInput<'a>>, F: Facade
     {
-        let input = input.into();
-
-        let (raw, outputs_srgb, uses_point_size) = match input {
-            ProgramCreationInput::SourceCode { vertex_shader, tessellation_control_shader,
-                                               tessellation_evaluation_shader, geometry_shader,
-                                               fragment_shader, transform_feedback_varyings,
-                                               outputs_srgb, uses_point_size } =>
-            {
-                let mut has_geometry_shader = false;
-                let mut has_tessellation_shaders = false;
-
-                let mut shaders = vec![
-                    (vertex_shader, gl::VERTEX_SHADER),
-                    (fragment_shader, gl::FRAGMENT_SHADER)
-                ];
-
-                if let Some(gs) = geometry_shader {
-                    shaders.push((gs, gl::GEOMETRY_SHADER));
-                    has_geometry_shader = true;
-                }
+        let link = try!(ProgramLink::new(facade, input.into()));
 
-                if let Some(ts) = tessellation_control_shader {
-                    shaders.push((ts, gl::TESS_CONTROL_SHADER));
-                    has_tessellation_shaders = true;
-                }
-
-                if let Some(ts) = tessellation_evaluation_shader {
-                    shaders.push((ts, gl::TESS_EVALUATION_SHADER));
-                    has_tessellation_shaders = true;
-                }
-
-                // TODO: move somewhere else
-                if transform_feedback_varyings.is_some() &&
-                    (facade.get_context().get_version() >= &Version(Api::Gl, 3, 0) ||
-                        !facade.get_context().get_extensions().gl_ext_transform_feedback)
-                {
-                    return Err(ProgramCreationError::TransformFeedbackNotSupported);
-                }
-
-                if uses_point_size && !(facade.get_context().get_version() >= &Version(Api::Gl, 3, 0)) {
-                    return Err(ProgramCreationError::PointSizeNotSupported);
-                }
-
-                let _lock = COMPILER_GLOBAL_LOCK.lock();
+        Ok(Program {
+            links: RefCell::new(vec![Box::new(link)]),
+            generation: Cell::new(0),
+            printed_warnings: Cell::new(0),
+        })
+    }
 
-                let shaders_store = {
-                    let mut shaders_store = Vec::new();
-                    for (src, ty) in shaders.into_iter() {
-                        shaders_store.push(try!(build_shader(facade, ty, src)));
-                    }
-                    shaders_store
-                };
+    /// Compiles and links new shaders, and replaces the content of this program with them.
+    ///
+    /// This is meant for reloading shaders while the application is running, without having
+    /// to update all the objects that refer to this program.
+    ///
+    /// If an error happens, the program is left untouched and can still be used. Otherwise,
+    /// the program gets a new OpenGL object and the old one is destroyed. The values of the
+    /// uniforms are not carried over and must be set again, which is what happens anyway if
+    /// you pass them when drawing. The `UniformHandle`s obtained before the reload are
+    /// rejected by the draw commands, and you must call `get_uniform_handle` again.
+    ///
+    /// The informations returned by the getters before the reload (like `get_uniform`) stay
+    /// valid but describe the old shaders. They are freed only when the `Program` is destroyed.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+    /// # let vertex_source = ""; let fragment_source = "";
+    /// match program.reload_from_source(glium::program::SourceCode {
+    ///     vertex_shader: vertex_source,
+    ///     tessellation_control_shader: None,
+    ///     tessellation_evaluation_shader: None,
+    ///     geometry_shader: None,
+    ///     fragment_shader: fragment_source,
+    /// }) {
+    ///     Ok(()) => (),
+    ///     Err(err) => println!("Failed to reload the shaders: {}", err),
+    /// }
+    /// ```
+    pub fn reload_from_source<'a, I>(&self, new_sources: I) -> Result<(), ProgramCreationError>
+                                     where I: Into<ProgramCreationInput<'a>>
+    {
+        let context = self.link().raw.get_context().clone();
+        let link = try!(ProgramLink::new(&context, new_sources.into()));
+
+        self.link().raw.release();
+        self.links.borrow_mut().push(Box::new(link));
+        self.generation.set(self.generation.get() + 1);
+        self.printed_warnings.set(0);
+        Ok(())
+    }
 
-                (try!(RawProgram::from_shaders(facade, &shaders_store, has_geometry_shader,
-                                               has_tessellation_shaders, transform_feedback_varyings)),
-                 outputs_srgb, uses_point_size)
-            },
+    /// Turns the program into a `DetachedProgram`, without destroying the OpenGL program.
+    #[doc(hidden)]
+    pub fn detach(self) -> DetachedProgram {
+        let links = self.links.into_inner().into_iter().map(|link| {
+            let link = *link;
+            DetachedProgramLink {
+                raw: link.raw.detach(),
+                outputs_srgb: link.outputs_srgb,
+                uses_point_size: link.uses_point_size,
+                uses_clip_distance: link.uses_clip_distance,
+                has_early_fragment_tests: link.has_early_fragment_tests,
+                writes_frag_depth: link.writes_frag_depth,
+            }
+        }).collect();
 
-            ProgramCreationInput::Binary { data, outputs_srgb, uses_point_size } => {
-                if uses_point_size && !(facade.get_context().get_version() >= &Version(Api::Gl, 3, 0)) {
-                    return Err(ProgramCreationError::PointSizeNotSupported);
-                }
+        DetachedProgram {
+            links: links,
+            generation: self.generation.get(),
+            printed_warnings: self.printed_warnings.get(),
+        }
+    }
 
-                (try!(RawProgram::from_binary(facade, data)), outputs_srgb, uses_point_size)
-            },
-        };
+    /// Builds back a program from a `DetachedProgram`.
+    #[doc(hidden)]
+    pub fn attach(detached: DetachedProgram, context: &Rc<Context>) -> Program {
+        let links = detached.links.into_iter().map(|link| {
+            Box::new(ProgramLink {
+                raw: RawProgram::attach(link.raw, context),
+                outputs_srgb: link.outputs_srgb,
+                uses_point_size: link.uses_point_size,
+                uses_clip_distance: link.uses_clip_distance,
+                has_early_fragment_tests: link.has_early_fragment_tests,
+                writes_frag_depth: link.writes_frag_depth,
+            })
+        }).collect();
+
+        Program {
+            links: RefCell::new(links),
+            generation: Cell::new(detached.generation),
+            printed_warnings: Cell::new(detached.printed_warnings),
+        }
+    }
 
-        Ok(Program {
-            raw: raw,
-            outputs_srgb: outputs_srgb,
-            uses_point_size: uses_point_size,
-        })
+    /// Returns the current link of the program.
+    #[inline]
+    fn link(&self) -> &ProgramLink {
+        let links = self.links.borrow();
+        let link: *const ProgramLink = &**links.last().unwrap();
+        // the boxes are never modified or removed before the program is destroyed
+        unsafe { &*link }
     }
 
     /// Builds a new program from GLSL source code.
@@ -146,6 +233,7 @@ impl Program {
             transform_feedback_varyings: None,
             outputs_srgb: false,
             uses_point_size: false,
+            dual_source_output: None,
         })
     }
 
@@ -155,7 +243,7 @@ impl Program {
     /// the source code every time.
     #[inline]
     pub fn get_binary(&self) -> Result<Binary, GetBinaryError> {
-        self.raw.get_binary()
+        self.link().raw.get_binary()
     }
 
     /// Returns the *location* of an output fragment, if it exists.
@@ -171,13 +259,25 @@ impl Program {
     ///
     #[inline]
     pub fn get_frag_data_location(&self, name: &str) -> Option<u32> {
-        self.raw.get_frag_data_location(name)
+        self.link().raw.get_frag_data_location(name)
     }
 
     /// Returns informations about a uniform variable, if it exists.
     #[inline]
     pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
-        self.raw.get_uniform(name)
+        self.link().raw.get_uniform(name)
+    }
+
+    /// Returns a handle to a uniform variable, if it exists.
+    ///
+    /// Binding values with handles avoids looking up the names of the uniforms at each draw
+    /// command. See `PrecomputedUniforms`.
+    #[inline]
+    pub fn get_uniform_handle(&self, name: &str) -> Option<UniformHandle> {
+        let raw = &self.link().raw;
+        raw.get_uniform(name).map(|u| {
+            UniformHandle::new(raw.get_id(), self.generation.get(), name, u)
+        })
     }
     
     /// Returns an iterator to the list of uniforms.
@@ -192,7 +292,7 @@ impl Program {
     /// ```
     #[inline]
     pub fn uniforms(&self) -> hash_map::Iter<String, Uniform> {
-        self.raw.uniforms()
+        self.link().raw.uniforms()
     }
     
     /// Returns a list of uniform blocks.
@@ -207,13 +307,13 @@ impl Program {
     /// ```
     #[inline]
     pub fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlock> {
-        self.raw.get_uniform_blocks()
+        self.link().raw.get_uniform_blocks()
     }
 
     /// Returns the list of transform feedback varyings.
     #[inline]
     pub fn get_transform_feedback_buffers(&self) -> &[TransformFeedbackBuffer] {
-        self.raw.get_transform_feedback_buffers()
+        self.link().raw.get_transform_feedback_buffers()
     }
 
     /// True if the transform feedback output of this program matches the specified `VertexFormat`
@@ -222,7 +322,7 @@ impl Program {
     /// The `stride` is the number of bytes between two vertices.
     #[inline]
     pub fn transform_feedback_matches(&self, format: &VertexFormat, stride: usize) -> bool {
-        self.raw.transform_feedback_matches(format, stride)
+        self.link().raw.transform_feedback_matches(format, stride)
     }
 
     /// Returns the type of geometry that transform feedback would generate, or `None` if it
@@ -232,19 +332,22 @@ impl Program {
     /// contain either a geometry shader or a tessellation evaluation shader, returns `None`.
     #[inline]
     pub fn get_output_primitives(&self) -> Option<OutputPrimitives> {
-        self.raw.get_output_primitives()
+        self.link().raw.get_output_primitives()
     }
 
     /// Returns true if the program contains a tessellation stage.
     #[inline]
     pub fn has_tessellation_shaders(&self) -> bool {
-        self.raw.has_tessellation_shaders()
+        self.link().raw.has_tessellation_shaders()
     }
 
     /// Returns informations about an attribute, if it exists.
+    ///
+    /// The attributes are stored under their name without the decorations that some drivers
+    /// report, like `name` for `name[0]`. Both forms can be passed here.
     #[inline]
     pub fn get_attribute(&self, name: &str) -> Option<&Attribute> {
-        self.raw.get_attribute(name)
+        self.link().raw.get_attribute(name)
     }
 
     /// Returns an iterator to the list of attributes.
@@ -259,13 +362,13 @@ impl Program {
     /// ```
     #[inline]
     pub fn attributes(&self) -> hash_map::Iter<String, Attribute> {
-        self.raw.attributes()
+        self.link().raw.attributes()
     }
 
     /// Returns true if the program has been configured to output sRGB instead of RGB.
     #[inline]
     pub fn has_srgb_output(&self) -> bool {
-        self.outputs_srgb
+        self.link().outputs_srgb
     }
     
     /// Returns the list of shader storage blocks.
@@ -280,7 +383,7 @@ impl Program {
     /// ```
     #[inline]
     pub fn get_shader_storage_blocks(&self) -> &HashMap<String, UniformBlock> {
-        self.raw.get_shader_storage_blocks()
+        self.link().raw.get_shader_storage_blocks()
     }
 
     /// Returns true if the program has been configured to use the `gl_PointSize` variable.
@@ -289,14 +392,381 @@ impl Program {
     /// setting the value of `gl_PointSize` will have no effect.
     #[inline]
     pub fn uses_point_size(&self) -> bool {
-      self.uses_point_size
+      self.link().uses_point_size
+    }
+
+    /// Returns true if the program writes to `gl_ClipDistance`.
+    ///
+    /// This is determined by looking at the source code of the shaders, ignoring comments.
+    /// Programs that were created from a binary are always assumed to write to
+    /// `gl_ClipDistance`.
+    #[inline]
+    pub fn uses_clip_distance(&self) -> bool {
+        self.link().uses_clip_distance
+    }
+
+    /// Returns true if the fragment shader declares `layout(early_fragment_tests) in;`.
+    ///
+    /// With early fragment tests, the depth and stencil tests are performed before the fragment
+    /// shader is executed, and the values written to `gl_FragDepth` are ignored.
+    ///
+    /// This is determined by looking at the source code of the fragment shader. Programs that
+    /// were created from a binary are always assumed not to use early fragment tests.
+    #[inline]
+    pub fn has_early_fragment_tests(&self) -> bool {
+        self.link().has_early_fragment_tests
+    }
+
+    /// Returns true if the fragment shader writes to `gl_FragDepth`.
+    ///
+    /// This is determined by looking at the source code of the fragment shader, ignoring
+    /// comments. Programs that were created from a binary are always assumed to write to
+    /// `gl_FragDepth`.
+    #[inline]
+    pub fn writes_frag_depth(&self) -> bool {
+        self.link().writes_frag_depth
+    }
+
+    /// Executes a draw command that uses this program, the given draw parameters and the
+    /// given vertex format, without drawing anything.
+    ///
+    /// Drivers often compile the internal state that corresponds to a combination of program,
+    /// draw parameters and vertex format the first time it is used, which causes hitches.
+    /// Calling this function during a loading screen moves this work out of the gameplay.
+    ///
+    /// The draw command targets the default framebuffer, reads a single vertex filled with
+    /// zeroes and uses an empty scissor box, so that nothing is written. The viewport, the
+    /// queries, the conditional rendering and the transform feedback of the draw parameters
+    /// are ignored, and no uniform is set.
+    ///
+    /// Returns an error if the draw command would fail, for example if the vertex format
+    /// doesn't match the attributes of the program or if the draw parameters require a depth
+    /// buffer and the default framebuffer doesn't have one.
+    pub fn prewarm<F>(&self, facade: &F, draw_parameters: &DrawParameters,
+                      vertex_format: &VertexFormat) -> Result<(), PrewarmError>
+                      where F: Facade
+    {
+        let buffer = try!(build_prewarm_buffer(facade, vertex_format));
+        self.prewarm_with_buffer(facade.get_context(), draw_parameters, &buffer)
+    }
+
+    fn prewarm_with_buffer(&self, context: &Rc<Context>, draw_parameters: &DrawParameters,
+                           buffer: &VertexBufferAny) -> Result<(), PrewarmError>
+    {
+        if context.capabilities().depth_bits.is_none() &&
+           (draw_parameters.depth.test.requires_depth_buffer() || draw_parameters.depth.write)
+        {
+            return Err(PrewarmError::DrawError(DrawError::NoDepthBuffer));
+        }
+
+        let draw_parameters = DrawParameters {
+            viewport: None,
+            scissor: Some(Rect { left: 0, bottom: 0, width: 0, height: 0 }),
+            samples_passed_query: None,
+            time_elapsed_query: None,
+            primitives_generated_query: None,
+            transform_feedback_primitives_written_query: None,
+            condition: None,
+            transform_feedback: None,
+            .. draw_parameters.clone()
+        };
+
+        let primitives = if self.has_tessellation_shaders() {
+            PrimitiveType::Patches { vertices_per_patch: 1 }
+        } else {
+            PrimitiveType::Points
+        };
+
+        let dimensions = context.get_framebuffer_dimensions();
+        try!(ops::draw(context, None, buffer, NoIndices(primitives).into(), self,
+                       &EmptyUniforms, &draw_parameters, dimensions, false));
+        Ok(())
+    }
+}
+
+impl ProgramLink {
+    /// Compiles and links the shaders.
+    fn new<'a, F>(facade: &F, input: ProgramCreationInput<'a>)
+                  -> Result<ProgramLink, ProgramCreationError> where F: Facade
+    {
+        let (raw, outputs_srgb, uses_point_size, uses_clip_distance, has_early_fragment_tests,
+             writes_frag_depth) = match input
+        {
+            ProgramCreationInput::SourceCode { vertex_shader, tessellation_control_shader,
+                                               tessellation_evaluation_shader, geometry_shader,
+                                               fragment_shader, transform_feedback_varyings,
+                                               outputs_srgb, uses_point_size,
+                                               dual_source_output } =>
+            {
+                let mut has_geometry_shader = false;
+                let mut has_tessellation_shaders = false;
+
+                // OpenGL only reports the built-ins used by the last stage of a program, so the
+                // source code of the shaders has to be looked at ; the fragment shader can only
+                // read `gl_ClipDistance`, so it is ignored
+                let uses_clip_distance = [Some(vertex_shader), geometry_shader,
+                                          tessellation_control_shader,
+                                          tessellation_evaluation_shader]
+                                            .iter().filter_map(|s| *s)
+                                            .any(|s| uses_identifier(s, "gl_ClipDistance"));
+
+                let has_early_fragment_tests = has_early_fragment_tests(fragment_shader);
+                let writes_frag_depth = uses_identifier(fragment_shader, "gl_FragDepth");
+
+                let mut shaders = vec![
+                    (vertex_shader, gl::VERTEX_SHADER),
+                    (fragment_shader, gl::FRAGMENT_SHADER)
+                ];
+
+                if let Some(gs) = geometry_shader {
+                    shaders.push((gs, gl::GEOMETRY_SHADER));
+                    has_geometry_shader = true;
+                }
+
+                if let Some(ts) = tessellation_control_shader {
+                    shaders.push((ts, gl::TESS_CONTROL_SHADER));
+                    has_tessellation_shaders = true;
+                }
+
+                if let Some(ts) = tessellation_evaluation_shader {
+                    shaders.push((ts, gl::TESS_EVALUATION_SHADER));
+                    has_tessellation_shaders = true;
+                }
+
+                // TODO: move somewhere else
+                if transform_feedback_varyings.is_some() &&
+                    (facade.get_context().get_version() >= &Version(Api::Gl, 3, 0) ||
+                        !facade.get_context().get_extensions().gl_ext_transform_feedback)
+                {
+                    return Err(ProgramCreationError::TransformFeedbackNotSupported);
+                }
+
+                if uses_point_size && !(facade.get_context().get_version() >= &Version(Api::Gl, 3, 0)) {
+                    return Err(ProgramCreationError::PointSizeNotSupported);
+                }
+
+                if dual_source_output.is_some() &&
+                   facade.get_context().get_capabilities().max_dual_source_draw_buffers == 0
+                {
+                    return Err(ProgramCreationError::DualSourceBlendingNotSupported);
+                }
+
+                let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+                let shaders_store = {
+                    let mut shaders_store = Vec::new();
+                    for (src, ty) in shaders.into_iter() {
+                        shaders_store.push(try!(build_shader(facade, ty, src)));
+                    }
+                    shaders_store
+                };
+
+                (try!(RawProgram::from_shaders(facade, &shaders_store, has_geometry_shader,
+                                               has_tessellation_shaders, transform_feedback_varyings,
+                                               dual_source_output)),
+                 outputs_srgb, uses_point_size, uses_clip_distance, has_early_fragment_tests,
+                 writes_frag_depth)
+            },
+
+            ProgramCreationInput::Binary { data, outputs_srgb, uses_point_size } => {
+                if uses_point_size && !(facade.get_context().get_version() >= &Version(Api::Gl, 3, 0)) {
+                    return Err(ProgramCreationError::PointSizeNotSupported);
+                }
+
+                // we can't know whether a binary uses `gl_ClipDistance` or `gl_FragDepth`, so
+                // we assume it does, and that it doesn't use early fragment tests
+                (try!(RawProgram::from_binary(facade, data)), outputs_srgb, uses_point_size, true,
+                 false, true)
+            },
+        };
+
+        Ok(ProgramLink {
+            raw: raw,
+            outputs_srgb: outputs_srgb,
+            uses_point_size: uses_point_size,
+            uses_clip_distance: uses_clip_distance,
+            has_early_fragment_tests: has_early_fragment_tests,
+            writes_frag_depth: writes_frag_depth,
+        })
+    }
+}
+
+/// Returns true if the source code of a fragment shader contains a `layout` qualifier with
+/// `early_fragment_tests`.
+fn has_early_fragment_tests(source: &str) -> bool {
+    let mut remaining = source;
+
+    while let Some(pos) = remaining.find("layout") {
+        remaining = &remaining[pos + 6 ..];
+
+        let qualifiers = remaining.trim_left();
+        if !qualifiers.starts_with('(') {
+            continue;
+        }
+
+        if let Some(end) = qualifiers.find(')') {
+            if qualifiers[1 .. end].split(',').any(|q| q.trim() == "early_fragment_tests") {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Returns true if the source code of a shader contains the given identifier outside of
+/// comments.
+fn uses_identifier(source: &str, identifier: &str) -> bool {
+    let bytes = source.as_bytes();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        if bytes[pos ..].starts_with(b"//") {
+            pos = source[pos ..].find('\n').map(|p| pos + p).unwrap_or(bytes.len());
+
+        } else if bytes[pos ..].starts_with(b"/*") {
+            pos = source[pos + 2 ..].find("*/").map(|p| pos + p + 4).unwrap_or(bytes.len());
+
+        } else if is_identifier_byte(bytes[pos]) {
+            let start = pos;
+            while pos < bytes.len() && is_identifier_byte(bytes[pos]) {
+                pos += 1;
+            }
+
+            if &bytes[start .. pos] == identifier.as_bytes() {
+                return true;
+            }
+
+        } else {
+            pos += 1;
+        }
+    }
+
+    false
+}
+
+/// Returns true if the byte can be part of a GLSL identifier or number.
+#[inline]
+fn is_identifier_byte(b: u8) -> bool {
+    b == b'_' || (b >= b'a' && b <= b'z') || (b >= b'A' && b <= b'Z') || (b >= b'0' && b <= b'9')
+}
+
+/// Builds a vertex buffer that contains one vertex filled with zeroes.
+fn build_prewarm_buffer<F>(facade: &F, vertex_format: &VertexFormat)
+                           -> Result<VertexBufferAny, PrewarmError> where F: Facade
+{
+    let stride = vertex_format.iter()
+                              .map(|&(_, offset, ty, _, _)| offset + ty.get_size_bytes())
+                              .max().unwrap_or(1);
+    let stride = if stride == 0 { 1 } else { stride };
+
+    let data = vec![0u8; stride];
+    let buffer = unsafe { VertexBufferAny::new_raw(facade, &data, vertex_format.clone(), stride) };
+    buffer.map_err(PrewarmError::VertexBufferCreation)
+}
+
+/// Error that can happen when prewarming a program.
+#[derive(Clone, Debug)]
+pub enum PrewarmError {
+    /// Error while creating the vertex buffer that contains the vertex.
+    VertexBufferCreation(VertexBufferCreationError),
+
+    /// The draw command failed.
+    DrawError(DrawError),
+}
+
+impl fmt::Display for PrewarmError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &PrewarmError::VertexBufferCreation(ref err) => {
+                write!(fmt, "Error while creating the vertex buffer: {}", err)
+            },
+            &PrewarmError::DrawError(ref err) => write!(fmt, "{}", err),
+        }
+    }
+}
+
+impl Error for PrewarmError {
+    fn description(&self) -> &str {
+        match self {
+            &PrewarmError::VertexBufferCreation(_) => "Error while creating the vertex buffer",
+            &PrewarmError::DrawError(ref err) => err.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &PrewarmError::VertexBufferCreation(ref err) => Some(err),
+            &PrewarmError::DrawError(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<DrawError> for PrewarmError {
+    #[inline]
+    fn from(err: DrawError) -> PrewarmError {
+        PrewarmError::DrawError(err)
+    }
+}
+
+/// Iterator that prewarms a list of programs, one at each call to `next`.
+///
+/// Returned by `Context::prewarm_many`. Consecutive elements that use the same vertex format
+/// share the same vertex buffer.
+pub struct PrewarmIter<'a, I> {
+    context: Rc<Context>,
+    list: I,
+    buffer: Option<(&'a VertexFormat, VertexBufferAny)>,
+}
+
+impl<'a, I> PrewarmIter<'a, I> {
+    #[inline]
+    pub fn new(context: Rc<Context>, list: I) -> PrewarmIter<'a, I> {
+        PrewarmIter {
+            context: context,
+            list: list,
+            buffer: None,
+        }
+    }
+}
+
+impl<'a, 'b: 'a, I> Iterator for PrewarmIter<'a, I>
+    where I: Iterator<Item = (&'a Program, &'a DrawParameters<'b>, &'a VertexFormat)>
+{
+    type Item = Result<(), PrewarmError>;
+
+    fn next(&mut self) -> Option<Result<(), PrewarmError>> {
+        let (program, draw_parameters, vertex_format) = match self.list.next() {
+            Some(e) => e,
+            None => return None,
+        };
+
+        let reuse = match self.buffer {
+            Some((format, _)) => format == vertex_format,
+            None => false,
+        };
+
+        if !reuse {
+            match build_prewarm_buffer(&self.context, vertex_format) {
+                Ok(buffer) => self.buffer = Some((vertex_format, buffer)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        let buffer = &self.buffer.as_ref().unwrap().1;
+        Some(program.prewarm_with_buffer(&self.context, draw_parameters, buffer))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.list.size_hint()
     }
 }
 
 impl fmt::Debug for Program {
     #[inline]
     fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(formatter, "{:?}", self.raw)
+        write!(formatter, "{:?}", self.link().raw)
     }
 }
 
@@ -305,26 +775,47 @@ impl GlObject for Program {
 
     #[inline]
     fn get_id(&self) -> Handle {
-        self.raw.get_id()
+        self.link().raw.get_id()
     }
 }
 
 impl ProgramExt for Program {
+    #[inline]
+    fn get_context(&self) -> &Rc<Context> {
+        self.link().raw.get_context()
+    }
+
+    #[inline]
+    fn get_generation(&self) -> u32 {
+        self.generation.get()
+    }
+
+    #[inline]
+    fn should_print_warning(&self, warning: ProgramWarning) -> bool {
+        let printed = self.printed_warnings.get();
+        if printed & warning as u8 != 0 {
+            return false;
+        }
+
+        self.printed_warnings.set(printed | warning as u8);
+        true
+    }
+
     fn use_program(&self, ctxt: &mut CommandContext) {
         // compatibility was checked at program creation
-        if self.uses_point_size && !ctxt.state.enabled_program_point_size {
+        if self.link().uses_point_size && !ctxt.state.enabled_program_point_size {
             unsafe { ctxt.gl.Enable(gl::PROGRAM_POINT_SIZE); }
-        } else if !self.uses_point_size && ctxt.state.enabled_program_point_size {
+        } else if !self.link().uses_point_size && ctxt.state.enabled_program_point_size {
             unsafe { ctxt.gl.Disable(gl::PROGRAM_POINT_SIZE); }
         }
 
         if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_framebuffer_srgb ||
            ctxt.extensions.gl_ext_framebuffer_srgb || ctxt.extensions.gl_ext_srgb_write_control
         {
-            if ctxt.state.enabled_framebuffer_srgb == self.outputs_srgb {
-                ctxt.state.enabled_framebuffer_srgb = !self.outputs_srgb;
+            if ctxt.state.enabled_framebuffer_srgb == self.link().outputs_srgb {
+                ctxt.state.enabled_framebuffer_srgb = !self.link().outputs_srgb;
 
-                if self.outputs_srgb {
+                if self.link().outputs_srgb {
                     unsafe { ctxt.gl.Disable(gl::FRAMEBUFFER_SRGB) };
                 } else {
                     unsafe { ctxt.gl.Enable(gl::FRAMEBUFFER_SRGB) };
@@ -332,21 +823,28 @@ impl ProgramExt for Program {
             }
         }
 
-        self.raw.use_program(ctxt)
+        self.link().raw.use_program(ctxt)
     }
 
     #[inline]
     fn set_uniform(&self, ctxt: &mut CommandContext, uniform_location: gl::types::GLint,
                    value: &RawUniformValue)
     {
-        self.raw.set_uniform(ctxt, uniform_location, value)
+        self.link().raw.set_uniform(ctxt, uniform_location, value)
+    }
+
+    #[inline]
+    fn set_uniform_matrix_array(&self, ctxt: &mut CommandContext, uniform_location: gl::types::GLint,
+                                dimension: u8, data: &[f32])
+    {
+        self.link().raw.set_uniform_matrix_array(ctxt, uniform_location, dimension, data)
     }
 
     #[inline]
     fn set_uniform_block_binding(&self, ctxt: &mut CommandContext, block_location: gl::types::GLuint,
                                  value: gl::types::GLuint)
     {
-        self.raw.set_uniform_block_binding(ctxt, block_location, value)
+        self.link().raw.set_uniform_block_binding(ctxt, block_location, value)
     }
 
     #[inline]
@@ -354,21 +852,21 @@ impl ProgramExt for Program {
                                         block_location: gl::types::GLuint,
                                         value: gl::types::GLuint)
     {
-        self.raw.set_shader_storage_block_binding(ctxt, block_location, value)
+        self.link().raw.set_shader_storage_block_binding(ctxt, block_location, value)
     }
 
     #[inline]
     fn get_uniform(&self, name: &str) -> Option<&Uniform> {
-        self.raw.get_uniform(name)
+        self.link().raw.get_uniform(name)
     }
 
     #[inline]
     fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlock> {
-        self.raw.get_uniform_blocks()
+        self.link().raw.get_uniform_blocks()
     }
 
     #[inline]
     fn get_shader_storage_blocks(&self) -> &HashMap<String, UniformBlock> {
-        self.raw.get_shader_storage_blocks()
+        self.link().raw.get_shader_storage_blocks()
     }
 }
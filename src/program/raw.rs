@@ -2,6 +2,7 @@ use gl;
 use libc;
 
 use context::CommandContext;
+use context::ObjectKind;
 use version::Version;
 use version::Api;
 
@@ -10,11 +11,11 @@ use context::Context;
 use ContextExt;
 use UniformsExt;
 
-use std::{ffi, fmt, mem};
+use std::{ffi, fmt, mem, ptr};
 use std::error::Error;
 use std::collections::hash_map::{self, HashMap};
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use DrawError;
 use GlObject;
@@ -38,9 +39,11 @@ use program::reflection::{Attribute, TransformFeedbackMode, TransformFeedbackBuf
 use program::reflection::{reflect_uniforms, reflect_attributes, reflect_uniform_blocks};
 use program::reflection::{reflect_transform_feedback, reflect_geometry_output_type};
 use program::reflection::{reflect_tess_eval_output_type, reflect_shader_storage_blocks};
+use program::reflection::normalize_variable_name;
 use program::shader::Shader;
 
 use uniforms::Uniforms;
+use uniforms::UniformHandle;
 
 use vertex::VertexFormat;
 use vertex_array_object::VertexAttributesSystem;
@@ -58,6 +61,7 @@ pub struct RawProgram {
     ssbos: HashMap<String, UniformBlock>,
     output_primitives: Option<OutputPrimitives>,
     has_tessellation_shaders: bool,
+    released: Cell<bool>,
 }
 
 impl RawProgram {
@@ -65,7 +69,8 @@ impl RawProgram {
     // TODO: the "has_*" parameters are bad
     pub fn from_shaders<'a, F, I>(facade: &'a F, shaders: I, has_geometry_shader: bool,
                                   has_tessellation_shaders: bool,
-                                  transform_feedback: Option<(Vec<String>, TransformFeedbackMode)>)
+                                  transform_feedback: Option<(Vec<String>, TransformFeedbackMode)>,
+                                  dual_source_output: Option<&str>)
                                   -> Result<RawProgram, ProgramCreationError>
                                   where F: Facade, I: IntoIterator<Item = &'a Shader>
     {
@@ -129,6 +134,27 @@ impl RawProgram {
                 }
             }
 
+            // binding the second output of dual-source blending
+            if let Some(name) = dual_source_output {
+                let id = match id {
+                    Handle::Id(id) => id,
+                    Handle::Handle(_) => unreachable!()     // dual-source blending isn't
+                                                            // available with handles
+                };
+
+                let name = ffi::CString::new(name.as_bytes()).unwrap();
+
+                if ctxt.version >= &Version(Api::Gl, 3, 3) ||
+                   ctxt.extensions.gl_arb_blend_func_extended
+                {
+                    ctxt.gl.BindFragDataLocationIndexed(id, 0, 1, name.as_ptr());
+                } else if ctxt.extensions.gl_ext_blend_func_extended {
+                    ctxt.gl.BindFragDataLocationIndexedEXT(id, 0, 1, name.as_ptr());
+                } else {
+                    unreachable!();     // has been checked in the frontend
+                }
+            }
+
             // linking
             {
                 ctxt.report_debug_output_errors.set(false);
@@ -180,6 +206,7 @@ impl RawProgram {
             ssbos: ssbos,
             output_primitives: output_primitives,
             has_tessellation_shaders: has_tessellation_shaders,
+            released: Cell::new(false),
         })
     }
 
@@ -230,6 +257,7 @@ impl RawProgram {
             ssbos: ssbos,
             output_primitives: None,            // FIXME: 
             has_tessellation_shaders: true,     // FIXME: 
+            released: Cell::new(false),
         })
     }
 
@@ -319,6 +347,14 @@ impl RawProgram {
     pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
         self.uniforms.get(name)
     }
+
+    /// Returns a handle to a uniform variable, if it exists.
+    ///
+    /// See `PrecomputedUniforms`.
+    #[inline]
+    pub fn get_uniform_handle(&self, name: &str) -> Option<UniformHandle> {
+        self.uniforms.get(name).map(|u| UniformHandle::new(self.id, 0, name, u))
+    }
     
     /// Returns an iterator to the list of uniforms.
     ///
@@ -374,7 +410,11 @@ impl RawProgram {
         }
 
         for elem in buf.elements.iter() {
-            if format.iter().find(|e| &e.0 == &*elem.name && e.1 == elem.offset && e.2 == elem.ty)
+            // the name reported by the driver can be decorated, see `normalize_variable_name`
+            let name = normalize_variable_name(&elem.name);
+
+            if format.iter().find(|e| (&e.0 == &*elem.name || &e.0 == name) &&
+                                      e.1 == elem.offset && e.2 == elem.ty)
                             .is_none()
             {
                 return false;
@@ -401,9 +441,12 @@ impl RawProgram {
     }
 
     /// Returns informations about an attribute, if it exists.
+    ///
+    /// The attributes are stored under their name without the decorations that some drivers
+    /// report, like `name` for `name[0]`. Both forms can be passed here.
     #[inline]
     pub fn get_attribute(&self, name: &str) -> Option<&Attribute> {
-        self.attributes.get(name)
+        self.attributes.get(name).or_else(|| self.attributes.get(normalize_variable_name(name)))
     }
 
     /// Returns an iterator to the list of attributes.
@@ -533,6 +576,11 @@ impl GlObject for RawProgram {
 }
 
 impl ProgramExt for RawProgram {
+    #[inline]
+    fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+
     #[inline]
     fn use_program(&self, ctxt: &mut CommandContext) {
         unsafe {
@@ -543,6 +591,7 @@ impl ProgramExt for RawProgram {
                     Handle::Handle(id) => ctxt.gl.UseProgramObjectARB(id),
                 }
                 ctxt.state.program = program_id;
+                ctxt.frame_statistics.update(|s| s.program_changes += 1);
             }
         }
     }
@@ -554,6 +603,14 @@ impl ProgramExt for RawProgram {
         self.uniform_values.set_uniform_value(ctxt, self.id, uniform_location, value);
     }
 
+    #[inline]
+    fn set_uniform_matrix_array(&self, ctxt: &mut CommandContext, uniform_location: gl::types::GLint,
+                                dimension: u8, data: &[f32])
+    {
+        self.uniform_values.set_uniform_matrix_array(ctxt, self.id, uniform_location, dimension,
+                                                     data);
+    }
+
     #[inline]
     fn set_uniform_block_binding(&self, ctxt: &mut CommandContext, block_location: gl::types::GLuint,
                                  value: gl::types::GLuint)
@@ -585,40 +642,161 @@ impl ProgramExt for RawProgram {
     }
 }
 
+impl RawProgram {
+    /// Turns the program into a `DetachedRawProgram`, without destroying the OpenGL program.
+    pub fn detach(self) -> DetachedRawProgram {
+        let detached = unsafe {
+            DetachedRawProgram {
+                id: self.id,
+                uniform_values: ptr::read(&self.uniform_values),
+                uniforms: ptr::read(&self.uniforms),
+                uniform_blocks: ptr::read(&self.uniform_blocks),
+                attributes: ptr::read(&self.attributes),
+                frag_data_locations: ptr::read(&self.frag_data_locations).into_inner(),
+                tf_buffers: ptr::read(&self.tf_buffers),
+                ssbos: ptr::read(&self.ssbos),
+                output_primitives: self.output_primitives,
+                has_tessellation_shaders: self.has_tessellation_shaders,
+                released: self.released.get(),
+            }
+        };
+
+        // releasing the context without running the destructor
+        unsafe { drop(ptr::read(&self.context)) };
+        mem::forget(self);
+
+        detached
+    }
+
+    /// Builds back a program from a `DetachedRawProgram`.
+    pub fn attach(detached: DetachedRawProgram, context: &Rc<Context>) -> RawProgram {
+        RawProgram {
+            context: context.clone(),
+            id: detached.id,
+            uniform_values: detached.uniform_values,
+            uniforms: detached.uniforms,
+            uniform_blocks: detached.uniform_blocks,
+            attributes: detached.attributes,
+            frag_data_locations: RefCell::new(detached.frag_data_locations),
+            tf_buffers: detached.tf_buffers,
+            ssbos: detached.ssbos,
+            output_primitives: detached.output_primitives,
+            has_tessellation_shaders: detached.has_tessellation_shaders,
+            released: Cell::new(detached.released),
+        }
+    }
+
+    /// Destroys the OpenGL program object while keeping the reflection data of the program.
+    ///
+    /// The program must not be used anymore afterwards. Used when a `Program` is reloaded.
+    pub fn release(&self) {
+        if self.released.get() {
+            return;
+        }
+
+        self.released.set(true);
+        self.destroy_gl_object();
+    }
+
+    #[inline]
+    fn destroy_gl_object(&self) {
+        destroy_program(&self.context, self.id);
+    }
+}
+
 impl Drop for RawProgram {
+    #[inline]
     fn drop(&mut self) {
-        let mut ctxt = self.context.make_current();
+        if !self.released.get() {
+            self.destroy_gl_object();
+        }
+    }
+}
 
-        // removing VAOs which contain this program
-        VertexAttributesSystem::purge_program(&mut ctxt, self.id);
+/// Content of a `RawProgram` without the reference to its context. Doesn't destroy the OpenGL
+/// program when dropped.
+pub struct DetachedRawProgram {
+    id: Handle,
+    uniform_values: UniformsStorage,
+    uniforms: HashMap<String, Uniform>,
+    uniform_blocks: HashMap<String, UniformBlock>,
+    attributes: HashMap<String, Attribute>,
+    frag_data_locations: HashMap<String, Option<u32>>,
+    tf_buffers: Vec<TransformFeedbackBuffer>,
+    ssbos: HashMap<String, UniformBlock>,
+    output_primitives: Option<OutputPrimitives>,
+    has_tessellation_shaders: bool,
+    released: bool,
+}
 
-        // sending the destroy command
-        unsafe {
-            match self.id {
-                Handle::Id(id) => {
-                    assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
-                            ctxt.version >= &Version(Api::GlEs, 2, 0));
+impl DetachedRawProgram {
+    /// Destroys the OpenGL program. Must be called in the thread of the context.
+    #[inline]
+    pub fn destroy(self, context: &Context) {
+        if !self.released {
+            destroy_program(context, self.id);
+        }
+    }
+}
 
-                    if ctxt.state.program == Handle::Id(id) {
-                        ctxt.gl.UseProgram(0);
-                        ctxt.state.program = Handle::Id(0);
-                    }
+/// Destroys a program and removes it from the caches of all the contexts that share it.
+fn destroy_program(context: &Context, id: Handle) {
+    let mut ctxt = match context.make_current_for_drop() {
+        Some(ctxt) => ctxt,
+        None => return,
+    };
 
-                    ctxt.gl.DeleteProgram(id);
-                },
-                Handle::Handle(id) => {
-                    assert!(ctxt.extensions.gl_arb_shader_objects);
+    // removing VAOs which contain this program
+    VertexAttributesSystem::purge_program(&mut ctxt, id);
 
-                    if ctxt.state.program == Handle::Handle(id) {
-                        ctxt.gl.UseProgramObjectARB(0 as gl::types::GLhandleARB);
-                        ctxt.state.program = Handle::Handle(0 as gl::types::GLhandleARB);
-                    }
+    // sending the destroy command
+    unsafe {
+        match id {
+            Handle::Id(id) => {
+                assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
+                        ctxt.version >= &Version(Api::GlEs, 2, 0));
 
-                    ctxt.gl.DeleteObjectARB(id);
+                if ctxt.state.program == Handle::Id(id) {
+                    ctxt.gl.UseProgram(0);
+                    ctxt.state.program = Handle::Id(0);
                 }
+
+                ctxt.gl.DeleteProgram(id);
+            },
+            Handle::Handle(id) => {
+                assert!(ctxt.extensions.gl_arb_shader_objects);
+
+                if ctxt.state.program == Handle::Handle(id) {
+                    ctxt.gl.UseProgramObjectARB(0 as gl::types::GLhandleARB);
+                    ctxt.state.program = Handle::Handle(0 as gl::types::GLhandleARB);
+                }
+
+                ctxt.gl.DeleteObjectARB(id);
             }
         }
     }
+
+    ctxt.live_objects.remove(ObjectKind::Program, handle_to_usize(id));
+    drop(ctxt);
+
+    // cleaning the caches of the contexts that share this program
+    context.for_each_shared_context(|ctxt| {
+        VertexAttributesSystem::purge_program(ctxt, id);
+
+        if ctxt.state.program == id {
+            unsafe {
+                match id {
+                    Handle::Id(_) => ctxt.gl.UseProgram(0),
+                    Handle::Handle(_) => ctxt.gl.UseProgramObjectARB(0 as gl::types::GLhandleARB),
+                }
+            }
+
+            ctxt.state.program = match id {
+                Handle::Id(_) => Handle::Id(0),
+                Handle::Handle(_) => Handle::Handle(0 as gl::types::GLhandleARB),
+            };
+        }
+    });
 }
 
 /// Builds an empty program from within the GL context.
@@ -637,9 +815,19 @@ unsafe fn create_program(ctxt: &mut CommandContext) -> Handle {
         panic!("glCreateProgram failed");
     }
 
+    ctxt.live_objects.add(ObjectKind::Program, handle_to_usize(id));
     id
 }
 
+/// Turns a program handle into an identifier for the registry of live objects.
+#[inline]
+fn handle_to_usize(handle: Handle) -> usize {
+    match handle {
+        Handle::Id(id) => id as usize,
+        Handle::Handle(id) => id as usize,
+    }
+}
+
 unsafe fn check_program_link_errors(ctxt: &mut CommandContext, id: Handle)
                                     -> Result<(), ProgramCreationError>
 {
@@ -224,7 +224,14 @@ pub unsafe fn reflect_uniforms(ctxt: &mut CommandContext, program: Handle)
 
         uniform_name_tmp.set_len(uniform_name_tmp_len as usize);
 
-        let uniform_name = String::from_utf8(uniform_name_tmp).unwrap();
+        let mut uniform_name = String::from_utf8(uniform_name_tmp).unwrap();
+
+        // arrays are reported as `name[0]`, but they are accessed with `name`
+        if uniform_name.ends_with("[0]") {
+            let len = uniform_name.len();
+            uniform_name.truncate(len - 3);
+        }
+
         let location = match program {
             Handle::Id(program) => {
                 assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
@@ -323,7 +330,7 @@ pub unsafe fn reflect_attributes(ctxt: &mut CommandContext, program: Handle)
             }
         };
 
-        attributes.insert(attr_name, Attribute {
+        attributes.insert(normalize_variable_name(&attr_name).to_owned(), Attribute {
             location: location,
             ty: glenum_to_attribute_type(data_type),
             size: data_size as usize,
@@ -584,6 +591,23 @@ pub unsafe fn reflect_transform_feedback(ctxt: &mut CommandContext, program: Han
     result
 }
 
+/// Removes the decorations that drivers may add to the name of an attribute or of a varying.
+///
+/// Depending on the driver, arrays can be reported as `name[0]` and the members of interface
+/// blocks as `Block.member`, while vertex formats only contain `name` or `member`.
+pub fn normalize_variable_name(name: &str) -> &str {
+    let name = if name.ends_with("[0]") {
+        &name[.. name.len() - 3]
+    } else {
+        name
+    };
+
+    match name.rfind('.') {
+        Some(pos) => &name[pos + 1 ..],
+        None => name,
+    }
+}
+
 /// Obtains the type of data that the geometry shader stage outputs.
 ///
 /// # Unsafety
@@ -1017,3 +1041,27 @@ fn glenum_to_transform_feedback_mode(value: gl::types::GLenum) -> TransformFeedb
         v => panic!("Unknown value returned by OpenGL varying mode: {}", v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_variable_name;
+
+    #[test]
+    fn plain_names() {
+        assert_eq!(normalize_variable_name("position"), "position");
+        assert_eq!(normalize_variable_name("gl_Position"), "gl_Position");
+    }
+
+    #[test]
+    fn array_suffix() {
+        assert_eq!(normalize_variable_name("weights[0]"), "weights");
+        assert_eq!(normalize_variable_name("weights[1]"), "weights[1]");
+    }
+
+    #[test]
+    fn block_members() {
+        assert_eq!(normalize_variable_name("Output.position"), "position");
+        assert_eq!(normalize_variable_name("Output.weights[0]"), "weights");
+        assert_eq!(normalize_variable_name("Output[0].position"), "position");
+    }
+}
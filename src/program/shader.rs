@@ -32,7 +32,10 @@ impl GlObject for Shader {
 
 impl Drop for Shader {
     fn drop(&mut self) {
-        let ctxt = self.context.make_current();
+        let ctxt = match self.context.make_current_for_drop() {
+            Some(ctxt) => ctxt,
+            None => return,
+        };
 
         unsafe {
             match self.id {
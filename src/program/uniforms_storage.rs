@@ -310,6 +310,45 @@ impl UniformsStorage {
         }
     }
 
+    /// Calls `glUniformMatrix3fv` or `glUniformMatrix4fv` with the whole array.
+    ///
+    /// Arrays are not compared with the stored values, and the value stored for `location` is
+    /// invalidated.
+    pub fn set_uniform_matrix_array(&self, ctxt: &mut CommandContext, program: Handle,
+                                    location: gl::types::GLint, dimension: u8, data: &[f32])
+    {
+        // TODO: don't assume that, instead use DSA if the program is not current
+        assert!(ctxt.state.program == program);
+
+        self.values.borrow_mut().insert(location, None);
+
+        let elements = dimension as usize * dimension as usize;
+        assert!(data.len() % elements == 0);
+        let count = (data.len() / elements) as gl::types::GLsizei;
+
+        if count == 0 {
+            return;
+        }
+
+        unsafe {
+            let arb = !(ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                        ctxt.version >= &Version(Api::GlEs, 2, 0));
+            if arb {
+                assert!(ctxt.extensions.gl_arb_shader_objects);
+            }
+
+            match (dimension, arb) {
+                (3, false) => ctxt.gl.UniformMatrix3fv(location, count, gl::FALSE, data.as_ptr()),
+                (3, true) => ctxt.gl.UniformMatrix3fvARB(location, count, gl::FALSE,
+                                                         data.as_ptr()),
+                (4, false) => ctxt.gl.UniformMatrix4fv(location, count, gl::FALSE, data.as_ptr()),
+                (4, true) => ctxt.gl.UniformMatrix4fvARB(location, count, gl::FALSE,
+                                                         data.as_ptr()),
+                _ => unreachable!()
+            }
+        }
+    }
+
     /// Compares `value` with the value stored in this object. If the values differ, updates
     /// the storage and calls `glUniformBlockBinding`.
     pub fn set_uniform_block_binding(&self, ctxt: &mut CommandContext, program: Handle,
@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+use std::mem;
+
 use DrawError;
 
 use uniforms::SamplerBehavior;
 
 use gl;
+use context;
 use context::CommandContext;
 use version::Version;
 use version::Api;
@@ -52,17 +56,26 @@ impl SamplerObject {
             }
         }
 
+        context::label_internal_object(ctxt, gl::SAMPLER, sampler,
+                                       format_args!("sampler object for {:?}", behavior));
+
         SamplerObject {
             id: sampler,
             destroyed: false,
         }
     }
 
-    /// 
+    /// Destroys the sampler object. Deleting a sampler unbinds it from all the texture units.
     #[inline]
     pub fn destroy(mut self, ctxt: &mut CommandContext) {
         self.destroyed = true;
 
+        for unit in ctxt.state.texture_units.iter_mut() {
+            if unit.sampler == self.id {
+                unit.sampler = 0;
+            }
+        }
+
         unsafe {
             ctxt.gl.DeleteSamplers(1, [self.id].as_ptr());
         }
@@ -107,3 +120,16 @@ pub fn get_sampler(ctxt: &mut CommandContext, behavior: &SamplerBehavior)
     ctxt.samplers.insert(behavior.clone(), sampler);
     Ok(id)
 }
+
+/// Destroys all the sampler objects of the context by increasing identifier, so that the
+/// order doesn't depend on the iteration order of the hash map.
+pub fn purge_all(ctxt: &mut CommandContext) {
+    let samplers = mem::replace(&mut *ctxt.samplers, HashMap::with_capacity(0));
+
+    let mut samplers: Vec<_> = samplers.into_iter().map(|(_, s)| s).collect();
+    samplers.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for sampler in samplers {
+        sampler.destroy(ctxt);
+    }
+}
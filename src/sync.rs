@@ -1,19 +1,38 @@
 use context::CommandContext;
+use context::WaitKind;
 use version::Api;
 use version::Version;
 use gl;
 
 use backend::Facade;
 use context::Context;
+use buffer::DetachedAlloc;
+use texture::DetachedTexture;
 use ContextExt;
 use std::rc::Rc;
 
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 /// Error that happens when sync functionnalities are not supported.
 #[derive(Copy, Clone, Debug)]
 pub struct SyncNotSupportedError;
 
+impl fmt::Display for SyncNotSupportedError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for SyncNotSupportedError {
+    fn description(&self) -> &str {
+        "Sync fences are not supported by the backend"
+    }
+}
+
 /// Provides a way to wait for a server-side operation to be finished.
 ///
 /// Creating a `SyncFence` injects an element in the commands queue of the backend.
@@ -46,7 +65,7 @@ impl SyncFence {
         let sync = self.id.take().unwrap();
 
         let mut ctxt = self.context.make_current();
-        let result = unsafe { client_wait(&mut ctxt, sync) };
+        let result = unsafe { client_wait(&mut ctxt, sync, WaitKind::Fence) };
         unsafe { delete_fence(&mut ctxt, sync) };
 
         match result {
@@ -64,8 +83,9 @@ impl Drop for SyncFence {
             Some(s) => s
         };
 
-        let mut ctxt = self.context.make_current();
-        unsafe { delete_fence(&mut ctxt, sync) };
+        if let Some(mut ctxt) = self.context.make_current_for_drop() {
+            unsafe { delete_fence(&mut ctxt, sync) };
+        }
     }
 }
 
@@ -81,6 +101,43 @@ pub struct LinearSyncFence {
 unsafe impl Send for LinearSyncFence {}
 
 impl LinearSyncFence {
+    /// Builds a new `LinearSyncFence` that is injected in the server.
+    ///
+    /// Contrary to a `SyncFence`, this object can be sent to another thread. This makes it
+    /// possible to signal a fence in one context and wait for it in another context that
+    /// shares its objects with the first one. The commands queue is flushed so that the
+    /// other context is guaranteed to see the fence.
+    pub fn new<F>(facade: &F) -> Result<LinearSyncFence, SyncNotSupportedError> where F: Facade {
+        let mut ctxt = facade.get_context().make_current();
+        let fence = try!(unsafe { new_linear_sync_fence(&mut ctxt) });
+        unsafe { ctxt.gl.Flush(); }
+        Ok(fence)
+    }
+
+    /// Makes the server of the given context wait until the fence is signaled, then destroys
+    /// the fence.
+    ///
+    /// This function doesn't block. All the commands that are submitted to `facade` after
+    /// this call will only be executed once the fence has been signaled.
+    pub fn wait_server<F>(mut self, facade: &F) where F: Facade {
+        let fence = self.id.take().unwrap();
+        let mut ctxt = facade.get_context().make_current();
+
+        unsafe {
+            if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+            {
+                ctxt.gl.WaitSync(fence, 0, gl::TIMEOUT_IGNORED);
+            } else if ctxt.extensions.gl_apple_sync {
+                ctxt.gl.WaitSyncAPPLE(fence, 0, gl::TIMEOUT_IGNORED_APPLE);
+            } else {
+                unreachable!();
+            }
+
+            delete_fence(&mut ctxt, fence);
+        }
+    }
+
     /// Turns the prototype into a real fence.
     #[inline]
     pub fn into_sync_fence<F>(mut self, facade: &F) -> SyncFence where F: Facade {
@@ -100,6 +157,164 @@ impl Drop for LinearSyncFence {
     }
 }
 
+/// Wrapper around an object that belongs to a context, and that can be sent to other threads.
+///
+/// Objects like textures or buffers can't be sent to other threads, because they must be
+/// destroyed in the thread where their context lives. Wrapping them inside a `DeferredDrop`
+/// allows you to move them around freely, for example as part of a scene that is processed by
+/// a job system. The wrapped object can only be accessed again with `into_inner`, in the thread
+/// of the context.
+///
+/// The wrapper only contains the OpenGL name of the object and plain data, and doesn't keep the
+/// context alive. If a `DeferredDrop` is dropped, the OpenGL object is not destroyed
+/// immediately. Instead it is put in a queue and destroyed by a context of the same share group
+/// the next time buffers are swapped, or when the context is destroyed. If all the contexts of
+/// the share group have already been destroyed, the OpenGL object has been destroyed with them
+/// and nothing happens.
+///
+/// ## Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+/// # let texture: glium::Texture2d = unsafe { std::mem::uninitialized() };
+/// let texture = glium::DeferredDrop::new(&display, texture);
+///
+/// std::thread::spawn(move || {
+///     // the texture will be destroyed by the context's thread
+///     drop(texture);
+/// });
+/// ```
+pub struct DeferredDrop<T> where T: DeferredDestroy {
+    object: Option<T::Detached>,
+    queue: DeferredDropQueue,
+}
+
+impl<T> DeferredDrop<T> where T: DeferredDestroy {
+    /// Wraps an object that belongs to the context of `facade`.
+    #[inline]
+    pub fn new<F>(facade: &F, object: T) -> DeferredDrop<T> where F: Facade {
+        DeferredDrop {
+            object: Some(object.detach()),
+            queue: facade.get_context().get_deferred_drop_queue().clone(),
+        }
+    }
+
+    /// Extracts the object back.
+    ///
+    /// The facade is used as a proof that this function is called in the thread where the
+    /// context lives.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the context of the facade doesn't share its objects with the context that was
+    /// used to create the `DeferredDrop`.
+    #[inline]
+    pub fn into_inner<F>(mut self, facade: &F) -> T where F: Facade {
+        let context = facade.get_context();
+        assert!(&*self.queue as *const _ ==
+                &**context.get_deferred_drop_queue() as *const _,
+                "The facade doesn't share its objects with the context of the object");
+        T::attach(self.object.take().unwrap(), context)
+    }
+}
+
+impl<T> Drop for DeferredDrop<T> where T: DeferredDestroy {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(object) = self.object.take() {
+            let mut queue = self.queue.lock().unwrap();
+            if queue.alive {
+                queue.objects.push(T::into_deferred_object(object));
+            }
+        }
+    }
+}
+
+/// Objects that can be wrapped inside a `DeferredDrop`.
+///
+/// This trait is implemented for buffers, vertex buffers, index buffers and textures.
+pub trait DeferredDestroy: Sized {
+    /// Content of the object, without any reference to its context.
+    #[doc(hidden)]
+    type Detached: Send;
+
+    /// Turns the object into its content without destroying the OpenGL object. Called in the
+    /// thread of the context.
+    #[doc(hidden)]
+    fn detach(self) -> Self::Detached;
+
+    /// Builds the object back from its content. Called in the thread of the context.
+    #[doc(hidden)]
+    fn attach(detached: Self::Detached, context: &Rc<Context>) -> Self;
+
+    /// Returns the OpenGL object that must be destroyed.
+    #[doc(hidden)]
+    fn into_deferred_object(detached: Self::Detached) -> DeferredObject;
+}
+
+/// An OpenGL object that has been dropped in another thread and that is waiting to be
+/// destroyed.
+#[doc(hidden)]
+pub enum DeferredObject {
+    Buffer(DetachedAlloc),
+    Texture(DetachedTexture),
+}
+
+impl DeferredObject {
+    /// Destroys the object. Must be called in the thread of the context.
+    fn destroy(self, context: &Context) {
+        match self {
+            DeferredObject::Buffer(buffer) => buffer.destroy(context),
+            DeferredObject::Texture(texture) => texture.destroy(context),
+        }
+    }
+}
+
+/// Objects that have been dropped in other threads and that must be destroyed by a context of
+/// the share group.
+pub struct DeferredDrops {
+    objects: Vec<DeferredObject>,
+
+    /// False if all the contexts of the share group have been destroyed. The OpenGL objects have
+    /// been destroyed with them, so the objects dropped afterwards are discarded.
+    alive: bool,
+}
+
+/// Queue of objects that must be destroyed by the contexts of a share group.
+pub type DeferredDropQueue = Arc<Mutex<DeferredDrops>>;
+
+/// Builds a new empty queue.
+#[inline]
+pub fn new_deferred_drop_queue() -> DeferredDropQueue {
+    Arc::new(Mutex::new(DeferredDrops {
+        objects: Vec::new(),
+        alive: true,
+    }))
+}
+
+/// Destroys the objects of the queue with the given context.
+///
+/// Must not be called while holding a `CommandContext`.
+pub fn destroy_deferred_drops(queue: &DeferredDropQueue, context: &Context) {
+    // the lock must be released before the objects are destroyed
+    let objects = {
+        let mut queue = queue.lock().unwrap();
+        mem::replace(&mut queue.objects, Vec::new())
+    };
+
+    for object in objects {
+        object.destroy(context);
+    }
+}
+
+/// Marks the queue as belonging to a share group whose contexts have all been destroyed.
+#[inline]
+pub fn close_deferred_drop_queue(queue: &DeferredDropQueue) {
+    let mut queue = queue.lock().unwrap();
+    queue.alive = false;
+    queue.objects.clear();
+}
+
 pub unsafe fn new_linear_sync_fence(ctxt: &mut CommandContext)
                                     -> Result<LinearSyncFence, SyncNotSupportedError>
 {
@@ -126,10 +341,51 @@ pub unsafe fn wait_linear_sync_fence_and_drop(mut fence: LinearSyncFence,
                                               ctxt: &mut CommandContext)
 {
     let fence = fence.id.take().unwrap();
-    client_wait(ctxt, fence);
+    client_wait(ctxt, fence, WaitKind::Fence);
     delete_fence(ctxt, fence);
 }
 
+/// Same as `wait_linear_sync_fence_and_drop`, but the wait is counted in
+/// `SyncStatistics::frame_pacing`.
+#[inline]
+pub unsafe fn wait_frame_fence_and_drop(mut fence: LinearSyncFence, ctxt: &mut CommandContext) {
+    let fence = fence.id.take().unwrap();
+    client_wait(ctxt, fence, WaitKind::FramePacing);
+    delete_fence(ctxt, fence);
+}
+
+/// Returns true if the backend supports sync fences.
+#[inline]
+pub fn is_supported(ctxt: &CommandContext) -> bool {
+    ctxt.version >= &Version(Api::Gl, 3, 2) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+    ctxt.extensions.gl_arb_sync || ctxt.extensions.gl_apple_sync
+}
+
+/// Returns true if the operations that precede the fence have finished. Never blocks.
+///
+/// The commands queue is flushed so that the fence is guaranteed to be signaled at some point.
+pub fn is_signaled(fence: &SyncFence) -> bool {
+    let sync = fence.id.unwrap();
+    let mut ctxt = fence.context.make_current();
+
+    let result = unsafe {
+        if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+           ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+        {
+            ctxt.gl.ClientWaitSync(sync, gl::SYNC_FLUSH_COMMANDS_BIT, 0)
+        } else if ctxt.extensions.gl_apple_sync {
+            ctxt.gl.ClientWaitSyncAPPLE(sync, gl::SYNC_FLUSH_COMMANDS_BIT_APPLE, 0)
+        } else {
+            unreachable!();
+        }
+    };
+
+    match result {
+        gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => true,
+        _ => false
+    }
+}
+
 /// Destroys a fence, from within the commands context.
 #[inline]
 pub unsafe fn destroy_linear_sync_fence(ctxt: &mut CommandContext, mut fence: LinearSyncFence) {
@@ -139,13 +395,16 @@ pub unsafe fn destroy_linear_sync_fence(ctxt: &mut CommandContext, mut fence: Li
 
 /// Calls `glClientWaitSync` and returns the result.
 ///
-/// Tries without flushing first, then with flushing.
+/// Tries without flushing first, then with flushing. The time spent waiting is added to the
+/// statistics of the given kind.
 ///
 /// # Unsafety
 ///
 /// The fence object must exist.
 ///
-unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync) -> gl::types::GLenum {
+unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync, kind: WaitKind)
+                      -> gl::types::GLenum
+{
     // trying without flushing first
     let result = if ctxt.version >= &Version(Api::Gl, 3, 2) ||
                     ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
@@ -167,8 +426,9 @@ unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync) -> gl
     // waiting with a deadline of one year
     // the reason why the deadline is so long is because if you attach a GL debugger,
     // the wait can be blocked during a breaking point of the debugger
-    if ctxt.version >= &Version(Api::Gl, 3, 2) ||
-       ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+    let wait = ctxt.frame_statistics.start_wait();
+    let result = if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+                    ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
     {
         ctxt.gl.ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT,
                                365 * 24 * 3600 * 1000 * 1000 * 1000)
@@ -177,7 +437,9 @@ unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync) -> gl
                                     365 * 24 * 3600 * 1000 * 1000 * 1000)
     } else {
         unreachable!();
-    }
+    };
+    ctxt.frame_statistics.end_wait(wait, kind);
+    result
 }
 
 /// Deletes a fence.
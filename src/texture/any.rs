@@ -5,26 +5,35 @@ use backend::Facade;
 use version::Version;
 use context::Context;
 use context::CommandContext;
+use context::WaitKind;
+use context::ObjectKind;
 use CapabilitiesSource;
 use ContextExt;
 use TextureExt;
 use TextureMipmapExt;
 use version::Api;
 use Rect;
+use BlitTarget;
+use BlitError;
+use StreamingWriteStatus;
+use sync::{DeferredDestroy, DeferredObject};
 
-use image_format::{self, TextureFormatRequest, ClientFormatAny};
+use image_format::{self, TextureFormatRequest, ClientFormat, ClientFormatAny};
 use texture::Texture2dDataSink;
-use texture::{MipmapsOption, TextureFormat, TextureCreationError, CubeLayer};
+use texture::{MipmapsOption, TextureFormat, TextureCreationError, CubeLayer, RawImage2d};
+use texture::{WriteRowsError, ReadImageError, CompressedLevelError};
 use texture::{get_format, InternalFormat, GetFormatError};
 use texture::pixel::PixelValue;
 use texture::pixel_buffer::PixelBuffer;
 
 use buffer::BufferSlice;
+use buffer::{Buffer, BufferType, BufferMode, BufferCreationError, TRANSFER_CHUNK_SIZE};
 use buffer::BufferAny;
 use BufferExt;
 use BufferSliceExt;
 
 use libc;
+use smallvec::SmallVec;
 use std::cmp;
 use std::fmt;
 use std::mem;
@@ -33,10 +42,14 @@ use std::borrow::Cow;
 use std::cell::Cell;
 use std::rc::Rc;
 use std::ops::Range;
+use std::time::{Duration, Instant};
 
 use ops;
 use fbo;
 
+use uniforms::{IncompleteTextureReason, SamplerBehavior};
+use uniforms::{MinifySamplerFilter, MagnifySamplerFilter};
+
 /// Type of a texture.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[allow(missing_docs)]      // TODO: document and remove
@@ -69,18 +82,174 @@ pub struct TextureAny {
     levels: u32,
     /// Is automatic mipmap generation allowed for this texture?
     generate_mipmaps: bool,
+    /// Should the mipmaps be marked as outdated when the main level is rendered to?
+    regenerate_mipmaps_after_render: bool,
+    /// True if the main level has been modified since the mipmaps have last been generated.
+    mipmaps_dirty: Cell<bool>,
+
+    /// Range of mipmap levels that can be sampled, as `(base, max)`. Both bounds are inclusive.
+    level_range: Cell<(u32, u32)>,
+    /// True if `level_range` has been modified but not yet applied to the OpenGL texture.
+    level_range_dirty: Cell<bool>,
+
+    /// False if a streaming write to the texture is in progress or has been interrupted.
+    complete: Cell<bool>,
+
+    /// Estimated number of bytes of video memory used by this texture.
+    memory_size: usize,
+}
+
+/// Checks whether the backend supports this type of texture with these dimensions.
+fn check_dimensions(context: &Context, ty: Dimensions) -> Result<(), TextureCreationError> {
+    let version = context.get_version();
+    let extensions = context.get_extensions();
+    let capabilities = context.get_capabilities();
+
+    let supported = match ty {
+        Dimensions::Texture1d { .. } | Dimensions::Texture1dArray { .. } => {
+            version >= &Version(Api::Gl, 1, 0)
+        },
+        Dimensions::Texture2d { .. } | Dimensions::Cubemap { .. } => true,
+        Dimensions::Texture3d { .. } => capabilities.max_3d_texture_size != 0,
+        Dimensions::Texture2dArray { .. } => capabilities.max_array_texture_layers != 0,
+        Dimensions::Texture2dMultisample { .. } => {
+            version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 1) ||
+            extensions.gl_arb_texture_multisample
+        },
+        Dimensions::Texture2dMultisampleArray { .. } => {
+            version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 2) ||
+            extensions.gl_arb_texture_multisample
+        },
+        Dimensions::CubemapArray { .. } => {
+            version >= &Version(Api::Gl, 4, 0) || version >= &Version(Api::GlEs, 3, 2) ||
+            extensions.gl_arb_texture_cube_map_array ||
+            extensions.gl_ext_texture_cube_map_array ||
+            extensions.gl_oes_texture_cube_map_array
+        },
+    };
+
+    if !supported {
+        return Err(TextureCreationError::TypeNotSupported);
+    }
+
+    let empty = match ty {
+        Dimensions::Texture1d { width } => width == 0,
+        Dimensions::Texture1dArray { width, array_size } => width == 0 || array_size == 0,
+        Dimensions::Texture2d { width, height } => width == 0 || height == 0,
+        Dimensions::Texture2dMultisample { width, height, samples } => {
+            width == 0 || height == 0 || samples == 0
+        },
+        Dimensions::Texture2dArray { width, height, array_size } => {
+            width == 0 || height == 0 || array_size == 0
+        },
+        Dimensions::Texture2dMultisampleArray { width, height, array_size, samples } => {
+            width == 0 || height == 0 || array_size == 0 || samples == 0
+        },
+        Dimensions::Texture3d { width, height, depth } => width == 0 || height == 0 || depth == 0,
+        Dimensions::Cubemap { dimension } => dimension == 0,
+        Dimensions::CubemapArray { dimension, array_size } => dimension == 0 || array_size == 0,
+    };
+
+    if empty {
+        return Err(TextureCreationError::ZeroDimension);
+    }
+
+    fn check(requested: u32, max: gl::types::GLint) -> Result<(), TextureCreationError> {
+        if requested > max as u32 {
+            Err(TextureCreationError::DimensionsTooLarge { requested: requested, max: max as u32 })
+        } else {
+            Ok(())
+        }
+    }
+
+    let max_size = capabilities.max_texture_size;
+    let max_layers = capabilities.max_array_texture_layers;
+
+    match ty {
+        Dimensions::Texture1d { width } => check(width, max_size),
+        Dimensions::Texture1dArray { width, array_size } => {
+            try!(check(width, max_size));
+            check(array_size, max_layers)
+        },
+        Dimensions::Texture2d { width, height } |
+        Dimensions::Texture2dMultisample { width, height, .. } => {
+            try!(check(width, max_size));
+            check(height, max_size)
+        },
+        Dimensions::Texture2dArray { width, height, array_size } |
+        Dimensions::Texture2dMultisampleArray { width, height, array_size, .. } => {
+            try!(check(width, max_size));
+            try!(check(height, max_size));
+            check(array_size, max_layers)
+        },
+        Dimensions::Texture3d { width, height, depth } => {
+            let max = capabilities.max_3d_texture_size;
+            try!(check(width, max));
+            try!(check(height, max));
+            check(depth, max)
+        },
+        Dimensions::Cubemap { dimension } => {
+            check(dimension, capabilities.max_cube_map_texture_size)
+        },
+        Dimensions::CubemapArray { dimension, array_size } => {
+            try!(check(dimension, capabilities.max_cube_map_texture_size));
+            check(array_size * 6, max_layers)
+        },
+    }
+}
+
+/// Returns true if the format is an integral or unsigned format.
+fn is_integral_format(format: TextureFormatRequest) -> bool {
+    match format {
+        TextureFormatRequest::AnyIntegral | TextureFormatRequest::AnyUnsigned |
+        TextureFormatRequest::AnyStencil => true,
+        TextureFormatRequest::Specific(TextureFormat::UncompressedIntegral(_)) => true,
+        TextureFormatRequest::Specific(TextureFormat::UncompressedUnsigned(_)) => true,
+        TextureFormatRequest::Specific(TextureFormat::StencilFormat(_)) => true,
+        _ => false,
+    }
 }
 
 /// Builds a new texture.
 ///
-/// # Panic
-///
-/// Panicks if the size of the data doesn't match the texture dimensions.
+/// Returns an error if the size of the data doesn't match the texture dimensions.
+#[inline]
 pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
                              data: Option<(ClientFormatAny, Cow<'a, [P]>)>,
                              mipmaps: MipmapsOption, ty: Dimensions)
                              -> Result<TextureAny, TextureCreationError>
                              where P: Send + Clone + 'a, F: Facade
+{
+    new_texture_impl(facade, format, data, mipmaps, ty, false)
+}
+
+/// Builds a new 2D texture from data whose pixels are in the BGR or BGRA order.
+///
+/// The data is directly uploaded with `GL_BGRA` if the backend supports it. Otherwise it is
+/// converted to RGBA first.
+pub fn new_texture_bgra<'a, F>(facade: &F, format: TextureFormatRequest,
+                               image: RawImage2d<'a, u8>, mipmaps: MipmapsOption)
+                               -> Result<TextureAny, TextureCreationError>
+                               where F: Facade
+{
+    let ty = Dimensions::Texture2d { width: image.width, height: image.height };
+
+    if facade.get_context().get_version() >= &Version(Api::Gl, 1, 2) {
+        let client_format = ClientFormatAny::ClientFormat(image.format);
+        new_texture_impl(facade, format, Some((client_format, image.data)), mipmaps, ty, true)
+
+    } else {
+        let image = image.bgra_to_rgba();
+        let client_format = ClientFormatAny::ClientFormat(image.format);
+        new_texture_impl(facade, format, Some((client_format, image.data)), mipmaps, ty, false)
+    }
+}
+
+fn new_texture_impl<'a, F, P>(facade: &F, format: TextureFormatRequest,
+                              data: Option<(ClientFormatAny, Cow<'a, [P]>)>,
+                              mipmaps: MipmapsOption, ty: Dimensions, inverted: bool)
+                              -> Result<TextureAny, TextureCreationError>
+                              where P: Send + Clone + 'a, F: Facade
 {
     // getting the width, height, depth, array_size, samples from the type
     let (width, height, depth, array_size, samples) = match ty {
@@ -104,12 +273,16 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
     };
 
     if let Some((_, ref data)) = data {
-        if data.len() * mem::size_of::<P>() != data_bufsize
-        {
-            panic!("Texture data size mismatch");
+        if data.len() * mem::size_of::<P>() != data_bufsize {
+            return Err(TextureCreationError::DataLengthMismatch {
+                expected: data_bufsize,
+                provided: data.len() * mem::size_of::<P>(),
+            });
         }
     }
 
+    try!(check_dimensions(facade.get_context(), ty));
+
     // getting the `GLenum` corresponding to this texture type
     let bind_point = match ty {
         Dimensions::Texture1d { .. } => gl::TEXTURE_1D,
@@ -139,13 +312,21 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
     }
 
     let generate_mipmaps = mipmaps.should_generate();
+    let regenerate_mipmaps_after_render = mipmaps.should_regenerate_after_render();
     let texture_levels = mipmaps.num_levels(width, height, depth) as gl::types::GLsizei;
 
+    // norm16 formats are optional on OpenGL ES
+    if let TextureFormatRequest::Specific(TextureFormat::UncompressedFloat(f)) = format {
+        if f.is_norm16() && !f.is_supported(facade.get_context()) {
+            return Err(TextureCreationError::Norm16NotSupported);
+        }
+    }
+
     let teximg_internal_format = try!(image_format::format_request_to_glenum(facade.get_context(), format, image_format::RequestType::TexImage(data.as_ref().map(|&(c, _)| c))));
     let storage_internal_format = image_format::format_request_to_glenum(facade.get_context(), format, image_format::RequestType::TexStorage).ok();
 
     let (client_format, client_type) = match (&data, format) {
-        (&Some((client_format, _)), f) => try!(image_format::client_format_to_glenum(facade.get_context(), client_format, f, false)),
+        (&Some((client_format, _)), f) => try!(image_format::client_format_to_glenum(facade.get_context(), client_format, f, inverted)),
         (&None, TextureFormatRequest::AnyDepth) => (gl::DEPTH_COMPONENT, gl::FLOAT),
         (&None, TextureFormatRequest::Specific(TextureFormat::DepthFormat(_))) => (gl::DEPTH_COMPONENT, gl::FLOAT),
         (&None, TextureFormatRequest::AnyDepthStencil) => (gl::DEPTH_STENCIL, gl::UNSIGNED_INT_24_8),
@@ -164,11 +345,7 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
             ptr::null()
         };
 
-        if ctxt.state.pixel_store_unpack_alignment != 1 {
-            ctxt.state.pixel_store_unpack_alignment = 1;
-            ctxt.gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-        }
-
+        ops::set_unpack_tightly_packed(&mut ctxt);
         BufferAny::unbind_pixel_unpack(&mut ctxt);
 
         let id: gl::types::GLuint = mem::uninitialized();
@@ -180,8 +357,15 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
             ctxt.state.texture_units[act].texture = id;
         }
 
+        // integral textures are incomplete if they use linear filtering
+        let integral = is_integral_format(format);
+
         ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-        ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        if integral {
+            ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+        } else {
+            ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        }
 
         match ty {
             Dimensions::Texture1d { .. } => (),
@@ -199,7 +383,13 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
             },
         };
 
-        if has_mipmaps {
+        if integral && has_mipmaps {
+            ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MIN_FILTER,
+                                  gl::NEAREST_MIPMAP_NEAREST as i32);
+        } else if integral {
+            ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MIN_FILTER,
+                                  gl::NEAREST as i32);
+        } else if has_mipmaps {
             ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MIN_FILTER,
                                   gl::LINEAR_MIPMAP_LINEAR as i32);
         } else {
@@ -416,6 +606,29 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
         id
     };
 
+    // computing the amount of memory used by the texture from the requested format ; if only
+    // a kind of format has been requested we assume 32 bits per texel
+    let bits_per_texel = match format {
+        TextureFormatRequest::Specific(format) => format.get_bits_per_texel(),
+        _ => 32,
+    };
+
+    let mut texels = 0;
+    for level in 0 .. texture_levels as u32 {
+        texels += cmp::max(width >> level, 1) as usize *
+                  cmp::max(height.unwrap_or(1) >> level, 1) as usize *
+                  cmp::max(depth.unwrap_or(1) >> level, 1) as usize;
+    }
+    let layers = match ty {
+        Dimensions::Cubemap { .. } => 6,
+        _ => array_size.unwrap_or(1) as usize,
+    };
+    let memory_size = texels * layers * samples.unwrap_or(1) as usize * bits_per_texel / 8;
+
+    ctxt.live_objects.add(ObjectKind::Texture, id as usize);
+    drop(ctxt);
+    facade.get_context().get_memory_usage_counters().add_texture(memory_size);
+
     Ok(TextureAny {
         context: facade.get_context().clone(),
         id: id,
@@ -424,10 +637,59 @@ pub fn new_texture<'a, F, P>(facade: &F, format: TextureFormatRequest,
         ty: ty,
         levels: texture_levels as u32,
         generate_mipmaps: generate_mipmaps,
+        regenerate_mipmaps_after_render: regenerate_mipmaps_after_render,
+        mipmaps_dirty: Cell::new(false),
+        level_range: Cell::new((0, texture_levels as u32 - 1)),
+        level_range_dirty: Cell::new(false),
+        complete: Cell::new(true),
+        memory_size: memory_size,
     })
 }
 
 impl TextureAny {
+    /// Turns the texture into a `DetachedTexture`, without destroying the OpenGL texture.
+    pub fn detach(self) -> DetachedTexture {
+        let detached = DetachedTexture {
+            id: self.id,
+            requested_format: self.requested_format,
+            actual_format: self.actual_format.get(),
+            ty: self.ty,
+            levels: self.levels,
+            generate_mipmaps: self.generate_mipmaps,
+            regenerate_mipmaps_after_render: self.regenerate_mipmaps_after_render,
+            mipmaps_dirty: self.mipmaps_dirty.get(),
+            level_range: self.level_range.get(),
+            level_range_dirty: self.level_range_dirty.get(),
+            complete: self.complete.get(),
+            memory_size: self.memory_size,
+        };
+
+        // releasing the context without running the destructor
+        unsafe { drop(ptr::read(&self.context)) };
+        mem::forget(self);
+
+        detached
+    }
+
+    /// Builds back a texture from a `DetachedTexture`.
+    pub fn attach(detached: DetachedTexture, context: &Rc<Context>) -> TextureAny {
+        TextureAny {
+            context: context.clone(),
+            id: detached.id,
+            requested_format: detached.requested_format,
+            actual_format: Cell::new(detached.actual_format),
+            ty: detached.ty,
+            levels: detached.levels,
+            generate_mipmaps: detached.generate_mipmaps,
+            regenerate_mipmaps_after_render: detached.regenerate_mipmaps_after_render,
+            mipmaps_dirty: Cell::new(detached.mipmaps_dirty),
+            level_range: Cell::new(detached.level_range),
+            level_range_dirty: Cell::new(detached.level_range_dirty),
+            complete: Cell::new(detached.complete),
+            memory_size: detached.memory_size,
+        }
+    }
+
     /// Returns the width of the texture.
     #[inline]
     pub fn get_width(&self) -> u32 {
@@ -526,6 +788,16 @@ impl TextureAny {
         self.ty
     }
 
+    /// Returns the format that has been requested when creating the texture, or `None` if the
+    /// backend was free to choose among several formats.
+    #[inline]
+    pub fn get_requested_format(&self) -> Option<TextureFormat> {
+        match self.requested_format {
+            TextureFormatRequest::Specific(format) => Some(format),
+            _ => None,
+        }
+    }
+
     /// Determines the internal format of this texture.
     #[inline]
     pub fn get_internal_format(&self) -> Result<InternalFormat, GetFormatError> {
@@ -546,6 +818,139 @@ impl TextureAny {
         self.levels
     }
 
+    /// Restricts the mipmap levels that are sampled to `base ..= max`.
+    ///
+    /// This is useful when the levels of a texture are uploaded progressively, starting with the
+    /// smallest ones. The levels outside of the range don't need to contain any data, and the
+    /// texture is not considered incomplete because of them.
+    ///
+    /// The range is stored in the `GL_TEXTURE_BASE_LEVEL` and `GL_TEXTURE_MAX_LEVEL` parameters
+    /// of the texture the next time it is sampled. Since these parameters are part of the
+    /// texture and not of the sampler, the range is also honored when sampler objects are used.
+    /// Calling this function multiple times before a draw only changes the texture once, and
+    /// setting the same range again doesn't change anything.
+    ///
+    /// If the texture has automatically-generated mipmaps, they are not regenerated as long as
+    /// `base` is not `0`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `base` is greater than `max`, if `max` is not a valid level, or if the backend
+    /// doesn't support OpenGL 1.2 or OpenGL ES 3.0.
+    pub fn set_resident_level_range(&self, base: u32, max: u32) {
+        assert!(base <= max, "The base level must not be greater than the max level");
+        assert!(max < self.levels, "The max level must be lower than the number of levels");
+        assert!(self.context.get_version() >= &Version(Api::Gl, 1, 2) ||
+                self.context.get_version() >= &Version(Api::GlEs, 3, 0),
+                "Restricting the mipmap levels is not supported by the backend");
+
+        if self.level_range.get() != (base, max) {
+            self.level_range.set((base, max));
+            self.level_range_dirty.set(true);
+
+            // the levels that were outside of the range may not have been generated
+            self.mark_mipmaps_dirty();
+        }
+    }
+
+    /// Returns the range of mipmap levels that are sampled, as `(base, max)`.
+    ///
+    /// This is `(0, levels - 1)` unless `set_resident_level_range` has been called.
+    #[inline]
+    pub fn get_resident_level_range(&self) -> (u32, u32) {
+        self.level_range.get()
+    }
+
+    /// Writes the range of levels to the OpenGL texture if it has been modified.
+    unsafe fn apply_level_range(&self, ctxt: &mut CommandContext) {
+        if !self.level_range_dirty.get() {
+            return;
+        }
+
+        let (base, max) = self.level_range.get();
+
+        if ctxt.capabilities.direct_state_access {
+            ctxt.gl.TextureParameteri(self.id, gl::TEXTURE_BASE_LEVEL, base as gl::types::GLint);
+            ctxt.gl.TextureParameteri(self.id, gl::TEXTURE_MAX_LEVEL, max as gl::types::GLint);
+        } else {
+            // binding through the cache so that it stays consistent with the actual state
+            let bind_point = self.bind_to_current_for_edit(ctxt);
+            ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_BASE_LEVEL, base as gl::types::GLint);
+            ctxt.gl.TexParameteri(bind_point, gl::TEXTURE_MAX_LEVEL, max as gl::types::GLint);
+        }
+
+        self.level_range_dirty.set(false);
+    }
+
+    /// Returns false if a streaming write to the texture is in progress or has been interrupted.
+    /// Sampling such a texture returns `DrawError::IncompleteTexture`.
+    ///
+    /// The texture becomes complete again when a streaming write is done.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.complete.get()
+    }
+
+    /// Starts uploading `data` to the main level of the texture incrementally.
+    ///
+    /// See `TextureAnyMipmap::begin_streaming_write` for more details.
+    #[inline]
+    pub fn begin_streaming_write<P>(&self, data: Vec<P>)
+                                    -> Result<StreamingWrite<P>, BufferCreationError>
+                                    where P: PixelValue
+    {
+        self.main_level().begin_streaming_write(data)
+    }
+
+    /// Same as `bind_to_current`, but for the functions that bind the texture only in order to
+    /// modify it. Updates the `edit_binds` statistics.
+    #[inline]
+    fn bind_to_current_for_edit(&self, ctxt: &mut CommandContext) -> gl::types::GLenum {
+        ctxt.frame_statistics.update(|s| s.edit_binds += 1);
+        self.bind_to_current(ctxt)
+    }
+
+    /// Tells glium that the main level of the texture has been modified outside of glium, for
+    /// example by a library that uses the raw OpenGL texture.
+    ///
+    /// If the texture has been created with automatically-generated mipmaps, they will be
+    /// regenerated the next time the texture is sampled with a filter that uses them.
+    #[inline]
+    pub fn mark_mipmaps_dirty(&self) {
+        if self.generate_mipmaps && self.levels > 1 {
+            self.mipmaps_dirty.set(true);
+        }
+    }
+
+    /// Regenerates the mipmaps from the main level with `glGenerateMipmap`.
+    ///
+    /// Does nothing if the base level of the texture isn't `0`, as the mipmaps would be generated
+    /// from the base level.
+    unsafe fn regenerate_mipmaps(&self, ctxt: &mut CommandContext) {
+        if self.level_range.get().0 != 0 {
+            return;
+        }
+
+        // `glGenerateMipmap` uses the levels range of the texture
+        self.apply_level_range(ctxt);
+
+        if ctxt.capabilities.direct_state_access {
+            ctxt.gl.GenerateTextureMipmap(self.id);
+        } else if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                  ctxt.version >= &Version(Api::GlEs, 2, 0)
+        {
+            let bind_point = self.bind_to_current_for_edit(ctxt);
+            ctxt.gl.GenerateMipmap(bind_point);
+        } else if ctxt.extensions.gl_ext_framebuffer_object {
+            let bind_point = self.bind_to_current_for_edit(ctxt);
+            ctxt.gl.GenerateMipmapEXT(bind_point);
+        } else {
+            unreachable!();
+        }
+
+        self.mipmaps_dirty.set(false);
+    }
+
     /// Returns a structure that represents the main mipmap level of the texture.
     #[inline]
     pub fn main_level(&self) -> TextureAnyMipmap {
@@ -570,6 +975,255 @@ impl TextureAny {
             depth: self.get_depth().map(|depth| cmp::max(1, depth / pow)),
         })
     }
+
+    /// Fills the mipmap level `target_level` with the content of `source_level` by blitting
+    /// with linear filtering.
+    ///
+    /// All the layers (and all the faces for cubemaps) are blitted. The source and the target can
+    /// have different dimensions, in which case the content is scaled. This is typically used to
+    /// downsample a level into the next one.
+    ///
+    /// Returns an error if the levels can't be attached to a framebuffer, for example because
+    /// the texture doesn't have a color format, or if the blit isn't possible.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if one of the levels is out of range or if both levels are the same.
+    /// - Panics if the texture is a 3D texture or a multisample texture.
+    ///
+    pub fn blit_level_to(&self, source_level: u32, target_level: u32) -> Result<(), BlitError> {
+        assert!(source_level != target_level, "Can't blit a mipmap level to itself");
+        let source = self.mipmap(source_level).expect("Source mipmap level out of range");
+        let target = self.mipmap(target_level).expect("Target mipmap level out of range");
+
+        match self.ty {
+            Dimensions::Texture3d { .. } => panic!("Can't blit between levels of a 3D texture"),
+            Dimensions::Texture2dMultisample { .. } |
+            Dimensions::Texture2dMultisampleArray { .. } => {
+                panic!("Multisample textures don't have mipmaps")
+            },
+            _ => ()
+        }
+
+        let cube_layers: &[Option<CubeLayer>] = match self.ty {
+            Dimensions::Cubemap { .. } | Dimensions::CubemapArray { .. } => {
+                &[Some(CubeLayer::PositiveX), Some(CubeLayer::NegativeX),
+                  Some(CubeLayer::PositiveY), Some(CubeLayer::NegativeY),
+                  Some(CubeLayer::PositiveZ), Some(CubeLayer::NegativeZ)]
+            },
+            _ => &[None]
+        };
+
+        let src_rect = Rect {
+            left: 0,
+            bottom: 0,
+            width: source.get_width(),
+            height: source.get_height().unwrap_or(1),
+        };
+
+        let target_rect = BlitTarget {
+            left: 0,
+            bottom: 0,
+            width: target.get_width() as i32,
+            height: target.get_height().unwrap_or(1) as i32,
+        };
+
+        for layer in 0 .. self.get_array_size().unwrap_or(1) {
+            for &cube_layer in cube_layers {
+                let source = source.layer(layer).unwrap().into_image(cube_layer).unwrap();
+                let target = target.layer(layer).unwrap().into_image(cube_layer).unwrap();
+
+                let source = fbo::FramebufferAttachments::Regular(fbo::FramebufferSpecificAttachments {
+                    colors: { let mut v = SmallVec::new();
+                              v.push((0, fbo::RegularAttachment::Texture(source))); v },
+                    depth_stencil: fbo::DepthStencilAttachments::None,
+                }).validate(&*self.context);
+                let source = try!(source.map_err(BlitError::FramebufferValidation));
+
+                let target = fbo::FramebufferAttachments::Regular(fbo::FramebufferSpecificAttachments {
+                    colors: { let mut v = SmallVec::new();
+                              v.push((0, fbo::RegularAttachment::Texture(target))); v },
+                    depth_stencil: fbo::DepthStencilAttachments::None,
+                }).validate(&*self.context);
+                let target = try!(target.map_err(BlitError::FramebufferValidation));
+
+                try!(ops::blit(&self.context, Some(&source), Some(&target), gl::COLOR_BUFFER_BIT,
+                               &src_rect, &target_rect, gl::LINEAR));
+            }
+        }
+
+        // the framebuffers stay in the cache, but we don't leave them bound
+        let mut ctxt = self.context.make_current();
+        unsafe { fbo::bind_framebuffer(&mut ctxt, 0, true, true) };
+        Ok(())
+    }
+
+    /// Generates all the mipmaps of the texture by blitting each level to the next one with
+    /// linear filtering.
+    ///
+    /// This is an alternative to the automatic mipmaps generation, which is slow or of poor
+    /// quality with some drivers. The dimensions of each level are those defined by OpenGL,
+    /// which means that they are rounded down for non-power-of-two textures.
+    ///
+    /// # Panic
+    ///
+    /// Same as `blit_level_to`.
+    ///
+    pub fn generate_mipmaps_via_blit(&self) -> Result<(), BlitError> {
+        for level in 1 .. self.levels {
+            try!(self.blit_level_to(level - 1, level));
+        }
+
+        Ok(())
+    }
+
+    /// Reads a mipmap level of one layer of the texture to RAM.
+    ///
+    /// `layer` is the index of the layer for array textures and must be `0` for other textures.
+    /// `cube_layer` is the face to read for cubemaps and cubemap arrays, and must be `None` for
+    /// other textures. The result has the dimensions of the mipmap level. For 3D textures, only
+    /// the first slice of the level is returned.
+    ///
+    /// With desktop OpenGL, the image is read with `glGetTexImage`, which also works with depth
+    /// textures. With OpenGL ES, the image is attached to a framebuffer and read with
+    /// `glReadPixels`, which only supports `(u8, u8, u8, u8)` and color textures by default.
+    ///
+    /// You should avoid doing this during performance-critical operations, as it waits for the
+    /// GPU to finish writing to the texture.
+    pub fn read_image<T, P>(&self, level: u32, layer: u32, cube_layer: Option<CubeLayer>)
+                            -> Result<T, ReadImageError>
+                            where T: Texture2dDataSink<P>, P: PixelValue
+    {
+        if self.get_samples().is_some() {
+            return Err(ReadImageError::Multisample);
+        }
+
+        let image = match self.mipmap(level) {
+            Some(mipmap) => mipmap,
+            None => return Err(ReadImageError::LevelOutOfRange { level: level,
+                                                                  levels: self.levels }),
+        };
+
+        let image = match image.layer(layer) {
+            Some(layer) => layer,
+            None => {
+                let layers = self.get_array_size().unwrap_or(1);
+                return Err(ReadImageError::LayerOutOfRange { layer: layer, layers: layers });
+            },
+        };
+
+        let image = match (image.into_image(cube_layer), cube_layer) {
+            (Some(image), _) => image,
+            (None, Some(_)) => return Err(ReadImageError::NotCubemap),
+            (None, None) => return Err(ReadImageError::MissingCubeLayer),
+        };
+
+        let width = image.get_width();
+        let height = image.get_height().unwrap_or(1);
+
+        let mut ctxt = self.context.make_current();
+
+        if level != 0 && self.mipmaps_dirty.get() {
+            unsafe { self.regenerate_mipmaps(&mut ctxt) };
+        }
+
+        let data = if ctxt.version >= &Version(Api::Gl, 1, 0) {
+            try!(self.get_tex_image(&mut ctxt, &image))
+        } else {
+            let rect = Rect { left: 0, bottom: 0, width: width, height: height };
+            let mut data = Vec::new();
+            try!(ops::read(&mut ctxt, &fbo::RegularAttachment::Texture(image), &rect,
+                           &mut data, false));
+            data
+        };
+
+        Ok(T::from_raw(Cow::Owned(data), width, height))
+    }
+
+    /// Reads an image of the texture with `glGetTexImage`. Only available on desktop OpenGL.
+    ///
+    /// `glGetTexImage` returns all the layers of the level at once, so the layer of the image is
+    /// extracted from the result.
+    fn get_tex_image<P>(&self, ctxt: &mut CommandContext, image: &TextureAnyImage)
+                        -> Result<Vec<P>, ReadImageError> where P: PixelValue
+    {
+        let client_format = <P as PixelValue>::get_format();
+        let (format, gltype) = ops::client_format_to_gl_enum(&client_format);
+
+        let format = match self.requested_format {
+            TextureFormatRequest::AnyDepth |
+            TextureFormatRequest::Specific(TextureFormat::DepthFormat(_)) => {
+                if client_format.get_num_components() != 1 {
+                    return Err(ops::ReadError::OutputFormatNotSupported.into());
+                }
+
+                gl::DEPTH_COMPONENT
+            },
+            TextureFormatRequest::AnyStencil | TextureFormatRequest::AnyDepthStencil |
+            TextureFormatRequest::Specific(TextureFormat::StencilFormat(_)) |
+            TextureFormatRequest::Specific(TextureFormat::DepthStencilFormat(_)) => {
+                return Err(ops::ReadError::AttachmentTypeNotSupported.into());
+            },
+            _ => {
+                if fbo::get_color_attachment_type(self) != fbo::ColorAttachmentType::Float {
+                    ops::integer_client_format(format)
+                } else {
+                    format
+                }
+            },
+        };
+
+        let pixels_per_layer = image.get_width() as usize *
+                               image.get_height().unwrap_or(1) as usize;
+
+        // number of layers returned by `glGetTexImage`, and the one that we want
+        let (layers, wanted) = match self.ty {
+            Dimensions::Texture1dArray { array_size, .. } |
+            Dimensions::Texture2dArray { array_size, .. } => (array_size, image.get_layer()),
+            Dimensions::CubemapArray { array_size, .. } => {
+                let face = image.get_cubemap_layer().unwrap().get_layer_index() as u32;
+                (array_size * 6, image.get_layer() * 6 + face)
+            },
+            Dimensions::Texture3d { .. } => {
+                let depth = self.mipmap(image.get_level()).unwrap().get_depth().unwrap();
+                (depth, 0)
+            },
+            _ => (1, 0),
+        };
+
+        let target = match (self.ty, image.get_cubemap_layer()) {
+            (Dimensions::Cubemap { .. }, Some(face)) => {
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face.get_layer_index() as gl::types::GLenum
+            },
+            _ => self.get_bind_point(),
+        };
+
+        let total = pixels_per_layer * layers as usize;
+        let mut buf: Vec<P> = Vec::with_capacity(total);
+
+        unsafe {
+            self.bind_to_current(ctxt);
+            BufferAny::unbind_pixel_pack(ctxt);
+
+            // the rows of the result are tightly packed
+            ops::set_pack_tightly_packed(ctxt);
+
+            let wait = ctxt.frame_statistics.start_wait();
+            ctxt.gl.GetTexImage(target, image.get_level() as gl::types::GLint, format, gltype,
+                                buf.as_mut_ptr() as *mut _);
+            ctxt.frame_statistics.end_wait(wait, WaitKind::Read);
+            buf.set_len(total);
+        }
+
+        ctxt.frame_statistics.update(|s| s.reads += 1);
+
+        if layers == 1 {
+            Ok(buf)
+        } else {
+            let start = wanted as usize * pixels_per_layer;
+            Ok(buf[start .. start + pixels_per_layer].to_vec())
+        }
+    }
 }
 
 impl TextureExt for TextureAny {
@@ -578,6 +1232,99 @@ impl TextureExt for TextureAny {
         self.id
     }
 
+    fn check_sampling_completeness(&self, ctxt: &CommandContext,
+                                   sampler: Option<&SamplerBehavior>)
+                                   -> Result<(), IncompleteTextureReason>
+    {
+        match self.ty {
+            Dimensions::Texture2dMultisample { .. } |
+            Dimensions::Texture2dMultisampleArray { .. } => return Ok(()),
+            _ => ()
+        };
+
+        let integral = is_integral_format(self.requested_format);
+
+        // the default filters are the ones that are set when creating the texture
+        let (uses_mipmaps, linear) = match sampler {
+            Some(sampler) => {
+                let uses_mipmaps = match sampler.minify_filter {
+                    MinifySamplerFilter::Nearest | MinifySamplerFilter::Linear => false,
+                    _ => true,
+                };
+
+                let linear = match (sampler.minify_filter, sampler.magnify_filter) {
+                    (MinifySamplerFilter::Nearest, MagnifySamplerFilter::Nearest) => false,
+                    (MinifySamplerFilter::NearestMipmapNearest,
+                     MagnifySamplerFilter::Nearest) => false,
+                    _ => true,
+                };
+
+                (uses_mipmaps, linear)
+            },
+            None => (self.levels > 1, !integral),
+        };
+
+        if integral && linear {
+            return Err(IncompleteTextureReason::IntegralTextureWithLinearFiltering);
+        }
+
+        // textures without mipmaps have their max level set to 0 at creation if the backend
+        // supports it ; levels outside of the resident range are never sampled
+        let (base, max) = self.level_range.get();
+        if uses_mipmaps && max == base && !(ctxt.version >= &Version(Api::Gl, 1, 2) ||
+                                                 ctxt.version >= &Version(Api::GlEs, 3, 0))
+        {
+            return Err(IncompleteTextureReason::MissingMipmaps);
+        }
+
+        Ok(())
+    }
+
+    fn get_sampled_levels(&self, sampler: Option<&SamplerBehavior>) -> Range<u32> {
+        let uses_mipmaps = match sampler {
+            Some(sampler) => match sampler.minify_filter {
+                MinifySamplerFilter::Nearest | MinifySamplerFilter::Linear => false,
+                _ => true,
+            },
+            None => self.levels > 1,
+        };
+
+        let (base, max) = self.level_range.get();
+        if uses_mipmaps {
+            base .. max + 1
+        } else {
+            base .. base + 1
+        }
+    }
+
+    #[inline]
+    fn is_upload_complete(&self) -> bool {
+        self.complete.get()
+    }
+
+    #[inline]
+    fn get_regenerate_mipmaps_after_render(&self) -> bool {
+        self.regenerate_mipmaps_after_render && self.levels > 1
+    }
+
+    #[inline]
+    fn mark_rendered(&self) {
+        self.mipmaps_dirty.set(true);
+    }
+
+    fn prepare_for_sampling(&self, ctxt: &mut CommandContext, sampler: Option<&SamplerBehavior>) {
+        unsafe { self.apply_level_range(ctxt) };
+
+        if !self.mipmaps_dirty.get() {
+            return;
+        }
+
+        let levels = self.get_sampled_levels(sampler);
+        if levels.end - levels.start > 1 {
+            unsafe { self.regenerate_mipmaps(ctxt) };
+        }
+    }
+
     #[inline]
     fn get_context(&self) -> &Rc<Context> {
         &self.context
@@ -605,6 +1352,7 @@ impl TextureExt for TextureAny {
         if ctxt.state.texture_units[texture_unit as usize].texture != self.id {
             unsafe { ctxt.gl.BindTexture(bind_point, self.id) };
             ctxt.state.texture_units[texture_unit as usize].texture = self.id;
+            ctxt.frame_statistics.update(|s| s.texture_binds += 1);
         }
 
         bind_point
@@ -630,21 +1378,92 @@ impl fmt::Debug for TextureAny {
 }
 
 impl Drop for TextureAny {
+    #[inline]
     fn drop(&mut self) {
-        let mut ctxt = self.context.make_current();
+        destroy_texture(&self.context, self.id, self.memory_size);
+    }
+}
+
+impl DeferredDestroy for TextureAny {
+    type Detached = DetachedTexture;
+
+    #[inline]
+    fn detach(self) -> DetachedTexture {
+        TextureAny::detach(self)
+    }
+
+    #[inline]
+    fn attach(detached: DetachedTexture, context: &Rc<Context>) -> TextureAny {
+        TextureAny::attach(detached, context)
+    }
+
+    #[inline]
+    fn into_deferred_object(detached: DetachedTexture) -> DeferredObject {
+        DeferredObject::Texture(detached)
+    }
+}
+
+/// Content of a `TextureAny` without the reference to its context, used to send textures to
+/// other threads. Doesn't destroy the OpenGL texture when dropped.
+#[doc(hidden)]
+pub struct DetachedTexture {
+    id: gl::types::GLuint,
+    requested_format: TextureFormatRequest,
+    actual_format: Option<Result<InternalFormat, GetFormatError>>,
+    ty: Dimensions,
+    levels: u32,
+    generate_mipmaps: bool,
+    regenerate_mipmaps_after_render: bool,
+    mipmaps_dirty: bool,
+    level_range: (u32, u32),
+    level_range_dirty: bool,
+    complete: bool,
+    memory_size: usize,
+}
+
+impl DetachedTexture {
+    /// Destroys the OpenGL texture. Must be called in the thread of the context.
+    #[inline]
+    pub fn destroy(self, context: &Context) {
+        destroy_texture(context, self.id, self.memory_size);
+    }
+}
+
+/// Destroys a texture and removes it from the caches of all the contexts that share it.
+fn destroy_texture(context: &Context, id: gl::types::GLuint, memory_size: usize) {
+    // the counters are updated even if the texture is leaked, so that they stay in sync with
+    // the textures that are alive from the user's point of view
+    context.get_memory_usage_counters().remove_texture(memory_size);
+
+    let mut ctxt = match context.make_current_for_drop() {
+        Some(ctxt) => ctxt,
+        None => return,
+    };
+
+    // removing FBOs which contain this texture
+    fbo::FramebuffersContainer::purge_texture(&mut ctxt, id);
+
+    // resetting the bindings
+    for tex_unit in ctxt.state.texture_units.iter_mut() {
+        if tex_unit.texture == id {
+            tex_unit.texture = 0;
+        }
+    }
 
-        // removing FBOs which contain this texture
-        fbo::FramebuffersContainer::purge_texture(&mut ctxt, self.id);
+    unsafe { ctxt.gl.DeleteTextures(1, [ id ].as_ptr()); }
+    ctxt.live_objects.remove(ObjectKind::Texture, id as usize);
+    drop(ctxt);
+
+    // cleaning the caches of the contexts that share this texture
+    context.for_each_shared_context(|ctxt| {
+        fbo::FramebuffersContainer::purge_texture(ctxt, id);
 
-        // resetting the bindings
         for tex_unit in ctxt.state.texture_units.iter_mut() {
-            if tex_unit.texture == self.id {
+            if tex_unit.texture == id {
                 tex_unit.texture = 0;
             }
         }
-
-        unsafe { ctxt.gl.DeleteTextures(1, [ self.id ].as_ptr()); }
-    }
+    });
 }
 
 /// Represents a specific layer of an array texture and 3D textures.
@@ -824,16 +1643,28 @@ impl<'a> TextureAnyMipmap<'a> {
         self.raw_upload_from_pixel_buffer_impl(source, x, y, z, true);
     }
 
+    /// Returns the number of pixels, rows and slices that can be uploaded to this level, which
+    /// are the ranges accepted by `raw_upload_from_pixel_buffer`.
+    ///
+    /// The layers of array textures are the rows of 1D arrays and the slices of 2D arrays.
+    fn get_upload_extents(&self) -> (u32, u32, u32) {
+        match self.texture.ty {
+            Dimensions::Texture1dArray { array_size, .. } => (self.width, array_size, 1),
+            Dimensions::Texture2dArray { array_size, .. } => {
+                (self.width, self.height.unwrap_or(1), array_size)
+            },
+            _ => (self.width, self.height.unwrap_or(1), self.depth.unwrap_or(1)),
+        }
+    }
+
     fn raw_upload_from_pixel_buffer_impl<P>(&self, source: BufferSlice<[P]>, x: Range<u32>,
                                             y: Range<u32>, z: Range<u32>, inverted: bool)
                                             where P: PixelValue
     {
-        assert!(x.start <= self.width);
-        assert!(y.start <= self.height.unwrap_or(0));
-        assert!(z.start <= self.depth.unwrap_or(0));
-        assert!(x.end <= self.width);
-        assert!(y.end <= self.height.unwrap_or(1));
-        assert!(z.end <= self.depth.unwrap_or(1));
+        let (max_x, max_y, max_z) = self.get_upload_extents();
+        assert!(x.start <= x.end && x.end <= max_x);
+        assert!(y.start <= y.end && y.end <= max_y);
+        assert!(z.start <= z.end && z.end <= max_z);
 
         let width = x.end - x.start;
         let height = y.end - y.start;
@@ -850,14 +1681,15 @@ impl<'a> TextureAnyMipmap<'a> {
 
         let mut ctxt = self.texture.context.make_current();
 
+        // the rows are tightly packed in the buffer
+        ops::set_unpack_tightly_packed(&mut ctxt);
+
         // binds the pixel buffer
         source.prepare_and_bind_for_pixel_unpack(&mut ctxt);
 
         match self.texture.ty {
             Dimensions::Texture1d { .. } => {
-                if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                   ctxt.extensions.gl_arb_direct_state_access
-                {
+                if ctxt.capabilities.direct_state_access {
                     unsafe {
                         ctxt.gl.TextureSubImage1D(self.texture.id,
                                                   self.level as gl::types::GLint,
@@ -878,7 +1710,7 @@ impl<'a> TextureAnyMipmap<'a> {
                     }
 
                 } else {
-                    self.texture.bind_to_current(&mut ctxt);
+                    self.texture.bind_to_current_for_edit(&mut ctxt);
                     unsafe {
                         ctxt.gl.TexSubImage1D(self.texture.get_bind_point(),
                                               self.level as gl::types::GLint,
@@ -893,9 +1725,7 @@ impl<'a> TextureAnyMipmap<'a> {
             Dimensions::Texture1dArray { .. } | Dimensions::Texture2d { .. } |
             Dimensions::Texture2dMultisample { .. } |
             Dimensions::Texture2dMultisampleArray { .. } => {
-                if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                   ctxt.extensions.gl_arb_direct_state_access
-                {
+                if ctxt.capabilities.direct_state_access {
                     unsafe {
                         ctxt.gl.TextureSubImage2D(self.texture.id,
                                                   self.level as gl::types::GLint,
@@ -920,7 +1750,7 @@ impl<'a> TextureAnyMipmap<'a> {
                     }
 
                 } else {
-                    self.texture.bind_to_current(&mut ctxt);
+                    self.texture.bind_to_current_for_edit(&mut ctxt);
                     unsafe {
                         ctxt.gl.TexSubImage2D(self.texture.get_bind_point(),
                                               self.level as gl::types::GLint,
@@ -935,9 +1765,7 @@ impl<'a> TextureAnyMipmap<'a> {
             },
 
             Dimensions::Texture2dArray { .. } | Dimensions::Texture3d { .. } => {
-                if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                   ctxt.extensions.gl_arb_direct_state_access
-                {
+                if ctxt.capabilities.direct_state_access {
                     unsafe {
                         ctxt.gl.TextureSubImage3D(self.texture.id,
                                                   self.level as gl::types::GLint,
@@ -966,7 +1794,7 @@ impl<'a> TextureAnyMipmap<'a> {
                     }
 
                 } else {
-                    self.texture.bind_to_current(&mut ctxt);
+                    self.texture.bind_to_current_for_edit(&mut ctxt);
                     unsafe {
                         ctxt.gl.TexSubImage3D(self.texture.get_bind_point(),
                                               self.level as gl::types::GLint,
@@ -992,6 +1820,252 @@ impl<'a> TextureAnyMipmap<'a> {
             fence.insert(&mut ctxt);
         }
     }
+
+    /// Uploads data to a rectangle of the texture, one row at a time.
+    ///
+    /// The first row produced by `rows` is written at the bottom of the rectangle. Each row
+    /// must contain exactly `rect.width` pixels, and `rows` must produce at least
+    /// `rect.height` rows. The other rows are ignored.
+    ///
+    /// Contrary to `write`, the rows are streamed through a pixel unpack buffer of at most
+    /// `TRANSFER_CHUNK_SIZE` bytes, and `glTexSubImage2D` is called once per chunk of rows. This
+    /// means that the whole image is never held in memory at once.
+    ///
+    /// If an error is returned, the rows that have been produced before the error have already
+    /// been uploaded.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the rectangle is outside the boundaries of the texture, or if the texture is
+    /// not a two-dimensional texture.
+    pub fn write_rows<P, I, R>(&self, rect: Rect, rows: I) -> Result<(), WriteRowsError>
+                               where P: PixelValue, I: IntoIterator<Item = R>, R: AsRef<[P]>
+    {
+        match self.texture.ty {
+            Dimensions::Texture2d { .. } | Dimensions::Texture1dArray { .. } => (),
+            _ => panic!("write_rows can only be used with two-dimensional textures"),
+        };
+
+        assert!(rect.left >= 0 && rect.bottom >= 0, "The rectangle is out of bounds");
+        assert!(rect.left as u32 + rect.width <= self.width);
+        assert!(rect.bottom as u32 + rect.height <= self.height.unwrap_or(1));
+
+        if rect.width == 0 || rect.height == 0 {
+            return Ok(());
+        }
+
+        let x = rect.left as u32 .. rect.left as u32 + rect.width;
+        let row_len = rect.width as usize;
+        let chunk_rows = cmp::max(1, TRANSFER_CHUNK_SIZE / (row_len * mem::size_of::<P>()));
+        let chunk_rows = cmp::min(chunk_rows, rect.height as usize);
+
+        let mut buffer: Buffer<[P]> = match Buffer::empty_array(&self.texture.context,
+                                                                BufferType::PixelUnpackBuffer,
+                                                                chunk_rows * row_len,
+                                                                BufferMode::Dynamic)
+        {
+            Ok(b) => b,
+            Err(err) => return Err(WriteRowsError::BufferCreationError(err)),
+        };
+
+        let mut rows = rows.into_iter();
+        let mut y = rect.bottom as u32;
+        let y_end = rect.bottom as u32 + rect.height;
+
+        while y < y_end {
+            let num_rows = cmp::min(chunk_rows as u32, y_end - y);
+
+            let first_row = y - rect.bottom as u32;
+            if buffer.is_mapping_supported() {
+                let mut mapping = buffer.map_write();
+                try!(fill_rows(&mut rows, first_row .. first_row + num_rows, rect.height, row_len,
+                               |offset, row| mapping.set_range(offset, row)));
+            } else {
+                try!(fill_rows(&mut rows, first_row .. first_row + num_rows, rect.height, row_len,
+                               |offset, row| {
+                                   buffer.slice(offset .. offset + row_len).unwrap().write(row)
+                               }));
+            }
+
+            let source = buffer.slice(0 .. num_rows as usize * row_len).unwrap();
+            self.raw_upload_from_pixel_buffer(source, x.clone(), y .. y + num_rows, 0 .. 1);
+            y += num_rows;
+        }
+
+        Ok(())
+    }
+
+    /// Starts uploading `data` to this mipmap level incrementally.
+    ///
+    /// `data` must contain the whole level, row by row starting with the bottom row, and layer
+    /// by layer for array textures and 3D textures.
+    ///
+    /// Nothing is uploaded by this function. Call `advance` on the returned object regularly,
+    /// for example once per frame, until it returns `StreamingWriteStatus::Done`. This spreads
+    /// the upload of a large texture over several frames instead of blocking the thread of the
+    /// context.
+    ///
+    /// Until the upload is done, `is_complete` returns false and sampling the texture returns
+    /// `DrawError::IncompleteTexture`. If the `StreamingWrite` is dropped before the end, the
+    /// texture stays incomplete and the part that hasn't been uploaded is undefined.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` doesn't match the dimensions of the level, or if the
+    /// texture is a cubemap or a multisample texture.
+    pub fn begin_streaming_write<P>(&self, data: Vec<P>)
+                                    -> Result<StreamingWrite<'a, P>, BufferCreationError>
+                                    where P: PixelValue
+    {
+        match self.texture.ty {
+            Dimensions::Cubemap { .. } | Dimensions::CubemapArray { .. } |
+            Dimensions::Texture2dMultisample { .. } |
+            Dimensions::Texture2dMultisampleArray { .. } => {
+                panic!("Streaming writes are not supported by cubemaps and multisample textures");
+            },
+            _ => ()
+        };
+
+        let (width, rows, slices) = self.get_upload_extents();
+        assert_eq!(data.len(), width as usize * rows as usize * slices as usize);
+
+        // the staging buffer holds one chunk of rows
+        let chunk_rows = cmp::max(1, TRANSFER_CHUNK_SIZE / (width as usize * mem::size_of::<P>()));
+        let chunk_rows = cmp::min(chunk_rows, rows as usize * slices as usize) as u32;
+
+        let buffer = try!(Buffer::empty_array(&self.texture.context,
+                                              BufferType::PixelUnpackBuffer,
+                                              chunk_rows as usize * width as usize,
+                                              BufferMode::Dynamic));
+
+        self.texture.complete.set(false);
+
+        Ok(StreamingWrite {
+            mipmap: *self,
+            data: data,
+            buffer: buffer,
+            chunk_rows: chunk_rows,
+            next_row: 0,
+        })
+    }
+}
+
+/// Incremental upload of data to a mipmap level of a texture. Built with
+/// `TextureAnyMipmap::begin_streaming_write`.
+pub struct StreamingWrite<'a, P> where P: PixelValue {
+    mipmap: TextureAnyMipmap<'a>,
+    data: Vec<P>,
+
+    /// Pixel unpack buffer that holds a chunk of rows.
+    buffer: Buffer<[P]>,
+
+    /// Number of rows that fit in `buffer`.
+    chunk_rows: u32,
+
+    /// Index of the next row to upload. The rows of all the slices are counted.
+    next_row: u32,
+}
+
+impl<'a, P> StreamingWrite<'a, P> where P: PixelValue {
+    /// Uploads chunks of rows until `budget` has elapsed or until all the data has been
+    /// uploaded.
+    ///
+    /// Each chunk is at most `TRANSFER_CHUNK_SIZE` bytes, or a single row if a row is larger.
+    /// At least one chunk is uploaded by each call, so a call can take a bit longer than
+    /// `budget`. Once `Done` has been returned, further calls do nothing.
+    pub fn advance(&mut self, budget: Duration) -> StreamingWriteStatus {
+        let start_time = Instant::now();
+        let (width, rows, slices) = self.mipmap.get_upload_extents();
+        let total_rows = rows * slices;
+
+        while self.next_row < total_rows {
+            let y = self.next_row % rows;
+            let z = self.next_row / rows;
+
+            // uploading whole slices at once if they fit in the staging buffer
+            let (y_range, z_range) = if y == 0 && self.chunk_rows >= rows {
+                let num_slices = cmp::min(self.chunk_rows / rows, slices - z);
+                (0 .. rows, z .. z + num_slices)
+            } else {
+                let num_rows = cmp::min(self.chunk_rows, rows - y);
+                (y .. y + num_rows, z .. z + 1)
+            };
+
+            let num_rows = (y_range.end - y_range.start) * (z_range.end - z_range.start);
+            let start = self.next_row as usize * width as usize;
+            let end = start + num_rows as usize * width as usize;
+
+            let source = self.buffer.slice(0 .. end - start).unwrap();
+            source.write(&self.data[start .. end]);
+            self.mipmap.raw_upload_from_pixel_buffer(source, 0 .. width, y_range, z_range);
+            self.next_row += num_rows;
+
+            if self.next_row == total_rows {
+                self.mipmap.texture.complete.set(true);
+                if self.mipmap.level == 0 {
+                    self.mipmap.texture.mark_mipmaps_dirty();
+                }
+                break;
+            }
+
+            if start_time.elapsed() >= budget {
+                break;
+            }
+        }
+
+        self.get_status()
+    }
+
+    /// Returns whether all the data has been uploaded.
+    #[inline]
+    pub fn get_status(&self) -> StreamingWriteStatus {
+        let (_, rows, slices) = self.mipmap.get_upload_extents();
+
+        if self.next_row == rows * slices {
+            StreamingWriteStatus::Done
+        } else {
+            StreamingWriteStatus::InProgress
+        }
+    }
+
+    /// Returns the number of bytes that have been uploaded and the total number of bytes.
+    #[inline]
+    pub fn get_progress(&self) -> (usize, usize) {
+        let row_bytes = self.mipmap.width as usize * mem::size_of::<P>();
+        let total_bytes = self.data.len() * mem::size_of::<P>();
+        (self.next_row as usize * row_bytes, total_bytes)
+    }
+}
+
+/// Calls `write` with the offset in the chunk and the content of each row of `range`.
+///
+/// Checks that the iterator produces enough rows and that they have the right length.
+fn fill_rows<P, I, R, W>(rows: &mut I, range: Range<u32>, total_rows: u32, row_len: usize,
+                         mut write: W) -> Result<(), WriteRowsError>
+                         where I: Iterator<Item = R>, R: AsRef<[P]>, W: FnMut(usize, &[P])
+{
+    for row_index in range.clone() {
+        let row = match rows.next() {
+            Some(row) => row,
+            None => return Err(WriteRowsError::NotEnoughRows {
+                expected: total_rows,
+                provided: row_index,
+            }),
+        };
+
+        let row = row.as_ref();
+        if row.len() != row_len {
+            return Err(WriteRowsError::RowLengthMismatch {
+                row: row_index,
+                expected: row_len,
+                provided: row.len(),
+            });
+        }
+
+        write((row_index - range.start) as usize * row_len, row);
+    }
+
+    Ok(())
 }
 
 impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
@@ -1031,13 +2105,9 @@ impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
         let mut ctxt = self.texture.context.make_current();
 
         unsafe {
-            if ctxt.state.pixel_store_unpack_alignment != 1 {
-                ctxt.state.pixel_store_unpack_alignment = 1;
-                ctxt.gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-            }
-
+            ops::set_unpack_tightly_packed(&mut ctxt);
             BufferAny::unbind_pixel_unpack(&mut ctxt);
-            let bind_point = self.texture.bind_to_current(&mut ctxt);
+            let bind_point = self.texture.get_bind_point();
 
             if bind_point == gl::TEXTURE_3D || bind_point == gl::TEXTURE_2D_ARRAY {
                 unimplemented!();
@@ -1045,7 +2115,28 @@ impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
             } else if bind_point == gl::TEXTURE_2D || bind_point == gl::TEXTURE_1D_ARRAY {
                 assert!(z_offset == 0);
                 // FIXME should glTexImage be used here somewhere or glTexSubImage does it just fine?
-                if is_client_compressed {
+                if ctxt.capabilities.direct_state_access {
+                    if is_client_compressed {
+                        ctxt.gl.CompressedTextureSubImage2D(id, level as gl::types::GLint,
+                                                            x_offset as gl::types::GLint,
+                                                            y_offset as gl::types::GLint,
+                                                            width as gl::types::GLsizei,
+                                                            height.unwrap_or(1) as gl::types::GLsizei,
+                                                            client_format,
+                                                            data_bufsize as gl::types::GLsizei,
+                                                            data.as_ptr() as *const libc::c_void);
+                    } else {
+                        ctxt.gl.TextureSubImage2D(id, level as gl::types::GLint,
+                                                  x_offset as gl::types::GLint,
+                                                  y_offset as gl::types::GLint,
+                                                  width as gl::types::GLsizei,
+                                                  height.unwrap_or(1) as gl::types::GLsizei,
+                                                  client_format, client_type,
+                                                  data.as_ptr() as *const libc::c_void);
+                    }
+
+                } else if is_client_compressed {
+                    self.texture.bind_to_current_for_edit(&mut ctxt);
                     ctxt.gl.CompressedTexSubImage2D(bind_point, level as gl::types::GLint,
                                                     x_offset as gl::types::GLint,
                                                     y_offset as gl::types::GLint,
@@ -1055,6 +2146,7 @@ impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
                                                     data_bufsize  as gl::types::GLsizei,
                                                     data.as_ptr() as *const libc::c_void);
                 } else {
+                    self.texture.bind_to_current_for_edit(&mut ctxt);
                     ctxt.gl.TexSubImage2D(bind_point, level as gl::types::GLint,
                                           x_offset as gl::types::GLint,
                                           y_offset as gl::types::GLint,
@@ -1073,11 +2165,17 @@ impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
 
             // regenerate mipmaps if there are some
             if regen_mipmaps {
-                if ctxt.version >= &Version(Api::Gl, 3, 0) {
+                if ctxt.capabilities.direct_state_access {
+                    ctxt.gl.GenerateTextureMipmap(id);
+                } else if ctxt.version >= &Version(Api::Gl, 3, 0) {
+                    self.texture.bind_to_current_for_edit(&mut ctxt);
                     ctxt.gl.GenerateMipmap(bind_point);
                 } else {
+                    self.texture.bind_to_current_for_edit(&mut ctxt);
                     ctxt.gl.GenerateMipmapEXT(bind_point);
                 }
+
+                self.texture.mipmaps_dirty.set(false);
             }
 
             Ok(())
@@ -1093,47 +2191,175 @@ impl<'t> TextureMipmapExt for TextureAnyMipmap<'t> {
         unsafe {
             let bind_point = texture.bind_to_current(&mut ctxt);
 
-            let mut is_compressed = mem::uninitialized();
-            ctxt.gl.GetTexLevelParameteriv(bind_point, level, gl::TEXTURE_COMPRESSED, &mut is_compressed);
-            if is_compressed != 0 {
-
-                let mut buffer_size = mem::uninitialized();
-                ctxt.gl.GetTexLevelParameteriv(bind_point, level, gl::TEXTURE_COMPRESSED_IMAGE_SIZE, &mut buffer_size);
-                let mut internal_format = mem::uninitialized();
-                ctxt.gl.GetTexLevelParameteriv(bind_point, level, gl::TEXTURE_INTERNAL_FORMAT, &mut internal_format);
-
-                match ClientFormatAny::from_internal_compressed_format(internal_format as gl::types::GLenum) {
-                    Some(known_format) => {
-                        let mut buf = Vec::with_capacity(buffer_size as usize);
-                        buf.set_len(buffer_size as usize);
-
-                        BufferAny::unbind_pixel_pack(&mut ctxt);
-
-                        // adjusting data alignement
-                        let ptr = buf.as_ptr() as *const u8;
-                        let ptr = ptr as usize;
-                        if (ptr % 8) == 0 {
-                        } else if (ptr % 4) == 0 && ctxt.state.pixel_store_pack_alignment != 4 {
-                            ctxt.state.pixel_store_pack_alignment = 4;
-                            ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
-                        } else if (ptr % 2) == 0 && ctxt.state.pixel_store_pack_alignment > 2 {
-                            ctxt.state.pixel_store_pack_alignment = 2;
-                            ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 2);
-                        } else if ctxt.state.pixel_store_pack_alignment != 1 {
-                            ctxt.state.pixel_store_pack_alignment = 1;
-                            ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
-                        }
-
-                        ctxt.gl.GetCompressedTexImage(bind_point, level, buf.as_mut_ptr() as *mut _);
-                        Some((known_format, buf))
+            match get_compressed_format(&mut ctxt, bind_point, level) {
+                Some(known_format) => {
+                    let buf = download_compressed_image(&mut ctxt, bind_point, level);
+                    Some((known_format, buf))
+                },
+                None => None,
+            }
+        }
+    }
+
+    fn download_compressed_level(&self) -> Result<(ClientFormatAny, Vec<u8>),
+                                                  CompressedLevelError>
+    {
+        let texture = self.texture;
+        let level = self.level as i32;
+
+        let mut ctxt = texture.context.make_current();
+
+        // `glGetCompressedTexImage` doesn't exist in OpenGL ES
+        if ctxt.version.0 == Api::GlEs {
+            return Err(CompressedLevelError::NotSupported);
+        }
+
+        unsafe {
+            let bind_point = texture.bind_to_current(&mut ctxt);
+
+            let format = match get_compressed_format(&mut ctxt, bind_point, level) {
+                Some(format) => format,
+                None => return Err(CompressedLevelError::UnknownFormat),
+            };
+
+            try!(check_block_alignment(format, self.width, self.height.unwrap_or(1)));
+
+            let buf = download_compressed_image(&mut ctxt, bind_point, level);
+            Ok((format, buf))
+        }
+    }
+
+    fn upload_compressed_level(&self, format: ClientFormatAny, data: &[u8])
+                               -> Result<(), CompressedLevelError>
+    {
+        let width = self.width;
+        let height = self.height.unwrap_or(1);
+
+        let actual_format = {
+            let mut ctxt = self.texture.context.make_current();
+
+            // OpenGL ES can't query the format of a level, so we rely on the requested format
+            if ctxt.version.0 == Api::GlEs {
+                match self.texture.requested_format {
+                    TextureFormatRequest::Specific(TextureFormat::CompressedFormat(f)) => {
+                        Some(ClientFormatAny::CompressedFormat(f))
+                    },
+                    TextureFormatRequest::Specific(TextureFormat::CompressedSrgbFormat(f)) => {
+                        Some(ClientFormatAny::CompressedSrgbFormat(f))
                     },
-                    None => None,
+                    _ => None,
                 }
 
             } else {
-                None
+                unsafe {
+                    let bind_point = self.texture.bind_to_current(&mut ctxt);
+                    get_compressed_format(&mut ctxt, bind_point, self.level as i32)
+                }
             }
+        };
+
+        match actual_format {
+            Some(f) if f == format => (),
+            Some(_) => return Err(CompressedLevelError::FormatMismatch),
+            None => return Err(CompressedLevelError::UnknownFormat),
+        };
+
+        try!(check_block_alignment(format, width, height));
+
+        let expected = format.get_buffer_size(width, Some(height), None, None);
+        if data.len() != expected {
+            return Err(CompressedLevelError::DataLengthMismatch {
+                expected: expected,
+                provided: data.len(),
+            });
         }
+
+        self.upload_texture(0, 0, 0, (format, Cow::Borrowed(data)), width, Some(height), None,
+                            false).map_err(|_| CompressedLevelError::NotSupported)
+    }
+
+    unsafe fn copy_from_read_framebuffer(&self, ctxt: &mut CommandContext, rect: &Rect,
+                                         (x_offset, y_offset): (u32, u32))
+    {
+        let level = self.level as gl::types::GLint;
+
+        if ctxt.capabilities.direct_state_access {
+            ctxt.gl.CopyTextureSubImage2D(self.texture.id, level,
+                                          x_offset as gl::types::GLint,
+                                          y_offset as gl::types::GLint,
+                                          rect.left as gl::types::GLint,
+                                          rect.bottom as gl::types::GLint,
+                                          rect.width as gl::types::GLsizei,
+                                          rect.height as gl::types::GLsizei);
+        } else {
+            let bind_point = self.texture.bind_to_current_for_edit(ctxt);
+            ctxt.gl.CopyTexSubImage2D(bind_point, level,
+                                      x_offset as gl::types::GLint,
+                                      y_offset as gl::types::GLint,
+                                      rect.left as gl::types::GLint,
+                                      rect.bottom as gl::types::GLint,
+                                      rect.width as gl::types::GLsizei,
+                                      rect.height as gl::types::GLsizei);
+        }
+
+        if self.level == 0 {
+            self.texture.mark_mipmaps_dirty();
+        }
+    }
+}
+
+/// Returns the format of a mipmap level of the texture bound to `bind_point`, or `None` if the
+/// level isn't compressed or if its format is generic or unknown.
+unsafe fn get_compressed_format(ctxt: &mut CommandContext, bind_point: gl::types::GLenum,
+                                level: gl::types::GLint) -> Option<ClientFormatAny>
+{
+    let mut is_compressed = mem::uninitialized();
+    ctxt.gl.GetTexLevelParameteriv(bind_point, level, gl::TEXTURE_COMPRESSED, &mut is_compressed);
+    if is_compressed == 0 {
+        return None;
+    }
+
+    let mut internal_format = mem::uninitialized();
+    ctxt.gl.GetTexLevelParameteriv(bind_point, level, gl::TEXTURE_INTERNAL_FORMAT,
+                                   &mut internal_format);
+    ClientFormatAny::from_internal_compressed_format(internal_format as gl::types::GLenum)
+}
+
+/// Reads the compressed data of a mipmap level of the texture bound to `bind_point`. The size of
+/// the data is given by `GL_TEXTURE_COMPRESSED_IMAGE_SIZE`.
+unsafe fn download_compressed_image(ctxt: &mut CommandContext, bind_point: gl::types::GLenum,
+                                    level: gl::types::GLint) -> Vec<u8>
+{
+    let mut buffer_size = mem::uninitialized();
+    ctxt.gl.GetTexLevelParameteriv(bind_point, level, gl::TEXTURE_COMPRESSED_IMAGE_SIZE,
+                                   &mut buffer_size);
+
+    let mut buf = Vec::with_capacity(buffer_size as usize);
+    buf.set_len(buffer_size as usize);
+
+    BufferAny::unbind_pixel_pack(ctxt);
+    ops::set_pack_tightly_packed(ctxt);
+
+    ctxt.gl.GetCompressedTexImage(bind_point, level, buf.as_mut_ptr() as *mut _);
+    buf
+}
+
+/// Checks that the dimensions of a mipmap level are a multiple of the dimensions of the blocks
+/// of the format. The last levels of a mipmaps chain can be smaller than a block.
+fn check_block_alignment(format: ClientFormatAny, width: u32, height: u32)
+                         -> Result<(), CompressedLevelError>
+{
+    let (block_width, block_height) = format.get_block_dimensions();
+
+    if (width < block_width || width % block_width == 0) &&
+       (height < block_height || height % block_height == 0)
+    {
+        Ok(())
+    } else {
+        Err(CompressedLevelError::UnalignedDimensions {
+            dimensions: (width, height),
+            block: (block_width, block_height),
+        })
     }
 }
 
@@ -1282,35 +2508,143 @@ impl<'a> TextureAnyImage<'a> {
 
     /// Reads the content of the image.
     ///
-    /// # Panic
+    /// The rectangle is clipped to the dimensions of the image. The result has the dimensions
+    /// of the part that has actually been read.
+    pub fn raw_read<T>(&self, rect: &Rect) -> Result<T, ops::ReadError>
+                       where T: Texture2dDataSink<(u8, u8, u8, u8)>
+    {
+        self.raw_read_impl(rect, false)
+    }
+
+    /// Reads the content of the image, with the rows in top-to-bottom order.
+    ///
+    /// The rectangle is still expressed with OpenGL's coordinates, where `(0, 0)` is the
+    /// bottom-left hand corner of the image. Only the order of the rows of the result is
+    /// reversed.
+    ///
+    /// The rectangle is clipped to the dimensions of the image, like with `raw_read`.
+    pub fn raw_read_flipped<T>(&self, rect: &Rect) -> Result<T, ops::ReadError>
+                               where T: Texture2dDataSink<(u8, u8, u8, u8)>
+    {
+        self.raw_read_impl(rect, true)
+    }
+
+    /// Reads the content of the image as floating-point values.
+    ///
+    /// The values are not clamped, which means that you can read values outside of the
+    /// `[0.0, 1.0]` range from floating-point textures.
+    ///
+    /// The rectangle is clipped to the dimensions of the image, like with `raw_read`.
     ///
-    /// Panicks if the rect is out of range.
+    /// Returns an error if the backend doesn't support reading floating-point values (OpenGL ES
+    /// only supports reading `(u8, u8, u8, u8)`).
+    pub fn raw_read_float<T>(&self, rect: &Rect) -> Result<T, ops::ReadError>
+                             where T: Texture2dDataSink<(f32, f32, f32, f32)>
+    {
+        self.raw_read_impl(rect, false)
+    }
+
+    /// Reads the content of the image as 16-bits unsigned normalized values.
+    ///
+    /// Contrary to `raw_read_float`, the values are not converted to floating-point, which
+    /// means that reading a 16-bits normalized texture doesn't lose any precision.
     ///
-    pub fn raw_read<T>(&self, rect: &Rect) -> T where T: Texture2dDataSink<(u8, u8, u8, u8)> {
-        assert!(rect.left + rect.width <= self.width);
-        assert!(rect.bottom + rect.height <= self.height.unwrap_or(1));
+    /// The rectangle is clipped to the dimensions of the image, like with `raw_read`.
+    ///
+    /// Returns an error if the backend doesn't support reading 16-bits values (OpenGL ES only
+    /// supports it with the `GL_EXT_texture_norm16` extension).
+    pub fn raw_read_u16<T>(&self, rect: &Rect) -> Result<T, ops::ReadError>
+                           where T: Texture2dDataSink<(u16, u16, u16, u16)>
+    {
+        self.raw_read_impl(rect, false)
+    }
 
+    /// Reads the content of an image that contains signed integers.
+    ///
+    /// The rectangle is clipped to the dimensions of the image, like with `raw_read`.
+    ///
+    /// Returns an error if the backend doesn't support reading integers (OpenGL ES only
+    /// supports reading `(u8, u8, u8, u8)`).
+    pub fn raw_read_integer<T>(&self, rect: &Rect) -> Result<T, ops::ReadError>
+                               where T: Texture2dDataSink<(i32, i32, i32, i32)>
+    {
+        self.raw_read_impl(rect, false)
+    }
+
+    /// Reads the content of an image that contains unsigned integers.
+    ///
+    /// The rectangle is clipped to the dimensions of the image, like with `raw_read`.
+    ///
+    /// Returns an error if the backend doesn't support reading integers (OpenGL ES only
+    /// supports reading `(u8, u8, u8, u8)`).
+    pub fn raw_read_unsigned<T>(&self, rect: &Rect) -> Result<T, ops::ReadError>
+                                where T: Texture2dDataSink<(u32, u32, u32, u32)>
+    {
+        self.raw_read_impl(rect, false)
+    }
+
+    fn raw_read_impl<T, P>(&self, rect: &Rect, flip: bool) -> Result<T, ops::ReadError>
+                           where T: Texture2dDataSink<P>, P: PixelValue
+    {
         let mut ctxt = self.texture.context.make_current();
 
         let mut data = Vec::new();
-        ops::read(&mut ctxt, &fbo::RegularAttachment::Texture(*self), &rect, &mut data, false);
-        T::from_raw(Cow::Owned(data), self.width, self.height.unwrap_or(1))
+        let rect = try!(ops::read(&mut ctxt, &fbo::RegularAttachment::Texture(*self), &rect,
+                                  &mut data, false));
+
+        if flip {
+            ops::flip_rows(&mut data, rect.width as usize, rect.height as usize);
+        }
+
+        Ok(T::from_raw(Cow::Owned(data), rect.width, rect.height))
+    }
+
+    /// Reads the content of the image into an existing slice, without allocating.
+    ///
+    /// Contrary to `raw_read`, the rectangle is not clipped. An error is returned if it is not
+    /// entirely inside of the image, or if the length of `dest` is not exactly
+    /// `rect.width * rect.height * format.get_size()`.
+    pub fn raw_read_into(&self, rect: &Rect, dest: &mut [u8], format: ClientFormat)
+                         -> Result<(), ops::ReadError>
+    {
+        self.raw_read_into_impl(rect, dest, format, false)
+    }
+
+    /// Same as `raw_read_into`, but with the rows in top-to-bottom order.
+    pub fn raw_read_into_flipped(&self, rect: &Rect, dest: &mut [u8], format: ClientFormat)
+                                 -> Result<(), ops::ReadError>
+    {
+        self.raw_read_into_impl(rect, dest, format, true)
+    }
+
+    fn raw_read_into_impl(&self, rect: &Rect, dest: &mut [u8], format: ClientFormat, flip: bool)
+                          -> Result<(), ops::ReadError>
+    {
+        let mut ctxt = self.texture.context.make_current();
+
+        try!(ops::read::<_, _, u8>(&mut ctxt, &fbo::RegularAttachment::Texture(*self), rect,
+                                   ops::Destination::Bytes(&mut *dest, format), false));
+
+        if flip {
+            ops::flip_rows(dest, rect.width as usize * format.get_size(), rect.height as usize);
+        }
+
+        Ok(())
     }
 
     /// Reads the content of the image to a pixel buffer.
     ///
+    /// The rectangle is clipped to the dimensions of the image. Returns the rectangle that has
+    /// actually been read.
+    ///
     /// # Panic
     ///
-    /// - Panicks if the rect is out of range.
-    /// - Panicks if the buffer is not large enough.
+    /// Panicks if the buffer is not large enough.
     ///
-    pub fn raw_read_to_pixel_buffer(&self, rect: &Rect, dest: &PixelBuffer<(u8, u8, u8, u8)>) {
-        assert!(rect.left + rect.width <= self.width);
-        assert!(rect.bottom + rect.height <= self.height.unwrap_or(1));
-        assert!(dest.len() >= rect.width as usize * rect.height as usize);
-
-        let size = rect.width as usize * rect.height as usize * 4;
+    pub fn raw_read_to_pixel_buffer(&self, rect: &Rect, dest: &PixelBuffer<(u8, u8, u8, u8)>)
+                                    -> Rect
+    {
         let mut ctxt = self.texture.context.make_current();
-        ops::read(&mut ctxt, &fbo::RegularAttachment::Texture(*self), &rect, dest, false);
+        ops::read(&mut ctxt, &fbo::RegularAttachment::Texture(*self), &rect, dest, false).unwrap()
     }
 }
@@ -0,0 +1,378 @@
+/*!
+A texture atlas packs many small images, like glyphs or sprites, in a single `Texture2d`.
+
+```no_run
+# let display: glium::Display = unsafe { std::mem::uninitialized() };
+# let image: glium::texture::RawImage2d<u8> = unsafe { std::mem::uninitialized() };
+use glium::texture::{Atlas, UncompressedFloatFormat};
+
+let mut atlas = Atlas::new(&display, UncompressedFloatFormat::U8U8U8U8, 512, 512, 1).unwrap();
+
+let region = atlas.allocate(image.width, image.height).expect("the atlas is full");
+atlas.upload(&region, image);
+
+// the texture coordinates to use when drawing the image, as `[left, bottom, right, top]`
+let uv = atlas.get_uv_rect(&region);
+```
+
+The images are packed with a shelf packer: they are placed left to right in horizontal rows
+called shelves, and a new shelf is opened on top of the previous one when an image doesn't fit
+in any of the existing shelves. This works best when the images have similar heights.
+
+# Padding
+
+With linear filtering, sampling near the border of an image also reads the pixels next to it.
+To avoid colors bleeding from one image to another, the atlas leaves `padding` pixels between
+the images and between the images and the bottom-left border of the texture. These pixels are
+cleared to `(0.0, 0.0, 0.0, 0.0)`. A padding of `1` is enough without mipmaps.
+
+# Growth and generations
+
+`grow` replaces the texture with a bigger one and copies the content of the old texture. The
+regions that have been allocated stay valid, but their texture coordinates change.
+
+`clear` forgets all the regions. Using a region that was allocated before the call to `clear`
+panics.
+
+Both operations increase the value returned by `get_generation`. If you cache texture
+coordinates, store the generation along with them and compute them again when it changes.
+
+*/
+use backend::Facade;
+use uniforms::MagnifySamplerFilter;
+
+use BlitTarget;
+use Rect;
+use Surface;
+
+use texture::{Texture2d, Texture2dDataSource, TextureCreationError, MipmapsOption};
+use texture::UncompressedFloatFormat;
+
+/// A texture in which images are packed.
+///
+/// See the documentation of the `atlas` module.
+pub struct Atlas {
+    texture: Texture2d,
+    format: UncompressedFloatFormat,
+    packer: ShelfPacker,
+    generation: u32,
+    epoch: u32,
+}
+
+/// A rectangle of an `Atlas` that has been allocated with `Atlas::allocate`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AtlasRegion {
+    /// Position and dimensions of the region in the texture, in pixels. The padding is not
+    /// included.
+    pub rect: Rect,
+
+    // value of `Atlas::epoch` when the region was allocated
+    epoch: u32,
+}
+
+impl Atlas {
+    /// Builds a new empty atlas.
+    ///
+    /// `padding` is the number of pixels left between the images.
+    pub fn new<F>(facade: &F, format: UncompressedFloatFormat, width: u32, height: u32,
+                  padding: u32) -> Result<Atlas, TextureCreationError> where F: Facade
+    {
+        let texture = try!(build_texture(facade, format, width, height));
+
+        Ok(Atlas {
+            texture: texture,
+            format: format,
+            packer: ShelfPacker::new(width, height, padding),
+            generation: 0,
+            epoch: 0,
+        })
+    }
+
+    /// Returns the texture that contains the images.
+    #[inline]
+    pub fn get_texture(&self) -> &Texture2d {
+        &self.texture
+    }
+
+    /// Returns the width and height of the texture.
+    #[inline]
+    pub fn get_dimensions(&self) -> (u32, u32) {
+        (self.packer.width, self.packer.height)
+    }
+
+    /// Returns a number that is increased every time `grow` or `clear` is called.
+    ///
+    /// The texture coordinates returned by `get_uv_rect` are only valid for the current
+    /// generation.
+    #[inline]
+    pub fn get_generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Reserves a rectangle of `width` by `height` pixels.
+    ///
+    /// Returns `None` if there is not enough room in the texture. In this situation you can
+    /// call `grow` and try again.
+    #[inline]
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasRegion> {
+        self.packer.allocate(width, height).map(|rect| {
+            AtlasRegion {
+                rect: rect,
+                epoch: self.epoch,
+            }
+        })
+    }
+
+    /// Returns true if the region has been allocated since the last call to `clear`.
+    #[inline]
+    pub fn is_valid(&self, region: &AtlasRegion) -> bool {
+        region.epoch == self.epoch
+    }
+
+    /// Uploads an image in a region of the atlas.
+    ///
+    /// ## Panic
+    ///
+    /// Panics if the region has been allocated before the last call to `clear`, or if the
+    /// dimensions of the image don't match the dimensions of the region.
+    pub fn upload<'a, T>(&self, region: &AtlasRegion, data: T) where T: Texture2dDataSource<'a> {
+        assert!(self.is_valid(region), "The region has been allocated before `Atlas::clear`");
+
+        let image = data.into_raw();
+        assert_eq!((image.width, image.height), (region.rect.width, region.rect.height));
+
+        if image.width == 0 || image.height == 0 {
+            return;
+        }
+
+        self.texture.write(region.rect, image);
+    }
+
+    /// Returns the texture coordinates of a region, as `[left, bottom, right, top]`.
+    ///
+    /// The coordinates change when the atlas grows.
+    #[inline]
+    pub fn get_uv_rect(&self, region: &AtlasRegion) -> [f32; 4] {
+        let (width, height) = (self.packer.width as f32, self.packer.height as f32);
+
+        [
+            region.rect.left as f32 / width,
+            region.rect.bottom as f32 / height,
+            (region.rect.left as u32 + region.rect.width) as f32 / width,
+            (region.rect.bottom as u32 + region.rect.height) as f32 / height,
+        ]
+    }
+
+    /// Forgets all the regions that have been allocated and clears the texture.
+    ///
+    /// The regions that have been allocated before can no longer be used.
+    pub fn clear(&mut self) {
+        self.packer.clear();
+        self.texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        self.epoch += 1;
+        self.generation += 1;
+    }
+
+    /// Replaces the texture with a bigger one and copies the content of the current texture.
+    ///
+    /// The regions stay valid and keep their position, but their texture coordinates change.
+    ///
+    /// ## Panic
+    ///
+    /// Panics if the new dimensions are smaller than the current ones.
+    pub fn grow<F>(&mut self, facade: &F, width: u32, height: u32)
+                   -> Result<(), TextureCreationError> where F: Facade
+    {
+        assert!(width >= self.packer.width && height >= self.packer.height,
+                "The dimensions of an atlas can't be reduced");
+
+        let texture = try!(build_texture(facade, self.format, width, height));
+
+        let target = BlitTarget {
+            left: 0,
+            bottom: 0,
+            width: self.packer.width as i32,
+            height: self.packer.height as i32,
+        };
+
+        self.texture.as_surface().blit_whole_color_to(&texture.as_surface(), &target,
+                                                      MagnifySamplerFilter::Nearest);
+
+        self.texture = texture;
+        self.packer.width = width;
+        self.packer.height = height;
+        self.generation += 1;
+        Ok(())
+    }
+}
+
+/// Builds a texture whose content is cleared to zero.
+fn build_texture<F>(facade: &F, format: UncompressedFloatFormat, width: u32, height: u32)
+                    -> Result<Texture2d, TextureCreationError> where F: Facade
+{
+    let texture = try!(Texture2d::empty_with_format(facade, format, MipmapsOption::NoMipmap,
+                                                    width, height));
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    Ok(texture)
+}
+
+/// A row of rectangles.
+#[derive(Debug, Copy, Clone)]
+struct Shelf {
+    /// Position of the bottom of the shelf.
+    bottom: u32,
+    /// Height of the tallest rectangle that fits in the shelf.
+    height: u32,
+    /// Position of the next rectangle of the shelf.
+    next_left: u32,
+}
+
+/// Shelf packing of rectangles.
+struct ShelfPacker {
+    width: u32,
+    height: u32,
+    padding: u32,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    #[inline]
+    fn new(width: u32, height: u32, padding: u32) -> ShelfPacker {
+        ShelfPacker {
+            width: width,
+            height: height,
+            padding: padding,
+            shelves: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.shelves.clear();
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<Rect> {
+        let padding = self.padding;
+        let atlas_width = self.width;
+
+        // choosing the existing shelf that wastes the least height
+        let best = self.shelves.iter().enumerate()
+                       .filter(|&(_, s)| height <= s.height &&
+                                         s.next_left as u64 + width as u64 <= atlas_width as u64)
+                       .min_by_key(|&(_, s)| s.height - height)
+                       .map(|(n, _)| n);
+
+        let index = match best {
+            Some(index) => index,
+            None => {
+                // opening a new shelf on top of the last one
+                let bottom = self.shelves.last().map(|s| s.bottom + s.height + padding)
+                                                .unwrap_or(padding);
+
+                if bottom as u64 + height as u64 > self.height as u64 ||
+                   padding as u64 + width as u64 > atlas_width as u64
+                {
+                    return None;
+                }
+
+                self.shelves.push(Shelf {
+                    bottom: bottom,
+                    height: height,
+                    next_left: padding,
+                });
+
+                self.shelves.len() - 1
+            },
+        };
+
+        let shelf = &mut self.shelves[index];
+        let rect = Rect {
+            left: shelf.next_left as i32,
+            bottom: shelf.bottom as i32,
+            width: width,
+            height: height,
+        };
+
+        shelf.next_left += width + padding;
+        Some(rect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Rect;
+    use super::ShelfPacker;
+
+    fn overlap(a: &Rect, b: &Rect) -> bool {
+        a.left < b.left + b.width as i32 && b.left < a.left + a.width as i32 &&
+        a.bottom < b.bottom + b.height as i32 && b.bottom < a.bottom + a.height as i32
+    }
+
+    #[test]
+    fn no_overlap() {
+        let mut packer = ShelfPacker::new(256, 256, 2);
+        let mut rects = Vec::new();
+
+        for n in 0 .. 64 {
+            if let Some(rect) = packer.allocate(5 + n % 13, 7 + n % 5) {
+                rects.push(rect);
+            }
+        }
+
+        assert!(rects.len() > 32);
+
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.left >= 2 && a.bottom >= 2);
+            assert!(a.left as u32 + a.width <= 256 && a.bottom as u32 + a.height <= 256);
+
+            // the padding must also be free
+            let padded = Rect { left: a.left - 2, bottom: a.bottom - 2,
+                                width: a.width + 4, height: a.height + 4 };
+
+            for b in rects[i + 1 ..].iter() {
+                assert!(!overlap(&padded, b));
+            }
+        }
+    }
+
+    #[test]
+    fn full() {
+        let mut packer = ShelfPacker::new(16, 16, 0);
+        let rect = Rect { left: 0, bottom: 0, width: 16, height: 16 };
+        assert_eq!(packer.allocate(16, 16), Some(rect));
+        assert_eq!(packer.allocate(1, 1), None);
+
+        packer.clear();
+        assert!(packer.allocate(17, 1).is_none());
+        assert!(packer.allocate(1, 17).is_none());
+        assert!(packer.allocate(8, 8).is_some());
+    }
+
+    #[test]
+    fn shelf_reuse() {
+        let mut packer = ShelfPacker::new(64, 64, 1);
+        let a = packer.allocate(10, 10).unwrap();
+        let b = packer.allocate(10, 20).unwrap();
+        let c = packer.allocate(10, 8).unwrap();
+
+        assert_eq!(a.bottom, 1);
+        assert_eq!(b.bottom, 12);
+        // the smaller rectangle goes in the first shelf
+        assert_eq!(c.bottom, 1);
+        assert_eq!(c.left, 12);
+    }
+
+    #[test]
+    fn grow() {
+        let mut packer = ShelfPacker::new(16, 16, 0);
+        assert!(packer.allocate(16, 16).is_some());
+        assert!(packer.allocate(16, 16).is_none());
+
+        packer.width = 32;
+        packer.height = 32;
+        let right = Rect { left: 16, bottom: 0, width: 16, height: 16 };
+        assert_eq!(packer.allocate(16, 16), Some(right));
+        let top = Rect { left: 0, bottom: 16, width: 16, height: 16 };
+        assert_eq!(packer.allocate(16, 16), Some(top));
+    }
+}
@@ -64,6 +64,8 @@ use GlObject;
 use ContextExt;
 use gl;
 
+use std::error::Error;
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
@@ -112,7 +114,7 @@ impl ResidentTexture {
         self.into_inner_impl()
     }
 
-    /// Implementation of `into_inner`. Also called by the destructor.
+    /// Implementation of `into_inner`.
     fn into_inner_impl(&mut self) -> TextureAny {
         let texture = self.texture.take().unwrap();
 
@@ -143,9 +145,16 @@ impl DerefMut for ResidentTexture {
 }
 
 impl Drop for ResidentTexture {
-    #[inline]
     fn drop(&mut self) {
-        self.into_inner_impl();
+        let texture = match self.texture.take() {
+            Some(texture) => texture,
+            None => return,     // `into_inner` has been called
+        };
+
+        if let Some(mut ctxt) = texture.get_context().make_current_for_drop() {
+            unsafe { ctxt.gl.MakeTextureHandleNonResidentARB(self.handle) };
+            ctxt.resident_texture_handles.retain(|&t| t != self.handle);
+        }
     }
 }
 
@@ -290,6 +299,18 @@ impl<'a> UniformBlock for TextureHandle<'a> {
 #[derive(Debug, Copy, Clone)]
 pub struct BindlessTexturesNotSupportedError;
 
+impl fmt::Display for BindlessTexturesNotSupportedError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for BindlessTexturesNotSupportedError {
+    fn description(&self) -> &str {
+        "Bindless textures are not supported by the backend"
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::mem;
@@ -52,9 +52,11 @@ to sample from a buffer texture of type `Unsigned` you need to use a `usamplerBu
 wrong type will result in an error.
 
 */
+use std::error::Error;
+use std::fmt;
 use std::mem;
 use std::marker::PhantomData;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 use std::rc::Rc;
 
 use gl;
@@ -63,6 +65,7 @@ use version::Api;
 use backend::Facade;
 use context::Context;
 use context::CommandContext;
+use context::ObjectKind;
 use ContextExt;
 
 use TextureExt;
@@ -76,6 +79,7 @@ use buffer::Content as BufferContent;
 
 use uniforms::AsUniformValue;
 use uniforms::UniformValue;
+use uniforms::{IncompleteTextureReason, SamplerBehavior};
 
 /// Error that can happen while building the texture part of a buffer texture.
 #[derive(Copy, Clone, Debug)]
@@ -90,6 +94,24 @@ pub enum TextureCreationError {
     TooLarge,
 }
 
+impl fmt::Display for TextureCreationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for TextureCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &TextureCreationError::NotSupported => "Buffer textures are not supported",
+            &TextureCreationError::FormatNotSupported => "The requested format is not supported \
+                                                          with this type of buffer texture",
+            &TextureCreationError::TooLarge => "The buffer is too large to be used as a \
+                                                buffer texture",
+        }
+    }
+}
+
 /// Error that can happen while building a buffer texture.
 #[derive(Copy, Clone, Debug)]
 pub enum CreationError {
@@ -100,6 +122,31 @@ pub enum CreationError {
     TextureCreationError(TextureCreationError),
 }
 
+impl fmt::Display for CreationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &CreationError::BufferCreationError(ref err) => err.fmt(formatter),
+            &CreationError::TextureCreationError(ref err) => err.fmt(formatter),
+        }
+    }
+}
+
+impl Error for CreationError {
+    fn description(&self) -> &str {
+        match self {
+            &CreationError::BufferCreationError(_) => "Error while creating the buffer",
+            &CreationError::TextureCreationError(_) => "Error while creating the texture",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &CreationError::BufferCreationError(ref err) => Some(err),
+            &CreationError::TextureCreationError(ref err) => Some(err),
+        }
+    }
+}
+
 impl From<BufferCreationError> for CreationError {
     #[inline]
     fn from(err: BufferCreationError) -> CreationError {
@@ -345,9 +392,7 @@ impl<T> BufferTexture<T> where [T]: BufferContent, T: TextureBufferContent + Cop
 
         // now the texture creation
         debug_assert_eq!(buffer.get_offset_bytes(), 0);
-        let id = if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                    ctxt.extensions.gl_arb_direct_state_access
-        {
+        let id = if ctxt.capabilities.direct_state_access {
             unsafe {
                 let mut id = mem::uninitialized();
                 ctxt.gl.CreateTextures(gl::TEXTURE_BUFFER, 1, &mut id);
@@ -404,6 +449,8 @@ impl<T> BufferTexture<T> where [T]: BufferContent, T: TextureBufferContent + Cop
             id
         };
 
+        ctxt.live_objects.add(ObjectKind::Texture, id as usize);
+
         Ok(BufferTexture {
             buffer: buffer,
             ty: ty,
@@ -430,7 +477,10 @@ impl<T> DerefMut for BufferTexture<T> where [T]: BufferContent {
 
 impl<T> Drop for BufferTexture<T> where [T]: BufferContent {
     fn drop(&mut self) {
-        let mut ctxt = self.buffer.get_context().make_current();
+        let mut ctxt = match self.buffer.get_context().make_current_for_drop() {
+            Some(ctxt) => ctxt,
+            None => return,
+        };
 
         // resetting the bindings
         for tex_unit in ctxt.state.texture_units.iter_mut() {
@@ -440,6 +490,7 @@ impl<T> Drop for BufferTexture<T> where [T]: BufferContent {
         }
 
         unsafe { ctxt.gl.DeleteTextures(1, [ self.texture ].as_ptr()); }
+        ctxt.live_objects.remove(ObjectKind::Texture, self.texture as usize);
     }
 }
 
@@ -506,8 +557,41 @@ impl<'a> TextureExt for BufferTextureRef<'a> {
     #[inline]
     fn bind_to_current(&self, ctxt: &mut CommandContext) -> gl::types::GLenum {
         unsafe { ctxt.gl.BindTexture(gl::TEXTURE_BUFFER, self.texture); }
+        ctxt.frame_statistics.update(|s| s.texture_binds += 1);
         gl::TEXTURE_BUFFER
     }
+
+    #[inline]
+    fn check_sampling_completeness(&self, _: &CommandContext, _: Option<&SamplerBehavior>)
+                                   -> Result<(), IncompleteTextureReason>
+    {
+        // buffer textures are fetched without filtering
+        Ok(())
+    }
+
+    #[inline]
+    fn get_sampled_levels(&self, _: Option<&SamplerBehavior>) -> Range<u32> {
+        // buffer textures don't have mipmaps and can't be attached to a framebuffer
+        0 .. 0
+    }
+
+    #[inline]
+    fn is_upload_complete(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn get_regenerate_mipmaps_after_render(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn mark_rendered(&self) {
+    }
+
+    #[inline]
+    fn prepare_for_sampling(&self, _: &mut CommandContext, _: Option<&SamplerBehavior>) {
+    }
 }
 
 ///
@@ -3,6 +3,8 @@ use version::Version;
 use version::Api;
 use gl;
 
+use std::error::Error;
+use std::fmt;
 use std::mem;
 
 use texture::any::TextureAny;
@@ -16,6 +18,21 @@ pub enum GetFormatError {
     NotSupported,
 }
 
+impl fmt::Display for GetFormatError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for GetFormatError {
+    fn description(&self) -> &str {
+        match self {
+            &GetFormatError::NotSupported => "The backend doesn't support retrieving the \
+                                              internal format of a texture",
+        }
+    }
+}
+
 /// Internal format of a texture.
 ///
 /// The actual format of a texture is not necessarly one of the predefined ones, so we have
@@ -146,9 +163,7 @@ pub fn get_format(ctxt: &mut CommandContext, texture: &TextureAny)
             let mut depth_sz = mem::uninitialized();
             let mut depth_ty = mem::uninitialized();
 
-            if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-               ctxt.extensions.gl_arb_direct_state_access
-            {
+            if ctxt.capabilities.direct_state_access {
                 let id = texture.get_id();
                 ctxt.gl.GetTextureLevelParameteriv(id, 0, gl::TEXTURE_RED_SIZE, &mut red_sz);
                 ctxt.gl.GetTextureLevelParameteriv(id, 0, gl::TEXTURE_RED_TYPE, &mut red_ty);
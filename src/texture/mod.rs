@@ -76,27 +76,37 @@ You can use thousands of textures if you want.
 #![allow(unreachable_code)]     // TODO: remove
 
 use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::mem;
 
 #[cfg(feature = "image")]
 use image;
 
 use image_format::FormatNotSupportedError;
+use buffer::BufferCreationError;
+use ops::ReadError;
+use Rect;
 
 pub use image_format::{ClientFormat, TextureFormat};
 pub use image_format::{UncompressedFloatFormat, UncompressedIntFormat, UncompressedUintFormat};
 pub use image_format::{CompressedFormat, DepthFormat, DepthStencilFormat, StencilFormat};
 pub use image_format::{CompressedSrgbFormat, SrgbFormat};
+pub use self::atlas::{Atlas, AtlasRegion};
 pub use self::any::{TextureAny, TextureAnyMipmap, TextureAnyLayer, TextureAnyLayerMipmap};
-pub use self::any::{TextureAnyImage, Dimensions};
+pub use self::any::{TextureAnyImage, Dimensions, StreamingWrite};
+#[doc(hidden)]
+pub use self::any::DetachedTexture;
 pub use self::bindless::{ResidentTexture, TextureHandle, BindlessTexturesNotSupportedError};
 pub use self::get_format::{InternalFormat, InternalFormatType, GetFormatError};
-pub use self::pixel::PixelValue;
+pub use self::pixel::{PixelValue, PackedRgba8};
 pub use self::ty_support::{is_texture_1d_supported, is_texture_2d_supported};
 pub use self::ty_support::{is_texture_3d_supported, is_texture_1d_array_supported};
 pub use self::ty_support::{is_texture_2d_array_supported, is_texture_2d_multisample_supported};
 pub use self::ty_support::{is_texture_2d_multisample_array_supported, is_cubemaps_supported};
 pub use self::ty_support::is_cubemap_arrays_supported;
 
+pub mod atlas;
 pub mod bindless;
 pub mod buffer_texture;
 pub mod pixel_buffer;
@@ -163,6 +173,13 @@ pub enum MipmapsOption {
     /// This does not mean that you will get mipmaps, instead it indicates that mipmaps are *allowed*
     /// to be generated if possible.
     AutoGeneratedMipmapsMax(u32),
+
+    /// Same as `AutoGeneratedMipmaps`, but the mipmaps are also regenerated after the main level
+    /// has been rendered to.
+    ///
+    /// Drawing, clearing or blitting to the main level marks the mipmaps as outdated, and they
+    /// are regenerated the next time the texture is sampled with a filter that uses them.
+    AutoGeneratedAfterRender,
 }
 
 impl MipmapsOption {
@@ -171,7 +188,16 @@ impl MipmapsOption {
     fn should_generate(self) -> bool {
         use self::MipmapsOption::*;
         match self {
-            AutoGeneratedMipmaps | AutoGeneratedMipmapsMax(_) => true,
+            AutoGeneratedMipmaps | AutoGeneratedMipmapsMax(_) | AutoGeneratedAfterRender => true,
+            _ => false,
+        }
+    }
+
+    /// Tells whether mipmaps should be regenerated after the main level has been rendered to.
+    #[inline]
+    fn should_regenerate_after_render(self) -> bool {
+        match self {
+            MipmapsOption::AutoGeneratedAfterRender => true,
             _ => false,
         }
     }
@@ -182,7 +208,7 @@ impl MipmapsOption {
         use std::cmp;
         match self {
             NoMipmap => 1,
-            EmptyMipmaps | AutoGeneratedMipmaps => {
+            EmptyMipmaps | AutoGeneratedMipmaps | AutoGeneratedAfterRender => {
                 let max_dimension = cmp::max(width, cmp::max(height.unwrap_or(1),
                                              depth.unwrap_or(1))) as f32;
                 match max_dimension {
@@ -374,6 +400,154 @@ impl<'a, T: Clone + 'a> RawImage2d<'a, T> {
             format: format,
         }
     }
+
+    /// Builds a `RawImage2d` from data whose rows are separated by `stride` bytes.
+    ///
+    /// Any padding at the end of each row is removed. The first row of `data` is the bottom row
+    /// of the image.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `stride` is smaller than the size of a row or if `data` is too small.
+    pub fn from_raw_with_stride(data: &[T], width: u32, height: u32, stride: usize,
+                                format: ClientFormat) -> RawImage2d<'static, T>
+    {
+        let elem_size = mem::size_of::<T>();
+        let row_bytes = width as usize * format.get_size();
+        assert!(stride >= row_bytes, "The stride is smaller than the size of a row");
+        assert!(stride % elem_size == 0, "The stride must be a multiple of the element size");
+
+        let row_len = row_bytes / elem_size;
+        let stride_len = stride / elem_size;
+        assert!(height == 0 || data.len() >= stride_len * (height as usize - 1) + row_len,
+                "The data is too small for the requested dimensions");
+
+        let mut raw_data = Vec::with_capacity(row_len * height as usize);
+        for row in 0 .. height as usize {
+            let start = row * stride_len;
+            raw_data.extend(data[start .. start + row_len].iter().cloned());
+        }
+
+        RawImage2d {
+            data: Cow::Owned(raw_data),
+            width: width,
+            height: height,
+            format: format,
+        }
+    }
+
+    /// Returns a view of a rectangle of this image.
+    ///
+    /// The data is borrowed if the rectangle covers whole rows, and copied otherwise.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the rectangle is out of the bounds of the image.
+    pub fn sub_image(&self, rect: &Rect) -> RawImage2d<T> {
+        assert!(rect.clip((self.width, self.height)) == *rect, "The rectangle is out of bounds");
+
+        let pixel_len = self.format.get_size() / mem::size_of::<T>();
+        let row_len = self.width as usize * pixel_len;
+        let sub_row_len = rect.width as usize * pixel_len;
+
+        let data = if rect.left == 0 && rect.width == self.width {
+            let start = rect.bottom as usize * row_len;
+            Cow::Borrowed(&self.data[start .. start + rect.height as usize * row_len])
+        } else {
+            let mut data = Vec::with_capacity(sub_row_len * rect.height as usize);
+            for row in rect.bottom as u32 .. rect.bottom as u32 + rect.height {
+                let start = row as usize * row_len + rect.left as usize * pixel_len;
+                data.extend(self.data[start .. start + sub_row_len].iter().cloned());
+            }
+            Cow::Owned(data)
+        };
+
+        RawImage2d {
+            data: data,
+            width: rect.width,
+            height: rect.height,
+            format: self.format,
+        }
+    }
+
+    /// Returns a copy of this image with the order of the rows reversed.
+    ///
+    /// This is useful to convert between top-to-bottom images and the bottom-to-top order
+    /// expected by OpenGL.
+    pub fn flip_vertical(&self) -> RawImage2d<'static, T> {
+        let row_len = self.width as usize * self.format.get_size() / mem::size_of::<T>();
+
+        let data = if row_len == 0 {
+            Vec::new()
+        } else {
+            self.data.chunks(row_len).rev().flat_map(|row| row.iter()).cloned().collect()
+        };
+
+        RawImage2d {
+            data: Cow::Owned(data),
+            width: self.width,
+            height: self.height,
+            format: self.format,
+        }
+    }
+}
+
+impl<'a> RawImage2d<'a, u8> {
+    /// Converts this image to the `U8U8U8U8` format.
+    ///
+    /// Missing color components are set to 0 and a missing alpha component is set to 255.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the format is not one of `U8`, `U8U8`, `U8U8U8` or `U8U8U8U8`.
+    pub fn to_rgba(&self) -> RawImage2d<'static, u8> {
+        let data = match self.format {
+            ClientFormat::U8 => {
+                self.data.iter().flat_map(|&r| vec![r, 0, 0, 255].into_iter()).collect()
+            },
+            ClientFormat::U8U8 => {
+                self.data.chunks(2).flat_map(|p| vec![p[0], p[1], 0, 255].into_iter()).collect()
+            },
+            ClientFormat::U8U8U8 => {
+                self.data.chunks(3).flat_map(|p| vec![p[0], p[1], p[2], 255].into_iter())
+                         .collect()
+            },
+            ClientFormat::U8U8U8U8 => self.data.to_vec(),
+            _ => panic!("Unsupported format for the RGBA conversion: {:?}", self.format),
+        };
+
+        RawImage2d {
+            data: Cow::Owned(data),
+            width: self.width,
+            height: self.height,
+            format: ClientFormat::U8U8U8U8,
+        }
+    }
+
+    /// Treats the data of this image as BGR or BGRA and swaps the red and blue components.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the format is not `U8U8U8` or `U8U8U8U8`.
+    pub fn bgra_to_rgba(&self) -> RawImage2d<'static, u8> {
+        let pixel_len = match self.format {
+            ClientFormat::U8U8U8 => 3,
+            ClientFormat::U8U8U8U8 => 4,
+            _ => panic!("Unsupported format for the BGRA conversion: {:?}", self.format),
+        };
+
+        let mut data = self.data.to_vec();
+        for pixel in data.chunks_mut(pixel_len) {
+            pixel.swap(0, 2);
+        }
+
+        RawImage2d {
+            data: Cow::Owned(data),
+            width: self.width,
+            height: self.height,
+            format: self.format,
+        }
+    }
 }
 
 impl<'a, P: PixelValue + Clone> Texture2dDataSource<'a> for Vec<Vec<P>> {
@@ -403,6 +577,10 @@ impl<'a, P: PixelValue + Clone> Texture2dDataSource<'a> for RawImage2d<'a, P> {
 
 impl<P> Texture2dDataSink<P> for Vec<Vec<P>> where P: Copy + Clone {
     fn from_raw(data: Cow<[P]>, width: u32, height: u32) -> Self {
+        if width == 0 {
+            return (0 .. height).map(|_| Vec::new()).collect();
+        }
+
         data.chunks(width as usize).map(|e| e.to_vec()).collect()
     }
 }
@@ -582,16 +760,298 @@ impl<P> Texture3dDataSink<P> for Vec<Vec<Vec<P>>> where P: Copy + Clone {
 }
 
 /// Error that can happen when creating a texture.
+///
+/// All the checks are done before any OpenGL call is made.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextureCreationError {
     /// The requested format is not supported by the backend.
     FormatNotSupported,
 
-    /// The requested texture dimensions are not supported.
+    /// The requested texture dimensions are not supported. This happens for example with
+    /// non-power-of-two dimensions on old backends.
     DimensionsNotSupported,
 
-    /// The texture format is not supported by the backend.
+    /// One of the dimensions of the texture is larger than what the backend supports.
+    DimensionsTooLarge {
+        /// The value that was requested.
+        requested: u32,
+        /// The maximum value supported by the backend.
+        max: u32,
+    },
+
+    /// The length of the data doesn't match the dimensions and the format of the texture.
+    DataLengthMismatch {
+        /// Number of bytes that the data should have.
+        expected: usize,
+        /// Number of bytes of the data that was provided.
+        provided: usize,
+    },
+
+    /// This type of texture (for example 3D textures or texture arrays) is not supported by
+    /// the backend.
     TypeNotSupported,
+
+    /// A 16-bits normalized format was requested, but the backend doesn't support it. This
+    /// happens on OpenGL ES without the `GL_EXT_texture_norm16` extension.
+    ///
+    /// You should fall back to a floating-point format like `F16` or to an 8-bits format.
+    Norm16NotSupported,
+
+    /// One of the dimensions of the texture, its number of layers or its number of samples
+    /// is 0. OpenGL doesn't allow creating empty textures.
+    ZeroDimension,
+}
+
+impl fmt::Display for TextureCreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &TextureCreationError::DimensionsTooLarge { requested, max } => {
+                write!(fmt, "{} ({} requested, the maximum is {})", self.description(),
+                       requested, max)
+            },
+            &TextureCreationError::DataLengthMismatch { expected, provided } => {
+                write!(fmt, "{} ({} bytes expected, {} bytes provided)", self.description(),
+                       expected, provided)
+            },
+            _ => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for TextureCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &TextureCreationError::FormatNotSupported => "The requested format is not supported \
+                                                          by the backend",
+            &TextureCreationError::DimensionsNotSupported => "The requested texture dimensions \
+                                                              are not supported",
+            &TextureCreationError::DimensionsTooLarge { .. } => "The requested texture dimensions \
+                                                                 are too large",
+            &TextureCreationError::DataLengthMismatch { .. } => "The length of the data doesn't \
+                                                                 match the texture dimensions",
+            &TextureCreationError::TypeNotSupported => "This type of texture is not supported \
+                                                        by the backend",
+            &TextureCreationError::Norm16NotSupported => "16-bits normalized formats are not \
+                                                          supported by the backend, use a \
+                                                          floating-point or 8-bits format \
+                                                          instead",
+            &TextureCreationError::ZeroDimension => "One of the dimensions of the texture is 0",
+        }
+    }
+}
+
+/// Error that can happen when uploading the rows of an image with `write_rows`.
+#[derive(Debug, Copy, Clone)]
+pub enum WriteRowsError {
+    /// The iterator produced less rows than the height of the rectangle.
+    NotEnoughRows {
+        /// Height of the rectangle.
+        expected: u32,
+        /// Number of rows that have been produced.
+        provided: u32,
+    },
+
+    /// One of the rows doesn't have the same number of pixels as the width of the rectangle.
+    RowLengthMismatch {
+        /// Index of the row, starting from the bottom of the rectangle.
+        row: u32,
+        /// Width of the rectangle.
+        expected: usize,
+        /// Number of pixels in the row.
+        provided: usize,
+    },
+
+    /// Error while creating the pixel buffer used to upload the rows.
+    BufferCreationError(BufferCreationError),
+}
+
+impl fmt::Display for WriteRowsError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &WriteRowsError::NotEnoughRows { expected, provided } => {
+                write!(fmt, "{} ({} rows expected, {} rows provided)", self.description(),
+                       expected, provided)
+            },
+            &WriteRowsError::RowLengthMismatch { row, expected, provided } => {
+                write!(fmt, "{} (row {} has {} pixels, {} expected)", self.description(),
+                       row, provided, expected)
+            },
+            &WriteRowsError::BufferCreationError(ref err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl Error for WriteRowsError {
+    fn description(&self) -> &str {
+        match self {
+            &WriteRowsError::NotEnoughRows { .. } => "Not enough rows have been provided",
+            &WriteRowsError::RowLengthMismatch { .. } => "The length of a row doesn't match the \
+                                                          width of the rectangle",
+            &WriteRowsError::BufferCreationError(_) => "Error while creating the pixel buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &WriteRowsError::BufferCreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Error that can happen when reading an image of a texture with `TextureAny::read_image`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReadImageError {
+    /// The texture doesn't have this mipmap level.
+    LevelOutOfRange {
+        /// The level that has been requested.
+        level: u32,
+        /// Number of mipmap levels of the texture.
+        levels: u32,
+    },
+
+    /// The texture doesn't have this layer.
+    LayerOutOfRange {
+        /// The layer that has been requested.
+        layer: u32,
+        /// Number of layers of the texture. Non-array textures have one layer.
+        layers: u32,
+    },
+
+    /// The texture is a cubemap or a cubemap array, but no face has been specified.
+    MissingCubeLayer,
+
+    /// A face has been specified, but the texture is not a cubemap or a cubemap array.
+    NotCubemap,
+
+    /// Multisample textures can't be read.
+    Multisample,
+
+    /// Error while reading the image.
+    ReadError(ReadError),
+}
+
+impl fmt::Display for ReadImageError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &ReadImageError::LevelOutOfRange { level, levels } => {
+                write!(fmt, "{} (level {} requested, the texture has {} levels)",
+                       self.description(), level, levels)
+            },
+            &ReadImageError::LayerOutOfRange { layer, layers } => {
+                write!(fmt, "{} (layer {} requested, the texture has {} layers)",
+                       self.description(), layer, layers)
+            },
+            &ReadImageError::ReadError(ref err) => err.fmt(fmt),
+            _ => self.description().fmt(fmt),
+        }
+    }
+}
+
+impl Error for ReadImageError {
+    fn description(&self) -> &str {
+        match self {
+            &ReadImageError::LevelOutOfRange { .. } => "The mipmap level is out of range",
+            &ReadImageError::LayerOutOfRange { .. } => "The layer is out of range",
+            &ReadImageError::MissingCubeLayer => "A cubemap face must be specified",
+            &ReadImageError::NotCubemap => "A cubemap face has been specified, but the texture \
+                                            is not a cubemap",
+            &ReadImageError::Multisample => "Multisample textures can't be read",
+            &ReadImageError::ReadError(ref err) => err.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &ReadImageError::ReadError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ReadError> for ReadImageError {
+    #[inline]
+    fn from(err: ReadError) -> ReadImageError {
+        ReadImageError::ReadError(err)
+    }
+}
+
+/// Error that can happen when reading or writing a whole mipmap level of a compressed texture
+/// with `read_compressed_level` or `write_compressed_level`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompressedLevelError {
+    /// The backend doesn't support this operation. OpenGL ES can't read compressed data.
+    NotSupported,
+
+    /// The texture doesn't have this mipmap level.
+    LevelOutOfRange {
+        /// The level that has been requested.
+        level: u32,
+        /// Number of mipmap levels of the texture.
+        levels: u32,
+    },
+
+    /// The dimensions of the mipmap level are not a multiple of the dimensions of the blocks of
+    /// the format. Dimensions that are smaller than a block are allowed.
+    UnalignedDimensions {
+        /// Dimensions of the mipmap level.
+        dimensions: (u32, u32),
+        /// Dimensions of a block of the format.
+        block: (u32, u32),
+    },
+
+    /// The internal format of the texture is not a known compressed format.
+    UnknownFormat,
+
+    /// The format of the data doesn't match the internal format of the texture.
+    FormatMismatch,
+
+    /// The length of the data doesn't match the size of the mipmap level.
+    DataLengthMismatch {
+        /// Number of bytes of the mipmap level.
+        expected: usize,
+        /// Number of bytes that have been provided.
+        provided: usize,
+    },
+}
+
+impl fmt::Display for CompressedLevelError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &CompressedLevelError::LevelOutOfRange { level, levels } => {
+                write!(fmt, "{} (level {} requested, the texture has {} levels)",
+                       self.description(), level, levels)
+            },
+            &CompressedLevelError::UnalignedDimensions { dimensions, block } => {
+                write!(fmt, "{} ({}x{} pixels, blocks of {}x{} pixels)", self.description(),
+                       dimensions.0, dimensions.1, block.0, block.1)
+            },
+            &CompressedLevelError::DataLengthMismatch { expected, provided } => {
+                write!(fmt, "{} ({} bytes expected, {} bytes provided)", self.description(),
+                       expected, provided)
+            },
+            _ => self.description().fmt(fmt),
+        }
+    }
+}
+
+impl Error for CompressedLevelError {
+    fn description(&self) -> &str {
+        match self {
+            &CompressedLevelError::NotSupported => "The backend doesn't support transferring \
+                                                    compressed data",
+            &CompressedLevelError::LevelOutOfRange { .. } => "The mipmap level is out of range",
+            &CompressedLevelError::UnalignedDimensions { .. } => "The dimensions of the mipmap \
+                                                                  level are not aligned to the \
+                                                                  blocks of the format",
+            &CompressedLevelError::UnknownFormat => "The internal format of the texture is not \
+                                                     a known compressed format",
+            &CompressedLevelError::FormatMismatch => "The format of the data doesn't match the \
+                                                      format of the texture",
+            &CompressedLevelError::DataLengthMismatch { .. } => "The length of the data doesn't \
+                                                                 match the size of the level",
+        }
+    }
 }
 
 impl From<FormatNotSupportedError> for TextureCreationError {
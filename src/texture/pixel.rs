@@ -1,7 +1,18 @@
 #[cfg(feature = "image")]
 use image;
 
+#[cfg(feature = "half")]
+use half::f16;
+
+use std::mem;
+
 /// A trait that must be implemented for any type that can represent the value of a pixel.
+///
+/// Scalars, tuples and arrays of `i8`, `u8`, `i16`, `u16`, `i32`, `u32` and `f32` are mapped
+/// to the `ClientFormat` with the same number of components and the same type. For example
+/// `u16` can be used with `R16` and 16-bits depth textures, and `(f32,)` and `[f32; 1]` with
+/// `R32F` textures. A `u32` is a single 32-bits component: use `PackedRgba8` to manipulate
+/// a RGBA8 pixel as a single integer.
 pub unsafe trait PixelValue: Copy + Clone + Send + 'static {
     /// Returns corresponding client format.
     fn get_format() -> super::ClientFormat;
@@ -203,6 +214,138 @@ unsafe impl PixelValue for (f32, f32, f32, f32) {
     }
 }
 
+/// Implements `PixelValue` for the one-element tuple and for the arrays of a scalar type.
+///
+/// Arrays have the same formats as the tuples with the same number of components.
+macro_rules! impl_pixel_value_arrays {
+    ($ty:ty, $one:ident, $two:ident, $three:ident, $four:ident) => (
+        unsafe impl PixelValue for ($ty,) {
+            #[inline]
+            fn get_format() -> super::ClientFormat {
+                super::ClientFormat::$one
+            }
+        }
+
+        unsafe impl PixelValue for [$ty; 1] {
+            #[inline]
+            fn get_format() -> super::ClientFormat {
+                super::ClientFormat::$one
+            }
+        }
+
+        unsafe impl PixelValue for [$ty; 2] {
+            #[inline]
+            fn get_format() -> super::ClientFormat {
+                super::ClientFormat::$two
+            }
+        }
+
+        unsafe impl PixelValue for [$ty; 3] {
+            #[inline]
+            fn get_format() -> super::ClientFormat {
+                super::ClientFormat::$three
+            }
+        }
+
+        unsafe impl PixelValue for [$ty; 4] {
+            #[inline]
+            fn get_format() -> super::ClientFormat {
+                super::ClientFormat::$four
+            }
+        }
+    );
+}
+
+impl_pixel_value_arrays!(i8, I8, I8I8, I8I8I8, I8I8I8I8);
+impl_pixel_value_arrays!(u8, U8, U8U8, U8U8U8, U8U8U8U8);
+impl_pixel_value_arrays!(i16, I16, I16I16, I16I16I16, I16I16I16I16);
+impl_pixel_value_arrays!(u16, U16, U16U16, U16U16U16, U16U16U16U16);
+impl_pixel_value_arrays!(i32, I32, I32I32, I32I32I32, I32I32I32I32);
+impl_pixel_value_arrays!(u32, U32, U32U32, U32U32U32, U32U32U32U32);
+impl_pixel_value_arrays!(f32, F32, F32F32, F32F32F32, F32F32F32F32);
+
+#[cfg(feature = "half")]
+unsafe impl PixelValue for f16 {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl PixelValue for (f16, f16) {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl PixelValue for (f16, f16, f16) {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl PixelValue for (f16, f16, f16, f16) {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::F16F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+impl_pixel_value_arrays!(f16, F16, F16F16, F16F16F16, F16F16F16F16);
+
+/// A RGBA pixel with 8 bits per component, stored in a single `u32`.
+///
+/// The components are stored in memory in the R, G, B, A order, exactly like `[u8; 4]` and
+/// like what `glReadPixels` returns with `ClientFormat::U8U8U8U8`. This means that the value
+/// of the integer depends on the endianness of the machine: on little-endian machines, red is
+/// the lowest byte and alpha the highest byte.
+///
+/// Converting from and to `[u8; 4]` or `(u8, u8, u8, u8)` doesn't cost anything.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PackedRgba8(pub u32);
+
+unsafe impl PixelValue for PackedRgba8 {
+    #[inline]
+    fn get_format() -> super::ClientFormat {
+        super::ClientFormat::U8U8U8U8
+    }
+}
+
+impl From<[u8; 4]> for PackedRgba8 {
+    #[inline]
+    fn from(value: [u8; 4]) -> PackedRgba8 {
+        PackedRgba8(unsafe { mem::transmute(value) })
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for PackedRgba8 {
+    #[inline]
+    fn from((r, g, b, a): (u8, u8, u8, u8)) -> PackedRgba8 {
+        From::from([r, g, b, a])
+    }
+}
+
+impl From<PackedRgba8> for [u8; 4] {
+    #[inline]
+    fn from(value: PackedRgba8) -> [u8; 4] {
+        unsafe { mem::transmute(value.0) }
+    }
+}
+
+impl From<PackedRgba8> for (u8, u8, u8, u8) {
+    #[inline]
+    fn from(value: PackedRgba8) -> (u8, u8, u8, u8) {
+        let value: [u8; 4] = From::from(value);
+        (value[0], value[1], value[2], value[3])
+    }
+}
+
 #[cfg(feature = "image")]
 unsafe impl PixelValue for image::Rgb<u8> {
     #[inline]
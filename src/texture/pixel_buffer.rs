@@ -12,7 +12,7 @@ use backend::Facade;
 
 use GlObject;
 use BufferExt;
-use buffer::{ReadError, Buffer, BufferType, BufferMode};
+use buffer::{ReadError, Buffer, BufferType, BufferMode, BufferCreationError};
 use gl;
 
 use texture::PixelValue;
@@ -44,6 +44,17 @@ impl<T> PixelBuffer<T> where T: PixelValue {
         let data = try!(self.read());
         Ok(S::from_raw(Cow::Owned(data), dimensions.0, dimensions.1))
     }
+
+    /// Builds a new pixel buffer with the same content and dimensions as this one.
+    #[inline]
+    pub fn clone_buffer<F>(&self, facade: &F) -> Result<PixelBuffer<T>, BufferCreationError>
+                           where F: Facade
+    {
+        Ok(PixelBuffer {
+            buffer: try!(self.buffer.clone_buffer(facade)),
+            dimensions: Cell::new(self.dimensions.get()),
+        })
+    }
 }
 
 impl<T> Deref for PixelBuffer<T> where T: PixelValue {
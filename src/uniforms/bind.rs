@@ -5,6 +5,11 @@ Handles binding uniforms to the OpenGL state machine.
 */
 use gl;
 
+use std::cmp;
+use std::slice;
+
+use smallvec::SmallVec;
+
 use BufferExt;
 use BufferSliceExt;
 use DrawError;
@@ -15,11 +20,20 @@ use TextureExt;
 
 use uniforms::Uniforms;
 use uniforms::UniformValue;
+use uniforms::UniformType;
 use uniforms::SamplerBehavior;
+use uniforms::IncompleteTextureReason;
 
 use context::CommandContext;
+use context::GlState;
+use draw_uniforms;
+use draw_uniforms::DRAW_UNIFORMS_BLOCK;
+use fbo::ValidatedAttachments;
+use framebuffer::AttachmentPoint;
+use texture::TextureAny;
 use buffer::Inserter;
 use ContextExt;
+use GlObject;
 
 use utils::bitsfield::Bitsfield;
 
@@ -36,34 +50,46 @@ impl<U> UniformsExt for U where U: Uniforms {
                             -> Result<(), DrawError>
                             where P: ProgramExt
     {
-        let mut texture_bind_points = Bitsfield::new();
+        let mut texture_units = TextureUnits::new(ctxt);
         let mut uniform_buffer_bind_points = Bitsfield::new();
         let mut shared_storage_buffer_bind_points = Bitsfield::new();
 
+        // the binding points of the global blocks must not be used by the blocks of this draw
+        let global_uniform_blocks = ctxt.global_uniform_blocks;
+        global_uniform_blocks.reserve_bind_points(&mut uniform_buffer_bind_points);
+        let mut bound_blocks: SmallVec<[i32; 8]> = SmallVec::new();
+
+        // if the program declares a `glium_DrawUniforms` block, the values of its members are
+        // gathered here instead of being set with `glUniform`
+        let draw_block = program.get_uniform_blocks().get(DRAW_UNIFORMS_BLOCK);
+        let mut draw_block_data = draw_block.map(|block| vec![0u8; block.size]);
+
         let mut visiting_result = Ok(());
         self.visit_values(|name, value| {
             if visiting_result.is_err() { return; }
 
-            if let Some(uniform) = program.get_uniform(name) {
-                assert!(uniform.size.is_none(), "Uniform arrays not supported yet");
+            if let Err(e) = check_context(&value, program) {
+                visiting_result = Err(e);
+                return;
+            }
 
-                if !value.is_usable_with(&uniform.ty) {
-                    visiting_result = Err(DrawError::UniformTypeMismatch {
-                        name: name.to_owned(),
-                        expected: uniform.ty,
-                    });
-                    return;
+            if let (Some(block), Some(data)) = (draw_block, draw_block_data.as_mut()) {
+                match draw_uniforms::write_member(&block.layout, name, &value, data) {
+                    Some(Ok(())) => return,
+                    Some(Err(ty)) => {
+                        visiting_result = Err(DrawError::UniformTypeMismatch {
+                            name: name.to_owned(),
+                            expected: ty,
+                        });
+                        return;
+                    },
+                    None => (),
                 }
+            }
 
-                match bind_uniform(&mut ctxt, &value, program, uniform.location,
-                                   &mut texture_bind_points, name)
-                {
-                    Ok(_) => (),
-                    Err(e) => {
-                        visiting_result = Err(e);
-                        return;
-                    }
-                };
+            if let Some(uniform) = program.get_uniform(name) {
+                visiting_result = bind_program_uniform(&mut ctxt, &value, program, uniform,
+                                                       &mut texture_units, name);
 
             } else if let Some(block) = program.get_uniform_blocks().get(name) {
                 let fence = match bind_uniform_block(&mut ctxt, &value, block,
@@ -76,6 +102,8 @@ impl<U> UniformsExt for U where U: Uniforms {
                     }
                 };
 
+                bound_blocks.push(block.id);
+
                 if let Some(fence) = fence {
                     fences.push(fence);
                 }
@@ -98,8 +126,160 @@ impl<U> UniformsExt for U where U: Uniforms {
             }
         });
 
-        visiting_result
+        try!(visiting_result);
+
+        // values whose location has been resolved in advance
+        let program_id = program.get_id();
+        let program_generation = program.get_generation();
+        self.visit_handles(|handle, value| {
+            if visiting_result.is_err() { return; }
+
+            if handle.get_program_id() != program_id ||
+               handle.get_program_generation() != program_generation
+            {
+                visiting_result = Err(DrawError::UniformHandleProgramMismatch {
+                    name: handle.get_name().to_owned(),
+                });
+                return;
+            }
+
+            if let Err(e) = check_context(&value, program) {
+                visiting_result = Err(e);
+                return;
+            }
+
+            visiting_result = bind_program_uniform(&mut ctxt, &value, program,
+                                                   &handle.get_uniform(),
+                                                   &mut texture_units, handle.get_name());
+        });
+
+        try!(visiting_result);
+
+        texture_units.flush(ctxt);
+
+        // a buffer passed with the name of the block takes precedence over the gathered values
+        if let (Some(block), Some(data)) = (draw_block, draw_block_data) {
+            if !bound_blocks.contains(&block.id) {
+                let bind_point = uniform_buffer_bind_points.get_unused()
+                                                           .expect("Not enough buffer units");
+                uniform_buffer_bind_points.set_used(bind_point);
+
+                let draw_uniforms = ctxt.draw_uniforms;
+                draw_uniforms.upload_and_bind(ctxt, &data, bind_point as gl::types::GLuint);
+                program.set_uniform_block_binding(ctxt, block.id as gl::types::GLuint,
+                                                  bind_point as gl::types::GLuint);
+                bound_blocks.push(block.id);
+            }
+        }
+
+        // blocks that haven't been passed to the draw command use the global blocks
+        global_uniform_blocks.bind(ctxt, program, &bound_blocks)
+    }
+
+    fn check_feedback_loops<P>(&self, program: &P, framebuffer: &ValidatedAttachments,
+                               depth_read_only: bool) -> Result<(), DrawError>
+                               where P: ProgramExt
+    {
+        if !framebuffer.has_texture_attachments() {
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        self.visit_values(|name, value| {
+            if result.is_err() || program.get_uniform(name).is_none() {
+                return;
+            }
+
+            result = check_feedback_loop(&value, framebuffer, name, depth_read_only);
+        });
+
+        let program_id = program.get_id();
+        self.visit_handles(|handle, value| {
+            if result.is_err() || handle.get_program_id() != program_id {
+                return;
+            }
+
+            result = check_feedback_loop(&value, framebuffer, handle.get_name(),
+                                         depth_read_only);
+        });
+
+        result
+    }
+}
+
+/// Checks that the texture or the buffer of a value is visible from the context of the program.
+///
+/// The program has already been checked against the context of the target.
+fn check_context<P>(value: &UniformValue, program: &P) -> Result<(), DrawError>
+                    where P: ProgramExt
+{
+    let context = program.get_context();
+
+    if let Some((texture, _)) = get_sampled_texture(value) {
+        if !texture.get_context().is_sharing_objects_with(context) {
+            return Err(DrawError::WrongContext { object_kind: "texture" });
+        }
+    }
+
+    if let &UniformValue::Block(ref buffer, _) = value {
+        if !buffer.get_context().is_sharing_objects_with(context) {
+            return Err(DrawError::WrongContext { object_kind: "uniform buffer" });
+        }
+    }
+
+    Ok(())
+}
+
+fn check_feedback_loop(value: &UniformValue, framebuffer: &ValidatedAttachments, name: &str,
+                       depth_read_only: bool) -> Result<(), DrawError>
+{
+    if let Some((texture, sampler)) = get_sampled_texture(value) {
+        let levels = texture.get_sampled_levels(sampler.as_ref());
+        if let Some(attachment) = framebuffer.find_texture_attachment(texture.get_texture_id(),
+                                                                      levels)
+        {
+            // sampling a depth buffer that isn't written to is safe
+            if depth_read_only && attachment == AttachmentPoint::Depth {
+                return Ok(());
+            }
+
+            return Err(DrawError::FeedbackLoop {
+                texture: name.to_owned(),
+                attachment: attachment,
+            });
+        }
     }
+
+    Ok(())
+}
+
+/// Checks that the value can be bound to the uniform, and binds it.
+fn bind_program_uniform<P>(ctxt: &mut context::CommandContext, value: &UniformValue,
+                           program: &P, uniform: &program::Uniform,
+                           texture_units: &mut TextureUnits, name: &str)
+                           -> Result<(), DrawError> where P: ProgramExt
+{
+    // only arrays of matrices can be bound to uniform arrays for the moment
+    match *value {
+        UniformValue::Mat3Array(_) | UniformValue::Mat4Array(_) => (),
+        _ => assert!(uniform.size.is_none(), "Uniform arrays not supported yet"),
+    };
+
+    if cfg!(debug_assertions) && is_depth_texture(value) && is_shadow_sampler(uniform.ty) {
+        return Err(DrawError::IncompleteTexture {
+            name: name.to_owned(),
+            reason: IncompleteTextureReason::DepthComparisonDisabled,
+        });
+    }
+
+    if !value.is_usable_with(&uniform.ty) {
+        return Err(DrawError::UniformTypeMismatch {
+            name: name.to_owned(),
+            expected: uniform.ty,
+        });
+    }
+
+    bind_uniform(ctxt, value, program, uniform.location, uniform.size, texture_units, name)
 }
 
 fn bind_uniform_block<'a, P>(ctxt: &mut context::CommandContext, value: &UniformValue<'a>,
@@ -120,10 +300,16 @@ fn bind_uniform_block<'a, P>(ctxt: &mut context::CommandContext, value: &Uniform
                 }
             }
 
+            let alignment = ctxt.capabilities.uniform_buffer_offset_alignment as usize;
+            if buffer.get_offset_bytes() % alignment != 0 {
+                return Err(DrawError::UniformBufferOffsetNotAligned {
+                    name: name.to_owned(),
+                });
+            }
+
             let bind_point = buffer_bind_points.get_unused().expect("Not enough buffer units");
             buffer_bind_points.set_used(bind_point);
 
-            assert!(buffer.get_offset_bytes() == 0);     // TODO: not implemented
             let fence = buffer.add_fence();
             let block_id = block.id as gl::types::GLuint;
 
@@ -176,7 +362,7 @@ fn bind_shared_storage_block<'a, P>(ctxt: &mut context::CommandContext, value: &
 
 fn bind_uniform<P>(ctxt: &mut context::CommandContext,
                    value: &UniformValue, program: &P, location: gl::types::GLint,
-                   texture_bind_points: &mut Bitsfield, name: &str)
+                   array_size: Option<usize>, texture_units: &mut TextureUnits, name: &str)
                    -> Result<(), DrawError> where P: ProgramExt
 {
     assert!(location >= 0);
@@ -216,6 +402,19 @@ fn bind_uniform<P>(ctxt: &mut context::CommandContext,
             program.set_uniform(ctxt, location, &RawUniformValue::Mat4(val));
             Ok(())
         },
+        UniformValue::Mat3Array(val) => {
+            // the elements that don't exist in the program are ignored
+            let count = cmp::min(val.len(), array_size.unwrap_or(1));
+            let data = unsafe { slice::from_raw_parts(val.as_ptr() as *const f32, count * 9) };
+            program.set_uniform_matrix_array(ctxt, location, 3, data);
+            Ok(())
+        },
+        UniformValue::Mat4Array(val) => {
+            let count = cmp::min(val.len(), array_size.unwrap_or(1));
+            let data = unsafe { slice::from_raw_parts(val.as_ptr() as *const f32, count * 16) };
+            program.set_uniform_matrix_array(ctxt, location, 4, data);
+            Ok(())
+        },
         UniformValue::Vec2(val) => {
             program.set_uniform(ctxt, location, &RawUniformValue::Vec2(val));
             Ok(())
@@ -328,184 +527,244 @@ fn bind_uniform<P>(ctxt: &mut context::CommandContext,
             Ok(())
         },
         UniformValue::Texture1d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedTexture1d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbTexture1d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedSrgbTexture1d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralTexture1d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedTexture1d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthTexture1d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::Texture2d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedTexture2d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbTexture2d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedSrgbTexture2d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralTexture2d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedTexture2d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthTexture2d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::Texture2dMultisample(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbTexture2dMultisample(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralTexture2dMultisample(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedTexture2dMultisample(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthTexture2dMultisample(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::Texture3d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedTexture3d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbTexture3d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedSrgbTexture3d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralTexture3d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedTexture3d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthTexture3d(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::Texture1dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedTexture1dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbTexture1dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedSrgbTexture1dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralTexture1dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedTexture1dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthTexture1dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::Texture2dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedTexture2dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbTexture2dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedSrgbTexture2dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralTexture2dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedTexture2dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthTexture2dArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::Texture2dMultisampleArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbTexture2dMultisampleArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralTexture2dMultisampleArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedTexture2dMultisampleArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthTexture2dMultisampleArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::Cubemap(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedCubemap(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbCubemap(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedSrgbCubemap(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralCubemap(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedCubemap(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthCubemap(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CubemapArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedCubemapArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::SrgbCubemapArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::CompressedSrgbCubemapArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::IntegralCubemapArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::UnsignedCubemapArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::DepthCubemapArray(texture, sampler) => {
-            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &**texture, sampler, location, program, texture_units,
+                                 name)
         },
         UniformValue::BufferTexture(texture) => {
-            bind_texture_uniform(ctxt, &texture, None, location, program, texture_bind_points)
+            bind_texture_uniform(ctxt, &texture, None, location, program, texture_units,
+                                 name)
         },
     }
 }
@@ -513,9 +772,30 @@ fn bind_uniform<P>(ctxt: &mut context::CommandContext,
 fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
                               texture: &T, sampler: Option<SamplerBehavior>,
                               location: gl::types::GLint, program: &P,
-                              texture_bind_points: &mut Bitsfield)
+                              texture_units: &mut TextureUnits, name: &str)
                               -> Result<(), DrawError> where P: ProgramExt, T: TextureExt
 {
+    // the content of a texture whose upload isn't finished is undefined
+    if !texture.is_upload_complete() {
+        return Err(DrawError::IncompleteTexture {
+            name: name.to_owned(),
+            reason: IncompleteTextureReason::IncompleteUpload,
+        });
+    }
+
+    // sampling an incomplete texture silently returns black or garbage
+    if cfg!(debug_assertions) {
+        if let Err(reason) = texture.check_sampling_completeness(ctxt, sampler.as_ref()) {
+            return Err(DrawError::IncompleteTexture {
+                name: name.to_owned(),
+                reason: reason,
+            });
+        }
+    }
+
+    // regenerating outdated mipmaps before choosing a texture unit, as this may bind the texture
+    texture.prepare_for_sampling(ctxt, sampler.as_ref());
+
     let sampler = if let Some(sampler) = sampler {
         Some(try!(::sampler_object::get_sampler(ctxt, &sampler)))
     } else {
@@ -525,14 +805,15 @@ fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
     let sampler = sampler.unwrap_or(0);
 
     // finding an appropriate texture unit
+    let texture_id = texture.get_texture_id();
     let texture_unit =
-        ctxt.state.texture_units
-            .iter().enumerate()
-            .find(|&(unit, content)| {
-                content.texture == texture.get_texture_id() && (content.sampler == sampler ||
-                                                        !texture_bind_points.is_used(unit as u16))
+        (0 .. ctxt.state.texture_units.len() as u16)
+            .find(|&unit| {
+                let (content_texture, content_sampler) =
+                    texture_units.get_content(&ctxt.state, unit);
+                content_texture == texture_id && (content_sampler == sampler ||
+                                                  !texture_units.used.is_used(unit))
             })
-            .map(|(unit, _)| unit as u16)
             .or_else(|| {
                 if ctxt.state.texture_units.len() <
                     ctxt.capabilities.max_combined_texture_image_units as usize
@@ -543,11 +824,11 @@ fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
                 }
             })
             .unwrap_or_else(|| {
-                texture_bind_points.get_unused().expect("Not enough texture units available")
+                texture_units.used.get_unused().expect("Not enough texture units available")
             });
     assert!((texture_unit as gl::types::GLint) <
             ctxt.capabilities.max_combined_texture_image_units);
-    texture_bind_points.set_used(texture_unit);
+    texture_units.used.set_used(texture_unit);
 
     // updating the program to use the right unit
     program.set_uniform(ctxt, location,
@@ -560,8 +841,14 @@ fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
         }
     }
 
+    // with `glBindTextures`, all the units are changed at once by `TextureUnits::flush`
+    if texture_units.multi_bind {
+        texture_units.set_pending(texture_unit, texture_id, sampler);
+        return Ok(());
+    }
+
     // TODO: do better
-    if ctxt.state.texture_units[texture_unit as usize].texture != texture.get_texture_id() ||
+    if ctxt.state.texture_units[texture_unit as usize].texture != texture_id ||
        ctxt.state.texture_units[texture_unit as usize].sampler != sampler
     {
         // TODO: what if it's not supported?
@@ -583,3 +870,183 @@ fn bind_texture_uniform<P, T>(mut ctxt: &mut context::CommandContext,
 
     Ok(())
 }
+
+/// Texture units used by the textures of the draw command being prepared.
+struct TextureUnits {
+    /// The units that have already been assigned to a texture of the draw command.
+    used: Bitsfield,
+
+    /// True if `glBindTextures` and `glBindSamplers` are supported. In this situation the
+    /// units are not modified immediately but are added to `pending`.
+    multi_bind: bool,
+
+    /// Units that must contain a texture and a sampler when drawing.
+    pending: SmallVec<[(u16, gl::types::GLuint, gl::types::GLuint); 16]>,
+}
+
+impl TextureUnits {
+    #[inline]
+    fn new(ctxt: &CommandContext) -> TextureUnits {
+        TextureUnits {
+            used: Bitsfield::new(),
+            multi_bind: ctxt.version >= &Version(Api::Gl, 4, 4) ||
+                        ctxt.extensions.gl_arb_multi_bind,
+            pending: SmallVec::new(),
+        }
+    }
+
+    /// Returns the texture and the sampler that the unit will contain when drawing, if nothing
+    /// else is changed.
+    #[inline]
+    fn get_content(&self, state: &GlState, unit: u16) -> (gl::types::GLuint, gl::types::GLuint) {
+        if let Some(&(_, texture, sampler)) = self.pending.iter().find(|&&(u, _, _)| u == unit) {
+            return (texture, sampler);
+        }
+
+        let content = &state.texture_units[unit as usize];
+        (content.texture, content.sampler)
+    }
+
+    /// Sets the texture and the sampler that the unit must contain when drawing.
+    fn set_pending(&mut self, unit: u16, texture: gl::types::GLuint,
+                   sampler: gl::types::GLuint)
+    {
+        if let Some(entry) = self.pending.iter_mut().find(|&&mut (u, _, _)| u == unit) {
+            *entry = (unit, texture, sampler);
+            return;
+        }
+
+        self.pending.push((unit, texture, sampler));
+    }
+
+    /// Binds the pending textures and samplers with a single call to `glBindTextures` and a
+    /// single call to `glBindSamplers`.
+    ///
+    /// The whole range of units between the first and the last modified unit is passed to
+    /// OpenGL. The units of this range that don't need to change are rebound to their current
+    /// content.
+    fn flush(&mut self, ctxt: &mut CommandContext) {
+        // finding the range of units that must be changed
+        let mut range: Option<(usize, usize)> = None;
+        for &(unit, texture, sampler) in self.pending.iter() {
+            let content = &ctxt.state.texture_units[unit as usize];
+            if content.texture == texture && content.sampler == sampler {
+                continue;
+            }
+
+            let unit = unit as usize;
+            range = Some(match range {
+                Some((first, last)) => (cmp::min(first, unit), cmp::max(last, unit)),
+                None => (unit, unit),
+            });
+        }
+
+        let (first, last) = match range {
+            Some(r) => r,
+            None => return
+        };
+
+        let mut textures: SmallVec<[gl::types::GLuint; 16]> = SmallVec::new();
+        let mut samplers: SmallVec<[gl::types::GLuint; 16]> = SmallVec::new();
+        for content in ctxt.state.texture_units[first .. last + 1].iter() {
+            textures.push(content.texture);
+            samplers.push(content.sampler);
+        }
+
+        for &(unit, texture, sampler) in self.pending.iter() {
+            let unit = unit as usize;
+            if unit >= first && unit <= last {
+                textures[unit - first] = texture;
+                samplers[unit - first] = sampler;
+            }
+        }
+
+        let textures_changed = ctxt.state.texture_units[first .. last + 1].iter()
+                                   .zip(textures.iter()).any(|(u, &t)| u.texture != t);
+        let samplers_changed = ctxt.state.texture_units[first .. last + 1].iter()
+                                   .zip(samplers.iter()).any(|(u, &s)| u.sampler != s);
+
+        if textures_changed {
+            unsafe {
+                ctxt.gl.BindTextures(first as gl::types::GLuint,
+                                     textures.len() as gl::types::GLsizei, textures.as_ptr());
+            }
+
+            ctxt.frame_statistics.update(|s| s.texture_binds += 1);
+        }
+
+        if samplers_changed {
+            unsafe {
+                ctxt.gl.BindSamplers(first as gl::types::GLuint,
+                                     samplers.len() as gl::types::GLsizei, samplers.as_ptr());
+            }
+        }
+
+        for (offset, (&texture, &sampler)) in textures.iter().zip(samplers.iter()).enumerate() {
+            let unit = &mut ctxt.state.texture_units[first + offset];
+            unit.texture = texture;
+            unit.sampler = sampler;
+        }
+
+        self.pending = SmallVec::new();
+    }
+}
+
+/// Returns true if the value is a depth texture.
+fn is_depth_texture(value: &UniformValue) -> bool {
+    match *value {
+        UniformValue::DepthTexture1d(_, _) | UniformValue::DepthTexture2d(_, _) |
+        UniformValue::DepthTexture2dMultisample(_, _) | UniformValue::DepthTexture3d(_, _) |
+        UniformValue::DepthTexture1dArray(_, _) | UniformValue::DepthTexture2dArray(_, _) |
+        UniformValue::DepthTexture2dMultisampleArray(_, _) |
+        UniformValue::DepthCubemap(_, _) | UniformValue::DepthCubemapArray(_, _) => true,
+        _ => false
+    }
+}
+
+/// Returns true if the uniform type is a shadow sampler.
+fn is_shadow_sampler(ty: UniformType) -> bool {
+    match ty {
+        UniformType::Sampler1dShadow | UniformType::Sampler2dShadow |
+        UniformType::SamplerCubeShadow | UniformType::Sampler1dArrayShadow |
+        UniformType::Sampler2dArrayShadow | UniformType::SamplerCubeArrayShadow |
+        UniformType::Sampler2dRectShadow => true,
+        _ => false
+    }
+}
+
+/// Returns the texture and the sampler of the value, if it is a texture that can be attached to
+/// a framebuffer.
+fn get_sampled_texture<'a>(value: &UniformValue<'a>)
+                           -> Option<(&'a TextureAny, Option<SamplerBehavior>)>
+{
+    macro_rules! textures {
+        ($($variant:ident),+) => (
+            match *value {
+                $(
+                    UniformValue::$variant(texture, sampler) => Some((&**texture, sampler)),
+                )+
+                _ => None
+            }
+        );
+    }
+
+    textures!(Texture1d, CompressedTexture1d, SrgbTexture1d, CompressedSrgbTexture1d,
+              IntegralTexture1d, UnsignedTexture1d, DepthTexture1d, Texture2d,
+              CompressedTexture2d, SrgbTexture2d, CompressedSrgbTexture2d, IntegralTexture2d,
+              UnsignedTexture2d, DepthTexture2d, Texture2dMultisample, SrgbTexture2dMultisample,
+              IntegralTexture2dMultisample, UnsignedTexture2dMultisample,
+              DepthTexture2dMultisample, Texture3d, CompressedTexture3d, SrgbTexture3d,
+              CompressedSrgbTexture3d, IntegralTexture3d, UnsignedTexture3d, DepthTexture3d,
+              Texture1dArray, CompressedTexture1dArray, SrgbTexture1dArray,
+              CompressedSrgbTexture1dArray, IntegralTexture1dArray, UnsignedTexture1dArray,
+              DepthTexture1dArray, Texture2dArray, CompressedTexture2dArray, SrgbTexture2dArray,
+              CompressedSrgbTexture2dArray, IntegralTexture2dArray, UnsignedTexture2dArray,
+              DepthTexture2dArray, Texture2dMultisampleArray, SrgbTexture2dMultisampleArray,
+              IntegralTexture2dMultisampleArray, UnsignedTexture2dMultisampleArray,
+              DepthTexture2dMultisampleArray, Cubemap, CompressedCubemap, SrgbCubemap,
+              CompressedSrgbCubemap, IntegralCubemap, UnsignedCubemap, DepthCubemap,
+              CubemapArray, CompressedCubemapArray, SrgbCubemapArray,
+              CompressedSrgbCubemapArray, IntegralCubemapArray, UnsignedCubemapArray,
+              DepthCubemapArray)
+}
@@ -107,6 +107,16 @@ impl<T> UniformBuffer<T> where T: Copy {
 }
 
 impl<T: ?Sized> UniformBuffer<T> where T: Content {
+    /// Builds a new buffer with the same mode and content as this one.
+    #[inline]
+    pub fn clone_buffer<F>(&self, facade: &F) -> Result<UniformBuffer<T>, BufferCreationError>
+                           where F: Facade
+    {
+        Ok(UniformBuffer {
+            buffer: try!(self.buffer.clone_buffer(facade)),
+        })
+    }
+
     /// Creates an empty buffer.
     ///
     /// # Panic
@@ -88,20 +88,183 @@ let uniforms = uniform! {
 # }
 ```
 
+## Per-object data in a single buffer
+
+Instead of creating one buffer per object, you can store the data of all your objects in a
+single buffer and bind a different slice of it for each draw command. Binding a slice is cheap,
+as glium only calls `glBindBufferRange` if the range has changed.
+
+The offset of the slice must be a multiple of `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`, otherwise
+drawing returns a `UniformBufferOffsetNotAligned` error. You can use
+`Context::align_uniform_buffer_size` to compute the space to reserve for each object, or
+use a `RingBuffer` created with `BufferType::UniformBuffer`, which automatically aligns its
+allocations.
+
+```no_run
+#[macro_use]
+extern crate glium;
+# fn main() {
+# use glium::Surface;
+# let display: glium::Display = unsafe { std::mem::uninitialized() };
+# let program: glium::Program = unsafe { std::mem::uninitialized() };
+# let vertex_buffer: glium::VertexBuffer<u8> = unsafe { std::mem::uninitialized() };
+# let indices: glium::index::NoIndices = unsafe { std::mem::uninitialized() };
+# let mut frame = display.draw();
+#[derive(Copy, Clone)]
+struct PerObject {
+    color: [f32; 4],
+}
+
+implement_uniform_block!(PerObject, color);
+
+// number of elements between two objects
+let stride = display.align_uniform_buffer_size(std::mem::size_of::<PerObject>()) /
+             std::mem::size_of::<PerObject>();
+
+let data = vec![PerObject { color: [0.0; 4] }; 16 * stride];
+let buffer = glium::buffer::Buffer::new(&display, &data[..],
+                                        glium::buffer::BufferType::UniformBuffer,
+                                        glium::buffer::BufferMode::Default).unwrap();
+
+for object in 0 .. 16 {
+    let uniforms = uniform! {
+        PerObject: buffer.as_slice().get(object * stride).unwrap()
+    };
+
+    frame.draw(&vertex_buffer, &indices, &program, &uniforms, &Default::default()).unwrap();
+}
+# }
+```
+
+This only works if the size of an object is a divisor of the alignment. Otherwise, add padding
+at the end of your structure.
+
+## Global blocks
+
+Data that is the same for all the draw commands, like a camera, can be set once on the context
+with `Context::set_global_uniform_block`. Each program that declares a block with this name then uses
+the buffer, without the block being passed to the draw commands. A block of the same name
+passed with the uniforms of a draw command takes precedence over the global block.
+
+```no_run
+#[macro_use]
+extern crate glium;
+# fn main() {
+# let display: glium::Display = unsafe { std::mem::uninitialized() };
+#[derive(Copy, Clone)]
+struct Camera {
+    view: [[f32; 4]; 4],
+    projection: [[f32; 4]; 4],
+}
+
+implement_uniform_block!(Camera, view, projection);
+
+let camera = glium::uniforms::UniformBuffer::new(&display, Camera {
+    view: [[0.0; 4]; 4],
+    projection: [[0.0; 4]; 4],
+}).unwrap();
+
+display.set_global_uniform_block("Camera", &camera).unwrap();
+# }
+```
+
+Each name reserves one binding point, starting from the last one. The layout of the block is
+checked against the content of the buffer when drawing, so a program whose `Camera` block
+doesn't match returns a `UniformBlockLayoutMismatch` error. The buffer must be kept alive as
+long as it is used. Destroying it makes drawing with a program that uses the block return a
+`GlobalUniformBlockUnbound` error until the block is set again.
+
+## Per-draw blocks
+
+Setting many small uniforms with `glUniform` for each draw command can be expensive. If a
+program declares a uniform block named `glium_DrawUniforms` with the `std140` layout and without
+an instance name, glium writes the values passed to the draw command whose names match members
+of this block in a temporary buffer, and binds it with a single call to `glBindBufferRange`.
+The uniforms are passed exactly like regular uniforms, and programs without this block keep
+using `glUniform`.
+
+```no_run
+#[macro_use]
+extern crate glium;
+# fn main() {
+# use glium::Surface;
+# let display: glium::Display = unsafe { std::mem::uninitialized() };
+# let vertex_buffer: glium::VertexBuffer<u8> = unsafe { std::mem::uninitialized() };
+# let indices: glium::index::NoIndices = unsafe { std::mem::uninitialized() };
+# let mut frame = display.draw();
+let program = glium::Program::from_source(&display,
+    "
+        #version 140
+
+        layout(std140) uniform glium_DrawUniforms {
+            mat4 matrix;
+            vec3 color;
+            float alpha;
+        };
+
+        in vec2 position;
+
+        void main() {
+            gl_Position = matrix * vec4(position, 0.0, 1.0);
+        }
+    ",
+    "
+        #version 140
+
+        layout(std140) uniform glium_DrawUniforms {
+            mat4 matrix;
+            vec3 color;
+            float alpha;
+        };
+
+        out vec4 f_color;
+
+        void main() {
+            f_color = vec4(color, alpha);
+        }
+    ",
+    None).unwrap();
+
+let uniforms = uniform! {
+    matrix: [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0f32],
+    ],
+    color: [1.0, 0.0, 0.0f32],
+    alpha: 0.5f32,
+};
+
+frame.draw(&vertex_buffer, &indices, &program, &uniforms, &Default::default()).unwrap();
+# }
+```
+
+The data of all the draw commands is sub-allocated from a buffer that belongs to the context
+and that is reused as a ring. The members of the block that aren't passed to the draw command
+are set to zero. Samplers can't be members of a block and are still bound individually. A
+buffer passed with the name `glium_DrawUniforms` takes precedence over the gathered values.
+
 */
 pub use self::buffer::UniformBuffer;
 pub use self::sampler::{SamplerWrapFunction, MagnifySamplerFilter, MinifySamplerFilter};
 pub use self::sampler::{Sampler, SamplerBehavior};
-pub use self::uniforms::{EmptyUniforms, UniformsStorage};
+pub use self::precomputed::{UniformHandle, PrecomputedUniforms};
+pub use self::uniforms::{EmptyUniforms, UniformsStorage, DynamicUniformsStorage, UniformsChain};
 pub use self::value::{UniformValue, UniformType};
 
+use std::error::Error;
+use std::fmt;
+
 use buffer::Content as BufferContent;
 use buffer::Buffer;
+use buffer::BufferSlice;
 use program;
 use program::BlockLayout;
 
 mod bind;
 mod buffer;
+mod precomputed;
 mod sampler;
 mod uniforms;
 mod value;
@@ -112,6 +275,15 @@ mod value;
 pub trait Uniforms {
     /// Calls the parameter once with the name and value of each uniform.
     fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, F);
+
+    /// Calls the parameter once with the handle and value of each uniform whose location has
+    /// been resolved in advance with `Program::get_uniform_handle`.
+    ///
+    /// These values are bound after the ones of `visit_values`. The default implementation
+    /// doesn't do anything.
+    #[inline]
+    fn visit_handles<'a, F: FnMut(&UniformHandle, UniformValue<'a>)>(&'a self, _: F) {
+    }
 }
 
 /// Error about a block layout mismatch.
@@ -158,6 +330,134 @@ pub enum LayoutMismatchError {
     },
 }
 
+impl fmt::Display for LayoutMismatchError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &LayoutMismatchError::TypeMismatch { ref expected, ref obtained } => {
+                write!(formatter, "{} (expected {:?}, obtained {:?})", self.description(),
+                       expected, obtained)
+            },
+            &LayoutMismatchError::OffsetMismatch { expected, obtained } => {
+                write!(formatter, "{} (expected {}, obtained {})", self.description(),
+                       expected, obtained)
+            },
+            &LayoutMismatchError::MemberMismatch { ref member, ref err } => {
+                write!(formatter, "Mismatch in member `{}`: {}", member, err)
+            },
+            &LayoutMismatchError::MissingField { ref name } => {
+                write!(formatter, "{}: `{}`", self.description(), name)
+            },
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for LayoutMismatchError {
+    fn description(&self) -> &str {
+        match self {
+            &LayoutMismatchError::TypeMismatch { .. } => "Mismatch in the type of an element",
+            &LayoutMismatchError::LayoutMismatch { .. } => "The layout of the data is totally \
+                                                            different from the expected layout",
+            &LayoutMismatchError::OffsetMismatch { .. } => "Mismatch in the offset of a member",
+            &LayoutMismatchError::MemberMismatch { .. } => "Mismatch in a member of the layout",
+            &LayoutMismatchError::MissingField { .. } => "A field is missing",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match self {
+            &LayoutMismatchError::MemberMismatch { ref err, .. } => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
+/// Reason why a texture can't be sampled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IncompleteTextureReason {
+    /// The minification filter uses mipmaps, but the texture only has one level and the backend
+    /// doesn't allow restricting the sampling to this level.
+    MissingMipmaps,
+
+    /// The texture has an integral or unsigned format, which can only be sampled with the
+    /// `Nearest` and `NearestMipmapNearest` filters.
+    IntegralTextureWithLinearFiltering,
+
+    /// The texture is a depth texture and is bound to a shadow sampler, but depth comparison is
+    /// not enabled on the texture.
+    DepthComparisonDisabled,
+
+    /// A streaming write to the texture is in progress or has been interrupted, and the content
+    /// of the texture is partially undefined.
+    IncompleteUpload,
+}
+
+impl fmt::Display for IncompleteTextureReason {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for IncompleteTextureReason {
+    fn description(&self) -> &str {
+        match self {
+            &IncompleteTextureReason::MissingMipmaps => "The minification filter requires \
+                                                         mipmaps, but the texture doesn't \
+                                                         have any",
+            &IncompleteTextureReason::IntegralTextureWithLinearFiltering => "Integral textures \
+                                                                             can't be sampled \
+                                                                             with linear \
+                                                                             filtering",
+            &IncompleteTextureReason::DepthComparisonDisabled => "Depth textures can't be \
+                                                                  sampled by shadow samplers \
+                                                                  without depth comparison",
+            &IncompleteTextureReason::IncompleteUpload => "The upload of the texture is not \
+                                                           finished or has been interrupted",
+        }
+    }
+}
+
+/// Error that can happen when setting a global uniform block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlobalUniformBlockError {
+    /// Uniform buffers are not supported by the backend.
+    NotSupported,
+
+    /// The value is not a buffer.
+    NotABuffer,
+
+    /// The offset of the buffer slice is not a multiple of
+    /// `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`.
+    OffsetNotAligned,
+
+    /// All the binding points of the backend are already reserved.
+    TooManyBlocks,
+}
+
+impl fmt::Display for GlobalUniformBlockError {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for GlobalUniformBlockError {
+    fn description(&self) -> &str {
+        match self {
+            &GlobalUniformBlockError::NotSupported => "Uniform buffers are not supported by \
+                                                       the backend",
+            &GlobalUniformBlockError::NotABuffer => "Only buffers can be used as global \
+                                                     uniform blocks",
+            &GlobalUniformBlockError::OffsetNotAligned => "The offset of the buffer is not a \
+                                                           multiple of the uniform buffer \
+                                                           offset alignment",
+            &GlobalUniformBlockError::TooManyBlocks => "All the uniform buffer binding points \
+                                                        are already reserved",
+        }
+    }
+}
+
 /// Value that can be used as the value of a uniform.
 ///
 /// This includes buffers and textures for example.
@@ -166,7 +466,6 @@ pub trait AsUniformValue {
     fn as_uniform_value(&self) -> UniformValue;
 }
 
-// TODO: no way to bind a slice
 impl<'a, T: ?Sized> AsUniformValue for &'a Buffer<T> where T: UniformBlock + BufferContent {
     #[inline]
     fn as_uniform_value(&self) -> UniformValue {
@@ -182,6 +481,22 @@ impl<'a, T: ?Sized> AsUniformValue for &'a Buffer<T> where T: UniformBlock + Buf
     }
 }
 
+impl<'a, T: ?Sized> AsUniformValue for BufferSlice<'a, T>
+    where T: UniformBlock + BufferContent
+{
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        #[inline]
+        fn f<T: ?Sized>(block: &program::UniformBlock)
+                        -> Result<(), LayoutMismatchError> where T: UniformBlock + BufferContent
+        {
+            T::matches(&block.layout, 0)
+        }
+
+        UniformValue::Block(self.as_slice_any(), f::<T>)
+    }
+}
+
 /// Objects that are suitable for being inside a uniform block or a SSBO.
 pub trait UniformBlock {        // TODO: `: Copy`, but unsized structs don't impl `Copy`
     /// Checks whether the uniforms' layout matches the given block if `Self` starts at
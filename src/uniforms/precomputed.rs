@@ -0,0 +1,157 @@
+use Handle;
+use GlObject;
+
+use program::Uniform;
+
+use uniforms::{Uniforms, UniformValue, UniformType, AsUniformValue};
+
+/// Identifies a uniform of a specific program.
+///
+/// Obtained with `Program::get_uniform_handle`. Binding a value with a handle doesn't require
+/// looking up the name of the uniform in the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformHandle {
+    program: Handle,
+    generation: u32,
+    name: String,
+    location: i32,
+    ty: UniformType,
+    size: Option<usize>,
+}
+
+impl UniformHandle {
+    /// Builds a handle for a uniform of a program.
+    #[doc(hidden)]
+    #[inline]
+    pub fn new(program: Handle, generation: u32, name: &str, uniform: &Uniform)
+               -> UniformHandle
+    {
+        UniformHandle {
+            program: program,
+            generation: generation,
+            name: name.to_owned(),
+            location: uniform.location,
+            ty: uniform.ty,
+            size: uniform.size,
+        }
+    }
+
+    /// Returns the name of the uniform.
+    #[inline]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the identifier of the program that this handle belongs to.
+    #[inline]
+    pub fn get_program_id(&self) -> Handle {
+        self.program
+    }
+
+    /// Returns the number of times the program had been reloaded when this handle was
+    /// obtained.
+    ///
+    /// Handles that were obtained before `Program::reload_from_source` are rejected when
+    /// drawing.
+    #[inline]
+    pub fn get_program_generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns informations about the uniform.
+    #[inline]
+    pub fn get_uniform(&self) -> Uniform {
+        Uniform {
+            location: self.location,
+            ty: self.ty,
+            size: self.size,
+        }
+    }
+}
+
+/// Uniforms whose locations have been resolved in advance.
+///
+/// Contrary to the other implementations of `Uniforms`, the names of the uniforms are never
+/// looked up when drawing. The values are bound to the locations stored in the handles.
+///
+/// Drawing with a program other than the one the handles have been obtained from returns
+/// a `DrawError::UniformHandleProgramMismatch`.
+///
+/// ## Example
+///
+/// ```no_run
+/// # use glium::Surface;
+/// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+/// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+/// # let vertex_buffer: glium::VertexBuffer<u8> = unsafe { std::mem::uninitialized() };
+/// # let indices: glium::index::NoIndices = unsafe { std::mem::uninitialized() };
+/// # let mut frame = display.draw();
+/// let handle = program.get_uniform_handle("view_proj").unwrap();
+/// let matrix = [[1.0f32, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0],
+///               [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+///
+/// let uniforms = glium::uniforms::PrecomputedUniforms::new(&program).add(&handle, &matrix);
+/// frame.draw(&vertex_buffer, &indices, &program, &uniforms, &Default::default()).unwrap();
+/// ```
+pub struct PrecomputedUniforms<'a> {
+    program: Handle,
+    values: Vec<(UniformHandle, UniformValue<'a>)>,
+}
+
+impl<'a> PrecomputedUniforms<'a> {
+    /// Builds an empty list of uniforms for the given program.
+    #[inline]
+    pub fn new<P>(program: &P) -> PrecomputedUniforms<'a> where P: GlObject<Id = Handle> {
+        PrecomputedUniforms {
+            program: program.get_id(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Adds a value.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the handle doesn't belong to the program of this object.
+    #[inline]
+    pub fn add<U>(mut self, handle: &UniformHandle, value: &'a U) -> PrecomputedUniforms<'a>
+                  where U: AsUniformValue
+    {
+        self.set(handle, value);
+        self
+    }
+
+    /// Changes the value of a uniform, or adds it if it doesn't exist.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the handle doesn't belong to the program of this object.
+    pub fn set<U>(&mut self, handle: &UniformHandle, value: &'a U) where U: AsUniformValue {
+        assert!(handle.program == self.program,
+                "The uniform handle doesn't belong to the program of these uniforms");
+
+        let value = value.as_uniform_value();
+
+        for &mut (ref h, ref mut v) in self.values.iter_mut() {
+            if h.location == handle.location {
+                *v = value;
+                return;
+            }
+        }
+
+        self.values.push((handle.clone(), value));
+    }
+}
+
+impl<'v> Uniforms for PrecomputedUniforms<'v> {
+    #[inline]
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, _: F) {
+    }
+
+    #[inline]
+    fn visit_handles<'a, F: FnMut(&UniformHandle, UniformValue<'a>)>(&'a self, mut output: F) {
+        for &(ref handle, ref value) in self.values.iter() {
+            output(handle, value.clone());
+        }
+    }
+}
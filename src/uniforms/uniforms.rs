@@ -1,9 +1,31 @@
-use uniforms::{Uniforms, UniformValue, AsUniformValue};
+use uniforms::{Uniforms, UniformValue, AsUniformValue, UniformHandle};
 
 /// Object that can be used when you don't have any uniforms.
 #[derive(Debug, Copy, Clone)]
 pub struct EmptyUniforms;
 
+impl EmptyUniforms {
+    /// Adds a value.
+    #[inline]
+    pub fn add<'n, U>(self, name: &'n str, value: U) -> UniformsStorage<'n, U, EmptyUniforms>
+                      where U: AsUniformValue
+    {
+        UniformsStorage::new(name, value)
+    }
+
+    /// Adds a value whose name is only known at runtime.
+    #[inline]
+    pub fn add_dynamic<S, U>(self, name: S, value: U) -> DynamicUniformsStorage<U, EmptyUniforms>
+                             where S: Into<String>, U: AsUniformValue
+    {
+        DynamicUniformsStorage {
+            name: name.into(),
+            value: value,
+            rest: self,
+        }
+    }
+}
+
 impl Uniforms for EmptyUniforms {
     #[inline]
     fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, _: F) {
@@ -44,6 +66,19 @@ impl<'n, T, R> UniformsStorage<'n, T, R> where T: AsUniformValue, R: Uniforms {
             rest: self,
         }
     }
+
+    /// Adds a value whose name is only known at runtime.
+    #[inline]
+    pub fn add_dynamic<S, U>(self, name: S, value: U)
+                             -> DynamicUniformsStorage<U, UniformsStorage<'n, T, R>>
+                             where S: Into<String>, U: AsUniformValue
+    {
+        DynamicUniformsStorage {
+            name: name.into(),
+            value: value,
+            rest: self,
+        }
+    }
 }
 
 impl<'n, T, R> Uniforms for UniformsStorage<'n, T, R> where T: AsUniformValue, R: Uniforms {
@@ -53,3 +88,80 @@ impl<'n, T, R> Uniforms for UniformsStorage<'n, T, R> where T: AsUniformValue, R
         self.rest.visit_values(output);
     }
 }
+
+/// Stores uniforms. Contrary to `UniformsStorage`, the name of the last value is owned.
+pub struct DynamicUniformsStorage<T, R> where T: AsUniformValue, R: Uniforms {
+    name: String,
+    value: T,
+    rest: R,
+}
+
+impl<T, R> DynamicUniformsStorage<T, R> where T: AsUniformValue, R: Uniforms {
+    /// Adds a value to the storage.
+    #[inline]
+    pub fn add<'n, U>(self, name: &'n str, value: U)
+                      -> UniformsStorage<'n, U, DynamicUniformsStorage<T, R>>
+                      where U: AsUniformValue
+    {
+        UniformsStorage {
+            name: name,
+            value: value,
+            rest: self,
+        }
+    }
+
+    /// Adds a value whose name is only known at runtime.
+    #[inline]
+    pub fn add_dynamic<S, U>(self, name: S, value: U)
+                             -> DynamicUniformsStorage<U, DynamicUniformsStorage<T, R>>
+                             where S: Into<String>, U: AsUniformValue
+    {
+        DynamicUniformsStorage {
+            name: name.into(),
+            value: value,
+            rest: self,
+        }
+    }
+}
+
+impl<T, R> Uniforms for DynamicUniformsStorage<T, R> where T: AsUniformValue, R: Uniforms {
+    #[inline]
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        output(&self.name[..], self.value.as_uniform_value());
+        self.rest.visit_values(output);
+    }
+}
+
+/// Combines two objects that contain uniforms.
+///
+/// If both objects contain a value with the same name, the value of `overrides` is used.
+pub struct UniformsChain<B, O> where B: Uniforms, O: Uniforms {
+    base: B,
+    overrides: O,
+}
+
+impl<B, O> UniformsChain<B, O> where B: Uniforms, O: Uniforms {
+    /// Builds a new chain.
+    #[inline]
+    pub fn new(base: B, overrides: O) -> UniformsChain<B, O> {
+        UniformsChain {
+            base: base,
+            overrides: overrides,
+        }
+    }
+}
+
+impl<B, O> Uniforms for UniformsChain<B, O> where B: Uniforms, O: Uniforms {
+    #[inline]
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        // values that are visited last have priority
+        self.base.visit_values(&mut output);
+        self.overrides.visit_values(output);
+    }
+
+    #[inline]
+    fn visit_handles<'a, F: FnMut(&UniformHandle, UniformValue<'a>)>(&'a self, mut output: F) {
+        self.base.visit_handles(&mut output);
+        self.overrides.visit_handles(output);
+    }
+}
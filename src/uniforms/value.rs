@@ -155,6 +155,10 @@ pub enum UniformValue<'a> {
     Mat3([[f32; 3]; 3]),
     /// 4x4 column-major matrix.
     Mat4([[f32; 4]; 4]),
+    /// Array of 3x3 column-major matrices, bound to a uniform array with a single call.
+    Mat3Array(&'a [[[f32; 3]; 3]]),
+    /// Array of 4x4 column-major matrices, bound to a uniform array with a single call.
+    Mat4Array(&'a [[[f32; 4]; 4]]),
     Vec2([f32; 2]),
     Vec3([f32; 3]),
     Vec4([f32; 4]),
@@ -263,6 +267,8 @@ impl<'a> UniformValue<'a> {
             (&UniformValue::Mat2(_), UniformType::FloatMat2) => true,
             (&UniformValue::Mat3(_), UniformType::FloatMat3) => true,
             (&UniformValue::Mat4(_), UniformType::FloatMat4) => true,
+            (&UniformValue::Mat3Array(_), UniformType::FloatMat3) => true,
+            (&UniformValue::Mat4Array(_), UniformType::FloatMat4) => true,
             (&UniformValue::Vec2(_), UniformType::FloatVec2) => true,
             (&UniformValue::Vec3(_), UniformType::FloatVec3) => true,
             (&UniformValue::Vec4(_), UniformType::FloatVec4) => true,
@@ -643,6 +649,34 @@ impl AsUniformValue for [[f32; 4]; 4] {
 
 impl_uniform_block_basic!([[f32; 4]; 4], UniformType::FloatMat4);
 
+impl<'a> AsUniformValue for &'a [[[f32; 3]; 3]] {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::Mat3Array(*self)
+    }
+}
+
+impl AsUniformValue for Vec<[[f32; 3]; 3]> {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::Mat3Array(&self[..])
+    }
+}
+
+impl<'a> AsUniformValue for &'a [[[f32; 4]; 4]] {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::Mat4Array(*self)
+    }
+}
+
+impl AsUniformValue for Vec<[[f32; 4]; 4]> {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::Mat4Array(&self[..])
+    }
+}
+
 impl AsUniformValue for (f32, f32) {
     #[inline]
     fn as_uniform_value(&self) -> UniformValue {
@@ -0,0 +1,76 @@
+//! Conversions between window coordinates and framebuffer coordinates.
+//!
+//! Window coordinates are in logical units, with the origin at the top-left corner of the
+//! window. Framebuffer coordinates are in pixels, with the origin at the bottom-left corner.
+
+use std::cmp;
+
+use Rect;
+
+/// Returns the framebuffer pixel that contains the given window position.
+///
+/// The result is outside of the framebuffer if the position is outside of the window.
+pub fn window_to_framebuffer(window_dimensions: (u32, u32), framebuffer_dimensions: (u32, u32),
+                             position: (f64, f64)) -> (i32, i32)
+{
+    let scale_x = framebuffer_dimensions.0 as f64 / cmp::max(window_dimensions.0, 1) as f64;
+    let scale_y = framebuffer_dimensions.1 as f64 / cmp::max(window_dimensions.1, 1) as f64;
+
+    let x = (position.0 * scale_x).floor() as i32;
+    let y = framebuffer_dimensions.1 as i32 - 1 - (position.1 * scale_y).floor() as i32;
+    (x, y)
+}
+
+/// Returns a rectangle of `size` pixels centered on the framebuffer pixel that contains the
+/// given window position, clamped to the dimensions of the framebuffer.
+///
+/// The rectangle is empty if it is entirely outside of the framebuffer.
+pub fn pick_rect(window_dimensions: (u32, u32), framebuffer_dimensions: (u32, u32),
+                 position: (f64, f64), size: (u32, u32)) -> Rect
+{
+    let (x, y) = window_to_framebuffer(window_dimensions, framebuffer_dimensions, position);
+
+    let left = x - (size.0 / 2) as i32;
+    let bottom = y - (size.1 / 2) as i32;
+    let right = cmp::min(left + size.0 as i32, framebuffer_dimensions.0 as i32);
+    let top = cmp::min(bottom + size.1 as i32, framebuffer_dimensions.1 as i32);
+
+    let left = cmp::max(left, 0);
+    let bottom = cmp::max(bottom, 0);
+
+    Rect {
+        left: left,
+        bottom: bottom,
+        width: cmp::max(right - left, 0) as u32,
+        height: cmp::max(top - bottom, 0) as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Rect;
+    use super::{pick_rect, window_to_framebuffer};
+
+    #[test]
+    fn corners_hidpi() {
+        let window = (400, 300);
+        let framebuffer = (800, 600);
+
+        assert_eq!(window_to_framebuffer(window, framebuffer, (0.0, 0.0)), (0, 599));
+        assert_eq!(window_to_framebuffer(window, framebuffer, (399.9, 0.0)), (799, 599));
+        assert_eq!(window_to_framebuffer(window, framebuffer, (0.0, 299.9)), (0, 0));
+        assert_eq!(window_to_framebuffer(window, framebuffer, (399.9, 299.9)), (799, 0));
+    }
+
+    #[test]
+    fn pick_rect_clamped() {
+        let rect = pick_rect((400, 300), (800, 600), (0.0, 0.0), (4, 4));
+        assert_eq!(rect, Rect { left: 0, bottom: 597, width: 2, height: 3 });
+    }
+
+    #[test]
+    fn pick_rect_outside() {
+        let rect = pick_rect((400, 300), (800, 600), (-100.0, 50.0), (4, 4));
+        assert_eq!(rect.width, 0);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod bitsfield;
+pub mod coords;
 pub mod range;
@@ -1,14 +1,20 @@
 use std::error::Error;
 use std::fmt;
+use std::io;
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 use utils::range::RangeArgument;
 
 use buffer::{Buffer, BufferSlice, BufferMutSlice, BufferAny, BufferType, BufferMode, BufferCreationError, Content};
+use buffer::DetachedAlloc;
 use vertex::{Vertex, VerticesSource, IntoVerticesSource, PerInstance};
-use vertex::format::VertexFormat;
+use vertex::format::{self, VertexFormat};
 
 use backend::Facade;
+use context::Context;
+use sync::{DeferredDestroy, DeferredObject};
 use version::{Api, Version};
+use BufferAnyExt;
 use CapabilitiesSource;
 
 /// Error that can happen when creating a vertex buffer.
@@ -19,6 +25,9 @@ pub enum CreationError {
     /// Anything 64bits-related may not be supported.
     FormatNotSupported,
 
+    /// Two elements of the vertex format have been given the same explicit location.
+    ConflictingLocations,
+
     /// Error while creating the vertex buffer.
     BufferCreationError(BufferCreationError),
 }
@@ -35,6 +44,8 @@ impl fmt::Display for CreationError {
         match self {
             &CreationError::FormatNotSupported => "The vertex format is not supported by the \
                                                    backend".fmt(formatter),
+            &CreationError::ConflictingLocations => "Two elements of the vertex format have the \
+                                                     same location".fmt(formatter),
             &CreationError::BufferCreationError(error) => error.fmt(formatter),
         }
     }
@@ -45,6 +56,8 @@ impl Error for CreationError {
         match self {
             &CreationError::FormatNotSupported => "The vertex format is not supported by the \
                                                    backend",
+            &CreationError::ConflictingLocations => "Two elements of the vertex format have the \
+                                                     same location",
             &CreationError::BufferCreationError(..) => "Error while creating the vertex buffer",
         }
     }
@@ -52,6 +65,7 @@ impl Error for CreationError {
     fn cause(&self) -> Option<&Error> {
         match self {
             &CreationError::FormatNotSupported => None,
+            &CreationError::ConflictingLocations => None,
             &CreationError::BufferCreationError(ref error) => Some(error),
         }
     }
@@ -162,10 +176,51 @@ impl<T> VertexBuffer<T> where T: Vertex {
             return Err(CreationError::FormatNotSupported);
         }
 
+        if format::has_conflicting_locations(&<T as Vertex>::build_bindings()) {
+            return Err(CreationError::ConflictingLocations);
+        }
+
         let buffer = try!(Buffer::new(facade, data, BufferType::ArrayBuffer, mode));
         Ok(buffer.into())
     }
 
+    /// Builds a new vertex buffer by uploading the data in chunks of at most `chunk_bytes`
+    /// bytes.
+    ///
+    /// This avoids a staging area as large as the data, which matters for very large sources
+    /// like a memory-mapped file. See `Buffer::new_streamed`.
+    #[inline]
+    pub fn new_streamed<F>(facade: &F, data: &[T], chunk_bytes: usize)
+                           -> Result<VertexBuffer<T>, CreationError>
+                           where F: Facade
+    {
+        VertexBuffer::new_streamed_with_progress(facade, data, chunk_bytes, |_, _| true)
+    }
+
+    /// Same as `new_streamed`, but calls `progress` after each chunk with the number of bytes
+    /// that have been uploaded and the total number of bytes.
+    ///
+    /// If `progress` returns `false`, the upload stops and the buffer is returned anyway. Its
+    /// content is partially undefined and drawing with it returns
+    /// `DrawError::IncompleteBuffer`. You can check this with `is_complete`.
+    pub fn new_streamed_with_progress<F, P>(facade: &F, data: &[T], chunk_bytes: usize,
+                                            progress: P)
+                                            -> Result<VertexBuffer<T>, CreationError>
+                                            where F: Facade, P: FnMut(usize, usize) -> bool
+    {
+        if !T::is_supported(facade) {
+            return Err(CreationError::FormatNotSupported);
+        }
+
+        if format::has_conflicting_locations(&<T as Vertex>::build_bindings()) {
+            return Err(CreationError::ConflictingLocations);
+        }
+
+        let buffer = try!(Buffer::new_streamed(facade, data, BufferType::ArrayBuffer,
+                                               chunk_bytes, progress));
+        Ok(buffer.into())
+    }
+
     /// Builds an empty vertex buffer.
     ///
     /// The parameter indicates the number of elements.
@@ -216,6 +271,10 @@ impl<T> VertexBuffer<T> where T: Vertex {
             return Err(CreationError::FormatNotSupported);
         }
 
+        if format::has_conflicting_locations(&<T as Vertex>::build_bindings()) {
+            return Err(CreationError::ConflictingLocations);
+        }
+
         let buffer = try!(Buffer::empty_array(facade, BufferType::ArrayBuffer, elements, mode));
         Ok(buffer.into())
     }
@@ -234,10 +293,10 @@ impl<T> VertexBuffer<T> where T: Copy {
     ///
     /// let bindings = Cow::Owned(vec![(
     ///         Cow::Borrowed("position"), 0,
-    ///         glium::vertex::AttributeType::F32F32,
+    ///         glium::vertex::AttributeType::F32F32, None, None,
     ///     ), (
     ///         Cow::Borrowed("color"), 2 * ::std::mem::size_of::<f32>(),
-    ///         glium::vertex::AttributeType::F32,
+    ///         glium::vertex::AttributeType::F32, None, None,
     ///     ),
     /// ]);
     ///
@@ -261,6 +320,10 @@ impl<T> VertexBuffer<T> where T: Copy {
     {
         // FIXME: check that the format is supported
 
+        if format::has_conflicting_locations(&bindings) {
+            return Err(CreationError::ConflictingLocations);
+        }
+
         Ok(VertexBuffer {
             buffer: try!(Buffer::new(facade, data, BufferType::ArrayBuffer,
                                          BufferMode::Default)),
@@ -277,6 +340,10 @@ impl<T> VertexBuffer<T> where T: Copy {
     {
         // FIXME: check that the format is supported
 
+        if format::has_conflicting_locations(&bindings) {
+            return Err(CreationError::ConflictingLocations);
+        }
+
         Ok(VertexBuffer {
             buffer: try!(Buffer::new(facade, data, BufferType::ArrayBuffer,
                                          BufferMode::Dynamic)),
@@ -306,6 +373,17 @@ impl<T> VertexBuffer<T> where T: Copy {
         &self.bindings
     }
 
+    /// Builds a new vertex buffer with the same mode, bindings and content as this one.
+    #[inline]
+    pub fn clone_buffer<F>(&self, facade: &F) -> Result<VertexBuffer<T>, BufferCreationError>
+                           where F: Facade
+    {
+        Ok(VertexBuffer {
+            buffer: try!(self.buffer.clone_buffer(facade)),
+            bindings: self.bindings.clone(),
+        })
+    }
+
     /// Creates a marker that instructs glium to use multiple instances.
     ///
     /// Instead of calling `surface.draw(&vertex_buffer, ...)` you can call
@@ -351,6 +429,28 @@ impl<T> From<Buffer<[T]>> for VertexBuffer<T> where T: Vertex + Copy {
     }
 }
 
+impl<T> DeferredDestroy for VertexBuffer<T> where T: Copy {
+    type Detached = (DetachedAlloc, VertexFormat);
+
+    #[inline]
+    fn detach(self) -> (DetachedAlloc, VertexFormat) {
+        (self.buffer.detach(), self.bindings)
+    }
+
+    #[inline]
+    fn attach(detached: (DetachedAlloc, VertexFormat), context: &Rc<Context>) -> VertexBuffer<T> {
+        VertexBuffer {
+            buffer: Buffer::attach(detached.0, context),
+            bindings: detached.1,
+        }
+    }
+
+    #[inline]
+    fn into_deferred_object(detached: (DetachedAlloc, VertexFormat)) -> DeferredObject {
+        DeferredObject::Buffer(detached.0)
+    }
+}
+
 impl<T> Deref for VertexBuffer<T> where T: Copy {
     type Target = Buffer<[T]>;
 
@@ -434,6 +534,79 @@ pub struct VertexBufferAny {
 }
 
 impl VertexBufferAny {
+    /// Builds a new vertex buffer from raw bytes and bindings.
+    ///
+    /// Contrary to `VertexBuffer::new_raw`, the number of bytes between two consecutive vertices
+    /// is given by `elements_size` instead of the type of the data. This is useful when the
+    /// format is loaded at runtime, for example with `deserialize_vertex_format`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is not a multiple of `elements_size`.
+    pub unsafe fn new_raw<F>(facade: &F, data: &[u8], bindings: VertexFormat,
+                             elements_size: usize) -> Result<VertexBufferAny, CreationError>
+                             where F: Facade
+    {
+        assert!(elements_size != 0 && data.len() % elements_size == 0);
+        try!(check_raw_bindings(facade, &bindings));
+
+        let buffer = try!(Buffer::new(facade, data, BufferType::ArrayBuffer,
+                                      BufferMode::Default));
+        let mut buffer: BufferAny = buffer.into();
+        buffer.set_elements_size(elements_size);
+
+        Ok(VertexBufferAny {
+            buffer: buffer,
+            bindings: bindings,
+        })
+    }
+
+    /// Builds a new vertex buffer of `len` vertices whose raw bytes are read from `reader`.
+    ///
+    /// The data is streamed in chunks of at most `TRANSFER_CHUNK_SIZE` bytes, so the whole
+    /// content is never held in memory at once. The bytes must be in the native endianness
+    /// of the machine.
+    ///
+    /// Errors while creating the buffer are returned as an `io::Error` of kind `Other`.
+    pub unsafe fn upload_from_reader<F, R: ?Sized>(facade: &F, reader: &mut R,
+                                                   bindings: VertexFormat, elements_size: usize,
+                                                   len: usize) -> io::Result<VertexBufferAny>
+                                                   where F: Facade, R: io::Read
+    {
+        assert!(elements_size != 0);
+
+        if let Err(err) = check_raw_bindings(facade, &bindings) {
+            return Err(io::Error::new(io::ErrorKind::Other, err));
+        }
+
+        let buffer = match Buffer::<[u8]>::empty_array(facade, BufferType::ArrayBuffer,
+                                                       len * elements_size, BufferMode::Default)
+        {
+            Ok(b) => b,
+            Err(err) => return Err(io::Error::new(io::ErrorKind::Other,
+                                                  CreationError::BufferCreationError(err))),
+        };
+
+        let mut buffer: BufferAny = buffer.into();
+        buffer.set_elements_size(elements_size);
+        try!(buffer.upload_from_reader(reader));
+
+        Ok(VertexBufferAny {
+            buffer: buffer,
+            bindings: bindings,
+        })
+    }
+
+    /// Writes the raw content of the buffer to `writer`.
+    ///
+    /// See `Buffer::download_to_writer`.
+    #[inline]
+    pub fn download_to_writer<W: ?Sized>(&self, writer: &mut W) -> io::Result<()>
+                                         where W: io::Write
+    {
+        self.buffer.download_to_writer(writer)
+    }
+
     /// Returns the number of bytes between two consecutive elements in the buffer.
     #[inline]
     pub fn get_elements_size(&self) -> usize {
@@ -502,3 +675,20 @@ impl<'a> IntoVerticesSource<'a> for &'a VertexBufferAny {
 /// Instancing is not supported by the backend.
 #[derive(Debug, Copy, Clone)]
 pub struct InstancingNotSupported;
+
+/// Checks that bindings passed by the user can be used to create a vertex buffer.
+fn check_raw_bindings<F>(facade: &F, bindings: &VertexFormat) -> Result<(), CreationError>
+                         where F: Facade
+{
+    if format::has_conflicting_locations(bindings) {
+        return Err(CreationError::ConflictingLocations);
+    }
+
+    for &(_, _, ty, _, _) in bindings.iter() {
+        if !ty.is_supported(facade) {
+            return Err(CreationError::FormatNotSupported);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use std::borrow::Cow;
+
+use vertex::{IntoVerticesSource, VerticesSource};
+
+/// Value of a vertex attribute that is not provided by any buffer.
+///
+/// Missing components are filled with `0` for the second and third components and with `1`
+/// for the fourth one, like OpenGL does.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AttributeDefault {
+    /// Value of an attribute of type `float`, `vec2`, `vec3` or `vec4`.
+    Float([f32; 4]),
+
+    /// Value of an attribute of type `int`, `ivec2`, `ivec3` or `ivec4`.
+    Int([i32; 4]),
+
+    /// Value of an attribute of type `uint`, `uvec2`, `uvec3` or `uvec4`.
+    UnsignedInt([u32; 4]),
+}
+
+impl From<f32> for AttributeDefault {
+    #[inline]
+    fn from(v: f32) -> AttributeDefault {
+        AttributeDefault::Float([v, 0.0, 0.0, 1.0])
+    }
+}
+
+impl From<[f32; 2]> for AttributeDefault {
+    #[inline]
+    fn from(v: [f32; 2]) -> AttributeDefault {
+        AttributeDefault::Float([v[0], v[1], 0.0, 1.0])
+    }
+}
+
+impl From<[f32; 3]> for AttributeDefault {
+    #[inline]
+    fn from(v: [f32; 3]) -> AttributeDefault {
+        AttributeDefault::Float([v[0], v[1], v[2], 1.0])
+    }
+}
+
+impl From<[f32; 4]> for AttributeDefault {
+    #[inline]
+    fn from(v: [f32; 4]) -> AttributeDefault {
+        AttributeDefault::Float(v)
+    }
+}
+
+impl From<i32> for AttributeDefault {
+    #[inline]
+    fn from(v: i32) -> AttributeDefault {
+        AttributeDefault::Int([v, 0, 0, 1])
+    }
+}
+
+impl From<[i32; 2]> for AttributeDefault {
+    #[inline]
+    fn from(v: [i32; 2]) -> AttributeDefault {
+        AttributeDefault::Int([v[0], v[1], 0, 1])
+    }
+}
+
+impl From<[i32; 3]> for AttributeDefault {
+    #[inline]
+    fn from(v: [i32; 3]) -> AttributeDefault {
+        AttributeDefault::Int([v[0], v[1], v[2], 1])
+    }
+}
+
+impl From<[i32; 4]> for AttributeDefault {
+    #[inline]
+    fn from(v: [i32; 4]) -> AttributeDefault {
+        AttributeDefault::Int(v)
+    }
+}
+
+impl From<u32> for AttributeDefault {
+    #[inline]
+    fn from(v: u32) -> AttributeDefault {
+        AttributeDefault::UnsignedInt([v, 0, 0, 1])
+    }
+}
+
+impl From<[u32; 2]> for AttributeDefault {
+    #[inline]
+    fn from(v: [u32; 2]) -> AttributeDefault {
+        AttributeDefault::UnsignedInt([v[0], v[1], 0, 1])
+    }
+}
+
+impl From<[u32; 3]> for AttributeDefault {
+    #[inline]
+    fn from(v: [u32; 3]) -> AttributeDefault {
+        AttributeDefault::UnsignedInt([v[0], v[1], v[2], 1])
+    }
+}
+
+impl From<[u32; 4]> for AttributeDefault {
+    #[inline]
+    fn from(v: [u32; 4]) -> AttributeDefault {
+        AttributeDefault::UnsignedInt(v)
+    }
+}
+
+/// List of values to use for the attributes that the program expects but that none of the
+/// vertex buffers provide.
+///
+/// Can be passed as a vertex source alongside the buffers. You are encouraged to use the
+/// `vertex_defaults!` macro to build this.
+#[derive(Clone, Debug, Default)]
+pub struct VertexDefaults {
+    values: Vec<(Cow<'static, str>, AttributeDefault)>,
+}
+
+impl VertexDefaults {
+    /// Builds an empty list of default values.
+    #[inline]
+    pub fn new() -> VertexDefaults {
+        VertexDefaults {
+            values: Vec::new(),
+        }
+    }
+
+    /// Adds a default value for the attribute with the given name.
+    ///
+    /// If a value was already present for this name, it is replaced.
+    #[inline]
+    pub fn add<S, V>(mut self, name: S, value: V) -> VertexDefaults
+                     where S: Into<Cow<'static, str>>, V: Into<AttributeDefault>
+    {
+        let name = name.into();
+        let value = value.into();
+
+        if let Some(entry) = self.values.iter_mut().find(|&&mut (ref n, _)| *n == name) {
+            entry.1 = value;
+            return self;
+        }
+
+        self.values.push((name, value));
+        self
+    }
+
+    /// Returns the default value of an attribute, if any.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<AttributeDefault> {
+        self.values.iter().find(|&&(ref n, _)| n == name).map(|&(_, v)| v)
+    }
+
+    /// Iterates over the attributes names and their default values.
+    #[inline]
+    pub fn iter(&self) -> ::std::slice::Iter<(Cow<'static, str>, AttributeDefault)> {
+        self.values.iter()
+    }
+}
+
+impl<'a> IntoVerticesSource<'a> for &'a VertexDefaults {
+    #[inline]
+    fn into_vertices_source(self) -> VerticesSource<'a> {
+        VerticesSource::Defaults(self)
+    }
+}
@@ -6,13 +6,15 @@ use version::Api;
 use version::Version;
 use CapabilitiesSource;
 
+#[cfg(feature = "half")]
+use half::f16;
 #[cfg(feature = "cgmath")]
 use cgmath;
 #[cfg(feature = "nalgebra")]
 use nalgebra;
 
 #[allow(missing_docs)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AttributeType {
     I8,
     I8I8,
@@ -30,6 +32,54 @@ pub enum AttributeType {
     U16U16,
     U16U16U16,
     U16U16U16U16,
+    /// Same as `I8`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I8Normalized,
+    /// Same as `I8I8`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I8I8Normalized,
+    /// Same as `I8I8I8`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I8I8I8Normalized,
+    /// Same as `I8I8I8I8`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I8I8I8I8Normalized,
+    /// Same as `U8`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U8Normalized,
+    /// Same as `U8U8`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U8U8Normalized,
+    /// Same as `U8U8U8`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U8U8U8Normalized,
+    /// Same as `U8U8U8U8`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U8U8U8U8Normalized,
+    /// Same as `I16`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I16Normalized,
+    /// Same as `I16I16`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I16I16Normalized,
+    /// Same as `I16I16I16`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I16I16I16Normalized,
+    /// Same as `I16I16I16I16`, but the values are normalized to `[-1.0, 1.0]` when read by a floating-point
+    /// attribute.
+    I16I16I16I16Normalized,
+    /// Same as `U16`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U16Normalized,
+    /// Same as `U16U16`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U16U16Normalized,
+    /// Same as `U16U16U16`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U16U16U16Normalized,
+    /// Same as `U16U16U16U16`, but the values are normalized to `[0.0, 1.0]` when read by a floating-point
+    /// attribute.
+    U16U16U16U16Normalized,
     I32,
     I32I32,
     I32I32I32,
@@ -135,6 +185,17 @@ pub enum AttributeType {
     ///
     /// Corresponds to `GL_UNSIGNED_INT_2_10_10_10_REV`.
     U2U10U10U10Reversed,
+    /// Same as `I2I10I10I10Reversed`, but the values are normalized to `[-1.0, 1.0]` when read
+    /// by a floating-point attribute.
+    ///
+    /// Use `vertex::pack_snorm_10_10_10_2` to build values of this type. This is usually used
+    /// for normals and tangents.
+    I2I10I10I10ReversedNormalized,
+    /// Same as `U2U10U10U10Reversed`, but the values are normalized to `[0.0, 1.0]` when read
+    /// by a floating-point attribute.
+    ///
+    /// Use `vertex::pack_unorm_10_10_10_2` to build values of this type.
+    U2U10U10U10ReversedNormalized,
     /// Corresponds to `GL_INT_10_10_10_2`.
     I10I10I10I2,
     /// Corresponds to `GL_UNSIGNED_INT_10_10_10_2`.
@@ -158,7 +219,14 @@ impl AttributeType {
             &AttributeType::U8U8U8 | &AttributeType::U8U8U8U8 | &AttributeType::I16 |
             &AttributeType::I16I16 | &AttributeType::I16I16I16 | &AttributeType::I16I16I16I16 |
             &AttributeType::U16 | &AttributeType::U16U16 | &AttributeType::U16U16U16 |
-            &AttributeType::U16U16U16U16 | &AttributeType::F32 |
+            &AttributeType::U16U16U16U16 |
+            &AttributeType::I8Normalized | &AttributeType::I8I8Normalized | &AttributeType::I8I8I8Normalized |
+            &AttributeType::I8I8I8I8Normalized | &AttributeType::U8Normalized | &AttributeType::U8U8Normalized |
+            &AttributeType::U8U8U8Normalized | &AttributeType::U8U8U8U8Normalized | &AttributeType::I16Normalized |
+            &AttributeType::I16I16Normalized | &AttributeType::I16I16I16Normalized | &AttributeType::I16I16I16I16Normalized |
+            &AttributeType::U16Normalized | &AttributeType::U16U16Normalized | &AttributeType::U16U16U16Normalized |
+            &AttributeType::U16U16U16U16Normalized |
+            &AttributeType::F32 |
             &AttributeType::F32F32 | &AttributeType::F32F32F32 | &AttributeType::F32F32F32F32 |
             &AttributeType::F32x2x2 | &AttributeType::F32x2x3 | &AttributeType::F32x2x4 |
             &AttributeType::F32x3x2 | &AttributeType::F32x3x3 | &AttributeType::F32x3x4 |
@@ -204,7 +272,7 @@ impl AttributeType {
             &AttributeType::F16x4x2 | &AttributeType::F16x4x3 | &AttributeType::F16x4x4 => 
             {
                 caps.get_version() >= &Version(Api::GlEs, 3, 0) ||
-                caps.get_version() >= &Version(Api::Gl, 4, 0) ||
+                caps.get_version() >= &Version(Api::Gl, 3, 0) ||
                 caps.get_extensions().gl_arb_es3_compatibility ||
                 caps.get_extensions().gl_oes_vertex_half_float ||
                 caps.get_extensions().gl_arb_vertex_half_float ||
@@ -218,8 +286,11 @@ impl AttributeType {
                 caps.get_extensions().gl_oes_fixed_point
             },
 
-            &AttributeType::I2I10I10I10Reversed | &AttributeType::U2U10U10U10Reversed => {
-                caps.get_version() >= &Version(Api::Gl, 3, 0) ||
+            &AttributeType::I2I10I10I10Reversed | &AttributeType::U2U10U10U10Reversed |
+            &AttributeType::I2I10I10I10ReversedNormalized |
+            &AttributeType::U2U10U10U10ReversedNormalized =>
+            {
+                caps.get_version() >= &Version(Api::Gl, 3, 3) ||
                 caps.get_version() >= &Version(Api::GlEs, 3, 0) ||
                 caps.get_extensions().gl_arb_vertex_type_2_10_10_10_rev ||
                 caps.get_extensions().gl_arb_es3_compatibility
@@ -255,6 +326,22 @@ impl AttributeType {
             AttributeType::U16U16 => 2 * mem::size_of::<u16>(),
             AttributeType::U16U16U16 => 3 * mem::size_of::<u16>(),
             AttributeType::U16U16U16U16 => 4 * mem::size_of::<u16>(),
+            AttributeType::I8Normalized => 1 * mem::size_of::<i8>(),
+            AttributeType::I8I8Normalized => 2 * mem::size_of::<i8>(),
+            AttributeType::I8I8I8Normalized => 3 * mem::size_of::<i8>(),
+            AttributeType::I8I8I8I8Normalized => 4 * mem::size_of::<i8>(),
+            AttributeType::U8Normalized => 1 * mem::size_of::<u8>(),
+            AttributeType::U8U8Normalized => 2 * mem::size_of::<u8>(),
+            AttributeType::U8U8U8Normalized => 3 * mem::size_of::<u8>(),
+            AttributeType::U8U8U8U8Normalized => 4 * mem::size_of::<u8>(),
+            AttributeType::I16Normalized => 1 * mem::size_of::<i16>(),
+            AttributeType::I16I16Normalized => 2 * mem::size_of::<i16>(),
+            AttributeType::I16I16I16Normalized => 3 * mem::size_of::<i16>(),
+            AttributeType::I16I16I16I16Normalized => 4 * mem::size_of::<i16>(),
+            AttributeType::U16Normalized => 1 * mem::size_of::<u16>(),
+            AttributeType::U16U16Normalized => 2 * mem::size_of::<u16>(),
+            AttributeType::U16U16U16Normalized => 3 * mem::size_of::<u16>(),
+            AttributeType::U16U16U16U16Normalized => 4 * mem::size_of::<u16>(),
             AttributeType::I32 => 1 * mem::size_of::<i32>(),
             AttributeType::I32I32 => 2 * mem::size_of::<i32>(),
             AttributeType::I32I32I32 => 3 * mem::size_of::<i32>(),
@@ -312,6 +399,8 @@ impl AttributeType {
             AttributeType::F64x4x4 => 16 * mem::size_of::<f64>(),
             AttributeType::I2I10I10I10Reversed => 4,
             AttributeType::U2U10U10U10Reversed => 4,
+            AttributeType::I2I10I10I10ReversedNormalized => 4,
+            AttributeType::U2U10U10U10ReversedNormalized => 4,
             AttributeType::I10I10I10I2 => 4,
             AttributeType::U10U10U10U2 => 4,
             AttributeType::F10F11F11UnsignedIntReversed => 4,
@@ -338,6 +427,22 @@ impl AttributeType {
             AttributeType::U16U16 => 2,
             AttributeType::U16U16U16 => 3,
             AttributeType::U16U16U16U16 => 4,
+            AttributeType::I8Normalized => 1,
+            AttributeType::I8I8Normalized => 2,
+            AttributeType::I8I8I8Normalized => 3,
+            AttributeType::I8I8I8I8Normalized => 4,
+            AttributeType::U8Normalized => 1,
+            AttributeType::U8U8Normalized => 2,
+            AttributeType::U8U8U8Normalized => 3,
+            AttributeType::U8U8U8U8Normalized => 4,
+            AttributeType::I16Normalized => 1,
+            AttributeType::I16I16Normalized => 2,
+            AttributeType::I16I16I16Normalized => 3,
+            AttributeType::I16I16I16I16Normalized => 4,
+            AttributeType::U16Normalized => 1,
+            AttributeType::U16U16Normalized => 2,
+            AttributeType::U16U16U16Normalized => 3,
+            AttributeType::U16U16U16U16Normalized => 4,
             AttributeType::I32 => 1,
             AttributeType::I32I32 => 2,
             AttributeType::I32I32I32 => 3,
@@ -395,6 +500,8 @@ impl AttributeType {
             AttributeType::F64x4x4 => 16,
             AttributeType::I2I10I10I10Reversed => 4,
             AttributeType::U2U10U10U10Reversed => 4,
+            AttributeType::I2I10I10I10ReversedNormalized => 4,
+            AttributeType::U2U10U10U10ReversedNormalized => 4,
             AttributeType::I10I10I10I2 => 4,
             AttributeType::U10U10U10U2 => 4,
             AttributeType::F10F11F11UnsignedIntReversed => 3,
@@ -407,7 +514,48 @@ impl AttributeType {
 ///
 /// The first element is the name of the binding, the second element is the offset
 /// from the start of each vertex to this element, and the third element is the type.
-pub type VertexFormat = Cow<'static, [(Cow<'static, str>, usize, AttributeType)]>;
+///
+/// The fourth element is an optional explicit location. If it is `Some`, the data is bound
+/// to the program attribute at this location instead of being matched by name.
+///
+/// The fifth element is an optional instance divisor. If it is `Some`, the attribute advances
+/// once every this number of instances instead of using the divisor of the vertices source.
+/// This is only allowed when the buffer is used per instance.
+pub type VertexFormat = Cow<'static, [(Cow<'static, str>, usize, AttributeType, Option<u32>,
+                                       Option<u32>)]>;
+
+/// Returns true if two elements of the format have explicit locations that overlap.
+pub fn has_conflicting_locations(format: &VertexFormat) -> bool {
+    // matrices use one location per column
+    fn locations_count(ty: AttributeType) -> u32 {
+        match ty {
+            AttributeType::F16x2x2 | AttributeType::F16x3x2 | AttributeType::F16x4x2 |
+            AttributeType::F32x2x2 | AttributeType::F32x3x2 | AttributeType::F32x4x2 |
+            AttributeType::F64x2x2 | AttributeType::F64x3x2 | AttributeType::F64x4x2 => 2,
+            AttributeType::F16x2x3 | AttributeType::F16x3x3 | AttributeType::F16x4x3 |
+            AttributeType::F32x2x3 | AttributeType::F32x3x3 | AttributeType::F32x4x3 |
+            AttributeType::F64x2x3 | AttributeType::F64x3x3 | AttributeType::F64x4x3 => 3,
+            AttributeType::F16x2x4 | AttributeType::F16x3x4 | AttributeType::F16x4x4 |
+            AttributeType::F32x2x4 | AttributeType::F32x3x4 | AttributeType::F32x4x4 |
+            AttributeType::F64x2x4 | AttributeType::F64x3x4 | AttributeType::F64x4x4 => 4,
+            _ => 1
+        }
+    }
+
+    let ranges = format.iter().filter_map(|&(_, _, ty, location, _)| {
+        location.map(|l| (l, l + locations_count(ty)))
+    }).collect::<Vec<_>>();
+
+    for (i, &(start1, end1)) in ranges.iter().enumerate() {
+        for &(start2, end2) in ranges[i + 1 ..].iter() {
+            if start1 < end2 && start2 < end1 {
+                return true;
+            }
+        }
+    }
+
+    false
+}
 
 unsafe impl Attribute for i8 {
     #[inline]
@@ -871,6 +1019,86 @@ unsafe impl Attribute for [[f32; 4]; 4] {
     }
 }
 
+#[cfg(feature = "half")]
+unsafe impl Attribute for f16 {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for (f16, f16) {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [f16; 2] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for (f16, f16, f16) {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [f16; 3] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for (f16, f16, f16, f16) {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [f16; 4] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16F16F16F16
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [[f16; 2]; 2] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16x2x2
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [[f16; 3]; 3] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16x3x3
+    }
+}
+
+#[cfg(feature = "half")]
+unsafe impl Attribute for [[f16; 4]; 4] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F16x4x4
+    }
+}
+
 unsafe impl Attribute for f64 {
     #[inline]
     fn get_type() -> AttributeType {
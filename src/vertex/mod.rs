@@ -71,6 +71,7 @@ Each source can be:
  - The same with a slice, by calling `vertex_buffer.slice(start .. end).unwrap().per_instance()`.
  - A marker indicating a number of vertex sources, with `glium::vertex::EmptyVertexAttributes`.
  - A marker indicating a number of instances, with `glium::vertex::EmptyInstanceAttributes`.
+ - A reference to a `VertexDefaults`, usually built with the `vertex_defaults!` macro.
 
 ```no_run
 # use glium::Surface;
@@ -114,12 +115,49 @@ frame.draw((&vertex_buffer, glium::vertex::EmptyInstanceAttributes { len: 36 }),
            &program, &uniforms, &Default::default()).unwrap();
 ```
 
+## Default values
+
+If the program expects an attribute that none of the vertex sources provide, drawing panics.
+You can instead pass a `VertexDefaults` alongside the buffers in order to give a value to these
+attributes. This value is the same for all the vertices, and is ignored for the attributes that
+a buffer provides. This allows using the same program with meshes that have optional
+attributes.
+
+```no_run
+# #[macro_use]
+# extern crate glium;
+# fn main() {
+# use glium::Surface;
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# #[derive(Copy, Clone)]
+# struct MyVertex { position: [f32; 3], texcoords: [f32; 2], }
+# impl glium::vertex::Vertex for MyVertex {
+#     fn build_bindings() -> glium::vertex::VertexFormat { unimplemented!() }
+# }
+# let program: glium::program::Program = unsafe { ::std::mem::uninitialized() };
+# let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+# let uniforms = glium::uniforms::EmptyUniforms;
+# let vertex_buffer: glium::vertex::VertexBuffer<MyVertex> = unsafe { ::std::mem::uninitialized() };
+# let mut frame = display.draw();
+let defaults = vertex_defaults! { color: [1.0, 1.0, 1.0, 1.0f32] };
+frame.draw((&vertex_buffer, &defaults), &indices, &program,
+           &uniforms, &Default::default()).unwrap();
+# }
+```
+
 Note that if you use `index::EmptyIndices` as indices the length of all vertex sources must
 be the same, or a `DrawError::VerticesSourcesLengthMismatch` will be produced.
 
 In all situation, the length of all per-instance sources must match, or
 `DrawError::InstancesCountMismatch` will be retured.
 
+The attributes of a per-instance source advance once per instance by default. An attribute
+of the format can have its own divisor, given with `divisor(...)` in `implement_vertex!`, in
+which case it advances once every this number of instances. This allows for example
+interleaving a per-instance transformation with a color that is shared by groups of instances
+in the same buffer. The number of instances is still the length of the source. Using a format
+that contains divisors for a per-vertex source returns a `DrawError::DivisorOnPerVertexSource`.
+
 # Transform feedback
 
 Transform feedback allows you to write in a buffer the list of primitives that are generated by
@@ -138,14 +176,22 @@ use std::option::IntoIter;
 pub use self::buffer::{VertexBuffer, VertexBufferAny};
 pub use self::buffer::VertexBufferSlice;
 pub use self::buffer::CreationError as BufferCreationError;
+pub use self::defaults::{AttributeDefault, VertexDefaults};
 pub use self::format::{AttributeType, VertexFormat};
+pub use self::packing::{pack_f16, unpack_f16};
+pub use self::packing::{pack_snorm_10_10_10_2, pack_unorm_10_10_10_2};
+pub use self::serialization::{serialize_vertex_format, deserialize_vertex_format};
+pub use self::serialization::VertexFormatDeserializationError;
 pub use self::transform_feedback::{is_transform_feedback_supported, TransformFeedbackSession};
 
 use buffer::BufferAnySlice;
 use CapabilitiesSource;
 
 mod buffer;
+mod defaults;
 mod format;
+mod packing;
+mod serialization;
 mod transform_feedback;
 
 /// Describes the source to use for the vertices when drawing.
@@ -167,6 +213,10 @@ pub enum VerticesSource<'a> {
         /// Whether or not this buffer is "per instance" (true) or "per vertex" (false).
         per_instance: bool,
     },
+
+    /// Values to use for the attributes that the program expects but that no other source
+    /// provides.
+    Defaults(&'a VertexDefaults),
 }
 
 /// Objects that can be used as vertex sources.
@@ -195,9 +245,17 @@ impl<'a> IntoVerticesSource<'a> for EmptyVertexAttributes {
     }
 }
 
-/// Marker that can be passed instead of a buffer to indicate an empty list of buffers.
+/// Marker that can be passed instead of a buffer to draw multiple instances without any
+/// per-instance attribute.
+///
+/// Each instance can be identified in the shader with `gl_InstanceID`. If this marker is used
+/// alongside buffers passed with `per_instance()`, their lengths must match or a
+/// `DrawError::InstancesCountMismatch` is returned.
+///
+/// Drawing with this marker returns a `DrawError::InstancingNotSupported` if the backend doesn't
+/// support instancing.
 pub struct EmptyInstanceAttributes {
-    /// Number of phantom vertices.
+    /// Number of instances to draw.
     pub len: usize,
 }
 
@@ -308,7 +366,7 @@ pub trait Vertex: Copy + Sized {
     fn is_supported<C>(caps: &C) -> bool where C: CapabilitiesSource {
         let format = Self::build_bindings();
 
-        for &(_, _, ref ty) in format.iter() {
+        for &(_, _, ref ty, _) in format.iter() {
             if !ty.is_supported(caps) {
                 return false;
             }
@@ -0,0 +1,129 @@
+//! Functions that convert values to the packed representations of the vertex attribute types.
+
+use std::mem;
+
+/// Converts an `f32` to the nearest half-precision floating-point number, and returns the bits
+/// of the result.
+///
+/// The result can be used for attributes of type `AttributeType::F16`, `F16F16`, etc. Values
+/// that are too large to be represented become infinite, and NaN stays NaN.
+///
+/// ## Example
+///
+/// ```
+/// use glium::vertex::pack_f16;
+///
+/// assert_eq!(pack_f16(1.0), 0x3c00);
+/// assert_eq!(pack_f16(-2.0), 0xc000);
+/// ```
+pub fn pack_f16(value: f32) -> u16 {
+    let bits: u32 = unsafe { mem::transmute(value) };
+
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7fffff;
+
+    // infinity and NaN, making sure that NaN doesn't become infinity
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan | (mantissa >> 13) as u16;
+    }
+
+    let half_exponent = exponent - 127 + 15;
+
+    // too large, rounded to infinity
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // too small for a normal half, producing a denormal or zero
+    if half_exponent <= 0 {
+        let shift = (14 - half_exponent) as u32;
+        if shift > 24 {
+            return sign;
+        }
+
+        let mantissa = mantissa | 0x800000;
+        let result = mantissa >> shift;
+        let round_bit = 1 << (shift - 1);
+        let round_up = (mantissa & round_bit) != 0 &&
+                       ((mantissa & (round_bit - 1)) != 0 || (result & 1) != 0);
+        return sign | (result + if round_up { 1 } else { 0 }) as u16;
+    }
+
+    // rounding to the nearest even value; an overflow of the mantissa correctly increases
+    // the exponent
+    let result = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    let round_up = (mantissa & 0x1000) != 0 &&
+                   ((mantissa & 0xfff) != 0 || (result & 1) != 0);
+    sign | (result + if round_up { 1 } else { 0 }) as u16
+}
+
+/// Converts the bits of a half-precision floating-point number to an `f32`. This conversion
+/// is exact.
+pub fn unpack_f16(value: u16) -> f32 {
+    let sign = ((value & 0x8000) as u32) << 16;
+    let exponent = ((value >> 10) & 0x1f) as u32;
+    let mantissa = (value & 0x3ff) as u32;
+
+    let bits = match exponent {
+        0 => {
+            // zero and denormals are exactly representable as normal `f32`s
+            let value = mantissa as f32 / 16777216.0;
+            return if sign != 0 { -value } else { value };
+        },
+        0x1f => sign | 0x7f800000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+
+    unsafe { mem::transmute(bits) }
+}
+
+/// Packs three values between `-1.0` and `1.0` and a fourth value that is either `-1.0`, `0.0`
+/// or `1.0` into a value of type `AttributeType::I2I10I10I10ReversedNormalized`.
+///
+/// The first value is stored in the ten least significant bits and `w` in the two most
+/// significant bits. Values outside of the range are clamped. This is usually used to store
+/// normals and tangents, with `w` containing the handedness of the tangent space.
+///
+/// ## Example
+///
+/// ```
+/// use glium::vertex::pack_snorm_10_10_10_2;
+///
+/// assert_eq!(pack_snorm_10_10_10_2([1.0, 0.0, -1.0], 1.0), 0x6010_01ff);
+/// ```
+pub fn pack_snorm_10_10_10_2(xyz: [f32; 3], w: f32) -> u32 {
+    fn pack(value: f32, max: f32, bits: u32) -> u32 {
+        let value = value.max(-1.0).min(1.0);
+        let value = (value * max).round() as i32;
+        (value as u32) & ((1 << bits) - 1)
+    }
+
+    pack(xyz[0], 511.0, 10) | (pack(xyz[1], 511.0, 10) << 10) |
+    (pack(xyz[2], 511.0, 10) << 20) | (pack(w, 1.0, 2) << 30)
+}
+
+/// Packs three values between `0.0` and `1.0` and a fourth value that is either `0.0`,
+/// `1.0 / 3.0`, `2.0 / 3.0` or `1.0` into a value of type
+/// `AttributeType::U2U10U10U10ReversedNormalized`.
+///
+/// The first value is stored in the ten least significant bits and `w` in the two most
+/// significant bits. Values outside of the range are clamped.
+///
+/// ## Example
+///
+/// ```
+/// use glium::vertex::pack_unorm_10_10_10_2;
+///
+/// assert_eq!(pack_unorm_10_10_10_2([1.0, 0.0, 1.0], 1.0), 0xfff0_03ff);
+/// ```
+pub fn pack_unorm_10_10_10_2(xyz: [f32; 3], w: f32) -> u32 {
+    fn pack(value: f32, max: f32) -> u32 {
+        let value = value.max(0.0).min(1.0);
+        (value * max).round() as u32
+    }
+
+    pack(xyz[0], 1023.0) | (pack(xyz[1], 1023.0) << 10) | (pack(xyz[2], 1023.0) << 20) |
+    (pack(w, 3.0) << 30)
+}
@@ -0,0 +1,425 @@
+/*!
+Serialization of vertex formats.
+
+This allows you to store a `VertexFormat` alongside some vertex data, for example in a file
+generated by an asset pipeline, and to later pass it to `VertexBuffer::new_raw`.
+
+The encoding is stable and versioned. It starts with the bytes `GLVF` followed by the version
+of the encoding, then the number of elements. Each element is made of its name, its offset,
+the identifier of its `AttributeType`, its optional location and its optional instance divisor.
+All numbers are little-endian.
+
+Data serialized with the first version of the encoding, which doesn't contain the divisors, can
+still be loaded.
+
+*/
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use vertex::AttributeType;
+use vertex::VertexFormat;
+
+/// Bytes at the start of a serialized vertex format.
+const MAGIC: &'static [u8] = b"GLVF";
+
+/// Current version of the encoding.
+const VERSION: u8 = 2;
+
+/// Error that can happen when deserializing a vertex format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VertexFormatDeserializationError {
+    /// The data doesn't start with the expected header.
+    InvalidHeader,
+
+    /// The data was serialized with a version of the encoding that is not supported.
+    UnsupportedVersion(u8),
+
+    /// The data ended before the end of the vertex format.
+    UnexpectedEnd,
+
+    /// The name of an element is not valid UTF-8.
+    InvalidName,
+
+    /// The offset of an element doesn't fit in a `usize`.
+    OffsetTooLarge,
+
+    /// The identifier of an attribute type is unknown.
+    UnknownAttributeType(u16),
+
+    /// There is some data after the end of the vertex format.
+    TrailingData,
+}
+
+impl fmt::Display for VertexFormatDeserializationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &VertexFormatDeserializationError::UnsupportedVersion(v) => {
+                write!(formatter, "{} ({})", self.description(), v)
+            },
+            &VertexFormatDeserializationError::UnknownAttributeType(id) => {
+                write!(formatter, "{} ({})", self.description(), id)
+            },
+            _ => self.description().fmt(formatter),
+        }
+    }
+}
+
+impl Error for VertexFormatDeserializationError {
+    fn description(&self) -> &str {
+        match self {
+            &VertexFormatDeserializationError::InvalidHeader => "The data is not a serialized \
+                                                                 vertex format",
+            &VertexFormatDeserializationError::UnsupportedVersion(_) => "The version of the \
+                                                                        encoding is not supported",
+            &VertexFormatDeserializationError::UnexpectedEnd => "The data ended unexpectedly",
+            &VertexFormatDeserializationError::InvalidName => "The name of an element is not \
+                                                               valid UTF-8",
+            &VertexFormatDeserializationError::OffsetTooLarge => "The offset of an element is \
+                                                                  too large",
+            &VertexFormatDeserializationError::UnknownAttributeType(_) => "Unknown attribute type",
+            &VertexFormatDeserializationError::TrailingData => "There is some data after the \
+                                                                vertex format",
+        }
+    }
+}
+
+/// Turns a vertex format into bytes that can be stored and later loaded with
+/// `deserialize_vertex_format`.
+pub fn serialize_vertex_format(format: &VertexFormat) -> Vec<u8> {
+    let mut output = Vec::new();
+    output.extend(MAGIC.iter().cloned());
+    output.push(VERSION);
+    write_u32(&mut output, format.len() as u32);
+
+    for &(ref name, offset, ty, location, divisor) in format.iter() {
+        write_u32(&mut output, name.len() as u32);
+        output.extend(name.as_bytes().iter().cloned());
+        write_u64(&mut output, offset as u64);
+        write_u16(&mut output, attribute_type_to_id(ty));
+        write_option_u32(&mut output, location);
+        write_option_u32(&mut output, divisor);
+    }
+
+    output
+}
+
+/// Loads a vertex format that has been serialized with `serialize_vertex_format`.
+pub fn deserialize_vertex_format(data: &[u8])
+                                 -> Result<VertexFormat, VertexFormatDeserializationError>
+{
+    let mut reader = Reader { data: data };
+
+    if try!(reader.read_bytes(MAGIC.len())) != MAGIC {
+        return Err(VertexFormatDeserializationError::InvalidHeader);
+    }
+
+    let version = try!(reader.read_bytes(1))[0];
+    if version == 0 || version > VERSION {
+        return Err(VertexFormatDeserializationError::UnsupportedVersion(version));
+    }
+
+    let num_elements = try!(reader.read_u32());
+    let mut elements = Vec::new();
+
+    for _ in 0 .. num_elements {
+        let name_len = try!(reader.read_u32()) as usize;
+        let name = match str::from_utf8(try!(reader.read_bytes(name_len))) {
+            Ok(n) => n.to_owned(),
+            Err(_) => return Err(VertexFormatDeserializationError::InvalidName)
+        };
+
+        let offset = try!(reader.read_u64());
+        if offset > usize::max_value() as u64 {
+            return Err(VertexFormatDeserializationError::OffsetTooLarge);
+        }
+
+        let ty_id = try!(reader.read_u16());
+        let ty = match attribute_type_from_id(ty_id) {
+            Some(ty) => ty,
+            None => return Err(VertexFormatDeserializationError::UnknownAttributeType(ty_id))
+        };
+
+        let location = try!(reader.read_option_u32());
+
+        // the divisors were added in the second version
+        let divisor = if version >= 2 { try!(reader.read_option_u32()) } else { None };
+
+        elements.push((Cow::Owned(name), offset as usize, ty, location, divisor));
+    }
+
+    if !reader.data.is_empty() {
+        return Err(VertexFormatDeserializationError::TrailingData);
+    }
+
+    Ok(Cow::Owned(elements))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], VertexFormatDeserializationError> {
+        if self.data.len() < len {
+            return Err(VertexFormatDeserializationError::UnexpectedEnd);
+        }
+
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, VertexFormatDeserializationError> {
+        let bytes = try!(self.read_bytes(2));
+        Ok(bytes[0] as u16 | (bytes[1] as u16) << 8)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, VertexFormatDeserializationError> {
+        let bytes = try!(self.read_bytes(4));
+        Ok(bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u32))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, VertexFormatDeserializationError> {
+        let bytes = try!(self.read_bytes(8));
+        Ok(bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    fn read_option_u32(&mut self) -> Result<Option<u32>, VertexFormatDeserializationError> {
+        match try!(self.read_bytes(1))[0] {
+            0 => Ok(None),
+            _ => Ok(Some(try!(self.read_u32()))),
+        }
+    }
+}
+
+fn write_u16(output: &mut Vec<u8>, value: u16) {
+    output.push(value as u8);
+    output.push((value >> 8) as u8);
+}
+
+fn write_u32(output: &mut Vec<u8>, value: u32) {
+    for i in 0 .. 4 {
+        output.push((value >> (i * 8)) as u8);
+    }
+}
+
+fn write_u64(output: &mut Vec<u8>, value: u64) {
+    for i in 0 .. 8 {
+        output.push((value >> (i * 8)) as u8);
+    }
+}
+
+fn write_option_u32(output: &mut Vec<u8>, value: Option<u32>) {
+    match value {
+        Some(value) => {
+            output.push(1);
+            write_u32(output, value);
+        },
+        None => output.push(0),
+    }
+}
+
+// The identifiers are part of the encoding and must never change. New attribute types must
+// be given new identifiers.
+fn attribute_type_to_id(ty: AttributeType) -> u16 {
+    match ty {
+        AttributeType::I8 => 0,
+        AttributeType::I8I8 => 1,
+        AttributeType::I8I8I8 => 2,
+        AttributeType::I8I8I8I8 => 3,
+        AttributeType::U8 => 4,
+        AttributeType::U8U8 => 5,
+        AttributeType::U8U8U8 => 6,
+        AttributeType::U8U8U8U8 => 7,
+        AttributeType::I16 => 8,
+        AttributeType::I16I16 => 9,
+        AttributeType::I16I16I16 => 10,
+        AttributeType::I16I16I16I16 => 11,
+        AttributeType::U16 => 12,
+        AttributeType::U16U16 => 13,
+        AttributeType::U16U16U16 => 14,
+        AttributeType::U16U16U16U16 => 15,
+        AttributeType::I8Normalized => 16,
+        AttributeType::I8I8Normalized => 17,
+        AttributeType::I8I8I8Normalized => 18,
+        AttributeType::I8I8I8I8Normalized => 19,
+        AttributeType::U8Normalized => 20,
+        AttributeType::U8U8Normalized => 21,
+        AttributeType::U8U8U8Normalized => 22,
+        AttributeType::U8U8U8U8Normalized => 23,
+        AttributeType::I16Normalized => 24,
+        AttributeType::I16I16Normalized => 25,
+        AttributeType::I16I16I16Normalized => 26,
+        AttributeType::I16I16I16I16Normalized => 27,
+        AttributeType::U16Normalized => 28,
+        AttributeType::U16U16Normalized => 29,
+        AttributeType::U16U16U16Normalized => 30,
+        AttributeType::U16U16U16U16Normalized => 31,
+        AttributeType::I32 => 32,
+        AttributeType::I32I32 => 33,
+        AttributeType::I32I32I32 => 34,
+        AttributeType::I32I32I32I32 => 35,
+        AttributeType::U32 => 36,
+        AttributeType::U32U32 => 37,
+        AttributeType::U32U32U32 => 38,
+        AttributeType::U32U32U32U32 => 39,
+        AttributeType::I64 => 40,
+        AttributeType::I64I64 => 41,
+        AttributeType::I64I64I64 => 42,
+        AttributeType::I64I64I64I64 => 43,
+        AttributeType::U64 => 44,
+        AttributeType::U64U64 => 45,
+        AttributeType::U64U64U64 => 46,
+        AttributeType::U64U64U64U64 => 47,
+        AttributeType::F16 => 48,
+        AttributeType::F16F16 => 49,
+        AttributeType::F16F16F16 => 50,
+        AttributeType::F16F16F16F16 => 51,
+        AttributeType::F16x2x2 => 52,
+        AttributeType::F16x2x3 => 53,
+        AttributeType::F16x2x4 => 54,
+        AttributeType::F16x3x2 => 55,
+        AttributeType::F16x3x3 => 56,
+        AttributeType::F16x3x4 => 57,
+        AttributeType::F16x4x2 => 58,
+        AttributeType::F16x4x3 => 59,
+        AttributeType::F16x4x4 => 60,
+        AttributeType::F32 => 61,
+        AttributeType::F32F32 => 62,
+        AttributeType::F32F32F32 => 63,
+        AttributeType::F32F32F32F32 => 64,
+        AttributeType::F32x2x2 => 65,
+        AttributeType::F32x2x3 => 66,
+        AttributeType::F32x2x4 => 67,
+        AttributeType::F32x3x2 => 68,
+        AttributeType::F32x3x3 => 69,
+        AttributeType::F32x3x4 => 70,
+        AttributeType::F32x4x2 => 71,
+        AttributeType::F32x4x3 => 72,
+        AttributeType::F32x4x4 => 73,
+        AttributeType::F64 => 74,
+        AttributeType::F64F64 => 75,
+        AttributeType::F64F64F64 => 76,
+        AttributeType::F64F64F64F64 => 77,
+        AttributeType::F64x2x2 => 78,
+        AttributeType::F64x2x3 => 79,
+        AttributeType::F64x2x4 => 80,
+        AttributeType::F64x3x2 => 81,
+        AttributeType::F64x3x3 => 82,
+        AttributeType::F64x3x4 => 83,
+        AttributeType::F64x4x2 => 84,
+        AttributeType::F64x4x3 => 85,
+        AttributeType::F64x4x4 => 86,
+        AttributeType::I2I10I10I10Reversed => 87,
+        AttributeType::U2U10U10U10Reversed => 88,
+        AttributeType::I10I10I10I2 => 89,
+        AttributeType::U10U10U10U2 => 90,
+        AttributeType::F10F11F11UnsignedIntReversed => 91,
+        AttributeType::FixedFloatI16U16 => 92,
+        AttributeType::I2I10I10I10ReversedNormalized => 93,
+        AttributeType::U2U10U10U10ReversedNormalized => 94,
+    }
+}
+
+fn attribute_type_from_id(id: u16) -> Option<AttributeType> {
+    Some(match id {
+        0 => AttributeType::I8,
+        1 => AttributeType::I8I8,
+        2 => AttributeType::I8I8I8,
+        3 => AttributeType::I8I8I8I8,
+        4 => AttributeType::U8,
+        5 => AttributeType::U8U8,
+        6 => AttributeType::U8U8U8,
+        7 => AttributeType::U8U8U8U8,
+        8 => AttributeType::I16,
+        9 => AttributeType::I16I16,
+        10 => AttributeType::I16I16I16,
+        11 => AttributeType::I16I16I16I16,
+        12 => AttributeType::U16,
+        13 => AttributeType::U16U16,
+        14 => AttributeType::U16U16U16,
+        15 => AttributeType::U16U16U16U16,
+        16 => AttributeType::I8Normalized,
+        17 => AttributeType::I8I8Normalized,
+        18 => AttributeType::I8I8I8Normalized,
+        19 => AttributeType::I8I8I8I8Normalized,
+        20 => AttributeType::U8Normalized,
+        21 => AttributeType::U8U8Normalized,
+        22 => AttributeType::U8U8U8Normalized,
+        23 => AttributeType::U8U8U8U8Normalized,
+        24 => AttributeType::I16Normalized,
+        25 => AttributeType::I16I16Normalized,
+        26 => AttributeType::I16I16I16Normalized,
+        27 => AttributeType::I16I16I16I16Normalized,
+        28 => AttributeType::U16Normalized,
+        29 => AttributeType::U16U16Normalized,
+        30 => AttributeType::U16U16U16Normalized,
+        31 => AttributeType::U16U16U16U16Normalized,
+        32 => AttributeType::I32,
+        33 => AttributeType::I32I32,
+        34 => AttributeType::I32I32I32,
+        35 => AttributeType::I32I32I32I32,
+        36 => AttributeType::U32,
+        37 => AttributeType::U32U32,
+        38 => AttributeType::U32U32U32,
+        39 => AttributeType::U32U32U32U32,
+        40 => AttributeType::I64,
+        41 => AttributeType::I64I64,
+        42 => AttributeType::I64I64I64,
+        43 => AttributeType::I64I64I64I64,
+        44 => AttributeType::U64,
+        45 => AttributeType::U64U64,
+        46 => AttributeType::U64U64U64,
+        47 => AttributeType::U64U64U64U64,
+        48 => AttributeType::F16,
+        49 => AttributeType::F16F16,
+        50 => AttributeType::F16F16F16,
+        51 => AttributeType::F16F16F16F16,
+        52 => AttributeType::F16x2x2,
+        53 => AttributeType::F16x2x3,
+        54 => AttributeType::F16x2x4,
+        55 => AttributeType::F16x3x2,
+        56 => AttributeType::F16x3x3,
+        57 => AttributeType::F16x3x4,
+        58 => AttributeType::F16x4x2,
+        59 => AttributeType::F16x4x3,
+        60 => AttributeType::F16x4x4,
+        61 => AttributeType::F32,
+        62 => AttributeType::F32F32,
+        63 => AttributeType::F32F32F32,
+        64 => AttributeType::F32F32F32F32,
+        65 => AttributeType::F32x2x2,
+        66 => AttributeType::F32x2x3,
+        67 => AttributeType::F32x2x4,
+        68 => AttributeType::F32x3x2,
+        69 => AttributeType::F32x3x3,
+        70 => AttributeType::F32x3x4,
+        71 => AttributeType::F32x4x2,
+        72 => AttributeType::F32x4x3,
+        73 => AttributeType::F32x4x4,
+        74 => AttributeType::F64,
+        75 => AttributeType::F64F64,
+        76 => AttributeType::F64F64F64,
+        77 => AttributeType::F64F64F64F64,
+        78 => AttributeType::F64x2x2,
+        79 => AttributeType::F64x2x3,
+        80 => AttributeType::F64x2x4,
+        81 => AttributeType::F64x3x2,
+        82 => AttributeType::F64x3x3,
+        83 => AttributeType::F64x3x4,
+        84 => AttributeType::F64x4x2,
+        85 => AttributeType::F64x4x3,
+        86 => AttributeType::F64x4x4,
+        87 => AttributeType::I2I10I10I10Reversed,
+        88 => AttributeType::U2U10U10U10Reversed,
+        89 => AttributeType::I10I10I10I2,
+        90 => AttributeType::U10U10U10U2,
+        91 => AttributeType::F10F11F11UnsignedIntReversed,
+        92 => AttributeType::FixedFloatI16U16,
+        93 => AttributeType::I2I10I10I10ReversedNormalized,
+        94 => AttributeType::U2U10U10U10ReversedNormalized,
+        _ => return None
+    })
+}
@@ -1,3 +1,5 @@
+use std::error::Error;
+use std::fmt;
 use std::mem;
 
 use version::Api;
@@ -104,6 +106,25 @@ pub enum TransformFeedbackSessionCreationError {
     WrongVertexFormat,
 }
 
+impl fmt::Display for TransformFeedbackSessionCreationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(formatter)
+    }
+}
+
+impl Error for TransformFeedbackSessionCreationError {
+    fn description(&self) -> &str {
+        match self {
+            &TransformFeedbackSessionCreationError::NotSupported => "Transform feedback is not \
+                                                                     supported by the backend",
+            &TransformFeedbackSessionCreationError::WrongVertexFormat => "The format of the \
+                                                                          output doesn't match \
+                                                                          what the program \
+                                                                          outputs",
+        }
+    }
+}
+
 /// Returns true if transform feedback is supported by the OpenGL implementation.
 #[inline]
 pub fn is_transform_feedback_supported<F>(facade: &F) -> bool where F: Facade {
@@ -1,4 +1,4 @@
-use std::borrow::Borrow;
+use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::mem;
@@ -7,22 +7,42 @@ use smallvec::SmallVec;
 
 use Handle;
 use buffer::BufferAnySlice;
-use program::Program;
+use program::{Attribute, Program};
 use vertex::AttributeType;
 use vertex::VertexFormat;
+use vertex::{AttributeDefault, VertexDefaults};
 use GlObject;
 use BufferExt;
 
 use {libc, gl};
+use context;
 use context::CommandContext;
 use version::Api;
 use version::Version;
 
+/// List of vertex buffers: buffer id, format, offset in bytes, stride and divisor.
+type VertexBuffersList = [(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)];
+
+/// Key of the VAOs that use separate attribute formats and bindings: the format, stride and
+/// divisor of each binding, the index buffer and the program.
+///
+/// The divisors of the individual attributes are part of the format.
+type FormatKey = (Vec<(VertexFormat, usize, Option<u32>)>, gl::types::GLuint, Handle);
+
+/// Key of the other VAOs: the id, offset and divisor of each buffer, and the program. The list
+/// of buffers must be sorted.
+///
+/// The format of a buffer, and thus the divisors of its attributes, never changes.
+type BuffersKey = (Vec<(gl::types::GLuint, usize, Option<u32>)>, Handle);
+
 /// Stores and handles vertex attributes.
 pub struct VertexAttributesSystem {
     // we maintain a list of VAOs for each vertexbuffer-indexbuffer-program association
-    // the key is a (buffers-list-with-offset, program) ; the buffers list must be sorted
-    vaos: RefCell<HashMap<(Vec<(gl::types::GLuint, usize)>, Handle), VertexArrayObject>>,
+    vaos: RefCell<HashMap<BuffersKey, VertexArrayObject>>,
+
+    // if `GL_ARB_vertex_attrib_binding` is supported, the VAOs only store the format of the
+    // attributes and the vertex buffers are switched with `glBindVertexBuffer`
+    format_vaos: RefCell<HashMap<FormatKey, VertexArrayObject>>,
 }
 
 /// Object allowing one to bind vertex attributes to the current context.
@@ -31,6 +51,7 @@ pub struct Binder<'a, 'b, 'c: 'b> {
     program: &'a Program,
     element_array_buffer: Option<BufferAnySlice<'a>>,
     vertex_buffers: SmallVec<[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>); 2]>,
+    defaults: Vec<(Cow<'static, str>, AttributeDefault)>,
     base_vertex: bool,
 }
 
@@ -40,6 +61,7 @@ impl VertexAttributesSystem {
     pub fn new() -> VertexAttributesSystem {
         VertexAttributesSystem {
             vaos: RefCell::new(HashMap::new()),
+            format_vaos: RefCell::new(HashMap::new()),
         }
     }
 
@@ -61,6 +83,7 @@ impl VertexAttributesSystem {
             program: program,
             element_array_buffer: indices,
             vertex_buffers: SmallVec::new(),
+            defaults: Vec::new(),
             base_vertex: base_vertex,
         }
     }
@@ -70,25 +93,32 @@ impl VertexAttributesSystem {
     #[inline]
     pub fn purge_buffer(ctxt: &mut CommandContext, id: gl::types::GLuint) {
         VertexAttributesSystem::purge_if(ctxt, |&(ref buffers, _)| {
-            buffers.iter().find(|&&(b, _)| b == id).is_some()
-        })
+            buffers.iter().find(|&&(b, _, _)| b == id).is_some()
+        });
+
+        VertexAttributesSystem::purge_format_vaos_if(ctxt, |&(_, element_array_buffer, _), vao| {
+            element_array_buffer == id || vao.uses_vertex_buffer(id)
+        });
     }
 
     /// This function *must* be called whenever you destroy a program so that the system can
     /// purge its VAOs cache.
     #[inline]
     pub fn purge_program(ctxt: &mut CommandContext, program: Handle) {
-        VertexAttributesSystem::purge_if(ctxt, |&(_, p)| p == program)
+        VertexAttributesSystem::purge_if(ctxt, |&(_, p)| p == program);
+        VertexAttributesSystem::purge_format_vaos_if(ctxt, |&(_, _, p), _| p == program);
     }
 
     /// Purges the VAOs cache.
     pub fn purge_all(ctxt: &mut CommandContext) {
         let vaos = mem::replace(&mut *ctxt.vertex_array_objects.vaos.borrow_mut(),
                                 HashMap::new());
+        let format_vaos = mem::replace(&mut *ctxt.vertex_array_objects.format_vaos.borrow_mut(),
+                                       HashMap::new());
 
-        for (_, vao) in vaos {
-            vao.destroy(ctxt);
-        }
+        let vaos = vaos.into_iter().map(|(_, vao)| vao)
+                       .chain(format_vaos.into_iter().map(|(_, vao)| vao)).collect();
+        destroy_in_order(ctxt, vaos);
     }
 
     /// Purges the VAOs cache. Contrary to `purge_all`, this function expects the system to be
@@ -96,10 +126,12 @@ impl VertexAttributesSystem {
     pub fn cleanup(ctxt: &mut CommandContext) {
         let vaos = mem::replace(&mut *ctxt.vertex_array_objects.vaos.borrow_mut(),
                                 HashMap::with_capacity(0));
+        let format_vaos = mem::replace(&mut *ctxt.vertex_array_objects.format_vaos.borrow_mut(),
+                                       HashMap::with_capacity(0));
 
-        for (_, vao) in vaos {
-            vao.destroy(ctxt);
-        }
+        let vaos = vaos.into_iter().map(|(_, vao)| vao)
+                       .chain(format_vaos.into_iter().map(|(_, vao)| vao)).collect();
+        destroy_in_order(ctxt, vaos);
     }
 
     /// Tells the VAOs system that the currently binded element array buffer will change.
@@ -112,11 +144,20 @@ impl VertexAttributesSystem {
                 return;
             }
         }
+
+        let vaos = ctxt.vertex_array_objects.format_vaos.borrow_mut();
+
+        for (_, vao) in vaos.iter() {
+            if vao.id == ctxt.state.vertex_array {
+                vao.element_array_buffer_hijacked.set(true);
+                return;
+            }
+        }
     }
 
     /// Purges VAOs that match a certain condition.
     fn purge_if<F>(ctxt: &mut CommandContext, mut condition: F)
-                   where F: FnMut(&(Vec<(gl::types::GLuint, usize)>, Handle)) -> bool
+                   where F: FnMut(&BuffersKey) -> bool
     {
         let mut vaos = ctxt.vertex_array_objects.vaos.borrow_mut();
 
@@ -127,9 +168,38 @@ impl VertexAttributesSystem {
             }
         }
 
-        for key in keys {
-            vaos.remove(&key).unwrap().destroy(ctxt);
+        let objects = keys.into_iter().map(|key| vaos.remove(&key).unwrap()).collect();
+        destroy_in_order(ctxt, objects);
+    }
+
+    /// Purges the VAOs that use separate attribute formats and bindings and that match a
+    /// certain condition.
+    fn purge_format_vaos_if<F>(ctxt: &mut CommandContext, mut condition: F)
+                               where F: FnMut(&FormatKey, &VertexArrayObject) -> bool
+    {
+        let mut vaos = ctxt.vertex_array_objects.format_vaos.borrow_mut();
+
+        let mut keys = Vec::with_capacity(0);
+        for (key, vao) in &*vaos {
+            if condition(key, vao) {
+                keys.push(key.clone());
+            }
         }
+
+        let objects = keys.into_iter().map(|key| vaos.remove(&key).unwrap()).collect();
+        destroy_in_order(ctxt, objects);
+    }
+}
+
+/// Destroys vertex array objects by increasing identifier.
+///
+/// The iteration order of the hash maps changes between runs, which would otherwise make the
+/// identifiers of the next vertex array objects nondeterministic.
+fn destroy_in_order(ctxt: &mut CommandContext, mut vaos: Vec<VertexArrayObject>) {
+    vaos.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for vao in vaos {
+        vao.destroy(ctxt);
     }
 }
 
@@ -141,6 +211,7 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
     /// - `buffer`: The buffer to bind.
     /// - `first`: Offset of the first element of the buffer in number of elements.
     /// - `divisor`: If `Some`, use this value for `glVertexAttribDivisor` (instancing-related).
+    ///   The attributes of `bindings` that have their own divisor use it instead.
     #[inline]
     pub fn add(mut self, buffer: &BufferAnySlice, bindings: &VertexFormat, divisor: Option<u32>)
                -> Binder<'a, 'b, 'c>
@@ -156,12 +227,33 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
         self
     }
 
+    /// Adds values to use for the attributes that no buffer provides.
+    #[inline]
+    pub fn add_defaults(mut self, defaults: &VertexDefaults) -> Binder<'a, 'b, 'c> {
+        for &(ref name, value) in defaults.iter() {
+            self.defaults.push((name.clone(), value));
+        }
+
+        self
+    }
+
     /// Finish binding the vertex attributes.
     ///
     /// If `base_vertex` was set to true, returns the base vertex to use when drawing.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the program expects an attribute that is neither provided by a buffer nor
+    /// by the default values.
     pub fn bind(mut self) -> Option<gl::types::GLint> {
         let ctxt = self.context;
 
+        // the current values of the attributes are part of the context state and not of the
+        // VAO state, so they can be set before binding
+        let defaulted = unsafe {
+            apply_defaults(ctxt, self.program, &self.vertex_buffers, &self.defaults)
+        };
+
         if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.version >= &Version(Api::GlEs, 3, 0) ||
            ctxt.extensions.gl_arb_vertex_array_object || ctxt.extensions.gl_oes_vertex_array_object
            || ctxt.extensions.gl_apple_vertex_array_object
@@ -185,15 +277,44 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
                 }
             }
 
+            let element_array_buffer = self.element_array_buffer.map(|b| b.get_buffer_id())
+                                                                 .unwrap_or(0);
+
+            // with separate attribute formats and bindings, the VAOs only depend on the format
+            // of the vertex buffers and switching buffers only requires `glBindVertexBuffer`
+            if is_attrib_binding_usable(ctxt, &self.vertex_buffers) {
+                let key = (self.vertex_buffers.iter()
+                                              .map(|&(_, ref f, _, s, d)| (f.clone(), s, d))
+                                              .collect::<Vec<_>>(),
+                           element_array_buffer, self.program.get_id());
+
+                if let Some(value) = ctxt.vertex_array_objects.format_vaos.borrow().get(&key) {
+                    value.bind(ctxt);
+                    unsafe { value.bind_vertex_buffers(ctxt, &self.vertex_buffers); }
+                    return base_vertex.map(|v| v as gl::types::GLint);
+                }
+
+                let new_vao = unsafe {
+                    VertexArrayObject::new(ctxt, &self.vertex_buffers,
+                                           self.element_array_buffer, self.program, true)
+                };
+
+                unsafe { new_vao.bind_vertex_buffers(ctxt, &self.vertex_buffers); }
+                ctxt.vertex_array_objects.format_vaos.borrow_mut().insert(key, new_vao);
+
+                return base_vertex.map(|v| v as gl::types::GLint);
+            }
+
             let mut buffers_list: Vec<_> = self.vertex_buffers.iter()
-                                                              .map(|&(v, _, o, s, _)| (v, o))
+                                                              .map(|&(v, _, o, _, d)| (v, o, d))
                                                               .collect();
-            buffers_list.push((self.element_array_buffer.map(|b| b.get_buffer_id()).unwrap_or(0), 0));
+            buffers_list.push((element_array_buffer, 0, None));
             buffers_list.sort();
 
             let program_id = self.program.get_id();
 
-            // trying to find an existing VAO in the cache
+            // trying to find an existing VAO in the cache ; the attributes that have a default
+            // value are never enabled in the VAO
             if let Some(value) = ctxt.vertex_array_objects.vaos.borrow_mut()
                                      .get(&(buffers_list.clone(), program_id))
             {
@@ -204,7 +325,7 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
             // if not found, building a new one
             let new_vao = unsafe {
                 VertexArrayObject::new(ctxt, &self.vertex_buffers,
-                                       self.element_array_buffer, self.program)
+                                       self.element_array_buffer, self.program, false)
             };
 
             new_vao.bind(ctxt);
@@ -229,6 +350,12 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
                 }
             }
 
+            // a previous draw may have left an array enabled at the location of an attribute
+            // that now uses its default value
+            for location in defaulted {
+                unsafe { ctxt.gl.DisableVertexAttribArray(location) };
+            }
+
             // TODO: it is unlikely that a backend supports base vertex but not VAOs, so we just
             //       ignore this case ; however it would ideally be better to handle it
             if self.base_vertex {
@@ -246,6 +373,9 @@ struct VertexArrayObject {
     destroyed: bool,
     element_array_buffer: gl::types::GLuint,
     element_array_buffer_hijacked: Cell<bool>,
+    // if the VAO uses separate attribute formats and bindings, contains the buffer, offset and
+    // stride currently bound to each binding index
+    vertex_buffer_bindings: RefCell<SmallVec<[(gl::types::GLuint, usize, usize); 2]>>,
 }
 
 impl VertexArrayObject {
@@ -253,14 +383,18 @@ impl VertexArrayObject {
     ///
     /// The vertex buffer, index buffer and program must not outlive the
     /// VAO, and the VB & program attributes must not change.
-    unsafe fn new(mut ctxt: &mut CommandContext,
-                  vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)],
-                  index_buffer: Option<BufferAnySlice>, program: &Program) -> VertexArrayObject
+    ///
+    /// If `attrib_binding` is true, only the format of the attributes is stored in the VAO and
+    /// `bind_vertex_buffers` must be called to bind the vertex buffers. The VAO stays bound
+    /// after this function returns.
+    unsafe fn new(mut ctxt: &mut CommandContext, vertex_buffers: &VertexBuffersList,
+                  index_buffer: Option<BufferAnySlice>, program: &Program, attrib_binding: bool)
+                  -> VertexArrayObject
     {
         // checking the attributes types
         for &(_, ref bindings, _, _, _) in vertex_buffers {
-            for &(ref name, _, ty) in bindings.iter() {
-                let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+            for &(ref name, _, ty, location, _) in bindings.iter() {
+                let attribute = match get_program_attribute(program, name, location) {
                     Some(a) => a,
                     None => continue
                 };
@@ -274,20 +408,6 @@ impl VertexArrayObject {
             }
         }
 
-        // checking for missing attributes
-        for (&ref name, _) in program.attributes() {
-            let mut found = false;
-            for &(_, ref bindings, _, _, _) in vertex_buffers {
-                if bindings.iter().find(|&&(ref n, _, _)| n == name).is_some() {
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                panic!("The program attribute `{}` is missing in the vertex bindings", name);
-            }
-        };
-
         // TODO: check for collisions between the vertices sources
 
         // building the VAO
@@ -311,14 +431,24 @@ impl VertexArrayObject {
         // we don't use DSA as we're going to make multiple calls for this VAO
         // and we're likely going to use the VAO right after it's been created
         bind_vao(&mut ctxt, id);
+        context::label_internal_object(ctxt, gl::VERTEX_ARRAY, id,
+                                       format_args!("vertex array object with {} vertex \
+                                                     buffer(s)", vertex_buffers.len()));
 
         // binding index buffer
         if let Some(index_buffer) = index_buffer {
             index_buffer.bind_to_element_array(&mut ctxt);
         }
 
-        for &(vertex_buffer, ref bindings, offset, stride, divisor) in vertex_buffers {
-            bind_attribute(ctxt, program, vertex_buffer, bindings, offset, stride, divisor);
+        if attrib_binding {
+            for (index, &(_, ref bindings, _, _, divisor)) in vertex_buffers.iter().enumerate() {
+                bind_attribute_format(ctxt, program, index as gl::types::GLuint, bindings,
+                                      divisor);
+            }
+        } else {
+            for &(vertex_buffer, ref bindings, offset, stride, divisor) in vertex_buffers {
+                bind_attribute(ctxt, program, vertex_buffer, bindings, offset, stride, divisor);
+            }
         }
 
         VertexArrayObject {
@@ -326,9 +456,90 @@ impl VertexArrayObject {
             destroyed: false,
             element_array_buffer: index_buffer.map(|b| b.get_buffer_id()).unwrap_or(0),
             element_array_buffer_hijacked: Cell::new(false),
+            vertex_buffer_bindings: RefCell::new(SmallVec::new()),
+        }
+    }
+
+    /// Binds the vertex buffers to the binding indices of this VAO, which must be the current
+    /// VAO and must have been created with `attrib_binding` set to true.
+    ///
+    /// Only the binding indices whose buffer, offset or stride have changed are updated.
+    unsafe fn bind_vertex_buffers(&self, ctxt: &mut CommandContext,
+                                  vertex_buffers: &VertexBuffersList)
+    {
+        debug_assert_eq!(ctxt.state.vertex_array, self.id);
+
+        let mut bindings = self.vertex_buffer_bindings.borrow_mut();
+
+        // with `glBindVertexBuffers`, all the binding indices between the first and the last
+        // modified one are changed with a single call
+        if ctxt.version >= &Version(Api::Gl, 4, 4) || ctxt.extensions.gl_arb_multi_bind {
+            let mut range: Option<(usize, usize)> = None;
+            for (index, &(buffer, _, offset, stride, _)) in vertex_buffers.iter().enumerate() {
+                if bindings.get(index) == Some(&(buffer, offset, stride)) {
+                    continue;
+                }
+
+                range = Some(match range {
+                    Some((first, _)) => (first, index),
+                    None => (index, index),
+                });
+            }
+
+            let (first, last) = match range {
+                Some(r) => r,
+                None => return
+            };
+
+            let mut buffers: SmallVec<[gl::types::GLuint; 8]> = SmallVec::new();
+            let mut offsets: SmallVec<[gl::types::GLintptr; 8]> = SmallVec::new();
+            let mut strides: SmallVec<[gl::types::GLsizei; 8]> = SmallVec::new();
+            for &(buffer, _, offset, stride, _) in vertex_buffers[first .. last + 1].iter() {
+                buffers.push(buffer);
+                offsets.push(offset as gl::types::GLintptr);
+                strides.push(stride as gl::types::GLsizei);
+            }
+
+            ctxt.gl.BindVertexBuffers(first as gl::types::GLuint,
+                                      buffers.len() as gl::types::GLsizei, buffers.as_ptr(),
+                                      offsets.as_ptr(), strides.as_ptr());
+
+            for index in first .. last + 1 {
+                let (buffer, _, offset, stride, _) = vertex_buffers[index];
+                if index < bindings.len() {
+                    bindings[index] = (buffer, offset, stride);
+                } else {
+                    bindings.push((buffer, offset, stride));
+                }
+            }
+
+            return;
+        }
+
+        for (index, &(buffer, _, offset, stride, _)) in vertex_buffers.iter().enumerate() {
+            let binding = (buffer, offset, stride);
+
+            if bindings.get(index) == Some(&binding) {
+                continue;
+            }
+
+            ctxt.gl.BindVertexBuffer(index as gl::types::GLuint, buffer,
+                                     offset as gl::types::GLintptr, stride as gl::types::GLsizei);
+
+            if index < bindings.len() {
+                bindings[index] = binding;
+            } else {
+                bindings.push(binding);
+            }
         }
     }
 
+    /// Returns true if a vertex buffer is currently bound to one of the binding indices of
+    /// this VAO.
+    fn uses_vertex_buffer(&self, id: gl::types::GLuint) -> bool {
+        self.vertex_buffer_bindings.borrow().iter().any(|&(b, _, _)| b == id)
+    }
+
     /// Sets this VAO as the current VAO.
     fn bind(&self, ctxt: &mut CommandContext) {
         unsafe {
@@ -411,6 +622,22 @@ fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types
         AttributeType::U16U16 => (gl::UNSIGNED_SHORT, 2, 1),
         AttributeType::U16U16U16 => (gl::UNSIGNED_SHORT, 3, 1),
         AttributeType::U16U16U16U16 => (gl::UNSIGNED_SHORT, 4, 1),
+        AttributeType::I8Normalized => (gl::BYTE, 1, 1),
+        AttributeType::I8I8Normalized => (gl::BYTE, 2, 1),
+        AttributeType::I8I8I8Normalized => (gl::BYTE, 3, 1),
+        AttributeType::I8I8I8I8Normalized => (gl::BYTE, 4, 1),
+        AttributeType::U8Normalized => (gl::UNSIGNED_BYTE, 1, 1),
+        AttributeType::U8U8Normalized => (gl::UNSIGNED_BYTE, 2, 1),
+        AttributeType::U8U8U8Normalized => (gl::UNSIGNED_BYTE, 3, 1),
+        AttributeType::U8U8U8U8Normalized => (gl::UNSIGNED_BYTE, 4, 1),
+        AttributeType::I16Normalized => (gl::SHORT, 1, 1),
+        AttributeType::I16I16Normalized => (gl::SHORT, 2, 1),
+        AttributeType::I16I16I16Normalized => (gl::SHORT, 3, 1),
+        AttributeType::I16I16I16I16Normalized => (gl::SHORT, 4, 1),
+        AttributeType::U16Normalized => (gl::UNSIGNED_SHORT, 1, 1),
+        AttributeType::U16U16Normalized => (gl::UNSIGNED_SHORT, 2, 1),
+        AttributeType::U16U16U16Normalized => (gl::UNSIGNED_SHORT, 3, 1),
+        AttributeType::U16U16U16U16Normalized => (gl::UNSIGNED_SHORT, 4, 1),
         AttributeType::I32 => (gl::INT, 1, 1),
         AttributeType::I32I32 => (gl::INT, 2, 1),
         AttributeType::I32I32I32 => (gl::INT, 3, 1),
@@ -466,11 +693,14 @@ fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types
         AttributeType::F64x4x2 => (gl::DOUBLE, 4, 2),
         AttributeType::F64x4x3 => (gl::DOUBLE, 4, 3),
         AttributeType::F64x4x4 => (gl::DOUBLE, 4, 4),
-        AttributeType::I2I10I10I10Reversed => (gl::INT_2_10_10_10_REV, 1, 1),
-        AttributeType::U2U10U10U10Reversed => (gl::UNSIGNED_INT_2_10_10_10_REV, 1, 1),
-        AttributeType::I10I10I10I2 => (gl::INT_10_10_10_2_OES, 1, 1),
-        AttributeType::U10U10U10U2 => (gl::UNSIGNED_INT_10_10_10_2_OES, 1, 1),
-        AttributeType::F10F11F11UnsignedIntReversed => (gl::UNSIGNED_INT_10F_11F_11F_REV, 1, 1),
+        // the packed types must be passed with a size of 3 or 4 components
+        AttributeType::I2I10I10I10Reversed => (gl::INT_2_10_10_10_REV, 4, 1),
+        AttributeType::U2U10U10U10Reversed => (gl::UNSIGNED_INT_2_10_10_10_REV, 4, 1),
+        AttributeType::I2I10I10I10ReversedNormalized => (gl::INT_2_10_10_10_REV, 4, 1),
+        AttributeType::U2U10U10U10ReversedNormalized => (gl::UNSIGNED_INT_2_10_10_10_REV, 4, 1),
+        AttributeType::I10I10I10I2 => (gl::INT_10_10_10_2_OES, 4, 1),
+        AttributeType::U10U10U10U2 => (gl::UNSIGNED_INT_10_10_10_2_OES, 4, 1),
+        AttributeType::F10F11F11UnsignedIntReversed => (gl::UNSIGNED_INT_10F_11F_11F_REV, 3, 1),
         AttributeType::FixedFloatI16U16 => (gl::FIXED, 1, 1),
     }
 }
@@ -499,6 +729,190 @@ fn bind_vao(ctxt: &mut CommandContext, vao_id: gl::types::GLuint) {
     }
 }
 
+/// Returns true if the vertex buffers can be bound with separate attribute formats and
+/// bindings (`GL_ARB_vertex_attrib_binding`).
+fn is_attrib_binding_usable(ctxt: &CommandContext, vertex_buffers: &VertexBuffersList) -> bool {
+    if !(ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+         ctxt.extensions.gl_arb_vertex_attrib_binding)
+    {
+        return false;
+    }
+
+    // the minimal values of `GL_MAX_VERTEX_ATTRIB_BINDINGS`, `GL_MAX_VERTEX_ATTRIB_STRIDE` and
+    // `GL_MAX_VERTEX_ATTRIB_RELATIVE_OFFSET` ; the rare formats that exceed them use
+    // `glVertexAttribPointer` instead
+    vertex_buffers.len() <= 16 &&
+    vertex_buffers.iter().all(|&(_, ref bindings, _, stride, _)| {
+        stride <= 2048 &&
+        bindings.iter().all(|&(_, offset, ty, _, _)| offset + ty.get_size_bytes() <= 2047)
+    }) &&
+    // the divisor is a property of the binding and not of the attributes, so the buffers whose
+    // attributes have their own divisor use `glVertexAttribDivisor` instead
+    vertex_buffers.iter().all(|&(_, ref bindings, _, _, divisor)| {
+        bindings.iter().all(|&(_, _, _, _, d)| d.is_none() || d == divisor)
+    })
+}
+
+/// Returns the attribute of the program that corresponds to a vertex binding.
+///
+/// Bindings with an explicit location are matched by location, the others by name.
+fn get_program_attribute<'a>(program: &'a Program, name: &str, location: Option<u32>)
+                             -> Option<&'a Attribute>
+{
+    match location {
+        Some(location) => program.attributes().map(|(_, attr)| attr)
+                                 .find(|attr| attr.location == location as i32),
+        None => program.get_attribute(name),
+    }
+}
+
+/// Returns true if the integer values of this type must be normalized when they are read
+/// by a floating-point attribute.
+fn is_normalized(ty: AttributeType) -> bool {
+    match ty {
+        AttributeType::I8Normalized | AttributeType::I8I8Normalized |
+        AttributeType::I8I8I8Normalized | AttributeType::I8I8I8I8Normalized |
+        AttributeType::U8Normalized | AttributeType::U8U8Normalized |
+        AttributeType::U8U8U8Normalized | AttributeType::U8U8U8U8Normalized |
+        AttributeType::I16Normalized | AttributeType::I16I16Normalized |
+        AttributeType::I16I16I16Normalized | AttributeType::I16I16I16I16Normalized |
+        AttributeType::U16Normalized | AttributeType::U16U16Normalized |
+        AttributeType::U16U16U16Normalized | AttributeType::U16U16U16U16Normalized |
+        AttributeType::I2I10I10I10ReversedNormalized |
+        AttributeType::U2U10U10U10ReversedNormalized => true,
+        _ => false
+    }
+}
+
+/// Returns true if one of the vertex buffers provides the given program attribute.
+fn is_attribute_provided(vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize,
+                                            Option<u32>)],
+                         name: &str, attribute: &Attribute) -> bool
+{
+    vertex_buffers.iter().any(|&(_, ref bindings, _, _, _)| {
+        bindings.iter().any(|&(ref n, _, _, location, _)| {
+            match location {
+                Some(location) => location as i32 == attribute.location,
+                None => n == name,
+            }
+        })
+    })
+}
+
+/// Sets the current value of the attributes that the program expects but that no vertex buffer
+/// provides, and returns their locations.
+///
+/// # Panic
+///
+/// Panics if an attribute has no default value, or if the default value doesn't match the type
+/// of the attribute.
+unsafe fn apply_defaults(ctxt: &mut CommandContext, program: &Program,
+                         vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize,
+                                            Option<u32>)],
+                         defaults: &[(Cow<'static, str>, AttributeDefault)])
+                         -> SmallVec<[gl::types::GLuint; 4]>
+{
+    let mut locations = SmallVec::new();
+
+    for (&ref name, attribute) in program.attributes() {
+        if is_attribute_provided(vertex_buffers, name, attribute) {
+            continue;
+        }
+
+        let value = match defaults.iter().find(|&&(ref n, _)| n == name) {
+            Some(&(_, value)) => value,
+            None => panic!("The program attribute `{}` is missing in the vertex bindings", name)
+        };
+
+        if attribute.location == -1 {
+            continue;
+        }
+
+        let location = attribute.location as gl::types::GLuint;
+        let (attribute_ty, _, instances_count) = vertex_binding_type_to_gl(attribute.ty);
+
+        match (attribute_ty, value) {
+            (gl::FLOAT, AttributeDefault::Float(v)) if instances_count == 1 => {
+                ctxt.gl.VertexAttrib4f(location, v[0], v[1], v[2], v[3]);
+            },
+            (gl::INT, AttributeDefault::Int(v)) => {
+                ctxt.gl.VertexAttribI4i(location, v[0], v[1], v[2], v[3]);
+            },
+            (gl::UNSIGNED_INT, AttributeDefault::UnsignedInt(v)) => {
+                ctxt.gl.VertexAttribI4ui(location, v[0], v[1], v[2], v[3]);
+            },
+            _ => panic!("The default value of the program attribute `{}` does not match its \
+                         type. Program expected {:?}, got {:?}.", name, attribute.ty, value)
+        }
+
+        locations.push(location);
+    }
+
+    locations
+}
+
+/// Specifies the format of the attributes of a vertex buffer in the current VAO and associates
+/// them with a binding index. The buffer itself is bound with `glBindVertexBuffer`.
+unsafe fn bind_attribute_format(ctxt: &mut CommandContext, program: &Program,
+                                binding_index: gl::types::GLuint, bindings: &VertexFormat,
+                                divisor: Option<u32>)
+{
+    for &(ref name, offset, ty, location, _) in bindings.iter() {
+        let (data_type, elements_count, instances_count) = vertex_binding_type_to_gl(ty);
+        let normalized = if is_normalized(ty) { gl::TRUE } else { gl::FALSE };
+        // size in bytes of each column of a matrix
+        let column_size = ty.get_size_bytes() / instances_count as usize;
+
+        let attribute = match get_program_attribute(program, name, location) {
+            Some(a) => a,
+            None => continue
+        };
+
+        if attribute.location == -1 {
+            continue;
+        }
+
+        let (attribute_ty, _, _) = vertex_binding_type_to_gl(attribute.ty);
+        match attribute_ty {
+            gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
+            gl::INT | gl::UNSIGNED_INT =>
+                ctxt.gl.VertexAttribIFormat(attribute.location as u32,
+                                            elements_count as gl::types::GLint, data_type,
+                                            offset as gl::types::GLuint),
+
+            gl::FLOAT => {
+                for i in 0..instances_count {
+                    ctxt.gl.VertexAttribFormat((attribute.location + i) as u32,
+                                               elements_count as gl::types::GLint, data_type,
+                                               normalized,
+                                               (offset + i as usize * column_size)
+                                                    as gl::types::GLuint);
+                }
+            },
+
+            gl::DOUBLE | gl::INT64_NV | gl::UNSIGNED_INT64_NV => {
+                for i in 0..instances_count {
+                    ctxt.gl.VertexAttribLFormat((attribute.location + i) as u32,
+                                                elements_count as gl::types::GLint, data_type,
+                                                (offset + i as usize * column_size)
+                                                    as gl::types::GLuint);
+                }
+            },
+
+            _ => unreachable!()
+        }
+
+        for i in 0..instances_count {
+            ctxt.gl.VertexAttribBinding((attribute.location + i) as u32, binding_index);
+            ctxt.gl.EnableVertexAttribArray((attribute.location + i) as u32);
+        }
+    }
+
+    if let Some(divisor) = divisor {
+        ctxt.gl.VertexBindingDivisor(binding_index, divisor);
+    }
+}
+
 /// Binds an individual attribute to the current VAO.
 unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
                          vertex_buffer: gl::types::GLuint, bindings: &VertexFormat,
@@ -520,10 +934,13 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
     }
 
     // binding attributes
-    for &(ref name, offset, ty) in bindings.iter() {
+    for &(ref name, offset, ty, location, attribute_divisor) in bindings.iter() {
         let (data_type, elements_count, instances_count) = vertex_binding_type_to_gl(ty);
+        let normalized = if is_normalized(ty) { gl::TRUE } else { gl::FALSE };
+        // size in bytes of each column of a matrix
+        let column_size = ty.get_size_bytes() / instances_count as usize;
 
-        let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+        let attribute = match get_program_attribute(program, name, location) {
             Some(a) => a,
             None => continue
         };
@@ -541,9 +958,10 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
                 gl::FLOAT => {
                     for i in 0..instances_count {
                         ctxt.gl.VertexAttribPointer((attribute.location + i) as u32,
-                                                    elements_count as gl::types::GLint, data_type, 0,
+                                                    elements_count as gl::types::GLint, data_type,
+                                                    normalized,
                                                     stride as i32,
-                                                    (buffer_offset + offset + (i * elements_count * 4) as usize) as *const libc::c_void)
+                                                    (buffer_offset + offset + i as usize * column_size) as *const libc::c_void)
                     }
                 },
 
@@ -552,7 +970,7 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
                         ctxt.gl.VertexAttribLPointer((attribute.location + i) as u32,
                                                      elements_count as gl::types::GLint, data_type,
                                                      stride as i32,
-                                                     (buffer_offset + offset + (i * elements_count * 8) as usize) as *const libc::c_void)
+                                                     (buffer_offset + offset + i as usize * column_size) as *const libc::c_void)
                     }
                 },
 
@@ -560,7 +978,8 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
             }
 
             for i in 0..instances_count {
-                if let Some(divisor) = divisor {
+                // the divisor of the attribute overrides the one of the buffer
+                if let Some(divisor) = attribute_divisor.or(divisor) {
                     ctxt.gl.VertexAttribDivisor((attribute.location + i) as u32, divisor);
                 }
                 ctxt.gl.EnableVertexAttribArray((attribute.location + i) as u32);
@@ -0,0 +1,123 @@
+extern crate glium;
+
+use glium::texture::{Atlas, UncompressedFloatFormat};
+
+mod support;
+
+fn build_atlas(display: &glium::Display, padding: u32) -> Atlas {
+    Atlas::new(display, UncompressedFloatFormat::U8U8U8U8, 64, 64, padding).unwrap()
+}
+
+#[test]
+fn atlas_upload() {
+    let display = support::build_display();
+    let mut atlas = build_atlas(&display, 1);
+
+    let a = atlas.allocate(2, 2).unwrap();
+    let b = atlas.allocate(3, 2).unwrap();
+
+    atlas.upload(&a, vec![vec![(255u8, 0u8, 0u8, 255u8); 2]; 2]);
+    atlas.upload(&b, vec![vec![(0u8, 255u8, 0u8, 255u8); 3]; 2]);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = atlas.get_texture().read();
+
+    let (ax, ay) = (a.rect.left as usize, a.rect.bottom as usize);
+    let (bx, by) = (b.rect.left as usize, b.rect.bottom as usize);
+    assert_eq!(data[ay][ax], (255, 0, 0, 255));
+    assert_eq!(data[ay + 1][ax + 1], (255, 0, 0, 255));
+    assert_eq!(data[by][bx + 2], (0, 255, 0, 255));
+
+    // the padding around the regions is left empty
+    assert_eq!(data[ay - 1][ax], (0, 0, 0, 0));
+    assert_eq!(data[ay][ax + 2], (0, 0, 0, 0));
+    assert_eq!(data[by][bx - 1], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn atlas_uv_rect() {
+    let display = support::build_display();
+    let mut atlas = build_atlas(&display, 0);
+
+    let region = atlas.allocate(16, 32).unwrap();
+    assert_eq!(atlas.get_uv_rect(&region), [0.0, 0.0, 0.25, 0.5]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn atlas_full() {
+    let display = support::build_display();
+    let mut atlas = build_atlas(&display, 0);
+
+    assert!(atlas.allocate(65, 1).is_none());
+    assert!(atlas.allocate(64, 64).is_some());
+    assert!(atlas.allocate(1, 1).is_none());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn atlas_grow() {
+    let display = support::build_display();
+    let mut atlas = build_atlas(&display, 0);
+
+    let region = atlas.allocate(64, 64).unwrap();
+    atlas.upload(&region, vec![vec![(0u8, 0u8, 255u8, 255u8); 64]; 64]);
+    assert!(atlas.allocate(8, 8).is_none());
+
+    let generation = atlas.get_generation();
+    atlas.grow(&display, 128, 128).unwrap();
+    assert!(atlas.get_generation() != generation);
+    assert_eq!(atlas.get_dimensions(), (128, 128));
+
+    // the existing regions are kept
+    assert!(atlas.is_valid(&region));
+    assert_eq!(atlas.get_uv_rect(&region), [0.0, 0.0, 0.5, 0.5]);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = atlas.get_texture().read();
+    assert_eq!(data[0][0], (0, 0, 255, 255));
+    assert_eq!(data[63][63], (0, 0, 255, 255));
+    assert_eq!(data[64][64], (0, 0, 0, 0));
+
+    let other = atlas.allocate(8, 8).unwrap();
+    assert_eq!((other.rect.left, other.rect.bottom), (64, 0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn atlas_clear() {
+    let display = support::build_display();
+    let mut atlas = build_atlas(&display, 0);
+
+    let region = atlas.allocate(64, 64).unwrap();
+    atlas.clear();
+
+    assert!(!atlas.is_valid(&region));
+    assert!(atlas.allocate(64, 64).is_some());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn atlas_upload_after_clear() {
+    let display = support::build_display();
+    let mut atlas = build_atlas(&display, 0);
+
+    let region = atlas.allocate(1, 1).unwrap();
+    atlas.clear();
+    atlas.upload(&region, vec![vec![(0u8, 0u8, 0u8, 0u8)]]);
+}
+
+#[test]
+#[should_panic]
+fn atlas_upload_wrong_dimensions() {
+    let display = support::build_display();
+    let mut atlas = build_atlas(&display, 0);
+
+    let region = atlas.allocate(2, 2).unwrap();
+    atlas.upload(&region, vec![vec![(0u8, 0u8, 0u8, 0u8)]]);
+}
@@ -307,3 +307,345 @@ attribute_test!(attribute_vec3_i32, [i32; 3], "vec3", [0, 0, 0], "vec4(field1, 1
 attribute_test!(attribute_vec3_tuple_i32, (i32, i32, i32), "vec3", (0, 0, 0), "vec4(field1, 1.0)");
 attribute_test!(attribute_vec4_i32, [i32; 4], "vec4", [0, 0, 0, 0], "field1");
 attribute_test!(attribute_vec4_tuple_i32, (i32, i32, i32, i32), "vec4", (0, 0, 0, 0), "field1");
+
+#[cfg(feature = "half")]
+attribute_test!(attribute_float_f16, glium::f16, "float", glium::f16::from_f32(0.0),
+                "vec4(field1, 0.0, 0.0, 1.0)");
+#[cfg(feature = "half")]
+attribute_test!(attribute_vec2_f16, [glium::f16; 2], "vec2", [glium::f16::from_f32(0.0); 2],
+                "vec4(field1, 0.0, 1.0)");
+#[cfg(feature = "half")]
+attribute_test!(attribute_vec3_tuple_f16, (glium::f16, glium::f16, glium::f16), "vec3",
+                (glium::f16::from_f32(0.0), glium::f16::from_f32(0.0),
+                 glium::f16::from_f32(0.0)), "vec4(field1, 1.0)");
+#[cfg(feature = "half")]
+attribute_test!(attribute_vec4_f16, [glium::f16; 4], "vec4", [glium::f16::from_f32(0.0); 4],
+                "field1");
+
+#[test]
+fn conflicting_locations() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+        field2: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1 location(0), field2 location(0));
+
+    match glium::VertexBuffer::new(&display, &[Vertex { field1: [0.0, 0.0], field2: [0.0, 0.0] }]) {
+        Err(glium::vertex::BufferCreationError::ConflictingLocations) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn attributes_by_location() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        pos: [f32; 2],
+        packed_color: u32,
+    }
+
+    implement_vertex!(Vertex, pos location(0), packed_color type(U8U8U8U8Normalized) location(1));
+
+    // the bytes are read in memory order, so this is red with a full alpha on both endiannesses
+    let color = unsafe { ::std::mem::transmute::<[u8; 4], u32>([255, 0, 0, 255]) };
+
+    let vertex_buffer = glium::VertexBuffer::new(&display, &[
+        Vertex { pos: [-1.0,  1.0], packed_color: color },
+        Vertex { pos: [ 1.0,  1.0], packed_color: color },
+        Vertex { pos: [-1.0, -1.0], packed_color: color },
+        Vertex { pos: [ 1.0, -1.0], packed_color: color },
+    ]).unwrap();
+
+    let program = match program!(&display,
+        330 => {
+            vertex: "
+                #version 330
+
+                layout(location = 0) in vec2 position;
+                layout(location = 1) in vec4 color;
+                out vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 330
+
+                in vec4 v_color;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = v_color;
+                }
+            "
+        }
+    ) {
+        Ok(p) => p,
+        Err(glium::program::ProgramChooserCreationError::NoVersion) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer,
+                              &glium::index::NoIndices(PrimitiveType::TriangleStrip),
+                              &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn packed_attributes() {
+    use glium::vertex::{pack_f16, pack_snorm_10_10_10_2, AttributeType};
+
+    let display = support::build_display();
+
+    if !AttributeType::F16F16F16F16.is_supported(&display) ||
+       !AttributeType::I2I10I10I10ReversedNormalized.is_supported(&display)
+    {
+        return;
+    }
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [u16; 4],
+        normal: u32,
+    }
+
+    implement_vertex!(Vertex, position type(F16F16F16F16) location(0),
+                      normal type(I2I10I10I10ReversedNormalized) location(1));
+
+    let vertex = |x: f32, y: f32| Vertex {
+        position: [pack_f16(x), pack_f16(y), pack_f16(0.0), pack_f16(1.0)],
+        normal: pack_snorm_10_10_10_2([1.0, -1.0, -1.0], 1.0),
+    };
+
+    let vertex_buffer = glium::VertexBuffer::new(&display, &[
+        vertex(-1.0,  1.0), vertex(1.0,  1.0), vertex(-1.0, -1.0), vertex(1.0, -1.0),
+    ]).unwrap();
+
+    let program = match program!(&display,
+        330 => {
+            vertex: "
+                #version 330
+
+                layout(location = 0) in vec4 position;
+                layout(location = 1) in vec4 normal;
+                out vec4 v_color;
+
+                void main() {
+                    v_color = vec4(normal.xyz * 0.5 + 0.5, normal.w);
+                    gl_Position = position;
+                }
+            ",
+            fragment: "
+                #version 330
+
+                in vec4 v_color;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = v_color;
+                }
+            "
+        }
+    ) {
+        Ok(p) => p,
+        Err(glium::program::ProgramChooserCreationError::NoVersion) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer,
+                              &glium::index::NoIndices(PrimitiveType::TriangleStrip),
+                              &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn default_attribute_value() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let vertex_buffer = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] },
+        Vertex { position: [ 1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] },
+        Vertex { position: [ 1.0, -1.0] },
+    ]).unwrap();
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec4 color;
+                out vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                in vec4 v_color;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = v_color;
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+                varying vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                varying vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            "
+        },
+    ).unwrap();
+
+    let defaults = vertex_defaults! { color: [1.0, 0.0, 0.0, 1.0f32] };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&vertex_buffer, &defaults),
+                              &glium::index::NoIndices(PrimitiveType::TriangleStrip),
+                              &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic(expected = "Add `#[repr(C)]` to the struct")]
+fn padded_vertex() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 3],
+        field2: u8,
+    }
+
+    implement_vertex!(Vertex, field1, field2);
+
+    <Vertex as glium::Vertex>::build_bindings();
+}
+
+#[test]
+#[should_panic(expected = "pass all its fields to the macro")]
+fn vertex_missing_field() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 3],
+        field2: f32,
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    <Vertex as glium::Vertex>::build_bindings();
+}
+
+#[test]
+fn array_attribute_names() {
+    let display = support::build_display();
+
+    let program = match program!(&display,
+        150 => {
+            vertex: "
+                #version 150
+
+                in vec2 position;
+                in float weights[2];
+
+                void main() {
+                    gl_Position = vec4(position * (weights[0] + weights[1]), 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 150
+
+                out vec4 color;
+
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            "
+        }
+    ) {
+        Ok(p) => p,
+        Err(glium::program::ProgramChooserCreationError::NoVersion) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    // drivers report either `weights` or `weights[0]`, the latter is normalized
+    assert!(program.attributes().any(|(name, _)| name == "weights"));
+    assert!(program.attributes().all(|(name, _)| !name.ends_with("[0]")));
+
+    // both forms can be used for lookups
+    let attribute = program.get_attribute("weights").unwrap();
+    assert_eq!(attribute.size, 2);
+    assert_eq!(program.get_attribute("weights[0]").unwrap().location, attribute.location);
+    assert!(program.get_attribute("position").is_some());
+
+    display.assert_no_error(None);
+}
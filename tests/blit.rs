@@ -56,3 +56,246 @@ fn blit_texture_to_window() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn generate_mipmaps_via_blit() {
+    let display = support::build_display();
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::EmptyMipmaps,
+                                              6, 5).unwrap();
+    assert_eq!(texture.get_mipmap_levels(), 3);
+
+    texture.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+    texture.generate_mipmaps_via_blit().unwrap();
+
+    let level = texture.mipmap(2).unwrap();
+    assert_eq!(level.get_width(), 1);
+    assert_eq!(level.get_height(), Some(1));
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = level.first_layer().into_image(None).unwrap()
+                                                .raw_read(&Rect { left: 0, bottom: 0,
+                                                                  width: 1, height: 1 }).unwrap();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn blit_level_to() {
+    let display = support::build_display();
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::EmptyMipmaps,
+                                              8, 8).unwrap();
+
+    texture.as_surface().clear_color(1.0, 0.0, 0.0, 1.0);
+    texture.blit_level_to(0, 3).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.mipmap(3).unwrap().first_layer()
+                                                  .into_image(None).unwrap()
+                                                  .raw_read(&Rect { left: 0, bottom: 0,
+                                                                    width: 1, height: 1 })
+                                                  .unwrap();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_texture_1to1() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![
+        vec![(255u8, 0u8, 0u8, 255u8), (0, 255, 0, 255)],
+        vec![(0u8, 0u8, 255u8, 255u8), (255, 255, 255, 255)],
+    ]).unwrap();
+
+    let target = support::build_renderable_texture(&display);
+    target.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    target.as_surface().draw_texture_1to1(&texture, (3, 5));
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = target.read();
+    let height = data.len();
+
+    // the last row of the texture is at the top of the destination rectangle
+    assert_eq!(data[height - 6][3], (0, 0, 255, 255));
+    assert_eq!(data[height - 6][4], (255, 255, 255, 255));
+    assert_eq!(data[height - 7][3], (255, 0, 0, 255));
+    assert_eq!(data[height - 7][4], (0, 255, 0, 255));
+
+    assert_eq!(data[height - 5][3], (0, 0, 0, 0));
+    assert_eq!(data[height - 8][3], (0, 0, 0, 0));
+    assert_eq!(data[height - 6][2], (0, 0, 0, 0));
+    assert_eq!(data[height - 6][5], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_texture_1to1_clipped() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![
+        vec![(255u8, 0u8, 0u8, 255u8), (0, 255, 0, 255)],
+        vec![(0u8, 0u8, 255u8, 255u8), (255, 255, 255, 255)],
+    ]).unwrap();
+
+    let target = support::build_renderable_texture(&display);
+    target.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    target.as_surface().draw_texture_1to1(&texture, (-1, -1));
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = target.read();
+    let height = data.len();
+
+    // only the bottom-right texel is visible, in the top-left corner
+    assert_eq!(data[height - 1][0], (0, 255, 0, 255));
+    assert_eq!(data[height - 1][1], (0, 0, 0, 0));
+    assert_eq!(data[height - 2][0], (0, 0, 0, 0));
+
+    // entirely outside of the surface
+    target.as_surface().draw_texture_1to1(&texture, (-10, -10));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn copy_depth_from() {
+    let display = support::build_display();
+
+    let color1 = support::build_renderable_texture(&display);
+    let depth1 = match glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                    glium::texture::DepthFormat::I24, 1024, 1024)
+    {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    let color2 = support::build_renderable_texture(&display);
+    let depth2 = glium::framebuffer::DepthRenderBuffer::new(&display,
+                                            glium::texture::DepthFormat::I24, 1024, 1024).unwrap();
+
+    let source = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display, &color1,
+                                                                          &depth1).unwrap();
+    let target = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display, &color2,
+                                                                          &depth2).unwrap();
+
+    target.copy_depth_from(&source).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn copy_depth_from_format_mismatch() {
+    let display = support::build_display();
+
+    let color1 = support::build_renderable_texture(&display);
+    let depth1 = match glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                    glium::texture::DepthFormat::I24, 1024, 1024)
+    {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    let color2 = support::build_renderable_texture(&display);
+    let depth2 = match glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                    glium::texture::DepthFormat::F32, 1024, 1024)
+    {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    let source = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display, &color1,
+                                                                          &depth1).unwrap();
+    let target = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display, &color2,
+                                                                          &depth2).unwrap();
+
+    match target.copy_depth_from(&source) {
+        Err(glium::BlitError::DepthFormatMismatch { .. }) => (),
+        r => panic!("{:?}", r)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn copy_depth_from_no_depth_buffer() {
+    let display = support::build_display();
+
+    let source = support::build_renderable_texture(&display);
+    let target = support::build_renderable_texture(&display);
+
+    match target.as_surface().copy_depth_from(&source.as_surface()) {
+        Err(glium::BlitError::NoDepthBuffer) => (),
+        r => panic!("{:?}", r)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn blit_integer_color_mismatch() {
+    let display = support::build_display();
+
+    let source = match glium::texture::IntegralTexture2d::empty_renderable(&display,
+                                            glium::texture::UncompressedIntFormat::I32I32I32I32,
+                                            glium::texture::MipmapsOption::NoMipmap, 64, 64)
+    {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let target = match glium::texture::UnsignedTexture2d::empty_renderable(&display,
+                                            glium::texture::UncompressedUintFormat::U32U32U32U32,
+                                            glium::texture::MipmapsOption::NoMipmap, 64, 64)
+    {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let source = glium::framebuffer::SimpleFrameBuffer::new(&display, &source).unwrap();
+    let target = glium::framebuffer::SimpleFrameBuffer::new(&display, &target).unwrap();
+
+    let rect = Rect { left: 0, bottom: 0, width: 64, height: 64 };
+    let blit_target = BlitTarget { left: 0, bottom: 0, width: 64, height: 64 };
+
+    match source.blit_buffers(&rect, &target, &blit_target,
+                              glium::uniforms::MagnifySamplerFilter::Nearest,
+                              glium::BlitMask::color())
+    {
+        Err(glium::BlitError::IntegerColorMismatch) => (),
+        r => panic!("{:?}", r)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn blit_from_empty_framebuffer() {
+    use glium::framebuffer::EmptyFrameBuffer;
+
+    let display = support::build_display();
+
+    // ignore the test
+    if !EmptyFrameBuffer::is_supported(&display) {
+        return;
+    }
+
+    let source = EmptyFrameBuffer::new(&display, 64, 64, None, None, true).unwrap();
+    let target = support::build_renderable_texture(&display);
+
+    let rect = Rect { left: 0, bottom: 0, width: 64, height: 64 };
+    let blit_target = BlitTarget { left: 0, bottom: 0, width: 64, height: 64 };
+
+    match source.blit_buffers(&rect, &target.as_surface(), &blit_target,
+                              glium::uniforms::MagnifySamplerFilter::Nearest,
+                              glium::BlitMask::color())
+    {
+        Err(glium::BlitError::NoAttachment) => (),
+        r => panic!("{:?}", r)
+    };
+
+    display.assert_no_error(None);
+}
@@ -455,6 +455,44 @@ fn buffer_dynamic_write() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn buffer_try_write_wrong_length() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, field);
+
+    fn write(vb: &glium::VertexBuffer<Vertex>) -> Result<(), glium::Error> {
+        try!(vb.try_write(&[Vertex { field: [1, 2] }]));
+        Ok(())
+    }
+
+    let vb = glium::VertexBuffer::new(&display,
+        &[
+            Vertex { field: [0, 0] },
+            Vertex { field: [0, 0] },
+        ]
+    ).unwrap();
+
+    match write(&vb) {
+        Err(glium::Error::BufferWrite(glium::buffer::WriteError::LengthMismatch {
+            expected: 4, obtained: 2
+        })) => (),
+        _ => panic!()
+    }
+
+    match vb.slice(0 .. 1).unwrap().try_write(&[Vertex { field: [1, 2] }; 2]) {
+        Err(glium::buffer::WriteError::LengthMismatch { expected: 2, obtained: 4 }) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn buffer_immutable_write_slice() {
     let display = support::build_display();
@@ -525,6 +563,258 @@ fn buffer_dynamic_write_slice() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn typed_buffer_write_at() {
+    let display = support::build_display();
+
+    let buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Default).unwrap();
+    assert_eq!(buffer.len(), 4);
+
+    buffer.write_at(1, &[12, 13]);
+
+    let data = match buffer.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(data, vec![1, 12, 13, 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn typed_buffer_write_at_out_of_range() {
+    let display = support::build_display();
+
+    let buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Default).unwrap();
+    buffer.write_at(3, &[12, 13]);
+}
+
+#[test]
+fn persistent_buffer_insert_fence() {
+    let display = support::build_display();
+
+    let mut buffer = match glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Persistent)
+    {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    buffer.insert_fence();
+    assert_eq!(buffer.map_read()[2], 3);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_clone_buffer() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let vb = glium::VertexBuffer::dynamic(&display,
+        &[Vertex { field1: [2, 3] }, Vertex { field1: [5, 7] }]
+    ).unwrap();
+
+    let clone = vb.clone_buffer(&display).unwrap();
+    vb.write(&[Vertex { field1: [0, 0] }, Vertex { field1: [0, 0] }]);
+
+    assert_eq!(clone.len(), 2);
+    assert_eq!(clone.get_bindings(), vb.get_bindings());
+
+    let data = match clone.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(data[0].field1, [2, 3]);
+    assert_eq!(data[1].field1, [5, 7]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn persistent_buffer_clone_buffer() {
+    let display = support::build_display();
+
+    let mut buffer = match glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Persistent)
+    {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    buffer.map()[1] = 12;
+
+    let mut clone = buffer.clone_buffer(&display).unwrap();
+    assert_eq!(clone.is_persistent(), buffer.is_persistent());
+    assert_eq!(&*clone.map_read(), &[1, 12, 3, 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_clear_to_zero() {
+    let display = support::build_display();
+
+    let buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Default).unwrap();
+    buffer.clear_to_zero();
+
+    let data = match buffer.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(data, vec![0, 0, 0, 0]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_slice_clear_with_value() {
+    let display = support::build_display();
+
+    let buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Default).unwrap();
+    buffer.slice(1 .. 3).unwrap().clear_with_value(&[0xff, 0xff, 0xff, 0xff]).unwrap();
+
+    let data = match buffer.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(data, vec![1, 0xffffffff, 0xffffffff, 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_clear_with_value_invalid_size() {
+    let display = support::build_display();
+
+    let buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Default).unwrap();
+
+    assert_eq!(buffer.clear_with_value(&[0, 0, 0]),
+               Err(glium::buffer::ClearError::InvalidValueSize));
+    assert_eq!(buffer.slice(1 .. 2).unwrap().clear_with_value(&[0; 8]),
+               Err(glium::buffer::ClearError::RangeNotAligned));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn persistent_coherent_mapping() {
+    let display = support::build_display();
+
+    let mut buffer = match glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::PersistentCoherent)
+    {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    {
+        let mut mapping = buffer.map();
+        mapping[2] = 12;
+        mapping.flush_range(0, 16);
+    }
+
+    let data = match buffer.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(data, vec![1, 2, 12, 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn persistent_mapping_flush_range() {
+    let display = support::build_display();
+
+    let mut buffer = match glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Persistent)
+    {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    {
+        let mut mapping = buffer.map_write();
+        mapping.set(1, 12);
+        mapping.flush_range(4, 4);
+    }
+
+    let data = match buffer.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(data, vec![1, 12, 3, 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn mapping_flush_range_out_of_bounds() {
+    let display = support::build_display();
+
+    let mut buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Persistent).unwrap();
+
+    let mut mapping = buffer.map_write();
+    mapping.flush_range(8, 16);
+}
+
+#[test]
+fn buffer_parameters() {
+    let display = support::build_display();
+
+    let buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Dynamic).unwrap();
+
+    assert_eq!(buffer.get_size(), 16);
+    assert_eq!(buffer.get_usage(), BufferMode::Dynamic);
+    assert!(!buffer.is_mapped());
+
+    match buffer.verify_against_gl() {
+        Ok(()) | Err(glium::buffer::VerificationError::NotSupported) => (),
+        Err(e) => panic!("{:?}", e)
+    }
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn zero_sized_immutable_buffer() {
     let display = support::build_display();
@@ -977,3 +1267,289 @@ fn copy_to_slice() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn ring_buffer_wrap_around() {
+    let display = support::build_display();
+
+    let mut ring = glium::buffer::RingBuffer::<u32>::new(&display,
+                                                         glium::buffer::BufferType::ArrayBuffer,
+                                                         16).unwrap();
+
+    for frame in 0 .. 8 {
+        {
+            let slice = ring.allocate(6);
+            assert!(slice.get_offset() + slice.len() <= 16);
+            slice.write(&[frame; 6]);
+        }
+
+        ring.next_frame();
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn ring_buffer_wrap_around_in_one_frame() {
+    let display = support::build_display();
+
+    let mut ring = glium::buffer::RingBuffer::<u32>::new(&display,
+                                                         glium::buffer::BufferType::ArrayBuffer,
+                                                         16).unwrap();
+
+    for frame in 0 .. 8 {
+        let slice = ring.allocate(6);
+        assert!(slice.get_offset() + slice.len() <= 16);
+        slice.write(&[frame; 6]);
+    }
+
+    ring.next_frame();
+    assert_eq!(ring.get_fence_failures_count(), 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn ring_buffer_zero_sized() {
+    let display = support::build_display();
+    let _ring = glium::buffer::RingBuffer::<()>::new(&display,
+                                                     glium::buffer::BufferType::ArrayBuffer, 16);
+}
+
+#[test]
+fn ring_buffer_vertex_source() {
+    let display = support::build_display();
+    let (_, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let mut ring = glium::buffer::RingBuffer::<Vertex>::vertex(&display, 64).unwrap();
+    let texture = support::build_renderable_texture(&display);
+
+    for _ in 0 .. 4 {
+        let slice = ring.allocate(4);
+        slice.write(&[
+            Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+            Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+        ]);
+
+        texture.as_surface().draw(slice,
+                                  &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                                  &program, &uniform!{}, &Default::default()).unwrap();
+
+        ring.next_frame();
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_download_upload() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [u8; 2],
+        field2: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, field1, field2);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { field1: [ 2,  3], field2: [ 5,  7] },
+        Vertex { field1: [12, 13], field2: [15, 17] },
+    ]).unwrap();
+
+    let mut bytes = Vec::new();
+    match vb.download_to_writer(&mut bytes) {
+        Err(_) => return,
+        Ok(_) => ()
+    };
+    assert_eq!(bytes, vec![2, 3, 5, 7, 12, 13, 15, 17]);
+
+    let loaded = unsafe {
+        glium::vertex::VertexBufferAny::upload_from_reader(&display, &mut &bytes[..],
+                                                           vb.get_bindings().clone(), 4, 2)
+                                                           .unwrap()
+    };
+    assert_eq!(loaded.len(), 2);
+
+    let mut bytes2 = Vec::new();
+    loaded.download_to_writer(&mut bytes2).unwrap();
+    assert_eq!(bytes2, bytes);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn index_buffer_upload_from_reader() {
+    let display = support::build_display();
+
+    let data = [1u16, 2, 3, 4, 5, 6];
+    let bytes = unsafe {
+        ::std::slice::from_raw_parts(data.as_ptr() as *const u8, 6 * 2)
+    };
+
+    let ib = glium::IndexBuffer::<u16>::upload_from_reader(&display, &mut &bytes[..],
+                                                           glium::index::PrimitiveType::TrianglesList,
+                                                           6).unwrap();
+
+    match ib.read() {
+        Ok(read) => assert_eq!(read, data.to_vec()),
+        Err(_) => ()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn upload_from_reader_not_enough_data() {
+    let display = support::build_display();
+
+    let bytes = [0u8; 5];
+    let result = glium::IndexBuffer::<u16>::upload_from_reader(&display, &mut &bytes[..],
+                                                               glium::index::PrimitiveType::Points,
+                                                               3);
+    assert!(result.is_err());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_map_if_supported() {
+    let display = support::build_display();
+
+    let mut buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[1, 2, 3, 4],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Default).unwrap();
+
+    if !glium::buffer::is_buffer_mapping_supported(&display) {
+        assert!(buffer.map_if_supported().is_none());
+        return;
+    }
+
+    buffer.map_write_if_supported().unwrap().set(1, 12);
+    assert_eq!(&*buffer.map_read_if_supported().unwrap(), &[1, 12, 3, 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn mapping_reinterpret_and_split() {
+    let display = support::build_display();
+
+    let mut buffer = glium::buffer::TypedBuffer::<[u32]>::new(&display, &[0, 0, 0, 0],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Default).unwrap();
+
+    {
+        let mut mapping = buffer.map();
+        assert_eq!(mapping.len(), 4);
+        assert_eq!(mapping.as_bytes().len(), 16);
+
+        {
+            let (first, second) = mapping.split_at_mut(1);
+            first[0] = 1;
+            second[2] = 4;
+        }
+
+        let halves = unsafe { mapping.reinterpret::<u16>() };
+        assert_eq!(halves.len(), 8);
+    }
+
+    assert_eq!(&*buffer.map_read(), &[1, 0, 0, 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn mapping_reinterpret_wrong_size() {
+    let display = support::build_display();
+
+    let mut buffer = glium::buffer::TypedBuffer::<[u8]>::new(&display, &[0, 0, 0],
+                                                         glium::buffer::BufferType::ArrayBuffer,
+                                                         BufferMode::Default).unwrap();
+
+    let mut mapping = buffer.map();
+    unsafe { mapping.reinterpret::<u16>(); }
+}
+
+#[test]
+fn mapping_fill_bytes_then_draw() {
+    let display = support::build_display();
+    let (_, index_buffer, program) = support::build_fullscreen_red_pipeline(&display);
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let mut vertex_buffer: glium::VertexBuffer<Vertex> =
+                                        glium::VertexBuffer::empty_dynamic(&display, 4).unwrap();
+
+    // raw data, for example loaded from a file
+    let positions: [f32; 8] = [-1.0, 1.0, 1.0, 1.0, -1.0, -1.0, 1.0, -1.0];
+    let blob = unsafe {
+        std::slice::from_raw_parts(positions.as_ptr() as *const u8, mem::size_of_val(&positions))
+    };
+
+    {
+        let mut mapping = vertex_buffer.map();
+        let bytes = mapping.as_bytes_mut();
+        assert_eq!(bytes.len(), blob.len());
+        for (dest, src) in bytes.iter_mut().zip(blob.iter()) {
+            *dest = *src;
+        }
+    }
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program,
+                              &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data[512][512], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn write_mapping_copy_from_slice() {
+    let display = support::build_display();
+
+    let mut buffer = match glium::buffer::TypedBuffer::<[u8]>::new(&display, &[0, 1, 2, 3, 4, 5],
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          BufferMode::Persistent)
+    {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    {
+        let mut mapping = buffer.map_write();
+        mapping.copy_from_slice(2, &[12, 13, 14]);
+
+        let ptr = mapping.as_write_ptr();
+        unsafe { (*ptr)[5] = 15; }
+    }
+
+    let data = match buffer.read() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap()
+    };
+
+    assert_eq!(data, vec![0, 1, 12, 13, 14, 15]);
+
+    display.assert_no_error(None);
+}
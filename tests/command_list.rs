@@ -0,0 +1,159 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::index::PrimitiveType;
+
+mod support;
+
+#[test]
+fn replay_command_list() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let uniforms = uniform! {};
+    let params = Default::default();
+
+    let mut list = glium::CommandList::new(&display);
+    list.draw(&vb, &ib, &program, &uniforms, &params).unwrap();
+    list.draw(&vb, &ib, &program, &uniforms, &params).unwrap();
+    assert_eq!(list.len(), 2);
+
+    let texture = support::build_renderable_texture(&display);
+
+    for _ in 0 .. 2 {
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw_command_list(&list).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        for row in data.iter() {
+            for pixel in row.iter() {
+                assert_eq!(pixel, &(255, 0, 0, 255));
+            }
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn command_list_reads_buffers_content() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let build = |color| {
+        [
+            Vertex { position: [-1.0,  1.0], color: color },
+            Vertex { position: [ 1.0,  1.0], color: color },
+            Vertex { position: [-1.0, -1.0], color: color },
+            Vertex { position: [ 1.0, -1.0], color: color },
+        ]
+    };
+
+    let vertex_buffer = glium::VertexBuffer::dynamic(&display, &build([1.0, 0.0, 0.0])).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    let indices = glium::index::NoIndices(PrimitiveType::TriangleStrip);
+    let uniforms = uniform! {};
+    let params = Default::default();
+
+    let mut list = glium::CommandList::new(&display);
+    list.draw(&vertex_buffer, &indices, &program, &uniforms, &params).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw_command_list(&list).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    vertex_buffer.write(&build([0.0, 1.0, 0.0]));
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw_command_list(&list).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn command_list_validates_when_recording() {
+    let display = support::build_display();
+    let (vb, _, program) = support::build_fullscreen_red_pipeline(&display);
+    let uniforms = uniform! {};
+    let params = Default::default();
+    let indices = glium::index::NoIndices(PrimitiveType::TriangleStrip);
+
+    let mut list = glium::CommandList::new(&display);
+    match list.draw((&vb, glium::vertex::EmptyVertexAttributes { len: 2 }), &indices, &program, &uniforms, &params) {
+        Err(glium::DrawError::VerticesSourcesLengthMismatch) => (),
+        _ => panic!()
+    };
+
+    assert!(list.is_empty());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn command_list_rejects_buffers_from_another_context() {
+    let display1 = support::build_display();
+    let display2 = support::build_display();
+
+    let (vb1, ib1, program1) = support::build_fullscreen_red_pipeline(&display1);
+    let (vb2, ib2, program2) = support::build_fullscreen_red_pipeline(&display2);
+    let uniforms = uniform! {};
+    let params = Default::default();
+
+    let mut list = glium::CommandList::new(&display1);
+    match list.draw(&vb2, &ib1, &program1, &uniforms, &params) {
+        Err(glium::DrawError::WrongContext { object_kind: "vertex buffer" }) => (),
+        e => panic!("{:?}", e)
+    };
+    assert!(list.is_empty());
+
+    let mut list = glium::CommandList::new(&display2);
+    list.draw(&vb2, &ib2, &program2, &uniforms, &params).unwrap();
+
+    let texture = support::build_renderable_texture(&display1);
+    match texture.as_surface().draw_command_list(&list) {
+        Err(glium::DrawError::WrongContext { object_kind: "vertex buffer" }) => (),
+        e => panic!("{:?}", e)
+    };
+
+    display1.assert_no_error(None);
+    display2.assert_no_error(None);
+}
@@ -0,0 +1,94 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+
+mod support;
+
+#[test]
+fn display_dropped_before_resources() {
+    let (display, errors) = support::build_display_with_error_counter();
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+    drop(display);
+
+    // the resources must still be usable
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    drop(vb);
+    drop(texture);
+    drop(ib);
+    drop(program);
+
+    assert_eq!(errors.get(), 0);
+}
+
+#[test]
+fn resources_dropped_before_display() {
+    let (display, errors) = support::build_display_with_error_counter();
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    drop(vb);
+    drop(texture);
+    drop(ib);
+    drop(program);
+    drop(display);
+
+    assert_eq!(errors.get(), 0);
+}
+
+#[test]
+fn asset_cache_outlives_display() {
+    // the fields are dropped in the order in which they are declared
+    struct Application {
+        display: glium::Display,
+        textures: Vec<glium::Texture2d>,
+    }
+
+    let (display, errors) = support::build_display_with_error_counter();
+
+    let textures = (0 .. 4).map(|_| support::build_renderable_texture(&display)).collect();
+    let application = Application { display: display, textures: textures };
+
+    assert_eq!(application.textures.len(), 4);
+    drop(application);
+
+    assert_eq!(errors.get(), 0);
+}
+
+#[test]
+#[should_panic(expected = "panic while the resources are alive")]
+fn resources_dropped_during_unwinding() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    // the display is dropped first, then the resources
+    let _resources = (display, texture, vb, ib, program);
+    panic!("panic while the resources are alive");
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "objects of type texture are alive")]
+fn texture_dropped_during_unwinding_inside_operation() {
+    use glium::context::{ObjectKind, ObjectLimit};
+
+    let display = support::build_display();
+
+    // the texture is created then immediately destroyed while glium is still executing
+    // commands ; this must not turn the panic into an abort
+    let count = display.get_live_object_counts().unwrap().textures;
+    display.set_live_object_limit(ObjectKind::Texture, ObjectLimit::Panic(count));
+    support::build_renderable_texture(&display);
+}
@@ -1,7 +1,8 @@
 #[macro_use]
 extern crate glium;
+extern crate libc;
 
-use glium::Surface;
+use glium::{CapabilitiesSource, Surface};
 
 mod support;
 
@@ -94,6 +95,215 @@ fn sync() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn linear_sync_wait_server() {
+    let display = support::build_display();
+
+    let fence = glium::LinearSyncFence::new(&display);
+    if let Ok(fence) = fence {
+        fence.wait_server(&display);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn upload_context_texture_sampled_by_display() {
+    let display = support::build_display();
+
+    // headless displays can't share their objects
+    let upload = match display.build_upload_context() {
+        Ok(upload) => upload,
+        Err(_) => return
+    };
+
+    let texture = support::build_unicolor_texture2d(&upload, 1.0, 0.0, 0.0);
+    if let Ok(fence) = glium::LinearSyncFence::new(&upload) {
+        fence.wait_server(&display);
+    }
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D tex;
+
+            void main() {
+                gl_FragColor = texture2D(tex, vec2(0.5, 0.5));
+            }
+        ",
+        None).unwrap();
+
+    let target = support::build_renderable_texture(&display);
+    target.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    target.as_surface().draw(&vb, &ib, &program, &uniform!{ tex: &texture },
+                             &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = target.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    upload.assert_no_error(None);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn memory_usage() {
+    let display = support::build_display();
+
+    let before = display.get_memory_usage();
+
+    let buffer = glium::VertexBuffer::new(&display, &[
+        (0.0f32, 0.0f32), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)
+    ]).unwrap();
+    let texture = glium::Texture2d::empty(&display, 64, 64).unwrap();
+
+    let during = display.get_memory_usage();
+    assert_eq!(during.buffer_count, before.buffer_count + 1);
+    assert_eq!(during.buffers_bytes, before.buffers_bytes + 32);
+    assert_eq!(during.texture_count, before.texture_count + 1);
+    assert!(during.textures_bytes > before.textures_bytes);
+
+    drop(buffer);
+    drop(texture);
+
+    let after = display.get_memory_usage();
+    assert_eq!(after.buffer_count, before.buffer_count);
+    assert_eq!(after.buffers_bytes, before.buffers_bytes);
+    assert_eq!(after.texture_count, before.texture_count);
+    assert_eq!(after.textures_bytes, before.textures_bytes);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn memory_usage_texture_format() {
+    let display = support::build_display();
+
+    let before = display.get_memory_usage();
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::F16F16,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              64, 64).unwrap();
+
+    let during = display.get_memory_usage();
+    assert_eq!(during.textures_bytes, before.textures_bytes + 64 * 64 * 4);
+
+    drop(texture);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn live_object_counts() {
+    let display = support::build_display();
+
+    let before = match display.get_live_object_counts() {
+        Some(c) => c,
+        None => {
+            // objects are only tracked in debug builds
+            assert!(!cfg!(debug_assertions));
+            return;
+        }
+    };
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    let during = display.get_live_object_counts().unwrap();
+    assert_eq!(during.buffers, before.buffers + 2);
+    assert_eq!(during.textures, before.textures + 1);
+    assert_eq!(during.programs, before.programs + 1);
+
+    drop(vb);
+    drop(ib);
+    drop(texture);
+    drop(program);
+
+    let after = display.get_live_object_counts().unwrap();
+    assert_eq!(after.buffers, before.buffers);
+    assert_eq!(after.textures, before.textures);
+    assert_eq!(after.framebuffers, before.framebuffers);
+    assert_eq!(after.programs, before.programs);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn live_object_limit_exceeded() {
+    use glium::context::{ObjectKind, ObjectLimit};
+
+    let display = support::build_display();
+
+    let count = display.get_live_object_counts().unwrap().buffers;
+    display.set_live_object_limit(ObjectKind::Buffer, ObjectLimit::Panic(count + 2));
+
+    let mut buffers = Vec::new();
+    for _ in 0 .. 3 {
+        buffers.push(glium::VertexBuffer::new(&display, &[(0.0f32, 0.0f32)]).unwrap());
+    }
+}
+
+#[test]
+fn deferred_drop_in_other_thread() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let texture = glium::DeferredDrop::new(&display, texture);
+
+    std::thread::spawn(move || {
+        drop(texture);
+    }).join().unwrap();
+
+    let mut target = display.draw();
+    target.clear_color(0.0, 0.0, 0.0, 0.0);
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn deferred_drop_into_inner() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let texture = glium::DeferredDrop::new(&display, texture);
+
+    let texture = texture.into_inner(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn deferred_drop_after_display() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let texture = glium::DeferredDrop::new(&display, texture);
+    drop(display);
+
+    std::thread::spawn(move || {
+        drop(texture);
+    }).join().unwrap();
+}
+
 #[test]
 fn multiple_displays() {
     let display1 = support::build_display();
@@ -141,6 +351,90 @@ fn debug_string() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn debug_scopes() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+
+    {
+        let _outer = display.debug_scope("outer");
+        let _inner = display.debug_scope("inner");
+        display.insert_debug_marker("marker").ok();
+        texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+    }
+
+    // very long names are truncated
+    let name: String = (0 .. 1000).map(|_| 'é').collect();
+    drop(display.debug_scope(&name));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn debug_scopes_tracing() {
+    use std::fs::File;
+    use std::io::Read;
+
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    display.set_tracing(2);
+
+    // this scope lasts until after the last frame
+    let loading = display.debug_scope("loading \"assets\"");
+
+    for _ in 0 .. 3 {
+        let mut frame = display.draw();
+        {
+            let _scope = frame.debug_scope("scene");
+            frame.draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+        }
+        frame.finish().unwrap();
+    }
+
+    drop(loading);
+
+    // making sure that the results of the queries are available
+    display.finish();
+
+    let path = std::env::temp_dir().join("glium-debug-scopes-tracing.json");
+    display.export_trace(&path).unwrap();
+
+    let mut trace = String::new();
+    File::open(&path).unwrap().read_to_string(&mut trace).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert!(trace.starts_with("{\"traceEvents\":["));
+
+    // only the last two frames and the current one are kept, and the scope that started
+    // during the first frame is discarded
+    let scenes = trace.matches("\"name\":\"scene\"").count();
+    assert!(scenes == 2 || scenes == 4);
+    assert!(!trace.contains("loading"));
+
+    display.set_tracing(0);
+    display.assert_no_error(None);
+}
+
+
+#[test]
+fn set_swap_interval() {
+    // tests that changing the swap interval doesn't trigger an OpenGL error
+    let display = support::build_display();
+    match display.set_swap_interval(glium::backend::SwapInterval::Immediate) {
+        Ok(interval) => assert_eq!(interval, glium::backend::SwapInterval::Immediate),
+        Err(glium::backend::SwapIntervalNotSupportedError) => (),
+    };
+
+    match display.set_swap_interval(glium::backend::SwapInterval::AdaptiveVsync) {
+        Ok(glium::backend::SwapInterval::AdaptiveVsync) => (),
+        Ok(glium::backend::SwapInterval::Vsync) => (),
+        Ok(interval) => panic!("{:?}", interval),
+        Err(glium::backend::SwapIntervalNotSupportedError) => (),
+    };
+
+    display.assert_no_error(None);
+}
 
 #[test]
 fn is_context_lost() {
@@ -149,3 +443,652 @@ fn is_context_lost() {
     display.is_context_lost();
     display.assert_no_error(None);
 }
+
+#[test]
+fn custom_backend() {
+    use std::env;
+
+    struct Backend(glium::glutin::HeadlessContext);
+
+    unsafe impl glium::backend::Backend for Backend {
+        fn swap_buffers(&self) -> Result<(), glium::SwapBuffersError> {
+            Ok(())
+        }
+
+        unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+            self.0.get_proc_address(symbol)
+        }
+
+        fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+            (1024, 768)
+        }
+
+        fn is_current(&self) -> bool {
+            self.0.is_current()
+        }
+
+        unsafe fn make_current(&self) {
+            self.0.make_current().unwrap();
+        }
+    }
+
+    if env::var("GLIUM_HEADLESS_TESTS").is_err() {
+        return;
+    }
+
+    let context = glium::glutin::HeadlessRendererBuilder::new(1024, 768).build().unwrap();
+    let display = unsafe { glium::Display::from_backend(Backend(context), true) }.unwrap();
+    assert!(display.get_window().is_none());
+
+    let mut target = display.draw();
+    assert_eq!(target.get_dimensions(), (1024, 768));
+    target.clear_color(0.0, 0.0, 0.0, 0.0);
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn map_after_context_loss() {
+    use std::env;
+
+    // simulates a context loss when the buffers are swapped
+    struct Backend(glium::glutin::HeadlessContext);
+
+    unsafe impl glium::backend::Backend for Backend {
+        fn swap_buffers(&self) -> Result<(), glium::SwapBuffersError> {
+            Err(glium::SwapBuffersError::ContextLost)
+        }
+
+        unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+            self.0.get_proc_address(symbol)
+        }
+
+        fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+            (1024, 768)
+        }
+
+        fn is_current(&self) -> bool {
+            self.0.is_current()
+        }
+
+        unsafe fn make_current(&self) {
+            self.0.make_current().unwrap();
+        }
+    }
+
+    if env::var("GLIUM_HEADLESS_TESTS").is_err() {
+        return;
+    }
+
+    let context = glium::glutin::HeadlessRendererBuilder::new(1024, 768).build().unwrap();
+    let display = unsafe { glium::Display::from_backend(Backend(context), true) }.unwrap();
+
+    let mut buf = glium::buffer::Buffer::new(&display, &[1, 2, 3],
+                                             glium::buffer::BufferType::ArrayBuffer,
+                                             glium::buffer::BufferMode::Default).unwrap();
+
+    match display.draw().finish() {
+        Err(glium::SwapBuffersError::ContextLost) => (),
+        r => panic!("{:?}", r)
+    };
+
+    match buf.try_map() {
+        Err(glium::buffer::MapError::ContextLost) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!("the buffer has been mapped after a context loss")
+    };
+}
+
+#[test]
+fn frame_swap_on_drop() {
+    let display = support::build_display();
+
+    {
+        let mut frame = display.draw();
+        frame.clear_color(0.0, 0.0, 0.0, 0.0);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn frame_discard_on_drop() {
+    let display = support::build_display();
+
+    {
+        let mut frame = display.draw();
+        frame.set_finish_behavior(glium::FinishBehavior::DiscardOnDrop);
+        frame.clear_color(0.0, 0.0, 0.0, 0.0);
+    }
+
+    display.draw().finish().unwrap();
+    display.assert_no_error(None);
+}
+
+#[test]
+fn frame_panic_during_frame() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    let display = support::build_display();
+
+    // dropping a frame while unwinding must neither abort nor leave the state cache borrowed
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut frame = display.draw();
+        frame.clear_color(0.0, 0.0, 0.0, 0.0);
+        panic!();
+    }));
+
+    assert!(result.is_err());
+
+    // the display must still be usable afterwards
+    let mut frame = display.draw();
+    frame.clear_color(1.0, 0.0, 0.0, 1.0);
+    let image = glium::CaptureSurface::capture(&frame).unwrap();
+    frame.finish().unwrap();
+
+    assert_eq!(&image.data[0 .. 4], &[255, 0, 0, 255]);
+    let last = image.data.len() - 4;
+    assert_eq!(&image.data[last ..], &[255, 0, 0, 255]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn exec_in_context_invalidates_state() {
+    use std::mem;
+
+    let display = support::build_display();
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              16, 16).unwrap();
+    texture.as_surface().clear_color(1.0, 0.0, 0.0, 1.0);
+
+    // enabling scissoring behind glium's back
+    unsafe {
+        display.exec_in_context(|data| {
+            let enable = data.get_proc_address("glEnable");
+            let scissor = data.get_proc_address("glScissor");
+            assert!(!enable.is_null());
+            assert!(!scissor.is_null());
+
+            let enable: extern "system" fn(u32) = mem::transmute(enable);
+            let scissor: extern "system" fn(i32, i32, i32, i32) = mem::transmute(scissor);
+            enable(0x0C11);     // GL_SCISSOR_TEST
+            scissor(0, 0, 1, 1);
+        });
+    }
+
+    texture.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+    assert_eq!(data[15][15], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn capture_async_in_order() {
+    use std::sync::mpsc;
+
+    let display = support::build_display();
+    let (tx, rx) = mpsc::channel();
+
+    for &color in &[(1.0, 0.0, 0.0, 1.0), (0.0, 1.0, 0.0, 1.0)] {
+        let mut target = display.draw();
+        target.clear_color(color.0, color.1, color.2, color.3);
+
+        let tx = tx.clone();
+        match display.capture_async(move |image| { tx.send(image).unwrap(); }) {
+            Ok(()) => (),
+            Err(glium::CaptureError::NotSupported) => return,
+            Err(e) => panic!("{}", e),
+        };
+
+        target.finish().unwrap();
+    }
+
+    // waiting for all the operations to finish, then delivering the remaining captures
+    display.finish();
+    display.poll_async_operations();
+
+    let first = rx.try_recv().unwrap().unwrap();
+    let second = rx.try_recv().unwrap().unwrap();
+    assert!(rx.try_recv().is_err());
+
+    assert_eq!(&first.data[0 .. 3], &[255, 0, 0]);
+    assert_eq!(&second.data[0 .. 3], &[0, 255, 0]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn driver_workarounds_override() {
+    let display = support::build_display();
+
+    let mut workarounds = display.get_driver_workarounds();
+    workarounds.no_buffer_invalidation = true;
+    workarounds.no_srgb_blits = true;
+    display.set_driver_workarounds(workarounds);
+    assert_eq!(display.get_driver_workarounds(), workarounds);
+
+    // invalidating a buffer must still work when the workaround is enabled
+    let buffer = glium::VertexBuffer::new(&display, &[1u32, 2, 3, 4]).unwrap();
+    buffer.invalidate();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn framebuffer_resize_callback() {
+    use std::cell::Cell;
+    use std::env;
+    use std::rc::Rc;
+
+    struct Backend(glium::glutin::HeadlessContext, Rc<Cell<(u32, u32)>>);
+
+    unsafe impl glium::backend::Backend for Backend {
+        fn swap_buffers(&self) -> Result<(), glium::SwapBuffersError> {
+            Ok(())
+        }
+
+        unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+            self.0.get_proc_address(symbol)
+        }
+
+        fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+            self.1.get()
+        }
+
+        fn is_current(&self) -> bool {
+            self.0.is_current()
+        }
+
+        unsafe fn make_current(&self) {
+            self.0.make_current().unwrap();
+        }
+    }
+
+    if env::var("GLIUM_HEADLESS_TESTS").is_err() {
+        return;
+    }
+
+    let dimensions = Rc::new(Cell::new((1024, 768)));
+    let context = glium::glutin::HeadlessRendererBuilder::new(1024, 768).build().unwrap();
+    let display = unsafe {
+        glium::Display::from_backend(Backend(context, dimensions.clone()), true)
+    }.unwrap();
+
+    let resized = Rc::new(Cell::new(None));
+    {
+        let resized = resized.clone();
+        display.on_framebuffer_resize(move |dims| resized.set(Some(dims)));
+    }
+
+    let target = display.draw();
+    assert_eq!(target.get_dimensions(), (1024, 768));
+    assert_eq!(resized.get(), None);
+    target.finish().unwrap();
+
+    // the window is resized between two frames
+    dimensions.set((512, 384));
+
+    let mut target = display.draw();
+    assert_eq!(resized.get(), Some((512, 384)));
+    assert_eq!(target.get_dimensions(), (512, 384));
+    target.clear_color(0.0, 0.0, 0.0, 0.0);
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn hidpi_window_coordinates() {
+    use std::env;
+
+    // backend of a window of 400x300 logical units with a HiDPI factor of 2
+    struct Backend(glium::glutin::HeadlessContext);
+
+    unsafe impl glium::backend::Backend for Backend {
+        fn swap_buffers(&self) -> Result<(), glium::SwapBuffersError> {
+            Ok(())
+        }
+
+        unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+            self.0.get_proc_address(symbol)
+        }
+
+        fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+            (800, 600)
+        }
+
+        fn get_window_dimensions(&self) -> (u32, u32) {
+            (400, 300)
+        }
+
+        fn is_current(&self) -> bool {
+            self.0.is_current()
+        }
+
+        unsafe fn make_current(&self) {
+            self.0.make_current().unwrap();
+        }
+    }
+
+    if env::var("GLIUM_HEADLESS_TESTS").is_err() {
+        return;
+    }
+
+    let context = glium::glutin::HeadlessRendererBuilder::new(800, 600).build().unwrap();
+    let display = unsafe { glium::Display::from_backend(Backend(context), true) }.unwrap();
+
+    assert_eq!(display.window_to_framebuffer_coords((0.0, 0.0)), (0, 599));
+    assert_eq!(display.window_to_framebuffer_coords((399.5, 0.0)), (799, 599));
+    assert_eq!(display.window_to_framebuffer_coords((0.0, 299.5)), (0, 0));
+    assert_eq!(display.window_to_framebuffer_coords((399.5, 299.5)), (799, 0));
+    assert_eq!(display.window_to_framebuffer_coords((200.0, 150.0)), (400, 299));
+
+    let target = display.draw();
+    assert_eq!(target.pick_rect_at((399.5, 299.5), (4, 4)),
+               glium::Rect { left: 797, bottom: 0, width: 3, height: 2 });
+    assert_eq!(target.pick_rect_at((200.0, 150.0), (1, 1)),
+               glium::Rect { left: 400, bottom: 299, width: 1, height: 1 });
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn frame_statistics() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+
+    // disabled by default
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    assert_eq!(display.get_frame_statistics(), Default::default());
+
+    display.set_frame_statistics_enabled(true);
+
+    let mut surface = texture.as_surface();
+    surface.clear_color(0.0, 0.0, 0.0, 0.0);
+    surface.draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+    surface.draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    let buffer = glium::VertexBuffer::new(&display, &[1u32, 2, 3, 4]).unwrap();
+    buffer.write(&[5, 6, 7, 8]);
+
+    let stats = display.get_frame_statistics();
+    assert_eq!(stats.draw_calls, 2);
+    assert_eq!(stats.clears, 1);
+    assert_eq!(stats.blits, 0);
+    assert_eq!(stats.buffer_uploads, 1);
+    assert_eq!(stats.buffer_bytes_uploaded, 16);
+
+    // the statistics are reset when the frame is finished
+    let target = display.draw();
+    let stats = target.finish_with_statistics().unwrap();
+    assert_eq!(stats.draw_calls, 2);
+    assert_eq!(display.get_frame_statistics(), Default::default());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn sync_statistics() {
+    let display = support::build_display();
+    let texture = support::build_renderable_texture(&display);
+
+    display.set_frame_statistics_enabled(true);
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    let _: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    display.finish();
+
+    let stats = display.get_frame_statistics().sync;
+    assert_eq!(stats.reads.count, 1);
+    assert!(stats.reads.max <= stats.reads.total);
+    assert_eq!(stats.finishes.count, 1);
+    assert_eq!(stats.swaps.count, 0);
+
+    let target = display.draw();
+    assert_eq!(target.get_sync_statistics(), stats);
+    let stats = target.finish_with_statistics().unwrap();
+    assert_eq!(stats.sync.swaps.count, 1);
+
+    // nothing is measured when the statistics are disabled
+    display.set_frame_statistics_enabled(false);
+    display.finish();
+    assert_eq!(display.get_frame_statistics().sync, Default::default());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn max_frames_in_flight() {
+    let display = support::build_display();
+    assert_eq!(display.get_max_frames_in_flight(), 0);
+
+    match display.set_max_frames_in_flight(1) {
+        Ok(()) => (),
+        Err(_) => return
+    };
+    assert_eq!(display.get_max_frames_in_flight(), 1);
+
+    for _ in 0 .. 3 {
+        let mut target = display.draw();
+        target.clear_color(0.0, 0.0, 0.0, 0.0);
+        target.finish().unwrap();
+    }
+
+    display.set_max_frames_in_flight(0).unwrap();
+    assert_eq!(display.get_max_frames_in_flight(), 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn frame_flush() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let mut target = display.draw();
+    target.clear_color(0.0, 0.0, 0.0, 0.0);
+    target.flush();
+    target.draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+    target.flush();
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn direct_state_access_edit_binds() {
+    let display = support::build_display();
+
+    let mut buffer = glium::VertexBuffer::dynamic(&display, &[1u32, 2, 3, 4]).unwrap();
+    display.set_frame_statistics_enabled(true);
+
+    buffer.write(&[5, 6, 7, 8]);
+    buffer.map()[0] = 9;
+
+    let stats = display.get_frame_statistics();
+    if display.get_context().get_capabilities().direct_state_access {
+        assert_eq!(stats.edit_binds, 0);
+    } else {
+        assert!(stats.edit_binds >= 1);
+    }
+
+    assert_eq!(buffer.read().unwrap(), vec![9, 6, 7, 8]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn objects_from_another_display() {
+    let display1 = support::build_display();
+    let display2 = support::build_display();
+
+    let (vb1, ib1, program1) = support::build_fullscreen_red_pipeline(&display1);
+    let (vb2, ib2, program2) = support::build_fullscreen_red_pipeline(&display2);
+
+    let texture = support::build_renderable_texture(&display1);
+
+    match texture.as_surface().draw(&vb1, &ib1, &program2, &glium::uniforms::EmptyUniforms,
+                                    &Default::default())
+    {
+        Err(glium::DrawError::WrongContext { object_kind: "program" }) => (),
+        e => panic!("{:?}", e)
+    }
+
+    match texture.as_surface().draw(&vb2, &ib1, &program1, &glium::uniforms::EmptyUniforms,
+                                    &Default::default())
+    {
+        Err(glium::DrawError::WrongContext { object_kind: "vertex buffer" }) => (),
+        e => panic!("{:?}", e)
+    }
+
+    match texture.as_surface().draw(&vb1, &ib2, &program1, &glium::uniforms::EmptyUniforms,
+                                    &Default::default())
+    {
+        Err(glium::DrawError::WrongContext { object_kind: "index buffer" }) => (),
+        e => panic!("{:?}", e)
+    }
+
+    display1.assert_no_error(None);
+    display2.assert_no_error(None);
+}
+
+#[test]
+fn texture_uniform_from_another_display() {
+    let display1 = support::build_display();
+    let display2 = support::build_display();
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display1);
+    let program = glium::Program::from_source(&display1,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D tex;
+
+            void main() {
+                gl_FragColor = texture2D(tex, vec2(0.5, 0.5));
+            }
+        ",
+        None).unwrap();
+
+    let sampled = support::build_unicolor_texture2d(&display2, 1.0, 0.0, 0.0);
+
+    let target = support::build_renderable_texture(&display1);
+    match target.as_surface().draw(&vb, &ib, &program, &uniform!{ tex: &sampled },
+                                   &Default::default())
+    {
+        Err(glium::DrawError::WrongContext { object_kind: "texture" }) => (),
+        e => panic!("{:?}", e)
+    }
+
+    display1.assert_no_error(None);
+    display2.assert_no_error(None);
+}
+
+#[test]
+fn framebuffer_attachment_from_another_display() {
+    let display1 = support::build_display();
+    let display2 = support::build_display();
+
+    let texture = support::build_renderable_texture(&display2);
+
+    match glium::framebuffer::SimpleFrameBuffer::new(&display1, &texture) {
+        Err(glium::framebuffer::ValidationError::WrongContext) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!()
+    }
+
+    display1.assert_no_error(None);
+    display2.assert_no_error(None);
+}
+
+#[test]
+fn dump_state() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.5, 0.0, 1.0, 1.0);
+
+    let snapshot = display.dump_state(false);
+    assert!(snapshot.entries.iter().all(|e| e.actual.is_none()));
+    assert!(snapshot.to_string().contains("GL_CURRENT_PROGRAM"));
+
+    // some initial values of the context, like `GL_DITHER`, aren't the ones that glium assumes
+    // until the state is reset
+    unsafe { display.exec_in_context(|_| ()) };
+
+    let snapshot = display.dump_state(true);
+    assert!(snapshot.entries.iter().any(|e| e.actual.is_some()));
+    assert!(snapshot.get_divergences().is_empty(), "{}", snapshot);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn dump_state_divergence() {
+    use std::mem;
+
+    let display = support::build_display();
+    unsafe { display.exec_in_context(|_| ()) };
+
+    // enabling blending behind glium's back
+    let (enable, disable) = unsafe {
+        let enable = display.get_proc_address("glEnable");
+        let disable = display.get_proc_address("glDisable");
+        assert!(!enable.is_null());
+        assert!(!disable.is_null());
+
+        let enable: extern "system" fn(u32) = mem::transmute(enable);
+        let disable: extern "system" fn(u32) = mem::transmute(disable);
+        (enable, disable)
+    };
+
+    enable(0x0BE2);     // GL_BLEND
+
+    let snapshot = display.dump_state(true);
+    let divergences = snapshot.get_divergences();
+    assert_eq!(divergences.len(), 1);
+    assert_eq!(divergences[0].name, "GL_BLEND");
+    assert_eq!(divergences[0].actual, Some(glium::backend::StateValue::Bool(true)));
+    assert!(snapshot.to_string().contains("DIVERGENT"));
+
+    disable(0x0BE2);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn frame_read_default_color_buffer() {
+    // reads from the back buffer, or from the front buffer of single-buffered contexts
+    let display = support::build_display();
+
+    let mut frame = display.draw();
+    frame.clear_color(0.0, 1.0, 0.0, 1.0);
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 2 };
+    let mut data = vec![0u8; 2 * 2 * 4];
+    frame.read_into(&rect, &mut data, glium::texture::ClientFormat::U8U8U8U8).unwrap();
+    assert_eq!(&data[0 .. 4], &[0, 255, 0, 255]);
+
+    // the frame is flushed instead of swapped if there is no back buffer
+    frame.finish().unwrap();
+
+    display.assert_no_error(None);
+}
@@ -1,7 +1,7 @@
 #[macro_use]
 extern crate glium;
 
-use glium::Surface;
+use glium::{CapabilitiesSource, Surface};
 use glium::index::PrimitiveType;
 
 mod support;
@@ -771,6 +771,212 @@ blending_test!(one_plus_one, glium::BlendingFunction::Addition {
                },
                (0.0, 1.0, 1.0, 0.0), (1.0, 0.0, 0.0, 1.0), (255, 255, 255, 255));
 
+#[test]
+fn dual_source_blending_subpixel_text() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    // the first output is the color of the text, and the second one is the coverage of each
+    // color channel, as with subpixel text rendering
+    let program = match program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                out vec4 color;
+                out vec4 coverage;
+
+                void main() {
+                    color = vec4(0.0, 0.0, 0.0, 1.0);
+                    coverage = vec4(1.0, 0.0, 1.0, 1.0);
+                }
+            ",
+            dual_source_output: "coverage",
+        },
+    ) {
+        Ok(p) => p,
+        Err(glium::program::ProgramChooserCreationError::NoVersion) => return,
+        Err(glium::program::ProgramChooserCreationError::ProgramCreationError(
+            glium::ProgramCreationError::DualSourceBlendingNotSupported)) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let params = glium::DrawParameters {
+        blend: glium::Blend {
+            color: glium::BlendingFunction::Addition {
+                source: glium::LinearBlendingFactor::Source1Color,
+                destination: glium::LinearBlendingFactor::OneMinusSource1Color,
+            },
+            alpha: glium::BlendingFunction::AlwaysReplace,
+            constant_value: (0.0, 0.0, 0.0, 0.0),
+        },
+        .. Default::default()
+    };
+    assert!(params.blend.uses_dual_source());
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(1.0, 1.0, 1.0, 1.0);
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &params).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn dual_source_blending_too_many_attachments() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let max = display.get_context().get_capabilities().max_dual_source_draw_buffers;
+    if max == 0 || max >= 2 {
+        return;
+    }
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+
+            out vec4 color1;
+            out vec4 color2;
+
+            void main() {
+                color1 = vec4(1.0, 1.0, 1.0, 1.0);
+                color2 = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None)
+    {
+        Err(glium::CompilationError(_)) => return,
+        Ok(p) => p,
+        e => e.unwrap()
+    };
+
+    let color1 = glium::Texture2d::empty(&display, 16, 16).unwrap();
+    let color2 = glium::Texture2d::empty(&display, 16, 16).unwrap();
+    let mut framebuffer = glium::framebuffer::MultiOutputFrameBuffer::new(&display,
+                                             &[("color1", &color1), ("color2", &color2)]).unwrap();
+
+    let params = glium::DrawParameters {
+        blend: glium::Blend {
+            color: glium::BlendingFunction::Addition {
+                source: glium::LinearBlendingFactor::Source1Color,
+                destination: glium::LinearBlendingFactor::OneMinusSource1Color,
+            },
+            alpha: glium::BlendingFunction::AlwaysReplace,
+            constant_value: (0.0, 0.0, 0.0, 0.0),
+        },
+        .. Default::default()
+    };
+
+    match framebuffer.draw(&vb, &ib, &program, &uniform!{}, &params) {
+        Err(glium::DrawError::TooManyDualSourceAttachments { attachments: 2, .. }) => (),
+        e => panic!("{:?}", e)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn blending_follows_draw_order() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform vec4 color;
+
+                void main() {
+                    gl_FragColor = color;
+                }
+            "
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                uniform lowp vec4 color;
+
+                void main() {
+                    gl_FragColor = color;
+                }
+            "
+        },
+    ).unwrap();
+
+    let params = glium::DrawParameters {
+        blend: glium::Blend::alpha_blending(),
+        .. Default::default()
+    };
+
+    let red = (1.0, 0.0, 0.0, 0.5f32);
+    let blue = (0.0, 0.0, 1.0, 0.5f32);
+
+    // the second color covers half of the first one
+    for &(first, second, expected) in &[(red, blue, (64, 0, 128)), (blue, red, (128, 0, 64))] {
+        let texture = support::build_renderable_texture(&display);
+        let mut surface = texture.as_surface();
+        surface.clear_color(0.0, 0.0, 0.0, 1.0);
+        surface.draw(&vb, &ib, &program, &uniform!{ color: first }, &params).unwrap();
+        surface.draw(&vb, &ib, &program, &uniform!{ color: second }, &params).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        for row in data.iter() {
+            for pixel in row.iter() {
+                assert!((pixel.0 as i32 - expected.0).abs() <= 2, "{:?}", pixel);
+                assert!((pixel.1 as i32 - expected.1).abs() <= 2, "{:?}", pixel);
+                assert!((pixel.2 as i32 - expected.2).abs() <= 2, "{:?}", pixel);
+            }
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
 
 #[test]
 fn provoking_vertex_last() {
@@ -1279,3 +1485,638 @@ fn primitive_bounding_box() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn clip_planes() {
+    let display = support::build_display();
+
+    let vertex_buffer = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display, &[
+            Vertex { position: [-1.0, 1.0] },
+            Vertex { position: [1.0, 1.0] },
+            Vertex { position: [-1.0, -1.0] },
+            Vertex { position: [1.0, -1.0] },
+        ]).unwrap()
+    };
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 130
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+                gl_ClipDistance[0] = position.x;
+            }
+        ",
+        "
+            #version 130
+
+            out vec4 color;
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ", None)
+    {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    assert!(program.uses_clip_distance());
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    let res = texture.as_surface().draw(&vertex_buffer,
+        &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip), &program,
+        &glium::uniforms::EmptyUniforms,
+        &glium::DrawParameters {
+            clip_planes_bitmask: 0b1,
+            .. Default::default()
+        });
+
+    match res {
+        Ok(_) => (),
+        Err(glium::DrawError::ClipPlaneIndexOutOfBounds) => {
+            display.assert_no_error(None);
+            return;
+        },
+        e => e.unwrap(),
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    let width = data[0].len();
+
+    for row in data.iter() {
+        assert_eq!(row[0], (0, 0, 0, 0));
+        assert_eq!(row[width - 1], (255, 0, 0, 255));
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn provoking_vertex_triangle_strip() {
+    let display = support::build_display();
+
+    if !display.is_provoking_vertex_supported() {
+        return;
+    }
+
+    let vertex_buffer = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+            value: f32,
+        }
+
+        implement_vertex!(Vertex, position, value);
+
+        glium::VertexBuffer::new(&display, &[
+            Vertex { position: [-1.0, 1.0], value: 1.0 },
+            Vertex { position: [-1.0, -1.0], value: 0.0 },
+            Vertex { position: [1.0, 1.0], value: 0.0 },
+            Vertex { position: [1.0, -1.0], value: 1.0 },
+        ]).unwrap()
+    };
+
+    let program = glium::Program::from_source(&display, "
+            #version 140
+
+            in vec2 position;
+            in float value;
+            flat out float v_value;
+
+            void main() {
+                v_value = value;
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 140
+
+            flat in float v_value;
+            out vec4 color;
+
+            void main() {
+                color = vec4(v_value, 0.0, 0.0, 1.0);
+            }
+        ", None);
+    let program = match program {
+        Err(_) => return,
+        Ok(p) => p
+    };
+
+    for &(convention, top_left, bottom_right) in &[
+        (glium::draw_parameters::ProvokingVertex::FirstVertex, 255, 0),
+        (glium::draw_parameters::ProvokingVertex::LastVertex, 0, 255),
+    ] {
+        let texture = support::build_renderable_texture(&display);
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw(&vertex_buffer,
+            &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip), &program,
+            &glium::uniforms::EmptyUniforms,
+            &glium::DrawParameters {
+                provoking_vertex: convention,
+                .. Default::default()
+            }).unwrap();
+
+        assert_eq!(display.get_provoking_vertex(), convention);
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        let height = data.len();
+        let width = data[0].len();
+
+        assert_eq!(data[height - 1][0], (top_left, 0, 0, 255));
+        assert_eq!(data[0][width - 1], (bottom_right, 0, 0, 255));
+    }
+
+    display.assert_no_error(None);
+}
+
+// the presets are destructured without `..`, so that adding a field to `DrawParameters` fails to
+// compile until a value has been chosen for each preset
+
+#[test]
+fn preset_opaque_3d() {
+    let glium::DrawParameters {
+        depth, stencil: _, blend, logic_op, color_mask, line_width, point_size,
+        backface_culling, polygon_mode, multisampling, dithering, viewport, scissor,
+        draw_primitives, samples_passed_query, time_elapsed_query, primitives_generated_query,
+        transform_feedback_primitives_written_query, condition, transform_feedback, smooth,
+        derivative_hint, provoking_vertex, primitive_bounding_box: _, clip_planes_bitmask,
+    } = glium::DrawParameters::opaque_3d();
+
+    assert_eq!(depth.test, glium::DepthTest::IfLess);
+    assert!(depth.write);
+    assert_eq!(blend, glium::Blend::default());
+    assert_eq!(color_mask, (true, true, true, true));
+    assert!(line_width.is_none() && point_size.is_none());
+    assert_eq!(backface_culling, glium::BackfaceCullingMode::CullClockwise);
+    assert_eq!(polygon_mode, glium::PolygonMode::Fill);
+    assert!(multisampling && dithering && draw_primitives);
+    assert!(viewport.is_none() && scissor.is_none());
+    assert!(samples_passed_query.is_none() && time_elapsed_query.is_none());
+    assert!(primitives_generated_query.is_none());
+    assert!(transform_feedback_primitives_written_query.is_none());
+    assert!(condition.is_none() && transform_feedback.is_none() && smooth.is_none());
+    assert_eq!(provoking_vertex, glium::draw_parameters::ProvokingVertex::LastVertex);
+    assert_eq!(derivative_hint, glium::DerivativeHint::DontCare);
+    assert!(logic_op.is_none());
+    assert_eq!(clip_planes_bitmask, 0);
+}
+
+#[test]
+fn preset_alpha_blended_ui() {
+    let glium::DrawParameters {
+        depth, stencil: _, blend, logic_op, color_mask, line_width, point_size,
+        backface_culling, polygon_mode, multisampling, dithering, viewport, scissor,
+        draw_primitives, samples_passed_query, time_elapsed_query, primitives_generated_query,
+        transform_feedback_primitives_written_query, condition, transform_feedback, smooth,
+        derivative_hint, provoking_vertex, primitive_bounding_box: _, clip_planes_bitmask,
+    } = glium::DrawParameters::alpha_blended_ui();
+
+    assert_eq!(depth.test, glium::DepthTest::Overwrite);
+    assert!(!depth.write);
+    assert_eq!(blend, glium::Blend::alpha_blending());
+    assert_eq!(color_mask, (true, true, true, true));
+    assert!(line_width.is_none() && point_size.is_none());
+    assert_eq!(backface_culling, glium::BackfaceCullingMode::CullingDisabled);
+    assert_eq!(polygon_mode, glium::PolygonMode::Fill);
+    assert!(multisampling && dithering && draw_primitives);
+    assert!(viewport.is_none() && scissor.is_none());
+    assert!(samples_passed_query.is_none() && time_elapsed_query.is_none());
+    assert!(primitives_generated_query.is_none());
+    assert!(transform_feedback_primitives_written_query.is_none());
+    assert!(condition.is_none() && transform_feedback.is_none() && smooth.is_none());
+    assert_eq!(provoking_vertex, glium::draw_parameters::ProvokingVertex::LastVertex);
+    assert_eq!(derivative_hint, glium::DerivativeHint::DontCare);
+    assert!(logic_op.is_none());
+    assert_eq!(clip_planes_bitmask, 0);
+}
+
+#[test]
+fn preset_shadow_caster() {
+    let glium::DrawParameters {
+        depth, stencil: _, blend, logic_op, color_mask, line_width, point_size,
+        backface_culling, polygon_mode, multisampling, dithering, viewport, scissor,
+        draw_primitives, samples_passed_query, time_elapsed_query, primitives_generated_query,
+        transform_feedback_primitives_written_query, condition, transform_feedback, smooth,
+        derivative_hint, provoking_vertex, primitive_bounding_box: _, clip_planes_bitmask,
+    } = glium::DrawParameters::shadow_caster();
+
+    assert_eq!(depth.test, glium::DepthTest::IfLess);
+    assert!(depth.write);
+    assert_eq!(blend, glium::Blend::default());
+    assert_eq!(color_mask, (false, false, false, false));
+    assert!(line_width.is_none() && point_size.is_none());
+    assert_eq!(backface_culling, glium::BackfaceCullingMode::CullCounterClockwise);
+    assert_eq!(polygon_mode, glium::PolygonMode::Fill);
+    assert!(multisampling && dithering && draw_primitives);
+    assert!(viewport.is_none() && scissor.is_none());
+    assert!(samples_passed_query.is_none() && time_elapsed_query.is_none());
+    assert!(primitives_generated_query.is_none());
+    assert!(transform_feedback_primitives_written_query.is_none());
+    assert!(condition.is_none() && transform_feedback.is_none() && smooth.is_none());
+    assert_eq!(provoking_vertex, glium::draw_parameters::ProvokingVertex::LastVertex);
+    assert_eq!(derivative_hint, glium::DerivativeHint::DontCare);
+    assert!(logic_op.is_none());
+    assert_eq!(clip_planes_bitmask, 0);
+}
+
+#[test]
+fn preset_depth_prepass() {
+    let glium::DrawParameters {
+        depth, stencil: _, blend, logic_op, color_mask, line_width, point_size,
+        backface_culling, polygon_mode, multisampling, dithering, viewport, scissor,
+        draw_primitives, samples_passed_query, time_elapsed_query, primitives_generated_query,
+        transform_feedback_primitives_written_query, condition, transform_feedback, smooth,
+        derivative_hint, provoking_vertex, primitive_bounding_box: _, clip_planes_bitmask,
+    } = glium::DrawParameters::depth_prepass();
+
+    assert_eq!(depth.test, glium::DepthTest::IfLess);
+    assert!(depth.write);
+    assert_eq!(blend, glium::Blend::default());
+    assert_eq!(color_mask, (false, false, false, false));
+    assert!(line_width.is_none() && point_size.is_none());
+    assert_eq!(backface_culling, glium::BackfaceCullingMode::CullClockwise);
+    assert_eq!(polygon_mode, glium::PolygonMode::Fill);
+    assert!(multisampling && dithering && draw_primitives);
+    assert!(viewport.is_none() && scissor.is_none());
+    assert!(samples_passed_query.is_none() && time_elapsed_query.is_none());
+    assert!(primitives_generated_query.is_none());
+    assert!(transform_feedback_primitives_written_query.is_none());
+    assert!(condition.is_none() && transform_feedback.is_none() && smooth.is_none());
+    assert_eq!(provoking_vertex, glium::draw_parameters::ProvokingVertex::LastVertex);
+    assert_eq!(derivative_hint, glium::DerivativeHint::DontCare);
+    assert!(logic_op.is_none());
+    assert_eq!(clip_planes_bitmask, 0);
+}
+
+#[test]
+fn preset_depth_prepass_main() {
+    let glium::DrawParameters {
+        depth, stencil: _, blend, logic_op, color_mask, line_width, point_size,
+        backface_culling, polygon_mode, multisampling, dithering, viewport, scissor,
+        draw_primitives, samples_passed_query, time_elapsed_query, primitives_generated_query,
+        transform_feedback_primitives_written_query, condition, transform_feedback, smooth,
+        derivative_hint, provoking_vertex, primitive_bounding_box: _, clip_planes_bitmask,
+    } = glium::DrawParameters::depth_prepass_main();
+
+    assert_eq!(depth.test, glium::DepthTest::IfEqual);
+    assert!(!depth.write);
+    assert_eq!(blend, glium::Blend::default());
+    assert_eq!(color_mask, (true, true, true, true));
+    assert!(line_width.is_none() && point_size.is_none());
+    assert_eq!(backface_culling, glium::BackfaceCullingMode::CullClockwise);
+    assert_eq!(polygon_mode, glium::PolygonMode::Fill);
+    assert!(multisampling && dithering && draw_primitives);
+    assert!(viewport.is_none() && scissor.is_none());
+    assert!(samples_passed_query.is_none() && time_elapsed_query.is_none());
+    assert!(primitives_generated_query.is_none());
+    assert!(transform_feedback_primitives_written_query.is_none());
+    assert!(condition.is_none() && transform_feedback.is_none() && smooth.is_none());
+    assert_eq!(provoking_vertex, glium::draw_parameters::ProvokingVertex::LastVertex);
+    assert_eq!(derivative_hint, glium::DerivativeHint::DontCare);
+    assert!(logic_op.is_none());
+    assert_eq!(clip_planes_bitmask, 0);
+}
+
+/// Builds a program that draws the rectangle of `build_rectangle_vb_ib` at the depth given by
+/// the `depth` uniform and with the color given by the `color` uniform.
+fn build_depth_color_program(display: &glium::Display) -> glium::Program {
+    program!(display,
+        140 => {
+            vertex: "
+                #version 140
+
+                uniform float depth;
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, depth, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                uniform vec4 color;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = color;
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                uniform float depth;
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, depth, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform vec4 color;
+
+                void main() {
+                    gl_FragColor = color;
+                }
+            "
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                uniform lowp float depth;
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, depth, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                uniform lowp vec4 color;
+
+                void main() {
+                    gl_FragColor = color;
+                }
+            "
+        },
+    ).unwrap()
+}
+
+#[test]
+fn depth_prepass_shades_closest_fragment() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+    let program = build_depth_color_program(&display);
+
+    let color = support::build_renderable_texture(&display);
+    let depth = match glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                    glium::texture::DepthFormat::I24, 1024, 1024)
+    {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                        &color, &depth).unwrap();
+    framebuffer.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+
+    // the rectangle is clockwise
+    let prepass = glium::DrawParameters {
+        backface_culling: glium::BackfaceCullingMode::CullingDisabled,
+        .. glium::DrawParameters::depth_prepass()
+    };
+    let main = glium::DrawParameters {
+        backface_culling: glium::BackfaceCullingMode::CullingDisabled,
+        .. glium::DrawParameters::depth_prepass_main()
+    };
+
+    // the near rectangle is drawn first
+    for &z in &[-0.5f32, 0.5] {
+        framebuffer.draw(&vb, &ib, &program,
+                         &uniform!{ depth: z, color: [1.0f32, 1.0, 1.0, 1.0] },
+                         &prepass).unwrap();
+    }
+
+    // the first pass must not write any color
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = color.read();
+    assert_eq!(data[0][0], (0, 0, 0, 255));
+    assert_eq!(data[512][512], (0, 0, 0, 255));
+
+    framebuffer.draw(&vb, &ib, &program,
+                     &uniform!{ depth: -0.5f32, color: [0.0f32, 1.0, 0.0, 1.0] },
+                     &main).unwrap();
+    framebuffer.draw(&vb, &ib, &program,
+                     &uniform!{ depth: 0.5f32, color: [1.0f32, 0.0, 0.0, 1.0] },
+                     &main).unwrap();
+
+    // only the near rectangle passes the `IfEqual` test
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = color.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+    assert_eq!(data[512][512], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn depth_prepass_restores_color_mask() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+    let program = build_depth_color_program(&display);
+
+    let color = support::build_renderable_texture(&display);
+    let depth = match glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                    glium::texture::DepthFormat::I24, 1024, 1024)
+    {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                        &color, &depth).unwrap();
+    framebuffer.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+
+    let prepass = glium::DrawParameters {
+        backface_culling: glium::BackfaceCullingMode::CullingDisabled,
+        .. glium::DrawParameters::depth_prepass()
+    };
+
+    framebuffer.draw(&vb, &ib, &program,
+                     &uniform!{ depth: 0.0f32, color: [1.0f32, 1.0, 1.0, 1.0] },
+                     &prepass).unwrap();
+
+    // a draw command with the default parameters writes colors and ignores the depth buffer
+    framebuffer.draw(&vb, &ib, &program,
+                     &uniform!{ depth: 0.5f32, color: [1.0f32, 0.0, 0.0, 1.0] },
+                     &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = color.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data[512][512], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn depth_prepass_requires_depth_buffer() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+    let program = build_depth_color_program(&display);
+
+    let texture = support::build_renderable_texture(&display);
+    let uniforms = uniform!{ depth: 0.0f32, color: [1.0f32, 1.0, 1.0, 1.0] };
+
+    match texture.as_surface().draw(&vb, &ib, &program, &uniforms,
+                                    &glium::DrawParameters::depth_prepass())
+    {
+        Err(glium::DrawError::NoDepthBuffer) => (),
+        e => panic!("{:?}", e)
+    }
+
+    match texture.as_surface().draw(&vb, &ib, &program, &uniforms,
+                                    &glium::DrawParameters::depth_prepass_main())
+    {
+        Err(glium::DrawError::NoDepthBuffer) => (),
+        e => panic!("{:?}", e)
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn builder() {
+    let params = glium::DrawParameters::builder()
+                        .depth_test(glium::DepthTest::IfLess)
+                        .depth_write(true)
+                        .backface_culling(glium::BackfaceCullingMode::CullClockwise)
+                        .build();
+
+    let preset = glium::DrawParameters::opaque_3d();
+    assert_eq!(params.depth.test, preset.depth.test);
+    assert_eq!(params.depth.write, preset.depth.write);
+    assert_eq!(params.backface_culling, preset.backface_culling);
+    assert_eq!(params.color_mask, preset.color_mask);
+}
+
+#[test]
+fn derivative_hint() {
+    let display = support::build_display();
+
+    let params = glium::DrawParameters::builder()
+                        .derivative_hint(glium::DerivativeHint::Nicest)
+                        .build();
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params).unwrap();
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn logic_op_xor() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let params = glium::DrawParameters::builder()
+                        .logic_op(glium::LogicOp::Xor)
+                        .build();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(1.0, 0.0, 1.0, 0.0);
+
+    match texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                                    &params)
+    {
+        Ok(_) => (),
+        Err(glium::DrawError::LogicOpNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 255, 255));
+
+    // the logic operation must be disabled by the next draw command
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn logic_op_integer_framebuffer() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = match program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                uniform uint value;
+                out uvec4 color;
+
+                void main() {
+                    color = uvec4(value, 0u, 0u, 1u);
+                }
+            "
+        },
+    ) {
+        Ok(p) => p,
+        Err(glium::program::ProgramChooserCreationError::NoVersion) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let texture = match glium::texture::UnsignedTexture2d::empty_renderable(&display,
+                                            glium::texture::UncompressedUintFormat::U32U32U32U32,
+                                            glium::texture::MipmapsOption::NoMipmap, 16, 16)
+    {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, &texture).unwrap();
+    framebuffer.clear_color_unsigned(0, [0b1100, 0, 0, 1]);
+
+    let params = glium::DrawParameters {
+        logic_op: Some(glium::LogicOp::Xor),
+        .. Default::default()
+    };
+
+    match framebuffer.draw(&vb, &ib, &program, &uniform!{ value: 0b1010u32 }, &params) {
+        Ok(_) => (),
+        Err(glium::DrawError::LogicOpNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let data: Vec<Vec<(u32, u32, u32, u32)>> = texture.read();
+    assert_eq!(data[0][0], (0b0110, 0, 0, 0));
+
+    let params = glium::DrawParameters {
+        logic_op: Some(glium::LogicOp::Invert),
+        .. Default::default()
+    };
+
+    framebuffer.draw(&vb, &ib, &program, &uniform!{ value: 0u32 }, &params).unwrap();
+
+    let data: Vec<Vec<(u32, u32, u32, u32)>> = texture.read();
+    assert_eq!(data[0][0], (!0b0110, !0, !0, !0));
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,113 @@
+extern crate glium;
+
+use std::error::Error;
+
+/// Converts `$err` into a `glium::Error`, checks that it gives the `$variant` variant, and that
+/// the description, the message and the cause are those of the original error.
+macro_rules! check_conversion {
+    ($err:expr, $variant:ident) => ({
+        let original = $err;
+        let converted = glium::Error::from($err);
+
+        match converted {
+            glium::Error::$variant(ref err) => assert_eq!(format!("{:?}", err),
+                                                          format!("{:?}", original)),
+            ref err => panic!("{:?}", err),
+        };
+
+        assert_eq!(converted.description(), original.description());
+        assert_eq!(converted.to_string(), original.to_string());
+        assert_eq!(converted.cause().unwrap().description(), original.description());
+    });
+}
+
+#[test]
+fn buffer_errors() {
+    check_conversion!(glium::buffer::BufferCreationError::OutOfMemory, BufferCreation);
+    check_conversion!(glium::buffer::ReadError::NotSupported, BufferRead);
+    check_conversion!(glium::buffer::MapError::ContextLost, BufferMap);
+    check_conversion!(glium::buffer::WriteError::LengthMismatch { expected: 4, obtained: 2 },
+                      BufferWrite);
+    check_conversion!(glium::buffer::CopyError::NotSupported, BufferCopy);
+    check_conversion!(glium::buffer::ClearError::InvalidValueSize, BufferClear);
+}
+
+#[test]
+fn vertex_and_index_errors() {
+    check_conversion!(glium::vertex::BufferCreationError::FormatNotSupported,
+                      VertexBufferCreation);
+    check_conversion!(glium::index::BufferCreationError::IndexTypeNotSupported,
+                      IndexBufferCreation);
+    check_conversion!(glium::index::FlipWindingError::PrimitiveTypeNotSupported, FlipWinding);
+    check_conversion!(glium::vertex::VertexFormatDeserializationError::InvalidHeader,
+                      VertexFormatDeserialization);
+}
+
+#[test]
+fn texture_errors() {
+    check_conversion!(glium::texture::TextureCreationError::FormatNotSupported,
+                      TextureCreation);
+    check_conversion!(glium::texture::ReadImageError::LevelOutOfRange { level: 3, levels: 1 },
+                      TextureRead);
+    check_conversion!(glium::texture::WriteRowsError::NotEnoughRows { expected: 4, provided: 2 },
+                      TextureWriteRows);
+    check_conversion!(glium::texture::CompressedLevelError::NotSupported, CompressedLevel);
+}
+
+#[test]
+fn program_errors() {
+    check_conversion!(glium::ProgramCreationError::CompilationError("error".to_owned()),
+                      ProgramCreation);
+    check_conversion!(glium::program::PrewarmError::DrawError(glium::DrawError::AttributeMissing),
+                      ProgramPrewarm);
+    check_conversion!(glium::uniforms::GlobalUniformBlockError::NotSupported,
+                      GlobalUniformBlock);
+}
+
+#[test]
+fn framebuffer_errors() {
+    check_conversion!(glium::DrawError::NoDepthBuffer, Draw);
+    check_conversion!(glium::framebuffer::ValidationError::EmptyFramebufferObjectsNotSupported,
+                      FramebufferValidation);
+    check_conversion!(glium::framebuffer::ReadError::OutputFormatNotSupported, FramebufferRead);
+    check_conversion!(glium::BlitError::NoDepthBuffer, Blit);
+    check_conversion!(glium::CaptureError::NotSupported, Capture);
+    check_conversion!(glium::draw_parameters::ToBufferError::NotSupported, QueryToBuffer);
+}
+
+#[test]
+fn context_errors() {
+    check_conversion!(glium::SwapBuffersError::ContextLost, SwapBuffers);
+    check_conversion!(glium::backend::SwapIntervalNotSupportedError, SwapIntervalNotSupported);
+}
+
+#[test]
+fn try_converts_errors() {
+    fn create() -> Result<(), glium::Error> {
+        try!(Err(glium::buffer::MapError::NotSupported));
+        Ok(())
+    }
+
+    match create() {
+        Err(glium::Error::BufferMap(glium::buffer::MapError::NotSupported)) => (),
+        r => panic!("{:?}", r),
+    };
+}
+
+#[test]
+fn draw_error_description() {
+    let err = glium::DrawError::NoDepthBuffer;
+    assert_eq!(err.description(),
+               "A depth function has been requested but no depth buffer is available");
+
+    let err = glium::DrawError::WrongContext { object_kind: "program" };
+    assert!(err.description() != glium::DrawError::ContextLost.description());
+
+    // the details are only in the message
+    let err = glium::DrawError::FeedbackLoop {
+        texture: "tex".to_owned(),
+        attachment: glium::framebuffer::AttachmentPoint::Depth,
+    };
+    assert!(!err.description().contains("tex"));
+    assert!(err.to_string().contains("`tex`"));
+}
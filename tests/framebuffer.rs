@@ -2,6 +2,7 @@
 extern crate glium;
 
 use glium::Surface;
+use glium::CaptureSurface;
 
 mod support;
 
@@ -448,3 +449,563 @@ fn empty_framebuffer_simple_draw() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn interleaved_clears_different_sizes() {
+    let display = support::build_display();
+
+    let texture1 = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              128, 128).unwrap();
+    let texture2 = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              16, 16).unwrap();
+
+    let mut framebuffer1 = glium::framebuffer::SimpleFrameBuffer::new(&display, &texture1).unwrap();
+    let mut framebuffer2 = glium::framebuffer::SimpleFrameBuffer::new(&display, &texture2).unwrap();
+
+    framebuffer1.clear_color(0.0, 0.0, 0.0, 0.0);
+
+    let rect = glium::Rect { left: 2, bottom: 2, width: 4, height: 4 };
+    framebuffer2.clear(Some(&rect), Some((0.0, 1.0, 0.0, 1.0)), false, None, None);
+
+    // must not be restricted by the scissor box of the previous clear
+    framebuffer1.clear_color(1.0, 0.0, 0.0, 1.0);
+
+    framebuffer2.clear(Some(&rect), Some((0.0, 0.0, 1.0, 1.0)), false, None, None);
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture1.read();
+    assert_eq!(read_back[0][0], (255, 0, 0, 255));
+    assert_eq!(read_back[64][64], (255, 0, 0, 255));
+    assert_eq!(read_back[127][127], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn capture_top_to_bottom() {
+    let display = support::build_display();
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              64, 32).unwrap();
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, &texture).unwrap();
+    framebuffer.clear_color(1.0, 0.0, 0.0, 0.5);
+    framebuffer.clear(Some(&glium::Rect { left: 0, bottom: 0, width: 64, height: 16 }),
+                      Some((0.0, 1.0, 0.0, 0.5)), false, None, None);
+
+    let image = framebuffer.capture().unwrap();
+    assert_eq!(image.width, 64);
+    assert_eq!(image.height, 32);
+    assert_eq!(image.data.len(), 64 * 32 * 4);
+    assert_eq!(&image.data[0 .. 3], &[255, 0, 0]);
+    assert_eq!(&image.data[(31 * 64) * 4 .. (31 * 64) * 4 + 3], &[0, 255, 0]);
+
+    let opaque = framebuffer.capture_opaque().unwrap();
+    assert_eq!(opaque.data[3], 255);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn feedback_loop() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform sampler2D texture;
+
+                void main() {
+                    gl_FragColor = texture2D(texture, vec2(0.5, 0.5));
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = support::build_unicolor_texture2d(&display, 1.0, 0.0, 0.0);
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, &texture).unwrap();
+
+    match framebuffer.draw(&vb, &ib, &program, &uniform!{ texture: &texture },
+                           &Default::default())
+    {
+        Err(glium::DrawError::FeedbackLoop { ref texture, attachment })
+            if texture == "texture" &&
+               attachment == glium::framebuffer::AttachmentPoint::Color(0) => (),
+        a => panic!("{:?}", a)
+    };
+
+    // sampling another texture is fine
+    let other = support::build_unicolor_texture2d(&display, 0.0, 1.0, 0.0);
+    framebuffer.draw(&vb, &ib, &program, &uniform!{ texture: &other },
+                     &Default::default()).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn feedback_loop_read_only_depth() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform sampler2D texture;
+
+                void main() {
+                    gl_FragColor = texture2D(texture, vec2(0.5, 0.5));
+                }
+            ",
+        },
+    ).unwrap();
+
+    let color = glium::Texture2d::empty(&display, 64, 64).unwrap();
+    let depth = match glium::texture::DepthTexture2d::empty(&display, 64, 64) {
+        Err(_) => return,
+        Ok(t) => t
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                                   &color, &depth).unwrap();
+
+    let params = glium::DrawParameters {
+        depth: glium::Depth {
+            test: glium::DepthTest::IfLess,
+            write: true,
+            .. Default::default()
+        },
+        .. Default::default()
+    };
+
+    match framebuffer.draw(&vb, &ib, &program, &uniform!{ texture: &depth }, &params) {
+        Err(glium::DrawError::FeedbackLoop { attachment, .. })
+            if attachment == glium::framebuffer::AttachmentPoint::Depth => (),
+        a => panic!("{:?}", a)
+    };
+
+    // the depth buffer is not written to, so sampling it is fine
+    let params = glium::DrawParameters {
+        depth: glium::Depth {
+            test: glium::DepthTest::IfLess,
+            write: false,
+            .. Default::default()
+        },
+        .. Default::default()
+    };
+
+    framebuffer.draw(&vb, &ib, &program, &uniform!{ texture: &depth }, &params).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn consecutive_clears_merged() {
+    let display = support::build_display();
+
+    let color = glium::Texture2d::empty(&display, 64, 64).unwrap();
+    let depth = match glium::texture::DepthTexture2d::empty(&display, 64, 64) {
+        Err(_) => return,
+        Ok(t) => t
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                                   &color, &depth).unwrap();
+
+    let before = display.get_clear_statistics();
+    framebuffer.clear_color(1.0, 0.0, 1.0, 1.0);
+    framebuffer.clear_depth(0.5);
+    let after = display.get_clear_statistics();
+    assert_eq!(after.clear_calls, before.clear_calls + 1);
+
+    // the clear values haven't changed
+    framebuffer.clear_color(1.0, 0.0, 1.0, 1.0);
+    framebuffer.clear_depth(0.5);
+    let last = display.get_clear_statistics();
+    assert_eq!(last.clear_calls, after.clear_calls + 1);
+    assert_eq!(last.clear_value_calls, after.clear_value_calls);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = color.read();
+    assert_eq!(data[0][0], (255, 0, 255, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn clear_unsigned_picking_buffer() {
+    use std::u32;
+
+    let display = support::build_display();
+
+    let picking = match glium::texture::UnsignedTexture2d::empty_renderable(&display,
+                                            glium::texture::UncompressedUintFormat::U32U32U32U32,
+                                            glium::texture::MipmapsOption::NoMipmap, 64, 64)
+    {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, &picking).unwrap();
+    framebuffer.clear_color_unsigned(0, [u32::MAX, u32::MAX, u32::MAX, u32::MAX]);
+
+    let data: Vec<Vec<(u32, u32, u32, u32)>> = picking.read();
+    assert_eq!(data[0][0], (u32::MAX, u32::MAX, u32::MAX, u32::MAX));
+    assert_eq!(data[63][63], (u32::MAX, u32::MAX, u32::MAX, u32::MAX));
+
+    // the generic clear converts the components to integers
+    framebuffer.clear_color(5.0, 0.0, 0.0, 1.0);
+
+    let data: Vec<Vec<(u32, u32, u32, u32)>> = picking.read();
+    assert_eq!(data[0][0], (5, 0, 0, 1));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn clear_unsigned_wrong_attachment_type() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color_unsigned(0, [0, 0, 0, 0]);
+}
+
+#[test]
+fn framebuffer_attachment_recreated() {
+    let display = support::build_display();
+
+    // the FBO created for the first texture is destroyed along with the texture, so that it
+    // isn't reused if the OpenGL implementation gives the same id to the second texture
+    {
+        let texture = support::build_renderable_texture(&display);
+        texture.as_surface().clear_color(1.0, 0.0, 0.0, 1.0);
+    }
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+    assert_eq!(data[1023][1023], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+/// Builds the attachments used by the `framebuffer_drop_order_*` tests, and draws on them.
+fn build_attachments(display: &glium::Display)
+                     -> Option<(glium::Texture2d, glium::framebuffer::DepthRenderBuffer)>
+{
+    let color = support::build_renderable_texture(display);
+    let depth = match glium::framebuffer::DepthRenderBuffer::new(display,
+                                                    glium::texture::DepthFormat::I24, 1024, 1024)
+    {
+        Ok(d) => d,
+        Err(_) => return None
+    };
+
+    {
+        let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(display,
+                                                                        &color, &depth).unwrap();
+        framebuffer.clear_color_and_depth((1.0, 0.0, 0.0, 1.0), 1.0);
+    }
+
+    Some((color, depth))
+}
+
+#[test]
+fn framebuffer_drop_order_display_last() {
+    let display = support::build_display();
+
+    let (color, depth) = match build_attachments(&display) {
+        Some(a) => a,
+        None => return
+    };
+
+    drop(color);
+    drop(depth);
+    display.assert_no_error(None);
+
+    // the cached FBO must not be used anymore
+    let (color, depth) = build_attachments(&display).unwrap();
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = color.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    drop(depth);
+    drop(color);
+    display.assert_no_error(None);
+    drop(display);
+}
+
+#[test]
+fn framebuffer_drop_order_display_first() {
+    let display = support::build_display();
+
+    let (color, depth) = match build_attachments(&display) {
+        Some(a) => a,
+        None => return
+    };
+
+    // the attachments keep the context alive
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                        &color, &depth).unwrap();
+    drop(display);
+
+    framebuffer.clear_color(0.0, 0.0, 1.0, 1.0);
+    drop(framebuffer);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = color.read();
+    assert_eq!(data[0][0], (0, 0, 255, 255));
+
+    drop(color);
+    drop(depth);
+}
+
+#[test]
+fn framebuffer_drop_order_display_between() {
+    let display = support::build_display();
+
+    let (color, depth) = match build_attachments(&display) {
+        Some(a) => a,
+        None => return
+    };
+
+    drop(depth);
+    drop(display);
+    drop(color);
+}
+
+/// Builds a 64x64 texture filled with a color.
+fn build_filled_texture(display: &glium::Display, color: (f32, f32, f32, f32))
+                        -> glium::Texture2d
+{
+    let texture = glium::Texture2d::empty_with_format(display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              64, 64).unwrap();
+    texture.as_surface().clear(None, Some(color), false, None, None);
+    texture
+}
+
+#[test]
+fn copy_to_texture() {
+    let display = support::build_display();
+
+    let source = build_filled_texture(&display, (1.0, 0.0, 0.0, 1.0));
+    let dest = build_filled_texture(&display, (0.0, 0.0, 1.0, 1.0));
+
+    let rect = glium::Rect { left: 4, bottom: 4, width: 16, height: 16 };
+    source.as_surface().copy_to_texture(&dest, &rect, (8, 8), 0).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = dest.read();
+    assert_eq!(data[0][0], (0, 0, 255, 255));
+    assert_eq!(data[8][8], (255, 0, 0, 255));
+    assert_eq!(data[23][23], (255, 0, 0, 255));
+    assert_eq!(data[24][24], (0, 0, 255, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn copy_to_texture_from_frame() {
+    let display = support::build_display();
+    let dest = build_filled_texture(&display, (0.0, 0.0, 1.0, 1.0));
+
+    let mut frame = display.draw();
+    frame.clear_color(0.0, 1.0, 0.0, 1.0);
+    let rect = glium::Rect { left: 0, bottom: 0, width: 8, height: 8 };
+    frame.copy_to_texture(&dest, &rect, (0, 0), 0).unwrap();
+    frame.finish().unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = dest.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+    assert_eq!(data[7][7], (0, 255, 0, 255));
+    assert_eq!(data[8][8], (0, 0, 255, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn copy_to_texture_out_of_bounds() {
+    let display = support::build_display();
+
+    let source = build_filled_texture(&display, (1.0, 0.0, 0.0, 1.0));
+    let dest = build_filled_texture(&display, (0.0, 0.0, 1.0, 1.0));
+    let surface = source.as_surface();
+
+    let rect = glium::Rect { left: 60, bottom: 0, width: 16, height: 16 };
+    match surface.copy_to_texture(&dest, &rect, (0, 0), 0) {
+        Err(glium::framebuffer::ReadError::RectOutOfBounds) => (),
+        e => panic!("{:?}", e)
+    };
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 16, height: 16 };
+    match surface.copy_to_texture(&dest, &rect, (0, 50), 0) {
+        Err(glium::framebuffer::ReadError::DestinationOutOfBounds) => (),
+        e => panic!("{:?}", e)
+    };
+
+    match surface.copy_to_texture(&dest, &rect, (0, 0), 1) {
+        Err(glium::framebuffer::ReadError::DestinationOutOfBounds) => (),
+        e => panic!("{:?}", e)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn copy_to_texture_multisample() {
+    let display = support::build_display();
+
+    let source = match glium::texture::Texture2dMultisample::empty(&display, 64, 64, 4) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let framebuffer = match glium::framebuffer::SimpleFrameBuffer::new(&display, &source) {
+        Ok(f) => f,
+        Err(_) => return
+    };
+
+    let dest = build_filled_texture(&display, (0.0, 0.0, 1.0, 1.0));
+    let rect = glium::Rect { left: 0, bottom: 0, width: 16, height: 16 };
+
+    match framebuffer.copy_to_texture(&dest, &rect, (0, 0), 0) {
+        Err(glium::framebuffer::ReadError::MultisampleSource) => (),
+        e => panic!("{:?}", e)
+    };
+
+    display.assert_no_error(None);
+}
+
+/// Creates a framebuffer object behind glium's back, with `texture` as its first color
+/// attachment if it is `Some`. Returns `None` if framebuffer objects are not supported.
+fn create_external_fbo(display: &glium::Display, texture: Option<&glium::Texture2d>)
+                       -> Option<u32>
+{
+    use std::mem;
+    use glium::GlObject;
+
+    let texture = texture.map(|t| t.get_id());
+
+    unsafe {
+        display.exec_in_context(move |data| {
+            let gen = data.get_proc_address("glGenFramebuffers");
+            let bind = data.get_proc_address("glBindFramebuffer");
+            let attach = data.get_proc_address("glFramebufferTexture2D");
+            if gen.is_null() || bind.is_null() || attach.is_null() {
+                return None;
+            }
+
+            let gen: extern "system" fn(i32, *mut u32) = mem::transmute(gen);
+            let bind: extern "system" fn(u32, u32) = mem::transmute(bind);
+            let attach: extern "system" fn(u32, u32, u32, u32, i32) = mem::transmute(attach);
+
+            let mut id = 0;
+            gen(1, &mut id);
+            bind(0x8D40, id);       // GL_FRAMEBUFFER
+            if let Some(texture) = texture {
+                // GL_FRAMEBUFFER, GL_COLOR_ATTACHMENT0, GL_TEXTURE_2D
+                attach(0x8D40, 0x8CE0, 0x0DE1, texture, 0);
+            }
+            bind(0x8D40, 0);
+
+            Some(id)
+        })
+    }
+}
+
+/// Destroys a framebuffer object created with `create_external_fbo`.
+fn destroy_external_fbo(display: &glium::Display, id: u32) {
+    use std::mem;
+
+    unsafe {
+        display.exec_in_context(move |data| {
+            let delete = data.get_proc_address("glDeleteFramebuffers");
+            let delete: extern "system" fn(i32, *const u32) = mem::transmute(delete);
+            delete(1, &id);
+        });
+    }
+}
+
+#[test]
+fn external_framebuffer_draw() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+
+    let id = match create_external_fbo(&display, Some(&texture)) {
+        Some(id) => id,
+        None => return
+    };
+
+    {
+        let mut framebuffer = unsafe {
+            glium::framebuffer::ExternalFramebuffer::from_raw_fbo(&display, id, (1024, 1024),
+                                                                  None, None)
+        };
+        assert!(framebuffer.is_complete());
+        assert_eq!(framebuffer.get_dimensions(), (1024, 1024));
+
+        framebuffer.clear_color(0.0, 1.0, 0.0, 1.0);
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(data[0][0], (0, 255, 0, 255));
+        assert_eq!(data[1023][1023], (0, 255, 0, 255));
+
+        framebuffer.draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                         &Default::default()).unwrap();
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(data[0][0], (255, 0, 0, 255));
+        assert_eq!(data[1023][1023], (255, 0, 0, 255));
+    }
+
+    destroy_external_fbo(&display, id);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn external_framebuffer_no_attachment_incomplete() {
+    let display = support::build_display();
+
+    let id = match create_external_fbo(&display, None) {
+        Some(id) => id,
+        None => return
+    };
+
+    {
+        let framebuffer = unsafe {
+            glium::framebuffer::ExternalFramebuffer::from_raw_fbo(&display, id, (1024, 1024),
+                                                                  None, None)
+        };
+        assert!(!framebuffer.is_complete());
+    }
+
+    destroy_external_fbo(&display, id);
+    display.assert_no_error(None);
+}
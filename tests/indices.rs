@@ -412,3 +412,156 @@ fn multidraw_elements() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn indexbuffer_from_iter() {
+    let display = support::build_display();
+
+    let indices = glium::IndexBuffer::from_iter(&display, PrimitiveType::TrianglesList,
+                                                (0 .. 6u16).rev()).unwrap();
+
+    assert_eq!(indices.len(), 6);
+    if let Ok(data) = indices.read() {
+        assert_eq!(data, vec![5, 4, 3, 2, 1, 0]);
+    }
+
+    display.assert_no_error(None);
+}
+
+/// Iterator whose `len()` doesn't match the number of elements that it produces.
+struct WrongLength(u16, usize);
+
+impl Iterator for WrongLength {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        if self.0 == 0 { return None; }
+        self.0 -= 1;
+        Some(self.0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.1, Some(self.1))
+    }
+}
+
+impl ExactSizeIterator for WrongLength {}
+
+#[test]
+fn indexbuffer_from_iter_too_few() {
+    let display = support::build_display();
+
+    match glium::IndexBuffer::from_iter(&display, PrimitiveType::TrianglesList,
+                                        WrongLength(3, 6))
+    {
+        Err(glium::index::BufferCreationError::IteratorLengthMismatch { expected: 6,
+                                                                        obtained: 3 }) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn indexbuffer_from_iter_too_many() {
+    let display = support::build_display();
+
+    match glium::IndexBuffer::from_iter(&display, PrimitiveType::TrianglesList,
+                                        WrongLength(9, 6))
+    {
+        Err(glium::index::BufferCreationError::IteratorLengthMismatch { expected: 6,
+                                                                        obtained: 7 }) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn generate_quad_list() {
+    let indices = glium::index::generate::quad_list_indices(2).collect::<Vec<_>>();
+    assert_eq!(indices, vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7]);
+}
+
+#[test]
+fn generate_grid_triangles() {
+    let indices = glium::index::generate::grid_triangles(2, 1);
+    assert_eq!(indices.len(), 12);
+    assert_eq!(indices.collect::<Vec<_>>(), vec![0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4]);
+}
+
+#[test]
+fn flip_winding_triangles_list() {
+    let mut indices = vec![0u16, 1, 2, 3, 4, 5, 6, 7];
+    index::winding::flip_winding(PrimitiveType::TrianglesList, &mut indices).unwrap();
+    assert_eq!(indices, vec![0, 2, 1, 3, 5, 4, 6, 7]);
+}
+
+#[test]
+fn flip_winding_triangle_fan() {
+    let mut indices = vec![0u32, 1, 2, 3, 4];
+    index::winding::flip_winding(PrimitiveType::TriangleFan, &mut indices).unwrap();
+    assert_eq!(indices, vec![0, 4, 3, 2, 1]);
+}
+
+#[test]
+fn flip_winding_triangle_strip_restart() {
+    let mut indices = vec![0u16, 1, 2, 3, 0xffff, 4, 5, 6];
+    index::winding::flip_winding(PrimitiveType::TriangleStrip, &mut indices).unwrap();
+    assert_eq!(indices, vec![0, 0, 1, 2, 3, 0xffff, 4, 4, 5, 6]);
+}
+
+#[test]
+fn flip_winding_restart_untouched() {
+    let mut indices = vec![0u8, 1, 2, 0xff, 3, 4, 5];
+    index::winding::flip_winding(PrimitiveType::TrianglesList, &mut indices).unwrap();
+    assert_eq!(indices, vec![0, 2, 1, 0xff, 3, 5, 4]);
+}
+
+#[test]
+fn flip_winding_lines() {
+    let mut indices = vec![0u16, 1, 2, 3];
+    match index::winding::flip_winding(PrimitiveType::LinesList, &mut indices)
+    {
+        Err(index::FlipWindingError::PrimitiveTypeNotSupported) => (),
+        _ => panic!()
+    };
+    assert_eq!(indices, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn index_buffer_flip_winding() {
+    let display = support::build_display();
+
+    let buffer = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                         &[0u16, 1, 2, 2, 1, 3]).unwrap();
+
+    let flipped = match buffer.flip_winding(&display) {
+        Ok(b) => b,
+        Err(index::FlipWindingError::ReadError(_)) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    assert_eq!(flipped.get_primitives_type(), PrimitiveType::TrianglesList);
+    assert_eq!(flipped.read().unwrap(), vec![0, 2, 1, 2, 3, 1]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn indexbuffer_new_streamed() {
+    let display = support::build_display();
+
+    let data = (0 .. 600u16).collect::<Vec<_>>();
+
+    let indices = glium::IndexBuffer::new_streamed(&display, PrimitiveType::TrianglesList,
+                                                   &data, 256).unwrap();
+
+    assert!(indices.is_complete());
+    assert_eq!(indices.len(), 600);
+    if let Ok(content) = indices.read() {
+        assert_eq!(content, data);
+    }
+
+    display.assert_no_error(None);
+}
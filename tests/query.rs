@@ -309,3 +309,101 @@ fn query_to_buffer() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn occlusion_query_result_to_indirect_buffer() {
+    let display = support::build_display();
+
+    let query = match glium::draw_parameters::AnySamplesPassedQuery::new(&display, false) {
+        Err(_) => return,
+        Ok(q) => q
+    };
+
+    let commands = match glium::index::DrawCommandsNoIndicesBuffer::empty(&display, 1) {
+        Err(_) => return,
+        Ok(b) => b
+    };
+
+    commands.write(&[
+        glium::index::DrawCommandNoIndices {
+            count: 4,
+            instance_count: 0,
+            first_index: 0,
+            base_instance: 0,
+        }
+    ]);
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    {
+        let params = glium::DrawParameters {
+            samples_passed_query: Some((&query).into()),
+            .. Default::default()
+        };
+
+        texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params)
+               .unwrap();
+    }
+
+    // `instance_count` is the second field of the command
+    match query.write_result_to_buffer(&*commands, 4) {
+        Err(glium::draw_parameters::ToBufferError::NotSupported) => return,
+        r => r.unwrap()
+    };
+
+    let data = commands.read().unwrap();
+    assert_eq!(data[0].instance_count, 1);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn query_result_to_buffer_errors() {
+    let display = support::build_display();
+
+    let query = match glium::draw_parameters::SamplesPassedQuery::new(&display) {
+        Err(_) => return,
+        Ok(q) => q
+    };
+
+    let buffer = glium::buffer::Buffer::<[u32]>::empty_array(&display,
+                                                  glium::buffer::BufferType::QueryBuffer, 4,
+                                                  glium::buffer::BufferMode::Default).unwrap();
+
+    match query.write_result_to_buffer(&buffer, 0) {
+        Err(glium::draw_parameters::ToBufferError::NotSupported) => return,
+        Err(glium::draw_parameters::ToBufferError::QueryNotUsed) => (),
+        r => panic!("{:?}", r)
+    };
+
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = support::build_renderable_texture(&display);
+
+    {
+        let params = glium::DrawParameters {
+            samples_passed_query: Some((&query).into()),
+            .. Default::default()
+        };
+
+        texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params)
+               .unwrap();
+    }
+
+    match query.write_result_to_buffer(&buffer, 2) {
+        Err(glium::draw_parameters::ToBufferError::MisalignedOffset) => (),
+        r => panic!("{:?}", r)
+    };
+
+    match query.write_result_to_buffer(&buffer, 16) {
+        Err(glium::draw_parameters::ToBufferError::BufferTooSmall) => (),
+        r => panic!("{:?}", r)
+    };
+
+    query.write_result_to_buffer(&buffer, 12).unwrap();
+    assert_eq!(buffer.read().unwrap()[3], 1024 * 1024);
+
+    display.assert_no_error(None);
+}
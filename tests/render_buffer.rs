@@ -0,0 +1,59 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::framebuffer::{RenderBuffer, SimpleFrameBuffer};
+use glium::texture::UncompressedFloatFormat;
+
+mod support;
+
+#[test]
+fn render_buffer_read() {
+    let display = support::build_display();
+
+    let buffer = RenderBuffer::new(&display, UncompressedFloatFormat::U8U8U8U8, 32, 16).unwrap();
+    SimpleFrameBuffer::new(&display, &buffer).unwrap().clear_color(1.0, 0.0, 0.0, 1.0);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = buffer.read().unwrap();
+    assert_eq!(data.len(), 16);
+    assert_eq!(data[0].len(), 32);
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data[15][31], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn render_buffer_multisample_resolve() {
+    let display = support::build_display();
+
+    let multisampled = match RenderBuffer::new_multisample(&display,
+                                                           UncompressedFloatFormat::U8U8U8U8,
+                                                           32, 32, 4)
+    {
+        Ok(b) => b,
+        Err(_) => return
+    };
+    assert_eq!(multisampled.get_samples(), Some(4));
+
+    let resolved = RenderBuffer::new(&display, UncompressedFloatFormat::U8U8U8U8, 32, 32).unwrap();
+
+    let mut source = SimpleFrameBuffer::new(&display, &multisampled).unwrap();
+    source.clear_color(0.0, 1.0, 0.0, 1.0);
+    let target = SimpleFrameBuffer::new(&display, &resolved).unwrap();
+    source.blit_whole_color_to(&target, &glium::BlitTarget { left: 0, bottom: 0,
+                                                             width: 32, height: 32 },
+                               glium::uniforms::MagnifySamplerFilter::Nearest);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = resolved.read().unwrap();
+    assert_eq!(data[16][16], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn render_buffer_format_supported() {
+    let display = support::build_display();
+    assert!(RenderBuffer::is_format_supported(&display, UncompressedFloatFormat::U8U8U8U8));
+    display.assert_no_error(None);
+}
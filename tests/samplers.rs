@@ -79,3 +79,60 @@ fn magnify_nearest_filtering() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn integral_texture_linear_filtering() {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 130
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 130
+
+            uniform isampler2D texture;
+            out vec4 color;
+
+            void main() {
+                color = vec4(texture(texture, vec2(0.5, 0.5)));
+            }
+        ", None)
+    {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let texture = match glium::texture::IntegralTexture2d::empty(&display, 2, 2) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let uniforms = uniform! {
+        texture: texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Linear)
+    };
+
+    let output = support::build_renderable_texture(&display);
+
+    match output.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()) {
+        Err(glium::DrawError::IncompleteTexture { reason, .. }) => {
+            assert_eq!(reason,
+                       glium::uniforms::IncompleteTextureReason::IntegralTextureWithLinearFiltering);
+        },
+        Err(glium::DrawError::SamplersNotSupported) => return,
+        r => panic!("{:?}", r)
+    };
+
+    display.assert_no_error(None);
+}
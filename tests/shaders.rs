@@ -368,6 +368,7 @@ fn get_transform_feedback_varyings() {
         geometry_shader: None,
         outputs_srgb: false,
         uses_point_size: false,
+        dual_source_output: None,
 
         vertex_shader: "
             #version 110
@@ -859,3 +860,191 @@ fn array_layout_offsets() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn program_prewarm() {
+    use std::borrow::Cow;
+
+    let display = support::build_display();
+    let (_, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let format: glium::vertex::VertexFormat = Cow::Owned(vec![
+        (Cow::Borrowed("position"), 0, glium::vertex::AttributeType::F32F32, None, None),
+    ]);
+
+    program.prewarm(&display, &Default::default(), &format).unwrap();
+
+    let params = glium::DrawParameters {
+        blend: glium::Blend::alpha_blending(),
+        .. Default::default()
+    };
+
+    let default = Default::default();
+    let list = vec![(&program, &default, &format), (&program, &params, &format)];
+    let results = display.prewarm_many(list.into_iter()).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    for result in results { result.unwrap(); }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn program_prewarm_wrong_format() {
+    use std::borrow::Cow;
+
+    let display = support::build_display();
+    let (_, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let format: glium::vertex::VertexFormat = Cow::Owned(vec![
+        (Cow::Borrowed("color"), 0, glium::vertex::AttributeType::F32F32F32, None, None),
+    ]);
+
+    match program.prewarm(&display, &Default::default(), &format) {
+        Err(glium::program::PrewarmError::DrawError(glium::DrawError::AttributeMissing)) => (),
+        r => panic!("{:?}", r),
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn early_fragment_tests() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 420
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 420
+
+            layout( early_fragment_tests ) in;
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None);
+
+    // GLSL 4.20 may not be supported
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    assert!(program.has_early_fragment_tests());
+    assert!(!program.writes_frag_depth());
+
+    let (_, _, program) = support::build_fullscreen_red_pipeline(&display);
+    assert!(!program.has_early_fragment_tests());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn early_fragment_tests_ignore_frag_depth() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 420
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.5, 1.0);
+            }
+        ",
+        "
+            #version 420
+
+            layout( early_fragment_tests ) in;
+            out vec4 color;
+
+            void main() {
+                gl_FragDepth = 0.0;
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None);
+
+    // GLSL 4.20 may not be supported
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    assert!(program.has_early_fragment_tests());
+    assert!(program.writes_frag_depth());
+
+    let color = support::build_renderable_texture(&display);
+    let depth = match glium::framebuffer::DepthRenderBuffer::new(&display,
+                                                    glium::texture::DepthFormat::I24, 1024, 1024)
+    {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                    &color, &depth).unwrap();
+    framebuffer.clear_color_and_depth((0.0, 0.0, 0.0, 0.0), 0.5);
+
+    let params = glium::DrawParameters {
+        depth: glium::Depth {
+            test: glium::DepthTest::IfLess,
+            write: true,
+            .. Default::default()
+        },
+        .. Default::default()
+    };
+
+    // the depth test uses the depth of the primitive (0.75) and not `gl_FragDepth`, so
+    // nothing is drawn, and drawing it a second time must not fail either
+    framebuffer.draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params).unwrap();
+    framebuffer.draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = color.read();
+    assert_eq!(data[0][0], (0, 0, 0, 0));
+    assert_eq!(data[512][512], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn builtins_in_comments_ignored() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                // gl_ClipDistance[0] = 1.0;
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                /* gl_FragDepth = 0.5; */
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert!(!program.uses_clip_distance());
+    assert!(!program.writes_frag_depth());
+
+    display.assert_no_error(None);
+}
@@ -7,9 +7,12 @@ Test supports module.
 
 use glium::{self, glutin, DisplayBuild};
 use glium::backend::Facade;
+use glium::debug;
 use glium::index::PrimitiveType;
 
+use std::cell::Cell;
 use std::env;
+use std::rc::Rc;
 
 /// Builds a headless display for tests.
 pub fn build_display() -> glium::Display {
@@ -27,6 +30,39 @@ pub fn build_display() -> glium::Display {
     display
 }
 
+/// Builds a headless display whose debug output counts the OpenGL errors.
+///
+/// The messages are reported synchronously, so the counter is up-to-date as soon as the
+/// function that triggered an error returns.
+pub fn build_display_with_error_counter() -> (glium::Display, Rc<Cell<usize>>) {
+    let version = parse_version();
+
+    let errors = Rc::new(Cell::new(0));
+    let errors_clone = errors.clone();
+
+    let behavior = debug::DebugCallbackBehavior::Custom {
+        callback: Box::new(move |_: debug::Source, ty: debug::MessageType, _: debug::Severity,
+                                 _: u32, _: bool, _: &str|
+        {
+            if let debug::MessageType::Error = ty {
+                errors_clone.set(errors_clone.get() + 1);
+            }
+        }),
+        synchronous: true,
+    };
+
+    let display = if env::var("GLIUM_HEADLESS_TESTS").is_ok() {
+        glutin::HeadlessRendererBuilder::new(1024, 768).with_gl_debug_flag(true)
+                                                       .with_gl(version)
+                                                       .build_glium_debug(behavior).unwrap()
+    } else {
+        glutin::WindowBuilder::new().with_gl_debug_flag(true).with_visibility(false)
+                                    .with_gl(version).build_glium_debug(behavior).unwrap()
+    };
+
+    (display, errors)
+}
+
 /// Rebuilds an existing display.
 ///
 /// In real applications this is used for things such as switching to fullscreen. Some things are
@@ -384,3 +384,105 @@ fn upload_from_pixel_buffer_inverted() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn empty_renderable_texture2d() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_renderable(&display,
+                                                       glium::texture::UncompressedFloatFormat::
+                                                           U8U8U8U8,
+                                                       glium::texture::MipmapsOption::NoMipmap,
+                                                       128, 128).unwrap();
+
+    texture.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn empty_renderable_texture2d_not_renderable() {
+    let display = support::build_display();
+
+    let format = glium::texture::UncompressedFloatFormat::U8U8U8;
+
+    if format.is_color_renderable(&display) {
+        return;
+    }
+
+    match glium::texture::Texture2d::empty_renderable(&display, format,
+                                                      glium::texture::MipmapsOption::NoMipmap,
+                                                      128, 128)
+    {
+        Err(glium::texture::TextureCreationError::FormatNotSupported) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_dimensions_too_large() {
+    let display = support::build_display();
+
+    match glium::texture::Texture2d::empty(&display, 1 << 30, 1) {
+        Err(glium::texture::TextureCreationError::DimensionsTooLarge { requested, .. }) => {
+            assert_eq!(requested, 1 << 30);
+        },
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_data_length_mismatch() {
+    let display = support::build_display();
+
+    let image = glium::texture::RawImage2d {
+        data: std::borrow::Cow::Owned(vec![0u8; 4 * 4 * 3]),
+        width: 4,
+        height: 4,
+        format: glium::texture::ClientFormat::U8U8U8U8,
+    };
+
+    match glium::texture::Texture2d::new(&display, image) {
+        Err(glium::texture::TextureCreationError::DataLengthMismatch { expected, provided }) => {
+            assert_eq!(expected, 4 * 4 * 4);
+            assert_eq!(provided, 4 * 4 * 3);
+        },
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_creation_error_into_glium_error() {
+    fn create(display: &glium::Display) -> Result<glium::texture::Texture2d, glium::Error> {
+        let image = glium::texture::RawImage2d {
+            data: std::borrow::Cow::Owned(vec![0u8; 4 * 4 * 3]),
+            width: 4,
+            height: 4,
+            format: glium::texture::ClientFormat::U8U8U8U8,
+        };
+
+        let texture = try!(glium::texture::Texture2d::new(display, image));
+        Ok(texture)
+    }
+
+    let display = support::build_display();
+
+    match create(&display) {
+        Err(err @ glium::Error::TextureCreation(_)) => {
+            assert_eq!(err.to_string(), "The length of the data doesn't match the texture \
+                                         dimensions (64 bytes expected, 48 bytes provided)");
+        },
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
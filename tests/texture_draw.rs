@@ -61,3 +61,167 @@ macro_rules! texture_draw_test {
 
 texture_draw_test!(texture_2d_draw, Texture2d, [1024, 1024], "vec4",
                    "vec4(1.0, 0.0, 1.0, 0.0)", (255, 0, 255, 0));
+
+#[test]
+fn fill_with_texture() {
+    let display = support::build_display();
+
+    let source = support::build_unicolor_texture2d(&display, 0.0, 1.0, 0.0);
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    texture.as_surface().fill_with_texture(&source, glium::uniforms::MagnifySamplerFilter::Nearest,
+                                           &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 255, 0, 255));
+        }
+    }
+
+    // the internal objects are only created once
+    let before = display.get_memory_usage();
+    texture.as_surface().fill_with_texture(&source, glium::uniforms::MagnifySamplerFilter::Linear,
+                                           &Default::default()).unwrap();
+    let after = display.get_memory_usage();
+    assert_eq!(before.buffer_count, after.buffer_count);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn fill_resources_dont_keep_context_alive() {
+    use glium::backend::Facade;
+    use std::rc::Rc;
+
+    let display = support::build_display();
+    let clone = display.clone();
+
+    let source = support::build_unicolor_texture2d(&display, 0.0, 1.0, 0.0);
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().fill_with_texture(&source, glium::uniforms::MagnifySamplerFilter::Nearest,
+                                           &Default::default()).unwrap();
+
+    // dropping a clone of the display doesn't affect the other clones
+    drop(clone);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().fill_with_texture(&source, glium::uniforms::MagnifySamplerFilter::Nearest,
+                                           &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+
+    let context = Rc::downgrade(display.get_context());
+    drop(source);
+    drop(texture);
+    drop(display);
+    assert!(context.upgrade().is_none());
+}
+
+#[test]
+fn reset_internal_caches() {
+    let display = support::build_display();
+    display.set_internal_object_labels(true);
+
+    let source = support::build_unicolor_texture2d(&display, 0.0, 1.0, 0.0);
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().fill_with_texture(&source, glium::uniforms::MagnifySamplerFilter::Nearest,
+                                           &Default::default()).unwrap();
+
+    display.reset_internal_caches();
+
+    if let Some(counts) = display.get_live_object_counts() {
+        assert_eq!(counts.framebuffers, 0);
+    }
+
+    // the objects are created again when needed
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().fill_with_texture(&source, glium::uniforms::MagnifySamplerFilter::Nearest,
+                                           &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn fill_with_program() {
+    let display = support::build_display();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec2 tex_coords;
+                varying vec2 v_tex_coords;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_tex_coords = tex_coords;
+                }
+            ",
+            fragment: "
+                #version 110
+
+                varying vec2 v_tex_coords;
+
+                void main() {
+                    gl_FragColor = vec4(v_tex_coords, 0.0, 1.0);
+                }
+            "
+        },
+        100 es => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec2 tex_coords;
+                varying lowp vec2 v_tex_coords;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_tex_coords = tex_coords;
+                }
+            ",
+            fragment: "
+                #version 100
+
+                varying lowp vec2 v_tex_coords;
+
+                void main() {
+                    gl_FragColor = vec4(v_tex_coords, 0.0, 1.0);
+                }
+            "
+        },
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 1.0, 0.0);
+    texture.as_surface().fill_with_program(&program, &uniform!{},
+                                           &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    let (width, height) = (data[0].len(), data.len());
+
+    let (r, g, b, a) = data[0][0];
+    assert!(r < 8 && g < 8 && b == 0 && a == 255);
+
+    let (r, g, b, a) = data[height - 1][width - 1];
+    assert!(r > 247 && g > 247 && b == 0 && a == 255);
+
+    display.assert_no_error(None);
+}
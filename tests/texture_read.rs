@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate glium;
 
+use glium::Surface;
+
 mod support;
 
 #[test]
@@ -40,6 +42,212 @@ fn texture_2d_read_pixelbuffer() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn texture_2d_read_flipped() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![
+        vec![(0u8, 1u8, 2u8), (4u8, 8u8, 16u8)],
+        vec![(32u8, 64u8, 128u8), (32u8, 16u8, 4u8)],
+        vec![(1u8, 2u8, 4u8), (8u8, 16u8, 32u8)],
+    ]).unwrap();
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read_flipped();
+
+    assert_eq!(read_back[0][0], (1, 2, 4, 255));
+    assert_eq!(read_back[0][1], (8, 16, 32, 255));
+    assert_eq!(read_back[1][0], (32, 64, 128, 255));
+    assert_eq!(read_back[1][1], (32, 16, 4, 255));
+    assert_eq!(read_back[2][0], (0, 1, 2, 255));
+    assert_eq!(read_back[2][1], (4, 8, 16, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_read_into() {
+    use glium::texture::ClientFormat;
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![
+        vec![(0u8, 1u8, 2u8), (4u8, 8u8, 16u8)],
+        vec![(32u8, 64u8, 128u8), (32u8, 16u8, 4u8)],
+        vec![(1u8, 2u8, 4u8), (8u8, 16u8, 32u8)],
+    ]).unwrap();
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 3 };
+    let mut dest = vec![0u8; 2 * 3 * 4];
+
+    texture.read_into(&rect, &mut dest, ClientFormat::U8U8U8U8).unwrap();
+    assert_eq!(&dest[..], &[0, 1, 2, 255, 4, 8, 16, 255,
+                            32, 64, 128, 255, 32, 16, 4, 255,
+                            1, 2, 4, 255, 8, 16, 32, 255][..]);
+
+    // the same slice can be reused
+    texture.read_into_flipped(&rect, &mut dest, ClientFormat::U8U8U8U8).unwrap();
+    assert_eq!(&dest[..], &[1, 2, 4, 255, 8, 16, 32, 255,
+                            32, 64, 128, 255, 32, 16, 4, 255,
+                            0, 1, 2, 255, 4, 8, 16, 255][..]);
+
+    let rect = glium::Rect { left: 1, bottom: 1, width: 1, height: 2 };
+    texture.read_into(&rect, &mut dest[.. 8], ClientFormat::U8U8U8U8).unwrap();
+    assert_eq!(&dest[.. 8], &[32, 16, 4, 255, 8, 16, 32, 255][..]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_read_into_errors() {
+    use glium::framebuffer::ReadError;
+    use glium::texture::ClientFormat;
+
+    let display = support::build_display();
+    let texture = support::build_unicolor_texture2d(&display, 1.0, 0.0, 0.0);
+    let (width, height) = (texture.get_width(), texture.get_height().unwrap());
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: width, height: height };
+    let mut dest = vec![0u8; width as usize * height as usize * 4 - 1];
+    match texture.read_into(&rect, &mut dest, ClientFormat::U8U8U8U8) {
+        Err(ReadError::WrongDestinationSize { expected, obtained }) => {
+            assert_eq!(expected, dest.len() + 1);
+            assert_eq!(obtained, dest.len());
+        },
+        r => panic!("{:?}", r)
+    };
+
+    let rect = glium::Rect { left: 1, bottom: 0, width: width, height: height };
+    let mut dest = vec![0u8; width as usize * height as usize * 4];
+    match texture.read_into(&rect, &mut dest, ClientFormat::U8U8U8U8) {
+        Err(ReadError::RectOutOfBounds) => (),
+        r => panic!("{:?}", r)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_read_rect_partially_outside() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![
+        vec![(0u8, 1u8, 2u8), (4u8, 8u8, 16u8)],
+        vec![(32u8, 64u8, 128u8), (32u8, 16u8, 4u8)],
+    ]).unwrap();
+
+    // 3x3 rect centered on the bottom-left texel
+    let rect = glium::Rect { left: -1, bottom: -1, width: 3, height: 3 };
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.main_level().first_layer()
+                                                       .into_image(None).unwrap()
+                                                       .raw_read(&rect).unwrap();
+
+    assert_eq!(read_back.len(), 2);
+    assert_eq!(read_back[0], vec![(0, 1, 2, 255), (4, 8, 16, 255)]);
+    assert_eq!(read_back[1], vec![(32, 64, 128, 255), (32, 16, 4, 255)]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_read_rect() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![
+        vec![(0u8, 1u8, 2u8), (4u8, 8u8, 16u8)],
+        vec![(32u8, 64u8, 128u8), (32u8, 16u8, 4u8)],
+    ]).unwrap();
+
+    let rect = glium::Rect::from((1, 0, 1, 2));
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.main_level().first_layer()
+                                                       .into_image(None).unwrap()
+                                                       .raw_read(&rect).unwrap();
+
+    assert_eq!(read_back.len(), 2);
+    assert_eq!(read_back[0], vec![(4, 8, 16, 255)]);
+    assert_eq!(read_back[1], vec![(32, 16, 4, 255)]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_from_bgra() {
+    let display = support::build_display();
+
+    let image = glium::texture::RawImage2d {
+        data: std::borrow::Cow::Owned(vec![2u8, 1, 0, 255, 16, 8, 4, 255]),
+        width: 2,
+        height: 1,
+        format: glium::texture::ClientFormat::U8U8U8U8,
+    };
+
+    let texture = glium::texture::Texture2d::from_bgra(&display, image).unwrap();
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+
+    assert_eq!(read_back[0][0], (0, 1, 2, 255));
+    assert_eq!(read_back[0][1], (4, 8, 16, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn raw_image_2d_sub_image() {
+    let image = glium::texture::RawImage2d {
+        data: std::borrow::Cow::Owned(vec![1u8, 2, 3, 4, 5, 6, 7, 8, 9]),
+        width: 3,
+        height: 3,
+        format: glium::texture::ClientFormat::U8,
+    };
+
+    let rows = image.sub_image(&glium::Rect { left: 0, bottom: 1, width: 3, height: 2 });
+    match rows.data {
+        std::borrow::Cow::Borrowed(data) => assert_eq!(data, &[4, 5, 6, 7, 8, 9]),
+        std::borrow::Cow::Owned(_) => panic!(),
+    }
+
+    let rect = image.sub_image(&glium::Rect { left: 1, bottom: 1, width: 2, height: 2 });
+    assert_eq!((rect.width, rect.height), (2, 2));
+    assert_eq!(&*rect.data, &[5, 6, 8, 9]);
+}
+
+#[test]
+fn raw_image_2d_flip_vertical() {
+    let image = glium::texture::RawImage2d {
+        data: std::borrow::Cow::Owned(vec![1u8, 2, 3, 4, 5, 6]),
+        width: 1,
+        height: 3,
+        format: glium::texture::ClientFormat::U8U8,
+    };
+
+    let flipped = image.flip_vertical();
+    assert_eq!(&*flipped.data, &[5, 6, 3, 4, 1, 2]);
+}
+
+#[test]
+fn raw_image_2d_to_rgba() {
+    let image = glium::texture::RawImage2d {
+        data: std::borrow::Cow::Owned(vec![1u8, 2, 3, 4, 5, 6]),
+        width: 2,
+        height: 1,
+        format: glium::texture::ClientFormat::U8U8U8,
+    };
+
+    let rgba = image.to_rgba();
+    assert_eq!(rgba.format, glium::texture::ClientFormat::U8U8U8U8);
+    assert_eq!(&*rgba.data, &[1, 2, 3, 255, 4, 5, 6, 255]);
+
+    let bgra = rgba.bgra_to_rgba();
+    assert_eq!(&*bgra.data, &[3, 2, 1, 255, 6, 5, 4, 255]);
+}
+
+#[test]
+fn raw_image_2d_from_raw_with_stride() {
+    let data = [1u8, 2, 0, 0, 3, 4, 0, 0];
+    let image = glium::texture::RawImage2d::from_raw_with_stride(&data, 2, 2, 4,
+                                                     glium::texture::ClientFormat::U8);
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_eq!(&*image.data, &[1, 2, 3, 4]);
+}
+
 macro_rules! read_texture_test {
     ($test_name:ident, $tex_ty:ident, $data_ty:ty, $data:expr) => (
         #[test]
@@ -134,3 +342,452 @@ read_texture_test!(read_unsignedtexture2darray, UnsignedTexture2dArray, (u8, u8,
 read_texture_test!(read_unsignedtexture3d, UnsignedTexture3d, (u8, u8, u8, u8),
     vec![vec![vec![(0, 1, 2, 3), (4, 5, 6, 7)], vec![(8, 9, 10, 11), (12, 13, 14, 15)]]]);*/
 // TODO: srgb textures
+
+#[test]
+fn texture_2d_read_u16() {
+    let display = support::build_display();
+
+    let data = vec![
+        vec![(0x8000u16, 0x0001u16, 0xffffu16, 0x1234u16), (0x7fff, 0xfffe, 0x0000, 0x4321)],
+        vec![(0x0101, 0x1010, 0xabcd, 0xdcba), (0x8001, 0x0002, 0xfff0, 0x000f)],
+    ];
+
+    let texture = match glium::texture::Texture2d::with_format(&display, data.clone(),
+                                        glium::texture::UncompressedFloatFormat::U16U16U16U16,
+                                        glium::texture::MipmapsOption::NoMipmap)
+    {
+        Ok(t) => t,
+        Err(glium::texture::TextureCreationError::Norm16NotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let read_back: Vec<Vec<(u16, u16, u16, u16)>> = texture.read_u16();
+    assert_eq!(read_back, data);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_read_float_unclamped() {
+    let display = support::build_display();
+
+    // reading floating-point values isn't supported by OpenGL ES
+    if let glium::Version(glium::Api::GlEs, _, _) = *display.get_opengl_version() {
+        return;
+    }
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::F32F32F32F32,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              16, 16).unwrap();
+
+    let mut framebuffer = match glium::framebuffer::SimpleFrameBuffer::new(&display, &texture) {
+        Ok(f) => f,
+        Err(glium::framebuffer::ValidationError::FloatColorAttachmentNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let (vb, ib, _) = support::build_fullscreen_red_pipeline(&display);
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                out vec4 color;
+
+                void main() {
+                    color = vec4(3.5, -1.0, 0.25, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    framebuffer.draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(f32, f32, f32, f32)>> = texture.read_float();
+    assert_eq!(data[0][0], (3.5, -1.0, 0.25, 1.0));
+    assert_eq!(data[15][15], (3.5, -1.0, 0.25, 1.0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_read_mipmap_level() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_with_mipmaps(&display,
+                                             glium::texture::MipmapsOption::EmptyMipmaps,
+                                             4, 4).unwrap();
+    assert_eq!(texture.get_mipmap_levels(), 3);
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 2 };
+    texture.mipmap(1).unwrap().write(rect, vec![
+        vec![(255u8, 0u8, 0u8, 255u8), (0u8, 255u8, 0u8, 255u8)],
+        vec![(0u8, 0u8, 255u8, 255u8), (255u8, 255u8, 255u8, 255u8)],
+    ]);
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read_image(1, 0, None).unwrap();
+
+    assert_eq!(read_back.len(), 2);
+    assert_eq!(read_back[0], vec![(255, 0, 0, 255), (0, 255, 0, 255)]);
+    assert_eq!(read_back[1], vec![(0, 0, 255, 255), (255, 255, 255, 255)]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_array_read_layer() {
+    let display = support::build_display();
+
+    let texture = match glium::texture::Texture2dArray::empty(&display, 4, 4, 3) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    for layer in 0 .. 3 {
+        let image = texture.main_level().layer(layer).unwrap();
+        let framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, image).unwrap();
+        let value = layer as f32 / 2.0;
+        framebuffer.clear_color(value, 0.0, 0.0, 1.0);
+    }
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read_image(0, 2, None).unwrap();
+    assert_eq!(read_back.len(), 4);
+    assert_eq!(read_back[0][0], (255, 0, 0, 255));
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read_image(0, 0, None).unwrap();
+    assert_eq!(read_back[3][3], (0, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn cubemap_read_face() {
+    let display = support::build_display();
+
+    let texture = match glium::texture::Cubemap::empty(&display, 4) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let layer = texture.main_level().first_layer();
+    let framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display,
+                                layer.image(glium::texture::CubeLayer::PositiveX)).unwrap();
+    framebuffer.clear_color(0.0, 0.0, 0.0, 1.0);
+    let framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display,
+                                layer.image(glium::texture::CubeLayer::NegativeY)).unwrap();
+    framebuffer.clear_color(0.0, 1.0, 0.0, 1.0);
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> =
+        texture.read_image(0, 0, Some(glium::texture::CubeLayer::NegativeY)).unwrap();
+    assert_eq!(read_back[0][0], (0, 255, 0, 255));
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> =
+        texture.read_image(0, 0, Some(glium::texture::CubeLayer::PositiveX)).unwrap();
+    assert_eq!(read_back[0][0], (0, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_read_image_out_of_range() {
+    use glium::texture::ReadImageError;
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_with_mipmaps(&display,
+                                             glium::texture::MipmapsOption::EmptyMipmaps,
+                                             4, 4).unwrap();
+
+    let result: Result<Vec<Vec<(u8, u8, u8, u8)>>, _> = texture.read_image(3, 0, None);
+    assert_eq!(result.err(), Some(ReadImageError::LevelOutOfRange { level: 3, levels: 3 }));
+
+    let result: Result<Vec<Vec<(u8, u8, u8, u8)>>, _> = texture.read_image(0, 1, None);
+    assert_eq!(result.err(), Some(ReadImageError::LayerOutOfRange { layer: 1, layers: 1 }));
+
+    let face = Some(glium::texture::CubeLayer::PositiveZ);
+    let result: Result<Vec<Vec<(u8, u8, u8, u8)>>, _> = texture.read_image(0, 0, face);
+    assert_eq!(result.err(), Some(ReadImageError::NotCubemap));
+
+    display.assert_no_error(None);
+}
+
+/// Returns the bytes of a slice of pixels, in memory order.
+fn pixels_as_bytes<T>(pixels: &[T]) -> &[u8] {
+    unsafe {
+        std::slice::from_raw_parts(pixels.as_ptr() as *const u8,
+                                   pixels.len() * std::mem::size_of::<T>())
+    }
+}
+
+#[test]
+fn texture_2d_array_pixels_layout() {
+    use glium::texture::ClientFormat;
+
+    let display = support::build_display();
+
+    let data = vec![
+        vec![[0u8, 1, 2, 3], [4, 8, 16, 32]],
+        vec![[64u8, 128, 255, 254], [253, 127, 63, 31]],
+    ];
+
+    let texture = glium::texture::Texture2d::new(&display, data.clone()).unwrap();
+
+    let read_back: Vec<Vec<[u8; 4]>> = texture.read_image(0, 0, None).unwrap();
+    assert_eq!(read_back, data);
+
+    // `glReadPixels` must produce the same bytes as the arrays in memory
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 2 };
+    let mut dest = vec![0u8; 2 * 2 * 4];
+    texture.read_into(&rect, &mut dest, ClientFormat::U8U8U8U8).unwrap();
+    let expected = data.iter().flat_map(|row| row.iter().cloned()).collect::<Vec<_>>();
+    assert_eq!(&dest[..], pixels_as_bytes(&expected));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_packed_rgba8_layout() {
+    use glium::texture::{ClientFormat, PackedRgba8};
+
+    let display = support::build_display();
+
+    let data = vec![
+        vec![PackedRgba8::from([255u8, 0, 0, 255]), PackedRgba8::from((1u8, 2u8, 4u8, 8u8))],
+        vec![PackedRgba8::from([0u8, 255, 0, 128]), PackedRgba8::from((16u8, 32u8, 64u8, 0u8))],
+    ];
+
+    let texture = glium::texture::Texture2d::new(&display, data.clone()).unwrap();
+
+    let read_back: Vec<Vec<PackedRgba8>> = texture.read_image(0, 0, None).unwrap();
+    assert_eq!(read_back, data);
+    assert_eq!(<[u8; 4]>::from(read_back[0][1]), [1, 2, 4, 8]);
+    assert_eq!(<(u8, u8, u8, u8)>::from(read_back[1][0]), (0, 255, 0, 128));
+
+    // the red component is the first byte in memory, whatever the endianness
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 2 };
+    let mut dest = vec![0u8; 2 * 2 * 4];
+    texture.read_into(&rect, &mut dest, ClientFormat::U8U8U8U8).unwrap();
+    assert_eq!(&dest[..], &[255, 0, 0, 255, 1, 2, 4, 8,
+                            0, 255, 0, 128, 16, 32, 64, 0][..]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_r16_layout() {
+    use glium::texture::ClientFormat;
+
+    let display = support::build_display();
+
+    let data = vec![vec![0x1234u16, 0xffff], vec![0x0001, 0x8000]];
+
+    let texture = match glium::texture::Texture2d::with_format(&display, data.clone(),
+                                        glium::texture::UncompressedFloatFormat::U16,
+                                        glium::texture::MipmapsOption::NoMipmap)
+    {
+        Ok(t) => t,
+        Err(glium::texture::TextureCreationError::Norm16NotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let read_back: Vec<Vec<u16>> = match texture.read_image(0, 0, None) {
+        Ok(r) => r,
+        Err(glium::texture::ReadImageError::ReadError(
+            glium::framebuffer::ReadError::OutputFormatNotSupported)) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+    assert_eq!(read_back, data);
+
+    let read_back: Vec<Vec<[u16; 1]>> = texture.read_image(0, 0, None).unwrap();
+    assert_eq!(read_back, vec![vec![[0x1234], [0xffff]], vec![[0x0001], [0x8000]]]);
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 2 };
+    let mut dest = vec![0u8; 2 * 2 * 2];
+    texture.read_into(&rect, &mut dest, ClientFormat::U16).unwrap();
+    assert_eq!(&dest[..], pixels_as_bytes(&[0x1234u16, 0xffff, 0x0001, 0x8000]));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_r32f_layout() {
+    use glium::texture::ClientFormat;
+
+    let display = support::build_display();
+
+    // reading floating-point values isn't supported by OpenGL ES
+    if let glium::Version(glium::Api::GlEs, _, _) = *display.get_opengl_version() {
+        return;
+    }
+
+    let data = vec![vec![(0.5f32,), (-3.25,)], vec![(1024.0,), (0.0,)]];
+
+    let texture = match glium::texture::Texture2d::with_format(&display, data.clone(),
+                                        glium::texture::UncompressedFloatFormat::F32,
+                                        glium::texture::MipmapsOption::NoMipmap)
+    {
+        Ok(t) => t,
+        Err(glium::texture::TextureCreationError::FormatNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let read_back: Vec<Vec<(f32,)>> = texture.read_image(0, 0, None).unwrap();
+    assert_eq!(read_back, data);
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 2 };
+    let mut dest = vec![0u8; 2 * 2 * 4];
+    texture.read_into(&rect, &mut dest, ClientFormat::F32).unwrap();
+    assert_eq!(&dest[..], pixels_as_bytes(&[0.5f32, -3.25, 1024.0, 0.0]));
+
+    display.assert_no_error(None);
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn texture_2d_rg16f_layout() {
+    use glium::f16;
+    use glium::texture::ClientFormat;
+
+    let display = support::build_display();
+
+    // reading floating-point values isn't supported by OpenGL ES
+    if let glium::Version(glium::Api::GlEs, _, _) = *display.get_opengl_version() {
+        return;
+    }
+
+    let pixel = |r: f32, g: f32| [f16::from_f32(r), f16::from_f32(g)];
+    let data = vec![
+        vec![pixel(0.5, -2.0), pixel(1.0, 65504.0)],
+        vec![pixel(0.0, 0.25), pixel(-0.125, 3.0)],
+    ];
+
+    let texture = match glium::texture::Texture2d::with_format(&display, data.clone(),
+                                        glium::texture::UncompressedFloatFormat::F16F16,
+                                        glium::texture::MipmapsOption::NoMipmap)
+    {
+        Ok(t) => t,
+        Err(glium::texture::TextureCreationError::FormatNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let read_back: Vec<Vec<[f16; 2]>> = texture.read_image(0, 0, None).unwrap();
+    assert_eq!(read_back, data);
+
+    let rect = glium::Rect { left: 0, bottom: 0, width: 2, height: 2 };
+    let mut dest = vec![0u8; 2 * 2 * 4];
+    texture.read_into(&rect, &mut dest, ClientFormat::F16F16).unwrap();
+    let expected = data.iter().flat_map(|row| row.iter().cloned()).collect::<Vec<_>>();
+    assert_eq!(&dest[..], pixels_as_bytes(&expected));
+
+    // the bits are the ones of `GL_HALF_FLOAT`
+    assert_eq!(&dest[.. 4], pixels_as_bytes(&[0x3800u16, 0xc000]));
+
+    display.assert_no_error(None);
+}
+
+/// Builds a 8x8 texture compressed with DXT1, or returns `None` if the format isn't supported.
+fn build_dxt1_texture(display: &glium::Display, data: &[u8])
+                      -> Option<glium::texture::CompressedTexture2d>
+{
+    match glium::texture::CompressedTexture2d::with_compressed_data(display, data, 8, 8,
+                                        glium::texture::CompressedFormat::S3tcDxt1NoAlpha,
+                                        glium::texture::CompressedMipmapsOption::NoMipmap)
+    {
+        Ok(t) => Some(t),
+        Err(glium::texture::TextureCreationError::FormatNotSupported) => None,
+        Err(e) => panic!("{:?}", e)
+    }
+}
+
+/// Four DXT1 blocks with red and blue as reference colors.
+fn dxt1_blocks(indices: u8) -> Vec<u8> {
+    (0 .. 4u8).flat_map(|n| {
+        vec![0x00, 0xf8, 0x1f, 0x00, indices, indices ^ n, indices.rotate_left(n as u32), n]
+    }).collect()
+}
+
+#[test]
+fn compressed_texture_2d_read_level() {
+    let display = support::build_display();
+
+    let data = dxt1_blocks(0x1b);
+    let texture = match build_dxt1_texture(&display, &data) {
+        Some(t) => t,
+        None => return
+    };
+
+    if let glium::Version(glium::Api::GlEs, _, _) = *display.get_opengl_version() {
+        assert_eq!(texture.read_compressed_level(0).unwrap_err(),
+                   glium::texture::CompressedLevelError::NotSupported);
+        return;
+    }
+
+    let (format, read_back) = texture.read_compressed_level(0).unwrap();
+    assert_eq!(format, glium::texture::CompressedFormat::S3tcDxt1NoAlpha);
+    assert_eq!(read_back, data);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn compressed_texture_2d_write_level() {
+    let display = support::build_display();
+
+    let texture = match build_dxt1_texture(&display, &dxt1_blocks(0x1b)) {
+        Some(t) => t,
+        None => return
+    };
+
+    let data = dxt1_blocks(0xe4);
+    texture.write_compressed_level(0, glium::texture::CompressedFormat::S3tcDxt1NoAlpha,
+                                   &data).unwrap();
+
+    if let glium::Version(glium::Api::Gl, _, _) = *display.get_opengl_version() {
+        let (_, read_back) = texture.read_compressed_level(0).unwrap();
+        assert_eq!(read_back, data);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn compressed_texture_2d_write_level_errors() {
+    let display = support::build_display();
+
+    let texture = match build_dxt1_texture(&display, &dxt1_blocks(0x1b)) {
+        Some(t) => t,
+        None => return
+    };
+
+    let format = glium::texture::CompressedFormat::S3tcDxt1NoAlpha;
+
+    match texture.write_compressed_level(1, format, &dxt1_blocks(0)) {
+        Err(glium::texture::CompressedLevelError::LevelOutOfRange { level: 1, levels: 1 }) => (),
+        e => panic!("{:?}", e)
+    };
+
+    match texture.write_compressed_level(0, format, &[0; 16]) {
+        Err(glium::texture::CompressedLevelError::DataLengthMismatch { expected: 32,
+                                                                       provided: 16 }) => (),
+        e => panic!("{:?}", e)
+    };
+
+    match texture.write_compressed_level(0, glium::texture::CompressedFormat::S3tcDxt5Alpha,
+                                         &[0; 64])
+    {
+        Err(glium::texture::CompressedLevelError::FormatMismatch) => (),
+        e => panic!("{:?}", e)
+    };
+
+    display.assert_no_error(None);
+}
@@ -130,3 +130,238 @@ fn bindless_texture() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn mipmaps_regenerated_after_render() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::AutoGeneratedAfterRender,
+                                              8, 8).unwrap();
+
+    // the bias makes the sampler read the smallest mipmap level
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D texture;
+
+            void main() {
+                gl_FragColor = texture2D(texture, vec2(0.5, 0.5), 16.0);
+            }
+        ",
+        None).unwrap();
+
+    let output = support::build_renderable_texture(&display);
+
+    for &((r, g, b, a), expected) in [((1.0, 0.0, 0.0, 1.0), (255, 0, 0, 255)),
+                                      ((0.0, 1.0, 0.0, 1.0), (0, 255, 0, 255))].iter()
+    {
+        texture.as_surface().clear_color(r, g, b, a);
+
+        let sampler = texture.sampled()
+                             .minify_filter(glium::uniforms::MinifySamplerFilter::NearestMipmapNearest);
+        output.as_surface().draw(&vb, &ib, &program, &uniform!{ texture: sampler },
+                                 &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+        assert_eq!(data[0][0], expected);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn norm16_sampling_precision() {
+    let display = support::build_display();
+
+    // reading floating-point values isn't supported by OpenGL ES
+    if let glium::Version(glium::Api::GlEs, _, _) = *display.get_opengl_version() {
+        return;
+    }
+
+    let data = vec![vec![0x8000u16, 0x8000], vec![0x8000, 0x8000]];
+    let texture = match glium::texture::Texture2d::with_format(&display, data,
+                                                glium::texture::UncompressedFloatFormat::U16,
+                                                glium::texture::MipmapsOption::NoMipmap)
+    {
+        Ok(t) => t,
+        Err(glium::texture::TextureCreationError::Norm16NotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let output = glium::Texture2d::empty_with_format(&display,
+                                             glium::texture::UncompressedFloatFormat::F32F32F32F32,
+                                             glium::texture::MipmapsOption::NoMipmap,
+                                             2, 2).unwrap();
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D texture;
+
+            void main() {
+                gl_FragColor = texture2D(texture, vec2(0.5, 0.5));
+            }
+        ",
+        None).unwrap();
+
+    output.as_surface().draw(&vb, &ib, &program, &uniform!{ texture: &texture },
+                             &Default::default()).unwrap();
+
+    // 0x8000 / 0xffff is 0.500007..., while an 8-bits format would give 0.50196...
+    let data: Vec<Vec<(f32, f32, f32, f32)>> = output.read_float();
+    assert!((data[0][0].0 - 0.50001).abs() < 0.00005);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn eight_textures_binds() {
+    use glium::CapabilitiesSource;
+
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    if display.get_capabilities().max_combined_texture_image_units < 8 {
+        return;
+    }
+
+    let textures = (0 .. 8).map(|_| {
+        glium::texture::Texture2d::new(&display, vec![vec![(255, 255, 255, 255u8)]]).unwrap()
+    }).collect::<Vec<_>>();
+
+    // the output is white only if all the textures are bound correctly
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D t0, t1, t2, t3, t4, t5, t6, t7;
+
+            void main() {
+                vec2 c = vec2(0.5, 0.5);
+                gl_FragColor = texture2D(t0, c) * texture2D(t1, c) * texture2D(t2, c) *
+                               texture2D(t3, c) * texture2D(t4, c) * texture2D(t5, c) *
+                               texture2D(t6, c) * texture2D(t7, c);
+            }
+        ",
+        None).unwrap();
+
+    let uniforms = uniform!{
+        t0: &textures[0], t1: &textures[1], t2: &textures[2], t3: &textures[3],
+        t4: &textures[4], t5: &textures[5], t6: &textures[6], t7: &textures[7],
+    };
+
+    let output = support::build_renderable_texture(&display);
+    output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    display.set_frame_statistics_enabled(true);
+    output.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+    let binds = display.get_frame_statistics().texture_binds;
+
+    // with `glBindTextures` all the textures are bound with a single call
+    if display.get_opengl_version() >= &glium::Version(glium::Api::Gl, 4, 4) ||
+       display.get_extensions().gl_arb_multi_bind
+    {
+        assert_eq!(binds, 1);
+    } else {
+        assert!(binds > 1);
+    }
+
+    // drawing again doesn't bind anything
+    output.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+    assert_eq!(display.get_frame_statistics().texture_binds, binds);
+    display.set_frame_statistics_enabled(false);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    assert_eq!(data[0][0], (255, 255, 255, 255));
+    assert_eq!(data[512][512], (255, 255, 255, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn resident_level_range() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::EmptyMipmaps,
+                                              8, 8).unwrap();
+    assert_eq!(texture.get_mipmap_levels(), 4);
+    assert_eq!(texture.get_resident_level_range(), (0, 3));
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D texture;
+
+            void main() {
+                gl_FragColor = texture2D(texture, vec2(0.5, 0.5));
+            }
+        ",
+        None).unwrap();
+
+    let output = support::build_renderable_texture(&display);
+
+    // only the smallest levels are uploaded, starting with the smallest one
+    for &(level, size, expected) in [(3, 1, (255, 0, 0, 255)), (2, 2, (0, 255, 0, 255))].iter() {
+        let data = vec![vec![expected; size as usize]; size as usize];
+        texture.mipmap(level).unwrap().write(glium::Rect { left: 0, bottom: 0,
+                                                           width: size, height: size }, data);
+        texture.set_resident_level_range(level, 3);
+
+        let sampler = texture.sampled()
+                             .minify_filter(glium::uniforms::MinifySamplerFilter::NearestMipmapNearest)
+                             .magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest);
+        output.as_surface().draw(&vb, &ib, &program, &uniform!{ texture: sampler },
+                                 &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+        assert_eq!(data[0][0], expected);
+    }
+
+    display.assert_no_error(None);
+}
@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate glium;
 
+use glium::Surface;
+
 mod support;
 
 #[test]
@@ -24,3 +26,256 @@ fn texture_2d_write() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn texture_2d_write_rows() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              3, 3).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    // the odd width checks that the rows are not aligned to four bytes
+    let rows = (0 .. 2u8).map(|y| vec![(y, 1u8, 2u8), (y, 3u8, 4u8), (y, 5u8, 6u8)]);
+    texture.write_rows(glium::Rect { left: 0, bottom: 1, width: 3, height: 2 }, rows).unwrap();
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(read_back[0][0], (0, 0, 0, 0));
+    assert_eq!(read_back[1][0], (0, 1, 2, 255));
+    assert_eq!(read_back[1][2], (0, 5, 6, 255));
+    assert_eq!(read_back[2][1], (1, 3, 4, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_write_rows_not_enough_rows() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty(&display, 2, 2).unwrap();
+
+    let rows = vec![vec![(0u8, 0u8, 0u8, 0u8), (0u8, 0u8, 0u8, 0u8)]];
+    match texture.write_rows(glium::Rect { left: 0, bottom: 0, width: 2, height: 2 }, rows) {
+        Err(glium::texture::WriteRowsError::NotEnoughRows { expected: 2, provided: 1 }) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_streaming_write() {
+    use std::time::Duration;
+
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                uniform sampler2D tex;
+                out vec4 color;
+
+                void main() {
+                    color = texture(tex, vec2(0.5, 0.5));
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                uniform sampler2D tex;
+
+                void main() {
+                    gl_FragColor = texture2D(tex, vec2(0.5, 0.5));
+                }
+            "
+        },
+    ).unwrap();
+
+    // 2 MB of data, which is uploaded in two chunks
+    let texture = glium::texture::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              1024, 512).unwrap();
+    let data = (0 .. 512u32).flat_map(|y| (0 .. 1024u32).map(move |x| {
+        (x as u8, y as u8, (x / 256) as u8, 255u8)
+    })).collect::<Vec<_>>();
+
+    let output = support::build_renderable_texture(&display);
+
+    {
+        let mut write = texture.begin_streaming_write(data).unwrap();
+        assert!(!texture.is_complete());
+        assert_eq!(write.get_progress(), (0, 2 * 1024 * 1024));
+
+        assert_eq!(write.advance(Duration::new(0, 0)), glium::StreamingWriteStatus::InProgress);
+        assert_eq!(write.get_progress(), (1024 * 1024, 2 * 1024 * 1024));
+
+        match output.as_surface().draw(&vb, &ib, &program, &uniform!{ tex: &texture },
+                                       &Default::default())
+        {
+            Err(glium::DrawError::IncompleteTexture { reason, .. }) => {
+                assert_eq!(reason, glium::uniforms::IncompleteTextureReason::IncompleteUpload);
+            },
+            r => panic!("{:?}", r)
+        };
+
+        while write.advance(Duration::new(0, 0)) != glium::StreamingWriteStatus::Done {}
+        assert_eq!(write.get_status(), glium::StreamingWriteStatus::Done);
+    }
+
+    assert!(texture.is_complete());
+    output.as_surface().draw(&vb, &ib, &program, &uniform!{ tex: &texture },
+                             &Default::default()).unwrap();
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(read_back[0][0], (0, 0, 0, 255));
+    assert_eq!(read_back[255][300], (44, 255, 1, 255));
+    assert_eq!(read_back[256][1023], (255, 0, 3, 255));
+    assert_eq!(read_back[511][513], (1, 255, 2, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_streaming_write_dropped() {
+    use std::time::Duration;
+
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::NoMipmap,
+                                              1024, 512).unwrap();
+    let data = vec![(0u8, 0u8, 0u8, 0u8); 1024 * 512];
+
+    {
+        let mut write = texture.begin_streaming_write(data.clone()).unwrap();
+        write.advance(Duration::new(0, 0));
+    }
+
+    // the texture stays incomplete until a streaming write is done
+    assert!(!texture.is_complete());
+
+    let mut write = texture.begin_streaming_write(data).unwrap();
+    while write.advance(Duration::from_millis(100)) != glium::StreamingWriteStatus::Done {}
+    assert!(texture.is_complete());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_2d_array_streaming_write() {
+    use std::time::Duration;
+
+    let display = support::build_display();
+
+    let texture = match glium::texture::Texture2dArray::empty(&display, 4, 4, 3) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    // the layers are consecutive in the data
+    let data = (0 .. 3u8).flat_map(|layer| (0 .. 16u8).map(move |i| (layer, i, 0u8, 255u8)))
+                         .collect::<Vec<_>>();
+
+    let mut write = texture.begin_streaming_write(data).unwrap();
+    assert_eq!(write.advance(Duration::new(0, 0)), glium::StreamingWriteStatus::Done);
+    assert!(texture.is_complete());
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read_image(0, 0, None).unwrap();
+    assert_eq!(read_back[0][0], (0, 0, 0, 255));
+    assert_eq!(read_back[3][3], (0, 15, 0, 255));
+
+    let read_back: Vec<Vec<(u8, u8, u8, u8)>> = texture.read_image(0, 2, None).unwrap();
+    assert_eq!(read_back[1][2], (2, 6, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn pixel_store_isolation() {
+    use std::mem;
+
+    let display = support::build_display();
+
+    // uploads and reads back an RGB texture, whose rows aren't a multiple of 4 bytes if the
+    // width is odd
+    let check = |width: u32| {
+        let data = (0 .. 3).map(|y| {
+            (0 .. width).map(|x| {
+                let v = (x * 3 + y * 3 * width) as u8;
+                (v, v.wrapping_add(1), v.wrapping_add(2))
+            }).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        let texture = glium::Texture2d::with_format(&display, data.clone(),
+                                            glium::texture::UncompressedFloatFormat::U8U8U8,
+                                            glium::texture::MipmapsOption::NoMipmap).unwrap();
+        texture.write(glium::Rect { left: 0, bottom: 1, width: width, height: 1 },
+                      vec![data[1].clone()]);
+
+        let read_back: Vec<Vec<(u8, u8, u8)>> = texture.read_image(0, 0, None).unwrap();
+        assert_eq!(read_back, data);
+    };
+
+    check(3);
+    check(4);
+
+    // an external library that expects the default values, then leaves its own values
+    unsafe {
+        display.exec_in_context(|data| {
+            let get = data.get_proc_address("glGetIntegerv");
+            let store = data.get_proc_address("glPixelStorei");
+            assert!(!get.is_null());
+            assert!(!store.is_null());
+
+            let get: extern "system" fn(u32, *mut i32) = mem::transmute(get);
+            let store: extern "system" fn(u32, i32) = mem::transmute(store);
+
+            let mut value = 0;
+            get(0x0CF5, &mut value);     // GL_UNPACK_ALIGNMENT
+            assert_eq!(value, 4);
+            get(0x0D05, &mut value);     // GL_PACK_ALIGNMENT
+            assert_eq!(value, 4);
+
+            store(0x0CF5, 8);            // GL_UNPACK_ALIGNMENT
+            store(0x0CF2, 16);           // GL_UNPACK_ROW_LENGTH
+            store(0x0CF3, 1);            // GL_UNPACK_SKIP_ROWS
+            store(0x0CF4, 2);            // GL_UNPACK_SKIP_PIXELS
+            store(0x0D05, 8);            // GL_PACK_ALIGNMENT
+            store(0x0D02, 16);           // GL_PACK_ROW_LENGTH
+            store(0x0D03, 1);            // GL_PACK_SKIP_ROWS
+            store(0x0D04, 2);            // GL_PACK_SKIP_PIXELS
+        });
+    }
+
+    check(5);
+    check(2);
+
+    display.assert_no_error(None);
+}
@@ -338,3 +338,390 @@ fn empty_uniform_buffer() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn block_buffer_slice() {
+    let display = support::build_display();
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+            uniform layout(std140);
+
+            uniform MyBlock {
+                vec4 color;
+            };
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    #[derive(Copy, Clone)]
+    struct Data {
+        color: [f32; 4],
+    }
+
+    implement_uniform_block!(Data, color);
+
+    let stride = display.align_uniform_buffer_size(std::mem::size_of::<Data>()) /
+                 std::mem::size_of::<Data>();
+
+    let mut data = vec![Data { color: [0.0, 0.0, 0.0, 0.0] }; 2 * stride];
+    data[0].color = [1.0, 0.0, 0.0, 1.0];
+    data[stride].color = [0.0, 1.0, 0.0, 1.0];
+
+    let buffer = match glium::buffer::Buffer::new(&display, &data[..],
+                                                  glium::buffer::BufferType::UniformBuffer,
+                                                  glium::buffer::BufferMode::Default)
+    {
+        Err(_) => return,
+        Ok(b) => b
+    };
+
+    let texture = support::build_renderable_texture(&display);
+
+    for &(index, expected) in &[(0, (255, 0, 0, 255)), (stride, (0, 255, 0, 255)),
+                                (0, (255, 0, 0, 255))]
+    {
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw(&vb, &ib, &program, &uniform!{
+            MyBlock: buffer.as_slice().get(index).unwrap()
+        }, &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(data[0][0], expected);
+        assert_eq!(data.last().unwrap().last().unwrap(), &expected);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn block_buffer_slice_unaligned() {
+    let display = support::build_display();
+
+    if display.get_uniform_buffer_offset_alignment() <= 16 {
+        return;
+    }
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+            uniform layout(std140);
+
+            uniform MyBlock {
+                vec4 color;
+            };
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    #[derive(Copy, Clone)]
+    struct Data {
+        color: [f32; 4],
+    }
+
+    implement_uniform_block!(Data, color);
+
+    let data = vec![Data { color: [0.0, 0.0, 0.0, 0.0] }; 2];
+    let buffer = match glium::buffer::Buffer::new(&display, &data[..],
+                                                  glium::buffer::BufferType::UniformBuffer,
+                                                  glium::buffer::BufferMode::Default)
+    {
+        Err(_) => return,
+        Ok(b) => b
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    let res = texture.as_surface().draw(&vb, &ib, &program, &uniform!{
+        MyBlock: buffer.as_slice().get(1).unwrap()
+    }, &Default::default());
+
+    match res {
+        Err(glium::DrawError::UniformBufferOffsetNotAligned { .. }) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn global_block() {
+    let display = support::build_display();
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+            uniform layout(std140);
+
+            uniform MyBlock {
+                vec3 color;
+            };
+
+            void main() {
+                gl_FragColor = vec4(color, 1.0);
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    #[derive(Copy, Clone)]
+    struct Data {
+        color: (f32, f32, f32),
+    }
+
+    implement_uniform_block!(Data, color);
+
+    let global = match glium::uniforms::UniformBuffer::new(&display, Data { color: (1.0f32, 1.0f32, 0.0f32) }) {
+        Err(_) => return,
+        Ok(b) => b
+    };
+
+    let local = glium::uniforms::UniformBuffer::new(&display, Data { color: (0.0f32, 0.0f32, 1.0f32) }).unwrap();
+
+    match display.set_global_uniform_block("MyBlock", &global) {
+        Err(glium::uniforms::GlobalUniformBlockError::NotSupported) => return,
+        r => r.unwrap()
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 255, 0, 255));
+        }
+    }
+
+    // a block passed to the draw command takes precedence over the global block
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{ MyBlock: &local },
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 0, 255, 255));
+        }
+    }
+
+    assert!(display.remove_global_uniform_block("MyBlock"));
+
+    display.assert_no_error(None);
+}
+
+/// Builds a program whose fragment shader declares `block` as the `glium_DrawUniforms` block
+/// and writes `output`. Returns `None` if the program isn't supported.
+fn build_draw_block_program(display: &glium::Display, block: &str, output: &str)
+                            -> Option<glium::Program>
+{
+    let fragment = format!("
+            #version 140
+
+            layout(std140) uniform glium_DrawUniforms {{
+                {}
+            }};
+
+            out vec4 f_color;
+
+            void main() {{
+                f_color = {};
+            }}
+        ", block, output);
+
+    glium::Program::from_source(display,
+        "
+            #version 140
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        &fragment, None).ok()
+}
+
+#[test]
+fn draw_block_vec3_alignment() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    // `scalar` is stored in the padding of `first`, and `second` starts at offset 16
+    let program = match build_draw_block_program(&display,
+                                                 "vec3 first; float scalar; vec3 second;",
+                                                 "vec4(first.x, scalar, second.z, 1.0)")
+    {
+        Some(p) => p,
+        None => return
+    };
+
+    let uniforms = uniform! {
+        first: [1.0, 0.0, 0.0f32],
+        scalar: 1.0f32,
+        second: [0.0, 0.0, 1.0f32],
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 255, 255, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_block_matrices_alignment() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    // each column of a matrix is aligned like a `vec4`
+    let program = match build_draw_block_program(&display,
+                                                 "float first; mat3 m3; mat2 m2; mat4 m4;",
+                                                 "vec4(m3[2][1], m2[1][0], m4[3][2], first)")
+    {
+        Some(p) => p,
+        None => return
+    };
+
+    let uniforms = uniform! {
+        first: 1.0f32,
+        m3: [[0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0f32]],
+        m2: [[0.0, 0.0], [1.0, 0.0f32]],
+        m4: [
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0f32],
+        ],
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 255, 255, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_block_type_mismatch() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = match build_draw_block_program(&display, "vec3 color;",
+                                                 "vec4(color, 1.0)")
+    {
+        Some(p) => p,
+        None => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw(&vb, &ib, &program, &uniform!{ color: 1.0f32 },
+                                    &Default::default())
+    {
+        Err(glium::DrawError::UniformTypeMismatch { .. }) => (),
+        a => panic!("{:?}", a)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_block_many_draws() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = match build_draw_block_program(&display, "vec3 color;",
+                                                 "vec4(color, 1.0)")
+    {
+        Some(p) => p,
+        None => return
+    };
+
+    // enough draws for the ring buffer to wrap around several times
+    let texture = support::build_renderable_texture(&display);
+    for i in 0 .. 5000 {
+        let color = if i % 2 == 0 { [1.0, 0.0, 0.0f32] } else { [0.0, 1.0, 0.0f32] };
+        texture.as_surface().draw(&vb, &ib, &program, &uniform!{ color: color },
+                                  &Default::default()).unwrap();
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(0, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
@@ -358,3 +358,313 @@ uniform_test!(uniform_type_boolarr_boolvec3, "bvec3", [true, true, true]);
 uniform_test!(uniform_type_booltup_boolvec3, "bvec3", (false, false, false));
 uniform_test!(uniform_type_boolarr_boolvec4, "bvec4", [true, false, false, true]);
 uniform_test!(uniform_type_booltup_boolvec4, "bvec4", (false, true, true, false));
+
+#[test]
+fn uniform_matrix_array() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform mat4 matrices[3];
+
+            void main() {
+                gl_FragColor = matrices[0][0] + matrices[2][1];
+            }
+        ",
+        None).unwrap();
+
+    let zero = [[0.0f32; 4]; 4];
+    let mut first = zero;
+    first[0] = [1.0, 0.0, 0.0, 0.0];
+    let mut third = zero;
+    third[1] = [0.0, 0.0, 0.0, 1.0];
+
+    // the last element doesn't exist in the program and must be ignored
+    let matrices = vec![first, zero, third, [[1.0f32; 4]; 4]];
+    let uniforms = uniform! { matrices: matrices };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data.last().unwrap().last().unwrap(), &(255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+fn collect_signed_ints<U>(uniforms: &U) -> Vec<(String, i32)> where U: glium::uniforms::Uniforms {
+    let mut values = Vec::new();
+    uniforms.visit_values(|name, value| {
+        match value {
+            glium::uniforms::UniformValue::SignedInt(v) => values.push((name.to_owned(), v)),
+            _ => panic!()
+        }
+    });
+    values
+}
+
+// the value that is visited last is the one that ends up being used
+fn effective_value(values: &[(String, i32)], name: &str) -> Option<i32> {
+    values.iter().rev().find(|&&(ref n, _)| n == name).map(|&(_, v)| v)
+}
+
+#[test]
+fn uniform_macro_trailing_comma() {
+    let uniforms = uniform! {
+        a: 1i32,
+        b: 2i32,
+    };
+
+    let values = collect_signed_ints(&uniforms);
+    assert_eq!(values.len(), 2);
+    assert_eq!(effective_value(&values, "a"), Some(1));
+    assert_eq!(effective_value(&values, "b"), Some(2));
+}
+
+#[test]
+fn uniform_macro_dynamic_names() {
+    let name = format!("light{}", 3);
+
+    let uniforms = uniform! {
+        a: 1i32,
+        [name] => 5i32,
+        ["other"] => 6i32,
+    };
+
+    let values = collect_signed_ints(&uniforms);
+    assert_eq!(values.len(), 3);
+    assert_eq!(effective_value(&values, "a"), Some(1));
+    assert_eq!(effective_value(&values, "light3"), Some(5));
+    assert_eq!(effective_value(&values, "other"), Some(6));
+}
+
+#[test]
+fn uniform_macro_spread() {
+    let base = uniform! {
+        a: 1i32,
+        b: 2i32,
+    };
+
+    let uniforms = uniform! {
+        b: 3i32,
+        c: 4i32,
+        ..base
+    };
+
+    let values = collect_signed_ints(&uniforms);
+    assert_eq!(effective_value(&values, "a"), Some(1));
+    assert_eq!(effective_value(&values, "b"), Some(3));
+    assert_eq!(effective_value(&values, "c"), Some(4));
+}
+
+#[test]
+fn uniform_macro_spread_only() {
+    let base = uniform! { a: 1i32 };
+    let uniforms = uniform! { ..base, };
+
+    let values = collect_signed_ints(&uniforms);
+    assert_eq!(values, vec![("a".to_owned(), 1)]);
+}
+
+#[test]
+fn precomputed_uniforms() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap();
+
+    assert!(program.get_uniform_handle("unknown").is_none());
+    let handle = program.get_uniform_handle("color").unwrap();
+    assert_eq!(handle.get_name(), "color");
+
+    let red = [1.0, 0.0, 0.0, 0.5f32];
+    let blue = [0.0, 0.0, 1.0, 1.0f32];
+    let mut uniforms = glium::uniforms::PrecomputedUniforms::new(&program).add(&handle, &red);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 128));
+
+    uniforms.set(&handle, &blue);
+    texture.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 255, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn precomputed_uniforms_wrong_program() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let vertex_shader = "
+        #version 110
+
+        attribute vec2 position;
+
+        void main() {
+            gl_Position = vec4(position, 0.0, 1.0);
+        }
+    ";
+
+    let fragment_shader = "
+        #version 110
+
+        uniform vec4 color;
+
+        void main() {
+            gl_FragColor = color;
+        }
+    ";
+
+    let program1 = glium::Program::from_source(&display, vertex_shader, fragment_shader,
+                                               None).unwrap();
+    let program2 = glium::Program::from_source(&display, vertex_shader, fragment_shader,
+                                               None).unwrap();
+
+    let handle = program1.get_uniform_handle("color").unwrap();
+    let color = [1.0, 0.0, 0.0, 1.0f32];
+    let uniforms = glium::uniforms::PrecomputedUniforms::new(&program1).add(&handle, &color);
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw(&vb, &ib, &program2, &uniforms, &Default::default()) {
+        Err(glium::DrawError::UniformHandleProgramMismatch { ref name }) if name == "color" => (),
+        e => panic!("{:?}", e)
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn program_reload_from_source() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let vertex_shader = "
+        #version 110
+
+        attribute vec2 position;
+
+        void main() {
+            gl_Position = vec4(position, 0.0, 1.0);
+        }
+    ";
+
+    let program = glium::Program::from_source(&display, vertex_shader,
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap();
+
+    let old_handle = program.get_uniform_handle("color").unwrap();
+    let red = [1.0, 0.0, 0.0, 1.0f32];
+    let old_uniforms = glium::uniforms::PrecomputedUniforms::new(&program)
+                                                              .add(&old_handle, &red);
+
+    // a compilation error leaves the program untouched
+    assert!(program.reload_from_source(glium::program::SourceCode {
+        vertex_shader: vertex_shader,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+        fragment_shader: "invalid glsl",
+    }).is_err());
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &old_uniforms,
+                              &Default::default()).unwrap();
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    // the new source declares other uniforms before `color`, which changes its location
+    program.reload_from_source(glium::program::SourceCode {
+        vertex_shader: vertex_shader,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+        fragment_shader: "
+            #version 110
+
+            uniform vec4 offset1;
+            uniform vec4 offset2;
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color + offset1 + offset2;
+            }
+        ",
+    }).unwrap();
+
+    match texture.as_surface().draw(&vb, &ib, &program, &old_uniforms, &Default::default()) {
+        Err(glium::DrawError::UniformHandleProgramMismatch { ref name }) if name == "color" => (),
+        e => panic!("{:?}", e)
+    }
+
+    // uniforms passed by name keep working
+    let zero = [0.0, 0.0, 0.0, 0.0f32];
+    let blue = [0.0, 0.0, 1.0, 1.0f32];
+    texture.as_surface().draw(&vb, &ib, &program,
+                              &uniform!{ color: blue, offset1: zero, offset2: zero },
+                              &Default::default()).unwrap();
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 255, 255));
+
+    let new_handle = program.get_uniform_handle("color").unwrap();
+    assert!(new_handle != old_handle);
+    let green = [0.0, 1.0, 0.0, 1.0f32];
+    let new_uniforms = glium::uniforms::PrecomputedUniforms::new(&program)
+                            .add(&new_handle, &green)
+                            .add(&program.get_uniform_handle("offset1").unwrap(), &zero)
+                            .add(&program.get_uniform_handle("offset2").unwrap(), &zero);
+    texture.as_surface().draw(&vb, &ib, &program, &new_uniforms,
+                              &Default::default()).unwrap();
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
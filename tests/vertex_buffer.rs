@@ -24,6 +24,7 @@ fn transform_feedback() {
         geometry_shader: None,
         outputs_srgb: false,
         uses_point_size: false,
+        dual_source_output: None,
 
         vertex_shader: "
             #version 110
@@ -88,3 +89,214 @@ fn transform_feedback() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn vertex_format_serialization() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: u32,
+    }
+
+    implement_vertex!(Vertex, position, color type(U8U8U8U8Normalized) location(3) divisor(4));
+
+    let format = <Vertex as glium::Vertex>::build_bindings();
+    let bytes = glium::vertex::serialize_vertex_format(&format);
+    let loaded = glium::vertex::deserialize_vertex_format(&bytes).unwrap();
+
+    assert_eq!(loaded, format);
+}
+
+#[test]
+fn vertex_format_deserialization_version_1() {
+    use std::borrow::Cow;
+
+    // a single `f32` named "a", without a location ; the first version doesn't have divisors
+    let mut bytes = b"GLVF".to_vec();
+    bytes.push(1);
+    bytes.extend([1, 0, 0, 0].iter().cloned());
+    bytes.extend([1, 0, 0, 0, b'a'].iter().cloned());
+    bytes.extend([0; 8].iter().cloned());
+    bytes.extend([61, 0, 0].iter().cloned());
+
+    let loaded = glium::vertex::deserialize_vertex_format(&bytes).unwrap();
+    let expected: glium::vertex::VertexFormat = Cow::Owned(vec![
+        (Cow::Borrowed("a"), 0, glium::vertex::AttributeType::F32, None, None),
+    ]);
+    assert_eq!(loaded, expected);
+}
+
+#[test]
+fn vertex_format_deserialization_errors() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        a: f32,
+    }
+
+    implement_vertex!(Vertex, a);
+
+    let bytes = glium::vertex::serialize_vertex_format(&<Vertex as glium::Vertex>::build_bindings());
+
+    match glium::vertex::deserialize_vertex_format(&bytes[.. bytes.len() - 1]) {
+        Err(glium::vertex::VertexFormatDeserializationError::UnexpectedEnd) => (),
+        r => panic!("{:?}", r)
+    };
+
+    let mut with_trailing = bytes.clone();
+    with_trailing.push(0);
+    match glium::vertex::deserialize_vertex_format(&with_trailing) {
+        Err(glium::vertex::VertexFormatDeserializationError::TrailingData) => (),
+        r => panic!("{:?}", r)
+    };
+
+    // header, element count, name length, name ("a") and offset come before the type
+    let mut unknown_type = bytes.clone();
+    unknown_type[4 + 1 + 4 + 4 + 1 + 8] = 0xff;
+    unknown_type[4 + 1 + 4 + 4 + 1 + 8 + 1] = 0xff;
+    match glium::vertex::deserialize_vertex_format(&unknown_type) {
+        Err(glium::vertex::VertexFormatDeserializationError::UnknownAttributeType(0xffff)) => (),
+        r => panic!("{:?}", r)
+    };
+
+    match glium::vertex::deserialize_vertex_format(b"hello") {
+        Err(glium::vertex::VertexFormatDeserializationError::InvalidHeader) => (),
+        r => panic!("{:?}", r)
+    };
+}
+
+#[test]
+fn vertex_buffer_any_new_raw() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        value: f32,
+    }
+
+    implement_vertex!(Vertex, position, value);
+
+    let format = glium::vertex::serialize_vertex_format(&<Vertex as glium::Vertex>::build_bindings());
+    let format = glium::vertex::deserialize_vertex_format(&format).unwrap();
+
+    let data = vec![0u8; 4 * ::std::mem::size_of::<Vertex>()];
+    let vb = unsafe {
+        glium::vertex::VertexBufferAny::new_raw(&display, &data, format,
+                                                ::std::mem::size_of::<Vertex>()).unwrap()
+    };
+
+    assert_eq!(vb.len(), 4);
+    assert_eq!(vb.get_elements_size(), ::std::mem::size_of::<Vertex>());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_new_streamed() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let data = (0 .. 1000).map(|i| Vertex { position: [i as f32, -i as f32] })
+                          .collect::<Vec<_>>();
+
+    let mut calls = Vec::new();
+    let vb = glium::VertexBuffer::new_streamed_with_progress(&display, &data, 3000, |done, total| {
+        calls.push((done, total));
+        true
+    }).unwrap();
+
+    assert!(vb.is_complete());
+    assert_eq!(vb.len(), 1000);
+    assert_eq!(calls, vec![(3000, 8000), (6000, 8000), (8000, 8000)]);
+
+    if let Ok(content) = vb.read() {
+        assert_eq!(content, data);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_new_streamed_interrupted() {
+    let display = support::build_display();
+    let (_, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let data = [
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ];
+
+    let vb = glium::VertexBuffer::new_streamed_with_progress(&display, &data, 8,
+                                                             |done, _| done < 16).unwrap();
+    assert!(!vb.is_complete());
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()) {
+        Err(glium::DrawError::IncompleteBuffer { object_kind: "vertex buffer" }) => (),
+        r => panic!("{:?}", r)
+    };
+
+    // writing the whole content makes the buffer usable again
+    vb.write(&data);
+    assert!(vb.is_complete());
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_streaming_write() {
+    use std::time::Duration;
+
+    let display = support::build_display();
+    let (_, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let data = vec![
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ];
+
+    let vb: glium::VertexBuffer<Vertex> = glium::VertexBuffer::empty(&display, 4).unwrap();
+    let texture = support::build_renderable_texture(&display);
+
+    let mut write = vb.begin_streaming_write(data.clone());
+    assert!(!vb.is_complete());
+    assert_eq!(write.get_progress(), (0, 32));
+
+    match texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()) {
+        Err(glium::DrawError::IncompleteBuffer { object_kind: "vertex buffer" }) => (),
+        r => panic!("{:?}", r)
+    };
+
+    assert_eq!(write.advance(Duration::new(0, 0)), glium::StreamingWriteStatus::Done);
+    assert_eq!(write.get_progress(), (32, 32));
+    assert!(vb.is_complete());
+
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    if let Ok(content) = vb.read() {
+        assert_eq!(content, data);
+    }
+
+    display.assert_no_error(None);
+}
@@ -659,3 +659,326 @@ fn per_instance_length_mismatch() {
     frame.finish().unwrap();
     display.assert_no_error(None);
 }
+
+#[test]
+fn per_attribute_divisor() {
+    let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    // `index` advances with each instance, `color` every four instances
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Instance {
+            index: f32,
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Instance, index, color divisor(4));
+
+        let data = (0 .. 8).map(|i| {
+            let color = match i {
+                0 => [0.0, 0.0, 1.0],
+                1 => [1.0, 0.0, 0.0],
+                _ => [0.0, 1.0, 0.0],
+            };
+
+            Instance { index: i as f32, color: color }
+        }).collect::<Vec<_>>();
+
+        glium::vertex::VertexBuffer::new(&display, &data).unwrap()
+    };
+
+    let buffer2 = match buffer2.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
+
+            in vec2 position;
+            in float index;
+            in vec3 color;
+
+            out vec3 v_color;
+            flat out int instance;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+                v_color = abs(index - float(gl_InstanceID)) < 0.5 ? color : vec3(1.0);
+                instance = gl_InstanceID;
+            }
+        ",
+        "
+            #version 330
+            in vec3 v_color;
+            flat in int instance;
+
+            void main() {
+                if (instance != 7) {
+                    discard;
+                }
+
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&buffer1, buffer2), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn divisor_on_per_vertex_source() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color divisor(2));
+
+    let buffer = glium::VertexBuffer::new(&display,
+        &[
+            Vertex { position: [-1.0,  1.0], color: [1.0, 0.0, 0.0] },
+            Vertex { position: [ 1.0,  1.0], color: [1.0, 0.0, 0.0] },
+            Vertex { position: [-1.0, -1.0], color: [1.0, 0.0, 0.0] },
+            Vertex { position: [ 1.0, -1.0], color: [1.0, 0.0, 0.0] },
+        ]
+    ).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let indices = glium::index::NoIndices(PrimitiveType::TriangleStrip);
+
+    let mut frame = display.draw();
+    match frame.draw(&buffer, &indices, &program, &uniform!{}, &Default::default()) {
+        Err(glium::DrawError::DivisorOnPerVertexSource) => (),
+        a => panic!("{:?}", a)
+    }
+
+    frame.finish().unwrap();
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instances_marker() {
+    let display = support::build_display();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 140
+
+            void main() {
+                float x = gl_InstanceID == 0 ? -1.0 : 0.0;
+
+                if (gl_VertexID == 0) {
+                    gl_Position = vec4(x, 1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 1) {
+                    gl_Position = vec4(x + 1.0, 1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 2) {
+                    gl_Position = vec4(x, -1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 3) {
+                    gl_Position = vec4(x + 1.0, -1.0, 0.0, 1.0);
+                }
+            }
+        ",
+        "
+            #version 140
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    match texture.as_surface().draw((glium::vertex::EmptyVertexAttributes { len: 4 },
+                                     glium::vertex::EmptyInstanceAttributes { len: 2 }),
+                                    &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                                    &program, &uniform!{}, &Default::default())
+    {
+        Ok(_) => (),
+        Err(glium::DrawError::InstancingNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn switch_buffers_of_same_format() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, color);
+
+    let display = support::build_display();
+
+    let positions = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let red = glium::VertexBuffer::new(&display, &[Vertex { color: [1.0, 0.0, 0.0] }; 4]).unwrap();
+    let green = glium::VertexBuffer::new(&display, &[Vertex { color: [0.0, 1.0, 0.0] }; 4]).unwrap();
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+
+    for &(buffer, expected) in &[(&red, (255, 0, 0, 255)), (&green, (0, 255, 0, 255)),
+                                 (&red, (255, 0, 0, 255))]
+    {
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw((&positions, buffer), &index_buffer, &program, &uniform!{},
+                                  &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        for row in data.iter() {
+            for pixel in row.iter() {
+                assert_eq!(pixel, &expected);
+            }
+        }
+    }
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,229 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::index::PrimitiveType;
+use glium::buffer::{Buffer, BufferType, BufferMode};
+
+mod support;
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+#[derive(Copy, Clone)]
+struct Instance {
+    color: [f32; 3],
+}
+
+implement_vertex!(Instance, color);
+
+#[test]
+fn empty_buffer_creation() {
+    let display = support::build_display();
+
+    let buffer = Buffer::<[u32]>::empty_array(&display, BufferType::ArrayBuffer, 0,
+                                              BufferMode::Default).unwrap();
+    assert_eq!(buffer.len(), 0);
+
+    let buffer = Buffer::<[u32]>::new(&display, &[], BufferType::ArrayBuffer,
+                                      BufferMode::Persistent).unwrap();
+    assert_eq!(buffer.len(), 0);
+
+    let vb = glium::VertexBuffer::<Vertex>::new(&display, &[]).unwrap();
+    assert_eq!(vb.len(), 0);
+    let vb = glium::VertexBuffer::<Vertex>::empty_dynamic(&display, 0).unwrap();
+    assert_eq!(vb.len(), 0);
+
+    let ib = glium::IndexBuffer::<u16>::new(&display, PrimitiveType::TrianglesList, &[]).unwrap();
+    assert_eq!(ib.len(), 0);
+    let ib = glium::IndexBuffer::<u16>::empty(&display, PrimitiveType::TrianglesList, 0).unwrap();
+    assert_eq!(ib.len(), 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn empty_buffer_read_write_map() {
+    let display = support::build_display();
+
+    let mut buffer = Buffer::<[u32]>::empty_array(&display, BufferType::ArrayBuffer, 0,
+                                                  BufferMode::Dynamic).unwrap();
+
+    buffer.write(&[]);
+
+    match buffer.read() {
+        Ok(data) => assert!(data.is_empty()),
+        Err(glium::buffer::ReadError::NotSupported) => (),
+        Err(e) => panic!("{:?}", e)
+    };
+
+    assert!(buffer.map().is_empty());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn empty_buffer_slice() {
+    let display = support::build_display();
+
+    let buffer = Buffer::new(&display, &[1u32, 2, 3, 4], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+
+    let slice = buffer.slice(4 .. 4).unwrap();
+    assert_eq!(slice.len(), 0);
+    slice.write(&[]);
+
+    match slice.read() {
+        Ok(data) => assert!(data.is_empty()),
+        Err(glium::buffer::ReadError::NotSupported) => (),
+        Err(e) => panic!("{:?}", e)
+    };
+
+    assert!(buffer.slice(0 .. 0).is_some());
+    assert!(buffer.slice(5 .. 5).is_none());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_empty_vertex_buffer() {
+    let display = support::build_display();
+    let (_, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let vb = glium::VertexBuffer::<Vertex>::new(&display, &[]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    display.set_frame_statistics_enabled(true);
+    let before = display.get_frame_statistics();
+    texture.as_surface().draw(&vb, glium::index::NoIndices(PrimitiveType::TrianglesList),
+                              &program, &uniform!{}, &Default::default()).unwrap();
+    assert_eq!(display.get_frame_statistics().draw_calls, before.draw_calls);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_empty_index_buffer() {
+    let display = support::build_display();
+    let (vb, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let ib = glium::IndexBuffer::<u16>::new(&display, PrimitiveType::TrianglesList, &[]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    display.set_frame_statistics_enabled(true);
+    let before = display.get_frame_statistics();
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+    assert_eq!(display.get_frame_statistics().draw_calls, before.draw_calls);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_empty_index_slice() {
+    let display = support::build_display();
+    let (vb, _, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let ib = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                     &[0u16, 1, 2, 3]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, ib.slice(2 .. 2).unwrap(), &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn draw_zero_instances() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let instances = glium::VertexBuffer::<Instance>::new(&display, &[]).unwrap();
+    let instances = match instances.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    display.set_frame_statistics_enabled(true);
+    let before = display.get_frame_statistics();
+    texture.as_surface().draw((&vb, instances), &ib, &program, &uniform!{},
+                              &Default::default()).unwrap();
+    assert_eq!(display.get_frame_statistics().draw_calls, before.draw_calls);
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (0, 0, 0, 0));
+
+    display.assert_no_error(None);
+}
+
+macro_rules! zero_dimension_test {
+    ($test_name:ident, $display:ident => $create:expr) => (
+        #[test]
+        fn $test_name() {
+            let $display = support::build_display();
+
+            match $create {
+                Err(glium::texture::TextureCreationError::ZeroDimension) => (),
+                Err(glium::texture::TextureCreationError::TypeNotSupported) => (),
+                Err(e) => panic!("{:?}", e),
+                Ok(_) => panic!(),
+            }
+
+            $display.assert_no_error(None);
+        }
+    );
+}
+
+zero_dimension_test!(texture1d_zero_width,
+                     display => glium::texture::Texture1d::empty(&display, 0));
+zero_dimension_test!(texture2d_zero_width,
+                     display => glium::texture::Texture2d::empty(&display, 0, 16));
+zero_dimension_test!(texture2d_zero_height,
+                     display => glium::texture::Texture2d::empty(&display, 16, 0));
+zero_dimension_test!(texture2d_empty_data,
+                     display => glium::texture::Texture2d::new(&display, Vec::<Vec<(u8, u8, u8, u8)>>::new()));
+zero_dimension_test!(texture2d_array_zero_layers,
+                     display => glium::texture::Texture2dArray::empty(&display, 16, 16, 0));
+zero_dimension_test!(texture3d_zero_depth,
+                     display => glium::texture::Texture3d::empty(&display, 16, 16, 0));
+zero_dimension_test!(cubemap_zero_dimension,
+                     display => glium::texture::Cubemap::empty(&display, 0));
+zero_dimension_test!(texture2d_multisample_zero_samples,
+                     display => glium::texture::Texture2dMultisample::empty(&display, 16, 16, 0));
+
+#[test]
+fn texture_read_empty_rect() {
+    let display = support::build_display();
+
+    let texture = glium::texture::Texture2d::empty(&display, 16, 16).unwrap();
+
+    let rect = glium::Rect { left: 4, bottom: 4, width: 0, height: 3 };
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.main_level().first_layer()
+                                                  .into_image(None).unwrap()
+                                                  .raw_read(&rect).unwrap();
+    assert_eq!(data.len(), 3);
+    assert!(data[0].is_empty());
+
+    display.assert_no_error(None);
+}